@@ -0,0 +1,115 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::bench::{reset_statistics, statistics};
+use xml_dom::level2::*;
+
+const SIZES: &[usize] = &[10, 100, 1000];
+
+// <catalog xmlns:dc="http://purl.org/dc/elements/1.1/">
+//   <item id="item-0" index="0"><dc:title>Title 0</dc:title></item>
+//   ...
+// </catalog>
+
+fn create_document(size: usize) -> RefNode {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node
+        .set_attribute("xmlns:dc", "http://purl.org/dc/elements/1.1/")
+        .unwrap();
+    for index in 0..size {
+        let mut item_node = document.create_element("item").unwrap();
+        item_node
+            .set_attribute("id", &format!("item-{}", index))
+            .unwrap();
+        item_node
+            .set_attribute("index", &index.to_string())
+            .unwrap();
+        let mut title_node = document
+            .create_element_ns("http://purl.org/dc/elements/1.1/", "dc:title")
+            .unwrap();
+        let _safe_to_ignore = title_node
+            .append_child(document.create_text_node(&format!("Title {}", index)))
+            .unwrap();
+        let _safe_to_ignore = item_node.append_child(title_node).unwrap();
+        let _safe_to_ignore = root_node.append_child(item_node).unwrap();
+    }
+    document_node
+}
+
+///
+/// Run `operation` once outside of the measurement, and print the counters it incremented.
+///
+fn report<T>(name: &str, size: usize, operation: impl Fn() -> T) {
+    let _previous = reset_statistics();
+    let _result = operation();
+    eprintln!("{}/{}: {}", name, size, statistics());
+}
+
+fn bench_construct(c: &mut Criterion) {
+    let mut group = c.benchmark_group("construct");
+    for size in SIZES {
+        report("construct", *size, || create_document(*size));
+        let _ = group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, size| {
+            b.iter(|| create_document(black_box(*size)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_clone(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone");
+    for size in SIZES {
+        let document_node = create_document(*size);
+        report("clone", *size, || document_node.clone_node(true));
+        let _ = group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &document_node,
+            |b, node| b.iter(|| node.clone_node(true)),
+        );
+    }
+    group.finish();
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    for size in SIZES {
+        let document_node = create_document(*size);
+        let _ = group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &document_node,
+            |b, node| b.iter(|| node.to_string()),
+        );
+    }
+    group.finish();
+}
+
+#[cfg(feature = "quick_parser")]
+fn bench_parse(c: &mut Criterion) {
+    use xml_dom::parser::read_xml;
+
+    let mut group = c.benchmark_group("parse");
+    for size in SIZES {
+        let xml = create_document(*size).to_string();
+        report("parse", *size, || read_xml(&xml).unwrap());
+        let _ = group.bench_with_input(BenchmarkId::from_parameter(size), &xml, |b, xml| {
+            b.iter(|| read_xml(black_box(xml)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "quick_parser"))]
+fn bench_parse(_: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    bench_construct,
+    bench_clone,
+    bench_serialize,
+    bench_parse
+);
+criterion_main!(benches);
@@ -0,0 +1,214 @@
+//!
+//! A small command-line tool, built only on the public API of this crate, run with
+//! `cargo run --features cli --example xml-dom -- <command> [options] [file]`. Each command reads
+//! the named file, or standard input if no file, or `-`, is given.
+//!
+
+use std::fs::File;
+use std::io::{stdin, stdout, BufRead, BufReader, Write};
+use std::process::exit;
+use xml_dom::level2::ext::normalize::{normalize_for_diff, NormalizeOptions};
+use xml_dom::level2::ext::serializer::{canonicalize, serialize, CanonicalOptions, WriteOptions};
+use xml_dom::level2::ext::xpath::{Value, XPath, XPathContext};
+use xml_dom::level2::ext::Selector;
+use xml_dom::level2::RefNode;
+use xml_dom::parser::read_reader;
+
+const USAGE: &str = "usage: xml-dom <command> [options] [file]
+
+commands:
+    validate [file...]          check that each document is well-formed, and namespace
+                                well-formed
+    pretty [--indent N] [file]  write the document with one element per indented line
+    canonicalize [--exclusive PREFIXES] [--with-comments] [file]
+                                write the Canonical XML form of the document; PREFIXES is a
+                                comma separated list, which may be empty
+    query [--selector] [--ns PREFIX=URI]... EXPRESSION [file]
+                                write the result of an XPath expression, or with --selector
+                                the elements matched by a selector, one per line
+    help                        write this message";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.split_first() {
+        Some((command, args)) => match command.as_str() {
+            "validate" => validate(args),
+            "pretty" => pretty(args),
+            "canonicalize" => canonicalize_command(args),
+            "query" => query(args),
+            "help" | "--help" | "-h" => {
+                println!("{}", USAGE);
+                Ok(())
+            }
+            _ => Err(format!("unknown command '{}'\n\n{}", command, USAGE)),
+        },
+        None => Err(USAGE.to_string()),
+    };
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        exit(1);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Commands
+// ------------------------------------------------------------------------------------------------
+
+fn validate(args: &[String]) -> Result<(), String> {
+    let files: Vec<Option<&str>> = if args.is_empty() {
+        vec![None]
+    } else {
+        args.iter().map(|file| Some(file.as_str())).collect()
+    };
+    let mut failed = 0;
+    for file in files {
+        let name = file.unwrap_or("-");
+        match read_document(file).and_then(|document_node| {
+            serialize(&document_node, &WriteOptions::strict())
+                .map_err(|e| format!("{}: {}", name, e))
+        }) {
+            Ok(_) => println!("{}: ok", name),
+            Err(message) => {
+                eprintln!("{}", message);
+                failed += 1;
+            }
+        }
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} document(s) failed validation", failed))
+    }
+}
+
+fn pretty(args: &[String]) -> Result<(), String> {
+    let mut options = NormalizeOptions {
+        normalize_prefixes: false,
+        ..Default::default()
+    };
+    let mut file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--indent" => {
+                let width: usize = next_value(&mut args, arg)?
+                    .parse()
+                    .map_err(|_| "--indent requires a number".to_string())?;
+                options.indent = " ".repeat(width);
+            }
+            _ => file = Some(positional(arg, file)?),
+        }
+    }
+    let document_node = read_document(file)?;
+    let output = normalize_for_diff(&document_node, &options).map_err(|e| e.to_string())?;
+    write_output(&output)
+}
+
+fn canonicalize_command(args: &[String]) -> Result<(), String> {
+    let mut options = CanonicalOptions::inclusive();
+    let mut file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--exclusive" => {
+                let prefixes: Vec<&str> = next_value(&mut args, arg)?
+                    .split(',')
+                    .filter(|prefix| !prefix.is_empty())
+                    .collect();
+                options.algorithm = CanonicalOptions::exclusive(&prefixes).algorithm;
+            }
+            "--with-comments" => options.with_comments = true,
+            _ => file = Some(positional(arg, file)?),
+        }
+    }
+    let document_node = read_document(file)?;
+    let output = canonicalize(&document_node, &options).map_err(|e| e.to_string())?;
+    write_output(&output)
+}
+
+fn query(args: &[String]) -> Result<(), String> {
+    let mut use_selector = false;
+    let mut context = XPathContext::default();
+    let mut positionals: Vec<&str> = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--selector" => use_selector = true,
+            "--ns" => {
+                let binding = next_value(&mut args, arg)?;
+                let (prefix, namespace_uri) = binding
+                    .split_once('=')
+                    .ok_or_else(|| format!("--ns requires PREFIX=URI, not '{}'", binding))?;
+                let _previous = context
+                    .namespaces
+                    .insert(prefix.to_string(), namespace_uri.to_string());
+            }
+            _ => positionals.push(arg),
+        }
+    }
+    let (expression, file) = match positionals.as_slice() {
+        [expression] => (*expression, None),
+        [expression, file] => (*expression, Some(*file)),
+        _ => return Err(format!("query requires an expression\n\n{}", USAGE)),
+    };
+    let document_node = read_document(file)?;
+    let results: Vec<String> = if use_selector {
+        Selector::parse(expression)
+            .map_err(|e| format!("invalid selector '{}': {}", expression, e))?
+            .select(&document_node)
+            .iter()
+            .map(|node| node.to_string())
+            .collect()
+    } else {
+        let value = XPath::compile(expression)
+            .and_then(|xpath| xpath.evaluate(&document_node, &context))
+            .map_err(|e| format!("invalid expression '{}': {}", expression, e))?;
+        match value {
+            Value::NodeSet(nodes) => nodes.iter().map(|node| node.to_string()).collect(),
+            value => vec![value.to_string()],
+        }
+    };
+    let mut output = String::new();
+    for result in results {
+        output.push_str(&result);
+        output.push('\n');
+    }
+    write_output(&output)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn read_document(file: Option<&str>) -> Result<RefNode, String> {
+    let name = file.unwrap_or("-");
+    let reader: Box<dyn BufRead> = match file {
+        None | Some("-") => Box::new(BufReader::new(stdin())),
+        Some(file) => Box::new(BufReader::new(
+            File::open(file).map_err(|e| format!("{}: {}", name, e))?,
+        )),
+    };
+    read_reader(reader).map_err(|e| format!("{}: {}", name, e))
+}
+
+fn write_output(output: &str) -> Result<(), String> {
+    stdout()
+        .lock()
+        .write_all(output.as_bytes())
+        .map_err(|e| e.to_string())
+}
+
+fn next_value<'a>(
+    args: &mut impl Iterator<Item = &'a String>,
+    option: &str,
+) -> Result<&'a String, String> {
+    args.next()
+        .ok_or_else(|| format!("{} requires a value", option))
+}
+
+fn positional<'a>(arg: &'a str, previous: Option<&str>) -> Result<&'a str, String> {
+    match previous {
+        None => Ok(arg),
+        Some(_) => Err(format!("unexpected argument '{}'\n\n{}", arg, USAGE)),
+    }
+}
@@ -7,6 +7,48 @@ Note that all of the `as_{name}` functions work as follows.
 * If the `node_type` does not correspond to the correct type, it returns `Error::InvalidState`.
 * If the `node_type` is not implemented it returns `Error::NotSupported`.
 
+With the `quick_parser` feature this module also provides conversions between nodes and sequences
+of quick-xml [`Event`](https://docs.rs/quick-xml/0.26.0/quick_xml/events/enum.Event.html)s, so
+that a client already reading or writing XML with quick-xml may construct a DOM for just the part
+of a document that needs editing, and then write it back.
+
+* `RefNode::try_from` accepts a `Vec` or slice of events and returns a new `Document` node, built
+  as by [`DomBuilder`](../../parser/struct.DomBuilder.html); `Eof` events are ignored, errors are
+  reported as [`parser::Error`](../../parser/enum.Error.html).
+* `Vec::<Event>::from` accepts a reference to a node and returns the events for the tree rooted at
+  that node, in document order and without an `Eof` event. An element without children is a
+  single `Empty` event, a document type is a `DocType` event, and an entity reference, or
+  unparsed markup, is a `Text` event holding its markup unescaped. Attribute, entity, and notation
+  nodes are not content and produce no events.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use std::convert::TryFrom;
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+
+let events = vec![
+    Event::Start(BytesStart::new("item")),
+    Event::Text(BytesText::new("pen")),
+    Event::End(BytesEnd::new("item")),
+];
+let document_node = RefNode::try_from(events).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+root_node.set_attribute("id", "1").unwrap();
+
+let mut writer = Writer::new(Vec::new());
+for event in Vec::<Event<'_>>::from(&root_node) {
+    writer.write_event(event).unwrap();
+}
+assert_eq!(writer.into_inner(), br#"<item id="1">pen</item>"#);
+# }
+```
 */
 use crate::level2::node_impl::*;
 use crate::level2::traits::*;
@@ -14,6 +56,27 @@ use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
 
 use crate::{make_is_as_functions, make_ref_type};
 
+#[cfg(feature = "quick_parser")]
+use crate::level2::ext::mutation::raw_value;
+#[cfg(feature = "quick_parser")]
+use crate::level2::ext::normalize::split_cdata_end;
+#[cfg(feature = "quick_parser")]
+use crate::level2::ext::{DocumentDecl, PrologPosition};
+#[cfg(feature = "quick_parser")]
+use crate::level2::trait_impls::ordered_attributes;
+#[cfg(feature = "quick_parser")]
+use crate::parser::{DomBuilder, Error as ParserError};
+#[cfg(feature = "quick_parser")]
+use crate::shared::display::separate_hyphens;
+#[cfg(feature = "quick_parser")]
+use crate::shared::syntax::{XML_DOCTYPE_END, XML_DOCTYPE_START};
+#[cfg(feature = "quick_parser")]
+use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+#[cfg(feature = "quick_parser")]
+use quick_xml::Reader;
+#[cfg(feature = "quick_parser")]
+use std::convert::TryFrom;
+
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
@@ -79,10 +142,10 @@ make_is_as_functions!(
 ///
 #[inline]
 pub fn is_character_data(ref_node: &RefNode) -> bool {
-    match ref_node.borrow().i_node_type {
-        NodeType::CData | NodeType::Comment | NodeType::Text => true,
-        _ => false,
-    }
+    matches!(
+        ref_node.borrow().i_node_type,
+        NodeType::CData | NodeType::Comment | NodeType::Text
+    )
 }
 
 ///
@@ -207,3 +270,140 @@ make_is_as_functions!(
     as_notation_mut,
     MutRefNotation
 );
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "quick_parser")]
+impl TryFrom<Vec<Event<'_>>> for RefNode {
+    type Error = ParserError;
+
+    fn try_from(events: Vec<Event<'_>>) -> std::result::Result<Self, Self::Error> {
+        build_from_events(events)
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+impl TryFrom<&[Event<'_>]> for RefNode {
+    type Error = ParserError;
+
+    fn try_from(events: &[Event<'_>]) -> std::result::Result<Self, Self::Error> {
+        build_from_events(events.iter().map(|event| event.borrow()))
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+impl From<&RefNode> for Vec<Event<'static>> {
+    fn from(node: &RefNode) -> Self {
+        let mut events = Vec::new();
+        push_events(node, &mut events);
+        events
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "quick_parser")]
+fn build_from_events<'a>(
+    events: impl IntoIterator<Item = Event<'a>>,
+) -> std::result::Result<RefNode, ParserError> {
+    //
+    // Events constructed by the client were not read, the decoder of an empty reader is used to
+    // decode their content as UTF-8.
+    //
+    let reader = Reader::from_str("");
+    let mut builder = DomBuilder::new();
+    for event in events {
+        builder.handle_event(&reader, event)?;
+    }
+    builder.finish()
+}
+
+#[cfg(feature = "quick_parser")]
+fn push_events(node: &RefNode, events: &mut Vec<Event<'static>>) {
+    match node.node_type() {
+        NodeType::Element => {
+            let name = node.node_name().to_string();
+            let mut start = BytesStart::new(name.clone());
+            for (attribute_name, attribute) in ordered_attributes(node) {
+                start.push_attribute((
+                    attribute_name.to_string().as_str(),
+                    raw_value(&attribute).as_str(),
+                ));
+            }
+            let children = node.child_nodes();
+            if children.is_empty() {
+                events.push(Event::Empty(start));
+            } else {
+                events.push(Event::Start(start));
+                for child in children {
+                    push_events(&child, events);
+                }
+                events.push(Event::End(BytesEnd::new(name)));
+            }
+        }
+        NodeType::Text => events.push(Event::Text(
+            BytesText::new(&node.node_value().unwrap_or_default()).into_owned(),
+        )),
+        NodeType::CData => {
+            for section in split_cdata_end(&node.node_value().unwrap_or_default()) {
+                events.push(Event::CData(BytesCData::new(section.to_string())));
+            }
+        }
+        NodeType::Comment => events.push(Event::Comment(BytesText::from_escaped(
+            separate_hyphens(&node.node_value().unwrap_or_default()),
+        ))),
+        NodeType::ProcessingInstruction => {
+            let content = match node.node_value() {
+                None => node.node_name().to_string(),
+                Some(data) => format!("{} {}", node.node_name(), data),
+            };
+            events.push(Event::PI(BytesText::from_escaped(content)));
+        }
+        NodeType::DocumentType => {
+            let markup = node.to_string();
+            let content = markup
+                .strip_prefix(XML_DOCTYPE_START)
+                .and_then(|content| content.strip_suffix(XML_DOCTYPE_END))
+                .unwrap_or_default()
+                .trim_start()
+                .to_string();
+            events.push(Event::DocType(BytesText::from_escaped(content)));
+        }
+        NodeType::Document => {
+            if let Some(xml_decl) = node.xml_declaration() {
+                events.push(Event::Decl(BytesDecl::new(
+                    &xml_decl.version().to_string(),
+                    xml_decl.encoding().as_deref(),
+                    xml_decl
+                        .standalone()
+                        .map(|standalone| if standalone { "yes" } else { "no" }),
+                )));
+            }
+            let mut doc_type = node.doc_type();
+            for child in node.child_nodes() {
+                if node.prolog_position(&child) != Some(PrologPosition::BeforeDocType) {
+                    if let Some(doc_type) = doc_type.take() {
+                        push_events(&doc_type, events);
+                    }
+                }
+                push_events(&child, events);
+            }
+            if let Some(doc_type) = doc_type {
+                push_events(&doc_type, events);
+            }
+        }
+        NodeType::DocumentFragment => {
+            for child in node.child_nodes() {
+                push_events(&child, events);
+            }
+        }
+        NodeType::Attribute | NodeType::Entity | NodeType::Notation => {}
+        NodeType::EntityReference | NodeType::UnparsedMarkup => {
+            events.push(Event::Text(BytesText::from_escaped(node.to_string())))
+        }
+    }
+}
@@ -1,25 +1,101 @@
 /*!
 This module implements certain capabilities required by, but not specified by, the DOM Core.
+
+The [`Implementation`](struct.Implementation.html) type is this crate's `DOMImplementation`. A
+shared default instance, returned by [`get_implementation`](fn.get_implementation.html), is used to
+bootstrap the creation of documents; however, an `Implementation` may also be configured and used
+to create documents directly, in which case each document created carries its own copy of the
+configuration. This is returned by `Document::implementation` and consulted by the document:
+
+* The [`ProcessingOptions`](../ext/options/struct.ProcessingOptions.html), the strictness profile,
+  used by `create_document`.
+* A registry of attribute names that are treated as IDs, in addition to `xml:id`, by
+  `Document::get_element_by_id`.
+* A registry of additional features, and their versions, reported by `has_feature` and by
+  `Node::is_supported` on any node of the document; this allows applications to advertise their
+  own extensions to generic code.
+* A [`UriResolver`](../ext/resolver/trait.UriResolver.html) that clients may use to fetch external
+  resources referenced from the document.
+
+Documents created by any other means, such as the parser, use the default implementation.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::dom_impl::Implementation;
+
+let mut implementation = Implementation::new();
+implementation.add_id_attribute("key");
+
+let document_node = implementation.create_document(None, Some("catalog"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut item_node = document.create_element("item").unwrap();
+item_node.set_attribute("key", "i-1").unwrap();
+let mut root_node = document.document_element().unwrap();
+let _safe_to_ignore = root_node.append_child(item_node.clone()).unwrap();
+
+assert_eq!(document.get_element_by_id("i-1"), Some(item_node));
+assert!(document.implementation().has_feature("XML", "2.0"));
+```
+
+Features are registered in the same way, using the convention of a reversed domain name prefix
+for features defined outside of the DOM specifications.
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::dom_impl::Implementation;
+
+let mut implementation = Implementation::new();
+implementation.add_feature("org.example.catalog", "1.0");
+
+let document_node = implementation.create_document(None, Some("catalog"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let root_node = document.document_element().unwrap();
+
+assert!(document.implementation().has_feature("org.example.catalog", "1.0"));
+assert!(root_node.is_supported("org.example.catalog", ""));
+assert!(!root_node.is_supported("org.example.catalog", "2.0"));
+```
 */
+use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::resolver::UriResolver;
 use crate::level2::node_impl::RefNode;
 use crate::level2::traits::DOMImplementation;
+use crate::shared::name::Name;
+use std::rc::Rc;
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
 // ------------------------------------------------------------------------------------------------
 
-#[doc(hidden)]
+///
+/// This crate's implementation of the `DOMImplementation` interface, along with the
+/// configuration of the documents it creates; see the [module](index.html) documentation.
+///
 #[derive(Clone, Debug)]
-pub(crate) struct Implementation {}
+pub struct Implementation {
+    options: Option<ProcessingOptions>,
+    id_attributes: Vec<String>,
+    features: Vec<(String, String)>,
+    resolver: Option<Rc<dyn UriResolver>>,
+}
 
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
-const THIS_IMPLEMENTATION: &'static dyn DOMImplementation<NodeRef = RefNode> = &Implementation {};
+pub(crate) const THIS_IMPLEMENTATION: &Implementation = &Implementation {
+    options: None,
+    id_attributes: Vec::new(),
+    features: Vec::new(),
+    resolver: None,
+};
 
 ///
-/// Return a reference to an instance of this `DOMImplementation` implementation.
+/// Return a reference to the shared default instance of this `DOMImplementation` implementation.
 ///
 /// This function gets around the DOM bootstrap issue, the `implementation` method on the
 /// [`Document`](trait.Document.html) trait requires an instance of `Document`; however, the
@@ -52,3 +128,111 @@ const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn get_implementation_version() -> String {
     format!("{}:{}", CRATE_NAME, CRATE_VERSION)
 }
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for Implementation {
+    fn default() -> Self {
+        THIS_IMPLEMENTATION.clone()
+    }
+}
+
+impl Implementation {
+    ///
+    /// Construct a new implementation with the same configuration as the shared default.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Construct a new implementation whose `create_document` method uses `options`.
+    ///
+    pub fn with_options(options: ProcessingOptions) -> Self {
+        Self {
+            options: Some(options),
+            ..Self::default()
+        }
+    }
+
+    ///
+    /// Returns the processing options used by `create_document`; by default only the
+    /// `add_namespaces` option is set.
+    ///
+    pub fn options(&self) -> ProcessingOptions {
+        match &self.options {
+            Some(options) => options.clone(),
+            None => {
+                let mut options = ProcessingOptions::new();
+                options.set_add_namespaces();
+                options
+            }
+        }
+    }
+
+    ///
+    /// Treat any attribute with the qualified name `qualified_name` as an ID attribute, in
+    /// documents created by this implementation.
+    ///
+    pub fn add_id_attribute(&mut self, qualified_name: &str) {
+        if !self.id_attributes.iter().any(|name| name == qualified_name) {
+            self.id_attributes.push(qualified_name.to_string());
+        }
+    }
+
+    ///
+    /// Returns `true` if `name` has been registered as an ID attribute with
+    /// [`add_id_attribute`](#method.add_id_attribute).
+    ///
+    pub fn is_id_attribute(&self, name: &Name) -> bool {
+        !self.id_attributes.is_empty() && self.id_attributes.contains(&name.to_string())
+    }
+
+    ///
+    /// Report support for version `version` of the feature named `feature` from `has_feature`,
+    /// and `Node::is_supported`, in documents created by this implementation. As with the
+    /// features of this crate, feature names are compared without regard to case, and a request
+    /// for an empty version is satisfied by any version.
+    ///
+    pub fn add_feature(&mut self, feature: &str, version: &str) {
+        if !self.is_added_feature(feature, version) {
+            self.features
+                .push((feature.to_string(), version.to_string()));
+        }
+    }
+
+    ///
+    /// Returns the features, and their versions, registered with
+    /// [`add_feature`](#method.add_feature), in the order they were added.
+    ///
+    pub fn features(&self) -> Vec<(String, String)> {
+        self.features.clone()
+    }
+
+    ///
+    /// Returns `true` if `version` of `feature` has been registered with
+    /// [`add_feature`](#method.add_feature); the built-in features are not included.
+    ///
+    pub(crate) fn is_added_feature(&self, feature: &str, version: &str) -> bool {
+        self.features.iter().any(|(added, added_version)| {
+            added.eq_ignore_ascii_case(feature) && (version.is_empty() || added_version == version)
+        })
+    }
+
+    ///
+    /// Set the resolver that clients may use to fetch external resources referenced from the
+    /// documents created by this implementation.
+    ///
+    pub fn set_resolver(&mut self, resolver: Rc<dyn UriResolver>) {
+        self.resolver = Some(resolver);
+    }
+
+    ///
+    /// Returns the resolver set for this implementation, if any.
+    ///
+    pub fn resolver(&self) -> Option<Rc<dyn UriResolver>> {
+        self.resolver.clone()
+    }
+}
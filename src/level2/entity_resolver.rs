@@ -2,7 +2,8 @@
 Implementation of `EntityResolver` that navigates the `Document` structure.
 */
 
-use crate::level2::convert::{as_document, as_document_type};
+use crate::level2::convert::as_document_type;
+use crate::level2::ext::DocumentEntities;
 use crate::level2::{Name, Node, NodeType, RefNode};
 use crate::shared::text::EntityResolver;
 use std::str::FromStr;
@@ -13,30 +14,15 @@ use std::str::FromStr;
 
 impl EntityResolver for RefNode {
     fn resolve(&self, entity: &str) -> Option<String> {
-        let doc_type = match self.node_type() {
-            NodeType::DocumentType => Some(self.clone()),
-            NodeType::Document => {
-                let document = as_document(self).unwrap();
-                document.doc_type()
+        let entity_node = match self.node_type() {
+            NodeType::DocumentType => {
+                let doc_type = as_document_type(self).unwrap();
+                let name = Name::from_str(entity).ok()?;
+                doc_type.entities().get(&name).cloned()
             }
-            _ => match self.owner_document() {
-                None => None,
-                Some(document_node) => {
-                    let document = as_document(&document_node).unwrap();
-                    document.doc_type()
-                }
-            },
+            NodeType::Document => self.entity(entity),
+            _ => self.owner_document()?.entity(entity),
         };
-        match doc_type {
-            None => None,
-            Some(doc_type) => {
-                let doc_type = as_document_type(&doc_type).unwrap();
-                let name = Name::from_str(entity).unwrap();
-                match doc_type.entities().get(&name) {
-                    None => None,
-                    Some(entity) => entity.node_value(),
-                }
-            }
-        }
+        entity_node?.node_value()
     }
 }
@@ -0,0 +1,271 @@
+/*!
+Provides [`DocumentActor`](struct.DocumentActor.html), an asynchronous interface to a document
+owned by a dedicated thread.
+
+Like the [`SyncDocument`](../sync/struct.SyncDocument.html) facade the document is created on, and
+only ever accessed from, its owning thread; queries and mutations are sent to that thread as
+messages and processed one at a time in the order they were received. Rather than blocking the
+caller until the message has been processed, each request returns a [`Reply`](struct.Reply.html)
+that implements `Future` and resolves to the result. As a `Reply` holds no reference to the actor,
+a closure may safely send further messages to the same actor; they are processed after the
+current message completes.
+
+No particular async runtime is required; a `Reply` may also be waited on synchronously with
+[`Reply::wait`](struct.Reply.html#method.wait).
+
+This module is only available when the `actor` feature is enabled.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::actor::DocumentActor;
+
+let actor = DocumentActor::spawn(|| {
+    get_implementation().create_document(None, Some("queue"), None)
+})
+.unwrap();
+
+let replies: Vec<_> = (0..3)
+    .map(|id| {
+        actor.mutate(move |document_node| {
+            let document = as_document(document_node).unwrap();
+            let mut root_node = document.document_element().unwrap();
+            let mut job_node = document.create_element("job")?;
+            job_node.set_attribute("id", &id.to_string())?;
+            let _safe_to_ignore = root_node.append_child(job_node)?;
+            Ok(id)
+        })
+    })
+    .collect();
+for reply in replies {
+    let _id = reply.wait().unwrap();
+}
+
+let xml = actor.query(|document_node| document_node.to_string()).wait().unwrap();
+assert!(xml.starts_with("<queue><job "));
+```
+*/
+
+use crate::level2::ext::sync::{spawn_owner, Command};
+use crate::level2::node_impl::RefNode;
+use crate::shared::error::{Error, Result, MSG_DOCUMENT_THREAD_UNAVAILABLE};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A handle to a document owned by a dedicated thread; see the [module](index.html) documentation.
+///
+/// Handles are cheap to clone and may be sent to, and shared between, threads. The owning thread
+/// exits, dropping the document, once all handles have been dropped.
+///
+#[derive(Clone, Debug)]
+pub struct DocumentActor {
+    commands: Sender<Command>,
+}
+
+///
+/// The pending result of a message sent to a [`DocumentActor`](struct.DocumentActor.html).
+///
+/// The result is retrieved by awaiting the reply as a `Future`, or by calling
+/// [`wait`](#method.wait). If the message could not be processed, because the owning thread has
+/// exited or the closure panicked, the result is `Err(Error::InvalidState)`.
+///
+pub struct Reply<T> {
+    shared: Arc<Shared<T>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentActor {
+    ///
+    /// Start a new thread that will own the document returned by `build`. The document must be
+    /// created on the owning thread, and so it is constructed by the closure rather than passed
+    /// in.
+    ///
+    /// **Exceptions**
+    ///
+    /// * Any error returned by `build`.
+    /// * `INVALID_STATE_ERR`: Raised if `build` returns a node that is not a `Document`, if the
+    ///   thread could not be started, or if `build` panics.
+    ///
+    pub fn spawn<F>(build: F) -> Result<Self>
+    where
+        F: FnOnce() -> Result<RefNode> + Send + 'static,
+    {
+        let (commands, _) = spawn_owner(build)?;
+        Ok(Self { commands })
+    }
+
+    ///
+    /// Send `query` to be run against the document, on the owning thread, after all previously
+    /// sent messages. The returned reply resolves to the query's result.
+    ///
+    pub fn query<F, T>(&self, query: F) -> Reply<T>
+    where
+        F: FnOnce(&RefNode) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.send(move |document_node| Ok(query(document_node)))
+    }
+
+    ///
+    /// Send `mutation` to be run against the document, on the owning thread, after all previously
+    /// sent messages. No other message is processed until the mutation has completed. The
+    /// returned reply resolves to the mutation's result.
+    ///
+    pub fn mutate<F, T>(&self, mutation: F) -> Reply<T>
+    where
+        F: FnOnce(&mut RefNode) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.send(mutation)
+    }
+
+    fn send<F, T>(&self, operation: F) -> Reply<T>
+    where
+        F: FnOnce(&mut RefNode) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let reply = Reply::new();
+        let completion = Completion(reply.shared.clone());
+        //
+        // If the send fails the closure, and so the completion, is dropped which resolves the
+        // reply with an error.
+        //
+        let _safe_to_ignore = self
+            .commands
+            .send(Box::new(move |document_node: &mut RefNode| {
+                completion.complete(operation(document_node));
+            }));
+        reply
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<T> Debug for Reply<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Reply")
+            .field("completed", &self.shared.state.lock().unwrap().completed)
+            .finish()
+    }
+}
+
+impl<T> Future for Reply<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Reply<T> {
+    ///
+    /// Block the current thread until the message has been processed, and return the result.
+    ///
+    /// **Note:** calling `wait` from within a closure run by the same actor will never return.
+    ///
+    pub fn wait(self) -> Result<T> {
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            match state.result.take() {
+                Some(result) => return result,
+                None => state = self.shared.ready.wait(state).unwrap(),
+            }
+        }
+    }
+
+    fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    completed: false,
+                    result: None,
+                    waker: None,
+                }),
+                ready: Condvar::new(),
+            }),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<T> Completion<T> {
+    fn complete(self, result: Result<T>) {
+        self.set(result);
+    }
+
+    fn set(&self, result: Result<T>) {
+        let waker = {
+            let mut state = self.0.state.lock().unwrap();
+            if state.completed {
+                return;
+            }
+            state.completed = true;
+            state.result = Some(result);
+            state.waker.take()
+        };
+        self.0.ready.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Completion<T> {
+    fn drop(&mut self) {
+        //
+        // If the closure panicked, or was never run, ensure the reply still resolves.
+        //
+        let completed = self
+            .0
+            .state
+            .lock()
+            .map(|state| state.completed)
+            .unwrap_or(true);
+        if !completed {
+            warn!("{}", MSG_DOCUMENT_THREAD_UNAVAILABLE);
+            self.set(Err(Error::InvalidState));
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    ready: Condvar,
+}
+
+struct State<T> {
+    completed: bool,
+    result: Option<Result<T>>,
+    waker: Option<Waker>,
+}
+
+///
+/// The sending half of a reply, if dropped before a result is set the reply resolves to an error.
+///
+struct Completion<T>(Arc<Shared<T>>);
@@ -0,0 +1,233 @@
+/*!
+Provides the [`ElementAudit`](../trait.ElementAudit.html) and
+[`DocumentAudit`](../trait.DocumentAudit.html) extensions, which record a history of the changes
+made to the attributes of selected elements; for example to show who changed a configuration
+value, and when.
+
+Auditing is off by default and is enabled per element with
+[`set_audited`](../trait.ElementAudit.html#tymethod.set_audited). Once enabled, every change to an
+attribute of the element, setting, replacing, or removing it, or changing the value of the
+attribute node itself, appends an [`AttributeChange`](struct.AttributeChange.html) recording the
+previous and new values and the time of the change. Elements that are not audited pay only the
+cost of checking the flag.
+
+The DOM has no notion of a user, so the actor responsible for a change is supplied by the caller
+for a set of changes, using
+[`audit_transaction`](../trait.DocumentAudit.html#tymethod.audit_transaction) on the owning
+document. Changes made outside of a transaction have no actor.
+
+Note that the history is not copied when an element is cloned or imported, and is not part of the
+serialized document.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{DocumentAudit, ElementAudit};
+
+let document_node = xml_dom::parser::read_xml(r#"<config><limit value="10"/></config>"#).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut limit_node = document.get_elements_by_tag_name("limit").pop().unwrap();
+limit_node.set_audited(true).unwrap();
+
+document_node.audit_transaction("admin", || {
+    limit_node.clone().set_attribute("value", "20").unwrap();
+});
+
+let history = limit_node.attribute_history("value");
+assert_eq!(history.len(), 1);
+assert_eq!(history[0].previous.as_deref(), Some("10"));
+assert_eq!(history[0].value.as_deref(), Some("20"));
+assert_eq!(history[0].actor.as_deref(), Some("admin"));
+# }
+```
+*/
+
+use crate::level2::ext::traits::{DocumentAudit, ElementAudit};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::traits::*;
+use crate::shared::error::{Error, Result, MSG_INVALID_EXTENSION, MSG_INVALID_NODE_TYPE};
+use crate::shared::name::Name;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single recorded change to an attribute of an audited element.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeChange {
+    /// The name of the changed attribute.
+    pub name: Name,
+    /// The value of the attribute before the change, `None` if it was not present.
+    pub previous: Option<String>,
+    /// The value of the attribute after the change, `None` if it was removed.
+    pub value: Option<String>,
+    /// The time at which the change was made.
+    pub timestamp: SystemTime,
+    /// The actor of the transaction in which the change was made, if any.
+    pub actor: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl ElementAudit for RefNode {
+    fn set_audited(&mut self, audited: bool) -> Result<()> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::Element {
+            i_attribute_history,
+            ..
+        } = &mut mut_self.i_extension
+        {
+            if !audited {
+                *i_attribute_history = None;
+            } else if i_attribute_history.is_none() {
+                *i_attribute_history = Some(Default::default());
+            }
+            Ok(())
+        } else {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            Err(Error::InvalidState)
+        }
+    }
+
+    fn is_audited(&self) -> bool {
+        is_audited(self)
+    }
+
+    fn attribute_history(&self, name: &str) -> Vec<AttributeChange> {
+        let name = match Name::from_str(name) {
+            Ok(name) => name,
+            Err(_) => return Default::default(),
+        };
+        let ref_self = self.borrow();
+        if let Extension::Element {
+            i_attribute_history: Some(history),
+            ..
+        } = &ref_self.i_extension
+        {
+            history
+                .iter()
+                .filter(|change| {
+                    change.name.prefix() == name.prefix()
+                        && change.name.local_name() == name.local_name()
+                })
+                .cloned()
+                .collect()
+        } else {
+            Default::default()
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentAudit for RefNode {
+    fn audit_transaction<R>(&self, actor: &str, f: impl FnOnce() -> R) -> R {
+        let _guard = ActorGuard {
+            previous: replace_actor(self, Some(actor.to_string())),
+            document: self.clone(),
+        };
+        f()
+    }
+
+    fn audit_actor(&self) -> Option<String> {
+        let ref_self = self.borrow();
+        if let Extension::Document { i_audit_actor, .. } = &ref_self.i_extension {
+            i_audit_actor.clone()
+        } else {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            None
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Drop for ActorGuard {
+    fn drop(&mut self) {
+        let _safe_to_ignore = replace_actor(&self.document, self.previous.take());
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The recorded changes to the attributes of an element, in the order they were made.
+///
+pub(crate) type AttributeHistory = Vec<AttributeChange>;
+
+///
+/// Restores the previous actor of a document when a transaction ends, even if it panics.
+///
+#[derive(Debug)]
+struct ActorGuard {
+    document: RefNode,
+    previous: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns `true` if `element` is an element with auditing enabled.
+///
+pub(crate) fn is_audited(element: &RefNode) -> bool {
+    matches!(
+        &element.borrow().i_extension,
+        Extension::Element {
+            i_attribute_history: Some(_),
+            ..
+        }
+    )
+}
+
+///
+/// Append a change to the history of `element`, if audited, taking the actor from its owner
+/// document. No borrow of `element`, or its owner document, may be held by the caller.
+///
+pub(crate) fn record_attribute_change(
+    element: &RefNode,
+    name: &Name,
+    previous: Option<String>,
+    value: Option<String>,
+) {
+    let actor = element
+        .owner_document()
+        .and_then(|document| document.audit_actor());
+    let mut mut_element = element.borrow_mut();
+    if let Extension::Element {
+        i_attribute_history: Some(history),
+        ..
+    } = &mut mut_element.i_extension
+    {
+        history.push(AttributeChange {
+            name: name.clone(),
+            previous,
+            value,
+            timestamp: SystemTime::now(),
+            actor,
+        });
+    }
+}
+
+fn replace_actor(document: &RefNode, actor: Option<String>) -> Option<String> {
+    let mut mut_document = document.borrow_mut();
+    if let Extension::Document { i_audit_actor, .. } = &mut mut_document.i_extension {
+        std::mem::replace(i_audit_actor, actor)
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        None
+    }
+}
@@ -0,0 +1,134 @@
+/*!
+Provides counters of internal work done by the node backend, so that changes to the
+representation of nodes can be evaluated by the number of allocations they make as well as by
+elapsed time.
+
+Two counters are kept for the current thread, documents are not shared between threads and so
+each thread has its own counters:
+
+* `node_allocations`, incremented each time a node is created, including when it is cloned by
+  `clone_node` or `import_node`.
+* `map_rehashes`, incremented each time an insert into one of the maps held by a node (the
+  attributes and namespace mappings of an element, the notations of a document type, and the ID
+  map of a document) grows the map's allocation.
+
+The Criterion benches in this crate's `benches` directory report these counters alongside their
+timings; run them with `cargo bench --features bench`.
+
+This module is only available when the `bench` feature is enabled.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::bench::{reset_statistics, statistics};
+
+let _previous = reset_statistics();
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("list"), None).unwrap();
+let document = convert::as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for index in 0..3 {
+    let mut item_node = document.create_element("item").unwrap();
+    item_node.set_attribute("index", &index.to_string()).unwrap();
+    let _safe_to_ignore = root_node.append_child(item_node).unwrap();
+}
+
+// the document and its element, and for each item an element, attribute, and text value.
+let statistics = statistics();
+assert_eq!(statistics.node_allocations, 11);
+assert_eq!(statistics.map_rehashes, 3);
+```
+*/
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::hash::Hash;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A snapshot of the counters for the current thread, see [`statistics`](fn.statistics.html).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of nodes created.
+    pub node_allocations: u64,
+    /// The number of inserts into node maps that grew the map's allocation.
+    pub map_rehashes: u64,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Return the current value of the counters for this thread.
+///
+pub fn statistics() -> Statistics {
+    Statistics {
+        node_allocations: NODE_ALLOCATIONS.with(Cell::get),
+        map_rehashes: MAP_REHASHES.with(Cell::get),
+    }
+}
+
+///
+/// Reset the counters for this thread to zero, returning their previous value.
+///
+pub fn reset_statistics() -> Statistics {
+    Statistics {
+        node_allocations: NODE_ALLOCATIONS.with(|counter| counter.replace(0)),
+        map_rehashes: MAP_REHASHES.with(|counter| counter.replace(0)),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "node allocations: {}, map rehashes: {}",
+            self.node_allocations, self.map_rehashes
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+thread_local! {
+    static NODE_ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+    static MAP_REHASHES: Cell<u64> = const { Cell::new(0) };
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Called for each node created.
+///
+pub(crate) fn count_node_allocation() {
+    NODE_ALLOCATIONS.with(|counter| counter.set(counter.get() + 1));
+}
+
+///
+/// Called before `key` is inserted into `map`, counts a rehash if the map has to grow to hold a
+/// new key.
+///
+pub(crate) fn count_map_insert<K, V>(map: &HashMap<K, V>, key: &K)
+where
+    K: Eq + Hash,
+{
+    if map.len() == map.capacity() && !map.contains_key(key) {
+        MAP_REHASHES.with(|counter| counter.set(counter.get() + 1));
+    }
+}
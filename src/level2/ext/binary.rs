@@ -0,0 +1,140 @@
+/*!
+This module provides support types for the [`BinaryContent`](trait.BinaryContent.html) trait.
+
+Base64 content is written in lines of at most 76 characters, as required by MIME
+([RFC 2045](https://tools.ietf.org/html/rfc2045#section-6.8)) and commonly used by XML
+Signature; hex content is written as a single line of lower-case digits. When reading content any
+XML white space is ignored, so content wrapped by other tools is accepted.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::*;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("data"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+
+root_node.set_binary_content(b"hello", BinaryEncoding::Hex).unwrap();
+assert_eq!(root_node.to_string(), "<data>68656c6c6f</data>");
+assert_eq!(root_node.binary_content(BinaryEncoding::Hex).unwrap(), b"hello".to_vec());
+```
+*/
+
+use crate::level2::ext::traits::BinaryContent;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Document, Node, NodeType};
+use crate::shared::error::{Error, Result, MSG_INVALID_BINARY_CONTENT, MSG_INVALID_NODE_TYPE};
+use crate::shared::text::is_xml_space;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The text encoding used to store binary content within an element.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryEncoding {
+    /// The Base64 encoding, as the XML Schema type `xs:base64Binary`.
+    Base64,
+    /// The hexadecimal encoding, as the XML Schema type `xs:hexBinary`.
+    Hex,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl BinaryContent for RefNode {
+    fn binary_content(&self, encoding: BinaryEncoding) -> Result<Vec<u8>> {
+        if self.node_type() != NodeType::Element {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let text: String = self
+            .child_nodes()
+            .iter()
+            .filter(|child| {
+                child.node_type() == NodeType::Text || child.node_type() == NodeType::CData
+            })
+            .filter_map(|child| child.node_value())
+            .flat_map(|value| value.chars().collect::<Vec<char>>())
+            .filter(|c| !is_xml_space(*c))
+            .collect();
+        let decoded = match encoding {
+            BinaryEncoding::Base64 => base64::decode(&text).ok(),
+            BinaryEncoding::Hex => decode_hex(&text),
+        };
+        decoded.ok_or_else(|| {
+            warn!("{}: '{}'", MSG_INVALID_BINARY_CONTENT, text);
+            Error::Syntax
+        })
+    }
+
+    fn set_binary_content(&mut self, data: &[u8], encoding: BinaryEncoding) -> Result<()> {
+        if self.node_type() != NodeType::Element {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let document = self.owner_document().ok_or(Error::NotFound)?;
+        let text = match encoding {
+            BinaryEncoding::Base64 => wrap_lines(&base64::encode(data), BASE64_LINE_LENGTH),
+            BinaryEncoding::Hex => encode_hex(data),
+        };
+        for child in self.child_nodes() {
+            let _safe_to_ignore = self.remove_child(child)?;
+        }
+        if !text.is_empty() {
+            let _safe_to_ignore = self.append_child(document.create_text_node(&text))?;
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const BASE64_LINE_LENGTH: usize = 76;
+
+const HEX_DIGITS: &[u8] = b"0123456789abcdef";
+
+fn wrap_lines(text: &str, length: usize) -> String {
+    text.as_bytes()
+        .chunks(length)
+        .map(|line| String::from_utf8_lossy(line).to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn encode_hex(data: &[u8]) -> String {
+    data.iter()
+        .flat_map(|b| {
+            vec![
+                HEX_DIGITS[(b >> 4) as usize] as char,
+                HEX_DIGITS[(b & 0x0F) as usize] as char,
+            ]
+        })
+        .collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let digits: Option<Vec<u8>> = text
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect();
+    let digits = digits?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect(),
+    )
+}
@@ -0,0 +1,540 @@
+/*!
+Provides an [`EntityCatalog`](struct.EntityCatalog.html) of named character entities, such as
+`&nbsp;` or `&alpha;`, that are commonly referenced by XHTML content through its DTD but are not
+predefined by XML itself.
+
+The built-in tables are the three XHTML 1.0 entity sets, selected using
+[`EntitySet`](enum.EntitySet.html); these also cover the Greek letters and most common
+mathematical symbols used by MathML content. Additional entities may be added to a catalog with
+[`insert`](struct.EntityCatalog.html#method.insert).
+
+A catalog may be used to decode entity references on input, and to choose how non-ASCII characters
+are represented on output, see [`CharacterOutput`](enum.CharacterOutput.html).
+
+This module is only available when the `entity_catalog` feature is enabled.
+
+# Example
+
+```rust
+use xml_dom::level2::ext::catalog::{CharacterOutput, EntityCatalog};
+
+let catalog = EntityCatalog::xhtml();
+assert_eq!(catalog.decode("&alpha; &amp; &beta;"), "α &amp; β");
+assert_eq!(catalog.encode("α < β", CharacterOutput::Named), "&alpha; < &beta;");
+assert_eq!(catalog.encode("α < β", CharacterOutput::Decimal), "&#945; < &#946;");
+```
+*/
+
+use crate::shared::syntax::{
+    XML_ENTITYREF_END, XML_ENTITYREF_START, XML_HEX_NUMBERED_ENTITYREF_START,
+    XML_NUMBERED_ENTITYREF_START,
+};
+use crate::shared::text::{is_xml_name, EntityResolver};
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The built-in sets of named character entities.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EntitySet {
+    /// The XHTML Latin 1 characters, `xhtml-lat1.ent`; for example `&nbsp;` and `&eacute;`.
+    XhtmlLat1,
+    /// The XHTML special characters, `xhtml-special.ent`; for example `&euro;` and `&mdash;`.
+    XhtmlSpecial,
+    /// The XHTML mathematical, Greek, and symbolic characters, `xhtml-symbol.ent`; for example
+    /// `&alpha;` and `&infin;`.
+    XhtmlSymbol,
+}
+
+///
+/// Determines how characters that are outside the ASCII range are written by
+/// [`EntityCatalog::encode`](struct.EntityCatalog.html#method.encode).
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CharacterOutput {
+    /// Write the character itself.
+    Literal,
+    /// Write a named entity reference if the catalog has one for the character, else write the
+    /// character itself.
+    Named,
+    /// Write a decimal character reference, `&#945;`.
+    Decimal,
+    /// Write a hexadecimal character reference, `&#x3B1;`.
+    Hex,
+}
+
+///
+/// A mapping between entity names and their replacement text.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EntityCatalog {
+    by_name: HashMap<String, String>,
+    by_char: HashMap<char, String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl EntitySet {
+    ///
+    /// Returns all of the built-in sets.
+    ///
+    pub fn all() -> Vec<EntitySet> {
+        vec![
+            EntitySet::XhtmlLat1,
+            EntitySet::XhtmlSpecial,
+            EntitySet::XhtmlSymbol,
+        ]
+    }
+
+    fn table(&self) -> &'static [(&'static str, char)] {
+        match self {
+            EntitySet::XhtmlLat1 => XHTML_LAT1,
+            EntitySet::XhtmlSpecial => XHTML_SPECIAL,
+            EntitySet::XhtmlSymbol => XHTML_SYMBOL,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl EntityResolver for EntityCatalog {
+    fn resolve(&self, entity: &str) -> Option<String> {
+        let name = entity
+            .trim_start_matches(XML_ENTITYREF_START)
+            .trim_end_matches(XML_ENTITYREF_END);
+        self.get(name).map(String::from)
+    }
+}
+
+impl EntityCatalog {
+    ///
+    /// Construct a new catalog containing all the built-in XHTML entity sets.
+    ///
+    pub fn xhtml() -> Self {
+        let mut catalog = Self::default();
+        for set in EntitySet::all() {
+            catalog.add_set(set);
+        }
+        catalog
+    }
+
+    ///
+    /// Construct a new catalog containing only the specified entity set.
+    ///
+    pub fn with_set(set: EntitySet) -> Self {
+        let mut catalog = Self::default();
+        catalog.add_set(set);
+        catalog
+    }
+
+    ///
+    /// Add all the entities from the built-in `set` to this catalog.
+    ///
+    pub fn add_set(&mut self, set: EntitySet) {
+        for (name, c) in set.table() {
+            let _safe_to_ignore = self.insert(name, &c.to_string());
+        }
+    }
+
+    ///
+    /// Add the entity `name` with the replacement text `value` to this catalog, returning `false`
+    /// if `name` is not a valid XML name.
+    ///
+    pub fn insert(&mut self, name: &str, value: &str) -> bool {
+        if !is_xml_name(name) {
+            return false;
+        }
+        let mut chars = value.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            let _safe_to_ignore = self.by_char.insert(c, name.to_string());
+        }
+        let _safe_to_ignore = self.by_name.insert(name.to_string(), value.to_string());
+        true
+    }
+
+    ///
+    /// Returns the replacement text for the entity `name`, if it is in this catalog.
+    ///
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.by_name.get(name).map(String::as_str)
+    }
+
+    ///
+    /// Returns the name of an entity that has the single character `c` as its replacement text,
+    /// if there is one in this catalog.
+    ///
+    pub fn name_for(&self, c: char) -> Option<&str> {
+        self.by_char.get(&c).map(String::as_str)
+    }
+
+    ///
+    /// Returns the number of entities in this catalog.
+    ///
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    ///
+    /// Returns `true` if this catalog has no entities.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+
+    ///
+    /// Replace all references in `text` to entities in this catalog with their replacement text.
+    /// Character references, and references to the five entities predefined by XML, are left
+    /// as-is so that the result remains correctly escaped.
+    ///
+    pub fn decode(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(XML_ENTITYREF_START) {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let replacement = rest[1..].find(XML_ENTITYREF_END).and_then(|end| {
+                let name = &rest[1..end + 1];
+                if is_predefined(name) {
+                    None
+                } else {
+                    self.get(name).map(|value| (value, end + 2))
+                }
+            });
+            match replacement {
+                Some((value, length)) => {
+                    result.push_str(value);
+                    rest = &rest[length..];
+                }
+                None => {
+                    result.push_str(XML_ENTITYREF_START);
+                    rest = &rest[1..];
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    ///
+    /// Write each non-ASCII character in `text` as determined by `output`; ASCII characters,
+    /// including markup characters, are never changed so `text` should already be escaped.
+    ///
+    pub fn encode(&self, text: &str, output: CharacterOutput) -> String {
+        let mut result = String::with_capacity(text.len());
+        for c in text.chars() {
+            if c.is_ascii() {
+                result.push(c);
+                continue;
+            }
+            match output {
+                CharacterOutput::Literal => result.push(c),
+                CharacterOutput::Named => match self.name_for(c) {
+                    None => result.push(c),
+                    Some(name) => {
+                        result.push_str(XML_ENTITYREF_START);
+                        result.push_str(name);
+                        result.push_str(XML_ENTITYREF_END);
+                    }
+                },
+                CharacterOutput::Decimal => result.push_str(&format!(
+                    "{}{}{}",
+                    XML_NUMBERED_ENTITYREF_START, c as u32, XML_ENTITYREF_END
+                )),
+                CharacterOutput::Hex => result.push_str(&format!(
+                    "{}{:X}{}",
+                    XML_HEX_NUMBERED_ENTITYREF_START, c as u32, XML_ENTITYREF_END
+                )),
+            }
+        }
+        result
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn is_predefined(name: &str) -> bool {
+    matches!(name, "amp" | "lt" | "gt" | "apos" | "quot")
+}
+
+// ------------------------------------------------------------------------------------------------
+// Built-in Entity Sets
+// ------------------------------------------------------------------------------------------------
+
+///
+/// From the XHTML 1.0 `xhtml-lat1.ent` entity set.
+///
+const XHTML_LAT1: &[(&str, char)] = &[
+    ("nbsp", '\u{A0}'),
+    ("iexcl", '\u{A1}'),
+    ("cent", '\u{A2}'),
+    ("pound", '\u{A3}'),
+    ("curren", '\u{A4}'),
+    ("yen", '\u{A5}'),
+    ("brvbar", '\u{A6}'),
+    ("sect", '\u{A7}'),
+    ("uml", '\u{A8}'),
+    ("copy", '\u{A9}'),
+    ("ordf", '\u{AA}'),
+    ("laquo", '\u{AB}'),
+    ("not", '\u{AC}'),
+    ("shy", '\u{AD}'),
+    ("reg", '\u{AE}'),
+    ("macr", '\u{AF}'),
+    ("deg", '\u{B0}'),
+    ("plusmn", '\u{B1}'),
+    ("sup2", '\u{B2}'),
+    ("sup3", '\u{B3}'),
+    ("acute", '\u{B4}'),
+    ("micro", '\u{B5}'),
+    ("para", '\u{B6}'),
+    ("middot", '\u{B7}'),
+    ("cedil", '\u{B8}'),
+    ("sup1", '\u{B9}'),
+    ("ordm", '\u{BA}'),
+    ("raquo", '\u{BB}'),
+    ("frac14", '\u{BC}'),
+    ("frac12", '\u{BD}'),
+    ("frac34", '\u{BE}'),
+    ("iquest", '\u{BF}'),
+    ("Agrave", '\u{C0}'),
+    ("Aacute", '\u{C1}'),
+    ("Acirc", '\u{C2}'),
+    ("Atilde", '\u{C3}'),
+    ("Auml", '\u{C4}'),
+    ("Aring", '\u{C5}'),
+    ("AElig", '\u{C6}'),
+    ("Ccedil", '\u{C7}'),
+    ("Egrave", '\u{C8}'),
+    ("Eacute", '\u{C9}'),
+    ("Ecirc", '\u{CA}'),
+    ("Euml", '\u{CB}'),
+    ("Igrave", '\u{CC}'),
+    ("Iacute", '\u{CD}'),
+    ("Icirc", '\u{CE}'),
+    ("Iuml", '\u{CF}'),
+    ("ETH", '\u{D0}'),
+    ("Ntilde", '\u{D1}'),
+    ("Ograve", '\u{D2}'),
+    ("Oacute", '\u{D3}'),
+    ("Ocirc", '\u{D4}'),
+    ("Otilde", '\u{D5}'),
+    ("Ouml", '\u{D6}'),
+    ("times", '\u{D7}'),
+    ("Oslash", '\u{D8}'),
+    ("Ugrave", '\u{D9}'),
+    ("Uacute", '\u{DA}'),
+    ("Ucirc", '\u{DB}'),
+    ("Uuml", '\u{DC}'),
+    ("Yacute", '\u{DD}'),
+    ("THORN", '\u{DE}'),
+    ("szlig", '\u{DF}'),
+    ("agrave", '\u{E0}'),
+    ("aacute", '\u{E1}'),
+    ("acirc", '\u{E2}'),
+    ("atilde", '\u{E3}'),
+    ("auml", '\u{E4}'),
+    ("aring", '\u{E5}'),
+    ("aelig", '\u{E6}'),
+    ("ccedil", '\u{E7}'),
+    ("egrave", '\u{E8}'),
+    ("eacute", '\u{E9}'),
+    ("ecirc", '\u{EA}'),
+    ("euml", '\u{EB}'),
+    ("igrave", '\u{EC}'),
+    ("iacute", '\u{ED}'),
+    ("icirc", '\u{EE}'),
+    ("iuml", '\u{EF}'),
+    ("eth", '\u{F0}'),
+    ("ntilde", '\u{F1}'),
+    ("ograve", '\u{F2}'),
+    ("oacute", '\u{F3}'),
+    ("ocirc", '\u{F4}'),
+    ("otilde", '\u{F5}'),
+    ("ouml", '\u{F6}'),
+    ("divide", '\u{F7}'),
+    ("oslash", '\u{F8}'),
+    ("ugrave", '\u{F9}'),
+    ("uacute", '\u{FA}'),
+    ("ucirc", '\u{FB}'),
+    ("uuml", '\u{FC}'),
+    ("yacute", '\u{FD}'),
+    ("thorn", '\u{FE}'),
+    ("yuml", '\u{FF}'),
+];
+
+///
+/// From the XHTML 1.0 `xhtml-special.ent` entity set.
+///
+const XHTML_SPECIAL: &[(&str, char)] = &[
+    ("quot", '\u{22}'),
+    ("amp", '\u{26}'),
+    ("apos", '\u{27}'),
+    ("lt", '\u{3C}'),
+    ("gt", '\u{3E}'),
+    ("OElig", '\u{152}'),
+    ("oelig", '\u{153}'),
+    ("Scaron", '\u{160}'),
+    ("scaron", '\u{161}'),
+    ("Yuml", '\u{178}'),
+    ("circ", '\u{2C6}'),
+    ("tilde", '\u{2DC}'),
+    ("ensp", '\u{2002}'),
+    ("emsp", '\u{2003}'),
+    ("thinsp", '\u{2009}'),
+    ("zwnj", '\u{200C}'),
+    ("zwj", '\u{200D}'),
+    ("lrm", '\u{200E}'),
+    ("rlm", '\u{200F}'),
+    ("ndash", '\u{2013}'),
+    ("mdash", '\u{2014}'),
+    ("lsquo", '\u{2018}'),
+    ("rsquo", '\u{2019}'),
+    ("sbquo", '\u{201A}'),
+    ("ldquo", '\u{201C}'),
+    ("rdquo", '\u{201D}'),
+    ("bdquo", '\u{201E}'),
+    ("dagger", '\u{2020}'),
+    ("Dagger", '\u{2021}'),
+    ("permil", '\u{2030}'),
+    ("lsaquo", '\u{2039}'),
+    ("rsaquo", '\u{203A}'),
+    ("euro", '\u{20AC}'),
+];
+
+///
+/// From the XHTML 1.0 `xhtml-symbol.ent` entity set.
+///
+const XHTML_SYMBOL: &[(&str, char)] = &[
+    ("fnof", '\u{192}'),
+    ("Alpha", '\u{391}'),
+    ("Beta", '\u{392}'),
+    ("Gamma", '\u{393}'),
+    ("Delta", '\u{394}'),
+    ("Epsilon", '\u{395}'),
+    ("Zeta", '\u{396}'),
+    ("Eta", '\u{397}'),
+    ("Theta", '\u{398}'),
+    ("Iota", '\u{399}'),
+    ("Kappa", '\u{39A}'),
+    ("Lambda", '\u{39B}'),
+    ("Mu", '\u{39C}'),
+    ("Nu", '\u{39D}'),
+    ("Xi", '\u{39E}'),
+    ("Omicron", '\u{39F}'),
+    ("Pi", '\u{3A0}'),
+    ("Rho", '\u{3A1}'),
+    ("Sigma", '\u{3A3}'),
+    ("Tau", '\u{3A4}'),
+    ("Upsilon", '\u{3A5}'),
+    ("Phi", '\u{3A6}'),
+    ("Chi", '\u{3A7}'),
+    ("Psi", '\u{3A8}'),
+    ("Omega", '\u{3A9}'),
+    ("alpha", '\u{3B1}'),
+    ("beta", '\u{3B2}'),
+    ("gamma", '\u{3B3}'),
+    ("delta", '\u{3B4}'),
+    ("epsilon", '\u{3B5}'),
+    ("zeta", '\u{3B6}'),
+    ("eta", '\u{3B7}'),
+    ("theta", '\u{3B8}'),
+    ("iota", '\u{3B9}'),
+    ("kappa", '\u{3BA}'),
+    ("lambda", '\u{3BB}'),
+    ("mu", '\u{3BC}'),
+    ("nu", '\u{3BD}'),
+    ("xi", '\u{3BE}'),
+    ("omicron", '\u{3BF}'),
+    ("pi", '\u{3C0}'),
+    ("rho", '\u{3C1}'),
+    ("sigmaf", '\u{3C2}'),
+    ("sigma", '\u{3C3}'),
+    ("tau", '\u{3C4}'),
+    ("upsilon", '\u{3C5}'),
+    ("phi", '\u{3C6}'),
+    ("chi", '\u{3C7}'),
+    ("psi", '\u{3C8}'),
+    ("omega", '\u{3C9}'),
+    ("thetasym", '\u{3D1}'),
+    ("upsih", '\u{3D2}'),
+    ("piv", '\u{3D6}'),
+    ("bull", '\u{2022}'),
+    ("hellip", '\u{2026}'),
+    ("prime", '\u{2032}'),
+    ("Prime", '\u{2033}'),
+    ("oline", '\u{203E}'),
+    ("frasl", '\u{2044}'),
+    ("image", '\u{2111}'),
+    ("weierp", '\u{2118}'),
+    ("real", '\u{211C}'),
+    ("trade", '\u{2122}'),
+    ("alefsym", '\u{2135}'),
+    ("larr", '\u{2190}'),
+    ("uarr", '\u{2191}'),
+    ("rarr", '\u{2192}'),
+    ("darr", '\u{2193}'),
+    ("harr", '\u{2194}'),
+    ("crarr", '\u{21B5}'),
+    ("lArr", '\u{21D0}'),
+    ("uArr", '\u{21D1}'),
+    ("rArr", '\u{21D2}'),
+    ("dArr", '\u{21D3}'),
+    ("hArr", '\u{21D4}'),
+    ("forall", '\u{2200}'),
+    ("part", '\u{2202}'),
+    ("exist", '\u{2203}'),
+    ("empty", '\u{2205}'),
+    ("nabla", '\u{2207}'),
+    ("isin", '\u{2208}'),
+    ("notin", '\u{2209}'),
+    ("ni", '\u{220B}'),
+    ("prod", '\u{220F}'),
+    ("sum", '\u{2211}'),
+    ("minus", '\u{2212}'),
+    ("lowast", '\u{2217}'),
+    ("radic", '\u{221A}'),
+    ("prop", '\u{221D}'),
+    ("infin", '\u{221E}'),
+    ("ang", '\u{2220}'),
+    ("and", '\u{2227}'),
+    ("or", '\u{2228}'),
+    ("cap", '\u{2229}'),
+    ("cup", '\u{222A}'),
+    ("int", '\u{222B}'),
+    ("there4", '\u{2234}'),
+    ("sim", '\u{223C}'),
+    ("cong", '\u{2245}'),
+    ("asymp", '\u{2248}'),
+    ("ne", '\u{2260}'),
+    ("equiv", '\u{2261}'),
+    ("le", '\u{2264}'),
+    ("ge", '\u{2265}'),
+    ("sub", '\u{2282}'),
+    ("sup", '\u{2283}'),
+    ("nsub", '\u{2284}'),
+    ("sube", '\u{2286}'),
+    ("supe", '\u{2287}'),
+    ("oplus", '\u{2295}'),
+    ("otimes", '\u{2297}'),
+    ("perp", '\u{22A5}'),
+    ("sdot", '\u{22C5}'),
+    ("lceil", '\u{2308}'),
+    ("rceil", '\u{2309}'),
+    ("lfloor", '\u{230A}'),
+    ("rfloor", '\u{230B}'),
+    ("lang", '\u{2329}'),
+    ("rang", '\u{232A}'),
+    ("loz", '\u{25CA}'),
+    ("spades", '\u{2660}'),
+    ("clubs", '\u{2663}'),
+    ("hearts", '\u{2665}'),
+    ("diams", '\u{2666}'),
+];
@@ -0,0 +1,331 @@
+/*!
+Provides a semantic comparison of two `RefNode` trees, ignoring differences that do not affect the
+information content of a document.
+
+The comparison performed by [`semantic_eq`](fn.semantic_eq.html) differs from a comparison of the
+serialized form of two trees in the following ways:
+
+1. attributes are compared as a set, so attribute order is never significant,
+1. element and attribute names are compared by namespace URI and local name where a namespace URI
+   is present, so the choice of prefix is not significant, and namespace declaration (`xmlns`)
+   attributes are ignored,
+1. adjacent `Text` nodes are merged before comparison, so the split of text across nodes is not
+   significant,
+1. optionally, `CDATASection` nodes are treated as `Text` nodes,
+1. optionally, whitespace-only text between other nodes is ignored,
+1. optionally, comments are ignored.
+
+This module also provides [`compare_document_position`](fn.compare_document_position.html), which
+corresponds to the DOM Level 3 `compareDocumentPosition` method, and
+[`sort_document_order`](fn.sort_document_order.html) which puts a list of nodes into document
+order; these define the document order that the query APIs of this crate guarantee, see the
+[`query`](../query/index.html#ordering) module.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::compare::{semantic_eq, CompareOptions};
+
+let implementation = get_implementation();
+let mut document_1 = implementation.create_document(None, Some("root"), None).unwrap();
+let mut document_2 = implementation.create_document(None, Some("root"), None).unwrap();
+
+let document = as_document_mut(&mut document_1).unwrap();
+let mut root = document.document_element().unwrap();
+let _safe_to_ignore = root.append_child(document.create_text_node("hello world")).unwrap();
+
+let document = as_document_mut(&mut document_2).unwrap();
+let mut root = document.document_element().unwrap();
+let _safe_to_ignore = root.append_child(document.create_cdata_section("hello world").unwrap()).unwrap();
+
+assert!(semantic_eq(&document_1, &document_2, &CompareOptions::default()));
+```
+*/
+
+use crate::level2::convert::as_attribute;
+use crate::level2::node_impl::{NodeImpl, RefNode};
+use crate::level2::traits::*;
+use crate::shared::name::Name;
+use crate::shared::syntax::XMLNS_NS_ATTRIBUTE;
+use crate::shared::text::is_xml_space;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Options that control the behavior of [`semantic_eq`](fn.semantic_eq.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompareOptions {
+    /// If `true` text that consists only of whitespace is ignored, the default is `true`.
+    pub ignore_whitespace: bool,
+    /// If `true` CDATA sections are compared as if they were text, the default is `true`.
+    pub cdata_as_text: bool,
+    /// If `true` comments are ignored, the default is `false`.
+    pub ignore_comments: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+
+/// The two nodes are in different trees.
+pub const DOCUMENT_POSITION_DISCONNECTED: u16 = 0x01;
+/// The other node precedes the reference node.
+pub const DOCUMENT_POSITION_PRECEDING: u16 = 0x02;
+/// The other node follows the reference node.
+pub const DOCUMENT_POSITION_FOLLOWING: u16 = 0x04;
+/// The other node is an ancestor of the reference node.
+pub const DOCUMENT_POSITION_CONTAINS: u16 = 0x08;
+/// The other node is a descendant of the reference node.
+pub const DOCUMENT_POSITION_CONTAINED_BY: u16 = 0x10;
+/// The order of the two nodes is determined by the implementation, not the tree.
+pub const DOCUMENT_POSITION_IMPLEMENTATION_SPECIFIC: u16 = 0x20;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns `true` if the trees rooted at `a` and `b` are semantically equal, according to the
+/// rules described in the [module documentation](index.html) and `options`.
+///
+pub fn semantic_eq(a: &RefNode, b: &RefNode, options: &CompareOptions) -> bool {
+    if a == b {
+        return true;
+    }
+    match (a.node_type(), b.node_type()) {
+        (NodeType::Document, NodeType::Document) => {
+            doc_type_eq(&a.doc_type(), &b.doc_type()) && children_eq(a, b, options)
+        }
+        (NodeType::DocumentFragment, NodeType::DocumentFragment)
+        | (NodeType::EntityReference, NodeType::EntityReference) => {
+            a.node_name() == b.node_name() && children_eq(a, b, options)
+        }
+        (NodeType::Element, NodeType::Element) => {
+            a.node_name() == b.node_name() && attributes_eq(a, b) && children_eq(a, b, options)
+        }
+        (NodeType::Attribute, NodeType::Attribute) => {
+            a.node_name() == b.node_name() && a.value() == b.value()
+        }
+        (NodeType::DocumentType, NodeType::DocumentType) => {
+            doc_type_eq(&Some(a.clone()), &Some(b.clone()))
+        }
+        (a_type, b_type) if a_type == b_type => {
+            a.node_name() == b.node_name() && a.node_value() == b.node_value()
+        }
+        (NodeType::Text, NodeType::CData) | (NodeType::CData, NodeType::Text) => {
+            options.cdata_as_text && a.node_value() == b.node_value()
+        }
+        _ => false,
+    }
+}
+
+///
+/// Compare the position of `other` with that of `reference` in document order, returning a
+/// bit-mask of the `DOCUMENT_POSITION_*` constants, or zero if they are the same node.
+///
+/// The owner element of an attribute is treated as its parent, so an element contains its
+/// attributes; the attributes of an element follow it, ordered by name, and precede its
+/// children. If the nodes are in different trees the result is `DOCUMENT_POSITION_DISCONNECTED`
+/// and `DOCUMENT_POSITION_IMPLEMENTATION_SPECIFIC` along with one of `DOCUMENT_POSITION_PRECEDING`
+/// or `DOCUMENT_POSITION_FOLLOWING`, which is consistent for any two trees while both exist.
+///
+/// # Specification
+///
+/// Compares the reference node, i.e. the node on which this method is being called, with a node,
+/// i.e. the one passed as a parameter, with regard to their position in the document and
+/// according to the document order.
+///
+pub fn compare_document_position(reference: &RefNode, other: &RefNode) -> u16 {
+    if reference == other {
+        return 0;
+    }
+    let (reference_root, reference_key) = order_key(reference);
+    let (other_root, other_key) = order_key(other);
+    if reference_root != other_root {
+        let order = if reference_root.as_inner().as_ptr() < other_root.as_inner().as_ptr() {
+            DOCUMENT_POSITION_FOLLOWING
+        } else {
+            DOCUMENT_POSITION_PRECEDING
+        };
+        DOCUMENT_POSITION_DISCONNECTED | DOCUMENT_POSITION_IMPLEMENTATION_SPECIFIC | order
+    } else if reference_key.starts_with(&other_key) {
+        DOCUMENT_POSITION_CONTAINS | DOCUMENT_POSITION_PRECEDING
+    } else if other_key.starts_with(&reference_key) {
+        DOCUMENT_POSITION_CONTAINED_BY | DOCUMENT_POSITION_FOLLOWING
+    } else if other_key < reference_key {
+        DOCUMENT_POSITION_PRECEDING
+    } else {
+        DOCUMENT_POSITION_FOLLOWING
+    }
+}
+
+///
+/// Sort `nodes` into document order, as defined by
+/// [`compare_document_position`](fn.compare_document_position.html), removing any duplicates.
+/// Nodes from different trees are grouped by tree.
+///
+pub fn sort_document_order(nodes: &mut Vec<RefNode>) {
+    let mut keyed: Vec<(usize, Vec<usize>, RefNode)> = Vec::with_capacity(nodes.len());
+    let mut seen: HashSet<*const RefCell<NodeImpl>> = HashSet::with_capacity(nodes.len());
+    for node in nodes.drain(..) {
+        if seen.insert(node.as_inner().as_ptr() as *const _) {
+            let (root, key) = order_key(&node);
+            keyed.push((root.as_inner().as_ptr() as usize, key, node));
+        }
+    }
+    keyed.sort_by(|lhs, rhs| (lhs.0, &lhs.1).cmp(&(rhs.0, &rhs.1)));
+    nodes.extend(keyed.into_iter().map(|(_, _, node)| node));
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            ignore_whitespace: true,
+            cdata_as_text: true,
+            ignore_comments: false,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum Item {
+    Node(RefNode),
+    Text(String),
+    CData(String),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The root of the tree containing `node`, and a key that orders nodes within that tree in
+/// document order; each child is numbered from one, and the attributes of an element are placed
+/// after the element but before its children by using a zero followed by the attribute's index.
+///
+fn order_key(node: &RefNode) -> (RefNode, Vec<usize>) {
+    let mut key: Vec<usize> = Vec::new();
+    let mut current = node.clone();
+    if let Ok(attribute) = as_attribute(&current) {
+        if let Some(element) = attribute.owner_element() {
+            let mut names: Vec<Name> = element.attributes().into_keys().collect();
+            names.sort_by_key(|name| name.to_string());
+            let name = current.node_name();
+            key.push(
+                names
+                    .iter()
+                    .position(|other| other == &name)
+                    .unwrap_or_default(),
+            );
+            key.push(0);
+            current = element;
+        }
+    }
+    while let Some(parent) = current.parent_node() {
+        let index = parent
+            .child_nodes()
+            .iter()
+            .position(|child| child == &current)
+            .unwrap_or_default();
+        key.push(index + 1);
+        current = parent;
+    }
+    key.reverse();
+    (current, key)
+}
+
+fn is_namespace_declaration(name: &Name) -> bool {
+    match name.prefix() {
+        Some(prefix) => prefix == XMLNS_NS_ATTRIBUTE,
+        None => name.local_name() == XMLNS_NS_ATTRIBUTE,
+    }
+}
+
+fn attributes_eq(a: &RefNode, b: &RefNode) -> bool {
+    let significant = |node: &RefNode| -> HashMap<Name, RefNode> {
+        node.attributes()
+            .into_iter()
+            .filter(|(name, _)| !is_namespace_declaration(name))
+            .collect()
+    };
+    let a_attributes = significant(a);
+    let b_attributes = significant(b);
+    a_attributes.len() == b_attributes.len()
+        && a_attributes.iter().all(|(name, a_attribute)| {
+            b_attributes
+                .get(name)
+                .is_some_and(|b_attribute| a_attribute.value() == b_attribute.value())
+        })
+}
+
+fn doc_type_eq(a: &Option<RefNode>, b: &Option<RefNode>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => {
+            let a: &dyn DocumentType<NodeRef = RefNode> = a;
+            let b: &dyn DocumentType<NodeRef = RefNode> = b;
+            a.node_name() == b.node_name()
+                && a.public_id() == b.public_id()
+                && a.system_id() == b.system_id()
+                && a.internal_subset() == b.internal_subset()
+        }
+        _ => false,
+    }
+}
+
+fn children_eq(a: &RefNode, b: &RefNode, options: &CompareOptions) -> bool {
+    let a_items = significant_children(a, options);
+    let b_items = significant_children(b, options);
+    a_items.len() == b_items.len()
+        && a_items.iter().zip(b_items.iter()).all(|pair| match pair {
+            (Item::Node(a), Item::Node(b)) => semantic_eq(a, b, options),
+            (Item::Text(a), Item::Text(b)) | (Item::CData(a), Item::CData(b)) => a == b,
+            _ => false,
+        })
+}
+
+///
+/// Returns the list of children to compare, with adjacent text merged and any ignorable content
+/// removed.
+///
+fn significant_children(node: &RefNode, options: &CompareOptions) -> Vec<Item> {
+    let mut items: Vec<Item> = Vec::new();
+    for child in node.child_nodes() {
+        let value = child.node_value().unwrap_or_default();
+        match child.node_type() {
+            NodeType::Text => push_text(&mut items, value, false),
+            NodeType::CData if options.cdata_as_text => push_text(&mut items, value, false),
+            NodeType::CData => push_text(&mut items, value, true),
+            NodeType::Comment if options.ignore_comments => {}
+            _ => items.push(Item::Node(child)),
+        }
+    }
+    if options.ignore_whitespace {
+        items.retain(|item| match item {
+            Item::Text(text) | Item::CData(text) => !text.chars().all(is_xml_space),
+            Item::Node(_) => true,
+        });
+    }
+    items
+}
+
+fn push_text(items: &mut Vec<Item>, value: String, is_cdata: bool) {
+    match (items.last_mut(), is_cdata) {
+        (Some(Item::Text(text)), false) | (Some(Item::CData(text)), true) => text.push_str(&value),
+        (_, false) => items.push(Item::Text(value)),
+        (_, true) => items.push(Item::CData(value)),
+    }
+}
@@ -0,0 +1,468 @@
+/*!
+Provides a [`Schema`](struct.Schema.html), built from the element type and attribute-list
+declarations of a DTD, that answers the questions an editor asks when offering completions: which
+elements may be added to an element, and which attributes may be added to it.
+
+The content model of each declared element is evaluated against the element children already
+present, so that [`allowed_children`](struct.Schema.html#method.allowed_children) returns only
+the names of elements that may validly be appended; for example, given the declaration
+`<!ELEMENT memo (to, from, body)>` and a `memo` element containing only a `to` element, the only
+completion is `from`. Element and attribute names are matched as written in the declarations,
+namespaces are not resolved.
+
+Declarations within conditional sections, and in parameter entities, are not included.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::completion::Schema;
+
+let document_node = xml_dom::parser::read_xml(
+    r#"<!DOCTYPE memo [
+<!ELEMENT memo (to+, from, body)>
+<!ELEMENT to (#PCDATA)>
+<!ATTLIST memo priority (low|high) "low" id ID #IMPLIED>
+]><memo priority="high"><to>Ann</to></memo>"#,
+)
+.unwrap();
+let document = as_document(&document_node).unwrap();
+let schema = Schema::from_doc_type(&document.doc_type().unwrap()).unwrap();
+
+let memo_node = document.document_element().unwrap();
+assert_eq!(schema.allowed_children(&memo_node), vec!["from", "to"]);
+let attributes = schema.allowed_attributes(&memo_node);
+assert_eq!(attributes.len(), 1);
+assert_eq!(attributes[0].name, "id");
+assert_eq!(attributes[0].attribute_type, "ID");
+# }
+```
+*/
+
+use crate::level2::convert::as_attribute;
+use crate::level2::ext::dtd::{attribute_list_types, element_declarations};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::*;
+use crate::shared::error::{Error, Result, MSG_INVALID_CONTENT_MODEL, MSG_INVALID_NODE_TYPE};
+use crate::shared::syntax::{
+    XML_ELEMENT_DECL_ANY, XML_ELEMENT_DECL_EMPTY, XML_ELEMENT_DECL_PCDATA,
+};
+use crate::shared::text::is_xml_space;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The element type and attribute-list declarations of a DTD, used to compute completions.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schema {
+    elements: HashMap<String, ContentModel>,
+    attributes: Vec<(String, String, String)>,
+}
+
+///
+/// An attribute that may be added to an element, from an attribute-list declaration.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllowedAttribute {
+    /// The name of the attribute, as declared.
+    pub name: String,
+    /// The type of the attribute, as declared; enumerated types include their parentheses.
+    pub attribute_type: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl FromStr for Schema {
+    type Err = Error;
+
+    ///
+    /// Construct a schema from the text of a DTD, such as the internal subset of a document type
+    /// or the content of an external subset.
+    ///
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut elements: HashMap<String, ContentModel> = Default::default();
+        for (name, content_spec) in element_declarations(s) {
+            let content_model = ContentModel::from_str(&content_spec)?;
+            let _safe_to_ignore = elements.entry(name).or_insert(content_model);
+        }
+        Ok(Self {
+            elements,
+            attributes: attribute_list_types(s),
+        })
+    }
+}
+
+impl Schema {
+    ///
+    /// Construct a schema from the internal subset of the document type `doc_type`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if `doc_type` is not a document type.
+    /// * `SYNTAX_ERR`: Raised if an element type declaration has an invalid content model.
+    ///
+    pub fn from_doc_type(doc_type: &RefNode) -> Result<Self> {
+        if doc_type.node_type() != NodeType::DocumentType {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        Self::from_str(&doc_type.internal_subset().unwrap_or_default())
+    }
+
+    ///
+    /// Returns the names of the elements that may be appended to the children of `node`, in
+    /// name order. For a document without a document element this is the name of its document
+    /// type, or if it has none the declared element names. For an element it is determined by
+    /// its content model and existing element children; an empty list is returned if the element
+    /// is not declared, or if its existing children do not match its content model.
+    ///
+    pub fn allowed_children(&self, node: &RefNode) -> Vec<String> {
+        match node.node_type() {
+            NodeType::Document if !has_element_child(node) => match node.doc_type() {
+                Some(doc_type) => vec![doc_type.node_name().to_string()],
+                None => self.element_names(),
+            },
+            NodeType::Element => match self.elements.get(&node.node_name().to_string()) {
+                Some(ContentModel::Any) => self.element_names(),
+                Some(ContentModel::Mixed(names)) => names.iter().cloned().collect(),
+                Some(ContentModel::Children(particle)) => node
+                    .child_nodes()
+                    .iter()
+                    .filter(|child| child.node_type() == NodeType::Element)
+                    .fold(particle.clone(), |particle, child| {
+                        particle.derive(&child.node_name().to_string())
+                    })
+                    .first()
+                    .into_iter()
+                    .collect(),
+                Some(ContentModel::Empty) | None => Default::default(),
+            },
+            _ => Default::default(),
+        }
+    }
+
+    ///
+    /// Returns the attributes declared for `element` that it does not already have, in
+    /// declaration order; an attribute that only has its default value is still allowed.
+    ///
+    pub fn allowed_attributes(&self, element: &RefNode) -> Vec<AllowedAttribute> {
+        if element.node_type() != NodeType::Element {
+            return Default::default();
+        }
+        let element_name = element.node_name().to_string();
+        let existing: Vec<String> = element
+            .attributes()
+            .into_iter()
+            .filter(|(_, attribute)| {
+                as_attribute(attribute).is_ok_and(|attribute| attribute.specified())
+            })
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let mut allowed: Vec<AllowedAttribute> = Vec::new();
+        for (declared_element, name, attribute_type) in &self.attributes {
+            if *declared_element == element_name
+                && !existing.contains(name)
+                && !allowed.iter().any(|attribute| attribute.name == *name)
+            {
+                allowed.push(AllowedAttribute {
+                    name: name.clone(),
+                    attribute_type: attribute_type.clone(),
+                });
+            }
+        }
+        allowed
+    }
+
+    ///
+    /// Returns `true` if `element` is declared with a content model that allows text.
+    ///
+    pub fn allows_text(&self, element: &RefNode) -> bool {
+        matches!(
+            self.elements.get(&element.node_name().to_string()),
+            Some(ContentModel::Any) | Some(ContentModel::Mixed(_))
+        )
+    }
+
+    fn element_names(&self) -> Vec<String> {
+        let names: BTreeSet<String> = self.elements.keys().cloned().collect();
+        names.into_iter().collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl FromStr for ContentModel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let content_spec: String = s.chars().filter(|c| !is_xml_space(*c)).collect();
+        match content_spec.as_str() {
+            XML_ELEMENT_DECL_EMPTY => Ok(ContentModel::Empty),
+            XML_ELEMENT_DECL_ANY => Ok(ContentModel::Any),
+            _ => {
+                let mixed = content_spec
+                    .strip_prefix('(')
+                    .and_then(|rest| rest.strip_prefix(XML_ELEMENT_DECL_PCDATA));
+                match mixed {
+                    Some(rest) => parse_mixed(rest),
+                    None => {
+                        let mut parser = ContentParser {
+                            rest: &content_spec,
+                        };
+                        let particle = parser.content_particle()?;
+                        if parser.rest.is_empty() {
+                            Ok(ContentModel::Children(particle))
+                        } else {
+                            invalid_content_model()
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Particle {
+    fn seq(lhs: Particle, rhs: Particle) -> Particle {
+        match (lhs, rhs) {
+            (Particle::Nothing, _) | (_, Particle::Nothing) => Particle::Nothing,
+            (Particle::Empty, particle) | (particle, Particle::Empty) => particle,
+            (lhs, rhs) => Particle::Seq(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    fn alt(lhs: Particle, rhs: Particle) -> Particle {
+        match (lhs, rhs) {
+            (Particle::Nothing, particle) | (particle, Particle::Nothing) => particle,
+            (lhs, rhs) if lhs == rhs => lhs,
+            (lhs, rhs) => Particle::Alt(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    fn star(particle: Particle) -> Particle {
+        match particle {
+            Particle::Nothing | Particle::Empty => Particle::Empty,
+            particle @ Particle::Star(_) => particle,
+            particle => Particle::Star(Box::new(particle)),
+        }
+    }
+
+    fn repeat(self, occurrence: Option<char>) -> Particle {
+        match occurrence {
+            Some('?') => Particle::alt(self, Particle::Empty),
+            Some('*') => Particle::star(self),
+            Some('+') => Particle::seq(self.clone(), Particle::star(self)),
+            _ => self,
+        }
+    }
+
+    fn is_nullable(&self) -> bool {
+        match self {
+            Particle::Empty | Particle::Star(_) => true,
+            Particle::Nothing | Particle::Name(_) => false,
+            Particle::Seq(lhs, rhs) => lhs.is_nullable() && rhs.is_nullable(),
+            Particle::Alt(lhs, rhs) => lhs.is_nullable() || rhs.is_nullable(),
+        }
+    }
+
+    ///
+    /// The particle matching whatever may follow `name` in a sequence matched by this particle.
+    ///
+    fn derive(&self, name: &str) -> Particle {
+        match self {
+            Particle::Empty | Particle::Nothing => Particle::Nothing,
+            Particle::Name(particle_name) if particle_name == name => Particle::Empty,
+            Particle::Name(_) => Particle::Nothing,
+            Particle::Seq(lhs, rhs) => {
+                let derived = Particle::seq(lhs.derive(name), *rhs.clone());
+                if lhs.is_nullable() {
+                    Particle::alt(derived, rhs.derive(name))
+                } else {
+                    derived
+                }
+            }
+            Particle::Alt(lhs, rhs) => Particle::alt(lhs.derive(name), rhs.derive(name)),
+            Particle::Star(inner) => Particle::seq(inner.derive(name), self.clone()),
+        }
+    }
+
+    ///
+    /// The names that may start a sequence matched by this particle.
+    ///
+    fn first(&self) -> BTreeSet<String> {
+        let mut names: BTreeSet<String> = Default::default();
+        self.collect_first(&mut names);
+        names
+    }
+
+    fn collect_first(&self, names: &mut BTreeSet<String>) {
+        match self {
+            Particle::Empty | Particle::Nothing => {}
+            Particle::Name(name) => {
+                let _safe_to_ignore = names.insert(name.clone());
+            }
+            Particle::Seq(lhs, rhs) => {
+                lhs.collect_first(names);
+                if lhs.is_nullable() {
+                    rhs.collect_first(names);
+                }
+            }
+            Particle::Alt(lhs, rhs) => {
+                lhs.collect_first(names);
+                rhs.collect_first(names);
+            }
+            Particle::Star(inner) => inner.collect_first(names),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ContentParser<'_> {
+    ///
+    /// ```ebnf
+    /// cp      ::= (Name | choice | seq) ('?' | '*' | '+')?
+    /// choice  ::= '(' S? cp ( S? '|' S? cp )+ S? ')'
+    /// seq     ::= '(' S? cp ( S? ',' S? cp )* S? ')'
+    /// ```
+    ///
+    fn content_particle(&mut self) -> Result<Particle> {
+        let particle = if let Some(rest) = self.rest.strip_prefix('(') {
+            self.rest = rest;
+            let mut particles = vec![self.content_particle()?];
+            let mut separator: Option<char> = None;
+            loop {
+                let c = self.rest.chars().next();
+                match c {
+                    Some(')') => {
+                        self.rest = &self.rest[1..];
+                        break;
+                    }
+                    Some(',') | Some('|') if separator.is_none() || separator == c => {
+                        separator = c;
+                        self.rest = &self.rest[1..];
+                        particles.push(self.content_particle()?);
+                    }
+                    _ => return invalid_content_model(),
+                }
+            }
+            let combine = if separator == Some('|') {
+                Particle::alt
+            } else {
+                Particle::seq
+            };
+            let last = particles.pop().unwrap();
+            particles
+                .into_iter()
+                .rev()
+                .fold(last, |rest, particle| combine(particle, rest))
+        } else {
+            let length = self
+                .rest
+                .find(['(', ')', ',', '|', '?', '*', '+'])
+                .unwrap_or(self.rest.len());
+            if length == 0 {
+                return invalid_content_model();
+            }
+            let name = &self.rest[..length];
+            self.rest = &self.rest[length..];
+            Particle::Name(name.to_string())
+        };
+        let occurrence = self
+            .rest
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '?' | '*' | '+'));
+        if occurrence.is_some() {
+            self.rest = &self.rest[1..];
+        }
+        Ok(particle.repeat(occurrence))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The content specification of an element type declaration.
+///
+/// ```ebnf
+/// contentspec  ::= 'EMPTY' | 'ANY' | Mixed | children
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+enum ContentModel {
+    Empty,
+    Any,
+    Mixed(BTreeSet<String>),
+    Children(Particle),
+}
+
+///
+/// A content particle, as a regular expression over element names.
+///
+#[derive(Clone, Debug, PartialEq)]
+enum Particle {
+    /// Matches only the empty sequence.
+    Empty,
+    /// Matches nothing at all.
+    Nothing,
+    Name(String),
+    Seq(Box<Particle>, Box<Particle>),
+    Alt(Box<Particle>, Box<Particle>),
+    Star(Box<Particle>),
+}
+
+struct ContentParser<'a> {
+    rest: &'a str,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Parse the remainder of a mixed content declaration, following `(#PCDATA`.
+///
+/// ```ebnf
+/// Mixed  ::= '(' S? '#PCDATA' (S? '|' S? Name)* S? ')*' | '(' S? '#PCDATA' S? ')'
+/// ```
+///
+fn parse_mixed(rest: &str) -> Result<ContentModel> {
+    if rest == ")" || rest == ")*" {
+        return Ok(ContentModel::Mixed(Default::default()));
+    }
+    match rest
+        .strip_prefix('|')
+        .and_then(|names| names.strip_suffix(")*"))
+    {
+        Some(names) if names.split('|').all(|name| !name.is_empty()) => Ok(ContentModel::Mixed(
+            names.split('|').map(String::from).collect(),
+        )),
+        _ => invalid_content_model(),
+    }
+}
+
+fn has_element_child(node: &RefNode) -> bool {
+    node.child_nodes()
+        .iter()
+        .any(|child| child.node_type() == NodeType::Element)
+}
+
+fn invalid_content_model<T>() -> Result<T> {
+    warn!("{}", MSG_INVALID_CONTENT_MODEL);
+    Err(Error::Syntax)
+}
@@ -0,0 +1,1518 @@
+/*!
+Provides an adapter that executes the test descriptions of the W3C DOM Test Suite (DOMTS), for
+DOM Level 1 and Level 2 Core, against this implementation and reports the outcome of each test;
+so that the DOM behaviors supported by this crate can be seen before adopting it.
+
+Each DOMTS test is an XML document whose `test` element contains variable declarations, `load`
+statements that read the suite's sample documents, calls to DOM attributes and methods, named
+after the IDL, assertions, and the control statements `if`, `while`, and `for-each`. The adapter
+interprets the common subset of this language used by the Core tests: a test that uses a
+statement, a method, or an implementation attribute outside that subset is reported as
+[`Unsupported`](enum.TestOutcome.html#variant.Unsupported) rather than failed. `NodeList` and
+`NamedNodeMap` values are live, the members of a `NamedNodeMap` are ordered by name.
+
+The sample documents are fetched with a [`UriResolver`](../resolver/trait.UriResolver.html); a
+`load` of `href="staff"` fetches `staff.xml`. Note that the parser provided by this crate does not
+preserve white space only text, or expand entity references, so tests that count these nodes in
+the sample documents are expected to fail.
+
+This module is only available with the `quick_parser` feature.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::conformance::{run_test, TestOutcome};
+use xml_dom::level2::ext::resolver::{Resource, UriResolver};
+
+#[derive(Debug)]
+struct Samples;
+
+impl UriResolver for Samples {
+    fn fetch(&self, uri: &str, _referrer: &RefNode) -> Result<Resource> {
+        match uri {
+            "staff.xml" => Ok(Resource::Text(
+                r#"<staff><employee id="1"><name>Margaret Martin</name></employee></staff>"#
+                    .to_string(),
+            )),
+            _ => Err(Error::NotFound),
+        }
+    }
+}
+
+let result = run_test(
+    r#"<test xmlns="http://www.w3.org/2001/DOM-Test-Suite/Level-1" name="nodename">
+  <var name="doc" type="Document"/>
+  <var name="names" type="NodeList"/>
+  <var name="nameNode" type="Node"/>
+  <var name="nodeName" type="DOMString"/>
+  <load var="doc" href="staff" willBeModified="false"/>
+  <getElementsByTagName interface="Document" obj="doc" tagname="&quot;name&quot;" var="names"/>
+  <item interface="NodeList" obj="names" index="0" var="nameNode"/>
+  <nodeName obj="nameNode" var="nodeName"/>
+  <assertEquals actual="nodeName" expected="&quot;name&quot;" id="nodeName" ignoreCase="false"/>
+</test>"#,
+    &Samples,
+);
+assert_eq!(result.name, "nodename");
+assert_eq!(result.outcome, TestOutcome::Passed);
+```
+*/
+
+use crate::level2::convert::{
+    as_attribute, as_attribute_mut, as_character_data, as_character_data_mut, as_document,
+    as_document_type, as_element, as_element_mut, as_entity, as_notation,
+    as_processing_instruction, as_processing_instruction_mut, as_text_mut,
+};
+use crate::level2::dom_impl::get_implementation;
+use crate::level2::ext::resolver::{FileResolver, Resource, UriResolver};
+use crate::level2::ext::workspace::import_node;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::*;
+use crate::parser::read_xml;
+use crate::shared::error::Error;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Result as IoResult;
+use std::path::Path;
+use std::result::Result as StdResult;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The outcome of a single test.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestOutcome {
+    /// Every assertion made by the test succeeded.
+    Passed,
+    /// An assertion failed, or a DOM operation raised an exception the test did not expect; the
+    /// message identifies which.
+    Failed(String),
+    /// The test uses part of the test language, or requires a parser setting, that is not
+    /// supported; the message identifies which.
+    Unsupported(String),
+    /// The test could not be run, as the description is malformed or a sample document could not
+    /// be loaded.
+    Error(String),
+}
+
+///
+/// The result of a single test, identified by the `name` attribute of its `test` element.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestResult {
+    /// The name of the test.
+    pub name: String,
+    /// The outcome of running the test.
+    pub outcome: TestOutcome,
+}
+
+///
+/// The results of running a set of tests, in the order in which they were run.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConformanceReport {
+    /// The result of each test.
+    pub results: Vec<TestResult>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Parse and run the test `description`, fetching the sample documents it loads from
+/// `resolver`.
+///
+pub fn run_test(description: &str, resolver: &dyn UriResolver) -> TestResult {
+    match read_xml(description) {
+        Ok(test_node) => run_test_document(&test_node, resolver),
+        Err(e) => TestResult {
+            name: String::new(),
+            outcome: TestOutcome::Error(format!("could not parse the test description: {}", e)),
+        },
+    }
+}
+
+///
+/// Run the test described by the document `test_node`, fetching the sample documents it loads
+/// from `resolver`.
+///
+pub fn run_test_document(test_node: &RefNode, resolver: &dyn UriResolver) -> TestResult {
+    let test_element = as_document(test_node)
+        .ok()
+        .and_then(|document| document.document_element());
+    let test_element = match test_element {
+        Some(test_element) if test_element.local_name() == TEST_ELEMENT => test_element,
+        _ => {
+            return TestResult {
+                name: String::new(),
+                outcome: TestOutcome::Error("the description has no 'test' element".to_string()),
+            }
+        }
+    };
+    let mut interpreter = Interpreter {
+        resolver,
+        referrer: test_node.clone(),
+        variables: Default::default(),
+    };
+    TestResult {
+        name: attribute(&test_element, "name").unwrap_or_default(),
+        outcome: match interpreter.execute_children(&test_element) {
+            Ok(()) => TestOutcome::Passed,
+            Err(interrupt) => interrupt.into_outcome(),
+        },
+    }
+}
+
+///
+/// Run each test description, a file with the extension `.xml` whose document element is a
+/// `test` element, in the directory `tests`, in file name order; sample documents are read from
+/// the directory `files`. In the DOMTS distribution the `files` directory is a sub-directory of
+/// each directory of tests.
+///
+pub fn run_directory(tests: &Path, files: &Path) -> IoResult<ConformanceReport> {
+    let mut paths: Vec<_> = std::fs::read_dir(tests)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "xml"))
+        .collect();
+    paths.sort();
+
+    let resolver = FileResolver::new(files);
+    let mut report = ConformanceReport::default();
+    for path in paths {
+        let description = std::fs::read_to_string(&path)?;
+        let file_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut result = match read_xml(&description) {
+            Ok(test_node) => {
+                let is_test = as_document(&test_node)
+                    .ok()
+                    .and_then(|document| document.document_element())
+                    .is_some_and(|element| element.local_name() == TEST_ELEMENT);
+                if !is_test {
+                    continue;
+                }
+                run_test_document(&test_node, &resolver)
+            }
+            Err(e) => TestResult {
+                name: String::new(),
+                outcome: TestOutcome::Error(format!("could not parse the test description: {}", e)),
+            },
+        };
+        if result.name.is_empty() {
+            result.name = file_name;
+        }
+        report.results.push(result);
+    }
+    Ok(report)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for TestOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            TestOutcome::Passed => write!(f, "passed"),
+            TestOutcome::Failed(message) => write!(f, "FAILED: {}", message),
+            TestOutcome::Unsupported(message) => write!(f, "unsupported: {}", message),
+            TestOutcome::Error(message) => write!(f, "error: {}", message),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl TestResult {
+    ///
+    /// Returns `true` if the test passed.
+    ///
+    pub fn is_passed(&self) -> bool {
+        self.outcome == TestOutcome::Passed
+    }
+}
+
+impl Display for TestResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {}", self.name, self.outcome)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ConformanceReport {
+    ///
+    /// Returns the number of tests that passed.
+    ///
+    pub fn passed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Passed))
+    }
+
+    ///
+    /// Returns the number of tests that failed.
+    ///
+    pub fn failed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Failed(_)))
+    }
+
+    ///
+    /// Returns the number of tests that use unsupported parts of the test language.
+    ///
+    pub fn unsupported(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Unsupported(_)))
+    }
+
+    ///
+    /// Returns the number of tests that could not be run.
+    ///
+    pub fn errors(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Error(_)))
+    }
+
+    fn count(&self, predicate: impl Fn(&TestOutcome) -> bool) -> usize {
+        self.results
+            .iter()
+            .filter(|result| predicate(&result.outcome))
+            .count()
+    }
+}
+
+impl Display for ConformanceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for result in &self.results {
+            writeln!(f, "{}", result)?;
+        }
+        write!(
+            f,
+            "{} tests: {} passed, {} failed, {} unsupported, {} errors",
+            self.results.len(),
+            self.passed(),
+            self.failed(),
+            self.unsupported(),
+            self.errors()
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<Error> for Interrupt {
+    fn from(e: Error) -> Self {
+        Interrupt::Exception(e, String::new())
+    }
+}
+
+impl Interrupt {
+    fn into_outcome(self) -> TestOutcome {
+        match self {
+            Interrupt::Exception(e, statement) => TestOutcome::Failed(format!(
+                "unexpected exception {} raised by '{}'",
+                exception_name(&e),
+                statement
+            )),
+            Interrupt::Stop(outcome) => outcome,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodeList {
+    fn nodes(&self) -> Vec<RefNode> {
+        match self {
+            NodeList::Children(node) => node.child_nodes(),
+            NodeList::ByTagName(node, tag_name) => match as_document(node) {
+                Ok(document) => document.get_elements_by_tag_name(tag_name),
+                Err(_) => as_element(node)
+                    .map(|element| element.get_elements_by_tag_name(tag_name))
+                    .unwrap_or_default(),
+            },
+            NodeList::ByTagNameNs(node, namespace_uri, local_name) => match as_document(node) {
+                Ok(document) => document.get_elements_by_tag_name_ns(namespace_uri, local_name),
+                Err(_) => as_element(node)
+                    .map(|element| element.get_elements_by_tag_name_ns(namespace_uri, local_name))
+                    .unwrap_or_default(),
+            },
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NamedNodeMap {
+    fn nodes(&self) -> Vec<RefNode> {
+        let map = match self {
+            NamedNodeMap::Attributes(element) => element.attributes(),
+            NamedNodeMap::Entities(doc_type) => as_document_type(doc_type)
+                .map(|doc_type| doc_type.entities())
+                .unwrap_or_default(),
+            NamedNodeMap::Notations(doc_type) => as_document_type(doc_type)
+                .map(|doc_type| doc_type.notations())
+                .unwrap_or_default(),
+        };
+        let mut nodes: Vec<(String, RefNode)> = map
+            .into_iter()
+            .map(|(name, node)| (name.to_string(), node))
+            .collect();
+        nodes.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+        nodes.into_iter().map(|(_, node)| node).collect()
+    }
+
+    fn get_named_item(&self, name: &str) -> Option<RefNode> {
+        self.nodes()
+            .into_iter()
+            .find(|node| node.node_name().to_string() == name)
+    }
+
+    fn get_named_item_ns(&self, namespace_uri: Option<&str>, local_name: &str) -> Option<RefNode> {
+        self.nodes().into_iter().find(|node| {
+            node.namespace_uri().as_deref() == namespace_uri && node.local_name() == local_name
+        })
+    }
+
+    fn set_named_item(&self, node: RefNode, ns: bool) -> StdResult<Value, Interrupt> {
+        match self {
+            NamedNodeMap::Attributes(element) => {
+                let mut element = element.clone();
+                let name = node.node_name();
+                let previous = element
+                    .attributes()
+                    .into_iter()
+                    .find(|(existing, _)| *existing == name)
+                    .map(|(_, previous)| previous);
+                let element = as_element_mut(&mut element)?;
+                let _safe_to_ignore = if ns {
+                    element.set_attribute_node_ns(node)?
+                } else {
+                    element.set_attribute_node(node)?
+                };
+                Ok(optional_node(previous))
+            }
+            _ => Err(Error::NoModificationAllowed.into()),
+        }
+    }
+
+    fn remove_named_item(&self, node: Option<RefNode>) -> StdResult<Value, Interrupt> {
+        match (self, node) {
+            (NamedNodeMap::Attributes(element), Some(node)) => {
+                let mut element = element.clone();
+                Ok(Value::Node(
+                    as_element_mut(&mut element)?.remove_attribute_node(node)?,
+                ))
+            }
+            (NamedNodeMap::Attributes(_), None) => Err(Error::NotFound.into()),
+            _ => Err(Error::NoModificationAllowed.into()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Value {
+    fn describe(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Int(value) => value.to_string(),
+            Value::String(value) => format!("\"{}\"", value),
+            Value::Node(node) => format!("node '{}'", node.node_name()),
+            Value::NodeList(list) => format!("node list of {}", list.nodes().len()),
+            Value::NamedNodeMap(map) => format!("named node map of {}", map.nodes().len()),
+            Value::Collection(items, _) => format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(|item| item.describe())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Value::Implementation(_) => "implementation".to_string(),
+        }
+    }
+
+    fn items(&self) -> Option<Vec<Value>> {
+        match self {
+            Value::NodeList(list) => Some(list.nodes().into_iter().map(Value::Node).collect()),
+            Value::NamedNodeMap(map) => Some(map.nodes().into_iter().map(Value::Node).collect()),
+            Value::Collection(items, _) => Some(items.clone()),
+            _ => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Interpreter<'_> {
+    fn execute_children(&mut self, parent: &RefNode) -> Outcome<()> {
+        for step in elements(parent) {
+            self.execute(&step)?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, step: &RefNode) -> Outcome<()> {
+        let name = step.local_name();
+        match name.as_str() {
+            "metadata" => Ok(()),
+            "var" => self.declare(step),
+            "load" => self.load(step),
+            "implementationAttribute" => {
+                let setting = self.required_attribute(step, "name")?;
+                let value = self.required_attribute(step, "value")?;
+                if value == "true" && !SUPPORTED_SETTINGS.contains(&setting.as_str()) {
+                    unsupported(format!("implementation attribute '{}'", setting))
+                } else {
+                    Ok(())
+                }
+            }
+            "hasFeature" if attribute(step, "obj").is_none() => {
+                let feature = self.string(step, "feature")?.unwrap_or_default();
+                let version = self.string(step, "version")?.unwrap_or_default();
+                if get_implementation().has_feature(&feature, &version) {
+                    Ok(())
+                } else {
+                    unsupported(format!("feature '{}' version '{}'", feature, version))
+                }
+            }
+            "implementation" => {
+                let document = match attribute(step, "obj") {
+                    Some(_) => Some(self.node(step, "obj")?),
+                    None => None,
+                };
+                self.assign(step, Value::Implementation(document))
+            }
+            "assign" => {
+                let value = self.operand(step, "value")?;
+                self.assign(step, value)
+            }
+            "increment" | "decrement" => {
+                let current = self.integer(step, "var")?;
+                let by = self.integer(step, "value")?;
+                let value = if name == "increment" {
+                    current + by
+                } else {
+                    current - by
+                };
+                self.assign(step, Value::Int(value))
+            }
+            "plus" | "subtract" | "mult" => {
+                let lhs = self.integer(step, "op1")?;
+                let rhs = self.integer(step, "op2")?;
+                let value = match name.as_str() {
+                    "plus" => lhs + rhs,
+                    "subtract" => lhs - rhs,
+                    _ => lhs * rhs,
+                };
+                self.assign(step, Value::Int(value))
+            }
+            "append" => {
+                let collection = self.required_attribute(step, "collection")?;
+                let item = self.operand(step, "item")?;
+                match self.variables.get_mut(&collection) {
+                    Some(Value::Collection(items, _)) => {
+                        items.push(item);
+                        Ok(())
+                    }
+                    _ => error(format!("'{}' is not a collection", collection)),
+                }
+            }
+            "if" => {
+                let mut steps = elements(step).into_iter();
+                let condition = match steps.next() {
+                    Some(condition) => self.condition(&condition)?,
+                    None => return error("'if' has no condition".to_string()),
+                };
+                for step in steps {
+                    let is_else = step.local_name() == "else";
+                    if is_else && !condition {
+                        self.execute_children(&step)?;
+                    } else if !is_else && condition {
+                        self.execute(&step)?;
+                    }
+                }
+                Ok(())
+            }
+            "while" => {
+                let steps = elements(step);
+                let (condition, body) = match steps.split_first() {
+                    Some(split) => split,
+                    None => return error("'while' has no condition".to_string()),
+                };
+                let mut iterations = 0;
+                while self.condition(condition)? {
+                    iterations += 1;
+                    if iterations > MAX_ITERATIONS {
+                        return error("'while' did not terminate".to_string());
+                    }
+                    for step in body {
+                        self.execute(step)?;
+                    }
+                }
+                Ok(())
+            }
+            "for-each" => {
+                let collection = self.operand(step, "collection")?;
+                let member = self.required_attribute(step, "member")?;
+                let items = match collection.items() {
+                    Some(items) => items,
+                    None => {
+                        return error(format!(
+                            "'for-each' over {} is not a collection",
+                            collection.describe()
+                        ))
+                    }
+                };
+                for item in items {
+                    let _safe_to_ignore = self.variables.insert(member.clone(), item);
+                    self.execute_children(step)?;
+                }
+                Ok(())
+            }
+            "assertTrue" | "assertFalse" => {
+                let steps = elements(step);
+                let (value, body) = match attribute(step, "actual") {
+                    Some(_) => (self.boolean(step, "actual")?, &steps[..]),
+                    None => match steps.split_first() {
+                        Some((condition, body)) => (self.condition(condition)?, body),
+                        None => return error(format!("'{}' has no condition", name)),
+                    },
+                };
+                self.check(step, value == (name == "assertTrue"), || {
+                    format!("expected {}", name == "assertTrue")
+                })?;
+                for step in body {
+                    self.execute(step)?;
+                }
+                Ok(())
+            }
+            "assertNull" | "assertNotNull" => {
+                let actual = self.operand(step, "actual")?;
+                let is_null = matches!(actual, Value::Null);
+                self.check(step, is_null == (name == "assertNull"), || {
+                    format!("unexpected value {}", actual.describe())
+                })?;
+                self.execute_children(step)
+            }
+            "assertEquals" | "assertNotEquals" => {
+                let actual = self.operand(step, "actual")?;
+                let expected = self.operand(step, "expected")?;
+                let equal = values_equal(&actual, &expected, ignore_case(step));
+                self.check(step, equal == (name == "assertEquals"), || {
+                    format!(
+                        "actual {}, expected {}{}",
+                        actual.describe(),
+                        if name == "assertEquals" { "" } else { "not " },
+                        expected.describe()
+                    )
+                })?;
+                self.execute_children(step)
+            }
+            "assertSame" => {
+                let actual = self.operand(step, "actual")?;
+                let expected = self.operand(step, "expected")?;
+                let same = values_same(&actual, &expected);
+                self.check(step, same, || {
+                    format!("{} is not {}", actual.describe(), expected.describe())
+                })?;
+                self.execute_children(step)
+            }
+            "assertSize" => {
+                let size = self.integer(step, "size")?;
+                let collection = self.operand(step, "collection")?;
+                let actual = self.size(&collection)?;
+                self.check(step, actual == size, || {
+                    format!("size {}, expected {}", actual, size)
+                })?;
+                self.execute_children(step)
+            }
+            "assertDOMException" => {
+                let (code, call) = match elements(step).first() {
+                    Some(code) => match elements(code).first() {
+                        Some(call) => (code.local_name(), call.clone()),
+                        None => return error(format!("'{}' has no method", code.local_name())),
+                    },
+                    None => return error("'assertDOMException' has no exception".to_string()),
+                };
+                match self.execute(&call) {
+                    Ok(()) => self.check(step, false, || {
+                        format!("expected {} from '{}'", code, call.local_name())
+                    }),
+                    Err(Interrupt::Exception(e, _)) => {
+                        self.check(step, exception_name(&e) == code, || {
+                            format!(
+                                "expected {} from '{}', not {}",
+                                code,
+                                call.local_name(),
+                                exception_name(&e)
+                            )
+                        })
+                    }
+                    Err(stop) => Err(stop),
+                }
+            }
+            "fail" => {
+                let id = attribute(step, "id").unwrap_or_default();
+                Err(Interrupt::Stop(TestOutcome::Failed(format!(
+                    "'{}' reached 'fail'",
+                    id
+                ))))
+            }
+            _ if name.starts_with("assert") => unsupported(format!("statement '{}'", name)),
+            _ => {
+                let value = self
+                    .call(step, &name)
+                    .map_err(|interrupt| match interrupt {
+                        Interrupt::Exception(e, _) => Interrupt::Exception(e, name.clone()),
+                        stop => stop,
+                    })?;
+                if attribute(step, "var").is_some() {
+                    self.assign(step, value)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn declare(&mut self, step: &RefNode) -> Outcome<()> {
+        let name = self.required_attribute(step, "name")?;
+        let var_type = attribute(step, "type").unwrap_or_default();
+        let members = elements(step);
+        let value = if let Some(member) = members.iter().find(|m| m.local_name() != "member") {
+            return unsupported(format!("'var' content '{}'", member.local_name()));
+        } else if !members.is_empty() || var_type.ends_with("List") || var_type == "Collection" {
+            let mut items = Vec::with_capacity(members.len());
+            for member in members {
+                items.push(self.literal(&text_of(&member))?);
+            }
+            Value::Collection(items, var_type.ends_with("List"))
+        } else if attribute(step, "value").is_some() {
+            self.operand(step, "value")?
+        } else {
+            Value::Null
+        };
+        let _safe_to_ignore = self.variables.insert(name, value);
+        Ok(())
+    }
+
+    fn load(&mut self, step: &RefNode) -> Outcome<()> {
+        let href = self.required_attribute(step, "href")?;
+        let uri = if href.contains('.') {
+            href
+        } else {
+            format!("{}.xml", href)
+        };
+        let document_node = match self.resolver.fetch(&uri, &self.referrer) {
+            Ok(Resource::Text(text)) => read_xml(&text).map_err(|e| {
+                Interrupt::Stop(TestOutcome::Error(format!(
+                    "could not parse '{}': {}",
+                    uri, e
+                )))
+            })?,
+            Ok(Resource::Node(node)) => match node.clone_node(true) {
+                Some(document_node) => document_node,
+                None => return error(format!("could not copy '{}'", uri)),
+            },
+            Err(e) => return error(format!("could not load '{}': {}", uri, e)),
+        };
+        self.assign(step, Value::Node(document_node))
+    }
+
+    fn condition(&mut self, condition: &RefNode) -> Outcome<bool> {
+        let name = condition.local_name();
+        Ok(match name.as_str() {
+            "equals" | "notEquals" => {
+                let actual = self.operand(condition, "actual")?;
+                let expected = self.operand(condition, "expected")?;
+                values_equal(&actual, &expected, ignore_case(condition)) == (name == "equals")
+            }
+            "same" => {
+                let actual = self.operand(condition, "actual")?;
+                let expected = self.operand(condition, "expected")?;
+                values_same(&actual, &expected)
+            }
+            "isNull" => matches!(self.operand(condition, "obj")?, Value::Null),
+            "notNull" => !matches!(self.operand(condition, "obj")?, Value::Null),
+            "isTrue" => self.boolean(condition, "value")?,
+            "isFalse" => !self.boolean(condition, "value")?,
+            "less" | "lessOrEquals" | "greater" | "greaterOrEquals" => {
+                let actual = self.integer(condition, "actual")?;
+                let expected = self.integer(condition, "expected")?;
+                match name.as_str() {
+                    "less" => actual < expected,
+                    "lessOrEquals" => actual <= expected,
+                    "greater" => actual > expected,
+                    _ => actual >= expected,
+                }
+            }
+            "hasSize" => {
+                let collection = self.operand(condition, "obj")?;
+                self.size(&collection)? == self.integer(condition, "expected")?
+            }
+            "and" | "or" => {
+                let mut result = name == "and";
+                for operand in elements(condition) {
+                    let value = self.condition(&operand)?;
+                    result = if name == "and" {
+                        result && value
+                    } else {
+                        result || value
+                    };
+                }
+                result
+            }
+            "not" => match elements(condition).first() {
+                Some(operand) => !self.condition(operand)?,
+                None => return error("'not' has no condition".to_string()),
+            },
+            _ => return unsupported(format!("condition '{}'", name)),
+        })
+    }
+
+    fn check(
+        &self,
+        step: &RefNode,
+        success: bool,
+        message: impl FnOnce() -> String,
+    ) -> Outcome<()> {
+        if success {
+            Ok(())
+        } else {
+            Err(Interrupt::Stop(TestOutcome::Failed(format!(
+                "'{}' {}",
+                attribute(step, "id").unwrap_or_else(|| step.local_name()),
+                message()
+            ))))
+        }
+    }
+
+    fn call(&mut self, step: &RefNode, method: &str) -> Outcome<Value> {
+        let target = self.operand(step, "obj")?;
+        let is_setter = attribute(step, "var").is_none() && attribute(step, "value").is_some();
+        match target {
+            Value::Node(node) => self.call_node(step, method, node, is_setter),
+            Value::NodeList(list) => match method {
+                "item" => {
+                    let index = self.integer(step, "index")?;
+                    Ok(optional_node(nth(list.nodes(), index)))
+                }
+                "length" => Ok(Value::Int(list.nodes().len() as i64)),
+                _ => unsupported(format!("NodeList method '{}'", method)),
+            },
+            Value::NamedNodeMap(map) => match method {
+                "item" => {
+                    let index = self.integer(step, "index")?;
+                    Ok(optional_node(nth(map.nodes(), index)))
+                }
+                "length" => Ok(Value::Int(map.nodes().len() as i64)),
+                "getNamedItem" => {
+                    let name = self.string(step, "name")?.unwrap_or_default();
+                    Ok(optional_node(map.get_named_item(&name)))
+                }
+                "getNamedItemNS" => {
+                    let namespace_uri = self.string(step, "namespaceURI")?;
+                    let local_name = self.string(step, "localName")?.unwrap_or_default();
+                    Ok(optional_node(
+                        map.get_named_item_ns(namespace_uri.as_deref(), &local_name),
+                    ))
+                }
+                "setNamedItem" | "setNamedItemNS" => {
+                    let node = self.node(step, "arg")?;
+                    map.set_named_item(node, method == "setNamedItemNS")
+                }
+                "removeNamedItem" => {
+                    let name = self.string(step, "name")?.unwrap_or_default();
+                    map.remove_named_item(map.get_named_item(&name))
+                }
+                "removeNamedItemNS" => {
+                    let namespace_uri = self.string(step, "namespaceURI")?;
+                    let local_name = self.string(step, "localName")?.unwrap_or_default();
+                    map.remove_named_item(
+                        map.get_named_item_ns(namespace_uri.as_deref(), &local_name),
+                    )
+                }
+                _ => unsupported(format!("NamedNodeMap method '{}'", method)),
+            },
+            Value::Implementation(document) => self.call_implementation(step, method, document),
+            Value::Null => Err(Interrupt::Stop(TestOutcome::Failed(format!(
+                "'{}' called on null",
+                method
+            )))),
+            other => error(format!("'{}' called on {}", method, other.describe())),
+        }
+    }
+
+    fn call_implementation(
+        &mut self,
+        step: &RefNode,
+        method: &str,
+        document: Option<RefNode>,
+    ) -> Outcome<Value> {
+        let implementation = match &document {
+            Some(document_node) => Some(as_document(document_node)?.implementation()),
+            None => None,
+        };
+        let implementation = match &implementation {
+            Some(implementation) => implementation.as_ref(),
+            None => get_implementation(),
+        };
+        Ok(match method {
+            "hasFeature" => {
+                let feature = self.string(step, "feature")?.unwrap_or_default();
+                let version = self.string(step, "version")?.unwrap_or_default();
+                Value::Bool(implementation.has_feature(&feature, &version))
+            }
+            "createDocumentType" => {
+                let qualified_name = self.string(step, "qualifiedName")?.unwrap_or_default();
+                let public_id = self.string(step, "publicId")?;
+                let system_id = self.string(step, "systemId")?;
+                Value::Node(implementation.create_document_type(
+                    &qualified_name,
+                    public_id.as_deref(),
+                    system_id.as_deref(),
+                )?)
+            }
+            "createDocument" => {
+                let namespace_uri = self.string(step, "namespaceURI")?;
+                let qualified_name = self.string(step, "qualifiedName")?;
+                let doc_type = self.optional_node(step, "doctype")?;
+                Value::Node(implementation.create_document(
+                    namespace_uri.as_deref(),
+                    qualified_name.as_deref(),
+                    doc_type,
+                )?)
+            }
+            _ => return unsupported(format!("DOMImplementation method '{}'", method)),
+        })
+    }
+
+    fn call_node(
+        &mut self,
+        step: &RefNode,
+        method: &str,
+        mut node: RefNode,
+        is_setter: bool,
+    ) -> Outcome<Value> {
+        let node_type = node.node_type();
+        Ok(match method {
+            // Node
+            "nodeName" => Value::String(node.node_name().to_string()),
+            "nodeValue" if is_setter => {
+                let value = self.string(step, "value")?;
+                match value {
+                    Some(value) => node.set_node_value(&value)?,
+                    None => node.unset_node_value()?,
+                }
+                Value::Null
+            }
+            "nodeValue" => optional_string(node.node_value()),
+            "nodeType" => Value::Int(node_type.clone() as i64),
+            "parentNode" => optional_node(node.parent_node()),
+            "childNodes" => Value::NodeList(NodeList::Children(node)),
+            "firstChild" => optional_node(node.first_child()),
+            "lastChild" => optional_node(node.last_child()),
+            "previousSibling" => optional_node(node.previous_sibling()),
+            "nextSibling" => optional_node(node.next_sibling()),
+            "attributes" if node_type == NodeType::Element => {
+                Value::NamedNodeMap(NamedNodeMap::Attributes(node))
+            }
+            "attributes" => Value::Null,
+            "ownerDocument" => optional_node(node.owner_document()),
+            "namespaceURI" => optional_string(node.namespace_uri()),
+            "prefix" if is_setter => return unsupported("setting 'prefix'".to_string()),
+            "prefix" => optional_string(node.prefix()),
+            "localName" if has_local_name(&node_type) => Value::String(node.local_name()),
+            "localName" => Value::Null,
+            "insertBefore" => {
+                let new_child = self.node(step, "newChild")?;
+                let ref_child = self.optional_node(step, "refChild")?;
+                Value::Node(node.insert_before(new_child, ref_child)?)
+            }
+            "replaceChild" => {
+                let new_child = self.node(step, "newChild")?;
+                let old_child = self.node(step, "oldChild")?;
+                Value::Node(node.replace_child(new_child, old_child)?)
+            }
+            "removeChild" => Value::Node(node.remove_child(self.node(step, "oldChild")?)?),
+            "appendChild" => Value::Node(node.append_child(self.node(step, "newChild")?)?),
+            "hasChildNodes" => Value::Bool(node.has_child_nodes()),
+            "cloneNode" => optional_node(node.clone_node(self.boolean(step, "deep")?)),
+            "normalize" => {
+                node.normalize();
+                Value::Null
+            }
+            "isSupported" => {
+                let feature = self.string(step, "feature")?.unwrap_or_default();
+                let version = self.string(step, "version")?.unwrap_or_default();
+                Value::Bool(node.is_supported(&feature, &version))
+            }
+            "hasAttributes" => Value::Bool(node.has_attributes()),
+            // Document
+            "doctype" => optional_node(as_document(&node)?.doc_type()),
+            "documentElement" => optional_node(as_document(&node)?.document_element()),
+            "createElement" => {
+                let tag_name = self.string(step, "tagName")?.unwrap_or_default();
+                Value::Node(as_document(&node)?.create_element(&tag_name)?)
+            }
+            "createElementNS" | "createAttributeNS" => {
+                let namespace_uri = self.string(step, "namespaceURI")?.unwrap_or_default();
+                let qualified_name = self.string(step, "qualifiedName")?.unwrap_or_default();
+                let document = as_document(&node)?;
+                Value::Node(if method == "createElementNS" {
+                    document.create_element_ns(&namespace_uri, &qualified_name)?
+                } else {
+                    document.create_attribute_ns(&namespace_uri, &qualified_name)?
+                })
+            }
+            "createDocumentFragment" => {
+                Value::Node(as_document(&node)?.create_document_fragment()?)
+            }
+            "createTextNode" => {
+                let data = self.string(step, "data")?.unwrap_or_default();
+                Value::Node(as_document(&node)?.create_text_node(&data))
+            }
+            "createComment" => {
+                let data = self.string(step, "data")?.unwrap_or_default();
+                Value::Node(as_document(&node)?.create_comment(&data))
+            }
+            "createCDATASection" => {
+                let data = self.string(step, "data")?.unwrap_or_default();
+                Value::Node(as_document(&node)?.create_cdata_section(&data)?)
+            }
+            "createProcessingInstruction" => {
+                let target = self.string(step, "target")?.unwrap_or_default();
+                let data = self.string(step, "data")?;
+                Value::Node(
+                    as_document(&node)?.create_processing_instruction(&target, data.as_deref())?,
+                )
+            }
+            "createAttribute" => {
+                let name = self.string(step, "name")?.unwrap_or_default();
+                Value::Node(as_document(&node)?.create_attribute(&name)?)
+            }
+            "createEntityReference" => {
+                let name = self.string(step, "name")?.unwrap_or_default();
+                Value::Node(as_document(&node)?.create_entity_reference(&name)?)
+            }
+            "getElementsByTagName" => {
+                let tag_name = match attribute(step, "tagname") {
+                    Some(_) => self.string(step, "tagname")?,
+                    None => self.string(step, "name")?,
+                };
+                Value::NodeList(NodeList::ByTagName(node, tag_name.unwrap_or_default()))
+            }
+            "getElementsByTagNameNS" => {
+                let namespace_uri = self.string(step, "namespaceURI")?.unwrap_or_default();
+                let local_name = self.string(step, "localName")?.unwrap_or_default();
+                Value::NodeList(NodeList::ByTagNameNs(node, namespace_uri, local_name))
+            }
+            "importNode" => {
+                let imported = self.node(step, "importedNode")?;
+                let deep = self.boolean(step, "deep")?;
+                let _safe_to_ignore = as_document(&node)?;
+                Value::Node(import_node(
+                    &mut Default::default(),
+                    &node,
+                    &imported,
+                    deep,
+                )?)
+            }
+            "getElementById" => {
+                let id = self.string(step, "elementId")?.unwrap_or_default();
+                optional_node(as_document(&node)?.get_element_by_id(&id))
+            }
+            // Element
+            "tagName" => Value::String(as_element(&node)?.tag_name()),
+            "getAttribute" => {
+                let name = self.string(step, "name")?.unwrap_or_default();
+                Value::String(as_element(&node)?.get_attribute(&name).unwrap_or_default())
+            }
+            "getAttributeNS" => {
+                let namespace_uri = self.string(step, "namespaceURI")?.unwrap_or_default();
+                let local_name = self.string(step, "localName")?.unwrap_or_default();
+                Value::String(
+                    as_element(&node)?
+                        .get_attribute_ns(&namespace_uri, &local_name)
+                        .unwrap_or_default(),
+                )
+            }
+            "setAttribute" => {
+                let name = self.string(step, "name")?.unwrap_or_default();
+                let value = self.string(step, "value")?.unwrap_or_default();
+                as_element_mut(&mut node)?.set_attribute(&name, &value)?;
+                Value::Null
+            }
+            "setAttributeNS" => {
+                let namespace_uri = self.string(step, "namespaceURI")?.unwrap_or_default();
+                let qualified_name = self.string(step, "qualifiedName")?.unwrap_or_default();
+                let value = self.string(step, "value")?.unwrap_or_default();
+                as_element_mut(&mut node)?.set_attribute_ns(
+                    &namespace_uri,
+                    &qualified_name,
+                    &value,
+                )?;
+                Value::Null
+            }
+            "removeAttribute" => {
+                let name = self.string(step, "name")?.unwrap_or_default();
+                as_element_mut(&mut node)?.remove_attribute(&name)?;
+                Value::Null
+            }
+            "removeAttributeNS" => {
+                let namespace_uri = self.string(step, "namespaceURI")?.unwrap_or_default();
+                let local_name = self.string(step, "localName")?.unwrap_or_default();
+                as_element_mut(&mut node)?.remove_attribute_ns(&namespace_uri, &local_name)?;
+                Value::Null
+            }
+            "getAttributeNode" => {
+                let name = self.string(step, "name")?.unwrap_or_default();
+                optional_node(as_element(&node)?.get_attribute_node(&name))
+            }
+            "getAttributeNodeNS" => {
+                let namespace_uri = self.string(step, "namespaceURI")?.unwrap_or_default();
+                let local_name = self.string(step, "localName")?.unwrap_or_default();
+                optional_node(as_element(&node)?.get_attribute_node_ns(&namespace_uri, &local_name))
+            }
+            "setAttributeNode" | "setAttributeNodeNS" => {
+                let new_attribute = self.node(step, "newAttr")?;
+                NamedNodeMap::Attributes(node)
+                    .set_named_item(new_attribute, method == "setAttributeNodeNS")?
+            }
+            "removeAttributeNode" => {
+                let old_attribute = self.node(step, "oldAttr")?;
+                Value::Node(as_element_mut(&mut node)?.remove_attribute_node(old_attribute)?)
+            }
+            "hasAttribute" => {
+                let name = self.string(step, "name")?.unwrap_or_default();
+                Value::Bool(as_element(&node)?.has_attribute(&name))
+            }
+            "hasAttributeNS" => {
+                let namespace_uri = self.string(step, "namespaceURI")?.unwrap_or_default();
+                let local_name = self.string(step, "localName")?.unwrap_or_default();
+                Value::Bool(as_element(&node)?.has_attribute_ns(&namespace_uri, &local_name))
+            }
+            // Attr, DocumentType
+            "name" => Value::String(node.node_name().to_string()),
+            "specified" => Value::Bool(as_attribute(&node)?.specified()),
+            "value" if is_setter => {
+                let value = self.string(step, "value")?.unwrap_or_default();
+                as_attribute_mut(&mut node)?.set_value(&value)?;
+                Value::Null
+            }
+            "value" => Value::String(as_attribute(&node)?.value().unwrap_or_default()),
+            "ownerElement" => optional_node(as_attribute(&node)?.owner_element()),
+            // CharacterData, ProcessingInstruction
+            "data" if is_setter => {
+                let data = self.string(step, "value")?.unwrap_or_default();
+                if node_type == NodeType::ProcessingInstruction {
+                    as_processing_instruction_mut(&mut node)?.set_data(&data)?;
+                } else {
+                    as_character_data_mut(&mut node)?.set_data(&data)?;
+                }
+                Value::Null
+            }
+            "data" if node_type == NodeType::ProcessingInstruction => {
+                Value::String(as_processing_instruction(&node)?.data().unwrap_or_default())
+            }
+            "data" => Value::String(as_character_data(&node)?.data().unwrap_or_default()),
+            "target" => Value::String(as_processing_instruction(&node)?.target()),
+            "length" => Value::Int(as_character_data(&node)?.length() as i64),
+            "substringData" => {
+                let offset = self.index(step, "offset")?;
+                let count = self.index(step, "count")?;
+                Value::String(as_character_data(&node)?.substring_data(offset, count)?)
+            }
+            "appendData" => {
+                let data = self.string(step, "arg")?.unwrap_or_default();
+                as_character_data_mut(&mut node)?.append_data(&data)?;
+                Value::Null
+            }
+            "insertData" => {
+                let offset = self.index(step, "offset")?;
+                let data = self.string(step, "arg")?.unwrap_or_default();
+                as_character_data_mut(&mut node)?.insert_data(offset, &data)?;
+                Value::Null
+            }
+            "deleteData" => {
+                let offset = self.index(step, "offset")?;
+                let count = self.index(step, "count")?;
+                as_character_data_mut(&mut node)?.delete_data(offset, count)?;
+                Value::Null
+            }
+            "replaceData" => {
+                let offset = self.index(step, "offset")?;
+                let count = self.index(step, "count")?;
+                let data = self.string(step, "arg")?.unwrap_or_default();
+                as_character_data_mut(&mut node)?.replace_data(offset, count, &data)?;
+                Value::Null
+            }
+            "splitText" => {
+                let offset = self.index(step, "offset")?;
+                Value::Node(as_text_mut(&mut node)?.split(offset)?)
+            }
+            // DocumentType, Entity, Notation
+            "entities" => {
+                let _safe_to_ignore = as_document_type(&node)?;
+                Value::NamedNodeMap(NamedNodeMap::Entities(node))
+            }
+            "notations" => {
+                let _safe_to_ignore = as_document_type(&node)?;
+                Value::NamedNodeMap(NamedNodeMap::Notations(node))
+            }
+            "publicId" | "systemId" => {
+                let is_public = method == "publicId";
+                optional_string(match node_type {
+                    NodeType::Entity => {
+                        let entity = as_entity(&node)?;
+                        if is_public {
+                            entity.public_id()
+                        } else {
+                            entity.system_id()
+                        }
+                    }
+                    NodeType::Notation => {
+                        let notation = as_notation(&node)?;
+                        if is_public {
+                            notation.public_id()
+                        } else {
+                            notation.system_id()
+                        }
+                    }
+                    _ => {
+                        let doc_type = as_document_type(&node)?;
+                        if is_public {
+                            doc_type.public_id()
+                        } else {
+                            doc_type.system_id()
+                        }
+                    }
+                })
+            }
+            "internalSubset" => optional_string(as_document_type(&node)?.internal_subset()),
+            "notationName" => optional_string(as_entity(&node)?.notation_name()),
+            _ => return unsupported(format!("method '{}'", method)),
+        })
+    }
+
+    fn assign(&mut self, step: &RefNode, value: Value) -> Outcome<()> {
+        let name = self.required_attribute(step, "var")?;
+        let _safe_to_ignore = self.variables.insert(name, value);
+        Ok(())
+    }
+
+    fn required_attribute(&self, step: &RefNode, name: &str) -> Outcome<String> {
+        match attribute(step, name) {
+            Some(value) => Ok(value),
+            None => error(format!(
+                "'{}' has no '{}' attribute",
+                step.local_name(),
+                name
+            )),
+        }
+    }
+
+    fn operand(&self, step: &RefNode, name: &str) -> Outcome<Value> {
+        let text = self.required_attribute(step, name)?;
+        self.literal(&text)
+    }
+
+    fn literal(&self, text: &str) -> Outcome<Value> {
+        let text = text.trim();
+        if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+            Ok(Value::String(text[1..text.len() - 1].to_string()))
+        } else if text == "null" {
+            Ok(Value::Null)
+        } else if text == "true" || text == "false" {
+            Ok(Value::Bool(text == "true"))
+        } else if let Ok(value) = text.parse::<i64>() {
+            Ok(Value::Int(value))
+        } else {
+            match self.variables.get(text) {
+                Some(value) => Ok(value.clone()),
+                None => error(format!("undeclared variable '{}'", text)),
+            }
+        }
+    }
+
+    fn string(&self, step: &RefNode, name: &str) -> Outcome<Option<String>> {
+        match self.operand(step, name)? {
+            Value::Null => Ok(None),
+            Value::String(value) => Ok(Some(value)),
+            Value::Int(value) => Ok(Some(value.to_string())),
+            Value::Bool(value) => Ok(Some(value.to_string())),
+            other => error(format!("'{}' is {}, not a string", name, other.describe())),
+        }
+    }
+
+    fn integer(&self, step: &RefNode, name: &str) -> Outcome<i64> {
+        match self.operand(step, name)? {
+            Value::Int(value) => Ok(value),
+            other => error(format!(
+                "'{}' is {}, not an integer",
+                name,
+                other.describe()
+            )),
+        }
+    }
+
+    fn index(&self, step: &RefNode, name: &str) -> Outcome<usize> {
+        let value = self.integer(step, name)?;
+        if value < 0 {
+            Err(Error::IndexSize.into())
+        } else {
+            Ok(value as usize)
+        }
+    }
+
+    fn boolean(&self, step: &RefNode, name: &str) -> Outcome<bool> {
+        match self.operand(step, name)? {
+            Value::Bool(value) => Ok(value),
+            other => error(format!("'{}' is {}, not a boolean", name, other.describe())),
+        }
+    }
+
+    fn node(&self, step: &RefNode, name: &str) -> Outcome<RefNode> {
+        match self.optional_node(step, name)? {
+            Some(node) => Ok(node),
+            None => Err(Interrupt::Stop(TestOutcome::Failed(format!(
+                "'{}' of '{}' is null",
+                name,
+                step.local_name()
+            )))),
+        }
+    }
+
+    fn optional_node(&self, step: &RefNode, name: &str) -> Outcome<Option<RefNode>> {
+        match self.operand(step, name)? {
+            Value::Null => Ok(None),
+            Value::Node(node) => Ok(Some(node)),
+            other => error(format!("'{}' is {}, not a node", name, other.describe())),
+        }
+    }
+
+    fn size(&self, collection: &Value) -> Outcome<i64> {
+        match collection.items() {
+            Some(items) => Ok(items.len() as i64),
+            None => error(format!("{} is not a collection", collection.describe())),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const TEST_ELEMENT: &str = "test";
+
+///
+/// Implementation attributes that may be required by a test; all others are only supported when
+/// `false`.
+///
+const SUPPORTED_SETTINGS: &[&str] = &["namespaceAware"];
+
+const MAX_ITERATIONS: usize = 100_000;
+
+///
+/// The state of a running test.
+///
+struct Interpreter<'a> {
+    resolver: &'a dyn UriResolver,
+    referrer: RefNode,
+    variables: HashMap<String, Value>,
+}
+
+///
+/// The reason a test stopped before its last statement: either a DOM exception, with the method
+/// that raised it, which may be expected by an enclosing `assertDOMException`, or the final
+/// outcome of the test.
+///
+#[derive(Debug)]
+enum Interrupt {
+    Exception(Error, String),
+    Stop(TestOutcome),
+}
+
+type Outcome<T> = StdResult<T, Interrupt>;
+
+///
+/// The value of a test variable, or literal.
+///
+#[derive(Clone, Debug)]
+enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    String(String),
+    Node(RefNode),
+    NodeList(NodeList),
+    NamedNodeMap(NamedNodeMap),
+    /// A list, if ordered, or collection of values declared by the test.
+    Collection(Vec<Value>, bool),
+    /// The implementation of a document, or the default implementation.
+    Implementation(Option<RefNode>),
+}
+
+///
+/// A live `NodeList`, the nodes are computed each time the list is used.
+///
+#[derive(Clone, Debug)]
+enum NodeList {
+    Children(RefNode),
+    ByTagName(RefNode, String),
+    ByTagNameNs(RefNode, String, String),
+}
+
+///
+/// A live `NamedNodeMap`, the nodes are computed each time the map is used.
+///
+#[derive(Clone, Debug)]
+enum NamedNodeMap {
+    Attributes(RefNode),
+    Entities(RefNode),
+    Notations(RefNode),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn error<T>(message: String) -> Outcome<T> {
+    Err(Interrupt::Stop(TestOutcome::Error(message)))
+}
+
+fn unsupported<T>(message: String) -> Outcome<T> {
+    Err(Interrupt::Stop(TestOutcome::Unsupported(message)))
+}
+
+///
+/// The text of the attribute `name` of a statement; `Element::get_attribute` is not used as it
+/// returns the escaped value.
+///
+fn attribute(element: &RefNode, name: &str) -> Option<String> {
+    as_element(element)
+        .ok()
+        .and_then(|element| element.get_attribute_node(name))
+        .map(|attribute| text_of(&attribute))
+}
+
+fn elements(parent: &RefNode) -> Vec<RefNode> {
+    parent
+        .child_nodes()
+        .into_iter()
+        .filter(|node| node.node_type() == NodeType::Element)
+        .collect()
+}
+
+fn text_of(element: &RefNode) -> String {
+    element
+        .child_nodes()
+        .iter()
+        .filter_map(|node| node.node_value())
+        .collect()
+}
+
+fn ignore_case(step: &RefNode) -> bool {
+    matches!(
+        attribute(step, "ignoreCase").as_deref(),
+        Some("true") | Some("auto")
+    )
+}
+
+fn has_local_name(node_type: &NodeType) -> bool {
+    *node_type == NodeType::Element || *node_type == NodeType::Attribute
+}
+
+fn nth(nodes: Vec<RefNode>, index: i64) -> Option<RefNode> {
+    if index < 0 {
+        None
+    } else {
+        nodes.into_iter().nth(index as usize)
+    }
+}
+
+fn optional_node(node: Option<RefNode>) -> Value {
+    node.map_or(Value::Null, Value::Node)
+}
+
+fn optional_string(value: Option<String>) -> Value {
+    value.map_or(Value::Null, Value::String)
+}
+
+fn values_equal(lhs: &Value, rhs: &Value, ignore_case: bool) -> bool {
+    match (lhs, rhs) {
+        (Value::Null, Value::Null) => true,
+        (Value::Bool(lhs), Value::Bool(rhs)) => lhs == rhs,
+        (Value::Int(lhs), Value::Int(rhs)) => lhs == rhs,
+        (Value::String(lhs), Value::String(rhs)) if ignore_case => {
+            lhs.to_lowercase() == rhs.to_lowercase()
+        }
+        (Value::String(lhs), Value::String(rhs)) => lhs == rhs,
+        (Value::Node(lhs), Value::Node(rhs)) => lhs == rhs,
+        (Value::Implementation(_), Value::Implementation(_)) => true,
+        _ => match (lhs.items(), rhs.items()) {
+            (Some(lhs_items), Some(rhs_items)) => {
+                let ordered = !matches!(lhs, Value::Collection(_, false))
+                    && !matches!(rhs, Value::Collection(_, false));
+                lhs_items.len() == rhs_items.len()
+                    && if ordered {
+                        lhs_items
+                            .iter()
+                            .zip(rhs_items.iter())
+                            .all(|(lhs, rhs)| values_equal(lhs, rhs, ignore_case))
+                    } else {
+                        let mut unmatched = rhs_items;
+                        lhs_items.iter().all(|lhs| {
+                            match unmatched
+                                .iter()
+                                .position(|rhs| values_equal(lhs, rhs, ignore_case))
+                            {
+                                Some(index) => {
+                                    let _safe_to_ignore = unmatched.remove(index);
+                                    true
+                                }
+                                None => false,
+                            }
+                        })
+                    }
+            }
+            _ => false,
+        },
+    }
+}
+
+fn values_same(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Node(lhs), Value::Node(rhs)) => lhs == rhs,
+        _ => values_equal(lhs, rhs, false),
+    }
+}
+
+///
+/// The DOMTS name of the `ExceptionCode` corresponding to `e`.
+///
+fn exception_name(e: &Error) -> &'static str {
+    match e {
+        Error::IndexSize => "INDEX_SIZE_ERR",
+        Error::StringSize => "DOMSTRING_SIZE_ERR",
+        Error::HierarchyRequest => "HIERARCHY_REQUEST_ERR",
+        Error::WrongDocument => "WRONG_DOCUMENT_ERR",
+        Error::InvalidCharacter => "INVALID_CHARACTER_ERR",
+        Error::NoDataAllowed => "NO_DATA_ALLOWED_ERR",
+        Error::NoModificationAllowed => "NO_MODIFICATION_ALLOWED_ERR",
+        Error::NotFound => "NOT_FOUND_ERR",
+        Error::NotSupported => "NOT_SUPPORTED_ERR",
+        Error::InUseAttribute => "INUSE_ATTRIBUTE_ERR",
+        Error::InvalidState => "INVALID_STATE_ERR",
+        Error::Syntax => "SYNTAX_ERR",
+        Error::InvalidModification => "INVALID_MODIFICATION_ERR",
+        Error::Namespace => "NAMESPACE_ERR",
+        Error::InvalidAccess => "INVALID_ACCESS_ERR",
+    }
+}
@@ -17,6 +17,12 @@ use crate::{make_is_as_functions, make_ref_type};
 
 make_ref_type!(RefDocumentDecl, MutRefDocumentDecl, DocumentDecl);
 
+make_ref_type!(
+    RefDocumentTypeDecl,
+    MutRefDocumentTypeDecl,
+    DocumentTypeDecl
+);
+
 make_ref_type!(RefNamespaced, Namespaced);
 pub(crate) type MutRefNamespaced<'a> = &'a mut dyn MutNamespaced<NodeRef = RefNode>;
 
@@ -33,6 +39,15 @@ make_is_as_functions!(
     MutRefDocumentDecl
 );
 
+make_is_as_functions!(
+    is_document_type_decl,
+    NodeType::DocumentType,
+    as_document_type_decl,
+    RefDocumentTypeDecl,
+    as_document_type_decl_mut,
+    MutRefDocumentTypeDecl
+);
+
 make_is_as_functions!(
     is_element_namespaced,
     NodeType::Element,
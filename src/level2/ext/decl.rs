@@ -50,6 +50,22 @@ pub struct XmlDecl {
     standalone: Option<bool>,
 }
 
+///
+/// Determines where a comment or processing instruction added to the document prolog is placed
+/// relative to the document type declaration.
+///
+/// ```text
+/// prolog ::= XMLDecl Misc* (doctypedecl Misc*)?
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PrologPosition {
+    /// Between the XML declaration and the document type declaration.
+    BeforeDocType,
+    /// Between the document type declaration and the document element.
+    AfterDocType,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
@@ -57,22 +73,18 @@ pub struct XmlDecl {
 pub(crate) const ENCODING_SEP_CHAR: char = '-';
 
 fn is_encoding_start_char(c: char) -> bool {
-    (c >= 'A' && c <= 'Z') || (c >= 'a' && c <= 'z')
+    c.is_ascii_uppercase() || c.is_ascii_lowercase()
 }
 
 fn is_encoding_rest_char(c: char) -> bool {
-    (c >= 'A' && c <= 'Z')
-        || (c >= 'a' && c <= 'z')
-        || (c >= '0' && c <= '9')
-        || c == '.'
-        || c == '_'
+    c.is_ascii_uppercase() || c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '_'
 }
 
 fn is_encoding_sub_string(s: &str) -> bool {
     s.chars().all(is_encoding_rest_char)
 }
 
-fn is_encoding(s: &str) -> bool {
+pub(crate) fn is_encoding(s: &str) -> bool {
     !s.is_empty()
         && s.starts_with(is_encoding_start_char)
         && s[1..].split(ENCODING_SEP_CHAR).all(is_encoding_sub_string)
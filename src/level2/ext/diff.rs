@@ -0,0 +1,312 @@
+/*!
+Provides a line-based difference between two trees, and a renderer that formats it as a unified
+diff, optionally colored with ANSI escape sequences, for tools that present document changes to
+reviewers.
+
+Both trees are first written in the pretty-printed, normalized, form produced by
+[`normalize_for_diff`](../normalize/fn.normalize_for_diff.html), so that the difference is aligned
+to the elements of the document and is not affected by attribute order, insignificant white space,
+or the choice of namespace prefixes. The edit script, the sequence of
+[`DiffLine`](enum.DiffLine.html) values returned by [`diff_lines`](fn.diff_lines.html), is a
+longest common subsequence of the two sets of lines; [`render_diff`](fn.render_diff.html) groups
+its changes into hunks with the requested number of lines of context.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::diff::{render_diff, DiffOptions};
+
+let old_node = xml_dom::parser::read_xml(r#"<order><item sku="1"/><item sku="2"/></order>"#).unwrap();
+let new_node = xml_dom::parser::read_xml(r#"<order><item sku="1"/><item sku="3"/></order>"#).unwrap();
+
+let options = DiffOptions::default();
+assert_eq!(
+    render_diff(&old_node, &new_node, &options).unwrap(),
+    r#"--- old
++++ new
+@@ -1,4 +1,4 @@
+ <order>
+   <item sku="1"></item>
+-  <item sku="2"></item>
++  <item sku="3"></item>
+ </order>
+"#
+);
+# }
+```
+*/
+
+use crate::level2::ext::normalize::{normalize_for_diff, NormalizeOptions};
+use crate::level2::node_impl::RefNode;
+use crate::shared::error::Result;
+use std::fmt::Write;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single line of an edit script, produced by [`diff_lines`](fn.diff_lines.html).
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    /// A line present, unchanged, in both trees.
+    Context(String),
+    /// A line present only in the old tree.
+    Removed(String),
+    /// A line present only in the new tree.
+    Added(String),
+}
+
+///
+/// Options that control the behavior of [`diff_lines`](fn.diff_lines.html) and
+/// [`render_diff`](fn.render_diff.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffOptions {
+    /// The options used to write each tree before comparison.
+    pub normalize: NormalizeOptions,
+    /// The number of unchanged lines shown around each change, the default is `3`.
+    pub context: usize,
+    /// If `true` the output is colored using ANSI escape sequences, the default is `false`.
+    pub color: bool,
+    /// The label written in the header for the old tree, the default is `old`.
+    pub old_label: String,
+    /// The label written in the header for the new tree, the default is `new`.
+    pub new_label: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the edit script that transforms the normalized form of `old` into that of `new`,
+/// including every unchanged line.
+///
+pub fn diff_lines(old: &RefNode, new: &RefNode, options: &DiffOptions) -> Result<Vec<DiffLine>> {
+    let old_text = normalize_for_diff(old, &options.normalize)?;
+    let new_text = normalize_for_diff(new, &options.normalize)?;
+    let old_lines: Vec<&str> = old_text.lines().collect();
+    let new_lines: Vec<&str> = new_text.lines().collect();
+    Ok(edit_script(&old_lines, &new_lines))
+}
+
+///
+/// Render the difference between `old` and `new` as a unified diff, returning an empty string if
+/// there are no differences. Each line, including the last, is terminated by `\n`.
+///
+pub fn render_diff(old: &RefNode, new: &RefNode, options: &DiffOptions) -> Result<String> {
+    let script = diff_lines(old, new, options)?;
+    let mut buffer = String::new();
+    let hunks = hunks(&script, options.context);
+    if hunks.is_empty() {
+        return Ok(buffer);
+    }
+    let (bold, red, green, cyan, reset) = if options.color {
+        (ANSI_BOLD, ANSI_RED, ANSI_GREEN, ANSI_CYAN, ANSI_RESET)
+    } else {
+        ("", "", "", "", "")
+    };
+    let _safe_to_ignore = writeln!(buffer, "{}--- {}{}", bold, options.old_label, reset);
+    let _safe_to_ignore = writeln!(buffer, "{}+++ {}{}", bold, options.new_label, reset);
+    for hunk in hunks {
+        let _safe_to_ignore = writeln!(
+            buffer,
+            "{}@@ -{} +{} @@{}",
+            cyan,
+            hunk_range(hunk.old_start, hunk.old_length),
+            hunk_range(hunk.new_start, hunk.new_length),
+            reset
+        );
+        for line in &script[hunk.start..hunk.end] {
+            let _safe_to_ignore = match line {
+                DiffLine::Context(text) => writeln!(buffer, " {}", text),
+                DiffLine::Removed(text) => writeln!(buffer, "{}-{}{}", red, text, reset),
+                DiffLine::Added(text) => writeln!(buffer, "{}+{}{}", green, text, reset),
+            };
+        }
+    }
+    Ok(buffer)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            normalize: Default::default(),
+            context: DEFAULT_CONTEXT,
+            color: false,
+            old_label: DEFAULT_OLD_LABEL.to_string(),
+            new_label: DEFAULT_NEW_LABEL.to_string(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const DEFAULT_CONTEXT: usize = 3;
+const DEFAULT_OLD_LABEL: &str = "old";
+const DEFAULT_NEW_LABEL: &str = "new";
+
+const ANSI_BOLD: &str = "\u{1b}[1m";
+const ANSI_RED: &str = "\u{1b}[31m";
+const ANSI_GREEN: &str = "\u{1b}[32m";
+const ANSI_CYAN: &str = "\u{1b}[36m";
+const ANSI_RESET: &str = "\u{1b}[0m";
+
+///
+/// A range of the edit script, `start..end`, and the one-based line ranges it covers in each
+/// tree.
+///
+#[derive(Debug)]
+struct Hunk {
+    start: usize,
+    end: usize,
+    old_start: usize,
+    old_length: usize,
+    new_start: usize,
+    new_length: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns an edit script for `old` and `new` built from a longest common subsequence; common
+/// leading and trailing lines are removed before the subsequence is computed.
+///
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(lhs, rhs)| lhs == rhs)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(lhs, rhs)| lhs == rhs)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // lengths[i][j] is the length of the longest common subsequence of old_middle[i..] and
+    // new_middle[j..].
+    let columns = new_middle.len() + 1;
+    let mut lengths = vec![0usize; (old_middle.len() + 1) * columns];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i * columns + j] = if old_middle[i] == new_middle[j] {
+                lengths[(i + 1) * columns + j + 1] + 1
+            } else {
+                lengths[(i + 1) * columns + j].max(lengths[i * columns + j + 1])
+            };
+        }
+    }
+
+    let mut script: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Context(line.to_string()))
+        .collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            script.push(DiffLine::Context(old_middle[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j == new_middle.len()
+            || (i < old_middle.len()
+                && lengths[(i + 1) * columns + j] >= lengths[i * columns + j + 1])
+        {
+            script.push(DiffLine::Removed(old_middle[i].to_string()));
+            i += 1;
+        } else {
+            script.push(DiffLine::Added(new_middle[j].to_string()));
+            j += 1;
+        }
+    }
+    script.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Context(line.to_string())),
+    );
+    script
+}
+
+///
+/// Group the changes in `script` into hunks, each including up to `context` unchanged lines
+/// before and after; changes separated by no more than twice `context` unchanged lines share a
+/// hunk.
+///
+fn hunks(script: &[DiffLine], context: usize) -> Vec<Hunk> {
+    let changes: Vec<usize> = script
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Context(_)))
+        .map(|(index, _)| index)
+        .collect();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for index in changes {
+        let start = index.saturating_sub(context);
+        let end = (index + 1 + context).min(script.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let (mut old_line, mut new_line, mut position) = (0, 0, 0);
+    for (start, end) in ranges {
+        for line in &script[position..start] {
+            advance(line, &mut old_line, &mut new_line);
+        }
+        let (old_start, new_start) = (old_line, new_line);
+        for line in &script[start..end] {
+            advance(line, &mut old_line, &mut new_line);
+        }
+        hunks.push(Hunk {
+            start,
+            end,
+            old_start,
+            old_length: old_line - old_start,
+            new_start,
+            new_length: new_line - new_start,
+        });
+        position = end;
+    }
+    hunks
+}
+
+fn advance(line: &DiffLine, old_line: &mut usize, new_line: &mut usize) {
+    match line {
+        DiffLine::Context(_) => {
+            *old_line += 1;
+            *new_line += 1;
+        }
+        DiffLine::Removed(_) => *old_line += 1,
+        DiffLine::Added(_) => *new_line += 1,
+    }
+}
+
+///
+/// Format a zero-based `start` and `length` as a unified diff range; an empty range is given the
+/// line number before it.
+///
+fn hunk_range(start: usize, length: usize) -> String {
+    match length {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, length),
+    }
+}
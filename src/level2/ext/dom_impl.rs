@@ -2,19 +2,18 @@
 This module implements extended capabilities but not specified by the DOM Core.
 */
 
-use crate::level2::dom_impl::Implementation;
+use crate::level2::dom_impl::{Implementation, THIS_IMPLEMENTATION};
 use crate::level2::ext::traits::DOMImplementation;
-use crate::level2::node_impl::{NodeImpl, RefNode};
-use crate::shared::error::Result;
+use crate::level2::node_impl::{Extension, NodeImpl, RefNode};
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
 use crate::shared::name::Name;
+use std::rc::Rc;
 use std::str::FromStr;
 
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
-const THIS_IMPLEMENTATION: &'static dyn DOMImplementation<NodeRef = RefNode> = &Implementation {};
-
 ///
 /// Return a reference to an instance of this `DOMImplementation` implementation.
 ///
@@ -38,10 +37,37 @@ pub fn get_implementation_ext() -> &'static dyn DOMImplementation<NodeRef = RefN
 }
 
 ///
-/// Required to create instances of the [`Entity`](../trait.Entity.html) extended interface.
+/// Return the [`Implementation`](../../dom_impl/struct.Implementation.html), including its
+/// configuration, associated with a document; this is the same object returned as a
+/// `DOMImplementation` by `Document::implementation`.
 ///
 /// Rather than add a non-standard member to the [`Document`](../trait.Document.html) trait
-/// this function takes a `Document` as the first parameter.
+/// this function takes a `Document` as the parameter.
+///
+/// **Exceptions**
+///
+/// * `INVALID_STATE_ERR`: Raised if `document` is not a `Document` node.
+///
+pub fn document_implementation(document: &RefNode) -> Result<Rc<Implementation>> {
+    let ref_document = document.borrow();
+    if let Extension::Document {
+        i_implementation, ..
+    } = &ref_document.i_extension
+    {
+        Ok(i_implementation.clone())
+    } else {
+        warn!("{}", MSG_INVALID_NODE_TYPE);
+        Err(Error::InvalidState)
+    }
+}
+
+///
+/// Required to create instances of the [`Notation`](../trait.Notation.html) extended interface.
+///
+/// Rather than add a non-standard member to the [`Document`](../trait.Document.html) trait
+/// this function takes a `Document` as the first parameter. Note that the notation returned is
+/// not declared by any document type, to create a notation and add it to a document type use
+/// [`DocumentTypeDecl::create_notation`](../trait.DocumentTypeDecl.html#tymethod.create_notation).
 ///
 pub fn create_notation(
     owner_document: RefNode,
@@ -74,7 +100,36 @@ pub fn create_entity(
 }
 
 ///
-/// Required to create instances of the [`Notation`](../trait.Notation.html) Extended interface.
+/// Required to create unparsed instances of the [`Entity`](../trait.Entity.html) extended
+/// interface, `<!ENTITY name SYSTEM "system_id" NDATA notation_name>`.
+///
+/// Rather than add a non-standard member to the [`Document`](../trait.Document.html) trait
+/// this function takes a `Document` as the first parameter. Note that the entity returned is
+/// not declared by any document type, and so the notation it names is not checked, to create an
+/// unparsed entity and add it to a document type use
+/// [`DocumentTypeDecl::create_unparsed_entity`](../trait.DocumentTypeDecl.html#tymethod.create_unparsed_entity).
+///
+pub fn create_unparsed_entity(
+    owner_document: RefNode,
+    name: &str,
+    public_id: Option<&str>,
+    system_id: &str,
+    notation_name: &str,
+) -> Result<RefNode> {
+    let name = Name::from_str(name)?;
+    let _safe_to_ignore = Name::from_str(notation_name)?;
+    let node_impl = NodeImpl::new_unparsed_entity(
+        Some(owner_document.downgrade()),
+        name,
+        public_id,
+        system_id,
+        notation_name,
+    );
+    Ok(RefNode::new(node_impl))
+}
+
+///
+/// Required to create instances of the [`Entity`](../trait.Entity.html) extended interface.
 ///
 /// Rather than add a non-standard member to the [`Document`](../trait.Document.html) trait
 /// this function takes a `Document` as the first parameter.
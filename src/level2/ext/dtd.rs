@@ -0,0 +1,1083 @@
+/*!
+This module provides support types for the entities, notations, parameter entities, and
+conditional sections of a document type's internal subset, managed by the
+[`DocumentTypeDecl`](../trait.DocumentTypeDecl.html) trait.
+
+When a document is parsed, or built from SAX events, the internal subset is split so that each
+general entity declaration becomes an `Entity` node in the document type's `entities` map, each
+notation declaration a `Notation` node in its `notations` map, each parameter entity declaration
+an `Entity` node in its `parameter_entities` map, and each conditional section becomes a
+[`ConditionalSection`](struct.ConditionalSection.html) in its `conditional_sections` list; a
+conditional section whose keyword is a parameter entity reference, such as `<![%draft;[ ... ]]>`,
+keeps the name of the entity so that it can be resolved with
+[`ConditionalSection::is_included`](struct.ConditionalSection.html#method.is_included). Only the
+first declaration of each entity, or notation, is added to its map, later declarations, those
+that are not well-formed, and all other markup, remain in the string returned by
+`internal_subset`.
+
+A general entity declared with a value, its replacement text, also has the nodes that text is
+parsed to as its children, returned by `child_nodes`, once the document type has an owner
+document. Parsing markup in the replacement text requires the `quick_parser` feature; without it
+only replacement text that contains no markup or references is added, as a single text node.
+
+When the document type is serialized the general entities are written first, then the parameter
+entities and notations, then the conditional sections in their original order, and finally the
+remaining internal subset.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::ext::dtd::{ConditionalKeyword, ConditionalSection};
+use std::str::FromStr;
+
+let implementation = get_implementation();
+let mut doc_type = implementation.create_document_type("doc", None, Some("doc.dtd")).unwrap();
+let _safe_to_ignore = doc_type.create_parameter_entity("draft", "INCLUDE").unwrap();
+doc_type
+    .add_conditional_section(
+        ConditionalSection::new(
+            ConditionalKeyword::Reference(Name::from_str("draft").unwrap()),
+            "<!ENTITY status \"draft\">",
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+let sections = doc_type.conditional_sections();
+assert_eq!(sections[0].is_included(&doc_type), Some(true));
+assert_eq!(
+    doc_type.to_string(),
+    "<!DOCTYPE doc SYSTEM \"doc.dtd\"[<!ENTITY % draft \"INCLUDE\"><![%draft;[<!ENTITY status \"draft\">]]>]>"
+);
+```
+*/
+
+use crate::level2::convert::as_document;
+use crate::level2::ext::traits::DocumentTypeDecl;
+use crate::level2::node_impl::{Extension, NodeImpl, RefNode, WeakRefNode};
+use crate::level2::traits::Node;
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_CONDITIONAL_SECTION, MSG_INVALID_REPLACEMENT_TEXT,
+};
+use crate::shared::name::Name;
+use crate::shared::syntax::{
+    XML_ATTLIST_END, XML_ATTLIST_FIXED, XML_ATTLIST_ID, XML_ATTLIST_IMPLIED, XML_ATTLIST_NOTATION,
+    XML_ATTLIST_START, XML_COMMENT_START, XML_CONDITIONAL_END, XML_CONDITIONAL_IGNORE,
+    XML_CONDITIONAL_INCLUDE, XML_CONDITIONAL_START, XML_DOCTYPE_PUBLIC, XML_DOCTYPE_SYSTEM,
+    XML_ELEMENT_DECL_END, XML_ELEMENT_DECL_START, XML_ENTITY_END, XML_ENTITY_NOTATION,
+    XML_ENTITY_PARAMETER, XML_ENTITY_START, XML_NOTATION_END, XML_NOTATION_START,
+    XML_PE_REFERENCE_END, XML_PE_REFERENCE_START, XML_PI_START,
+};
+use crate::shared::text::is_xml_space;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The keyword of a conditional section, which determines whether its content is included.
+///
+/// ```ebnf
+/// includeSect  ::= '<![' S? 'INCLUDE' S? '[' extSubsetDecl ']]>'
+/// ignoreSect   ::= '<![' S? 'IGNORE' S? '[' ignoreSectContents* ']]>'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConditionalKeyword {
+    /// The literal keyword `INCLUDE`.
+    Include,
+    /// The literal keyword `IGNORE`.
+    Ignore,
+    /// A parameter entity reference, such as `%draft;`, whose replacement text is the keyword.
+    Reference(Name),
+}
+
+///
+/// A conditional section of a document type declaration; the content is held as written, and
+/// may itself contain nested conditional sections.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConditionalSection {
+    keyword: ConditionalKeyword,
+    content: String,
+}
+
+///
+/// An attribute definition from an attribute-list declaration in the internal subset, such as
+/// `<!ATTLIST chapter key ID #REQUIRED>`; only the first definition of an attribute for an
+/// element type is binding. An attribute with a default value, given as a literal or after
+/// `#FIXED`, is added to each new element of the type, and is not `specified`.
+///
+/// ```ebnf
+/// AttlistDecl  ::= '<!ATTLIST' S Name AttDef* S? '>'
+/// AttDef       ::= S Name S AttType S DefaultDecl
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeDefinition {
+    element_name: Name,
+    attribute_name: Name,
+    attribute_type: String,
+    default_value: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns `true` if `node` is an `Entity` node that was declared as a parameter entity, else
+/// `false`.
+///
+pub fn is_parameter_entity(node: &RefNode) -> bool {
+    matches!(
+        node.borrow().i_extension,
+        Extension::Entity {
+            i_parameter: true,
+            ..
+        }
+    )
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ConditionalKeyword {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ConditionalKeyword::Include => write!(f, "{}", XML_CONDITIONAL_INCLUDE),
+            ConditionalKeyword::Ignore => write!(f, "{}", XML_CONDITIONAL_IGNORE),
+            ConditionalKeyword::Reference(name) => write!(
+                f,
+                "{}{}{}",
+                XML_PE_REFERENCE_START, name, XML_PE_REFERENCE_END
+            ),
+        }
+    }
+}
+
+impl FromStr for ConditionalKeyword {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let keyword = s.trim_matches(is_xml_space);
+        if keyword == XML_CONDITIONAL_INCLUDE {
+            Ok(ConditionalKeyword::Include)
+        } else if keyword == XML_CONDITIONAL_IGNORE {
+            Ok(ConditionalKeyword::Ignore)
+        } else if let Some(name) = keyword
+            .strip_prefix(XML_PE_REFERENCE_START)
+            .and_then(|keyword| keyword.strip_suffix(XML_PE_REFERENCE_END))
+        {
+            Ok(ConditionalKeyword::Reference(Name::from_str(name)?))
+        } else {
+            warn!("{}: '{}'", MSG_INVALID_CONDITIONAL_SECTION, s);
+            Err(Error::Syntax)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ConditionalSection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}{}[{}{}",
+            XML_CONDITIONAL_START, self.keyword, self.content, XML_CONDITIONAL_END
+        )
+    }
+}
+
+impl FromStr for ConditionalSection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match parse_conditional_section(s) {
+            Some((section, length)) if length == s.len() => Ok(section),
+            _ => {
+                warn!("{}: '{}'", MSG_INVALID_CONDITIONAL_SECTION, s);
+                Err(Error::Syntax)
+            }
+        }
+    }
+}
+
+impl ConditionalSection {
+    ///
+    /// Construct a new conditional section.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `content` contains a `]]>` that does not close a nested
+    ///   conditional section, or a nested section that is not closed.
+    ///
+    pub fn new(keyword: ConditionalKeyword, content: &str) -> Result<Self> {
+        if section_end(content, 0) != Some((content.len(), 0)) {
+            warn!("{}: '{}'", MSG_INVALID_CONDITIONAL_SECTION, content);
+            return Err(Error::Syntax);
+        }
+        Ok(Self {
+            keyword,
+            content: content.to_string(),
+        })
+    }
+
+    ///
+    /// Return the keyword of this section.
+    ///
+    pub fn keyword(&self) -> &ConditionalKeyword {
+        &self.keyword
+    }
+
+    ///
+    /// Return the content of this section, as written.
+    ///
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    ///
+    /// Returns `Some(true)` if the content of this section is included, and `Some(false)` if it
+    /// is ignored. A keyword that is a parameter entity reference is resolved using the
+    /// parameter entities declared by `doc_type`; if the entity is not declared, is external, or
+    /// its replacement text is not a keyword, `None` is returned.
+    ///
+    pub fn is_included(&self, doc_type: &RefNode) -> Option<bool> {
+        match &self.keyword {
+            ConditionalKeyword::Include => Some(true),
+            ConditionalKeyword::Ignore => Some(false),
+            ConditionalKeyword::Reference(name) => {
+                let value = doc_type.parameter_entities().get(name)?.node_value()?;
+                match ConditionalKeyword::from_str(&value).ok()? {
+                    ConditionalKeyword::Include => Some(true),
+                    ConditionalKeyword::Ignore => Some(false),
+                    ConditionalKeyword::Reference(_) => None,
+                }
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for AttributeDefinition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} {} ",
+            XML_ATTLIST_START, self.element_name, self.attribute_name, self.attribute_type
+        )?;
+        match &self.default_value {
+            Some(default_value) if default_value.contains('"') => write!(f, "'{}'", default_value)?,
+            Some(default_value) => write!(f, "\"{}\"", default_value)?,
+            None => write!(f, "{}", XML_ATTLIST_IMPLIED)?,
+        }
+        write!(f, "{}", XML_ATTLIST_END)
+    }
+}
+
+impl AttributeDefinition {
+    ///
+    /// Construct a new attribute definition; enumerated types are written with their
+    /// parentheses, as in `(left|right)`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if either name contains an illegal character.
+    ///
+    pub fn new(element_name: &str, attribute_name: &str, attribute_type: &str) -> Result<Self> {
+        Ok(Self {
+            element_name: Name::from_str(element_name)?,
+            attribute_name: Name::from_str(attribute_name)?,
+            attribute_type: attribute_type.to_string(),
+            default_value: None,
+        })
+    }
+
+    ///
+    /// Return this definition with the default value `default_value`, as written but without its
+    /// quotes.
+    ///
+    pub fn with_default_value(self, default_value: &str) -> Self {
+        Self {
+            default_value: Some(default_value.to_string()),
+            ..self
+        }
+    }
+
+    ///
+    /// Return the name of the element type this attribute is defined for.
+    ///
+    pub fn element_name(&self) -> &Name {
+        &self.element_name
+    }
+
+    ///
+    /// Return the name of the attribute.
+    ///
+    pub fn attribute_name(&self) -> &Name {
+        &self.attribute_name
+    }
+
+    ///
+    /// Return the type of the attribute, as written.
+    ///
+    pub fn attribute_type(&self) -> &str {
+        &self.attribute_type
+    }
+
+    ///
+    /// Return the default value of the attribute, if it has one.
+    ///
+    pub fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref()
+    }
+
+    ///
+    /// Returns `true` if the attribute is declared with the type `ID`.
+    ///
+    pub fn is_id(&self) -> bool {
+        self.attribute_type == XML_ATTLIST_ID
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum SubsetItem {
+    ParameterEntity {
+        name: Name,
+        value: Option<String>,
+        public_id: Option<String>,
+        system_id: Option<String>,
+    },
+    GeneralEntity {
+        name: Name,
+        value: Option<String>,
+        public_id: Option<String>,
+        system_id: Option<String>,
+        notation_name: Option<String>,
+    },
+    Notation {
+        name: Name,
+        public_id: Option<String>,
+        system_id: Option<String>,
+    },
+    Conditional(ConditionalSection),
+}
+
+///
+/// The content of a document type declaration, as read by the parsers; see `doc_type_parts`.
+///
+#[cfg(any(feature = "quick_parser", feature = "xml_rs"))]
+#[derive(Debug)]
+pub(crate) struct DocTypeParts {
+    pub(crate) name: String,
+    pub(crate) public_id: Option<String>,
+    pub(crate) system_id: Option<String>,
+    pub(crate) internal_subset: Option<String>,
+}
+
+///
+/// The element name, attribute name, attribute type, and default value of an attribute definition.
+///
+type AttributeListDeclaration = (String, String, String, Option<String>);
+
+#[cfg(feature = "quick_parser")]
+const REPLACEMENT_ELEMENT: &str = "replacement";
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Set `document_node` as the owner, and parent, of `doc_type`, and as the owner of each of its
+/// entities and notations, where they have no owner; then add the replacement nodes of each
+/// general entity.
+///
+pub(crate) fn adopt_document_type(document_node: &RefNode, doc_type: &RefNode) {
+    let owner_document = Some(document_node.clone().downgrade());
+    let (entities, others) = {
+        let mut mut_doc_type = doc_type.borrow_mut();
+        if mut_doc_type.i_owner_document.is_none() {
+            mut_doc_type.i_owner_document = owner_document.clone();
+            mut_doc_type.i_parent_node = owner_document.clone();
+        }
+        if let Extension::DocumentType {
+            i_entities,
+            i_notations,
+            i_parameter_entities,
+            ..
+        } = &mut_doc_type.i_extension
+        {
+            let entities: Vec<RefNode> = i_entities.values().cloned().collect();
+            let others: Vec<RefNode> = i_notations
+                .values()
+                .chain(i_parameter_entities.values())
+                .cloned()
+                .collect();
+            (entities, others)
+        } else {
+            return;
+        }
+    };
+    for node in entities.iter().chain(others.iter()) {
+        let mut mut_node = node.borrow_mut();
+        if mut_node.i_owner_document.is_none() {
+            mut_node.i_owner_document = owner_document.clone();
+        }
+    }
+    for entity in &entities {
+        add_replacement_nodes(entity);
+    }
+}
+
+///
+/// Add to the general entity `entity`, if it is declared with a value and has no children, the
+/// nodes its replacement text is parsed to, owned by the entity's owner document; as the entity
+/// is read-only so are these nodes.
+///
+pub(crate) fn add_replacement_nodes(entity: &RefNode) {
+    if is_parameter_entity(entity) || entity.has_child_nodes() {
+        return;
+    }
+    let value = match &entity.borrow().i_extension {
+        Extension::Entity {
+            i_public_id: None,
+            i_system_id: None,
+            ..
+        } => entity.node_value(),
+        _ => None,
+    };
+    if let (Some(document_node), Some(value)) = (entity.owner_document(), value) {
+        let mut entity = entity.clone();
+        entity.borrow_mut().i_read_only = false;
+        for node in replacement_nodes(&document_node, &value) {
+            let _safe_to_ignore = entity.append_child(node);
+        }
+        entity.borrow_mut().set_read_only();
+    }
+}
+
+///
+/// Create a new parameter entity node; either `value` or `system_id` is expected to be present.
+///
+pub(crate) fn new_parameter_entity(
+    owner_document: Option<WeakRefNode>,
+    name: Name,
+    value: Option<&str>,
+    public_id: Option<&str>,
+    system_id: Option<&str>,
+) -> RefNode {
+    let mut node_impl = match value {
+        Some(value) => NodeImpl::new_internal_entity(owner_document, name, value),
+        None => NodeImpl::new_entity(owner_document, name, public_id, system_id),
+    };
+    if let Extension::Entity { i_parameter, .. } = &mut node_impl.i_extension {
+        *i_parameter = true;
+    }
+    RefNode::new(node_impl)
+}
+
+///
+/// Set the internal subset of `doc_type`, moving the first declaration of each entity, notation,
+/// and parameter entity, and each conditional section, into the document type's structured
+/// model; the attribute
+/// definitions of attribute-list declarations are also added to the model, but remain in the
+/// internal subset.
+///
+pub(crate) fn set_internal_subset(doc_type: &RefNode, internal_subset: Option<&str>) {
+    let owner_document = doc_type.borrow().i_owner_document.clone();
+    let definitions: Vec<AttributeDefinition> = internal_subset
+        .map(attribute_list_declarations)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(
+            |(element_name, attribute_name, attribute_type, default_value)| {
+                let definition =
+                    AttributeDefinition::new(&element_name, &attribute_name, &attribute_type)
+                        .ok()?;
+                Some(match default_value {
+                    Some(default_value) => definition.with_default_value(&default_value),
+                    None => definition,
+                })
+            },
+        )
+        .collect();
+    let (items, remainder) = match internal_subset {
+        None => (Vec::new(), None),
+        Some(internal_subset) => {
+            let (items, remainder) = split_internal_subset(internal_subset);
+            if items.is_empty() || !remainder.chars().all(is_xml_space) {
+                (items, Some(remainder))
+            } else {
+                (items, None)
+            }
+        }
+    };
+    let mut general_entities: Vec<RefNode> = Vec::new();
+    let mut mut_doc_type = doc_type.borrow_mut();
+    if let Extension::DocumentType {
+        i_entities,
+        i_notations,
+        i_parameter_entities,
+        i_conditional_sections,
+        i_attribute_definitions,
+        i_internal_subset,
+        ..
+    } = &mut mut_doc_type.i_extension
+    {
+        *i_attribute_definitions = definitions;
+        for item in items {
+            match item {
+                SubsetItem::ParameterEntity {
+                    name,
+                    value,
+                    public_id,
+                    system_id,
+                } => {
+                    let entity_node = new_parameter_entity(
+                        owner_document.clone(),
+                        name.clone(),
+                        value.as_deref(),
+                        public_id.as_deref(),
+                        system_id.as_deref(),
+                    );
+                    let _safe_to_ignore = i_parameter_entities.insert(name, entity_node);
+                }
+                SubsetItem::GeneralEntity {
+                    name,
+                    value,
+                    public_id,
+                    system_id,
+                    notation_name,
+                } if !i_entities.contains_key(&name) => {
+                    let node_impl = match (value, system_id, notation_name) {
+                        (Some(value), _, _) => NodeImpl::new_internal_entity(
+                            owner_document.clone(),
+                            name.clone(),
+                            &value,
+                        ),
+                        (None, Some(system_id), Some(notation_name)) => {
+                            NodeImpl::new_unparsed_entity(
+                                owner_document.clone(),
+                                name.clone(),
+                                public_id.as_deref(),
+                                &system_id,
+                                &notation_name,
+                            )
+                        }
+                        (None, system_id, _) => NodeImpl::new_entity(
+                            owner_document.clone(),
+                            name.clone(),
+                            public_id.as_deref(),
+                            system_id.as_deref(),
+                        ),
+                    };
+                    let entity_node = RefNode::new(node_impl);
+                    general_entities.push(entity_node.clone());
+                    let _safe_to_ignore = i_entities.insert(name, entity_node);
+                }
+                SubsetItem::Notation {
+                    name,
+                    public_id,
+                    system_id,
+                } if !i_notations.contains_key(&name) => {
+                    let notation_node = RefNode::new(NodeImpl::new_notation(
+                        owner_document.clone(),
+                        name.clone(),
+                        public_id.as_deref(),
+                        system_id.as_deref(),
+                    ));
+                    let _safe_to_ignore = i_notations.insert(name, notation_node);
+                }
+                SubsetItem::GeneralEntity { .. } | SubsetItem::Notation { .. } => {}
+                SubsetItem::Conditional(section) => i_conditional_sections.push(section),
+            }
+        }
+        *i_internal_subset = remainder;
+    }
+    drop(mut_doc_type);
+    for entity in &general_entities {
+        add_replacement_nodes(entity);
+    }
+}
+
+///
+/// Returns the element name, attribute name, and attribute type of each attribute definition in
+/// the attribute-list declarations of `internal_subset`; declarations within conditional sections
+/// are not included. Enumerated types are returned as written, including their parentheses.
+///
+/// ```ebnf
+/// AttlistDecl  ::= '<!ATTLIST' S Name AttDef* S? '>'
+/// AttDef       ::= S Name S AttType S DefaultDecl
+/// ```
+///
+pub(crate) fn attribute_list_types(internal_subset: &str) -> Vec<(String, String, String)> {
+    attribute_list_declarations(internal_subset)
+        .into_iter()
+        .map(|(element, attribute, attribute_type, _)| (element, attribute, attribute_type))
+        .collect()
+}
+
+///
+/// As [`attribute_list_types`](fn.attribute_list_types.html), also returning the default value
+/// of each attribute definition, without its quotes, if it has one.
+///
+fn attribute_list_declarations(internal_subset: &str) -> Vec<AttributeListDeclaration> {
+    let mut declarations: Vec<AttributeListDeclaration> = Vec::new();
+    for_each_declaration(internal_subset, |declaration| {
+        if let Some(body) = declaration
+            .strip_prefix(XML_ATTLIST_START)
+            .and_then(|body| body.strip_suffix(XML_ATTLIST_END))
+        {
+            let mut tokens = declaration_tokens(body).into_iter();
+            if let Some(element) = tokens.next() {
+                while let (Some(attribute), Some(attribute_type)) = (tokens.next(), tokens.next()) {
+                    if attribute_type == XML_ATTLIST_NOTATION {
+                        let _safe_to_ignore = tokens.next();
+                    }
+                    let mut default_decl = tokens.next();
+                    if default_decl.as_deref() == Some(XML_ATTLIST_FIXED) {
+                        default_decl = tokens.next();
+                    }
+                    let default_value = default_decl
+                        .and_then(|literal| quoted_literal(&literal).map(|(value, _)| value));
+                    declarations.push((element.clone(), attribute, attribute_type, default_value));
+                }
+            }
+        }
+    });
+    declarations
+}
+
+///
+/// Returns the element name and content specification, as written, of each element type
+/// declaration in `internal_subset`; declarations within conditional sections are not included.
+///
+/// ```ebnf
+/// elementdecl  ::= '<!ELEMENT' S Name S contentspec S? '>'
+/// ```
+///
+pub(crate) fn element_declarations(internal_subset: &str) -> Vec<(String, String)> {
+    let mut declarations: Vec<(String, String)> = Vec::new();
+    for_each_declaration(internal_subset, |declaration| {
+        if let Some(body) = declaration
+            .strip_prefix(XML_ELEMENT_DECL_START)
+            .and_then(|body| body.strip_suffix(XML_ELEMENT_DECL_END))
+            .and_then(required_space)
+        {
+            if let Some(name_end) = body.find(is_xml_space) {
+                let content_spec = body[name_end..].trim_matches(is_xml_space);
+                declarations.push((body[..name_end].to_string(), content_spec.to_string()));
+            }
+        }
+    });
+    declarations
+}
+
+///
+/// Call `f` with the text of each markup declaration in `internal_subset`, skipping comments and
+/// processing instructions.
+///
+fn for_each_declaration(internal_subset: &str, mut f: impl FnMut(&str)) {
+    let mut rest = internal_subset;
+    while let Some(c) = rest.chars().next() {
+        let length = if rest.starts_with(XML_COMMENT_START) {
+            rest.find("-->").map_or(rest.len(), |end| end + 3)
+        } else if rest.starts_with(XML_PI_START) {
+            rest.find("?>").map_or(rest.len(), |end| end + 2)
+        } else if rest.starts_with("<!") {
+            let length = declaration_end(rest);
+            f(&rest[..length]);
+            length
+        } else {
+            c.len_utf8()
+        };
+        rest = &rest[length..];
+    }
+}
+
+///
+/// Split `text` into the parameter entity declarations and conditional sections it contains,
+/// and the remaining text. Anything that is not recognized is left in the remaining text.
+///
+fn split_internal_subset(text: &str) -> (Vec<SubsetItem>, String) {
+    let mut items: Vec<SubsetItem> = Vec::new();
+    let mut declared: HashSet<(u8, Name)> = HashSet::new();
+    let mut remainder = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let length = if rest.starts_with(XML_CONDITIONAL_START) {
+            match parse_conditional_section(rest) {
+                Some((section, length)) => {
+                    items.push(SubsetItem::Conditional(section));
+                    rest = &rest[length..];
+                    continue;
+                }
+                None => c.len_utf8(),
+            }
+        } else if rest.starts_with("<!--") {
+            rest.find("-->").map_or(rest.len(), |end| end + 3)
+        } else if rest.starts_with("<?") {
+            rest.find("?>").map_or(rest.len(), |end| end + 2)
+        } else if rest.starts_with("<!") {
+            let length = declaration_end(rest);
+            let declaration = &rest[..length];
+            let item = if declaration.starts_with(XML_NOTATION_START) {
+                parse_notation(declaration)
+            } else {
+                parse_entity(declaration)
+            };
+            let key = match &item {
+                Some(SubsetItem::ParameterEntity { name, .. }) => Some((0, name.clone())),
+                Some(SubsetItem::GeneralEntity { name, .. }) => Some((1, name.clone())),
+                Some(SubsetItem::Notation { name, .. }) => Some((2, name.clone())),
+                _ => None,
+            };
+            if let (Some(item), Some(key)) = (item, key) {
+                if declared.insert(key) {
+                    items.push(item);
+                    rest = &rest[length..];
+                    continue;
+                }
+            }
+            length
+        } else {
+            c.len_utf8()
+        };
+        remainder.push_str(&rest[..length]);
+        rest = &rest[length..];
+    }
+    (items, remainder)
+}
+
+///
+/// Parse the conditional section at the start of `text`, returning it and its length.
+///
+fn parse_conditional_section(text: &str) -> Option<(ConditionalSection, usize)> {
+    let after_start = text.strip_prefix(XML_CONDITIONAL_START)?;
+    let keyword_end = after_start.find('[')?;
+    let keyword = ConditionalKeyword::from_str(&after_start[..keyword_end]).ok()?;
+    let content_start = XML_CONDITIONAL_START.len() + keyword_end + 1;
+    let (content_end, _) = section_end(text, content_start)
+        .filter(|(end, depth)| *depth == 1 && text[*end..].starts_with(XML_CONDITIONAL_END))?;
+    Some((
+        ConditionalSection {
+            keyword,
+            content: text[content_start..content_end].to_string(),
+        },
+        content_end + XML_CONDITIONAL_END.len(),
+    ))
+}
+
+///
+/// Scan `text` from `start`, tracking the nesting of conditional sections; returns the position
+/// of the `]]>` that closes the section containing `start`, with the depth `1`, or the end of
+/// the text with the depth of any unclosed sections. `None` is returned if a section is closed
+/// that was not opened.
+///
+fn section_end(text: &str, start: usize) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut position = start;
+    loop {
+        let rest = &text[position..];
+        let open = rest.find(XML_CONDITIONAL_START);
+        let close = rest.find(XML_CONDITIONAL_END);
+        match (open, close) {
+            (Some(open), Some(close)) if open < close => {
+                depth += 1;
+                position += open + XML_CONDITIONAL_START.len();
+            }
+            (Some(open), None) => {
+                depth += 1;
+                position += open + XML_CONDITIONAL_START.len();
+            }
+            (_, Some(close)) => {
+                if depth == 0 {
+                    return if start > 0 {
+                        Some((position + close, 1))
+                    } else {
+                        None
+                    };
+                }
+                depth -= 1;
+                position += close + XML_CONDITIONAL_END.len();
+            }
+            (None, None) => return Some((text.len(), depth)),
+        }
+    }
+}
+
+///
+/// Returns the length of the markup declaration at the start of `text`, up to and including
+/// the closing `>` that is not within a quoted literal.
+///
+fn declaration_end(text: &str) -> usize {
+    let mut quote: Option<char> = None;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return index + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+///
+/// Parse an entity declaration, general or parameter, which must be the whole of `declaration`.
+///
+/// ```ebnf
+/// GEDecl       ::= '<!ENTITY' S Name S EntityDef S? '>'
+/// PEDecl       ::= '<!ENTITY' S '%' S Name S PEDef S? '>'
+/// EntityDef    ::= EntityValue | (ExternalID NDataDecl?)
+/// PEDef        ::= EntityValue | ExternalID
+/// NDataDecl    ::= S 'NDATA' S Name
+/// ```
+///
+fn parse_entity(declaration: &str) -> Option<SubsetItem> {
+    let body = declaration
+        .strip_prefix(XML_ENTITY_START)?
+        .strip_suffix(XML_ENTITY_END)?;
+    let body = required_space(body)?;
+    let (parameter, body) = match body.strip_prefix(XML_ENTITY_PARAMETER) {
+        Some(after) => (true, required_space(after)?),
+        None => (false, body),
+    };
+    let name_end = body.find(is_xml_space)?;
+    let name = Name::from_str(&body[..name_end]).ok()?;
+    let body = required_space(&body[name_end..])?;
+    let (value, external_id, rest) = match external_id(body) {
+        Some((public_id, Some(system_id), rest)) => (None, Some((public_id, system_id)), rest),
+        Some(_) => return None,
+        None => {
+            let (value, rest) = quoted_literal(body)?;
+            (Some(value), None, rest)
+        }
+    };
+    let (notation_name, rest) =
+        match required_space(rest).and_then(|after| after.strip_prefix(XML_ENTITY_NOTATION)) {
+            Some(after) if !parameter && external_id.is_some() => {
+                let after = required_space(after)?;
+                let name_end = after.find(is_xml_space).unwrap_or(after.len());
+                let notation_name = Name::from_str(&after[..name_end]).ok()?;
+                (Some(notation_name.to_string()), &after[name_end..])
+            }
+            Some(_) => return None,
+            None => (None, rest),
+        };
+    if !rest.chars().all(is_xml_space) {
+        return None;
+    }
+    let (public_id, system_id) = match external_id {
+        Some((public_id, system_id)) => (public_id, Some(system_id)),
+        None => (None, None),
+    };
+    Some(if parameter {
+        SubsetItem::ParameterEntity {
+            name,
+            value,
+            public_id,
+            system_id,
+        }
+    } else {
+        SubsetItem::GeneralEntity {
+            name,
+            value,
+            public_id,
+            system_id,
+            notation_name,
+        }
+    })
+}
+
+///
+/// Parse a notation declaration, which must be the whole of `declaration`.
+///
+/// ```ebnf
+/// NotationDecl ::= '<!NOTATION' S Name S (ExternalID | PublicID) S? '>'
+/// PublicID     ::= 'PUBLIC' S PubidLiteral
+/// ```
+///
+fn parse_notation(declaration: &str) -> Option<SubsetItem> {
+    let body = declaration
+        .strip_prefix(XML_NOTATION_START)?
+        .strip_suffix(XML_NOTATION_END)?;
+    let body = required_space(body)?;
+    let name_end = body.find(is_xml_space)?;
+    let name = Name::from_str(&body[..name_end]).ok()?;
+    let (public_id, system_id, rest) = external_id(required_space(&body[name_end..])?)?;
+    if rest.chars().all(is_xml_space) {
+        Some(SubsetItem::Notation {
+            name,
+            public_id,
+            system_id,
+        })
+    } else {
+        None
+    }
+}
+
+///
+/// Split the content of a document type declaration, the text between `<!DOCTYPE` and the final
+/// `>`, into its name, public and system identifiers, and internal subset; `None` is returned if
+/// the content is not well-formed.
+///
+/// ```ebnf
+/// doctypedecl  ::= '<!DOCTYPE' S Name (S ExternalID)? S? ('[' intSubset ']' S?)? '>'
+/// ```
+///
+#[cfg(any(feature = "quick_parser", feature = "xml_rs"))]
+pub(crate) fn doc_type_parts(text: &str) -> Option<DocTypeParts> {
+    let text = text.trim_matches(is_xml_space);
+    let name_end = text
+        .find(|c: char| is_xml_space(c) || c == '[')
+        .unwrap_or(text.len());
+    let (name, rest) = text.split_at(name_end);
+    if name.is_empty() {
+        return None;
+    }
+    let rest = rest.trim_start_matches(is_xml_space);
+    let (public_id, system_id, rest) = match external_id(rest) {
+        Some((public_id, Some(system_id), rest)) => (public_id, Some(system_id), rest),
+        Some(_) => return None,
+        None => (None, None, rest),
+    };
+    let rest = rest.trim_matches(is_xml_space);
+    let internal_subset = if rest.is_empty() {
+        None
+    } else if rest.starts_with('[') && rest.ends_with(']') {
+        Some(rest[1..rest.len() - 1].to_string())
+    } else {
+        return None;
+    };
+    Some(DocTypeParts {
+        name: name.to_string(),
+        public_id,
+        system_id,
+        internal_subset,
+    })
+}
+
+///
+/// Parse the external identifier at the start of `text`, returning the public identifier, if
+/// any, the system identifier, and the remaining text. The system identifier is optional after a
+/// public identifier, as in a notation declaration; `None` is returned if `text` does not start
+/// with `SYSTEM` or `PUBLIC`.
+///
+/// ```ebnf
+/// ExternalID   ::= 'SYSTEM' S SystemLiteral | 'PUBLIC' S PubidLiteral S SystemLiteral
+/// ```
+///
+fn external_id(text: &str) -> Option<(Option<String>, Option<String>, &str)> {
+    if let Some(after) = text.strip_prefix(XML_DOCTYPE_SYSTEM) {
+        let (system_id, rest) = quoted_literal(required_space(after)?)?;
+        Some((None, Some(system_id), rest))
+    } else if let Some(after) = text.strip_prefix(XML_DOCTYPE_PUBLIC) {
+        let (public_id, rest) = quoted_literal(required_space(after)?)?;
+        match required_space(rest).and_then(quoted_literal) {
+            Some((system_id, rest)) => Some((Some(public_id), Some(system_id), rest)),
+            None => Some((Some(public_id), None, rest)),
+        }
+    } else {
+        None
+    }
+}
+
+///
+/// Split the body of a markup declaration on white space, keeping quoted literals and
+/// parenthesized groups as single tokens.
+///
+fn declaration_tokens(text: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut rest = text.trim_start_matches(is_xml_space);
+    while let Some(c) = rest.chars().next() {
+        let length = match c {
+            '"' | '\'' => rest[1..].find(c).map_or(rest.len(), |end| end + 2),
+            '(' => rest.find(')').map_or(rest.len(), |end| end + 1),
+            _ => rest.find(is_xml_space).unwrap_or(rest.len()),
+        };
+        tokens.push(rest[..length].to_string());
+        rest = rest[length..].trim_start_matches(is_xml_space);
+    }
+    tokens
+}
+
+fn required_space(text: &str) -> Option<&str> {
+    let trimmed = text.trim_start_matches(is_xml_space);
+    if trimmed.len() < text.len() {
+        Some(trimmed)
+    } else {
+        None
+    }
+}
+
+fn quoted_literal(text: &str) -> Option<(String, &str)> {
+    let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let end = text[1..].find(quote)? + 1;
+    Some((text[1..end].to_string(), &text[end + 1..]))
+}
+
+///
+/// The nodes, owned by `document_node`, that `replacement_text` is parsed to; text that contains
+/// markup, or a reference, can only be parsed with the `quick_parser` feature, and is otherwise,
+/// or if it is not well-formed, ignored.
+///
+fn replacement_nodes(document_node: &RefNode, replacement_text: &str) -> Vec<RefNode> {
+    if replacement_text.is_empty() {
+        Vec::new()
+    } else if !replacement_text.contains(['<', '&']) {
+        match as_document(document_node) {
+            Ok(document) => vec![document.create_text_node(replacement_text)],
+            Err(_) => Vec::new(),
+        }
+    } else {
+        parse_replacement_text(document_node, replacement_text)
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+fn parse_replacement_text(document_node: &RefNode, replacement_text: &str) -> Vec<RefNode> {
+    use crate::level2::ext::workspace::import_node;
+    use crate::parser::read_events;
+    use quick_xml::Reader;
+
+    let parsed = read_events(&mut Reader::from_str(&format!(
+        "<{}>{}</{}>",
+        REPLACEMENT_ELEMENT, replacement_text, REPLACEMENT_ELEMENT
+    )));
+    let content = match parsed
+        .ok()
+        .and_then(|parsed| as_document(&parsed).ok()?.document_element())
+    {
+        Some(element) => element.child_nodes(),
+        None => {
+            warn!("{}: '{}'", MSG_INVALID_REPLACEMENT_TEXT, replacement_text);
+            return Vec::new();
+        }
+    };
+    let mut names = Default::default();
+    content
+        .iter()
+        .filter_map(|node| import_node(&mut names, document_node, node, true).ok())
+        .collect()
+}
+
+#[cfg(not(feature = "quick_parser"))]
+fn parse_replacement_text(_: &RefNode, replacement_text: &str) -> Vec<RefNode> {
+    warn!("{}: '{}'", MSG_INVALID_REPLACEMENT_TEXT, replacement_text);
+    Vec::new()
+}
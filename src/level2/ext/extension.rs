@@ -0,0 +1,168 @@
+/*!
+Provides the [`NodeExtension`](trait.NodeExtension.html) trait, implemented by applications to
+attach their own data, such as layout information or provenance, directly to a node using the
+[`NodeExtended`](../trait.NodeExtended.html) extension; rather than keeping a side table keyed by
+node that must be kept in step with the tree as it is edited.
+
+Each node holds at most one extension. When a node is copied, by `clone_node` or
+`Workspace::import_node`, the copy receives the value returned by the extension's
+[`clone_extension`](trait.NodeExtension.html#tymethod.clone_extension) method, which may choose
+not to copy the extension at all. Extensions are not written by the `Display` implementation, but
+the [`serializer`](../serializer/index.html) will write the value returned by
+[`serialize`](trait.NodeExtension.html#method.serialize) as a processing instruction preceding
+the node if
+[`WriteOptions::extension_target`](../serializer/struct.WriteOptions.html#structfield.extension_target)
+is set.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::NodeExtended;
+use xml_dom::level2::ext::extension::NodeExtension;
+use std::any::Any;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Provenance {
+    source: String,
+    line: usize,
+}
+
+impl NodeExtension for Provenance {
+    fn clone_extension(&self) -> Option<Box<dyn NodeExtension>> {
+        Some(Box::new(self.clone()))
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("order"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _no_previous = root_node.set_extension(Box::new(Provenance {
+    source: "orders.xml".to_string(),
+    line: 12,
+}));
+
+let copy_node = root_node.clone_node(false).unwrap();
+assert_eq!(copy_node.with_extension(|provenance: &Provenance| provenance.line), Some(12));
+```
+*/
+
+use crate::level2::ext::traits::NodeExtended;
+use crate::level2::node_impl::RefNode;
+use std::any::Any;
+use std::fmt::Debug;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Implemented by application types that are attached to nodes.
+///
+pub trait NodeExtension: Debug {
+    ///
+    /// Returns the extension to attach to a copy of the node this extension is attached to, or
+    /// `None` if the copy should have no extension.
+    ///
+    fn clone_extension(&self) -> Option<Box<dyn NodeExtension>>;
+
+    ///
+    /// Returns the serialized form of this extension, written by the serializer as the data of a
+    /// processing instruction; the default returns `None` and nothing is written.
+    ///
+    fn serialize(&self) -> Option<String> {
+        None
+    }
+
+    ///
+    /// Returns this extension as `Any`, so that it may be downcast to its concrete type.
+    ///
+    fn as_any(&self) -> &dyn Any;
+
+    ///
+    /// Returns this extension as mutable `Any`, so that it may be downcast to its concrete type.
+    ///
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl NodeExtended for RefNode {
+    fn set_extension(
+        &mut self,
+        extension: Box<dyn NodeExtension>,
+    ) -> Option<Box<dyn NodeExtension>> {
+        self.borrow_mut().i_user_extension.0.replace(extension)
+    }
+
+    fn take_extension(&mut self) -> Option<Box<dyn NodeExtension>> {
+        self.borrow_mut().i_user_extension.0.take()
+    }
+
+    fn has_extension(&self) -> bool {
+        self.borrow().i_user_extension.0.is_some()
+    }
+
+    fn with_extension<T: NodeExtension + 'static, R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let ref_self = self.borrow();
+        let extension = ref_self.i_user_extension.0.as_ref()?;
+        extension.as_any().downcast_ref::<T>().map(f)
+    }
+
+    fn with_extension_mut<T: NodeExtension + 'static, R>(
+        &mut self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<R> {
+        let mut mut_self = self.borrow_mut();
+        let extension = mut_self.i_user_extension.0.as_mut()?;
+        extension.as_any_mut().downcast_mut::<T>().map(f)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Clone for ExtensionSlot {
+    fn clone(&self) -> Self {
+        Self(
+            self.0
+                .as_ref()
+                .and_then(|extension| extension.clone_extension()),
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Holds the extension attached to a node, if any; cloning the slot clones the extension using
+/// its own `clone_extension` method.
+///
+#[derive(Debug, Default)]
+pub(crate) struct ExtensionSlot(pub(crate) Option<Box<dyn NodeExtension>>);
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the serialized form of the extension attached to `node`, if any.
+///
+pub(crate) fn serialized_extension(node: &RefNode) -> Option<String> {
+    node.borrow()
+        .i_user_extension
+        .0
+        .as_ref()
+        .and_then(|extension| extension.serialize())
+}
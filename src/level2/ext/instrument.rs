@@ -0,0 +1,238 @@
+/*!
+Provides hooks that report the time spent in DOM operations to an
+[`Instrument`](trait.Instrument.html), so that performance investigations can attribute time to
+node creation, insertion, queries, and serialization without an external profiler.
+
+An instrument is installed for the current thread with [`set_instrument`](fn.set_instrument.html),
+documents are not shared between threads and so each thread has its own instrument. When an
+instrumented operation is invoked by another instrumented operation, for example the insertion of
+children while serializing, only the outermost operation is reported; the reported time is
+therefore the total time spent in each operation as called by the client.
+
+The [`Counters`](struct.Counters.html) type is an instrument that accumulates a count, and the
+total elapsed time, for each operation.
+
+This module is only available when the `instrumentation` feature is enabled.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::instrument::{clear_instrument, set_instrument, Counters, Operation};
+use std::rc::Rc;
+
+let counters = Rc::new(Counters::default());
+let _previous = set_instrument(counters.clone());
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("list"), None).unwrap();
+let document = convert::as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for _ in 0..3 {
+    let item_node = document.create_element("item").unwrap();
+    let _safe_to_ignore = root_node.append_child(item_node).unwrap();
+}
+let _xml = document_node.to_string();
+
+let _counters = clear_instrument();
+assert_eq!(counters.get(&Operation::Create(NodeType::Element)).count, 3);
+assert_eq!(counters.get(&Operation::Insert).count, 3);
+assert_eq!(counters.get(&Operation::Serialize).count, 1);
+```
+*/
+
+use crate::level2::traits::NodeType;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The DOM operations that are reported to an instrument.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// The creation of a new node of the given type, by `DOMImplementation` or `Document`.
+    Create(NodeType),
+    /// The insertion of a node by `insert_before`, `append_child`, or `replace_child`.
+    Insert,
+    /// A query by `get_elements_by_tag_name`, `get_elements_by_tag_name_ns`, or
+    /// `get_element_by_id`.
+    Query,
+    /// The serialization of a node using its `Display` implementation.
+    Serialize,
+}
+
+///
+/// Implemented by clients that wish to receive timing information for DOM operations.
+///
+pub trait Instrument {
+    ///
+    /// Called when an instrumented `operation` completes, having taken `elapsed` time.
+    ///
+    fn record(&self, operation: Operation, elapsed: Duration);
+}
+
+///
+/// The accumulated count, and elapsed time, of an operation.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counter {
+    /// The number of times the operation completed.
+    pub count: u64,
+    /// The total time taken by the operation.
+    pub elapsed: Duration,
+}
+
+///
+/// An instrument that accumulates a [`Counter`](struct.Counter.html) for each operation.
+///
+/// The `Display` implementation writes one tab-separated line for each operation that has been
+/// recorded, with the operation, count, and total elapsed time.
+///
+#[derive(Debug, Default)]
+pub struct Counters {
+    counters: RefCell<HashMap<Operation, Counter>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Install `instrument` for the current thread, returning any previously installed instrument.
+///
+pub fn set_instrument(instrument: Rc<dyn Instrument>) -> Option<Rc<dyn Instrument>> {
+    INSTRUMENT.with(|current| current.borrow_mut().replace(instrument))
+}
+
+///
+/// Remove, and return, any instrument installed for the current thread.
+///
+pub fn clear_instrument() -> Option<Rc<dyn Instrument>> {
+    INSTRUMENT.with(|current| current.borrow_mut().take())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Operation::Create(node_type) => write!(f, "create({:?})", node_type),
+            Operation::Insert => write!(f, "insert"),
+            Operation::Query => write!(f, "query"),
+            Operation::Serialize => write!(f, "serialize"),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Instrument for Counters {
+    fn record(&self, operation: Operation, elapsed: Duration) {
+        let mut counters = self.counters.borrow_mut();
+        let counter = counters.entry(operation).or_default();
+        counter.count += 1;
+        counter.elapsed += elapsed;
+    }
+}
+
+impl Display for Counters {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut lines: Vec<(String, Counter)> = self
+            .counters
+            .borrow()
+            .iter()
+            .map(|(operation, counter)| (operation.to_string(), *counter))
+            .collect();
+        lines.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+        for (operation, counter) in lines {
+            writeln!(f, "{}\t{}\t{:?}", operation, counter.count, counter.elapsed)?;
+        }
+        Ok(())
+    }
+}
+
+impl Counters {
+    ///
+    /// Returns the counter for `operation`, which will be zero if the operation has not been
+    /// recorded.
+    ///
+    pub fn get(&self, operation: &Operation) -> Counter {
+        self.counters
+            .borrow()
+            .get(operation)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Returns a copy of the counters for all operations that have been recorded.
+    ///
+    pub fn snapshot(&self) -> HashMap<Operation, Counter> {
+        self.counters.borrow().clone()
+    }
+
+    ///
+    /// Reset all counters to zero.
+    ///
+    pub fn reset(&self) {
+        self.counters.borrow_mut().clear()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Timer {
+    ///
+    /// Start timing `operation`, the time is reported when the returned value is dropped. If no
+    /// instrument is installed, or another operation is already being timed, nothing is reported.
+    ///
+    pub(crate) fn start(operation: Operation) -> Self {
+        let instrument = if ACTIVE.with(|active| active.replace(true)) {
+            None
+        } else {
+            let instrument = INSTRUMENT.with(|current| current.borrow().clone());
+            if instrument.is_none() {
+                ACTIVE.with(|active| active.set(false));
+            }
+            instrument
+        };
+        Self {
+            started: instrument.map(|instrument| (instrument, operation, Instant::now())),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if let Some((instrument, operation, started)) = self.started.take() {
+            instrument.record(operation, started.elapsed());
+            ACTIVE.with(|active| active.set(false));
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+thread_local! {
+    static INSTRUMENT: RefCell<Option<Rc<dyn Instrument>>> = const { RefCell::new(None) };
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+}
+
+///
+/// Times a single operation, see [`Timer::start`].
+///
+#[allow(missing_debug_implementations)]
+pub(crate) struct Timer {
+    started: Option<(Rc<dyn Instrument>, Operation, Instant)>,
+}
@@ -0,0 +1,241 @@
+/*!
+Provides [`read_xml_lazy`](fn.read_xml_lazy.html), which parses a document without constructing
+the content of selected elements; the content of each such element is kept as a range of the input
+text and only parsed, into real nodes, the first time it is accessed. For large documents the
+memory used then grows with the part of the document the application actually reads, rather than
+with the size of the document.
+
+The elements whose content is deferred are chosen by [`LazyOptions`](struct.LazyOptions.html),
+either by depth, for deep trees, or by name, for documents made of many repetitive records. The
+element itself, with its attributes, is always constructed, so that it may be found, inspected, and
+skipped without parsing its content.
+
+Deferred content is parsed, *hydrated*, by any method that reads or changes the children of its
+element, for example `child_nodes`, `first_child`, or `append_child`, and so also by functions that
+visit the whole tree, such as `get_elements_by_tag_name` or serialization. Errors in deferred
+content are only found when it is hydrated; the [`hydrate`](fn.hydrate.html) function parses the
+content of an element explicitly and returns any such error, implicit hydration logs the error and
+the element then appears to have no children. IDs within deferred content are not known to the
+document's ID map until hydrated, although `get_element_by_id` will hydrate content as it searches.
+
+The input is held, shared by all deferred elements, until the last of them is hydrated or dropped.
+
+This module is only available with the `quick_parser` feature.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::lazy::{is_hydrated, read_xml_lazy, LazyOptions};
+
+let options = LazyOptions {
+    elements: vec!["record".to_string()],
+    ..Default::default()
+};
+let document_node = read_xml_lazy(
+    r#"<records><record id="1"><name>one</name></record><record id="2"/></records>"#,
+    &options,
+)
+.unwrap();
+
+let records = document_node.first_child().unwrap();
+let record = records.first_child().unwrap();
+assert_eq!(record.get_attribute("id"), Some("1".to_string()));
+assert!(!is_hydrated(&record));
+
+assert_eq!(record.first_child().unwrap().node_name().to_string(), "name");
+assert!(is_hydrated(&record));
+```
+*/
+
+use crate::level2::ext::namespaced::resolve_names;
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::parser::{DomBuilder, Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::fmt::{Debug, Formatter};
+use std::ops::Range;
+use std::rc::Rc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Options that select the elements whose content is deferred by
+/// [`read_xml_lazy`](fn.read_xml_lazy.html); an element is deferred if it is selected by either.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LazyOptions {
+    /// Defer the content of elements at this depth, the document element is at depth `1`.
+    pub depth: Option<usize>,
+    /// Defer the content of elements with these qualified names, as written in the input.
+    pub elements: Vec<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Parse the provided string into a DOM structure, as
+/// [`read_xml`](../../../parser/fn.read_xml.html) does, except that the content of the elements
+/// selected by `options` is not constructed until it is accessed; if the result is OK, the result
+/// returned can be safely assumed to be a `Document` node.
+///
+/// The input is checked only so far as is needed to find the end of each deferred element.
+///
+pub fn read_xml_lazy(xml: &str, options: &LazyOptions) -> Result<RefNode> {
+    let source: Rc<str> = Rc::from(xml);
+    let mut reader = Reader::from_str(&source);
+    let mut builder = DomBuilder::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => return builder.finish(),
+            Ok(Event::Start(ev)) => {
+                let name = reader.decoder().decode(ev.name().as_ref())?.to_string();
+                let deferred = options.depth == Some(builder.depth() + 1)
+                    || options.elements.iter().any(|element| element == &name);
+                let end = ev.to_end().into_owned();
+                builder.handle_event(&reader, Event::Start(ev))?;
+                if deferred {
+                    let span = reader.read_to_end(end.name())?;
+                    if !span.is_empty() {
+                        if let Some(element) = builder.open_element() {
+                            set_deferred(
+                                element,
+                                DeferredContent {
+                                    source: source.clone(),
+                                    span,
+                                },
+                            );
+                        }
+                    }
+                    builder.handle_event(&reader, Event::End(end))?;
+                }
+            }
+            Ok(event) => builder.handle_event(&reader, event)?,
+            Err(err) => {
+                error!("Unexpected parser error: {:?}", err);
+                return Error::from(err).into();
+            }
+        }
+    }
+}
+
+///
+/// Construct the deferred content of `node`, if any; this is done implicitly by methods that
+/// access the children of `node`, but any error in the content is only returned here.
+///
+/// If an error is returned the content remains deferred, and `node` has no children.
+///
+pub fn hydrate(node: &RefNode) -> Result<()> {
+    if is_hydrated(node) {
+        return Ok(());
+    }
+    let (deferred, read_only) = {
+        let mut mut_node = node.borrow_mut();
+        let read_only = mut_node.i_read_only;
+        mut_node.i_read_only = false;
+        match &mut mut_node.i_extension {
+            Extension::Element { i_deferred, .. } => (i_deferred.take(), read_only),
+            _ => (None, read_only),
+        }
+    };
+    let result = match &deferred {
+        Some(deferred) => build_content(node, deferred),
+        None => Ok(()),
+    };
+    let mut mut_node = node.borrow_mut();
+    if result.is_err() {
+        mut_node.i_child_nodes.clear();
+        if let Extension::Element { i_deferred, .. } = &mut mut_node.i_extension {
+            *i_deferred = deferred;
+        }
+    }
+    if read_only {
+        mut_node.set_read_only();
+    }
+    result
+}
+
+///
+/// Returns `true` unless `node` is an element whose content has been deferred and not yet
+/// constructed.
+///
+pub fn is_hydrated(node: &RefNode) -> bool {
+    !matches!(
+        &node.borrow().i_extension,
+        Extension::Element {
+            i_deferred: Some(_),
+            ..
+        }
+    )
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for DeferredContent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeferredContent")
+            .field("span", &self.span)
+            .finish()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The content of an element, as the range `span` of the input `source`, that has not yet been
+/// parsed.
+///
+#[derive(Clone)]
+pub(crate) struct DeferredContent {
+    source: Rc<str>,
+    span: Range<usize>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Construct the deferred content of `node`, if any, logging rather than returning any error.
+///
+pub(crate) fn hydrate_children(node: &RefNode) {
+    if let Err(err) = hydrate(node) {
+        error!(
+            "Could not construct the deferred content of an element: {}",
+            err
+        );
+    }
+}
+
+fn set_deferred(element: &RefNode, content: DeferredContent) {
+    if let Extension::Element { i_deferred, .. } = &mut element.borrow_mut().i_extension {
+        *i_deferred = Some(content);
+    }
+}
+
+fn build_content(element: &RefNode, deferred: &DeferredContent) -> Result<()> {
+    let mut reader = Reader::from_str(&deferred.source[deferred.span.clone()]);
+    let mut builder = DomBuilder::within(element)?;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => builder.handle_event(&reader, event)?,
+            Err(err) => {
+                error!("Unexpected parser error: {:?}", err);
+                return Error::from(err).into();
+            }
+        }
+    }
+    if builder.depth() != 1 {
+        return Error::Malformed.into();
+    }
+    Ok(resolve_names(element)?)
+}
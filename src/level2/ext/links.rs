@@ -0,0 +1,234 @@
+/*!
+This module provides support types for the [`DocumentLinks`](../trait.DocumentLinks.html) trait,
+which collects the links in a document as a building block for crawlers and reference checkers.
+
+A link is an attribute whose value is a URI reference; by default the attributes `xlink:href`
+(in the XLink namespace, whatever prefix is used), `href`, and `src` are collected, others may be
+added to a [`LinkConfig`](struct.LinkConfig.html). Each [`Link`](struct.Link.html) records the
+owning element and attribute, the value as written, the base URI in effect for the element, and
+the value resolved against that base.
+
+The base URI of an element is determined by the `xml:base` attributes on the element and its
+ancestors, as described in [XML Base](https://www.w3.org/TR/xmlbase/), starting from the base URI
+of the document itself if one is set in the configuration, or else the document's own
+[`document_uri`](../../trait.Document.html#tymethod.document_uri).
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::DocumentLinks;
+use xml_dom::level2::ext::links::LinkConfig;
+
+let document_node = xml_dom::parser::read_xml(
+    r#"<site xml:base="docs/"><a href="intro.html"/><img src="../logo.png"/></site>"#,
+)
+.unwrap();
+
+let mut config = LinkConfig::default();
+config.set_base_uri("http://example.com/index.xml");
+let links = document_node.extract_links(&config);
+assert_eq!(links.len(), 2);
+assert_eq!(links[0].value, "intro.html");
+assert_eq!(links[0].base_uri.as_deref(), Some("http://example.com/docs/"));
+assert_eq!(links[0].uri, "http://example.com/docs/intro.html");
+assert_eq!(links[1].uri, "http://example.com/logo.png");
+# }
+```
+*/
+
+use crate::level2::convert::as_attribute;
+use crate::level2::ext::namespaced::resolve_declared_prefix;
+use crate::level2::ext::traits::DocumentLinks;
+use crate::level2::ext::workspace::resolve_uri;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Document, Node, NodeType};
+use crate::shared::error::MSG_INVALID_NODE_TYPE;
+use crate::shared::name::Name;
+use crate::shared::syntax::{
+    LINK_ATTR_HREF, LINK_ATTR_SRC, XLINK_ATTR_HREF, XLINK_NS_URI, XML_NS_ATTRIBUTE,
+    XML_NS_ATTR_BASE, XML_NS_URI,
+};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Determines which attributes are collected by
+/// [`extract_links`](../trait.DocumentLinks.html#tymethod.extract_links), and the base URI of the
+/// document.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkConfig {
+    attributes: Vec<(Option<String>, String)>,
+    base_uri: Option<String>,
+}
+
+///
+/// A single link found in a document.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Link {
+    /// The element that owns the link attribute.
+    pub element: RefNode,
+    /// The link attribute.
+    pub attribute: RefNode,
+    /// The value of the attribute, as written.
+    pub value: String,
+    /// The base URI in effect for the element, if any.
+    pub base_uri: Option<String>,
+    /// The value of the attribute resolved against `base_uri`.
+    pub uri: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        let mut config = Self::empty();
+        config.add_attribute_ns(XLINK_NS_URI, XLINK_ATTR_HREF);
+        config.add_attribute(LINK_ATTR_HREF);
+        config.add_attribute(LINK_ATTR_SRC);
+        config
+    }
+}
+
+impl LinkConfig {
+    ///
+    /// Construct a configuration that collects no attributes, and has no base URI.
+    ///
+    pub fn empty() -> Self {
+        Self {
+            attributes: Default::default(),
+            base_uri: None,
+        }
+    }
+
+    ///
+    /// Collect the values of attributes named `local_name` that are not in a namespace.
+    ///
+    pub fn add_attribute(&mut self, local_name: &str) {
+        self.add(None, local_name)
+    }
+
+    ///
+    /// Collect the values of attributes named `local_name` in the namespace `namespace_uri`,
+    /// regardless of the prefix used in the document.
+    ///
+    pub fn add_attribute_ns(&mut self, namespace_uri: &str, local_name: &str) {
+        self.add(Some(namespace_uri), local_name)
+    }
+
+    ///
+    /// Set the base URI of the document, against which all links are resolved, in place of the
+    /// document's own `document_uri`.
+    ///
+    pub fn set_base_uri(&mut self, base_uri: &str) {
+        self.base_uri = Some(base_uri.to_string());
+    }
+
+    ///
+    /// Returns the base URI of the document, if set.
+    ///
+    pub fn base_uri(&self) -> Option<&String> {
+        self.base_uri.as_ref()
+    }
+
+    fn add(&mut self, namespace_uri: Option<&str>, local_name: &str) {
+        let attribute = (namespace_uri.map(String::from), local_name.to_string());
+        if !self.attributes.contains(&attribute) {
+            self.attributes.push(attribute);
+        }
+    }
+
+    fn matches(&self, element: &RefNode, name: &Name) -> bool {
+        let namespace_uri = attribute_namespace(element, name);
+        self.attributes
+            .iter()
+            .any(|(link_namespace_uri, local_name)| {
+                *link_namespace_uri == namespace_uri && local_name == name.local_name()
+            })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentLinks for RefNode {
+    fn extract_links(&self, config: &LinkConfig) -> Vec<Link> {
+        let mut links: Vec<Link> = Default::default();
+        if self.node_type() != NodeType::Document {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return links;
+        }
+        if let Some(element) = self.document_element() {
+            let base_uri = config.base_uri.clone().or_else(|| self.document_uri());
+            collect_links(&element, base_uri, config, &mut links);
+        }
+        links
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The namespace of the attribute `name` on `element`, from its name if present, else by
+/// resolving its prefix; an un-prefixed attribute is in no namespace.
+///
+fn attribute_namespace(element: &RefNode, name: &Name) -> Option<String> {
+    match (name.namespace_uri(), name.prefix().as_deref()) {
+        (Some(namespace_uri), _) => Some(namespace_uri.clone()),
+        (None, None) => None,
+        (None, Some(XML_NS_ATTRIBUTE)) => Some(XML_NS_URI.to_string()),
+        (None, Some(prefix)) => resolve_declared_prefix(element, Some(prefix)),
+    }
+}
+
+fn collect_links(
+    element: &RefNode,
+    base_uri: Option<String>,
+    config: &LinkConfig,
+    links: &mut Vec<Link>,
+) {
+    let mut attributes: Vec<(Name, RefNode)> = element.attributes().into_iter().collect();
+    attributes.sort_by_key(|(name, _)| name.to_string());
+
+    let base_uri = attributes
+        .iter()
+        .find(|(name, _)| {
+            name.local_name() == XML_NS_ATTR_BASE
+                && attribute_namespace(element, name).as_deref() == Some(XML_NS_URI)
+        })
+        .and_then(|(_, attribute)| as_attribute(attribute).ok()?.value())
+        .map(|value| resolve_uri(&value, base_uri.as_deref()))
+        .or(base_uri);
+
+    for (name, attribute) in attributes {
+        if !config.matches(element, &name) {
+            continue;
+        }
+        let value = as_attribute(&attribute)
+            .ok()
+            .and_then(|attribute| attribute.value())
+            .unwrap_or_default();
+        links.push(Link {
+            element: element.clone(),
+            attribute,
+            uri: resolve_uri(&value, base_uri.as_deref()),
+            value,
+            base_uri: base_uri.clone(),
+        });
+    }
+
+    for child_node in element.child_nodes() {
+        if child_node.node_type() == NodeType::Element {
+            collect_links(&child_node, base_uri.clone(), config, links);
+        }
+    }
+}
@@ -6,21 +6,123 @@ This module contains extensions above and beyond the Level 2 specification.
 // Public Modules
 // ------------------------------------------------------------------------------------------------
 
+#[cfg(feature = "actor")]
+pub mod actor;
+
+pub mod audit;
+pub use audit::AttributeChange;
+
+#[cfg(feature = "bench")]
+pub mod bench;
+
+pub mod binary;
+pub use binary::BinaryEncoding;
+
+#[cfg(feature = "entity_catalog")]
+pub mod catalog;
+
+pub mod compare;
+
+pub mod completion;
+
+#[cfg(feature = "quick_parser")]
+pub mod conformance;
+
 pub mod convert;
 
 pub mod decl;
-pub use decl::{XmlDecl, XmlVersion};
+pub use decl::{PrologPosition, XmlDecl, XmlVersion};
+
+pub mod diff;
 
 pub mod dom_impl;
 
+pub mod dtd;
+
+pub mod extension;
+pub use extension::NodeExtension;
+
+#[cfg(feature = "instrumentation")]
+pub mod instrument;
+
+#[cfg(feature = "quick_parser")]
+pub mod lazy;
+
+pub mod links;
+
 pub mod options;
 pub use options::ProcessingOptions;
 
+pub mod mutation;
+pub use mutation::{MutationLog, Recorder};
+
+pub mod named_node_map;
+pub use named_node_map::NamedNodeMap;
+
 pub mod namespaced;
-pub use namespaced::NamespacePrefix;
+pub use namespaced::{NamespaceContext, NamespacePrefix};
+
+pub mod node_list;
+pub use node_list::NodeList;
+
+pub mod normalize;
+
+pub mod outline;
+pub use outline::Outline;
+
+pub mod query;
+pub use query::QueryResult;
+
+pub mod references;
+
+pub mod resolver;
+
+#[cfg(feature = "quick_parser")]
+pub mod roundtrip;
+
+pub mod sax;
+pub use sax::{ContentHandler, DocumentBuilder};
+
+pub mod selector;
+pub use selector::Selector;
+
+pub mod serializer;
+
+pub mod split;
+
+pub mod sync;
+pub use sync::SyncDocument;
+
+#[cfg(feature = "soap")]
+pub mod soap;
+
+#[cfg(feature = "soap")]
+pub mod wsse;
+
+#[cfg(feature = "xop")]
+pub mod xop;
+
+pub mod tree_builder;
+pub use tree_builder::TreeBuilder;
+
+pub mod unparsed;
+
+pub mod user_data;
+pub use user_data::{UserDataHandler, UserDataOperation};
+
+pub mod workspace;
+pub use workspace::Workspace;
+
+#[cfg(feature = "xml_rs")]
+pub mod xml_rs;
+
+pub mod xpath;
+pub use xpath::XPath;
+
+pub mod xsd;
+pub use xsd::XsdType;
 
 pub(crate) mod traits;
 pub use traits::*;
 
 pub(crate) mod trait_impls;
-pub use trait_impls::*;
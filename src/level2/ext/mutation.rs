@@ -0,0 +1,1106 @@
+/*!
+Provides the ability to record the mutations applied to a document into a
+[`MutationLog`](struct.MutationLog.html), and to rebuild an identical document by replaying that
+log.
+
+Changes made through a [`Recorder`](struct.Recorder.html) are applied to its document and appended
+to its log. Nodes are identified in the log by their path, the index of each node within its
+parent's child list from the document down, so a log is independent of the `RefNode` values used
+to record it. A log may be written to, and read from, a line-oriented textual form using its
+`Display` and `FromStr` implementations.
+
+Each mutation in a log creates a new revision of the document, and any earlier revision may be
+materialized with [`MutationLog::at_revision`](struct.MutationLog.html#method.at_revision); this
+allows, for example, the state of a document to be inspected before and after each step of a long
+transformation pipeline.
+
+Note that only the name and identifiers of the document type are recorded, not any entity or
+notation declarations, and neither the XML declaration nor the processing options of the document
+are recorded.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::mutation::{MutationLog, Recorder};
+use std::str::FromStr;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("list"), None).unwrap();
+let mut recorder = Recorder::new(&document_node).unwrap();
+
+let document = as_document(&document_node).unwrap();
+let root_node = document.document_element().unwrap();
+let item_node = document.create_element("item").unwrap();
+let item_node = recorder.append_child(&root_node, item_node).unwrap();
+recorder.set_attribute(&item_node, "id", "one").unwrap();
+
+let log = MutationLog::from_str(&recorder.log().to_string()).unwrap();
+let replayed_node = log.replay().unwrap();
+assert_eq!(replayed_node.to_string(), document_node.to_string());
+```
+*/
+
+use crate::level2::convert::{as_document, as_document_type, as_element_mut};
+use crate::level2::dom_impl::get_implementation;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::ordered_attributes;
+use crate::level2::traits::*;
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_MUTATION, MSG_INVALID_NODE_TYPE, MSG_INVALID_PI_DATA,
+    MSG_MISSING_DOCUMENT_MUTATION, MSG_WRONG_DOCUMENT,
+};
+use crate::shared::name::Name;
+use crate::shared::text::is_processing_instruction_data;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Deref;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Identifies a node by the index of each node within its parent's child list, from the document
+/// down; the empty path identifies the document itself.
+///
+pub type NodePath = Vec<usize>;
+
+///
+/// Describes a node to be created, and inserted, by a `Mutation::Insert`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum NewNode {
+    /// An `Element`, created with `create_element_ns` if a namespace URI is present.
+    Element {
+        /// The namespace URI of the element.
+        namespace_uri: Option<String>,
+        /// The qualified name of the element.
+        qualified_name: String,
+    },
+    /// A `Text` node with the given data.
+    Text(String),
+    /// A `CDATASection` node with the given data.
+    CData(String),
+    /// A `Comment` node with the given data.
+    Comment(String),
+    /// A `ProcessingInstruction` node.
+    ProcessingInstruction {
+        /// The target of the processing instruction.
+        target: String,
+        /// The data of the processing instruction.
+        data: Option<String>,
+    },
+    /// An `EntityReference` node with the given name.
+    EntityReference(String),
+}
+
+///
+/// A single recorded change to a document.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mutation {
+    /// Create the document, this is always the first mutation in a log.
+    CreateDocument {
+        /// The name of the document type, if the document has one.
+        doc_type_name: Option<String>,
+        /// The public identifier of the document type.
+        public_id: Option<String>,
+        /// The system identifier of the document type.
+        system_id: Option<String>,
+    },
+    /// Create a new node and insert it into the child list of `parent` at `index`.
+    Insert {
+        /// The path to the parent node.
+        parent: NodePath,
+        /// The index of the new node in the parent's child list.
+        index: usize,
+        /// The node to create.
+        node: NewNode,
+    },
+    /// Remove the node from its parent.
+    Remove {
+        /// The path to the node to remove.
+        node: NodePath,
+    },
+    /// Set the value of an attribute on an element.
+    SetAttribute {
+        /// The path to the element.
+        element: NodePath,
+        /// The namespace URI of the attribute.
+        namespace_uri: Option<String>,
+        /// The qualified name of the attribute.
+        qualified_name: String,
+        /// The raw, un-escaped, value of the attribute.
+        value: String,
+    },
+    /// Remove an attribute from an element.
+    RemoveAttribute {
+        /// The path to the element.
+        element: NodePath,
+        /// The namespace URI of the attribute.
+        namespace_uri: Option<String>,
+        /// The qualified name of the attribute.
+        qualified_name: String,
+    },
+    /// Replace the data of a character data or processing instruction node.
+    SetData {
+        /// The path to the node.
+        node: NodePath,
+        /// The new data for the node.
+        data: String,
+    },
+}
+
+///
+/// An ordered list of mutations which, when replayed, will construct a document.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MutationLog(Vec<Mutation>);
+
+///
+/// Applies changes to a document, recording each one in a `MutationLog`.
+///
+/// Nodes may be freely created, and modified, before they are inserted into the document; the
+/// complete subtree is recorded at the point of insertion. Once inserted, all changes to a node
+/// must be made through the recorder for the log to remain complete.
+///
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    document: RefNode,
+    log: MutationLog,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for NewNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            NewNode::Element {
+                namespace_uri,
+                qualified_name,
+            } => write!(
+                f,
+                "{} {} {}",
+                KW_ELEMENT,
+                optional(namespace_uri),
+                quoted(qualified_name)
+            ),
+            NewNode::Text(data) => write!(f, "{} {}", KW_TEXT, quoted(data)),
+            NewNode::CData(data) => write!(f, "{} {}", KW_CDATA, quoted(data)),
+            NewNode::Comment(data) => write!(f, "{} {}", KW_COMMENT, quoted(data)),
+            NewNode::ProcessingInstruction { target, data } => {
+                write!(f, "{} {} {}", KW_PI, quoted(target), optional(data))
+            }
+            NewNode::EntityReference(name) => write!(f, "{} {}", KW_ENTITY_REF, quoted(name)),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Mutation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Mutation::CreateDocument {
+                doc_type_name,
+                public_id,
+                system_id,
+            } => match doc_type_name {
+                None => write!(f, "{}", KW_DOCUMENT),
+                Some(name) => write!(
+                    f,
+                    "{} {} {} {}",
+                    KW_DOCUMENT,
+                    quoted(name),
+                    optional(public_id),
+                    optional(system_id)
+                ),
+            },
+            Mutation::Insert {
+                parent,
+                index,
+                node,
+            } => write!(f, "{} {} {} {}", KW_INSERT, path(parent), index, node),
+            Mutation::Remove { node } => write!(f, "{} {}", KW_REMOVE, path(node)),
+            Mutation::SetAttribute {
+                element,
+                namespace_uri,
+                qualified_name,
+                value,
+            } => write!(
+                f,
+                "{} {} {} {} {}",
+                KW_SET_ATTRIBUTE,
+                path(element),
+                optional(namespace_uri),
+                quoted(qualified_name),
+                quoted(value)
+            ),
+            Mutation::RemoveAttribute {
+                element,
+                namespace_uri,
+                qualified_name,
+            } => write!(
+                f,
+                "{} {} {} {}",
+                KW_REMOVE_ATTRIBUTE,
+                path(element),
+                optional(namespace_uri),
+                quoted(qualified_name)
+            ),
+            Mutation::SetData { node, data } => {
+                write!(f, "{} {} {}", KW_SET_DATA, path(node), quoted(data))
+            }
+        }
+    }
+}
+
+impl FromStr for Mutation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut tokens = Tokens::new(s)?;
+        let mutation = match tokens.bare()?.as_str() {
+            KW_DOCUMENT => {
+                if tokens.is_empty() {
+                    Mutation::CreateDocument {
+                        doc_type_name: None,
+                        public_id: None,
+                        system_id: None,
+                    }
+                } else {
+                    Mutation::CreateDocument {
+                        doc_type_name: Some(tokens.quoted()?),
+                        public_id: tokens.optional()?,
+                        system_id: tokens.optional()?,
+                    }
+                }
+            }
+            KW_INSERT => Mutation::Insert {
+                parent: tokens.path()?,
+                index: tokens.index()?,
+                node: match tokens.bare()?.as_str() {
+                    KW_ELEMENT => NewNode::Element {
+                        namespace_uri: tokens.optional()?,
+                        qualified_name: tokens.quoted()?,
+                    },
+                    KW_TEXT => NewNode::Text(tokens.quoted()?),
+                    KW_CDATA => NewNode::CData(tokens.quoted()?),
+                    KW_COMMENT => NewNode::Comment(tokens.quoted()?),
+                    KW_PI => NewNode::ProcessingInstruction {
+                        target: tokens.quoted()?,
+                        data: tokens.optional()?,
+                    },
+                    KW_ENTITY_REF => NewNode::EntityReference(tokens.quoted()?),
+                    _ => return tokens.error(),
+                },
+            },
+            KW_REMOVE => Mutation::Remove {
+                node: tokens.path()?,
+            },
+            KW_SET_ATTRIBUTE => Mutation::SetAttribute {
+                element: tokens.path()?,
+                namespace_uri: tokens.optional()?,
+                qualified_name: tokens.quoted()?,
+                value: tokens.quoted()?,
+            },
+            KW_REMOVE_ATTRIBUTE => Mutation::RemoveAttribute {
+                element: tokens.path()?,
+                namespace_uri: tokens.optional()?,
+                qualified_name: tokens.quoted()?,
+            },
+            KW_SET_DATA => Mutation::SetData {
+                node: tokens.path()?,
+                data: tokens.quoted()?,
+            },
+            _ => return tokens.error(),
+        };
+        if tokens.is_empty() {
+            Ok(mutation)
+        } else {
+            tokens.error()
+        }
+    }
+}
+
+impl Mutation {
+    ///
+    /// Apply this mutation to `document`, which must have been constructed by replaying all
+    /// mutations preceding this one in the same log.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if any path in the mutation does not identify a node.
+    /// * `INVALID_STATE_ERR`: Raised if this is a `CreateDocument` mutation, which may only be
+    ///   applied by [`MutationLog::replay`](struct.MutationLog.html#method.replay).
+    ///
+    /// Any exception raised by the underlying DOM operation is also returned.
+    ///
+    pub fn apply(&self, document: &mut RefNode) -> Result<()> {
+        match self {
+            Mutation::CreateDocument { .. } => {
+                warn!("{}", MSG_MISSING_DOCUMENT_MUTATION);
+                Err(Error::InvalidState)
+            }
+            Mutation::Insert {
+                parent,
+                index,
+                node,
+            } => {
+                let mut parent_node = resolve(document, parent)?;
+                let ref_document = as_document(document)?;
+                let new_node = match node {
+                    NewNode::Element {
+                        namespace_uri: Some(namespace_uri),
+                        qualified_name,
+                    } => ref_document.create_element_ns(namespace_uri, qualified_name)?,
+                    NewNode::Element {
+                        namespace_uri: None,
+                        qualified_name,
+                    } => ref_document.create_element(qualified_name)?,
+                    NewNode::Text(data) => ref_document.create_text_node(data),
+                    NewNode::CData(data) => ref_document.create_cdata_section(data)?,
+                    NewNode::Comment(data) => ref_document.create_comment(data),
+                    NewNode::ProcessingInstruction { target, data } => {
+                        ref_document.create_processing_instruction(target, data.as_deref())?
+                    }
+                    NewNode::EntityReference(name) => ref_document.create_entity_reference(name)?,
+                };
+                let ref_child = parent_node.child_nodes().get(*index).cloned();
+                let _safe_to_ignore = parent_node.insert_before(new_node, ref_child)?;
+                Ok(())
+            }
+            Mutation::Remove { node } => {
+                let node = resolve(document, node)?;
+                match node.parent_node() {
+                    None => Err(Error::NotFound),
+                    Some(mut parent_node) => parent_node.remove_child(node).map(|_| ()),
+                }
+            }
+            Mutation::SetAttribute {
+                element,
+                namespace_uri,
+                qualified_name,
+                value,
+            } => {
+                let mut element_node = resolve(document, element)?;
+                let element = as_element_mut(&mut element_node)?;
+                match namespace_uri {
+                    Some(namespace_uri) => {
+                        element.set_attribute_ns(namespace_uri, qualified_name, value)
+                    }
+                    None => element.set_attribute(qualified_name, value),
+                }
+            }
+            Mutation::RemoveAttribute {
+                element,
+                namespace_uri,
+                qualified_name,
+            } => {
+                let mut element_node = resolve(document, element)?;
+                let element = as_element_mut(&mut element_node)?;
+                match namespace_uri {
+                    Some(namespace_uri) => {
+                        let name = Name::from_str(qualified_name)?;
+                        element.remove_attribute_ns(namespace_uri, name.local_name())
+                    }
+                    None => element.remove_attribute(qualified_name),
+                }
+            }
+            Mutation::SetData { node, data } => {
+                let mut node = resolve(document, node)?;
+                node.set_node_value(data)
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Deref for MutationLog {
+    type Target = [Mutation];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<MutationLog> for Vec<Mutation> {
+    fn from(log: MutationLog) -> Self {
+        log.0
+    }
+}
+
+impl Display for MutationLog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for mutation in &self.0 {
+            writeln!(f, "{}", mutation)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MutationLog {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mutations = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Mutation::from_str)
+            .collect::<Result<Vec<Mutation>>>()?;
+        Ok(Self(mutations))
+    }
+}
+
+impl MutationLog {
+    ///
+    /// Record the current content of `document` as a new log; replaying the result will
+    /// construct a copy of the document.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if `document` is not a `Document` node.
+    ///
+    pub fn snapshot(document: &RefNode) -> Result<Self> {
+        let ref_document = as_document(document).map_err(|_| Error::NotSupported)?;
+        let mut log = MutationLog(vec![match ref_document.doc_type() {
+            None => Mutation::CreateDocument {
+                doc_type_name: None,
+                public_id: None,
+                system_id: None,
+            },
+            Some(doc_type_node) => {
+                let doc_type = as_document_type(&doc_type_node)?;
+                Mutation::CreateDocument {
+                    doc_type_name: Some(doc_type.node_name().to_string()),
+                    public_id: doc_type.public_id(),
+                    system_id: doc_type.system_id(),
+                }
+            }
+        }]);
+        for (index, child) in document.child_nodes().iter().enumerate() {
+            log.record_subtree(&Vec::new(), index, child)?;
+        }
+        Ok(log)
+    }
+
+    ///
+    /// Construct a new document by replaying all the mutations in this log.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if the log does not begin with a `CreateDocument` mutation,
+    ///   or contains more than one.
+    ///
+    /// Any exception raised by [`Mutation::apply`](enum.Mutation.html#method.apply) is also
+    /// returned.
+    ///
+    pub fn replay(&self) -> Result<RefNode> {
+        self.at_revision(self.revisions())
+    }
+
+    ///
+    /// Construct a new document by replaying the mutations in this log up to, and including,
+    /// `revision`. Revision `0` is the newly created document, and each mutation following the
+    /// `CreateDocument` mutation creates a new revision.
+    ///
+    /// The returned document is independent of the document the log was recorded from, and of
+    /// any other revision, so it may be inspected, or compared with another revision, without
+    /// affecting either.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INDEX_SIZE_ERR`: Raised if `revision` is greater than the value returned by
+    ///   `revisions`.
+    /// * `INVALID_STATE_ERR`: Raised if the log does not begin with a `CreateDocument` mutation,
+    ///   or contains more than one.
+    ///
+    /// Any exception raised by [`Mutation::apply`](enum.Mutation.html#method.apply) is also
+    /// returned.
+    ///
+    pub fn at_revision(&self, revision: usize) -> Result<RefNode> {
+        if revision > self.revisions() {
+            return Err(Error::IndexSize);
+        }
+        let implementation = get_implementation();
+        let mut document = match self.0.first() {
+            Some(Mutation::CreateDocument {
+                doc_type_name,
+                public_id,
+                system_id,
+            }) => {
+                let doc_type = match doc_type_name {
+                    None => None,
+                    Some(name) => Some(implementation.create_document_type(
+                        name,
+                        public_id.as_deref(),
+                        system_id.as_deref(),
+                    )?),
+                };
+                implementation.create_document(None, None, doc_type)?
+            }
+            _ => {
+                warn!("{}", MSG_MISSING_DOCUMENT_MUTATION);
+                return Err(Error::InvalidState);
+            }
+        };
+        for mutation in self.0.iter().skip(1).take(revision) {
+            mutation.apply(&mut document)?;
+        }
+        Ok(document)
+    }
+
+    ///
+    /// Returns the number of the latest revision in this log, this is the number of mutations
+    /// following the `CreateDocument` mutation.
+    ///
+    pub fn revisions(&self) -> usize {
+        self.0.len().saturating_sub(1)
+    }
+
+    ///
+    /// Returns the mutations in this log.
+    ///
+    pub fn mutations(&self) -> &Vec<Mutation> {
+        &self.0
+    }
+
+    ///
+    /// Record the insertion of `node` at `index` in the child list of the node at `parent`,
+    /// followed by its attributes and all of its descendants. The read-only children of an entity
+    /// reference are not recorded, they are created from the entity when the reference is.
+    ///
+    fn record_subtree(&mut self, parent: &[usize], index: usize, node: &RefNode) -> Result<()> {
+        self.0.push(Mutation::Insert {
+            parent: parent.to_vec(),
+            index,
+            node: new_node(node)?,
+        });
+        let mut node_path = parent.to_vec();
+        node_path.push(index);
+        for (name, attribute) in ordered_attributes(node) {
+            self.0.push(Mutation::SetAttribute {
+                element: node_path.clone(),
+                namespace_uri: name.namespace_uri().clone(),
+                qualified_name: name.to_string(),
+                value: raw_value(&attribute),
+            });
+        }
+        if node.node_type() != NodeType::EntityReference {
+            for (index, child) in node.child_nodes().iter().enumerate() {
+                self.record_subtree(&node_path, index, child)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Recorder {
+    ///
+    /// Create a new recorder for `document`; the current content of the document is recorded as
+    /// the start of the log.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if `document` is not a `Document` node.
+    ///
+    pub fn new(document: &RefNode) -> Result<Self> {
+        Ok(Self {
+            document: document.clone(),
+            log: MutationLog::snapshot(document)?,
+        })
+    }
+
+    ///
+    /// Returns the document being recorded.
+    ///
+    pub fn document(&self) -> &RefNode {
+        &self.document
+    }
+
+    ///
+    /// Returns the log of all mutations recorded so far.
+    ///
+    pub fn log(&self) -> &MutationLog {
+        &self.log
+    }
+
+    ///
+    /// Consume this recorder, returning the log of all mutations recorded.
+    ///
+    pub fn into_log(self) -> MutationLog {
+        self.log
+    }
+
+    ///
+    /// Returns the current revision of the recorded document; see
+    /// [`MutationLog::at_revision`](struct.MutationLog.html#method.at_revision).
+    ///
+    pub fn revision(&self) -> usize {
+        self.log.revisions()
+    }
+
+    ///
+    /// Construct a new, independent, document with the state of the recorded document at
+    /// `revision`; see [`MutationLog::at_revision`](struct.MutationLog.html#method.at_revision).
+    ///
+    pub fn at_revision(&self, revision: usize) -> Result<RefNode> {
+        self.log.at_revision(revision)
+    }
+
+    ///
+    /// Perform, and record, `insert_before` on `parent`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `parent` is not part of the recorded document.
+    ///
+    /// Any exception raised by `Node::insert_before` is also returned.
+    ///
+    pub fn insert_before(
+        &mut self,
+        parent: &RefNode,
+        new_child: RefNode,
+        ref_child: Option<RefNode>,
+    ) -> Result<RefNode> {
+        let parent_path = self.path_to(parent)?;
+        let inserted = if new_child.node_type() == NodeType::DocumentFragment {
+            new_child.child_nodes()
+        } else {
+            vec![new_child.clone()]
+        };
+        let mut parent_node = parent.clone();
+        let new_child = parent_node.insert_before(new_child, ref_child)?;
+        let children = parent.child_nodes();
+        for node in &inserted {
+            // this cannot fail, the node has just been inserted.
+            let index = children.iter().position(|child| child == node).unwrap();
+            self.log.record_subtree(&parent_path, index, node)?;
+        }
+        Ok(new_child)
+    }
+
+    ///
+    /// Perform, and record, `append_child` on `parent`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `parent` is not part of the recorded document.
+    ///
+    /// Any exception raised by `Node::append_child` is also returned.
+    ///
+    pub fn append_child(&mut self, parent: &RefNode, new_child: RefNode) -> Result<RefNode> {
+        self.insert_before(parent, new_child, None)
+    }
+
+    ///
+    /// Perform, and record, `remove_child` on `parent`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `parent` is not part of the recorded document.
+    ///
+    /// Any exception raised by `Node::remove_child` is also returned.
+    ///
+    pub fn remove_child(&mut self, parent: &RefNode, old_child: RefNode) -> Result<RefNode> {
+        let _safe_to_ignore = self.path_to(parent)?;
+        let old_path = self.path_to(&old_child)?;
+        let mut parent_node = parent.clone();
+        let old_child = parent_node.remove_child(old_child)?;
+        self.log.0.push(Mutation::Remove { node: old_path });
+        Ok(old_child)
+    }
+
+    ///
+    /// Perform, and record, `set_attribute` on `element`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `element` is not part of the recorded document.
+    ///
+    /// Any exception raised by `Element::set_attribute` is also returned.
+    ///
+    pub fn set_attribute(&mut self, element: &RefNode, name: &str, value: &str) -> Result<()> {
+        let element_path = self.path_to(element)?;
+        let mut element_node = element.clone();
+        as_element_mut(&mut element_node)?.set_attribute(name, value)?;
+        self.log.0.push(Mutation::SetAttribute {
+            element: element_path,
+            namespace_uri: None,
+            qualified_name: name.to_string(),
+            value: value.to_string(),
+        });
+        Ok(())
+    }
+
+    ///
+    /// Perform, and record, `set_attribute_ns` on `element`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `element` is not part of the recorded document.
+    ///
+    /// Any exception raised by `Element::set_attribute_ns` is also returned.
+    ///
+    pub fn set_attribute_ns(
+        &mut self,
+        element: &RefNode,
+        namespace_uri: &str,
+        qualified_name: &str,
+        value: &str,
+    ) -> Result<()> {
+        let element_path = self.path_to(element)?;
+        let mut element_node = element.clone();
+        as_element_mut(&mut element_node)?.set_attribute_ns(
+            namespace_uri,
+            qualified_name,
+            value,
+        )?;
+        self.log.0.push(Mutation::SetAttribute {
+            element: element_path,
+            namespace_uri: Some(namespace_uri.to_string()),
+            qualified_name: qualified_name.to_string(),
+            value: value.to_string(),
+        });
+        Ok(())
+    }
+
+    ///
+    /// Perform, and record, `remove_attribute` on `element`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `element` is not part of the recorded document.
+    ///
+    /// Any exception raised by `Element::remove_attribute` is also returned.
+    ///
+    pub fn remove_attribute(&mut self, element: &RefNode, name: &str) -> Result<()> {
+        let element_path = self.path_to(element)?;
+        let mut element_node = element.clone();
+        let element = as_element_mut(&mut element_node)?;
+        if let Some(attribute) = element.get_attribute_node(name) {
+            let _safe_to_ignore = element.remove_attribute_node(attribute)?;
+            self.log.0.push(Mutation::RemoveAttribute {
+                element: element_path,
+                namespace_uri: None,
+                qualified_name: name.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    ///
+    /// Perform, and record, `remove_attribute_ns` on `element`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `element` is not part of the recorded document.
+    ///
+    /// Any exception raised by `Element::remove_attribute_ns` is also returned.
+    ///
+    pub fn remove_attribute_ns(
+        &mut self,
+        element: &RefNode,
+        namespace_uri: &str,
+        local_name: &str,
+    ) -> Result<()> {
+        let element_path = self.path_to(element)?;
+        let mut element_node = element.clone();
+        let element = as_element_mut(&mut element_node)?;
+        if let Some(attribute) = element.get_attribute_node_ns(namespace_uri, local_name) {
+            let qualified_name = attribute.node_name().to_string();
+            let _safe_to_ignore = element.remove_attribute_node(attribute)?;
+            self.log.0.push(Mutation::RemoveAttribute {
+                element: element_path,
+                namespace_uri: Some(namespace_uri.to_string()),
+                qualified_name,
+            });
+        }
+        Ok(())
+    }
+
+    ///
+    /// Replace, and record, the data of a `Text`, `CDATASection`, `Comment`, or
+    /// `ProcessingInstruction` node.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `node` is not part of the recorded document.
+    /// * `NOT_SUPPORTED_ERR`: Raised if `node` is not one of the node types listed above.
+    ///
+    pub fn set_data(&mut self, node: &RefNode, data: &str) -> Result<()> {
+        let node_path = self.path_to(node)?;
+        match node.node_type() {
+            NodeType::Text | NodeType::CData | NodeType::Comment => {}
+            NodeType::ProcessingInstruction => {
+                if !is_processing_instruction_data(data) {
+                    warn!("{}: '{}'", MSG_INVALID_PI_DATA, data);
+                    return Err(Error::Syntax);
+                }
+            }
+            _ => {
+                warn!("{}", MSG_INVALID_NODE_TYPE);
+                return Err(Error::NotSupported);
+            }
+        }
+        let mut node = node.clone();
+        node.set_node_value(data)?;
+        self.log.0.push(Mutation::SetData {
+            node: node_path,
+            data: data.to_string(),
+        });
+        Ok(())
+    }
+
+    ///
+    /// Returns the path from the recorded document to `node`.
+    ///
+    fn path_to(&self, node: &RefNode) -> Result<NodePath> {
+        let mut path = Vec::new();
+        let mut current = node.clone();
+        while let Some(parent) = current.parent_node() {
+            // this cannot fail, a node is always one of its parent's children.
+            let index = parent
+                .child_nodes()
+                .iter()
+                .position(|child| child == &current)
+                .unwrap();
+            path.insert(0, index);
+            current = parent;
+        }
+        if current == self.document {
+            Ok(path)
+        } else {
+            warn!("{}", MSG_WRONG_DOCUMENT);
+            Err(Error::WrongDocument)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+struct Tokens {
+    source: String,
+    tokens: Vec<(bool, String)>,
+}
+
+const KW_DOCUMENT: &str = "document";
+const KW_INSERT: &str = "insert";
+const KW_REMOVE: &str = "remove";
+const KW_SET_ATTRIBUTE: &str = "set-attribute";
+const KW_REMOVE_ATTRIBUTE: &str = "remove-attribute";
+const KW_SET_DATA: &str = "set-data";
+
+const KW_ELEMENT: &str = "element";
+const KW_TEXT: &str = "text";
+const KW_CDATA: &str = "cdata";
+const KW_COMMENT: &str = "comment";
+const KW_PI: &str = "pi";
+const KW_ENTITY_REF: &str = "entity-ref";
+
+const NONE_VALUE: &str = "-";
+const PATH_SEPARATOR: char = '/';
+
+// ------------------------------------------------------------------------------------------------
+
+impl Tokens {
+    ///
+    /// Split a line into tokens, each is either a bare word or a double-quoted string. The
+    /// boolean in each token is `true` if the token was quoted.
+    ///
+    fn new(s: &str) -> Result<Self> {
+        let mut tokens = Vec::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            } else if c == '"' {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Self::invalid(s),
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => token.push('\n'),
+                            Some('r') => token.push('\r'),
+                            Some('t') => token.push('\t'),
+                            Some(c @ '\\') | Some(c @ '"') => token.push(c),
+                            _ => return Self::invalid(s),
+                        },
+                        Some(c) => token.push(c),
+                    }
+                }
+                tokens.push((true, token));
+            } else {
+                let mut token = c.to_string();
+                while let Some(c) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    token.push(*c);
+                    let _safe_to_ignore = chars.next();
+                }
+                tokens.push((false, token));
+            }
+        }
+        tokens.reverse();
+        Ok(Self {
+            source: s.to_string(),
+            tokens,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    fn bare(&mut self) -> Result<String> {
+        match self.tokens.pop() {
+            Some((false, token)) => Ok(token),
+            _ => self.error(),
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        match self.tokens.pop() {
+            Some((true, token)) => Ok(token),
+            _ => self.error(),
+        }
+    }
+
+    fn optional(&mut self) -> Result<Option<String>> {
+        match self.tokens.pop() {
+            Some((true, token)) => Ok(Some(token)),
+            Some((false, token)) if token == NONE_VALUE => Ok(None),
+            _ => self.error(),
+        }
+    }
+
+    fn index(&mut self) -> Result<usize> {
+        let token = self.bare()?;
+        match token.parse::<usize>() {
+            Ok(index) => Ok(index),
+            Err(_) => self.error(),
+        }
+    }
+
+    fn path(&mut self) -> Result<NodePath> {
+        let token = self.bare()?;
+        if !token.starts_with(PATH_SEPARATOR) {
+            return self.error();
+        }
+        match token
+            .split(PATH_SEPARATOR)
+            .filter(|step| !step.is_empty())
+            .map(|step| step.parse::<usize>())
+            .collect::<std::result::Result<NodePath, _>>()
+        {
+            Ok(path) => Ok(path),
+            Err(_) => self.error(),
+        }
+    }
+
+    fn error<T>(&self) -> Result<T> {
+        Self::invalid(&self.source)
+    }
+
+    fn invalid<T>(s: &str) -> Result<T> {
+        warn!("{}: '{}'", MSG_INVALID_MUTATION, s);
+        Err(Error::Syntax)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn new_node(node: &RefNode) -> Result<NewNode> {
+    let name = node.node_name();
+    let value = node.node_value();
+    Ok(match node.node_type() {
+        NodeType::Element => NewNode::Element {
+            namespace_uri: name.namespace_uri().clone(),
+            qualified_name: name.to_string(),
+        },
+        NodeType::Text => NewNode::Text(value.unwrap_or_default()),
+        NodeType::CData => NewNode::CData(value.unwrap_or_default()),
+        NodeType::Comment => NewNode::Comment(value.unwrap_or_default()),
+        NodeType::ProcessingInstruction => NewNode::ProcessingInstruction {
+            target: name.to_string(),
+            data: value,
+        },
+        NodeType::EntityReference => NewNode::EntityReference(name.to_string()),
+        _ => {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::NotSupported);
+        }
+    })
+}
+
+///
+/// The attribute's value before normalization and escaping; replaying the value with
+/// `set_attribute` will result in the same `Attribute::value`.
+///
+pub(crate) fn raw_value(attribute: &RefNode) -> String {
+    attribute
+        .child_nodes()
+        .iter()
+        .filter_map(|child| child.node_value())
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+fn path(path: &[usize]) -> String {
+    if path.is_empty() {
+        PATH_SEPARATOR.to_string()
+    } else {
+        path.iter()
+            .map(|index| format!("{}{}", PATH_SEPARATOR, index))
+            .collect()
+    }
+}
+
+fn quoted(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            _ => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn optional(s: &Option<String>) -> String {
+    match s {
+        None => NONE_VALUE.to_string(),
+        Some(s) => quoted(s),
+    }
+}
+
+fn resolve(document: &RefNode, path: &[usize]) -> Result<RefNode> {
+    let mut current = document.clone();
+    for index in path {
+        let child = current.child_nodes().get(*index).cloned();
+        current = match child {
+            None => return Err(Error::NotFound),
+            Some(child) => child,
+        };
+    }
+    Ok(current)
+}
@@ -0,0 +1,177 @@
+/*!
+Provides [`NamedNodeMap`](struct.NamedNodeMap.html), a _live_ view of the attributes of an element
+corresponding to the DOM `NamedNodeMap` interface, returned by the
+[`NodeLists`](../trait.NodeLists.html) extension.
+
+The attributes of an element are kept in the order they were added, an attribute replaced by one
+with the same name keeps the position of the original, and this is the order used by `item`, by
+`attribute_names`, and when a document is displayed or serialized; so a parsed document is written
+with its attributes in their original order. The `HashMap` returned by `attributes` remains
+available for lookup by name.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::{as_attribute, as_document};
+use xml_dom::level2::ext::NodeLists;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("item"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+
+let attributes = root_node.live_attributes();
+root_node.set_attribute("id", "1").unwrap();
+root_node.set_attribute("name", "pen").unwrap();
+root_node.set_attribute("id", "2").unwrap();
+
+assert_eq!(attributes.length(), 2);
+let id_node = attributes.item(0).unwrap();
+assert_eq!(as_attribute(&id_node).unwrap().value().as_deref(), Some("2"));
+assert_eq!(attributes.item(1).unwrap().node_name().to_string(), "name");
+assert_eq!(root_node.to_string(), r#"<item id="2" name="pen"></item>"#);
+```
+*/
+
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::ordered_attributes;
+use crate::level2::traits::*;
+use crate::shared::name::Name;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A live view of the attributes of an element, in the order they were added, see the
+/// [module](index.html) documentation. For any node other than an element the map is always
+/// empty.
+///
+#[derive(Clone, Debug)]
+pub struct NamedNodeMap {
+    element: RefNode,
+}
+
+///
+/// An iterator over a [`NamedNodeMap`](struct.NamedNodeMap.html), which returns the attribute at
+/// each index in turn as the map is at the time it is reached.
+///
+#[derive(Clone, Debug)]
+pub struct NamedNodeMapIter {
+    map: NamedNodeMap,
+    index: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl NamedNodeMap {
+    ///
+    /// Returns the number of attributes currently in the map.
+    ///
+    pub fn length(&self) -> usize {
+        ordered_attributes(&self.element).len()
+    }
+
+    ///
+    /// Returns `true` if there are currently no attributes in the map.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.length() == 0
+    }
+
+    ///
+    /// Returns the attribute currently at `index` in the map, or `None` if `index` is greater than
+    /// or equal to the length of the map.
+    ///
+    pub fn item(&self, index: usize) -> Option<RefNode> {
+        ordered_attributes(&self.element)
+            .into_iter()
+            .nth(index)
+            .map(|(_, attribute)| attribute)
+    }
+
+    ///
+    /// Returns the attribute with the qualified name `name`, as `get_attribute_node` does.
+    ///
+    pub fn get_named_item(&self, name: &str) -> Option<RefNode> {
+        self.element.get_attribute_node(name)
+    }
+
+    ///
+    /// Returns the attribute with the local name `local_name` in the namespace `namespace_uri`,
+    /// as `get_attribute_node_ns` does.
+    ///
+    pub fn get_named_item_ns(&self, namespace_uri: &str, local_name: &str) -> Option<RefNode> {
+        self.element
+            .get_attribute_node_ns(namespace_uri, local_name)
+    }
+
+    ///
+    /// Returns a snapshot of the names of the attributes currently in the map, in order.
+    ///
+    pub fn names(&self) -> Vec<Name> {
+        ordered_attributes(&self.element)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    ///
+    /// Returns an iterator over the map.
+    ///
+    pub fn iter(&self) -> NamedNodeMapIter {
+        NamedNodeMapIter {
+            map: self.clone(),
+            index: 0,
+        }
+    }
+
+    ///
+    /// Returns a snapshot of the attributes currently in the map, in order.
+    ///
+    pub fn to_vec(&self) -> Vec<RefNode> {
+        self.iter().collect()
+    }
+
+    pub(crate) fn new(element: &RefNode) -> Self {
+        Self {
+            element: element.clone(),
+        }
+    }
+}
+
+impl IntoIterator for NamedNodeMap {
+    type Item = RefNode;
+    type IntoIter = NamedNodeMapIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        NamedNodeMapIter {
+            map: self,
+            index: 0,
+        }
+    }
+}
+
+impl IntoIterator for &NamedNodeMap {
+    type Item = RefNode;
+    type IntoIter = NamedNodeMapIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for NamedNodeMapIter {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.map.item(self.index)?;
+        self.index += 1;
+        Some(next)
+    }
+}
@@ -1,13 +1,48 @@
 /*!
-This module provides support types for the [`Namespaced`](trait.Namespaced.html) trait.
+This module provides support types for the [`Namespaced`](trait.Namespaced.html) trait, and the
+[`NamespaceContext`](struct.NamespaceContext.html) type which holds a set of in-scope namespace
+declarations independently of any node.
+
+A node detached from its document, or parsed on its own, loses the declarations made by the
+ancestors it would otherwise have; a context captured from those ancestors, or built by hand, may
+be supplied to the [serializer](../serializer/struct.WriteOptions.html#structfield.namespace_context)
+and to [`read_fragment`](../../../parser/fn.read_fragment.html) so that the prefixes used within
+the node still resolve.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::namespaced::NamespaceContext;
+
+let context = NamespaceContext::new()
+    .with_namespace(Some("dc"), "http://purl.org/dc/elements/1.1/")
+    .with_namespace(None, "urn:example:catalog");
+
+assert_eq!(
+    context.namespace_uri(Some("dc")).as_deref(),
+    Some("http://purl.org/dc/elements/1.1/")
+);
+assert_eq!(context.namespace_uri(None).as_deref(), Some("urn:example:catalog"));
+assert_eq!(context.namespace_uri(Some("rdf")), None);
+```
 */
 
+use crate::level2::convert::{as_attribute, as_document, as_element};
+#[cfg(any(feature = "quick_parser", feature = "xml_rs"))]
+use crate::level2::ext::traits::DocumentRename;
 use crate::level2::ext::traits::Namespaced;
 use crate::level2::node_impl::{Extension, RefNode};
-use crate::level2::traits::{Node, NodeType};
+use crate::level2::traits::{Attribute, Element, Node, NodeType};
 use crate::shared::error::{
-    Error, Result, MSG_INVALID_EXTENSION, MSG_INVALID_NODE_TYPE, MSG_WEAK_REF,
+    Error, Result, MSG_INVALID_EXTENSION, MSG_INVALID_NODE_TYPE, MSG_PREFIX_REBOUND,
+    MSG_PREFIX_UNDECLARED, MSG_RESERVED_NAMESPACE, MSG_RESERVED_PREFIX, MSG_WEAK_REF,
 };
+use crate::shared::name::Name;
+use crate::shared::syntax::{
+    XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_NS_ATTRIBUTE, XML_NS_SEPARATOR, XML_NS_URI,
+};
+use std::collections::HashMap;
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -27,7 +62,18 @@ pub enum NamespacePrefix {
     Some(String),
 }
 
+///
+/// A set of namespace declarations, mapping prefixes, or `None` for the default namespace, to
+/// namespace URIs, that are in scope at some point in a document. An empty namespace URI for the
+/// default namespace records that it has been un-declared.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NamespaceContext {
+    mappings: HashMap<Option<String>, String>,
+}
+
 #[doc(hidden)]
+#[allow(dead_code)]
 pub(crate) trait MutNamespaced: Namespaced {
     fn insert_mapping(
         &mut self,
@@ -38,6 +84,97 @@ pub(crate) trait MutNamespaced: Namespaced {
     fn normalize_mappings(&mut self) -> Result<()>;
 }
 
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The namespace of `element`, from its name if present, else by resolving its prefix.
+///
+pub(crate) fn element_namespace(element: &RefNode) -> Option<String> {
+    let name = element.node_name();
+    match name.namespace_uri() {
+        Some(namespace_uri) => Some(namespace_uri.clone()),
+        None => resolve_declared_prefix(element, name.prefix().as_deref()),
+    }
+}
+
+///
+/// Find the `xmlns` attribute declaring `prefix` on `element` or its nearest ancestor. A
+/// declaration with an empty value, such as `xmlns=""`, un-declares the prefix and so `None` is
+/// returned without looking any further up the tree.
+///
+pub(crate) fn resolve_declared_prefix(element: &RefNode, prefix: Option<&str>) -> Option<String> {
+    let mut current = Some(element.clone());
+    while let Some(node) = current {
+        if let Some(namespace_uri) = declared_namespace(&node, prefix) {
+            return Some(namespace_uri).filter(|namespace_uri| !namespace_uri.is_empty());
+        }
+        current = node.parent_node();
+    }
+    None
+}
+
+///
+/// Give each element and attribute below `parent_node`, a document or an element, the namespace
+/// URI its prefix, or the default namespace for an un-prefixed element, resolves to by the `xmlns`
+/// attributes in scope. This is used by the parsers, which create nodes from their qualified names
+/// as read.
+///
+#[cfg(any(feature = "quick_parser", feature = "xml_rs"))]
+pub(crate) fn resolve_names(parent_node: &RefNode) -> Result<()> {
+    let document_node = match parent_node.owner_document() {
+        Some(document_node) => document_node,
+        None => parent_node.clone(),
+    };
+    let mut renames: Vec<(RefNode, String)> = Vec::new();
+    for child in parent_node.borrow().i_child_nodes.iter() {
+        resolved_names(child, &mut renames);
+    }
+    for (node, namespace_uri) in renames {
+        let qualified_name = node.node_name().to_string();
+        let _safe_to_ignore =
+            document_node.rename_node(node, Some(&namespace_uri), &qualified_name)?;
+    }
+    Ok(())
+}
+
+///
+/// Check the declaration of `prefix` (or the default namespace if `None`) as `namespace_uri`
+/// against the reserved prefixes and namespaces of
+/// [Namespaces in XML 1.0 §3](https://www.w3.org/TR/xml-names/#ns-decl), unless the owner
+/// document of `element` has the lenient namespaces option set. An empty `namespace_uri`
+/// un-declares the default namespace, a prefix may not be un-declared in XML 1.0.
+///
+pub(crate) fn check_reserved_namespace(
+    element: &RefNode,
+    prefix: Option<&str>,
+    namespace_uri: &str,
+) -> Result<()> {
+    if lenient_namespaces(element) {
+        return Ok(());
+    }
+    let reserved_prefix = match prefix {
+        Some(XML_NS_ATTRIBUTE) => namespace_uri != XML_NS_URI,
+        Some(XMLNS_NS_ATTRIBUTE) => true,
+        _ => false,
+    };
+    if reserved_prefix {
+        warn!("{}: '{:?}'", MSG_RESERVED_PREFIX, prefix);
+        Err(Error::Namespace)
+    } else if prefix.is_some() && namespace_uri.is_empty() {
+        warn!("{}: '{:?}'", MSG_PREFIX_UNDECLARED, prefix);
+        Err(Error::Namespace)
+    } else if prefix != Some(XML_NS_ATTRIBUTE)
+        && (namespace_uri == XML_NS_URI || namespace_uri == XMLNS_NS_URI)
+    {
+        warn!("{}: '{}'", MSG_RESERVED_NAMESPACE, namespace_uri);
+        Err(Error::Namespace)
+    } else {
+        Ok(())
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
@@ -54,30 +191,21 @@ impl NamespacePrefix {
     /// Returns `true` of this is a `NamespacePrefix::None` value, otherwise `false`.
     ///
     pub fn is_none(&self) -> bool {
-        match *self {
-            NamespacePrefix::None => true,
-            _ => false,
-        }
+        matches!(*self, NamespacePrefix::None)
     }
 
     ///
     /// Returns `true` of this is a `NamespacePrefix::Default` value, otherwise `false`.
     ///
     pub fn is_default(&self) -> bool {
-        match *self {
-            NamespacePrefix::Default => true,
-            _ => false,
-        }
+        matches!(*self, NamespacePrefix::Default)
     }
 
     ///
     /// Returns `true` of this is a `NamespacePrefix::Some` value, otherwise `false`.
     ///
     pub fn is_some(&self) -> bool {
-        match *self {
-            NamespacePrefix::Some(_) => true,
-            _ => false,
-        }
+        matches!(*self, NamespacePrefix::Some(_))
     }
 
     ///
@@ -94,6 +222,137 @@ impl NamespacePrefix {
 
 // ------------------------------------------------------------------------------------------------
 
+impl NamespaceContext {
+    ///
+    /// Construct a new, empty, context.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Construct a context holding the declarations in scope at `node`; those made by the
+    /// element from which a namespace lookup on `node` starts, and by each of its ancestors, the
+    /// nearest declaration of each prefix taking precedence.
+    ///
+    pub fn from_node(node: &RefNode) -> Self {
+        let mut context = Self::new();
+        let mut current = lookup_element(node);
+        while let Some(element) = current {
+            if element.node_type() != NodeType::Element {
+                break;
+            }
+            for (name, attribute) in element.attributes() {
+                let prefix = match name.prefix() {
+                    Some(prefix) if prefix == XMLNS_NS_ATTRIBUTE => Some(name.local_name().clone()),
+                    None if name.local_name() == XMLNS_NS_ATTRIBUTE => None,
+                    _ => continue,
+                };
+                let _safe_to_ignore = context
+                    .mappings
+                    .entry(prefix)
+                    .or_insert_with(|| attribute.value().unwrap_or_default());
+            }
+            current = element.parent_node();
+        }
+        context
+    }
+
+    ///
+    /// Returns this context with `prefix` mapped to `namespace_uri`, replacing any existing
+    /// mapping.
+    ///
+    pub fn with_namespace(mut self, prefix: Option<&str>, namespace_uri: &str) -> Self {
+        let _safe_to_ignore = self.insert(prefix, namespace_uri);
+        self
+    }
+
+    ///
+    /// Map `prefix` to `namespace_uri`, returning the namespace URI it was previously mapped to,
+    /// if any.
+    ///
+    pub fn insert(&mut self, prefix: Option<&str>, namespace_uri: &str) -> Option<String> {
+        self.mappings
+            .insert(prefix.map(String::from), namespace_uri.to_string())
+    }
+
+    ///
+    /// Returns the namespace URI `prefix` is mapped to, if any; an un-declared default namespace
+    /// returns `None`.
+    ///
+    pub fn namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        self.mappings
+            .get(&prefix.map(String::from))
+            .filter(|namespace_uri| !namespace_uri.is_empty())
+            .cloned()
+    }
+
+    ///
+    /// Returns all the mappings in this context, as prefix and namespace URI pairs ordered by
+    /// prefix, with the default namespace first.
+    ///
+    pub fn declarations(&self) -> Vec<(Option<String>, String)> {
+        let mut declarations: Vec<(Option<String>, String)> = self
+            .mappings
+            .iter()
+            .map(|(prefix, namespace_uri)| (prefix.clone(), namespace_uri.clone()))
+            .collect();
+        declarations.sort();
+        declarations
+    }
+
+    ///
+    /// Returns `true` if this context holds no mappings.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Collect the elements in the tree rooted at `node`, and their attributes, whose names may be
+/// given the namespace URI they resolve to; an un-prefixed element takes the default namespace,
+/// un-prefixed attributes and namespace declarations are never changed. Deferred content, see the
+/// `lazy` module, is not constructed; its names are resolved when it is.
+///
+#[cfg(any(feature = "quick_parser", feature = "xml_rs"))]
+fn resolved_names(node: &RefNode, renames: &mut Vec<(RefNode, String)>) {
+    if node.node_type() != NodeType::Element {
+        return;
+    }
+    let name = node.node_name();
+    if let Some(namespace_uri) = resolve_declared_prefix(node, name.prefix().as_deref()) {
+        renames.push((node.clone(), namespace_uri));
+    }
+    for (attribute_name, attribute) in node.attributes() {
+        match attribute_name.prefix() {
+            Some(prefix) if prefix != XMLNS_NS_ATTRIBUTE => {
+                if let Some(namespace_uri) = resolve_declared_prefix(node, Some(prefix)) {
+                    renames.push((attribute, namespace_uri));
+                }
+            }
+            _ => {}
+        }
+    }
+    for child in node.borrow().i_child_nodes.iter() {
+        resolved_names(child, renames);
+    }
+}
+
+fn lenient_namespaces(element_node: &RefNode) -> bool {
+    if let Some(document) = element_node.owner_document() {
+        let ref_document = document.borrow();
+        if let Extension::Document { i_options, .. } = &ref_document.i_extension {
+            return i_options.has_lenient_namespaces();
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+        }
+    }
+    false
+}
+
 fn add_namespaces(element_node: &RefNode) -> bool {
     if let Some(document) = element_node.owner_document() {
         let ref_document = document.borrow();
@@ -106,6 +365,42 @@ fn add_namespaces(element_node: &RefNode) -> bool {
     false
 }
 
+///
+/// Returns the value of the `xmlns` attribute declaring `prefix` on `element` itself, an empty
+/// value is an un-declaration.
+///
+fn declared_namespace(element: &RefNode, prefix: Option<&str>) -> Option<String> {
+    let element = as_element(element).ok()?;
+    element
+        .attributes()
+        .iter()
+        .find(|(name, _)| match prefix {
+            None => name.prefix().is_none() && name.local_name() == XMLNS_NS_ATTRIBUTE,
+            Some(prefix) => {
+                name.prefix().as_deref() == Some(XMLNS_NS_ATTRIBUTE) && name.local_name() == prefix
+            }
+        })
+        .map(|(_, attribute)| attribute.value().unwrap_or_default())
+}
+
+///
+/// Returns the node from which a DOM Level 3 namespace lookup on `node` starts; the node itself
+/// for an element, the document element of a document, the owner element of an attribute, and
+/// the parent of other nodes. A document type, or document fragment, has no namespace context.
+///
+fn lookup_element(node: &RefNode) -> Option<RefNode> {
+    match node.node_type() {
+        NodeType::Element => Some(node.clone()),
+        NodeType::Document => as_document(node).ok()?.document_element(),
+        NodeType::Attribute => as_attribute(node).ok()?.owner_element(),
+        NodeType::DocumentType
+        | NodeType::DocumentFragment
+        | NodeType::Entity
+        | NodeType::Notation => None,
+        _ => node.parent_node(),
+    }
+}
+
 impl Namespaced for RefNode {
     fn contains_mapping(&self, prefix: Option<&str>) -> bool {
         if !add_namespaces(self) {
@@ -149,6 +444,7 @@ impl Namespaced for RefNode {
             return None;
         }
         match self.get_namespace(prefix) {
+            Some(namespace_uri) if namespace_uri.is_empty() => None,
             None => {
                 let ref_self = self.borrow();
                 match &ref_self.i_parent_node {
@@ -164,12 +460,73 @@ impl Namespaced for RefNode {
         }
     }
 
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        let mut current = lookup_element(self);
+        while let Some(node) = current {
+            if node.node_type() == NodeType::Element {
+                let name = node.node_name();
+                match name.namespace_uri() {
+                    Some(namespace_uri) if name.prefix().as_deref() == prefix => {
+                        return Some(namespace_uri.clone());
+                    }
+                    _ => {
+                        if let Some(namespace_uri) = declared_namespace(&node, prefix) {
+                            return Some(namespace_uri)
+                                .filter(|namespace_uri| !namespace_uri.is_empty());
+                        }
+                    }
+                }
+            }
+            current = node.parent_node();
+        }
+        None
+    }
+
+    fn lookup_prefix(&self, namespace_uri: &str) -> Option<String> {
+        if namespace_uri.is_empty() {
+            return None;
+        }
+        let in_scope = |prefix: &str| {
+            self.lookup_namespace_uri(Some(prefix)).as_deref() == Some(namespace_uri)
+        };
+        let mut current = lookup_element(self);
+        while let Some(node) = current {
+            if node.node_type() == NodeType::Element {
+                let name = node.node_name();
+                if let Some(prefix) = name.prefix() {
+                    if name.namespace_uri().as_deref() == Some(namespace_uri) && in_scope(prefix) {
+                        return Some(prefix.clone());
+                    }
+                }
+                let mut declared: Vec<String> = node
+                    .attributes()
+                    .into_iter()
+                    .filter(|(name, attribute)| {
+                        name.prefix().as_deref() == Some(XMLNS_NS_ATTRIBUTE)
+                            && attribute.value().as_deref() == Some(namespace_uri)
+                    })
+                    .map(|(name, _)| name.local_name().clone())
+                    .collect();
+                declared.sort();
+                if let Some(prefix) = declared.into_iter().find(|prefix| in_scope(prefix)) {
+                    return Some(prefix);
+                }
+            }
+            current = node.parent_node();
+        }
+        None
+    }
+
+    fn is_default_namespace(&self, namespace_uri: &str) -> bool {
+        self.lookup_namespace_uri(None).unwrap_or_default() == namespace_uri
+    }
+
     fn contains_mapped_namespace(&self, namespace_uri: &str) -> bool {
         !self.get_prefix(namespace_uri).is_none()
     }
 
     fn get_prefix(&self, namespace_uri: &str) -> NamespacePrefix {
-        if !add_namespaces(self) {
+        if !add_namespaces(self) || namespace_uri.is_empty() {
             return NamespacePrefix::None;
         }
         let ref_self = self.borrow();
@@ -193,23 +550,66 @@ impl Namespaced for RefNode {
     }
 
     fn resolve_prefix(&self, namespace_uri: &str) -> NamespacePrefix {
-        if !add_namespaces(self) {
+        if !add_namespaces(self) || namespace_uri.is_empty() {
             return NamespacePrefix::None;
         }
-        match self.get_prefix(namespace_uri) {
-            NamespacePrefix::None => {
-                let ref_self = self.borrow();
-                match &ref_self.i_parent_node {
-                    None => NamespacePrefix::None,
-                    Some(parent) => {
-                        let parent = parent.clone();
-                        let parent_node = parent.upgrade().expect(MSG_WEAK_REF);
-                        parent_node.resolve_prefix(namespace_uri)
-                    }
+        //
+        // A prefix mapped by an ancestor is only in scope if no element closer to this one has
+        // re-mapped, or un-declared, the same prefix.
+        //
+        let mut shadowed: Vec<Option<String>> = Vec::new();
+        let mut current = Some(self.clone());
+        while let Some(element) = current {
+            let ref_element = element.borrow();
+            if let Extension::Element { i_namespaces, .. } = &ref_element.i_extension {
+                let found = i_namespaces.iter().find(|(prefix, value)| {
+                    value.as_str() == namespace_uri && !shadowed.contains(prefix)
+                });
+                match found {
+                    Some((None, _)) => return NamespacePrefix::Default,
+                    Some((Some(prefix), _)) => return NamespacePrefix::new_some(prefix),
+                    None => shadowed.extend(i_namespaces.keys().cloned()),
                 }
             }
-            found => found,
+            current = ref_element
+                .i_parent_node
+                .as_ref()
+                .map(|parent| parent.clone().upgrade().expect(MSG_WEAK_REF));
         }
+        NamespacePrefix::None
+    }
+
+    fn declare_namespaces(&mut self, declarations: &[(Option<&str>, &str)]) -> Result<()> {
+        if self.node_type() != NodeType::Element {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let mut pending: Vec<(Option<&str>, String, &str)> = Vec::new();
+        for (prefix, namespace_uri) in declarations {
+            let qualified_name = match prefix {
+                None => XMLNS_NS_ATTRIBUTE.to_string(),
+                Some(prefix) => format!("{}{}{}", XMLNS_NS_ATTRIBUTE, XML_NS_SEPARATOR, prefix),
+            };
+            let _safe_to_ignore = Name::new_ns(XMLNS_NS_URI, &qualified_name)?;
+            check_reserved_namespace(self, *prefix, namespace_uri)?;
+            let declared = pending
+                .iter()
+                .find(|(pending_prefix, _, _)| pending_prefix == prefix)
+                .map(|(_, _, namespace_uri)| namespace_uri.to_string())
+                .or_else(|| declared_namespace(self, *prefix));
+            match declared {
+                Some(declared) if declared == *namespace_uri => {}
+                Some(declared) => {
+                    warn!("{}: '{}'", MSG_PREFIX_REBOUND, declared);
+                    return Err(Error::Namespace);
+                }
+                None => pending.push((*prefix, qualified_name, namespace_uri)),
+            }
+        }
+        for (_, qualified_name, namespace_uri) in pending {
+            self.set_attribute_ns(XMLNS_NS_URI, &qualified_name, namespace_uri)?;
+        }
+        Ok(())
     }
 }
 
@@ -225,7 +625,10 @@ impl MutNamespaced for RefNode {
         let mut mut_self = self.borrow_mut();
         if mut_self.i_node_type == NodeType::Element {
             if let Extension::Element { i_namespaces, .. } = &mut mut_self.i_extension {
-                Ok(i_namespaces.insert(prefix.map(String::from), namespace_uri.to_string()))
+                let prefix = prefix.map(String::from);
+                #[cfg(feature = "bench")]
+                crate::level2::ext::bench::count_map_insert(i_namespaces, &prefix);
+                Ok(i_namespaces.insert(prefix, namespace_uri.to_string()))
             } else {
                 warn!("{}", MSG_INVALID_EXTENSION);
                 Err(Error::InvalidState)
@@ -334,8 +737,8 @@ mod tests {
         // prefix
         let ns_result = Some(XSD.to_string());
 
-        assert_eq!(namespaced.contains_mapping(None), false);
-        assert_eq!(namespaced.contains_mapping(Some("xsd")), true);
+        assert!(!namespaced.contains_mapping(None));
+        assert!(namespaced.contains_mapping(Some("xsd")));
         assert_eq!(namespaced.get_namespace(None), None);
         assert_eq!(namespaced.get_namespace(Some("xsd")), ns_result);
         assert_eq!(namespaced.resolve_namespace(None), None);
@@ -344,8 +747,8 @@ mod tests {
         // namespace
         let prefix_result = NamespacePrefix::new_some("xsd");
 
-        assert_eq!(namespaced.contains_mapped_namespace(HTML), false);
-        assert_eq!(namespaced.contains_mapped_namespace(XSD), true);
+        assert!(!namespaced.contains_mapped_namespace(HTML));
+        assert!(namespaced.contains_mapped_namespace(XSD));
         assert_eq!(namespaced.get_prefix(XSD), prefix_result);
         assert_eq!(namespaced.resolve_prefix(XSD), prefix_result);
     }
@@ -362,8 +765,8 @@ mod tests {
         // prefix
         let ns_result = Some(XSD.to_string());
 
-        assert_eq!(namespaced.contains_mapping(None), true);
-        assert_eq!(namespaced.contains_mapping(Some("xsd")), false);
+        assert!(namespaced.contains_mapping(None));
+        assert!(!namespaced.contains_mapping(Some("xsd")));
         assert_eq!(namespaced.get_namespace(None), ns_result);
         assert_eq!(namespaced.get_namespace(Some("xsd")), None);
         assert_eq!(namespaced.resolve_namespace(None), ns_result);
@@ -372,8 +775,8 @@ mod tests {
         // namespace
         let prefix_result = NamespacePrefix::Default;
 
-        assert_eq!(namespaced.contains_mapped_namespace(HTML), false);
-        assert_eq!(namespaced.contains_mapped_namespace(XSD), true);
+        assert!(!namespaced.contains_mapped_namespace(HTML));
+        assert!(namespaced.contains_mapped_namespace(XSD));
         assert_eq!(namespaced.get_prefix(XSD), prefix_result);
         assert_eq!(namespaced.resolve_prefix(XSD), prefix_result);
     }
@@ -449,4 +852,70 @@ mod tests {
             NamespacePrefix::new_some("xslt")
         );
     }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_tree_undeclared_default() {
+        let mut document = make_document_node();
+        let mut ref_node = make_node(&mut document, "element");
+        let ref_root = as_element_namespaced_mut(&mut ref_node).unwrap();
+        ref_root.insert_mapping(None, EX);
+
+        let mut child_node = make_node(&mut document, "child");
+        {
+            let ref_child_ns = as_element_namespaced_mut(&mut child_node).unwrap();
+            ref_child_ns.insert_mapping(None, "");
+        }
+        ref_root.append_child(child_node.clone());
+        let mut leaf_node = make_node(&mut document, "leaf");
+        {
+            let ref_child = as_element_namespaced_mut(&mut child_node).unwrap();
+            ref_child.append_child(leaf_node.clone());
+        }
+
+        assert_eq!(ref_root.resolve_namespace(None), Some(EX.to_string()));
+        assert_eq!(ref_root.resolve_prefix(EX), NamespacePrefix::Default);
+
+        let ns_leaf = &leaf_node as RefNamespaced<'_>;
+        assert!(as_element_namespaced(&child_node)
+            .unwrap()
+            .contains_mapping(None));
+        assert_eq!(ns_leaf.resolve_namespace(None), None);
+        assert_eq!(ns_leaf.resolve_prefix(EX), NamespacePrefix::None);
+        assert_eq!(ns_leaf.resolve_prefix(""), NamespacePrefix::None);
+
+        // re-declaring the default below the un-declaration.
+        {
+            let ref_leaf_ns = as_element_namespaced_mut(&mut leaf_node).unwrap();
+            ref_leaf_ns.insert_mapping(None, HTML);
+        }
+        let ns_leaf = &leaf_node as RefNamespaced<'_>;
+        assert_eq!(ns_leaf.resolve_namespace(None), Some(HTML.to_string()));
+        assert_eq!(ns_leaf.resolve_prefix(HTML), NamespacePrefix::Default);
+    }
+
+    #[test]
+    fn test_undeclare_attributes() {
+        const XMLNS: &str = "http://www.w3.org/2000/xmlns/";
+        let mut document = make_document_node();
+        let mut ref_node = make_node(&mut document, "element");
+        let element = as_element_mut(&mut ref_node).unwrap();
+        assert!(element.set_attribute_ns(XMLNS, "xmlns:ex", EX).is_ok());
+        assert!(element.set_attribute_ns(XMLNS, "xmlns", "").is_ok());
+        assert_eq!(
+            element.set_attribute_ns(XMLNS, "xmlns:ex", ""),
+            Err(crate::shared::error::Error::Namespace)
+        );
+
+        let namespaced = as_element_namespaced(&ref_node).unwrap();
+        assert_eq!(namespaced.get_namespace(Some("ex")), Some(EX.to_string()));
+        assert!(namespaced.get_namespace(Some("xmlns")).is_none());
+        assert_eq!(namespaced.get_namespace(None), Some(String::new()));
+        assert_eq!(namespaced.resolve_namespace(None), None);
+        assert_eq!(
+            namespaced.lookup_namespace_uri(Some("ex")),
+            Some(EX.to_string())
+        );
+        assert_eq!(namespaced.lookup_namespace_uri(None), None);
+    }
 }
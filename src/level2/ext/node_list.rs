@@ -0,0 +1,275 @@
+/*!
+Provides [`NodeList`](struct.NodeList.html), a _live_ list of nodes corresponding to the DOM
+`NodeList` interface, returned by the [`NodeLists`](../trait.NodeLists.html) extension.
+
+Unlike the `Vec` returned by `child_nodes` and `get_elements_by_tag_name`, which is a snapshot
+taken at the time of the call, a `NodeList` holds only the node it was requested from and the
+selection made, and each access reflects the tree as it is at that moment. As in the DOM, items are
+addressed by index, and so iterating over a list while removing the nodes it selects skips the
+node that follows each one removed; to modify the tree freely while iterating use a snapshot, see
+[Mutation During Iteration](../query/index.html#mutation-during-iteration).
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::NodeLists;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("order"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+
+let items = document_node.live_elements_by_tag_name("item");
+assert_eq!(items.length(), 0);
+
+let _safe_to_ignore = root_node.append_child(document.create_element("item").unwrap()).unwrap();
+let _safe_to_ignore = root_node.append_child(document.create_element("item").unwrap()).unwrap();
+assert_eq!(items.length(), 2);
+assert_eq!(items.iter().count(), 2);
+```
+*/
+
+use crate::level2::ext::named_node_map::NamedNodeMap;
+use crate::level2::ext::traits::NodeLists;
+use crate::level2::node_impl::{hydrate_children, RefNode};
+use crate::level2::trait_impls::{namespaced_name_match, WILD_CARD};
+use crate::level2::traits::*;
+use crate::shared::name::Name;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A live list of nodes, see the [module](index.html) documentation. The DOM `item` method
+/// provides indexed access, as the `Index` trait cannot return a node that is not stored in the
+/// list itself.
+///
+#[derive(Clone, Debug)]
+pub struct NodeList {
+    root: RefNode,
+    selection: Selection,
+}
+
+///
+/// An iterator over a [`NodeList`](struct.NodeList.html), which returns the item at each index in
+/// turn as the list is at the time it is reached.
+///
+#[derive(Clone, Debug)]
+pub struct NodeListIter {
+    list: NodeList,
+    index: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl NodeList {
+    ///
+    /// Returns the number of nodes currently in the list.
+    ///
+    pub fn length(&self) -> usize {
+        match &self.selection {
+            Selection::Children => {
+                hydrate_children(&self.root);
+                self.root.borrow().i_child_nodes.len()
+            }
+            _ => {
+                let mut count = 0;
+                let _safe_to_ignore = self.find_element(|_| {
+                    count += 1;
+                    false
+                });
+                count
+            }
+        }
+    }
+
+    ///
+    /// Returns `true` if there are currently no nodes in the list.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.item(0).is_none()
+    }
+
+    ///
+    /// Returns the node currently at `index` in the list, or `None` if `index` is greater than or
+    /// equal to the length of the list.
+    ///
+    pub fn item(&self, index: usize) -> Option<RefNode> {
+        match &self.selection {
+            Selection::Children => {
+                hydrate_children(&self.root);
+                self.root.borrow().i_child_nodes.get(index).cloned()
+            }
+            _ => {
+                let mut remaining = index;
+                self.find_element(|_| {
+                    if remaining == 0 {
+                        true
+                    } else {
+                        remaining -= 1;
+                        false
+                    }
+                })
+            }
+        }
+    }
+
+    ///
+    /// Returns an iterator over the list.
+    ///
+    pub fn iter(&self) -> NodeListIter {
+        NodeListIter {
+            list: self.clone(),
+            index: 0,
+        }
+    }
+
+    ///
+    /// Returns a snapshot of the nodes currently in the list.
+    ///
+    pub fn to_vec(&self) -> Vec<RefNode> {
+        self.iter().collect()
+    }
+
+    fn new(root: &RefNode, selection: Selection) -> Self {
+        Self {
+            root: root.clone(),
+            selection,
+        }
+    }
+
+    ///
+    /// Visit the selected elements in document order, without recursion, until `visit` returns
+    /// `true`; returning that element. The elements of a document are those of its current
+    /// document element.
+    ///
+    fn find_element<F>(&self, mut visit: F) -> Option<RefNode>
+    where
+        F: FnMut(&RefNode) -> bool,
+    {
+        let start = match self.root.node_type() {
+            NodeType::Element => self.root.clone(),
+            NodeType::Document => self
+                .root
+                .borrow()
+                .i_child_nodes
+                .iter()
+                .find(|child| child.node_type() == NodeType::Element)
+                .cloned()?,
+            _ => return None,
+        };
+        let mut stack = vec![start];
+        while let Some(element) = stack.pop() {
+            if self.selection.matches(&element.borrow().i_name) && visit(&element) {
+                return Some(element);
+            }
+            hydrate_children(&element);
+            stack.extend(
+                element
+                    .borrow()
+                    .i_child_nodes
+                    .iter()
+                    .rev()
+                    .filter(|child| child.node_type() == NodeType::Element)
+                    .cloned(),
+            );
+        }
+        None
+    }
+}
+
+impl IntoIterator for NodeList {
+    type Item = RefNode;
+    type IntoIter = NodeListIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        NodeListIter {
+            list: self,
+            index: 0,
+        }
+    }
+}
+
+impl IntoIterator for &NodeList {
+    type Item = RefNode;
+    type IntoIter = NodeListIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for NodeListIter {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.list.item(self.index)?;
+        self.index += 1;
+        Some(next)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodeLists for RefNode {
+    fn live_child_nodes(&self) -> NodeList {
+        NodeList::new(self, Selection::Children)
+    }
+
+    fn live_elements_by_tag_name(&self, tag_name: &str) -> NodeList {
+        NodeList::new(self, Selection::TagName(tag_name.to_string()))
+    }
+
+    fn live_elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> NodeList {
+        NodeList::new(
+            self,
+            Selection::TagNameNs(namespace_uri.to_string(), local_name.to_string()),
+        )
+    }
+
+    fn live_attributes(&self) -> NamedNodeMap {
+        NamedNodeMap::new(self)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Selection {
+    fn matches(&self, name: &Name) -> bool {
+        match self {
+            Selection::Children => true,
+            Selection::TagName(tag_name) => {
+                tag_name == WILD_CARD || name.matches_qualified_name(tag_name)
+            }
+            Selection::TagNameNs(namespace_uri, local_name) => namespaced_name_match(
+                name.namespace_uri().as_deref(),
+                name.local_name(),
+                namespace_uri,
+                local_name,
+            ),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The nodes selected by a list; the children of its root, or the elements of its root, itself
+/// included, that match a name as for `get_elements_by_tag_name` and
+/// `get_elements_by_tag_name_ns`.
+///
+#[derive(Clone, Debug)]
+enum Selection {
+    Children,
+    TagName(String),
+    TagNameNs(String, String),
+}
@@ -0,0 +1,664 @@
+/*!
+Provides a writer that produces a normalized, line-oriented, form of a tree so that line-based
+diff tools produce meaningful results when comparing generated documents. The normalized form
+does not preserve the tree exactly, but two trees that differ only in the following ways produce
+the same output:
+
+* the order of attributes, which are written sorted by name;
+* insignificant white space, text outside an `xml:space="preserve"` region is trimmed and runs of
+  white space are collapsed, and each element, comment, and processing instruction is written on
+  its own indented line;
+* the form of empty elements, which are always written with a separate end tag;
+* the choice of namespace prefixes, which are replaced with prefixes assigned in document order,
+  and declared on the element where each namespace is first used.
+
+This module also provides the configuration for
+[`normalize_document`](../trait.DocumentNormalize.html#tymethod.normalize_document), from DOM
+Level 3, which modifies a document in place; merging adjacent text nodes, as `Node::normalize`
+does, and then applying the parameters set in a [`DOMConfiguration`](struct.DOMConfiguration.html):
+
+* `entities`, if `false` each entity reference is replaced by its expansion, either its children
+  or the replacement text of an internal entity declared in the document type; references to
+  other entities are kept;
+* `comments`, if `false` all comments are removed;
+* `cdata-sections`, if `false` each CDATA section is replaced by a text node, and merged with any
+  adjacent text;
+* `split-cdata-sections`, if `true` a CDATA section that is kept and contains the end delimiter
+  `"]]>"` is replaced by a sequence of CDATA sections split between `"]]"` and `">"`, if `false`
+  such a section is reported as an `Error::Syntax`;
+* `namespaces`, if `true` a namespace declaration is added to an element wherever the prefix of a
+  namespaced element or attribute name would not otherwise resolve to its namespace;
+* `element-content-whitespace`, if `false` white space only text is removed from element content,
+  as determined by
+  [`is_element_content_whitespace`](../trait.TextWhitespace.html#tymethod.is_element_content_whitespace);
+  as no DTD validation is performed, elements that are not declared in the internal subset are
+  in element content if they also contain elements, unless within an `xml:space="preserve"`
+  region.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::normalize::{normalize_for_diff, NormalizeOptions};
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("config"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let mut entry_node = document.create_element("entry").unwrap();
+entry_node.set_attribute("value", "1").unwrap();
+entry_node.set_attribute("key", "a").unwrap();
+let _safe_to_ignore = root_node.append_child(entry_node).unwrap();
+
+assert_eq!(
+    normalize_for_diff(&document_node, &NormalizeOptions::default()).unwrap(),
+    "<config>\n  <entry key=\"a\" value=\"1\"></entry>\n</config>\n"
+);
+```
+
+The following uses `normalize_document` to clean up a parsed document.
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::DocumentNormalize;
+use xml_dom::level2::ext::normalize::DOMConfiguration;
+
+let mut document_node = xml_dom::parser::read_xml(
+    "<list>\n  <!-- first -->\n  <item><![CDATA[a < b]]></item>\n</list>",
+)
+.unwrap();
+
+let mut config = DOMConfiguration::default();
+config.set_parameter("comments", false).unwrap();
+config.set_parameter("cdata-sections", false).unwrap();
+config.set_parameter("element-content-whitespace", false).unwrap();
+document_node.normalize_document(&config).unwrap();
+
+assert_eq!(document_node.to_string(), "<list><item>a &#60; b</item></list>");
+# }
+```
+*/
+
+use crate::level2::convert::as_document;
+use crate::level2::ext::decl::PrologPosition;
+use crate::level2::ext::namespaced::{element_namespace, resolve_declared_prefix};
+use crate::level2::ext::traits::{
+    DocumentDecl, DocumentEntities, DocumentNormalize, TextWhitespace,
+};
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::live_owner_document;
+use crate::level2::traits::*;
+use crate::shared::error::{
+    Error, Result, MSG_CDATA_END, MSG_INVALID_NODE_TYPE, MSG_UNKNOWN_PARAMETER,
+};
+use crate::shared::name::Name;
+use crate::shared::syntax::*;
+use crate::shared::text::is_xml_space;
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Options that control the behavior of [`normalize_for_diff`](fn.normalize_for_diff.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct NormalizeOptions {
+    /// The string written once for each level of nesting, the default is two spaces.
+    pub indent: String,
+    /// If `true`, the default, namespace prefixes are replaced with prefixes of the form `ns1`,
+    /// `ns2`, assigned in document order. If `false` the original names and `xmlns` attributes are
+    /// written unchanged.
+    pub normalize_prefixes: bool,
+}
+
+///
+/// The configuration parameters used by
+/// [`normalize_document`](../trait.DocumentNormalize.html#tymethod.normalize_document), the
+/// boolean subset of the DOM Level 3 `DOMConfiguration` interface. The default value of each
+/// parameter is the default required by the specification.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DOMConfiguration {
+    /// The `entities` parameter, if `true`, the default, entity references are kept.
+    pub entities: bool,
+    /// The `comments` parameter, if `true`, the default, comments are kept.
+    pub comments: bool,
+    /// The `cdata-sections` parameter, if `true`, the default, CDATA sections are kept.
+    pub cdata_sections: bool,
+    /// The `split-cdata-sections` parameter, if `true`, the default, CDATA sections containing
+    /// `"]]>"` are split, else they are reported as an error.
+    pub split_cdata_sections: bool,
+    /// The `namespaces` parameter, if `true`, the default, missing namespace declarations are
+    /// added.
+    pub namespaces: bool,
+    /// The `element-content-whitespace` parameter, if `true`, the default, white space in element
+    /// content is kept.
+    pub element_content_whitespace: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Write the tree rooted at `node` in the normalized form described in the
+/// [module documentation](index.html), according to `options`. Each line, including the last,
+/// is terminated by `\n`.
+///
+pub fn normalize_for_diff(node: &RefNode, options: &NormalizeOptions) -> Result<String> {
+    let mut writer = Writer {
+        options,
+        prefixes: Default::default(),
+        buffer: String::new(),
+    };
+    writer.write_node(node, 0, &[]);
+    Ok(writer.buffer)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            indent: DEFAULT_INDENT.to_string(),
+            normalize_prefixes: true,
+        }
+    }
+}
+
+impl Default for DOMConfiguration {
+    fn default() -> Self {
+        Self {
+            entities: true,
+            comments: true,
+            cdata_sections: true,
+            split_cdata_sections: true,
+            namespaces: true,
+            element_content_whitespace: true,
+        }
+    }
+}
+
+impl DOMConfiguration {
+    ///
+    /// Set the value of the parameter `name`, using the names from the DOM Level 3
+    /// specification.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if `name` is not a supported parameter.
+    ///
+    pub fn set_parameter(&mut self, name: &str, value: bool) -> Result<()> {
+        *self.parameter_mut(name)? = value;
+        Ok(())
+    }
+
+    ///
+    /// Returns the value of the parameter `name`, using the names from the DOM Level 3
+    /// specification.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if `name` is not a supported parameter.
+    ///
+    pub fn get_parameter(&self, name: &str) -> Result<bool> {
+        self.clone().parameter_mut(name).map(|value| *value)
+    }
+
+    ///
+    /// Returns `true` if the parameter `name` is supported.
+    ///
+    pub fn can_set_parameter(&self, name: &str) -> bool {
+        PARAMETER_NAMES.contains(&name)
+    }
+
+    ///
+    /// Returns the names of all supported parameters.
+    ///
+    pub fn parameter_names(&self) -> Vec<String> {
+        PARAMETER_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Result<&mut bool> {
+        match name {
+            PARAM_ENTITIES => Ok(&mut self.entities),
+            PARAM_COMMENTS => Ok(&mut self.comments),
+            PARAM_CDATA_SECTIONS => Ok(&mut self.cdata_sections),
+            PARAM_SPLIT_CDATA_SECTIONS => Ok(&mut self.split_cdata_sections),
+            PARAM_NAMESPACES => Ok(&mut self.namespaces),
+            PARAM_ELEMENT_CONTENT_WHITESPACE => Ok(&mut self.element_content_whitespace),
+            _ => {
+                warn!("{}", MSG_UNKNOWN_PARAMETER);
+                Err(Error::NotSupported)
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentNormalize for RefNode {
+    fn normalize_document(&mut self, config: &DOMConfiguration) -> Result<()> {
+        if self.node_type() != NodeType::Document {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        normalize_children(self, config)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const DEFAULT_INDENT: &str = "  ";
+
+const NORMALIZED_PREFIX: &str = "ns";
+
+const PARAM_ENTITIES: &str = "entities";
+const PARAM_COMMENTS: &str = "comments";
+const PARAM_CDATA_SECTIONS: &str = "cdata-sections";
+const PARAM_SPLIT_CDATA_SECTIONS: &str = "split-cdata-sections";
+const PARAM_NAMESPACES: &str = "namespaces";
+const PARAM_ELEMENT_CONTENT_WHITESPACE: &str = "element-content-whitespace";
+
+const PARAMETER_NAMES: [&str; 6] = [
+    PARAM_ENTITIES,
+    PARAM_COMMENTS,
+    PARAM_CDATA_SECTIONS,
+    PARAM_SPLIT_CDATA_SECTIONS,
+    PARAM_NAMESPACES,
+    PARAM_ELEMENT_CONTENT_WHITESPACE,
+];
+
+struct Writer<'a> {
+    options: &'a NormalizeOptions,
+    prefixes: HashMap<String, String>,
+    buffer: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+impl Writer<'_> {
+    fn write_node(&mut self, node: &RefNode, depth: usize, scope: &[String]) {
+        match node.node_type() {
+            NodeType::Element => self.write_element(node, depth, scope),
+            NodeType::Document => {
+                if let Some(xml_declaration) = node.xml_declaration() {
+                    self.write_line(depth, &xml_declaration.to_string());
+                }
+                let mut doc_type = node.doc_type();
+                for child in node.child_nodes() {
+                    if node.prolog_position(&child) != Some(PrologPosition::BeforeDocType) {
+                        if let Some(doc_type) = doc_type.take() {
+                            self.write_line(depth, &doc_type.to_string());
+                        }
+                    }
+                    self.write_node(&child, depth, scope);
+                }
+                if let Some(doc_type) = doc_type {
+                    self.write_line(depth, &doc_type.to_string());
+                }
+            }
+            NodeType::DocumentFragment => {
+                for child in node.child_nodes() {
+                    self.write_node(&child, depth, scope);
+                }
+            }
+            NodeType::Text => {
+                let text = collapse(&node.node_value().unwrap_or_default());
+                if !text.is_empty() {
+                    self.write_line(depth, &text);
+                }
+            }
+            _ => self.write_line(depth, &node.to_string()),
+        }
+    }
+
+    fn write_element(&mut self, element: &RefNode, depth: usize, parent_scope: &[String]) {
+        let mut scope = parent_scope.to_vec();
+        let (start_tag, end_tag) = self.element_tags(element, &mut scope);
+        if is_space_preserved(element) {
+            let mut line = start_tag;
+            for child in element.child_nodes() {
+                self.write_inline(&child, &scope, &mut line);
+            }
+            line.push_str(&end_tag);
+            self.write_line(depth, &line);
+            return;
+        }
+        let children: Vec<RefNode> = element
+            .child_nodes()
+            .into_iter()
+            .filter(|child| {
+                child.node_type() != NodeType::Text
+                    || !collapse(&child.node_value().unwrap_or_default()).is_empty()
+            })
+            .collect();
+        match children.as_slice() {
+            [] => self.write_line(depth, &format!("{}{}", start_tag, end_tag)),
+            [text] if text.node_type() == NodeType::Text => {
+                let text = collapse(&text.node_value().unwrap_or_default());
+                self.write_line(depth, &format!("{}{}{}", start_tag, text, end_tag));
+            }
+            _ => {
+                self.write_line(depth, &start_tag);
+                for child in &children {
+                    self.write_node(child, depth + 1, &scope);
+                }
+                self.write_line(depth, &end_tag);
+            }
+        }
+    }
+
+    ///
+    /// Write `node` without any change to its content or white space, as within an
+    /// `xml:space="preserve"` region.
+    ///
+    fn write_inline(&mut self, node: &RefNode, parent_scope: &[String], line: &mut String) {
+        if node.node_type() == NodeType::Element {
+            let mut scope = parent_scope.to_vec();
+            let (start_tag, end_tag) = self.element_tags(node, &mut scope);
+            line.push_str(&start_tag);
+            for child in node.child_nodes() {
+                self.write_inline(&child, &scope, line);
+            }
+            line.push_str(&end_tag);
+        } else {
+            line.push_str(&node.to_string());
+        }
+    }
+
+    ///
+    /// Returns the start and end tags of `element`; any namespace declarations required are
+    /// added to the start tag, and to `scope`.
+    ///
+    fn element_tags(&mut self, element: &RefNode, scope: &mut Vec<String>) -> (String, String) {
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        let name = if self.options.normalize_prefixes {
+            let mut used: Vec<String> = Vec::new();
+            let name =
+                self.normalized_name(element.node_name(), element_namespace(element), &mut used);
+            for (attribute_name, attribute) in element.attributes() {
+                if is_namespace_declaration(&attribute_name) {
+                    continue;
+                }
+                let namespace_uri = attribute_namespace(element, &attribute_name);
+                let attribute_name = self.normalized_name(attribute_name, namespace_uri, &mut used);
+                attributes.push((attribute_name, attribute.value().unwrap_or_default()));
+            }
+            for namespace_uri in used {
+                if !scope.contains(&namespace_uri) {
+                    attributes.push((
+                        format!("{}:{}", XMLNS_NS_ATTRIBUTE, self.prefixes[&namespace_uri]),
+                        namespace_uri.clone(),
+                    ));
+                    scope.push(namespace_uri);
+                }
+            }
+            name
+        } else {
+            for (attribute_name, attribute) in element.attributes() {
+                attributes.push((
+                    attribute_name.to_string(),
+                    attribute.value().unwrap_or_default(),
+                ));
+            }
+            element.node_name().to_string()
+        };
+        attributes.sort_by(|lhs, rhs| {
+            (!is_declaration_name(&lhs.0), &lhs.0).cmp(&(!is_declaration_name(&rhs.0), &rhs.0))
+        });
+
+        let mut start_tag = format!("{}{}", XML_ELEMENT_START_START, name);
+        for (attribute_name, value) in attributes {
+            start_tag.push_str(&format!(" {}=\"{}\"", attribute_name, value));
+        }
+        start_tag.push_str(XML_ELEMENT_START_END);
+        let end_tag = format!("{}{}{}", XML_ELEMENT_END_START, name, XML_ELEMENT_END_END);
+        (start_tag, end_tag)
+    }
+
+    fn normalized_name(
+        &mut self,
+        name: Name,
+        namespace_uri: Option<String>,
+        used: &mut Vec<String>,
+    ) -> String {
+        match namespace_uri {
+            None => name.local_name().clone(),
+            Some(namespace_uri) if namespace_uri == XML_NS_URI => {
+                format!("{}:{}", XML_NS_ATTRIBUTE, name.local_name())
+            }
+            Some(namespace_uri) => {
+                let next = self.prefixes.len() + 1;
+                let prefix = self
+                    .prefixes
+                    .entry(namespace_uri.clone())
+                    .or_insert_with(|| format!("{}{}", NORMALIZED_PREFIX, next))
+                    .clone();
+                if !used.contains(&namespace_uri) {
+                    used.push(namespace_uri);
+                }
+                format!("{}:{}", prefix, name.local_name())
+            }
+        }
+    }
+
+    fn write_line(&mut self, depth: usize, content: &str) {
+        for _ in 0..depth {
+            self.buffer.push_str(&self.options.indent);
+        }
+        self.buffer.push_str(content);
+        self.buffer.push('\n');
+    }
+}
+
+fn collapse(text: &str) -> String {
+    text.split(is_xml_space)
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn is_namespace_declaration(name: &Name) -> bool {
+    match name.prefix() {
+        Some(prefix) => prefix == XMLNS_NS_ATTRIBUTE,
+        None => name.local_name() == XMLNS_NS_ATTRIBUTE,
+    }
+}
+
+fn is_declaration_name(name: &str) -> bool {
+    name == XMLNS_NS_ATTRIBUTE
+        || name.starts_with(&format!("{}{}", XMLNS_NS_ATTRIBUTE, XML_NS_SEPARATOR))
+}
+
+fn attribute_namespace(element: &RefNode, name: &Name) -> Option<String> {
+    match (name.namespace_uri(), name.prefix()) {
+        (Some(namespace_uri), _) => Some(namespace_uri.clone()),
+        (None, Some(prefix)) if prefix == XML_NS_ATTRIBUTE => Some(XML_NS_URI.to_string()),
+        (None, Some(prefix)) => resolve_declared_prefix(element, Some(prefix)),
+        (None, None) => None,
+    }
+}
+
+///
+/// Returns `true` if the nearest `xml:space` attribute on `element` or its ancestors has the
+/// value `preserve`.
+///
+pub(crate) fn is_space_preserved(element: &RefNode) -> bool {
+    let mut current = Some(element.clone());
+    while let Some(node) = current {
+        let space = node.attributes().into_iter().find(|(name, _)| {
+            name.local_name() == XML_NS_ATTR_SPACE
+                && attribute_namespace(&node, name).as_deref() == Some(XML_NS_URI)
+        });
+        if let Some((_, attribute)) = space {
+            return attribute.value().as_deref() == Some(XML_NS_ATTR_SPACE_PRESERVE);
+        }
+        current = node.parent_node();
+    }
+    false
+}
+
+///
+/// Apply `config` to the children of `parent`, and their descendants, then merge any adjacent
+/// text nodes.
+///
+fn normalize_children(parent: &mut RefNode, config: &DOMConfiguration) -> Result<()> {
+    for child in parent.child_nodes() {
+        normalize_child(parent, child, config)?;
+    }
+    parent.normalize();
+    Ok(())
+}
+
+fn normalize_child(parent: &mut RefNode, child: RefNode, config: &DOMConfiguration) -> Result<()> {
+    match child.node_type() {
+        NodeType::Element => {
+            let mut element = child;
+            if config.namespaces {
+                fix_namespaces(&mut element)?;
+            }
+            normalize_children(&mut element, config)?;
+        }
+        NodeType::EntityReference if !config.entities => {
+            let expansion = entity_expansion(&child)?;
+            if !expansion.is_empty() {
+                for node in &expansion {
+                    let _safe_to_ignore =
+                        parent.insert_before(node.clone(), Some(child.clone()))?;
+                }
+                let _safe_to_ignore = parent.remove_child(child)?;
+                for node in expansion {
+                    normalize_child(parent, node, config)?;
+                }
+            }
+        }
+        NodeType::Comment if !config.comments => {
+            let _safe_to_ignore = parent.remove_child(child)?;
+        }
+        NodeType::CData if !config.cdata_sections => {
+            let document_node = live_owner_document(&child)?;
+            let text_node = as_document(&document_node)?
+                .create_text_node(&child.node_value().unwrap_or_default());
+            let _safe_to_ignore = parent.replace_child(text_node, child)?;
+        }
+        NodeType::CData => {
+            let data = child.node_value().unwrap_or_default();
+            if data.contains(XML_CDATA_END) {
+                if !config.split_cdata_sections {
+                    warn!("{}", MSG_CDATA_END);
+                    return Err(Error::Syntax);
+                }
+                let document_node = live_owner_document(&child)?;
+                let document = as_document(&document_node)?;
+                for section in split_cdata_end(&data) {
+                    let _safe_to_ignore = parent.insert_before(
+                        document.create_cdata_section(section)?,
+                        Some(child.clone()),
+                    )?;
+                }
+                let _safe_to_ignore = parent.remove_child(child)?;
+            }
+        }
+        NodeType::Text
+            if !config.element_content_whitespace && child.is_element_content_whitespace() =>
+        {
+            let _safe_to_ignore = parent.remove_child(child)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+///
+/// Split `data` between the `"]]"` and `">"` of each CDATA section end delimiter it contains.
+///
+pub(crate) fn split_cdata_end(data: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let mut rest = data;
+    while let Some(index) = rest.find(XML_CDATA_END) {
+        let (section, remainder) = rest.split_at(index + XML_CDATA_END.len() - 1);
+        sections.push(section);
+        rest = remainder;
+    }
+    sections.push(rest);
+    sections
+}
+
+///
+/// Returns copies of the nodes an entity reference expands to; its own children if it has any,
+/// else the replacement nodes of the internal entity of the same name, or a text node holding
+/// its value.
+///
+fn entity_expansion(entity_reference: &RefNode) -> Result<Vec<RefNode>> {
+    let children = entity_reference.child_nodes();
+    if !children.is_empty() {
+        return Ok(children
+            .iter()
+            .filter_map(|child| child.clone_node(true))
+            .collect());
+    }
+    let document_node = live_owner_document(entity_reference)?;
+    let document = as_document(&document_node)?;
+    match document_node.entity(&entity_reference.node_name().to_string()) {
+        Some(entity) if entity.has_child_nodes() => Ok(entity
+            .child_nodes()
+            .iter()
+            .filter_map(|child| child.clone_node(true))
+            .collect()),
+        Some(entity) => Ok(entity
+            .node_value()
+            .map(|value| vec![document.create_text_node(&value)])
+            .unwrap_or_default()),
+        None => Ok(Vec::new()),
+    }
+}
+
+///
+/// Declare the namespace of the name of `element`, and of each of its attributes, where the
+/// prefix used does not resolve to that namespace.
+///
+fn fix_namespaces(element: &mut RefNode) -> Result<()> {
+    let mut names = vec![element.node_name()];
+    names.extend(
+        element
+            .attributes()
+            .into_keys()
+            .filter(|name| !is_namespace_declaration(name)),
+    );
+    for (index, name) in names.iter().enumerate() {
+        let namespace_uri = match name.namespace_uri() {
+            Some(namespace_uri) if namespace_uri != XML_NS_URI => namespace_uri,
+            _ => continue,
+        };
+        let prefix = name.prefix().as_deref();
+        //
+        // An un-prefixed attribute is never in the default namespace, only the element is.
+        //
+        if (prefix.is_none() && index > 0)
+            || resolve_declared_prefix(element, prefix).as_ref() == Some(namespace_uri)
+        {
+            continue;
+        }
+        let qualified_name = match prefix {
+            None => XMLNS_NS_ATTRIBUTE.to_string(),
+            Some(prefix) => format!("{}{}{}", XMLNS_NS_ATTRIBUTE, XML_NS_SEPARATOR, prefix),
+        };
+        element.set_attribute_ns(XMLNS_NS_URI, &qualified_name, namespace_uri)?;
+    }
+    Ok(())
+}
@@ -17,7 +17,8 @@ use std::ops::{BitAnd, BitOr};
 /// settings these *are not* set by default by `create_document_with_options`.
 ///
 /// This type has a set of methods that turn on options, i.e. `set_assume_ids`,  and retrieve the
-/// state of an option, i.e. `has_assume_ids`.
+/// state of an option, i.e. `has_assume_ids`. In addition to these on/off options a maximum text
+/// node length may be set with `set_max_text_length`.
 ///
 /// # Example
 ///
@@ -45,8 +46,11 @@ use std::ops::{BitAnd, BitOr};
 ///     .unwrap();
 /// ```
 ///
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct ProcessingOptions(u8);
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessingOptions {
+    flags: u8,
+    max_text_length: Option<usize>,
+}
 
 // ------------------------------------------------------------------------------------------------
 // Private Types
@@ -59,20 +63,16 @@ enum ProcessingOptionFlags {
     AssumeIDs = 0b0000_0001,
     ParseEntities = 0b0000_0010,
     AddNamespaces = 0b0000_0100,
+    LenientNamespaces = 0b0000_1000,
+    StrictOwnerDocument = 0b0001_0000,
+    AutoImport = 0b0010_0000,
+    ByteOffsets = 0b0100_0000,
 }
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
-impl Default for ProcessingOptions {
-    fn default() -> Self {
-        Self(0)
-    }
-}
-
-// ------------------------------------------------------------------------------------------------
-
 impl Display for ProcessingOptions {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "ProcessingOptions {{")?;
@@ -87,7 +87,25 @@ impl Display for ProcessingOptions {
         if self.has_add_namespaces() {
             option_strings.push("AddNamespaces");
         }
+        if self.has_lenient_namespaces() {
+            option_strings.push("LenientNamespaces");
+        }
+        if self.has_strict_owner_document() {
+            option_strings.push("StrictOwnerDocument");
+        }
+        if self.has_auto_import() {
+            option_strings.push("AutoImport");
+        }
+        if self.has_byte_offsets() {
+            option_strings.push("ByteOffsets");
+        }
         write!(f, "{}", option_strings.join(", "))?;
+        if let Some(max_text_length) = self.max_text_length {
+            if !option_strings.is_empty() {
+                write!(f, ", ")?;
+            }
+            write!(f, "MaxTextLength({})", max_text_length)?;
+        }
 
         write!(f, "}}")
     }
@@ -98,30 +116,51 @@ impl Display for ProcessingOptions {
 impl Binary for ProcessingOptions {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         if f.alternate() {
-            write!(f, "{:#010b}", self.0)
+            write!(f, "{:#010b}", self.flags)
         } else {
-            write!(f, "{:08b}", self.0)
+            write!(f, "{:08b}", self.flags)
         }
     }
 }
 
 // ------------------------------------------------------------------------------------------------
 
+///
+/// The result has only those flags set in both operands; a maximum text length is retained only
+/// if both operands have the same value.
+///
 impl BitAnd for ProcessingOptions {
     type Output = Self;
 
     fn bitand(self, rhs: Self) -> Self::Output {
-        Self(self.0 & rhs.0)
+        Self {
+            flags: self.flags & rhs.flags,
+            max_text_length: if self.max_text_length == rhs.max_text_length {
+                self.max_text_length
+            } else {
+                None
+            },
+        }
     }
 }
 
 // ------------------------------------------------------------------------------------------------
 
+///
+/// The result has those flags set in either operand; if both operands have a maximum text length
+/// the smaller is retained.
+///
 impl BitOr for ProcessingOptions {
     type Output = Self;
 
     fn bitor(self, rhs: Self) -> Self::Output {
-        Self(self.0 | rhs.0)
+        Self {
+            flags: self.flags | rhs.flags,
+            max_text_length: match (self.max_text_length, rhs.max_text_length) {
+                (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+                (lhs, rhs) => lhs.or(rhs),
+            },
+        }
     }
 }
 
@@ -138,28 +177,63 @@ impl ProcessingOptions {
     /// Returns true if all options are `false`.
     ///
     pub fn has_none(&self) -> bool {
-        self.0 == 0
+        self.flags == 0 && self.max_text_length.is_none()
     }
     ///
     /// Returns `true` if the document will automatically assume certain attributes will be treated
     /// as XML `id` values, else `false`.
     ///
     pub fn has_assume_ids(&self) -> bool {
-        self.0 & (ProcessingOptionFlags::AssumeIDs as u8) != 0
+        self.flags & (ProcessingOptionFlags::AssumeIDs as u8) != 0
     }
     ///
     /// Returns `true` if the document will parse entities inside text nodes and create
     /// `EntityReference` nodes, else `false`.
     ///
     pub fn has_parse_entities(&self) -> bool {
-        self.0 & (ProcessingOptionFlags::ParseEntities as u8) != 0
+        self.flags & (ProcessingOptionFlags::ParseEntities as u8) != 0
     }
     ///
     /// Returns `true` if the document will automatically add namespace attributes to elements if
     /// qualified names are added that do not have current mappings., else `false`.
     ///
     pub fn has_add_namespaces(&self) -> bool {
-        self.0 & (ProcessingOptionFlags::AddNamespaces as u8) != 0
+        self.flags & (ProcessingOptionFlags::AddNamespaces as u8) != 0
+    }
+    ///
+    /// Returns `true` if the document will accept namespace declarations that bind the reserved
+    /// `xml` and `xmlns` prefixes or namespaces, else `false`.
+    ///
+    pub fn has_lenient_namespaces(&self) -> bool {
+        self.flags & (ProcessingOptionFlags::LenientNamespaces as u8) != 0
+    }
+    ///
+    /// Returns `true` if the document checks the owner document of each node in the subtree
+    /// inserted into it, rather than only the root of the subtree, else `false`.
+    ///
+    pub fn has_strict_owner_document(&self) -> bool {
+        self.flags & (ProcessingOptionFlags::StrictOwnerDocument as u8) != 0
+    }
+    ///
+    /// Returns `true` if the document imports a node owned by another document when it is
+    /// inserted, rather than failing with `Error::WrongDocument`, else `false`.
+    ///
+    pub fn has_auto_import(&self) -> bool {
+        self.flags & (ProcessingOptionFlags::AutoImport as u8) != 0
+    }
+    ///
+    /// Returns `true` if the offsets and counts of `CharacterData` operations are in bytes of the
+    /// UTF-8 data, rather than UTF-16 code units, else `false`.
+    ///
+    pub fn has_byte_offsets(&self) -> bool {
+        self.flags & (ProcessingOptionFlags::ByteOffsets as u8) != 0
+    }
+    ///
+    /// Returns the maximum length, in UTF-16 code units, of a `Text` node, if set. See
+    /// [`set_max_text_length`](#method.set_max_text_length).
+    ///
+    pub fn max_text_length(&self) -> Option<usize> {
+        self.max_text_length
     }
     ///
     /// TBD.
@@ -171,19 +245,75 @@ impl ProcessingOptions {
     /// for more details.
     ///
     pub fn set_assume_ids(&mut self) {
-        self.0 |= ProcessingOptionFlags::AssumeIDs as u8
+        self.flags |= ProcessingOptionFlags::AssumeIDs as u8
     }
     ///
     /// TBD
     ///
     pub fn set_parse_entities(&mut self) {
-        self.0 |= ProcessingOptionFlags::ParseEntities as u8
+        self.flags |= ProcessingOptionFlags::ParseEntities as u8
     }
     ///
     /// TBD
     ///
     pub fn set_add_namespaces(&mut self) {
-        self.0 |= ProcessingOptionFlags::AddNamespaces as u8
+        self.flags |= ProcessingOptionFlags::AddNamespaces as u8
+    }
+    ///
+    /// Accept namespace declarations that would otherwise be rejected with `Error::Namespace`
+    /// by `set_attribute_node`: binding the `xml` prefix to any namespace other than
+    /// `http://www.w3.org/XML/1998/namespace`, declaring the `xmlns` prefix, or binding any other
+    /// prefix, or the default namespace, to either of the reserved namespaces. This is intended
+    /// for working with existing documents that do not follow the rules of
+    /// [Namespaces in XML 1.0 §3](https://www.w3.org/TR/xml-names/#ns-decl).
+    ///
+    pub fn set_lenient_namespaces(&mut self) {
+        self.flags |= ProcessingOptionFlags::LenientNamespaces as u8
+    }
+    ///
+    /// Check, when a node is inserted by `insert_before`, `append_child`, or `replace_child`, that
+    /// it and every node in its subtree, including attributes, is owned by the document; see
+    /// [`NodeOwner::verify_owner`](../trait.NodeOwner.html#tymethod.verify_owner). Without this
+    /// option only the inserted node is checked, and a node with no owner document may be
+    /// inserted anywhere.
+    ///
+    pub fn set_strict_owner_document(&mut self) {
+        self.flags |= ProcessingOptionFlags::StrictOwnerDocument as u8
+    }
+    ///
+    /// When a node owned by another document is inserted, by `insert_before`, `append_child`, or
+    /// `replace_child`, insert a deep copy imported into the document instead of failing with
+    /// `Error::WrongDocument`; the copy is returned in place of the node given.
+    ///
+    pub fn set_auto_import(&mut self) {
+        self.flags |= ProcessingOptionFlags::AutoImport as u8
+    }
+    ///
+    /// Measure the `length`, and the offsets and counts given to `substring_data`, `insert_data`,
+    /// `delete_data`, `replace_data`, and `Text::split`, in bytes of the UTF-8 data held by each
+    /// node rather than in the UTF-16 code units the specification requires. This avoids counting
+    /// code units, and so is faster for large text, but offsets computed by another DOM
+    /// implementation will not select the same characters in non-ASCII text. In either case an
+    /// offset that does not fall between two characters is rejected with `Error::IndexSize`.
+    ///
+    pub fn set_byte_offsets(&mut self) {
+        self.flags |= ProcessingOptionFlags::ByteOffsets as u8
+    }
+    ///
+    /// Set the maximum length, in UTF-16 code units, of a `Text` node. When a `Text` node longer
+    /// than `max_text_length` is inserted into the document, or a `Text` node in the document grows
+    /// longer than that through the `CharacterData` methods, it is split into a sequence of
+    /// adjacent `Text` nodes, none of which is longer than `max_text_length`. This keeps the cost
+    /// of per-node operations bounded for documents with very large text content.
+    ///
+    /// Text is only split between characters, never within a surrogate pair, and so a chunk may be
+    /// shorter than `max_text_length`. Calling `normalize` on the parent merges the chunks back
+    /// into a single node. A value of zero is ignored.
+    ///
+    pub fn set_max_text_length(&mut self, max_text_length: usize) {
+        if max_text_length > 0 {
+            self.max_text_length = Some(max_text_length)
+        }
     }
 }
 
@@ -203,6 +333,10 @@ mod tests {
         assert!(!options.has_assume_ids());
         assert!(!options.has_parse_entities());
         assert!(!options.has_add_namespaces());
+        assert!(!options.has_lenient_namespaces());
+        assert!(!options.has_strict_owner_document());
+        assert!(!options.has_auto_import());
+        assert!(!options.has_byte_offsets());
 
         assert_eq!(format!("{}", options), r"ProcessingOptions {}".to_string());
         assert_eq!(format!("{:b}", options), r"00000000".to_string());
@@ -211,4 +345,52 @@ mod tests {
         let new_options = ProcessingOptions::new();
         assert_eq!(options, new_options);
     }
+
+    #[test]
+    fn test_max_text_length() {
+        let mut options = ProcessingOptions::default();
+        options.set_max_text_length(0);
+        assert!(options.has_none());
+
+        options.set_max_text_length(1024);
+        assert!(!options.has_none());
+        assert_eq!(options.max_text_length(), Some(1024));
+        assert_eq!(
+            format!("{}", options),
+            r"ProcessingOptions {MaxTextLength(1024)}".to_string()
+        );
+
+        options.set_assume_ids();
+        assert_eq!(
+            format!("{}", options),
+            r"ProcessingOptions {AssumeIDs, MaxTextLength(1024)}".to_string()
+        );
+
+        options.set_lenient_namespaces();
+        assert_eq!(
+            format!("{}", options),
+            r"ProcessingOptions {AssumeIDs, LenientNamespaces, MaxTextLength(1024)}".to_string()
+        );
+        assert_eq!(format!("{:b}", options), r"00001001".to_string());
+
+        options.set_strict_owner_document();
+        options.set_auto_import();
+        assert_eq!(
+            format!("{}", options),
+            r"ProcessingOptions {AssumeIDs, LenientNamespaces, StrictOwnerDocument, AutoImport, MaxTextLength(1024)}".to_string()
+        );
+        assert_eq!(format!("{:b}", options), r"00111001".to_string());
+
+        options.set_byte_offsets();
+        assert!(options.has_byte_offsets());
+        assert_eq!(format!("{:b}", options), r"01111001".to_string());
+
+        let mut other = ProcessingOptions::default();
+        other.set_max_text_length(512);
+        assert_eq!(
+            (options.clone() | other.clone()).max_text_length(),
+            Some(512)
+        );
+        assert_eq!((options & other).max_text_length(), None);
+    }
 }
@@ -0,0 +1,176 @@
+/*!
+This module provides support types for the [`DocumentOutline`](trait.DocumentOutline.html) trait.
+
+An outline is a structural summary of a document, intended for quickly understanding an
+unfamiliar document in log output or an interactive session. Sibling elements with the same name
+are merged into a single entry, with a count of the number of elements merged, and the union of
+their attribute names and child elements. Text, comments, and other non-element content is not
+included.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::*;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("catalog"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for id in &["1", "2", "3"] {
+    let mut book_node = document.create_element("book").unwrap();
+    book_node.set_attribute("id", id).unwrap();
+    let _safe_to_ignore = book_node
+        .append_child(document.create_element("title").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node.append_child(book_node).unwrap();
+}
+
+let outline = document_node.outline(1).unwrap();
+assert_eq!(outline.children[0].count, 3);
+assert_eq!(outline.to_string(), "catalog\n  book x3 @id ...\n");
+```
+*/
+
+use crate::level2::ext::traits::DocumentOutline;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Document, Node, NodeType};
+use crate::shared::error::MSG_INVALID_NODE_TYPE;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single entry in a document outline, summarizing one or more sibling elements that share the
+/// same name.
+///
+/// The `Display` implementation writes one line per entry, indented by two spaces for each level
+/// of nesting, in the form `name xCOUNT @attribute ...`. The count is omitted when only one
+/// element was merged, and the trailing `...` is only written when `truncated` is `true`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outline {
+    /// The qualified name of the elements summarized by this entry.
+    pub name: String,
+    /// The number of sibling elements, across all parents summarized by the containing entry,
+    /// with this name.
+    pub count: usize,
+    /// The names of all attributes found on any of the summarized elements, sorted.
+    pub attributes: Vec<String>,
+    /// The summarized child elements, in the order each name first appears.
+    pub children: Vec<Outline>,
+    /// `true` if the summarized elements have child elements that were not included because
+    /// they are beyond the requested depth.
+    pub truncated: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentOutline for RefNode {
+    fn outline(&self, depth: usize) -> Option<Outline> {
+        if self.node_type() != NodeType::Document {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return None;
+        }
+        self.document_element()
+            .map(|element| outline_elements(&[element], depth))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Outline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl Outline {
+    fn fmt_indented(&self, f: &mut Formatter<'_>, level: usize) -> FmtResult {
+        write!(
+            f,
+            "{:width$}{}",
+            "",
+            self.name,
+            width = level * INDENT_WIDTH
+        )?;
+        if self.count > 1 {
+            write!(f, " x{}", self.count)?;
+        }
+        for attribute in &self.attributes {
+            write!(f, " @{}", attribute)?;
+        }
+        if self.truncated {
+            write!(f, " ...")?;
+        }
+        writeln!(f)?;
+        for child in &self.children {
+            child.fmt_indented(f, level + 1)?;
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const INDENT_WIDTH: usize = 2;
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Summarize `elements`, which all share the same name, including `depth` further levels of
+/// child elements.
+///
+fn outline_elements(elements: &[RefNode], depth: usize) -> Outline {
+    let mut attributes: Vec<String> = Vec::new();
+    let mut groups: Vec<(String, Vec<RefNode>)> = Vec::new();
+    for element in elements {
+        for name in element.attributes().keys() {
+            let name = name.to_string();
+            if !attributes.contains(&name) {
+                attributes.push(name);
+            }
+        }
+        for child in element.child_nodes() {
+            if child.node_type() == NodeType::Element {
+                let name = child.node_name().to_string();
+                match groups
+                    .iter_mut()
+                    .find(|(group_name, _)| group_name == &name)
+                {
+                    Some((_, group)) => group.push(child),
+                    None => groups.push((name, vec![child])),
+                }
+            }
+        }
+    }
+    attributes.sort();
+
+    let (children, truncated) = if depth == 0 {
+        (Vec::new(), !groups.is_empty())
+    } else {
+        (
+            groups
+                .iter()
+                .map(|(_, group)| outline_elements(group, depth - 1))
+                .collect(),
+            false,
+        )
+    };
+    Outline {
+        name: elements[0].node_name().to_string(),
+        count: elements.len(),
+        attributes,
+        children,
+        truncated,
+    }
+}
@@ -0,0 +1,502 @@
+/*!
+Provides the [`QueryResult`](struct.QueryResult.html) wrapper around the list of nodes returned by
+queries such as `get_elements_by_tag_name`, with bulk operations for common extract and delete
+workflows.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::query::QueryResult;
+
+let implementation = get_implementation();
+let mut document_node = implementation.create_document(None, Some("catalog"), None).unwrap();
+let document = as_document_mut(&mut document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for title in &["One", "Two"] {
+    let mut book_node = document.create_element("book").unwrap();
+    let _safe_to_ignore = book_node.append_child(document.create_text_node(title)).unwrap();
+    let _safe_to_ignore = root_node.append_child(book_node).unwrap();
+}
+
+let mut books = QueryResult::by_tag_name(&document_node, "book");
+assert_eq!(books.texts(), vec!["One".to_string(), "Two".to_string()]);
+
+books.remove_all().unwrap();
+assert!(!root_node.has_child_nodes());
+```
+
+# Ordering
+
+All of the query APIs in this crate that return a list of nodes return them in document order,
+as defined by [`compare_document_position`](../compare/fn.compare_document_position.html), and
+without duplicates:
+
+* `get_elements_by_tag_name` and `get_elements_by_tag_name_ns` on `Document` and `Element`,
+  and therefore [`QueryResult::by_tag_name`](struct.QueryResult.html#method.by_tag_name) and
+  [`QueryResult::by_tag_name_ns`](struct.QueryResult.html#method.by_tag_name_ns).
+* [`Selector::select`](../selector/struct.Selector.html#method.select).
+* Node-sets from [`xpath::evaluate`](../xpath/fn.evaluate.html) and
+  [`xpath::select`](../xpath/fn.select.html), and the compiled form
+  [`XPath::evaluate`](../xpath/struct.XPath.html#method.evaluate).
+* [`NodeIterator`](../../traversal/struct.NodeIterator.html) and
+  [`TreeWalker`](../../traversal/struct.TreeWalker.html), when moving forward.
+
+Where order does not matter the `_unordered_fast` variants,
+[`xpath::select_unordered_fast`](../xpath/fn.select_unordered_fast.html) and
+[`XPath::evaluate_unordered_fast`](../xpath/struct.XPath.html#method.evaluate_unordered_fast),
+return the same nodes without the cost of sorting them; a list from any source may be put into
+document order with [`sort_document_order`](../compare/fn.sort_document_order.html).
+
+# Mutation During Iteration
+
+The same APIs, other than `NodeIterator` and `TreeWalker`, as well as `child_nodes`, return a
+_snapshot_: an owned list of the nodes selected at the time of the call, rather than a live
+view of the tree. The tree may therefore be modified freely while iterating over a result, with
+well-defined behavior:
+
+* Iteration never panics, and visits every node in the snapshot exactly once, in order.
+* Nodes inserted after the snapshot was taken are not visited.
+* Nodes removed after the snapshot was taken are still visited, they are detached from the tree
+  but remain valid and may be re-inserted. To skip them use
+  [`QueryResult::iter_connected`](struct.QueryResult.html#method.iter_connected), which checks
+  each node as it is reached.
+
+Where a live view is required, one that reflects insertions and removals as iteration proceeds,
+use a [`NodeIterator`](../../traversal/struct.NodeIterator.html), or a
+[`NodeList`](../node_list/struct.NodeList.html).
+*/
+
+use crate::level2::convert::{as_attribute, as_document, as_element};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::*;
+use crate::level2::traversal::is_inclusive_ancestor;
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_COLUMN_PATH, MSG_INVALID_NODE_TYPE, MSG_WRITE_FAILED,
+};
+use std::io::Write;
+use std::ops::Deref;
+use std::vec::IntoIter;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A list of nodes, in document order, resulting from a query. This dereferences to a slice of
+/// `RefNode` so that all the usual slice operations are available, and adds bulk operations over
+/// the nodes in the result.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueryResult(Vec<RefNode>);
+
+///
+/// A single row of the table returned by [`QueryResult::table`](struct.QueryResult.html#method.table),
+/// with one value for each requested column.
+///
+pub type Row = Vec<Option<String>>;
+
+///
+/// Configures the output of [`QueryResult::export_csv`](struct.QueryResult.html#method.export_csv).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvConfig {
+    /// The column paths, as described for [`QueryResult::table`](struct.QueryResult.html#method.table).
+    pub columns: Vec<String>,
+    /// If `true` the first record written contains the column paths.
+    pub header: bool,
+    /// The character separating fields, the default is `,`.
+    pub delimiter: char,
+    /// The character used to quote fields, the default is `"`.
+    pub quote: char,
+    /// The string terminating each record, the default is `\r\n` as in RFC 4180.
+    pub line_terminator: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl From<Vec<RefNode>> for QueryResult {
+    fn from(nodes: Vec<RefNode>) -> Self {
+        Self(nodes)
+    }
+}
+
+impl From<QueryResult> for Vec<RefNode> {
+    fn from(result: QueryResult) -> Self {
+        result.0
+    }
+}
+
+impl Deref for QueryResult {
+    type Target = [RefNode];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IntoIterator for QueryResult {
+    type Item = RefNode;
+    type IntoIter = IntoIter<RefNode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl QueryResult {
+    ///
+    /// Perform `get_elements_by_tag_name` on `node`, which must be either a `Document` or an
+    /// `Element`, and wrap the results.
+    ///
+    pub fn by_tag_name(node: &RefNode, tag_name: &str) -> Self {
+        match node.node_type() {
+            NodeType::Document => as_document(node)
+                .unwrap()
+                .get_elements_by_tag_name(tag_name),
+            NodeType::Element => as_element(node).unwrap().get_elements_by_tag_name(tag_name),
+            _ => {
+                warn!("{}", MSG_INVALID_NODE_TYPE);
+                Vec::default()
+            }
+        }
+        .into()
+    }
+
+    ///
+    /// Perform `get_elements_by_tag_name_ns` on `node`, which must be either a `Document` or an
+    /// `Element`, and wrap the results.
+    ///
+    pub fn by_tag_name_ns(node: &RefNode, namespace_uri: &str, local_name: &str) -> Self {
+        match node.node_type() {
+            NodeType::Document => as_document(node)
+                .unwrap()
+                .get_elements_by_tag_name_ns(namespace_uri, local_name),
+            NodeType::Element => as_element(node)
+                .unwrap()
+                .get_elements_by_tag_name_ns(namespace_uri, local_name),
+            _ => {
+                warn!("{}", MSG_INVALID_NODE_TYPE);
+                Vec::default()
+            }
+        }
+        .into()
+    }
+
+    ///
+    /// Return the first node in the result, if any.
+    ///
+    pub fn first(&self) -> Option<RefNode> {
+        self.0.first().cloned()
+    }
+
+    ///
+    /// Return the text content of each node in the result; this is the concatenation of all
+    /// `Text` and `CDATASection` descendants of each node.
+    ///
+    pub fn texts(&self) -> Vec<String> {
+        self.0.iter().map(text_content).collect()
+    }
+
+    ///
+    /// Return the value of the attribute `name` for each node in the result that has it.
+    ///
+    pub fn attribute_values(&self, name: &str) -> Vec<String> {
+        self.0
+            .iter()
+            .filter_map(|node| match as_element(node) {
+                Ok(element) => element.get_attribute(name),
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    ///
+    /// Extract a table with one row for each node in the result and one column for each of
+    /// `columns`, where each column is a path relative to the row node:
+    ///
+    /// * `.` selects the row node itself.
+    /// * `name` selects the first child element with the tag name `name`; steps may be separated
+    ///   by `/` to select descendants, as in `address/city`.
+    /// * `@name` selects the attribute `name`, and must be the last step, as in `address/@type`.
+    ///
+    /// The value of an element is its text content; a column is `None` where the path selects
+    /// nothing.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if any of `columns` is not a valid path.
+    ///
+    pub fn table(&self, columns: &[&str]) -> Result<Vec<Row>> {
+        let paths = parse_column_paths(columns.iter().copied())?;
+        Ok(self.0.iter().map(|node| select_row(node, &paths)).collect())
+    }
+
+    ///
+    /// Write the same table as [`table`](#method.table) to `writer` in CSV format, one record at
+    /// a time, with the columns and format described by `config`. Values that contain the
+    /// delimiter, the quote character, or a line break are quoted, and quote characters within
+    /// them are doubled; columns that select nothing are written as empty fields. Returns the
+    /// number of records written, not counting the header.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if any of the configured columns is not a valid path.
+    /// * `INVALID_STATE_ERR`: Raised if writing to `writer` fails.
+    ///
+    pub fn export_csv<W: Write>(&self, mut writer: W, config: &CsvConfig) -> Result<usize> {
+        let paths = parse_column_paths(config.columns.iter().map(String::as_str))?;
+        if config.header {
+            let header: Vec<Option<String>> = config.columns.iter().cloned().map(Some).collect();
+            write_record(&mut writer, &header, config)?;
+        }
+        for node in &self.0 {
+            write_record(&mut writer, &select_row(node, &paths), config)?;
+        }
+        Ok(self.0.len())
+    }
+
+    ///
+    /// Remove each node in the result from its parent; the nodes remain in the result so they may
+    /// be re-inserted elsewhere.
+    ///
+    pub fn remove_all(&mut self) -> Result<()> {
+        for node in self.outermost() {
+            if let Some(mut parent_node) = node.parent_node() {
+                let _safe_to_ignore = parent_node.remove_child(node)?;
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Move each node in the result into a new `DocumentFragment`, created by the owner document
+    /// of the first node, and return it. Nodes that are descendants of other nodes in the result
+    /// are moved along with their ancestor rather than individually.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if the result is empty, or the first node has no owner document.
+    ///
+    pub fn into_fragment(self) -> Result<RefNode> {
+        let document_node = match self.first().and_then(|node| node.owner_document()) {
+            None => return Err(Error::NotFound),
+            Some(document_node) => document_node,
+        };
+        let document = as_document(&document_node)?;
+        let mut fragment_node = document.create_document_fragment()?;
+        for node in self.outermost() {
+            let _safe_to_ignore = fragment_node.append_child(node)?;
+        }
+        Ok(fragment_node)
+    }
+
+    ///
+    /// Returns an iterator over the nodes in the result that, at the time each is reached, are
+    /// still `root` or one of its descendants; nodes removed from below `root` since the query
+    /// was made are skipped. See [Mutation During Iteration](index.html#mutation-during-iteration).
+    ///
+    pub fn iter_connected<'a>(&'a self, root: &'a RefNode) -> impl Iterator<Item = RefNode> + 'a {
+        self.0
+            .iter()
+            .filter(move |node| is_inclusive_descendant(node, root))
+            .cloned()
+    }
+
+    ///
+    /// Return the underlying vector of nodes.
+    ///
+    pub fn into_vec(self) -> Vec<RefNode> {
+        self.0
+    }
+
+    ///
+    /// Returns those nodes in the result that do not have an ancestor also in the result.
+    ///
+    fn outermost(&self) -> Vec<RefNode> {
+        self.0
+            .iter()
+            .filter(|node| {
+                let mut current = node.parent_node();
+                while let Some(ancestor) = current {
+                    if self.0.contains(&ancestor) {
+                        return false;
+                    }
+                    current = ancestor.parent_node();
+                }
+                true
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Default for CsvConfig {
+    fn default() -> Self {
+        Self {
+            columns: Vec::default(),
+            header: false,
+            delimiter: ',',
+            quote: '"',
+            line_terminator: "\r\n".to_string(),
+        }
+    }
+}
+
+impl CsvConfig {
+    ///
+    /// Return a configuration for tab-separated values with the given columns; records are
+    /// terminated by `\n`.
+    ///
+    pub fn tsv(columns: &[&str]) -> Self {
+        Self {
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            delimiter: '\t',
+            line_terminator: "\n".to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+enum PathStep {
+    Current,
+    Child(String),
+    Attribute(String),
+}
+
+const PATH_SEPARATOR: char = '/';
+
+const PATH_CURRENT: &str = ".";
+
+const PATH_ATTRIBUTE_PREFIX: char = '@';
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Is `node` either `root` or a descendant of `root`; an attribute is a descendant of `root` if
+/// its owner element is.
+///
+fn is_inclusive_descendant(node: &RefNode, root: &RefNode) -> bool {
+    let node = if node.node_type() == NodeType::Attribute {
+        match as_attribute(node)
+            .ok()
+            .and_then(|attribute| attribute.owner_element())
+        {
+            Some(owner_element) => owner_element,
+            None => return node == root,
+        }
+    } else {
+        node.clone()
+    };
+    is_inclusive_ancestor(root, &node)
+}
+
+fn parse_column_paths<'a>(columns: impl Iterator<Item = &'a str>) -> Result<Vec<Vec<PathStep>>> {
+    columns.map(parse_column_path).collect()
+}
+
+fn parse_column_path(path: &str) -> Result<Vec<PathStep>> {
+    let steps: Vec<&str> = path.split(PATH_SEPARATOR).collect();
+    let last = steps.len() - 1;
+    steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| {
+            if *step == PATH_CURRENT {
+                Ok(PathStep::Current)
+            } else if let Some(name) = step.strip_prefix(PATH_ATTRIBUTE_PREFIX) {
+                if index == last && !name.is_empty() {
+                    Ok(PathStep::Attribute(name.to_string()))
+                } else {
+                    warn!("{}: '{}'", MSG_INVALID_COLUMN_PATH, path);
+                    Err(Error::Syntax)
+                }
+            } else if !step.is_empty() {
+                Ok(PathStep::Child(step.to_string()))
+            } else {
+                warn!("{}: '{}'", MSG_INVALID_COLUMN_PATH, path);
+                Err(Error::Syntax)
+            }
+        })
+        .collect()
+}
+
+fn select_row(node: &RefNode, paths: &[Vec<PathStep>]) -> Row {
+    paths.iter().map(|path| select(node, path)).collect()
+}
+
+fn select(node: &RefNode, path: &[PathStep]) -> Option<String> {
+    let mut current = node.clone();
+    for step in path {
+        match step {
+            PathStep::Current => {}
+            PathStep::Child(name) => {
+                current = current.child_nodes().into_iter().find(|child| {
+                    child.node_type() == NodeType::Element
+                        && child.node_name().matches_qualified_name(name)
+                })?;
+            }
+            PathStep::Attribute(name) => {
+                return as_element(&current).ok()?.get_attribute(name);
+            }
+        }
+    }
+    Some(text_content(&current))
+}
+
+fn write_record<W: Write>(
+    writer: &mut W,
+    row: &[Option<String>],
+    config: &CsvConfig,
+) -> Result<()> {
+    let record = row
+        .iter()
+        .map(|value| quote_field(value.as_deref().unwrap_or_default(), config))
+        .collect::<Vec<String>>()
+        .join(&config.delimiter.to_string());
+    write!(writer, "{}{}", record, config.line_terminator).map_err(|e| {
+        warn!("{}: '{}'", MSG_WRITE_FAILED, e);
+        Error::InvalidState
+    })
+}
+
+fn quote_field(value: &str, config: &CsvConfig) -> String {
+    if value.contains([config.delimiter, config.quote, '\r', '\n']) {
+        let quote = config.quote.to_string();
+        format!(
+            "{}{}{}",
+            quote,
+            value.replace(&quote, &format!("{}{}", quote, quote)),
+            quote
+        )
+    } else {
+        value.to_string()
+    }
+}
+
+///
+/// The concatenated text of `node`, or of all of the text and CDATA section nodes it contains.
+///
+pub(crate) fn text_content(node: &RefNode) -> String {
+    match node.node_type() {
+        NodeType::Text | NodeType::CData => node.node_value().unwrap_or_default(),
+        _ => node
+            .child_nodes()
+            .iter()
+            .map(text_content)
+            .collect::<Vec<String>>()
+            .join(""),
+    }
+}
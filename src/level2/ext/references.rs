@@ -0,0 +1,452 @@
+/*!
+Provides [`check_references`](fn.check_references.html), which checks the integrity of the
+ID and IDREF attributes in a document: that no ID value is declared twice, and that every IDREF
+and IDREFS value refers to a declared ID. Cross-reference heavy formats, such as DITA or S1000D,
+depend on these links being intact.
+
+Attributes are identified as IDs, or references, from three sources:
+
+1. `xml:id` attributes are always IDs, and if the document has the `assume_ids` processing
+   option set any attribute named `id` is also an ID, matching `Document::get_element_by_id`.
+1. Attribute-list declarations, `<!ATTLIST ...>`, in the internal subset of the document type
+   that give an attribute the type `ID`, `IDREF`, or `IDREFS`.
+1. Attributes declared on a [`ReferenceConfig`](struct.ReferenceConfig.html), for example from
+   schema information not available in the document itself.
+
+Each problem found is reported as a [`ReferenceProblem`](enum.ReferenceProblem.html) that
+includes the path, as produced by [`node_path`](fn.node_path.html), of the attribute concerned.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::references::{check_references, ReferenceConfig, ReferenceKind};
+
+let document_node = xml_dom::parser::read_xml(
+    r#"<manual><topic key="a"/><topic key="a"/><xref to="a b"/></manual>"#,
+)
+.unwrap();
+
+let mut config = ReferenceConfig::default();
+config.declare("key", ReferenceKind::Id);
+config.declare("to", ReferenceKind::IdRefs);
+let problems = check_references(&document_node, &config);
+assert_eq!(problems.len(), 2);
+assert_eq!(
+    problems[0].to_string(),
+    "duplicate ID 'a' at /manual/topic[2]/@key, first declared at /manual/topic[1]/@key"
+);
+assert_eq!(problems[1].to_string(), "dangling reference 'b' at /manual/xref/@to");
+# }
+```
+*/
+
+use crate::level2::convert::as_attribute;
+use crate::level2::ext::dtd::attribute_list_types;
+use crate::level2::ext::namespaced::resolve_declared_prefix;
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::traits::{Document, DocumentType, Node, NodeType};
+use crate::shared::error::MSG_INVALID_EXTENSION;
+use crate::shared::name::Name;
+use crate::shared::syntax::{
+    XML_ATTLIST_ID, XML_ATTLIST_IDREF, XML_ATTLIST_IDREFS, XML_NS_ATTRIBUTE, XML_NS_ATTR_ID,
+    XML_NS_URI,
+};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The role of an attribute in cross-references.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// The attribute value is an ID that identifies its element.
+    Id,
+    /// The attribute value is a reference to a single ID.
+    IdRef,
+    /// The attribute value is a white space separated list of references to IDs.
+    IdRefs,
+}
+
+///
+/// Declares the attributes that are IDs and references, in addition to those identified by the
+/// document itself.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferenceConfig {
+    declarations: Vec<Declaration>,
+    use_document_type: bool,
+}
+
+///
+/// A problem found by [`check_references`](fn.check_references.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReferenceProblem {
+    /// An ID value that has already been declared.
+    DuplicateId {
+        /// The duplicated ID value.
+        id: String,
+        /// The path of the attribute with the duplicate value.
+        path: String,
+        /// The path of the attribute that first declared the value.
+        first_path: String,
+    },
+    /// A reference to an ID value that is not declared.
+    DanglingReference {
+        /// The ID value referenced.
+        id: String,
+        /// The path of the attribute containing the reference.
+        path: String,
+    },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Check the ID and reference attributes of the elements in the tree rooted at `node`, which is
+/// usually a `Document` node. Duplicate IDs are returned first, followed by dangling references,
+/// each in document order. References are only resolved against IDs within the same tree.
+///
+pub fn check_references(node: &RefNode, config: &ReferenceConfig) -> Vec<ReferenceProblem> {
+    let document_node = match node.node_type() {
+        NodeType::Document => Some(node.clone()),
+        _ => node.owner_document(),
+    };
+    let mut declarations = config.declarations.clone();
+    if let Some(document_node) = &document_node {
+        if config.use_document_type {
+            declarations.extend(document_type_declarations(document_node));
+        }
+        if assume_ids(document_node) {
+            declarations.push(Declaration {
+                element: None,
+                namespace_uri: None,
+                name: XML_NS_ATTR_ID.to_string(),
+                kind: ReferenceKind::Id,
+            });
+        }
+    }
+
+    let root_node = match node.node_type() {
+        NodeType::Document => document_node
+            .as_ref()
+            .and_then(|node| node.document_element()),
+        _ => Some(node.clone()),
+    };
+    let mut attributes: Vec<(RefNode, ReferenceKind, String)> = Vec::new();
+    if let Some(root_node) = root_node {
+        collect_attributes(&root_node, &declarations, &mut attributes);
+    }
+
+    let mut problems: Vec<ReferenceProblem> = Vec::new();
+    let mut ids: HashMap<String, RefNode> = HashMap::new();
+    for (attribute, _, value) in attributes
+        .iter()
+        .filter(|(_, kind, _)| *kind == ReferenceKind::Id)
+    {
+        let id = value.trim();
+        match ids.get(id) {
+            Some(first) => problems.push(ReferenceProblem::DuplicateId {
+                id: id.to_string(),
+                path: node_path(attribute),
+                first_path: node_path(first),
+            }),
+            None => {
+                let _safe_to_ignore = ids.insert(id.to_string(), attribute.clone());
+            }
+        }
+    }
+    for (attribute, kind, value) in &attributes {
+        let references: Vec<&str> = match kind {
+            ReferenceKind::Id => continue,
+            ReferenceKind::IdRef => vec![value.trim()],
+            ReferenceKind::IdRefs => value.split_whitespace().collect(),
+        };
+        for id in references {
+            if !ids.contains_key(id) {
+                problems.push(ReferenceProblem::DanglingReference {
+                    id: id.to_string(),
+                    path: node_path(attribute),
+                });
+            }
+        }
+    }
+    problems
+}
+
+///
+/// Returns a path that identifies `node` within its tree, in the form of an XPath location path
+/// such as `/manual/topic[2]/@xml:id`; a position is only included for a node that has siblings
+/// of the same type and, for elements, the same name.
+///
+pub fn node_path(node: &RefNode) -> String {
+    let mut steps: Vec<String> = Vec::new();
+    let mut current = Some(node.clone());
+    while let Some(node) = current {
+        current = match node.node_type() {
+            NodeType::Attribute => {
+                steps.push(format!("@{}", node.node_name()));
+                as_attribute(&node)
+                    .ok()
+                    .and_then(|attribute| attribute.owner_element())
+            }
+            NodeType::Document => None,
+            node_type => {
+                let name = match node_type {
+                    NodeType::Element => node.node_name().to_string(),
+                    NodeType::Text | NodeType::CData => "text()".to_string(),
+                    NodeType::Comment => "comment()".to_string(),
+                    NodeType::ProcessingInstruction => "processing-instruction()".to_string(),
+                    _ => "node()".to_string(),
+                };
+                let parent_node = node.parent_node();
+                let siblings: Vec<RefNode> = parent_node
+                    .as_ref()
+                    .map(|parent_node| parent_node.child_nodes())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|sibling| {
+                        sibling.node_type() == node_type
+                            && (node_type != NodeType::Element
+                                || sibling.node_name() == node.node_name())
+                    })
+                    .collect();
+                if siblings.len() > 1 {
+                    let position = siblings
+                        .iter()
+                        .position(|sibling| *sibling == node)
+                        .unwrap_or_default();
+                    steps.push(format!("{}[{}]", name, position + 1));
+                } else {
+                    steps.push(name);
+                }
+                parent_node
+            }
+        };
+    }
+    steps.reverse();
+    format!("/{}", steps.join("/"))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for ReferenceConfig {
+    fn default() -> Self {
+        let mut config = Self::empty();
+        config.use_document_type = true;
+        config.declare_ns(XML_NS_URI, XML_NS_ATTR_ID, ReferenceKind::Id);
+        config
+    }
+}
+
+impl ReferenceConfig {
+    ///
+    /// Construct a configuration that declares no attributes, and does not read the
+    /// declarations in the document type. Attributes named `id` are still IDs if the document
+    /// has the `assume_ids` processing option set.
+    ///
+    pub fn empty() -> Self {
+        Self {
+            declarations: Default::default(),
+            use_document_type: false,
+        }
+    }
+
+    ///
+    /// Declare the attribute with the qualified name `attribute`, on any element, to be of the
+    /// given `kind`.
+    ///
+    pub fn declare(&mut self, attribute: &str, kind: ReferenceKind) {
+        self.declarations.push(Declaration {
+            element: None,
+            namespace_uri: None,
+            name: attribute.to_string(),
+            kind,
+        })
+    }
+
+    ///
+    /// Declare the attribute with the qualified name `attribute`, only on elements with the
+    /// qualified name `element`, to be of the given `kind`.
+    ///
+    pub fn declare_for_element(&mut self, element: &str, attribute: &str, kind: ReferenceKind) {
+        self.declarations.push(Declaration {
+            element: Some(element.to_string()),
+            namespace_uri: None,
+            name: attribute.to_string(),
+            kind,
+        })
+    }
+
+    ///
+    /// Declare the attribute `local_name` in the namespace `namespace_uri`, on any element and
+    /// regardless of the prefix used in the document, to be of the given `kind`.
+    ///
+    pub fn declare_ns(&mut self, namespace_uri: &str, local_name: &str, kind: ReferenceKind) {
+        self.declarations.push(Declaration {
+            element: None,
+            namespace_uri: Some(namespace_uri.to_string()),
+            name: local_name.to_string(),
+            kind,
+        })
+    }
+
+    ///
+    /// Set whether the attribute-list declarations in the internal subset of the document type
+    /// are used, the default is `true`.
+    ///
+    pub fn set_use_document_type(&mut self, use_document_type: bool) {
+        self.use_document_type = use_document_type;
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ReferenceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                ReferenceKind::Id => XML_ATTLIST_ID,
+                ReferenceKind::IdRef => XML_ATTLIST_IDREF,
+                ReferenceKind::IdRefs => XML_ATTLIST_IDREFS,
+            }
+        )
+    }
+}
+
+impl Display for ReferenceProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ReferenceProblem::DuplicateId {
+                id,
+                path,
+                first_path,
+            } => write!(
+                f,
+                "duplicate ID '{}' at {}, first declared at {}",
+                id, path, first_path
+            ),
+            ReferenceProblem::DanglingReference { id, path } => {
+                write!(f, "dangling reference '{}' at {}", id, path)
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Declaration {
+    fn matches(&self, element: &RefNode, name: &Name) -> bool {
+        if let Some(element_name) = &self.element {
+            if element.node_name().to_string() != *element_name {
+                return false;
+            }
+        }
+        match &self.namespace_uri {
+            None => name.to_string() == self.name,
+            Some(namespace_uri) => {
+                *name.local_name() == self.name
+                    && match (name.namespace_uri(), name.prefix().as_deref()) {
+                        (Some(attribute_namespace_uri), _) => {
+                            attribute_namespace_uri == namespace_uri
+                        }
+                        (None, Some(XML_NS_ATTRIBUTE)) => namespace_uri == XML_NS_URI,
+                        (None, Some(prefix)) => {
+                            resolve_declared_prefix(element, Some(prefix)).as_ref()
+                                == Some(namespace_uri)
+                        }
+                        (None, None) => false,
+                    }
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+struct Declaration {
+    element: Option<String>,
+    namespace_uri: Option<String>,
+    name: String,
+    kind: ReferenceKind,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn document_type_declarations(document_node: &RefNode) -> Vec<Declaration> {
+    let internal_subset = document_node
+        .doc_type()
+        .and_then(|doc_type| DocumentType::internal_subset(&doc_type))
+        .unwrap_or_default();
+    attribute_list_types(&internal_subset)
+        .into_iter()
+        .filter_map(|(element, attribute, attribute_type)| {
+            let kind = match attribute_type.as_str() {
+                XML_ATTLIST_ID => ReferenceKind::Id,
+                XML_ATTLIST_IDREF => ReferenceKind::IdRef,
+                XML_ATTLIST_IDREFS => ReferenceKind::IdRefs,
+                _ => return None,
+            };
+            Some(Declaration {
+                element: Some(element),
+                namespace_uri: None,
+                name: attribute,
+                kind,
+            })
+        })
+        .collect()
+}
+
+fn assume_ids(document_node: &RefNode) -> bool {
+    let ref_document = document_node.borrow();
+    if let Extension::Document { i_options, .. } = &ref_document.i_extension {
+        i_options.has_assume_ids()
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        false
+    }
+}
+
+fn collect_attributes(
+    element: &RefNode,
+    declarations: &[Declaration],
+    attributes: &mut Vec<(RefNode, ReferenceKind, String)>,
+) {
+    let mut element_attributes: Vec<(Name, RefNode)> = element.attributes().into_iter().collect();
+    element_attributes.sort_by_key(|(name, _)| name.to_string());
+    for (name, attribute) in element_attributes {
+        if let Some(declaration) = declarations
+            .iter()
+            .find(|declaration| declaration.matches(element, &name))
+        {
+            let value = as_attribute(&attribute)
+                .ok()
+                .and_then(|attribute| attribute.value())
+                .unwrap_or_default();
+            attributes.push((attribute, declaration.kind, value));
+        }
+    }
+    for child_node in element.child_nodes() {
+        if child_node.node_type() == NodeType::Element {
+            collect_attributes(&child_node, declarations, attributes);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+/*!
+Provides the [`UriResolver`](trait.UriResolver.html) trait, used to fetch external resources
+referenced by URI, and the [`DocumentImport`](../trait.DocumentImport.html) extension that uses a
+resolver to splice the content of an external document, or fragment, into a document. This is the
+primitive on which inclusion mechanisms such as XInclude can be built.
+
+Two resolvers are provided:
+
+* [`FileResolver`](struct.FileResolver.html) reads resources from the local file system, relative
+  to a base directory.
+* [`Workspace`](../workspace/struct.Workspace.html) resolves references to the other documents in
+  the workspace, relative to the system identifier of the referring document.
+
+A resolver may return either the text of a resource, which is parsed as a document or, failing
+that, as a fragment of content, or an existing node. Parsing text requires the `quick_parser`
+feature.
+
+When the imported content is inserted, its namespace declarations are fixed up: where a prefix
+used by the content would be bound differently at its new location, including a default
+namespace inherited from the new parent, a declaration is added to the imported element so that
+every name keeps the namespace it had in the external resource.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::DocumentImport;
+use xml_dom::level2::ext::resolver::{Resource, UriResolver};
+
+#[derive(Debug)]
+struct Chapters;
+
+impl UriResolver for Chapters {
+    fn fetch(&self, uri: &str, _referrer: &RefNode) -> Result<Resource> {
+        match uri {
+            "one.xml" => Ok(Resource::Text("<chapter>One</chapter>".to_string())),
+            _ => Err(Error::NotFound),
+        }
+    }
+}
+
+let mut document_node = get_implementation()
+    .create_document(Some("http://example.com/book"), Some("book"), None)
+    .unwrap();
+let mut book = as_document(&document_node).unwrap().document_element().unwrap();
+book.set_attribute("xmlns", "http://example.com/book").unwrap();
+
+let imported = document_node
+    .import_from_uri("one.xml", &mut book, &Chapters)
+    .unwrap();
+assert_eq!(imported.len(), 1);
+assert_eq!(
+    book.to_string(),
+    r#"<book xmlns="http://example.com/book"><chapter xmlns="">One</chapter></book>"#
+);
+# }
+```
+*/
+
+use crate::level2::convert::as_document;
+use crate::level2::ext::namespaced::resolve_declared_prefix;
+use crate::level2::ext::traits::DocumentImport;
+use crate::level2::ext::workspace::{import_node, Workspace};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::*;
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_NODE_TYPE, MSG_RESOURCE_NOT_FOUND, MSG_WRONG_DOCUMENT,
+};
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_NS_ATTRIBUTE};
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A resource returned by a [`UriResolver`](trait.UriResolver.html).
+///
+#[derive(Clone, Debug)]
+pub enum Resource {
+    /// The text of the resource, to be parsed as a document or a fragment of content.
+    Text(String),
+    /// An existing node, usually a `Document` node, whose content is imported.
+    Node(RefNode),
+}
+
+///
+/// Implemented by types that fetch resources identified by URI.
+///
+pub trait UriResolver: Debug {
+    ///
+    /// Fetch the resource identified by `uri`, which was referenced from the node `referrer`;
+    /// relative URIs are resolved in whatever way is appropriate for the resolver.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NotFound` if the resource does not exist, or could not be read.
+    ///
+    fn fetch(&self, uri: &str, referrer: &RefNode) -> Result<Resource>;
+}
+
+///
+/// A resolver that reads resources from the local file system. Relative URIs, and `file:` URIs,
+/// are read relative to a base directory; URIs with any other scheme are not found.
+///
+#[derive(Clone, Debug, Default)]
+pub struct FileResolver {
+    base_directory: PathBuf,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl FileResolver {
+    ///
+    /// Construct a new resolver that reads files relative to `base_directory`.
+    ///
+    pub fn new<P: Into<PathBuf>>(base_directory: P) -> Self {
+        Self {
+            base_directory: base_directory.into(),
+        }
+    }
+}
+
+impl UriResolver for FileResolver {
+    fn fetch(&self, uri: &str, _referrer: &RefNode) -> Result<Resource> {
+        let path = match uri.strip_prefix(FILE_SCHEME) {
+            Some(path) => path.strip_prefix(FILE_AUTHORITY).unwrap_or(path),
+            None if uri.contains(URI_AUTHORITY_SEPARATOR) => {
+                warn!("{}: '{}'", MSG_RESOURCE_NOT_FOUND, uri);
+                return Err(Error::NotFound);
+            }
+            None => uri,
+        };
+        std::fs::read_to_string(self.base_directory.join(path))
+            .map(Resource::Text)
+            .map_err(|_| {
+                warn!("{}: '{}'", MSG_RESOURCE_NOT_FOUND, uri);
+                Error::NotFound
+            })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl UriResolver for Workspace {
+    fn fetch(&self, uri: &str, referrer: &RefNode) -> Result<Resource> {
+        match self.resolve(None, Some(uri), self.system_id_of(referrer)) {
+            Some(document_node) => Ok(Resource::Node(document_node.clone())),
+            None => {
+                warn!("{}: '{}'", MSG_RESOURCE_NOT_FOUND, uri);
+                Err(Error::NotFound)
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentImport for RefNode {
+    fn import_from_uri(
+        &mut self,
+        uri: &str,
+        at_node: &mut Self::NodeRef,
+        resolver: &dyn UriResolver,
+    ) -> Result<Vec<Self::NodeRef>> {
+        let _safe_to_ignore = as_document(self)?;
+        match at_node.node_type() {
+            NodeType::Document if *at_node == *self => {}
+            NodeType::Element | NodeType::DocumentFragment => {
+                if at_node.owner_document().as_ref() != Some(self) {
+                    warn!("{}", MSG_WRONG_DOCUMENT);
+                    return Err(Error::WrongDocument);
+                }
+            }
+            NodeType::Document => {
+                warn!("{}", MSG_WRONG_DOCUMENT);
+                return Err(Error::WrongDocument);
+            }
+            _ => {
+                warn!("{}", MSG_INVALID_NODE_TYPE);
+                return Err(Error::HierarchyRequest);
+            }
+        }
+
+        let content = match resolver.fetch(uri, at_node)? {
+            Resource::Node(node) => content_of(&node),
+            Resource::Text(text) => content_of(&parse(&text)?),
+        };
+
+        let mut names = Default::default();
+        let mut imported = Vec::with_capacity(content.len());
+        for source in content {
+            let node = import_node(&mut names, self, &source, true)?;
+            let mut node = at_node.append_child(node)?;
+            if node.node_type() == NodeType::Element {
+                fix_namespaces(&source, &mut node)?;
+            }
+            imported.push(node);
+        }
+        Ok(imported)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const FILE_SCHEME: &str = "file:";
+
+const FILE_AUTHORITY: &str = "//";
+
+const URI_AUTHORITY_SEPARATOR: &str = "://";
+
+#[cfg(feature = "quick_parser")]
+const FRAGMENT_ELEMENT: &str = "fragment";
+
+///
+/// The nodes to import from `node`: the children of a document, other than its document type, or
+/// of a fragment, else the node itself.
+///
+fn content_of(node: &RefNode) -> Vec<RefNode> {
+    match node.node_type() {
+        NodeType::Document | NodeType::DocumentFragment => node
+            .child_nodes()
+            .into_iter()
+            .filter(|child_node| child_node.node_type() != NodeType::DocumentType)
+            .collect(),
+        _ => vec![node.clone()],
+    }
+}
+
+///
+/// Parse `text` as a document, or failing that as the content of an element; in the latter case
+/// the parsed element is returned as a document fragment holding its children.
+///
+#[cfg(feature = "quick_parser")]
+fn parse(text: &str) -> Result<RefNode> {
+    use crate::parser::read_xml;
+
+    if let Ok(document_node) = read_xml(text) {
+        if as_document(&document_node)?.document_element().is_some() {
+            return Ok(document_node);
+        }
+    }
+    let content = match text.trim_start().strip_prefix("<?xml") {
+        Some(rest) => rest.split_once("?>").map(|(_, rest)| rest).unwrap_or(rest),
+        None => text,
+    };
+    let document_node = read_xml(&format!(
+        "<{}>{}</{}>",
+        FRAGMENT_ELEMENT, content, FRAGMENT_ELEMENT
+    ))
+    .map_err(|_| Error::Syntax)?;
+    let document = as_document(&document_node)?;
+    let mut fragment_node = document.create_document_fragment()?;
+    for child_node in document.document_element().unwrap().child_nodes() {
+        let _safe_to_ignore = fragment_node.append_child(child_node)?;
+    }
+    Ok(fragment_node)
+}
+
+#[cfg(not(feature = "quick_parser"))]
+fn parse(_: &str) -> Result<RefNode> {
+    warn!("{}", crate::shared::error::MSG_PARSER_UNAVAILABLE);
+    Err(Error::NotSupported)
+}
+
+///
+/// Add namespace declarations to `imported`, the copy of `source`, wherever a prefix used by the
+/// names in `source` would resolve differently in the new location.
+///
+fn fix_namespaces(source: &RefNode, imported: &mut RefNode) -> Result<()> {
+    let mut prefixes: Vec<(Option<String>, Option<String>)> = Default::default();
+    let mut seen: HashSet<Option<String>> = Default::default();
+    let mut pending = vec![source.clone()];
+    while let Some(node) = pending.pop() {
+        if node.node_type() != NodeType::Element {
+            continue;
+        }
+        let mut names = vec![node.node_name()];
+        names.extend(
+            node.attributes()
+                .into_keys()
+                .filter(|name| name.prefix().is_some() && !name.is_namespace_attribute()),
+        );
+        for name in names {
+            let prefix = name.prefix().clone();
+            if matches!(
+                prefix.as_deref(),
+                Some(XML_NS_ATTRIBUTE) | Some(XMLNS_NS_ATTRIBUTE)
+            ) || !seen.insert(prefix.clone())
+            {
+                continue;
+            }
+            let namespace_uri = resolve_declared_prefix(source, prefix.as_deref())
+                .or_else(|| name.namespace_uri().clone());
+            prefixes.push((prefix, namespace_uri));
+        }
+        pending.extend(node.child_nodes());
+    }
+
+    for (prefix, namespace_uri) in prefixes {
+        if resolve_declared_prefix(imported, prefix.as_deref()) == namespace_uri {
+            continue;
+        }
+        let qualified_name = match &prefix {
+            None => XMLNS_NS_ATTRIBUTE.to_string(),
+            Some(prefix) => format!("{}:{}", XMLNS_NS_ATTRIBUTE, prefix),
+        };
+        match (prefix, namespace_uri) {
+            (_, Some(namespace_uri)) => {
+                imported.set_attribute_ns(XMLNS_NS_URI, &qualified_name, &namespace_uri)?
+            }
+            (None, None) => imported.set_attribute_ns(XMLNS_NS_URI, &qualified_name, "")?,
+            // a prefix cannot be un-declared, so it is left to resolve to the new binding.
+            (Some(_), None) => {}
+        }
+    }
+    Ok(())
+}
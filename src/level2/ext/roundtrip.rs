@@ -0,0 +1,175 @@
+/*!
+Provides [`roundtrip_check`](fn.roundtrip_check.html), a single entry point that parses a document,
+serializes it, parses the serialized form again, and compares the two trees; intended as the
+target of a fuzzer, or for conformance testing of this crate and of pipelines built on it.
+
+A round trip succeeds if the serialized form can be parsed, the resulting tree is
+[semantically equal](../compare/fn.semantic_eq.html) to the tree parsed from the input, and the
+[canonical form](../serializer/fn.canonicalize.html), with comments, of both trees is identical;
+the serialized form itself is not compared as the order in which attributes are written is not
+defined. Input that cannot be parsed is reported, but is not a failure of the round trip
+itself, as most generated input is not well-formed;
+[`is_bug`](struct.RoundtripReport.html#method.is_bug) distinguishes the two.
+
+This module is only available with the `quick_parser` feature.
+
+# Example
+
+```rust
+use xml_dom::level2::ext::roundtrip::{roundtrip_check, RoundtripOptions};
+
+let report = roundtrip_check(
+    r#"<order id="1" status="open"><!-- note --><item>a &lt; b</item></order>"#,
+    &RoundtripOptions::default(),
+);
+assert!(report.is_ok());
+
+let report = roundtrip_check("<order>", &RoundtripOptions::default());
+assert!(!report.is_ok());
+assert!(!report.is_bug());
+```
+
+A fuzz target, for example using `cargo fuzz`, then reduces to the following.
+
+```rust,ignore
+fuzz_target!(|data: &str| {
+    let report = roundtrip_check(data, &RoundtripOptions::default());
+    assert!(!report.is_bug(), "{:?}", report);
+});
+```
+*/
+
+use crate::level2::ext::compare::{semantic_eq, CompareOptions};
+use crate::level2::ext::serializer::{canonicalize, serialize, CanonicalOptions, WriteOptions};
+use crate::parser::{read_xml, Error as ParseError};
+use crate::shared::error::Error;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Options that control the behavior of [`roundtrip_check`](fn.roundtrip_check.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoundtripOptions {
+    /// The options used to serialize each tree, the default is the lenient profile, so that input
+    /// accepted by the parser with undeclared prefixes is not reported as a failure.
+    pub write: WriteOptions,
+    /// The options used to compare the two trees, the default is `CompareOptions::default()`.
+    pub compare: CompareOptions,
+}
+
+///
+/// The reason a round trip did not succeed.
+///
+#[derive(Clone, Debug)]
+pub enum RoundtripFailure {
+    /// The input could not be parsed.
+    Parse(ParseError),
+    /// The tree parsed from the input could not be serialized.
+    Serialize(Error),
+    /// The serialized form of the input could not be parsed.
+    Reparse(ParseError),
+    /// The tree parsed from the serialized form is not semantically equal to the tree parsed from
+    /// the input.
+    NotEqual,
+    /// The canonical forms of the two trees, held by this variant, are not identical.
+    Unstable(String, String),
+}
+
+///
+/// The result of a [`roundtrip_check`](fn.roundtrip_check.html).
+///
+#[derive(Clone, Debug)]
+pub struct RoundtripReport {
+    /// The serialized form of the tree parsed from the input, if it could be parsed and
+    /// serialized.
+    pub serialized: Option<String>,
+    /// The reason the round trip did not succeed, `None` if it did.
+    pub failure: Option<RoundtripFailure>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Parse `input`, serialize the resulting tree, and check that the serialized form parses to an
+/// equal tree with the same canonical form, according to `options`. This function does not
+/// panic on any input, all failures are returned in the report.
+///
+pub fn roundtrip_check(input: &str, options: &RoundtripOptions) -> RoundtripReport {
+    let mut report = RoundtripReport {
+        serialized: None,
+        failure: None,
+    };
+    report.failure = check(input, options, &mut report.serialized).err();
+    report
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for RoundtripOptions {
+    fn default() -> Self {
+        Self {
+            write: WriteOptions::lenient(),
+            compare: Default::default(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl RoundtripReport {
+    ///
+    /// Returns `true` if the round trip succeeded.
+    ///
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    ///
+    /// Returns `true` if the round trip failed after the input was successfully parsed, which
+    /// indicates a defect in the parser, serializer, or comparison rather than bad input.
+    ///
+    pub fn is_bug(&self) -> bool {
+        !matches!(self.failure, None | Some(RoundtripFailure::Parse(_)))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn check(
+    input: &str,
+    options: &RoundtripOptions,
+    serialized: &mut Option<String>,
+) -> Result<(), RoundtripFailure> {
+    let first_node = read_xml(input).map_err(RoundtripFailure::Parse)?;
+    let first_text = serialize(&first_node, &options.write).map_err(RoundtripFailure::Serialize)?;
+    *serialized = Some(first_text.clone());
+
+    let second_node = read_xml(&first_text).map_err(RoundtripFailure::Reparse)?;
+    if !semantic_eq(&first_node, &second_node, &options.compare) {
+        return Err(RoundtripFailure::NotEqual);
+    }
+    let canonical_options = CanonicalOptions {
+        with_comments: true,
+        ..Default::default()
+    };
+    let first_canonical =
+        canonicalize(&first_node, &canonical_options).map_err(RoundtripFailure::Serialize)?;
+    let second_canonical =
+        canonicalize(&second_node, &canonical_options).map_err(RoundtripFailure::Serialize)?;
+    if first_canonical != second_canonical {
+        return Err(RoundtripFailure::Unstable(
+            first_canonical,
+            second_canonical,
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,318 @@
+/*!
+Provides a SAX-style event interface, so that the same [`ContentHandler`](trait.ContentHandler.html)
+implementations may be used for both DOM and streaming pipelines.
+
+The [`walk`](fn.walk.html) function traverses an existing tree and invokes a handler for each node
+in document order; the [`DocumentBuilder`](struct.DocumentBuilder.html) is a handler that
+constructs a new document from the events it receives. Together these allow a tree to be copied,
+or a handler to be tested against a DOM, and any other source of events, such as a streaming
+parser, may drive a `DocumentBuilder`.
+
+Text and attribute values are passed to handlers unescaped, as they would be reported by a parser.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::sax::{walk, ContentHandler};
+
+#[derive(Default)]
+struct ElementCounter(usize);
+
+impl ContentHandler for ElementCounter {
+    fn start_element(&mut self, _name: &Name, _attributes: &[(Name, String)]) -> Result<()> {
+        self.0 += 1;
+        Ok(())
+    }
+}
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("list"), None).unwrap();
+let document = convert::as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _safe_to_ignore = root_node.append_child(document.create_element("item").unwrap()).unwrap();
+
+let mut counter = ElementCounter::default();
+walk(&document_node, &mut counter).unwrap();
+assert_eq!(counter.0, 2);
+```
+*/
+
+use crate::level2::convert::as_document_type;
+use crate::level2::ext::decl::{PrologPosition, XmlDecl};
+use crate::level2::ext::mutation::raw_value;
+use crate::level2::ext::traits::DocumentDecl;
+use crate::level2::ext::tree_builder::TreeBuilder;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::ordered_attributes;
+use crate::level2::traits::*;
+use crate::shared::display::internal_subset_text;
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
+use crate::shared::name::Name;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Receives the content of a document as a sequence of events; all methods have a default
+/// implementation that ignores the event. Any error returned by a method stops the process
+/// producing events, and is returned to its caller.
+///
+pub trait ContentHandler {
+    ///
+    /// The beginning of a document, before any other event.
+    ///
+    fn start_document(&mut self) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// The end of a document, after all other events.
+    ///
+    fn end_document(&mut self) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// The XML declaration of a document, immediately after `start_document`.
+    ///
+    fn xml_declaration(&mut self, _xml_decl: &XmlDecl) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// The document type declaration, in its place within the document prolog.
+    ///
+    fn document_type(
+        &mut self,
+        _name: &Name,
+        _public_id: Option<&str>,
+        _system_id: Option<&str>,
+        _internal_subset: Option<&str>,
+    ) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// The start of an element, with its attributes in no particular order; namespace
+    /// declarations are included as attributes.
+    ///
+    fn start_element(&mut self, _name: &Name, _attributes: &[(Name, String)]) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// The end of the element started by the matching `start_element`.
+    ///
+    fn end_element(&mut self, _name: &Name) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// Character data within an element.
+    ///
+    fn characters(&mut self, _text: &str) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// The content of a CDATA section within an element.
+    ///
+    fn cdata_section(&mut self, _text: &str) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// A comment.
+    ///
+    fn comment(&mut self, _text: &str) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// A processing instruction.
+    ///
+    fn processing_instruction(&mut self, _target: &str, _data: Option<&str>) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// An entity reference that has not been expanded.
+    ///
+    fn skipped_entity(&mut self, _name: &Name) -> Result<()> {
+        Ok(())
+    }
+    ///
+    /// Markup that is not represented by the DOM, held by an unparsed markup node; see the
+    /// [`unparsed`](../unparsed/index.html) module.
+    ///
+    fn unparsed_markup(&mut self, _markup: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+///
+/// A [`ContentHandler`](trait.ContentHandler.html) that constructs a new document from the events
+/// it receives, by passing each to a
+/// [`TreeBuilder`](../tree_builder/struct.TreeBuilder.html); the sequence of events is therefore
+/// checked as it is for the [`parser`](../../../parser/index.html).
+///
+/// Comments and processing instructions that are received before the document type declaration
+/// are placed before it in the document prolog.
+///
+#[derive(Debug, Default)]
+pub struct DocumentBuilder {
+    builder: TreeBuilder,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Traverse the tree rooted at `node` in document order, invoking `handler` for each node.
+///
+/// If `node` is a document the traversal begins with `start_document` and ends with
+/// `end_document`, otherwise only the events for `node` and its descendants are produced.
+/// Entity references with children are expanded, and `skipped_entity` is called for those
+/// without.
+///
+/// **Exceptions**
+///
+/// * `NOT_SUPPORTED_ERR`: Raised if `node` is an `Attr`, `Entity`, or `Notation` node.
+///
+pub fn walk<H: ContentHandler + ?Sized>(node: &RefNode, handler: &mut H) -> Result<()> {
+    match node.node_type() {
+        NodeType::Document => {
+            handler.start_document()?;
+            if let Some(xml_declaration) = node.xml_declaration() {
+                handler.xml_declaration(&xml_declaration)?;
+            }
+            let mut doc_type = node.doc_type();
+            for child in node.child_nodes() {
+                if node.prolog_position(&child) != Some(PrologPosition::BeforeDocType) {
+                    if let Some(doc_type) = doc_type.take() {
+                        walk(&doc_type, handler)?;
+                    }
+                }
+                walk(&child, handler)?;
+            }
+            if let Some(doc_type) = doc_type {
+                walk(&doc_type, handler)?;
+            }
+            handler.end_document()
+        }
+        NodeType::DocumentType => {
+            let doc_type = as_document_type(node)?;
+            handler.document_type(
+                &doc_type.node_name(),
+                doc_type.public_id().as_deref(),
+                doc_type.system_id().as_deref(),
+                internal_subset_text(node).as_deref(),
+            )
+        }
+        NodeType::Element => {
+            let name = node.node_name();
+            let attributes: Vec<(Name, String)> = ordered_attributes(node)
+                .into_iter()
+                .map(|(name, attribute)| (name, raw_value(&attribute)))
+                .collect();
+            handler.start_element(&name, &attributes)?;
+            for child in node.child_nodes() {
+                walk(&child, handler)?;
+            }
+            handler.end_element(&name)
+        }
+        NodeType::Text => handler.characters(&node.node_value().unwrap_or_default()),
+        NodeType::CData => handler.cdata_section(&node.node_value().unwrap_or_default()),
+        NodeType::Comment => handler.comment(&node.node_value().unwrap_or_default()),
+        NodeType::ProcessingInstruction => handler
+            .processing_instruction(&node.node_name().to_string(), node.node_value().as_deref()),
+        NodeType::UnparsedMarkup => handler.unparsed_markup(&node.node_value().unwrap_or_default()),
+        NodeType::EntityReference if !node.has_child_nodes() => {
+            handler.skipped_entity(&node.node_name())
+        }
+        NodeType::EntityReference | NodeType::DocumentFragment => {
+            for child in node.child_nodes() {
+                walk(&child, handler)?;
+            }
+            Ok(())
+        }
+        NodeType::Attribute | NodeType::Entity | NodeType::Notation => {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            Err(Error::NotSupported)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl ContentHandler for DocumentBuilder {
+    fn xml_declaration(&mut self, xml_decl: &XmlDecl) -> Result<()> {
+        self.builder.xml_declaration(xml_decl.clone())
+    }
+
+    fn document_type(
+        &mut self,
+        name: &Name,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+        internal_subset: Option<&str>,
+    ) -> Result<()> {
+        self.builder
+            .document_type(&name.to_string(), public_id, system_id, internal_subset)
+    }
+
+    fn start_element(&mut self, name: &Name, attributes: &[(Name, String)]) -> Result<()> {
+        self.builder.start_element_ns(name, attributes)
+    }
+
+    fn end_element(&mut self, name: &Name) -> Result<()> {
+        self.builder.end_element(&name.to_string())
+    }
+
+    fn characters(&mut self, text: &str) -> Result<()> {
+        self.builder.text(text)
+    }
+
+    fn cdata_section(&mut self, text: &str) -> Result<()> {
+        self.builder.cdata_section(text)
+    }
+
+    fn comment(&mut self, text: &str) -> Result<()> {
+        self.builder.comment(text)
+    }
+
+    fn processing_instruction(&mut self, target: &str, data: Option<&str>) -> Result<()> {
+        self.builder.processing_instruction(target, data)
+    }
+
+    fn skipped_entity(&mut self, name: &Name) -> Result<()> {
+        self.builder.entity_reference(&name.to_string())
+    }
+
+    fn unparsed_markup(&mut self, markup: &str) -> Result<()> {
+        self.builder.unparsed_markup(markup)
+    }
+}
+
+impl DocumentBuilder {
+    ///
+    /// Construct a new builder.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Returns the number of elements that have been started but not yet ended.
+    ///
+    pub fn depth(&self) -> usize {
+        self.builder.depth()
+    }
+
+    ///
+    /// Return the document constructed from the events received.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if any element has been started but not ended.
+    /// * `SYNTAX_ERR`: Raised if the document has no document element.
+    ///
+    pub fn into_document(self) -> Result<RefNode> {
+        self.builder.finish()
+    }
+}
@@ -0,0 +1,728 @@
+/*!
+Provides [`Selector`](struct.Selector.html), and the [`Selectable`](../trait.Selectable.html)
+trait, to find elements using a subset of the
+[CSS Selectors Level 3](https://www.w3.org/TR/selectors-3/) syntax, for clients that do not need
+the full power of the [`xpath`](../xpath/index.html) module.
+
+The following selectors are supported:
+
+* `*`, `tag`, `ns|tag`, `*|tag`, and `|tag`; type selectors.
+* `#id`, matching an `id` or `xml:id` attribute.
+* `.class`, matching a word in a white space separated `class` attribute.
+* `[attr]`, `[attr=value]`, `[attr~=value]`, `[attr|=value]`, `[attr^=value]`,
+  `[attr$=value]`, and `[attr*=value]`; the attribute name may also have a namespace prefix and
+  the value may be quoted.
+* `:root`, `:first-child`, `:last-child`, `:only-child`, and `:empty`.
+* The descendant (white space), child (`>`), next-sibling (`+`), and subsequent-sibling (`~`)
+  combinators.
+* A comma separated list of selectors, matching any of them.
+
+# Names and Namespaces
+
+As XML names may contain the characters `.` and `:`, which are significant in selectors, the CSS
+namespace separator `|` is used between a prefix and local name, and any character may be
+escaped with a `\`.
+
+A type selector without a namespace prefix matches the local name of an element in any
+namespace, whereas an attribute name without a prefix only matches an attribute without one. A
+namespace prefix bound with [`Selector::set_namespace`](struct.Selector.html#method.set_namespace)
+matches by namespace URI; otherwise it matches elements and attributes with the same prefix.
+All comparisons are case sensitive.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::Selectable;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("book"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let mut chapter_node = document.create_element("chapter").unwrap();
+let mut title_node = document.create_element("title").unwrap();
+title_node.set_attribute("class", "heading numbered").unwrap();
+let _safe_to_ignore = chapter_node.append_child(title_node).unwrap();
+let _safe_to_ignore = root_node.append_child(chapter_node).unwrap();
+
+let titles = root_node.select("chapter > title.heading").unwrap();
+assert_eq!(titles.len(), 1);
+assert!(titles[0].matches_selector("book title[class~=numbered]").unwrap());
+```
+*/
+
+use crate::level2::ext::mutation::raw_value;
+use crate::level2::ext::namespaced::{element_namespace, resolve_declared_prefix};
+use crate::level2::ext::traits::Selectable;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Node, NodeType};
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE, MSG_INVALID_SELECTOR};
+use crate::shared::name::Name;
+use crate::shared::syntax::{XML_NS_ATTRIBUTE, XML_NS_URI};
+use crate::shared::text::is_xml_space;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A parsed selector, or list of selectors, that may be matched against many elements.
+///
+/// The `Display` implementation writes the original selector.
+///
+#[derive(Clone, Debug)]
+pub struct Selector {
+    source: String,
+    alternatives: Vec<Complex>,
+    namespaces: HashMap<String, String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Selectable for RefNode {
+    fn select(&self, selector: &str) -> Result<Vec<Self::NodeRef>> {
+        let selector = Selector::parse(selector)?;
+        match self.node_type() {
+            NodeType::Element | NodeType::Document | NodeType::DocumentFragment => {
+                Ok(selector.select(self))
+            }
+            _ => {
+                warn!("{}", MSG_INVALID_NODE_TYPE);
+                Err(Error::InvalidState)
+            }
+        }
+    }
+
+    fn select_first(&self, selector: &str) -> Result<Option<Self::NodeRef>> {
+        Ok(self.select(selector)?.into_iter().next())
+    }
+
+    fn matches_selector(&self, selector: &str) -> Result<bool> {
+        Ok(Selector::parse(selector)?.matches(self))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Selector {
+    ///
+    /// Parse `selector`, see the [module](index.html) documentation for the supported syntax.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `selector` is empty, or uses an unsupported feature.
+    ///
+    pub fn parse(selector: &str) -> Result<Self> {
+        let mut parser = Parser {
+            chars: selector.chars().collect(),
+            index: 0,
+        };
+        let mut alternatives = vec![parser.parse_complex()?];
+        while parser.next_is(',') {
+            alternatives.push(parser.parse_complex()?);
+        }
+        if parser.index != parser.chars.len() {
+            warn!("{}: '{}'", MSG_INVALID_SELECTOR, selector);
+            return Err(Error::Syntax);
+        }
+        Ok(Self {
+            source: selector.to_string(),
+            alternatives,
+            namespaces: Default::default(),
+        })
+    }
+
+    ///
+    /// Bind `prefix`, as used in this selector, to `namespace_uri` so that names with this prefix
+    /// are matched by namespace rather than by prefix.
+    ///
+    pub fn set_namespace(&mut self, prefix: &str, namespace_uri: &str) {
+        let _safe_to_ignore = self
+            .namespaces
+            .insert(prefix.to_string(), namespace_uri.to_string());
+    }
+
+    ///
+    /// Returns `true` if `node` is an element matched by any selector in this list.
+    ///
+    pub fn matches(&self, node: &RefNode) -> bool {
+        node.node_type() == NodeType::Element
+            && self
+                .alternatives
+                .iter()
+                .any(|complex| self.matches_complex(complex, complex.compounds.len() - 1, node))
+    }
+
+    ///
+    /// Returns all elements below `node` that are matched by this selector, in document order.
+    /// As for the DOM `querySelectorAll` method the whole selector is matched against the
+    /// document, so `a b` will select `b` elements below `node` even if `a` is an ancestor of
+    /// `node`.
+    ///
+    pub fn select(&self, node: &RefNode) -> Vec<RefNode> {
+        let mut results: Vec<RefNode> = Vec::new();
+        self.select_into(node, &mut results);
+        results
+    }
+
+    fn select_into(&self, node: &RefNode, results: &mut Vec<RefNode>) {
+        for child in node.child_nodes() {
+            if child.node_type() == NodeType::Element {
+                if self.matches(&child) {
+                    results.push(child.clone());
+                }
+                self.select_into(&child, results);
+            }
+        }
+    }
+
+    ///
+    /// Match the compound at `index`, and all those to its left, with `element` as the subject
+    /// of the compound at `index`.
+    ///
+    fn matches_complex(&self, complex: &Complex, index: usize, element: &RefNode) -> bool {
+        if !self.matches_compound(&complex.compounds[index], element) {
+            return false;
+        }
+        if index == 0 {
+            return true;
+        }
+        match complex.combinators[index - 1] {
+            Combinator::Child => parent_element(element)
+                .map(|parent| self.matches_complex(complex, index - 1, &parent))
+                .unwrap_or(false),
+            Combinator::Descendant => {
+                let mut current = parent_element(element);
+                while let Some(ancestor) = current {
+                    if self.matches_complex(complex, index - 1, &ancestor) {
+                        return true;
+                    }
+                    current = parent_element(&ancestor);
+                }
+                false
+            }
+            Combinator::NextSibling => preceding_elements(element)
+                .first()
+                .map(|sibling| self.matches_complex(complex, index - 1, sibling))
+                .unwrap_or(false),
+            Combinator::SubsequentSibling => preceding_elements(element)
+                .iter()
+                .any(|sibling| self.matches_complex(complex, index - 1, sibling)),
+        }
+    }
+
+    fn matches_compound(&self, compound: &Compound, element: &RefNode) -> bool {
+        let name = element.node_name();
+        if let Some(test) = &compound.element {
+            if !self.matches_name(test, &name, true, || element_namespace(element)) {
+                return false;
+            }
+        }
+        compound
+            .conditions
+            .iter()
+            .all(|condition| self.matches_condition(condition, element))
+    }
+
+    fn matches_condition(&self, condition: &Condition, element: &RefNode) -> bool {
+        match condition {
+            Condition::Id(id) => element.attributes().iter().any(|(name, attribute)| {
+                let is_id = match name.prefix() {
+                    None => name.local_name() == ATTRIBUTE_ID,
+                    Some(prefix) => prefix == XML_NS_ATTRIBUTE && name.local_name() == ATTRIBUTE_ID,
+                };
+                is_id && &raw_value(attribute) == id
+            }),
+            Condition::Class(class) => element.attributes().iter().any(|(name, attribute)| {
+                name.prefix().is_none()
+                    && name.local_name() == ATTRIBUTE_CLASS
+                    && raw_value(attribute)
+                        .split(is_xml_space)
+                        .any(|word| word == class)
+            }),
+            Condition::Attribute(test, value_test) => {
+                element.attributes().iter().any(|(name, attribute)| {
+                    self.matches_name(test, name, false, || attribute_namespace(element, name))
+                        && match value_test {
+                            None => true,
+                            Some((operator, expected)) => {
+                                operator.matches(&raw_value(attribute), expected)
+                            }
+                        }
+                })
+            }
+            Condition::Pseudo(pseudo) => match pseudo {
+                Pseudo::Root => element
+                    .parent_node()
+                    .map(|parent| parent.node_type() == NodeType::Document)
+                    .unwrap_or(false),
+                Pseudo::FirstChild => {
+                    element.parent_node().is_some() && preceding_elements(element).is_empty()
+                }
+                Pseudo::LastChild => {
+                    element.parent_node().is_some() && following_elements(element).is_empty()
+                }
+                Pseudo::OnlyChild => {
+                    element.parent_node().is_some()
+                        && preceding_elements(element).is_empty()
+                        && following_elements(element).is_empty()
+                }
+                Pseudo::Empty => element.child_nodes().iter().all(|child| {
+                    matches!(
+                        child.node_type(),
+                        NodeType::Comment | NodeType::ProcessingInstruction
+                    )
+                }),
+            },
+        }
+    }
+
+    ///
+    /// Match a name, the namespace is only computed if required. If `any_by_default` is `true`
+    /// a name without a namespace prefix in the selector matches in any namespace, as for
+    /// elements; otherwise it only matches names without a prefix, as for attributes.
+    ///
+    fn matches_name<F>(
+        &self,
+        test: &NameTest,
+        name: &Name,
+        any_by_default: bool,
+        namespace_uri: F,
+    ) -> bool
+    where
+        F: FnOnce() -> Option<String>,
+    {
+        if let Some(local_name) = &test.local_name {
+            if name.local_name() != local_name {
+                return false;
+            }
+        }
+        match &test.namespace {
+            NamespaceTest::Any => true,
+            NamespaceTest::Unspecified => any_by_default || name.prefix().is_none(),
+            NamespaceTest::None => {
+                if any_by_default {
+                    namespace_uri().is_none()
+                } else {
+                    name.prefix().is_none()
+                }
+            }
+            NamespaceTest::Prefix(prefix) => match self.namespaces.get(prefix) {
+                Some(expected) => namespace_uri().as_ref() == Some(expected),
+                None => name.prefix().as_ref() == Some(prefix),
+            },
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl AttributeOperator {
+    fn matches(&self, value: &str, expected: &str) -> bool {
+        match self {
+            AttributeOperator::Equal => value == expected,
+            AttributeOperator::Includes => {
+                !expected.is_empty() && value.split(is_xml_space).any(|word| word == expected)
+            }
+            AttributeOperator::DashMatch => {
+                value == expected
+                    || (value.starts_with(expected) && value[expected.len()..].starts_with('-'))
+            }
+            AttributeOperator::Prefix => !expected.is_empty() && value.starts_with(expected),
+            AttributeOperator::Suffix => !expected.is_empty() && value.ends_with(expected),
+            AttributeOperator::Substring => !expected.is_empty() && value.contains(expected),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Parser {
+    fn parse_complex(&mut self) -> Result<Complex> {
+        let _safe_to_ignore = self.skip_space();
+        let mut complex = Complex {
+            compounds: vec![self.parse_compound()?],
+            combinators: Vec::new(),
+        };
+        loop {
+            let had_space = self.skip_space();
+            let combinator = match self.peek() {
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::NextSibling,
+                Some('~') => Combinator::SubsequentSibling,
+                Some(',') | None => return Ok(complex),
+                Some(_) if had_space => Combinator::Descendant,
+                Some(_) => return self.invalid(),
+            };
+            if combinator != Combinator::Descendant {
+                self.index += 1;
+                let _safe_to_ignore = self.skip_space();
+            }
+            complex.combinators.push(combinator);
+            complex.compounds.push(self.parse_compound()?);
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound> {
+        let element = match self.peek() {
+            Some('*') | Some('|') => Some(self.parse_name_test()?),
+            Some(c) if is_ident_start(c) => Some(self.parse_name_test()?),
+            _ => None,
+        };
+        let mut conditions: Vec<Condition> = Vec::new();
+        loop {
+            let condition = match self.peek() {
+                Some('#') => {
+                    self.index += 1;
+                    Condition::Id(self.parse_ident()?)
+                }
+                Some('.') => {
+                    self.index += 1;
+                    Condition::Class(self.parse_ident()?)
+                }
+                Some('[') => {
+                    self.index += 1;
+                    self.parse_attribute()?
+                }
+                Some(':') => {
+                    self.index += 1;
+                    Condition::Pseudo(match self.parse_ident()?.as_str() {
+                        "root" => Pseudo::Root,
+                        "first-child" => Pseudo::FirstChild,
+                        "last-child" => Pseudo::LastChild,
+                        "only-child" => Pseudo::OnlyChild,
+                        "empty" => Pseudo::Empty,
+                        _ => return self.invalid(),
+                    })
+                }
+                _ => break,
+            };
+            conditions.push(condition);
+        }
+        if element.is_none() && conditions.is_empty() {
+            self.invalid()
+        } else {
+            Ok(Compound {
+                element,
+                conditions,
+            })
+        }
+    }
+
+    fn parse_attribute(&mut self) -> Result<Condition> {
+        let _safe_to_ignore = self.skip_space();
+        let test = self.parse_name_test()?;
+        if test.local_name.is_none() {
+            return self.invalid();
+        }
+        let _safe_to_ignore = self.skip_space();
+        let operator = match self.peek() {
+            Some(']') => {
+                self.index += 1;
+                return Ok(Condition::Attribute(test, None));
+            }
+            Some('=') => AttributeOperator::Equal,
+            Some('~') => AttributeOperator::Includes,
+            Some('|') => AttributeOperator::DashMatch,
+            Some('^') => AttributeOperator::Prefix,
+            Some('$') => AttributeOperator::Suffix,
+            Some('*') => AttributeOperator::Substring,
+            _ => return self.invalid(),
+        };
+        self.index += 1;
+        if operator != AttributeOperator::Equal && !self.next_is('=') {
+            return self.invalid();
+        }
+        let _safe_to_ignore = self.skip_space();
+        let value = match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.index += 1;
+                let mut value = String::new();
+                loop {
+                    match self.advance() {
+                        Some(c) if c == quote => break,
+                        Some('\\') => match self.advance() {
+                            Some(c) => value.push(c),
+                            None => return self.invalid(),
+                        },
+                        Some(c) => value.push(c),
+                        None => return self.invalid(),
+                    }
+                }
+                value
+            }
+            _ => self.parse_ident()?,
+        };
+        let _safe_to_ignore = self.skip_space();
+        if self.next_is(']') {
+            Ok(Condition::Attribute(test, Some((operator, value))))
+        } else {
+            self.invalid()
+        }
+    }
+
+    ///
+    /// Parse `name`, `*`, `prefix|name`, `*|name`, or `|name`; where `name` may also be `*`.
+    ///
+    fn parse_name_test(&mut self) -> Result<NameTest> {
+        let first = self.parse_ident_or_wildcard()?;
+        if self.peek() == Some('|') && self.peek_at(1) != Some('=') {
+            self.index += 1;
+            let namespace = match first {
+                Some(prefix) if prefix.is_empty() => NamespaceTest::None,
+                Some(prefix) => NamespaceTest::Prefix(prefix),
+                None => NamespaceTest::Any,
+            };
+            Ok(NameTest {
+                namespace,
+                local_name: self.parse_ident_or_wildcard()?,
+            })
+        } else {
+            match first {
+                Some(local_name) if local_name.is_empty() => self.invalid(),
+                local_name => Ok(NameTest {
+                    namespace: NamespaceTest::Unspecified,
+                    local_name,
+                }),
+            }
+        }
+    }
+
+    ///
+    /// Returns `None` for `*`, an empty string if the next character is `|`, otherwise an
+    /// identifier.
+    ///
+    fn parse_ident_or_wildcard(&mut self) -> Result<Option<String>> {
+        match self.peek() {
+            Some('*') => {
+                self.index += 1;
+                Ok(None)
+            }
+            Some('|') => Ok(Some(String::new())),
+            _ => Ok(Some(self.parse_ident()?)),
+        }
+    }
+
+    fn parse_ident(&mut self) -> Result<String> {
+        let mut ident = String::new();
+        loop {
+            match self.peek() {
+                Some('\\') => {
+                    self.index += 1;
+                    match self.advance() {
+                        Some(c) => ident.push(c),
+                        None => return self.invalid(),
+                    }
+                }
+                Some(c) if ident.is_empty() && is_ident_start(c) => {
+                    self.index += 1;
+                    ident.push(c);
+                }
+                Some(c) if !ident.is_empty() && is_ident_char(c) => {
+                    self.index += 1;
+                    ident.push(c);
+                }
+                _ => break,
+            }
+        }
+        if ident.is_empty() {
+            self.invalid()
+        } else {
+            Ok(ident)
+        }
+    }
+
+    fn skip_space(&mut self) -> bool {
+        let start = self.index;
+        while matches!(self.peek(), Some(c) if is_xml_space(c)) {
+            self.index += 1;
+        }
+        self.index != start
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.index += 1;
+        }
+        c
+    }
+
+    fn next_is(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn invalid<T>(&self) -> Result<T> {
+        warn!(
+            "{}: '{}'",
+            MSG_INVALID_SELECTOR,
+            self.chars.iter().collect::<String>()
+        );
+        Err(Error::Syntax)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const ATTRIBUTE_ID: &str = "id";
+const ATTRIBUTE_CLASS: &str = "class";
+
+///
+/// A sequence of compound selectors separated by combinators, `combinators[i]` is between
+/// `compounds[i]` and `compounds[i + 1]`.
+///
+#[derive(Clone, Debug)]
+struct Complex {
+    compounds: Vec<Compound>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+#[derive(Clone, Debug)]
+struct Compound {
+    element: Option<NameTest>,
+    conditions: Vec<Condition>,
+}
+
+///
+/// A name, where a `local_name` of `None` is the wild card `*`.
+///
+#[derive(Clone, Debug)]
+struct NameTest {
+    namespace: NamespaceTest,
+    local_name: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+enum NamespaceTest {
+    /// No `|` separator was present.
+    Unspecified,
+    /// `*|name`
+    Any,
+    /// `|name`
+    None,
+    /// `prefix|name`
+    Prefix(String),
+}
+
+#[derive(Clone, Debug)]
+enum Condition {
+    Id(String),
+    Class(String),
+    Attribute(NameTest, Option<(AttributeOperator, String)>),
+    Pseudo(Pseudo),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AttributeOperator {
+    Equal,
+    Includes,
+    DashMatch,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Pseudo {
+    Root,
+    FirstChild,
+    LastChild,
+    OnlyChild,
+    Empty,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn is_ident_start(c: char) -> bool {
+    c == '_' || c == '-' || c.is_alphabetic() || !c.is_ascii()
+}
+
+fn is_ident_char(c: char) -> bool {
+    is_ident_start(c) || c.is_ascii_digit()
+}
+
+fn parent_element(element: &RefNode) -> Option<RefNode> {
+    element
+        .parent_node()
+        .filter(|parent| parent.node_type() == NodeType::Element)
+}
+
+///
+/// The element siblings preceding `element`, nearest first.
+///
+fn preceding_elements(element: &RefNode) -> Vec<RefNode> {
+    let mut siblings: Vec<RefNode> = Vec::new();
+    let mut current = element.previous_sibling();
+    while let Some(sibling) = current {
+        current = sibling.previous_sibling();
+        if sibling.node_type() == NodeType::Element {
+            siblings.push(sibling);
+        }
+    }
+    siblings
+}
+
+///
+/// The element siblings following `element`, nearest first.
+///
+fn following_elements(element: &RefNode) -> Vec<RefNode> {
+    let mut siblings: Vec<RefNode> = Vec::new();
+    let mut current = element.next_sibling();
+    while let Some(sibling) = current {
+        current = sibling.next_sibling();
+        if sibling.node_type() == NodeType::Element {
+            siblings.push(sibling);
+        }
+    }
+    siblings
+}
+
+fn attribute_namespace(element: &RefNode, name: &Name) -> Option<String> {
+    match (name.namespace_uri(), name.prefix()) {
+        (Some(namespace_uri), _) => Some(namespace_uri.clone()),
+        (None, Some(prefix)) if prefix == XML_NS_ATTRIBUTE => Some(XML_NS_URI.to_string()),
+        (None, Some(prefix)) => resolve_declared_prefix(element, Some(prefix)),
+        (None, None) => None,
+    }
+}
@@ -0,0 +1,1326 @@
+/*!
+Provides a configurable serializer for `RefNode` trees, as an alternative to the `Display`
+implementation which writes each node without checking its namespaces.
+
+Both write character data so that it is read back unchanged: the markup characters `&`, `<`, `>`,
+`"`, and `'` in text and attribute values, a carriage return in text, and a tab, newline, or
+carriage return in an attribute value, are written as numeric character references; a `"]]>"` within
+a CDATA section ends that section and starts another; and, as a comment has no escape mechanism, a
+space is written after a hyphen that would otherwise be followed by another, or end the comment.
+
+The serializer performs a namespace well-formedness check as it writes, under the
+[`Profile::Strict`](enum.Profile.html) profile (the default) any element or attribute whose prefix
+has no in-scope `xmlns` declaration results in an `Error::Namespace` rather than broken XML. Setting
+[`WriteOptions::fix`](struct.WriteOptions.html#structfield.fix) will instead add any missing
+declarations to the output, using the namespace URI held in each node's name. Declarations made
+outside the node being written, for example by the former ancestors of a fragment detached from
+its document, may be supplied as
+[`WriteOptions::namespace_context`](struct.WriteOptions.html#structfield.namespace_context).
+
+The [`serialize`](fn.serialize.html) function returns the serialized form as a `String`, while
+[`write_document`](fn.write_document.html) writes it directly to an `io::Write` sink so that large
+documents may be streamed to a file or socket without first building the whole output in memory.
+
+The [`canonicalize`](fn.canonicalize.html) and [`write_canonical`](fn.write_canonical.html)
+functions instead write the canonical form of a node, as required when computing digests for XML
+signatures, using either [Canonical XML 1.0](https://www.w3.org/TR/2001/REC-xml-c14n-20010315) or
+[Exclusive XML Canonicalization 1.0](https://www.w3.org/TR/2002/REC-xml-exc-c14n-20020718/), see
+[`Canonicalization`](enum.Canonicalization.html).
+
+With the `encoding` feature the [`serialize_encoded`](fn.serialize_encoded.html) and
+[`write_encoded`](fn.write_encoded.html) functions write the same output in another character
+encoding, such as `UTF-16` or `ISO-8859-1`, and declare that encoding in the XML declaration of a
+document. A character in text or an attribute value that cannot be represented in the encoding is
+written as a numeric character reference, and a CDATA section is ended before, and restarted after,
+such a character so that its reference is written outside the section. As a character reference
+is not recognized elsewhere, such a character in a name, comment, processing instruction, or
+document type results in an `Error::InvalidCharacter`.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::serializer::{serialize, WriteOptions};
+
+let implementation = get_implementation();
+let document_node = implementation
+    .create_document(Some("http://www.w3.org/1999/02/22-rdf-syntax-ns#"), Some("rdf:RDF"), None)
+    .unwrap();
+
+assert_eq!(serialize(&document_node, &WriteOptions::default()), Err(Error::Namespace));
+
+let options = WriteOptions { fix: true, ..Default::default() };
+assert_eq!(
+    serialize(&document_node, &options).unwrap(),
+    "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"></rdf:RDF>"
+);
+```
+
+Writing to a sink instead:
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::serializer::{write_document, WriteOptions};
+
+let implementation = get_implementation();
+let document_node = implementation
+    .create_document(None, Some("catalog"), None)
+    .unwrap();
+
+let mut output: Vec<u8> = Vec::new();
+write_document(&document_node, &mut output, &WriteOptions::default()).unwrap();
+assert_eq!(String::from_utf8(output).unwrap(), "<catalog></catalog>");
+```
+
+Canonicalizing a sub-tree, where exclusive canonicalization only declares the namespaces used:
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::serializer::{canonicalize, CanonicalOptions};
+
+let implementation = get_implementation();
+let document_node = implementation
+    .create_document(Some("urn:example:envelope"), Some("env:Envelope"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+root_node
+    .set_attribute_ns("http://www.w3.org/2000/xmlns/", "xmlns:env", "urn:example:envelope")
+    .unwrap();
+root_node
+    .set_attribute_ns("http://www.w3.org/2000/xmlns/", "xmlns:b", "urn:example:body")
+    .unwrap();
+let mut body_node = document.create_element_ns("urn:example:body", "b:Body").unwrap();
+body_node.set_attribute("Id", "body").unwrap();
+let body_node = root_node.append_child(body_node).unwrap();
+
+assert_eq!(
+    canonicalize(&body_node, &CanonicalOptions::inclusive()).unwrap(),
+    "<b:Body xmlns:b=\"urn:example:body\" xmlns:env=\"urn:example:envelope\" Id=\"body\"></b:Body>"
+);
+assert_eq!(
+    canonicalize(&body_node, &CanonicalOptions::exclusive(&[])).unwrap(),
+    "<b:Body xmlns:b=\"urn:example:body\" Id=\"body\"></b:Body>"
+);
+```
+*/
+
+use crate::level2::ext::decl::{PrologPosition, XmlDecl};
+use crate::level2::ext::extension::serialized_extension;
+use crate::level2::ext::mutation::raw_value;
+use crate::level2::ext::namespaced::NamespaceContext;
+use crate::level2::ext::traits::DocumentDecl;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::ordered_attributes;
+use crate::level2::traits::*;
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_PI_DATA, MSG_UNDECLARED_PREFIX, MSG_WRITE_FAILED,
+};
+use crate::shared::name::Name;
+use crate::shared::syntax::*;
+use crate::shared::text::{escape, is_processing_instruction_data};
+use std::collections::HashMap;
+use std::fmt::{Error as FmtError, Result as FmtResult, Write as FmtWrite};
+use std::io::{Error as IoError, Write as IoWrite};
+
+#[cfg(feature = "encoding")]
+use crate::shared::error::{MSG_UNENCODABLE_CHARACTER, MSG_UNSUPPORTED_ENCODING};
+#[cfg(feature = "encoding")]
+use encoding_rs::{Encoder, EncoderResult, Encoding, UTF_8};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The profile determines how strictly the serializer checks the tree it is writing.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Profile {
+    /// Perform all well-formedness checks, this is the default.
+    Strict,
+    /// Write the tree as-is, in the same manner as `Display`.
+    Lenient,
+}
+
+///
+/// Options that control the behavior of [`serialize`](fn.serialize.html) and
+/// [`write_document`](fn.write_document.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct WriteOptions {
+    /// The checking profile to use while writing.
+    pub profile: Profile,
+    /// If `true` any prefix without an in-scope declaration is declared in the output, using the
+    /// namespace URI of the node's name, rather than being reported as an error.
+    pub fix: bool,
+    /// If set, the serialized form of any [`NodeExtension`](../extension/trait.NodeExtension.html)
+    /// attached to a node is written immediately before it, as a processing instruction with this
+    /// target.
+    pub extension_target: Option<String>,
+    /// If set, the namespace declarations in scope where the serialized node will be placed, for
+    /// example the context of a fragment detached from its document; prefixes declared by the
+    /// context are neither checked nor declared in the output.
+    pub namespace_context: Option<NamespaceContext>,
+}
+
+///
+/// The canonicalization algorithm used by [`canonicalize`](fn.canonicalize.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Canonicalization {
+    /// [Canonical XML 1.0](https://www.w3.org/TR/2001/REC-xml-c14n-20010315), all namespace
+    /// declarations in scope are written on the first element, and re-declared on any element
+    /// where they change.
+    Inclusive,
+    /// [Exclusive XML Canonicalization 1.0](https://www.w3.org/TR/2002/REC-xml-exc-c14n-20020718/),
+    /// namespace declarations are only written on elements that use them, either in the element
+    /// name or an attribute name. The prefixes in the `InclusiveNamespaces PrefixList`, held by
+    /// this variant, are instead treated as for inclusive canonicalization; the default namespace
+    /// is named by the token `#default` in this list.
+    Exclusive(Vec<String>),
+}
+
+///
+/// Options that control the behavior of [`canonicalize`](fn.canonicalize.html) and
+/// [`write_canonical`](fn.write_canonical.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct CanonicalOptions {
+    /// The canonicalization algorithm to use.
+    pub algorithm: Canonicalization,
+    /// If `true` comments are included in the canonical form, else they are removed.
+    pub with_comments: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Serialize the tree rooted at `node` into a new `String`, according to `options`.
+///
+/// **Exceptions**
+///
+/// * `NAMESPACE_ERR`: Raised under the strict profile if an element or attribute uses a prefix
+///   that has no in-scope declaration, and the namespace fix-up was either not requested or was
+///   not possible as the node has no namespace URI.
+/// * `SYNTAX_ERR`: Raised if the serialized form of a node extension contains `?>`.
+///
+pub fn serialize(node: &RefNode, options: &WriteOptions) -> Result<String> {
+    let mut buffer = String::new();
+    write_node(node, &write_scope(options), options, &mut buffer)?;
+    Ok(buffer)
+}
+
+///
+/// Serialize the tree rooted at `node` directly to `writer`, according to `options`. The output
+/// is identical to that of [`serialize`](fn.serialize.html) but is written incrementally, as many
+/// small writes, and so an unbuffered `writer` such as a `File` should be wrapped in a
+/// `BufWriter`.
+///
+/// If an error is returned some of the output may already have been written.
+///
+/// **Exceptions**
+///
+/// * `NAMESPACE_ERR`: Raised under the strict profile if an element or attribute uses a prefix
+///   that has no in-scope declaration, and the namespace fix-up was either not requested or was
+///   not possible as the node has no namespace URI.
+/// * `SYNTAX_ERR`: Raised if the serialized form of a node extension contains `?>`.
+/// * `INVALID_STATE_ERR`: Raised if writing to `writer` fails.
+///
+pub fn write_document<W: IoWrite>(
+    node: &RefNode,
+    writer: &mut W,
+    options: &WriteOptions,
+) -> Result<()> {
+    let mut adapter = IoAdapter {
+        inner: writer,
+        error: None,
+    };
+    let result = write_node(node, &write_scope(options), options, &mut adapter);
+    if let Some(e) = adapter.error {
+        warn!("{}: '{}'", MSG_WRITE_FAILED, e);
+    }
+    result
+}
+
+///
+/// Serialize the tree rooted at `node`, according to `options`, into a new byte vector in the
+/// character encoding named by `encoding`; see [`write_encoded`](fn.write_encoded.html).
+///
+/// **Exceptions**
+///
+/// * `NOT_SUPPORTED_ERR`: Raised if `encoding` does not name an encoding that may be written.
+/// * `INVALID_CHARACTER_ERR`: Raised if a name, comment, processing instruction, or document type
+///   contains a character that cannot be represented in the encoding.
+/// * `NAMESPACE_ERR`: Raised under the strict profile if an element or attribute uses a prefix
+///   that has no in-scope declaration, and the namespace fix-up was either not requested or was
+///   not possible as the node has no namespace URI.
+/// * `SYNTAX_ERR`: Raised if the serialized form of a node extension contains `?>`.
+///
+#[cfg(feature = "encoding")]
+pub fn serialize_encoded(
+    node: &RefNode,
+    options: &WriteOptions,
+    encoding: &str,
+) -> Result<Vec<u8>> {
+    let mut output: Vec<u8> = Vec::new();
+    write_encoded(node, &mut output, options, encoding)?;
+    Ok(output)
+}
+
+///
+/// Serialize the tree rooted at `node` directly to `writer`, according to `options`, in the
+/// character encoding named by `encoding`. The names `UTF-8`, `UTF-16`, `UTF-16BE`, `UTF-16LE`,
+/// and `ISO-8859-1`, or `latin1`, are supported directly, `UTF-16` is written big-endian with a
+/// byte order mark, and any other encoding label known to the
+/// [`encoding_rs`](https://docs.rs/encoding_rs) crate may be used if that crate can encode it.
+///
+/// A character in text or an attribute value that cannot be represented in the encoding is written
+/// as a decimal numeric character reference, a CDATA section containing such a character is split
+/// so that the reference is written between two sections. If `node` is a document its XML
+/// declaration is written, added if it has none, with the encoding pseudo-attribute set to the name
+/// of the encoding; the document itself is not changed.
+///
+/// If an error is returned some of the output may already have been written.
+///
+/// **Exceptions**
+///
+/// * `NOT_SUPPORTED_ERR`: Raised if `encoding` does not name an encoding that may be written.
+/// * `INVALID_CHARACTER_ERR`: Raised if a name, comment, processing instruction, or document type
+///   contains a character that cannot be represented in the encoding.
+/// * `NAMESPACE_ERR`: Raised under the strict profile if an element or attribute uses a prefix
+///   that has no in-scope declaration, and the namespace fix-up was either not requested or was
+///   not possible as the node has no namespace URI.
+/// * `SYNTAX_ERR`: Raised if the serialized form of a node extension contains `?>`.
+/// * `INVALID_STATE_ERR`: Raised if writing to `writer` fails.
+///
+#[cfg(feature = "encoding")]
+pub fn write_encoded<W: IoWrite>(
+    node: &RefNode,
+    writer: &mut W,
+    options: &WriteOptions,
+    encoding: &str,
+) -> Result<()> {
+    let mut adapter = EncodingAdapter {
+        inner: writer,
+        encoder: OutputEncoder::for_label(encoding)?,
+        markup: MarkupState::Text,
+        buffer: Vec::new(),
+        error: None,
+        unencodable: None,
+    };
+    let scope = write_scope(options);
+    let result = adapter
+        .write_byte_order_mark()
+        .and_then(|_| {
+            if node.node_type() == NodeType::Document {
+                let xml_decl = node.xml_declaration().unwrap_or_default();
+                let xml_decl = XmlDecl::new(
+                    xml_decl.version(),
+                    Some(adapter.encoder.name().to_string()),
+                    xml_decl.standalone(),
+                );
+                write_document_node(node, Some(xml_decl), &scope, options, &mut adapter)
+            } else {
+                write_node(node, &scope, options, &mut adapter)
+            }
+        })
+        .and_then(|_| adapter.finish());
+    if let Some(e) = adapter.error {
+        warn!("{}: '{}'", MSG_WRITE_FAILED, e);
+    }
+    if let Some(c) = adapter.unencodable {
+        warn!("{}: {:?}", MSG_UNENCODABLE_CHARACTER, c);
+        return Err(Error::InvalidCharacter);
+    }
+    result
+}
+
+///
+/// Write the canonical form of the tree rooted at `node` into a new `String`, according to
+/// `options`.
+///
+/// If `node` is not the document, or its document element, it is canonicalized as the apex of a
+/// document subset and so namespace declarations, and for inclusive canonicalization `xml:`
+/// attributes, inherited from its ancestors are written on `node` itself. Any XML declaration and
+/// document type are always removed, CDATA sections are written as text, and entity references are
+/// replaced by their children.
+///
+/// **Exceptions**
+///
+/// * `INVALID_STATE_ERR`: Raised if writing the output fails.
+///
+pub fn canonicalize(node: &RefNode, options: &CanonicalOptions) -> Result<String> {
+    let mut buffer = String::new();
+    write_canonical_root(node, options, &mut buffer)?;
+    Ok(buffer)
+}
+
+///
+/// Write the canonical form of the tree rooted at `node` directly to `writer`, according to
+/// `options`. The output is identical to that of [`canonicalize`](fn.canonicalize.html).
+///
+/// If an error is returned some of the output may already have been written.
+///
+/// **Exceptions**
+///
+/// * `INVALID_STATE_ERR`: Raised if writing to `writer` fails.
+///
+pub fn write_canonical<W: IoWrite>(
+    node: &RefNode,
+    writer: &mut W,
+    options: &CanonicalOptions,
+) -> Result<()> {
+    let mut adapter = IoAdapter {
+        inner: writer,
+        error: None,
+    };
+    let result = write_canonical_root(node, options, &mut adapter);
+    if let Some(e) = adapter.error {
+        warn!("{}: '{}'", MSG_WRITE_FAILED, e);
+    }
+    result
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+impl<W: IoWrite> FmtWrite for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            FmtError
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl WriteOptions {
+    ///
+    /// Options using the strict profile, without namespace fix-up.
+    ///
+    pub fn strict() -> Self {
+        Self {
+            profile: Profile::Strict,
+            fix: false,
+            extension_target: None,
+            namespace_context: None,
+        }
+    }
+
+    ///
+    /// Options using the lenient profile, without namespace fix-up.
+    ///
+    pub fn lenient() -> Self {
+        Self {
+            profile: Profile::Lenient,
+            fix: false,
+            extension_target: None,
+            namespace_context: None,
+        }
+    }
+
+    ///
+    /// Returns `true` if these options check namespace well-formedness while writing.
+    ///
+    pub fn has_namespace_checks(&self) -> bool {
+        self.profile == Profile::Strict
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Default for CanonicalOptions {
+    fn default() -> Self {
+        Self::inclusive()
+    }
+}
+
+impl CanonicalOptions {
+    ///
+    /// Options for Canonical XML 1.0, without comments.
+    ///
+    pub fn inclusive() -> Self {
+        Self {
+            algorithm: Canonicalization::Inclusive,
+            with_comments: false,
+        }
+    }
+
+    ///
+    /// Options for Exclusive XML Canonicalization 1.0, without comments, treating the namespace
+    /// prefixes in `inclusive_prefixes` as for inclusive canonicalization.
+    ///
+    pub fn exclusive(inclusive_prefixes: &[&str]) -> Self {
+        Self {
+            algorithm: Canonicalization::Exclusive(
+                inclusive_prefixes.iter().map(|s| s.to_string()).collect(),
+            ),
+            with_comments: false,
+        }
+    }
+
+    ///
+    /// Returns the algorithm identifier URI for these options, as used in the `Algorithm`
+    /// attribute of an XML signature's `CanonicalizationMethod` or `Transform` elements.
+    ///
+    pub fn algorithm_uri(&self) -> &'static str {
+        match (&self.algorithm, self.with_comments) {
+            (Canonicalization::Inclusive, false) => C14N_URI,
+            (Canonicalization::Inclusive, true) => C14N_WITH_COMMENTS_URI,
+            (Canonicalization::Exclusive(_), false) => EXC_C14N_URI,
+            (Canonicalization::Exclusive(_), true) => EXC_C14N_WITH_COMMENTS_URI,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const C14N_URI: &str = "http://www.w3.org/TR/2001/REC-xml-c14n-20010315";
+const C14N_WITH_COMMENTS_URI: &str = "http://www.w3.org/TR/2001/REC-xml-c14n-20010315#WithComments";
+const EXC_C14N_URI: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+const EXC_C14N_WITH_COMMENTS_URI: &str = "http://www.w3.org/2001/10/xml-exc-c14n#WithComments";
+
+///
+/// The token used in an `InclusiveNamespaces PrefixList` for the default namespace.
+///
+const DEFAULT_NAMESPACE_TOKEN: &str = "#default";
+
+type NamespaceScope = HashMap<Option<String>, String>;
+
+///
+/// Allows the serializer, which writes to any `fmt::Write`, to write to an `io::Write` while
+/// retaining the underlying I/O error for logging.
+///
+struct IoAdapter<'a, W: IoWrite> {
+    inner: &'a mut W,
+    error: Option<IoError>,
+}
+
+///
+/// As `IoAdapter`, but encoding each string written in the character encoding of `encoder`.
+///
+#[cfg(feature = "encoding")]
+struct EncodingAdapter<'a, W: IoWrite> {
+    inner: &'a mut W,
+    encoder: OutputEncoder,
+    markup: MarkupState,
+    buffer: Vec<u8>,
+    error: Option<IoError>,
+    unencodable: Option<char>,
+}
+
+///
+/// The markup an `EncodingAdapter` is writing, tracked only so far as to know whether a character
+/// reference may be written in place of a character that cannot be encoded.
+///
+#[cfg(feature = "encoding")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MarkupState {
+    Text,
+    MarkupStart,
+    DeclarationStart,
+    Declaration {
+        in_subset: bool,
+        quote: Option<char>,
+    },
+    CommentStart,
+    Comment {
+        hyphens: usize,
+    },
+    CDataStart,
+    CData {
+        brackets: usize,
+    },
+    ProcessingInstruction {
+        question: bool,
+    },
+    Tag {
+        quote: Option<char>,
+    },
+}
+
+///
+/// The encodings that may be written; UTF-16 and ISO-8859-1 are encoded here as `encoding_rs`
+/// only decodes UTF-16, and treats `ISO-8859-1` as a label of `windows-1252`.
+///
+#[cfg(feature = "encoding")]
+enum OutputEncoder {
+    Utf8,
+    Utf16 {
+        name: &'static str,
+        big_endian: bool,
+        byte_order_mark: bool,
+    },
+    Latin1,
+    Other(Encoder),
+}
+
+#[cfg(feature = "encoding")]
+impl<W: IoWrite> FmtWrite for EncodingAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        self.buffer.clear();
+        let mut input = s;
+        while !input.is_empty() {
+            let (read, unmappable) = self.encoder.encode(input, &mut self.buffer, false);
+            let (encoded, rest) = input.split_at(read);
+            input = rest;
+            match unmappable {
+                None => self.markup = encoded.chars().fold(self.markup, MarkupState::next),
+                Some(c) => {
+                    let encoded = &encoded[..read - c.len_utf8()];
+                    self.markup = encoded.chars().fold(self.markup, MarkupState::next);
+                    let reference = format!(
+                        "{}{}{}",
+                        XML_NUMBERED_ENTITYREF_START, c as u32, XML_ENTITYREF_END
+                    );
+                    let replacement = match self.markup {
+                        MarkupState::Text | MarkupState::Tag { quote: Some(_) } => reference,
+                        MarkupState::CData { .. } => {
+                            format!("{}{}{}", XML_CDATA_END, reference, XML_CDATA_START)
+                        }
+                        _ => {
+                            self.unencodable = Some(c);
+                            return Err(FmtError);
+                        }
+                    };
+                    let _safe_to_ignore =
+                        self.encoder.encode(&replacement, &mut self.buffer, false);
+                    self.markup = self.markup.next(c);
+                }
+            }
+        }
+        self.write_buffer()
+    }
+}
+
+#[cfg(feature = "encoding")]
+impl<W: IoWrite> EncodingAdapter<'_, W> {
+    fn write_byte_order_mark(&mut self) -> Result<()> {
+        if let OutputEncoder::Utf16 {
+            big_endian,
+            byte_order_mark: true,
+            ..
+        } = self.encoder
+        {
+            self.buffer = if big_endian {
+                vec![0xFE, 0xFF]
+            } else {
+                vec![0xFF, 0xFE]
+            };
+            self.write_buffer().map_err(write_failed)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.buffer.clear();
+        let _safe_to_ignore = self.encoder.encode("", &mut self.buffer, true);
+        self.write_buffer().map_err(write_failed)
+    }
+
+    fn write_buffer(&mut self) -> FmtResult {
+        self.inner.write_all(&self.buffer).map_err(|e| {
+            self.error = Some(e);
+            FmtError
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "encoding")]
+impl OutputEncoder {
+    fn for_label(label: &str) -> Result<Self> {
+        match label.to_ascii_uppercase().as_str() {
+            "UTF-16" => Ok(OutputEncoder::Utf16 {
+                name: "UTF-16",
+                big_endian: true,
+                byte_order_mark: true,
+            }),
+            "UTF-16BE" => Ok(OutputEncoder::Utf16 {
+                name: "UTF-16BE",
+                big_endian: true,
+                byte_order_mark: false,
+            }),
+            "UTF-16LE" => Ok(OutputEncoder::Utf16 {
+                name: "UTF-16LE",
+                big_endian: false,
+                byte_order_mark: false,
+            }),
+            "ISO-8859-1" | "LATIN1" => Ok(OutputEncoder::Latin1),
+            _ => match Encoding::for_label(label.as_bytes()) {
+                Some(encoding) if encoding == UTF_8 => Ok(OutputEncoder::Utf8),
+                Some(encoding) if encoding.output_encoding() == encoding => {
+                    Ok(OutputEncoder::Other(encoding.new_encoder()))
+                }
+                _ => {
+                    warn!("{}: '{}'", MSG_UNSUPPORTED_ENCODING, label);
+                    Err(Error::NotSupported)
+                }
+            },
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            OutputEncoder::Utf8 => UTF_8.name(),
+            OutputEncoder::Utf16 { name, .. } => name,
+            OutputEncoder::Latin1 => "ISO-8859-1",
+            OutputEncoder::Other(encoder) => encoder.encoding().name(),
+        }
+    }
+
+    ///
+    /// Append `s`, encoded, to `output` up to and including the first character that cannot be
+    /// encoded, which is not written; returns the number of bytes of `s` read, and that character.
+    /// `last` is `true` only for the final call, which flushes the state of a stateful encoder.
+    ///
+    fn encode(&mut self, s: &str, output: &mut Vec<u8>, last: bool) -> (usize, Option<char>) {
+        match self {
+            OutputEncoder::Utf8 => {
+                output.extend_from_slice(s.as_bytes());
+                (s.len(), None)
+            }
+            OutputEncoder::Utf16 { big_endian, .. } => {
+                for unit in s.encode_utf16() {
+                    output.extend_from_slice(&if *big_endian {
+                        unit.to_be_bytes()
+                    } else {
+                        unit.to_le_bytes()
+                    });
+                }
+                (s.len(), None)
+            }
+            OutputEncoder::Latin1 => {
+                for (index, c) in s.char_indices() {
+                    match c as u32 {
+                        code if code <= 0xFF => output.push(code as u8),
+                        _ => return (index + c.len_utf8(), Some(c)),
+                    }
+                }
+                (s.len(), None)
+            }
+            OutputEncoder::Other(encoder) => {
+                let mut read = 0;
+                loop {
+                    output.reserve(
+                        encoder
+                            .max_buffer_length_from_utf8_without_replacement(s.len() - read)
+                            .unwrap_or(s.len() - read),
+                    );
+                    let (result, more) = encoder.encode_from_utf8_to_vec_without_replacement(
+                        &s[read..],
+                        output,
+                        last,
+                    );
+                    read += more;
+                    match result {
+                        EncoderResult::InputEmpty => return (read, None),
+                        EncoderResult::OutputFull => {}
+                        EncoderResult::Unmappable(c) => return (read, Some(c)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "encoding")]
+impl MarkupState {
+    ///
+    /// The state after writing `c`; only the ASCII characters that delimit markup change it.
+    ///
+    fn next(self, c: char) -> Self {
+        match self {
+            MarkupState::Text if c == '<' => MarkupState::MarkupStart,
+            MarkupState::Text => MarkupState::Text,
+            MarkupState::MarkupStart => match c {
+                '!' => MarkupState::DeclarationStart,
+                '?' => MarkupState::ProcessingInstruction { question: false },
+                _ => MarkupState::Tag { quote: None },
+            },
+            MarkupState::DeclarationStart => match c {
+                '-' => MarkupState::CommentStart,
+                '[' => MarkupState::CDataStart,
+                _ => MarkupState::Declaration {
+                    in_subset: false,
+                    quote: None,
+                },
+            },
+            MarkupState::Declaration {
+                in_subset,
+                quote: Some(quote),
+            } => MarkupState::Declaration {
+                in_subset,
+                quote: if c == quote { None } else { Some(quote) },
+            },
+            MarkupState::Declaration {
+                in_subset,
+                quote: None,
+            } => match c {
+                '>' if !in_subset => MarkupState::Text,
+                '"' | '\'' => MarkupState::Declaration {
+                    in_subset,
+                    quote: Some(c),
+                },
+                '[' | ']' => MarkupState::Declaration {
+                    in_subset: c == '[',
+                    quote: None,
+                },
+                _ => self,
+            },
+            MarkupState::CommentStart => MarkupState::Comment { hyphens: 0 },
+            MarkupState::Comment { hyphens } => match c {
+                '>' if hyphens >= 2 => MarkupState::Text,
+                '-' => MarkupState::Comment {
+                    hyphens: hyphens + 1,
+                },
+                _ => MarkupState::Comment { hyphens: 0 },
+            },
+            MarkupState::CDataStart if c == '[' => MarkupState::CData { brackets: 0 },
+            MarkupState::CDataStart => MarkupState::CDataStart,
+            MarkupState::CData { brackets } => match c {
+                '>' if brackets >= 2 => MarkupState::Text,
+                ']' => MarkupState::CData {
+                    brackets: brackets + 1,
+                },
+                _ => MarkupState::CData { brackets: 0 },
+            },
+            MarkupState::ProcessingInstruction { question } => match c {
+                '>' if question => MarkupState::Text,
+                _ => MarkupState::ProcessingInstruction { question: c == '?' },
+            },
+            MarkupState::Tag { quote: None } => match c {
+                '>' => MarkupState::Text,
+                '"' | '\'' => MarkupState::Tag { quote: Some(c) },
+                _ => self,
+            },
+            MarkupState::Tag { quote: Some(quote) } if c == quote => {
+                MarkupState::Tag { quote: None }
+            }
+            MarkupState::Tag { .. } => self,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn initial_scope() -> NamespaceScope {
+    let mut scope = NamespaceScope::default();
+    let _safe_to_ignore = scope.insert(Some(XML_NS_ATTRIBUTE.to_string()), XML_NS_URI.to_string());
+    scope
+}
+
+fn write_scope(options: &WriteOptions) -> NamespaceScope {
+    let mut scope = initial_scope();
+    if let Some(context) = &options.namespace_context {
+        scope.extend(context.declarations());
+    }
+    scope
+}
+
+fn write_node<W: FmtWrite>(
+    node: &RefNode,
+    scope: &NamespaceScope,
+    options: &WriteOptions,
+    writer: &mut W,
+) -> Result<()> {
+    if let Some(target) = &options.extension_target {
+        if node.node_type() != NodeType::Document {
+            if let Some(data) = serialized_extension(node) {
+                if !is_processing_instruction_data(&data) {
+                    warn!("{}", MSG_INVALID_PI_DATA);
+                    return Err(Error::Syntax);
+                }
+                write!(writer, "{}{} {}{}", XML_PI_START, target, data, XML_PI_END)
+                    .map_err(write_failed)?;
+            }
+        }
+    }
+    match node.node_type() {
+        NodeType::Element => write_element(node, scope, options, writer),
+        NodeType::Document => {
+            write_document_node(node, node.xml_declaration(), scope, options, writer)
+        }
+        NodeType::DocumentFragment => write_children(node, scope, options, writer),
+        _ => write!(writer, "{}", node).map_err(write_failed),
+    }
+}
+
+fn write_document_node<W: FmtWrite>(
+    document: &RefNode,
+    xml_declaration: Option<XmlDecl>,
+    scope: &NamespaceScope,
+    options: &WriteOptions,
+    writer: &mut W,
+) -> Result<()> {
+    if let Some(xml_declaration) = xml_declaration {
+        write!(writer, "{}", xml_declaration).map_err(write_failed)?;
+    }
+    let mut doc_type = document.doc_type();
+    for child in document.child_nodes() {
+        if document.prolog_position(&child) != Some(PrologPosition::BeforeDocType) {
+            if let Some(doc_type) = doc_type.take() {
+                write!(writer, "{}", doc_type).map_err(write_failed)?;
+            }
+        }
+        write_node(&child, scope, options, writer)?;
+    }
+    if let Some(doc_type) = doc_type {
+        write!(writer, "{}", doc_type).map_err(write_failed)?;
+    }
+    Ok(())
+}
+
+fn write_children<W: FmtWrite>(
+    node: &RefNode,
+    scope: &NamespaceScope,
+    options: &WriteOptions,
+    writer: &mut W,
+) -> Result<()> {
+    for child in node.child_nodes() {
+        write_node(&child, scope, options, writer)?;
+    }
+    Ok(())
+}
+
+fn write_element<W: FmtWrite>(
+    element: &RefNode,
+    parent_scope: &NamespaceScope,
+    options: &WriteOptions,
+    writer: &mut W,
+) -> Result<()> {
+    let name = element.node_name();
+    let attributes = ordered_attributes(element);
+
+    let mut scope = parent_scope.clone();
+    for (attribute_name, attribute) in &attributes {
+        if let Some(prefix) = declared_prefix(attribute_name) {
+            let _safe_to_ignore = scope.insert(prefix, attribute.value().unwrap_or_default());
+        }
+    }
+
+    let mut declarations: Vec<(Option<String>, String)> = Vec::new();
+    check_prefix(&name, true, &mut scope, &mut declarations, options)?;
+    for (attribute_name, _) in &attributes {
+        if declared_prefix(attribute_name).is_none() {
+            check_prefix(
+                attribute_name,
+                false,
+                &mut scope,
+                &mut declarations,
+                options,
+            )?;
+        }
+    }
+
+    write!(writer, "{}{}", XML_ELEMENT_START_START, name).map_err(write_failed)?;
+    for (_, attribute) in &attributes {
+        write!(writer, " {}", attribute).map_err(write_failed)?;
+    }
+    for (prefix, namespace_uri) in declarations {
+        write!(
+            writer,
+            " {}=\"{}\"",
+            Name::for_namespace(prefix.as_deref()),
+            escape(&namespace_uri)
+        )
+        .map_err(write_failed)?;
+    }
+    writer
+        .write_str(XML_ELEMENT_START_END)
+        .map_err(write_failed)?;
+    write_children(element, &scope, options, writer)?;
+    write!(
+        writer,
+        "{}{}{}",
+        XML_ELEMENT_END_START, name, XML_ELEMENT_END_END
+    )
+    .map_err(write_failed)
+}
+
+fn write_failed(_: FmtError) -> Error {
+    warn!("{}", MSG_WRITE_FAILED);
+    Error::InvalidState
+}
+
+///
+/// If `name` is that of an `xmlns` attribute return the prefix it declares, `Some(None)` for the
+/// default namespace.
+///
+fn declared_prefix(name: &Name) -> Option<Option<String>> {
+    match name.prefix() {
+        Some(prefix) if prefix == XMLNS_NS_ATTRIBUTE => Some(Some(name.local_name().clone())),
+        None if name.local_name() == XMLNS_NS_ATTRIBUTE => Some(None),
+        _ => None,
+    }
+}
+
+fn check_prefix(
+    name: &Name,
+    is_element: bool,
+    scope: &mut NamespaceScope,
+    declarations: &mut Vec<(Option<String>, String)>,
+    options: &WriteOptions,
+) -> Result<()> {
+    match name.prefix() {
+        Some(prefix) => {
+            let prefix = Some(prefix.clone());
+            if scope.contains_key(&prefix) {
+                Ok(())
+            } else if let (true, Some(namespace_uri)) = (options.fix, name.namespace_uri()) {
+                let _safe_to_ignore = scope.insert(prefix.clone(), namespace_uri.clone());
+                declarations.push((prefix, namespace_uri.clone()));
+                Ok(())
+            } else if options.has_namespace_checks() {
+                warn!("{}: '{}'", MSG_UNDECLARED_PREFIX, name);
+                Err(Error::Namespace)
+            } else {
+                Ok(())
+            }
+        }
+        None => {
+            //
+            // Only elements are affected by the default namespace, un-prefixed attributes
+            // are never in a namespace. An element with no namespace within the scope of a
+            // default namespace has that namespace un-declared with `xmlns=""`.
+            //
+            if is_element && options.fix {
+                let namespace_uri = name.namespace_uri().clone().unwrap_or_default();
+                if scope.get(&None).cloned().unwrap_or_default() != namespace_uri {
+                    let _safe_to_ignore = scope.insert(None, namespace_uri.clone());
+                    declarations.push((None, namespace_uri));
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn write_canonical_root<W: FmtWrite>(
+    node: &RefNode,
+    options: &CanonicalOptions,
+    writer: &mut W,
+) -> Result<()> {
+    if node.node_type() != NodeType::Element {
+        return write_canonical_node(
+            node,
+            &initial_scope(),
+            &NamespaceScope::default(),
+            options,
+            writer,
+        );
+    }
+
+    //
+    // The apex of a document subset inherits the namespaces, and under inclusive
+    // canonicalization the `xml:` attributes, of its ancestors.
+    //
+    let mut ancestors: Vec<RefNode> = Vec::new();
+    let mut current = node.parent_node();
+    while let Some(ancestor) = current {
+        if ancestor.node_type() != NodeType::Element {
+            break;
+        }
+        current = ancestor.parent_node();
+        ancestors.push(ancestor);
+    }
+    let mut scope = initial_scope();
+    let mut inherited: Vec<(Name, String)> = Vec::new();
+    for ancestor in ancestors.iter().rev() {
+        for (attribute_name, attribute) in ancestor.attributes() {
+            if let Some(prefix) = declared_prefix(&attribute_name) {
+                let _safe_to_ignore = scope.insert(prefix, raw_value(&attribute));
+            } else if options.algorithm == Canonicalization::Inclusive
+                && attribute_name.prefix().as_deref() == Some(XML_NS_ATTRIBUTE)
+            {
+                inherited.retain(|(name, _)| name.local_name() != attribute_name.local_name());
+                inherited.push((attribute_name, raw_value(&attribute)));
+            }
+        }
+    }
+    write_canonical_element(
+        node,
+        &scope,
+        &NamespaceScope::default(),
+        &inherited,
+        options,
+        writer,
+    )
+}
+
+fn write_canonical_node<W: FmtWrite>(
+    node: &RefNode,
+    scope: &NamespaceScope,
+    rendered: &NamespaceScope,
+    options: &CanonicalOptions,
+    writer: &mut W,
+) -> Result<()> {
+    match node.node_type() {
+        NodeType::Element => write_canonical_element(node, scope, rendered, &[], options, writer),
+        NodeType::Document => {
+            //
+            // Only the document element, comments, and processing instructions are written;
+            // with a line feed separating each from the document element.
+            //
+            let mut after_element = false;
+            for child in node.child_nodes() {
+                match child.node_type() {
+                    NodeType::Element => {
+                        write_canonical_element(&child, scope, rendered, &[], options, writer)?;
+                        after_element = true;
+                    }
+                    NodeType::Comment if !options.with_comments => {}
+                    NodeType::Comment | NodeType::ProcessingInstruction => {
+                        if after_element {
+                            writer.write_char('\n').map_err(write_failed)?;
+                        }
+                        write_canonical_node(&child, scope, rendered, options, writer)?;
+                        if !after_element {
+                            writer.write_char('\n').map_err(write_failed)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        NodeType::DocumentFragment | NodeType::EntityReference => {
+            for child in node.child_nodes() {
+                write_canonical_node(&child, scope, rendered, options, writer)?;
+            }
+            Ok(())
+        }
+        NodeType::Text | NodeType::CData => writer
+            .write_str(&escape_canonical_text(
+                &node.node_value().unwrap_or_default(),
+            ))
+            .map_err(write_failed),
+        NodeType::Comment if options.with_comments => write!(
+            writer,
+            "{}{}{}",
+            XML_COMMENT_START,
+            node.node_value().unwrap_or_default(),
+            XML_COMMENT_END
+        )
+        .map_err(write_failed),
+        NodeType::ProcessingInstruction => {
+            write!(writer, "{}{}", XML_PI_START, node.node_name()).map_err(write_failed)?;
+            match node.node_value() {
+                Some(data) if !data.is_empty() => write!(writer, " {}", data),
+                _ => Ok(()),
+            }
+            .map_err(write_failed)?;
+            writer.write_str(XML_PI_END).map_err(write_failed)
+        }
+        _ => Ok(()),
+    }
+}
+
+///
+/// Write `element` in canonical form; `rendered` holds the namespace declarations already written
+/// by ancestors, and `inherited` any `xml:` attributes inherited by the apex of a document subset.
+///
+fn write_canonical_element<W: FmtWrite>(
+    element: &RefNode,
+    parent_scope: &NamespaceScope,
+    rendered: &NamespaceScope,
+    inherited: &[(Name, String)],
+    options: &CanonicalOptions,
+    writer: &mut W,
+) -> Result<()> {
+    let name = element.node_name();
+    let mut scope = parent_scope.clone();
+    let mut attributes: Vec<(String, Name, String)> = Vec::new();
+    for (attribute_name, attribute) in element.attributes() {
+        match declared_prefix(&attribute_name) {
+            Some(prefix) => {
+                let _safe_to_ignore = scope.insert(prefix, raw_value(&attribute));
+            }
+            None => {
+                let value = raw_value(&attribute);
+                attributes.push((String::new(), attribute_name, value));
+            }
+        }
+    }
+    for (attribute_name, value) in inherited {
+        if !attributes.iter().any(|(_, name, _)| {
+            name.prefix() == attribute_name.prefix()
+                && name.local_name() == attribute_name.local_name()
+        }) {
+            attributes.push((String::new(), attribute_name.clone(), value.clone()));
+        }
+    }
+    for (namespace_uri, attribute_name, _) in attributes.iter_mut() {
+        if let Some(prefix) = attribute_name.prefix() {
+            *namespace_uri =
+                namespace_for(attribute_name, &Some(prefix.clone()), &scope).unwrap_or_default();
+        }
+    }
+
+    //
+    // Determine the namespace declarations to write, those whose value differs from that
+    // already written by an ancestor.
+    //
+    let candidates: Vec<Option<String>> = match &options.algorithm {
+        Canonicalization::Inclusive => {
+            let mut prefixes: Vec<Option<String>> = scope.keys().cloned().collect();
+            if !prefixes.contains(&None) {
+                prefixes.push(None);
+            }
+            prefixes
+        }
+        Canonicalization::Exclusive(inclusive_prefixes) => {
+            let mut prefixes: Vec<Option<String>> = vec![name.prefix().clone()];
+            for (_, attribute_name, _) in &attributes {
+                if let Some(prefix) = attribute_name.prefix() {
+                    prefixes.push(Some(prefix.clone()));
+                }
+            }
+            for prefix in inclusive_prefixes {
+                let prefix = if prefix == DEFAULT_NAMESPACE_TOKEN {
+                    None
+                } else {
+                    Some(prefix.clone())
+                };
+                if prefix.is_none() || scope.contains_key(&prefix) {
+                    prefixes.push(prefix);
+                }
+            }
+            prefixes
+        }
+    };
+    let mut declarations: Vec<(Option<String>, String)> = Vec::new();
+    for prefix in candidates {
+        if prefix.as_deref() == Some(XML_NS_ATTRIBUTE)
+            || declarations.iter().any(|(declared, _)| declared == &prefix)
+        {
+            continue;
+        }
+        let namespace_uri = if prefix == name.prefix().clone() {
+            namespace_for(&name, &prefix, &scope)
+        } else if prefix.is_none() {
+            scope.get(&prefix).cloned()
+        } else {
+            scope.get(&prefix).cloned().or_else(|| {
+                attributes
+                    .iter()
+                    .find(|(_, attribute_name, _)| attribute_name.prefix() == &prefix)
+                    .and_then(|(_, attribute_name, _)| {
+                        namespace_for(attribute_name, &prefix, &scope)
+                    })
+            })
+        };
+        match (&prefix, namespace_uri) {
+            (None, namespace_uri) => {
+                let namespace_uri = namespace_uri.unwrap_or_default();
+                if rendered.get(&None).map(String::as_str).unwrap_or_default() != namespace_uri {
+                    declarations.push((None, namespace_uri));
+                }
+            }
+            (Some(_), Some(namespace_uri))
+                if !namespace_uri.is_empty() && rendered.get(&prefix) != Some(&namespace_uri) =>
+            {
+                declarations.push((prefix, namespace_uri));
+            }
+            _ => {}
+        }
+    }
+    declarations.sort();
+    attributes.sort_by(|lhs, rhs| (&lhs.0, lhs.1.local_name()).cmp(&(&rhs.0, rhs.1.local_name())));
+
+    let mut rendered = rendered.clone();
+    write!(writer, "{}{}", XML_ELEMENT_START_START, name).map_err(write_failed)?;
+    for (prefix, namespace_uri) in declarations {
+        write!(
+            writer,
+            " {}=\"{}\"",
+            Name::for_namespace(prefix.as_deref()),
+            escape_canonical_attribute(&namespace_uri)
+        )
+        .map_err(write_failed)?;
+        let _safe_to_ignore = rendered.insert(prefix, namespace_uri);
+    }
+    for (_, attribute_name, value) in attributes {
+        write!(
+            writer,
+            " {}=\"{}\"",
+            attribute_name,
+            escape_canonical_attribute(&value)
+        )
+        .map_err(write_failed)?;
+    }
+    writer
+        .write_str(XML_ELEMENT_START_END)
+        .map_err(write_failed)?;
+    for child in element.child_nodes() {
+        write_canonical_node(&child, &scope, &rendered, options, writer)?;
+    }
+    write!(
+        writer,
+        "{}{}{}",
+        XML_ELEMENT_END_START, name, XML_ELEMENT_END_END
+    )
+    .map_err(write_failed)
+}
+
+///
+/// The namespace URI for `prefix`, as used by `name`; either from an in-scope declaration or, if
+/// there is none, the namespace URI held in the name itself.
+///
+fn namespace_for(name: &Name, prefix: &Option<String>, scope: &NamespaceScope) -> Option<String> {
+    scope
+        .get(prefix)
+        .cloned()
+        .or_else(|| name.namespace_uri().clone())
+}
+
+fn escape_canonical_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '\r' => result.push_str("&#xD;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn escape_canonical_attribute(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '"' => result.push_str("&quot;"),
+            '\t' => result.push_str("&#x9;"),
+            '\n' => result.push_str("&#xA;"),
+            '\r' => result.push_str("&#xD;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
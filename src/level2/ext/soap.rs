@@ -0,0 +1,368 @@
+/*!
+Provides navigation helpers for SOAP 1.1 and SOAP 1.2 messages.
+
+An [`Envelope`](struct.Envelope.html) locates the `Envelope`, `Header`, and `Body` elements of a
+message by namespace URI and local name, so that the prefix used in a particular message is not
+significant. The namespace of an element is taken from its name if present, or is resolved from
+the `xmlns` attributes of the element and its ancestors.
+
+This module is only available when the `soap` feature is enabled.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::soap::{Envelope, SoapVersion};
+
+let implementation = get_implementation();
+let document_node = implementation
+    .create_document(Some(SoapVersion::V12.namespace_uri()), Some("env:Envelope"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut envelope_node = document.document_element().unwrap();
+let body_node = document
+    .create_element_ns(SoapVersion::V12.namespace_uri(), "env:Body")
+    .unwrap();
+let _safe_to_ignore = envelope_node.append_child(body_node).unwrap();
+
+let envelope = Envelope::from_document(&document_node).unwrap();
+assert_eq!(envelope.version(), SoapVersion::V12);
+assert!(envelope.header().is_none());
+assert!(envelope.body().is_some());
+assert!(envelope.fault().is_none());
+```
+*/
+
+use crate::level2::convert::as_document;
+use crate::level2::ext::namespaced::{element_namespace, resolve_declared_prefix};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::*;
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
+use crate::shared::name::Name;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The version of SOAP, as identified by the namespace of the `Envelope` element.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoapVersion {
+    /// SOAP 1.1, [http://schemas.xmlsoap.org/soap/envelope/](http://schemas.xmlsoap.org/soap/envelope/)
+    V11,
+    /// SOAP 1.2, [http://www.w3.org/2003/05/soap-envelope](http://www.w3.org/2003/05/soap-envelope)
+    V12,
+}
+
+///
+/// A SOAP message envelope.
+///
+#[derive(Clone, Debug)]
+pub struct Envelope {
+    version: SoapVersion,
+    element: RefNode,
+}
+
+///
+/// A single header block, a child element of the SOAP `Header`.
+///
+#[derive(Clone, Debug)]
+pub struct HeaderBlock {
+    /// The header block element itself.
+    pub element: RefNode,
+    /// The value of the `mustUnderstand` attribute, `false` if it is not present.
+    pub must_understand: bool,
+    /// The value of the `actor` (SOAP 1.1) or `role` (SOAP 1.2) attribute, if present.
+    pub role: Option<String>,
+}
+
+///
+/// The content of a SOAP `Fault`, with the differences between SOAP 1.1 and SOAP 1.2 smoothed over.
+///
+#[derive(Clone, Debug)]
+pub struct Fault {
+    /// The `Fault` element itself.
+    pub element: RefNode,
+    /// The fault code; `faultcode` (SOAP 1.1) or `Code/Value` (SOAP 1.2).
+    pub code: Option<String>,
+    /// The human readable reason; `faultstring` (SOAP 1.1) or the first `Reason/Text` (SOAP 1.2).
+    pub reason: Option<String>,
+    /// The node responsible; `faultactor` (SOAP 1.1) or `Node` (SOAP 1.2).
+    pub actor: Option<String>,
+    /// The element containing application specific details; `detail` (SOAP 1.1) or `Detail`
+    /// (SOAP 1.2).
+    pub detail: Option<RefNode>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl SoapVersion {
+    ///
+    /// Returns the envelope namespace URI for this version.
+    ///
+    pub fn namespace_uri(&self) -> &'static str {
+        match self {
+            SoapVersion::V11 => SOAP_11_ENVELOPE_NS,
+            SoapVersion::V12 => SOAP_12_ENVELOPE_NS,
+        }
+    }
+
+    ///
+    /// Returns the version for the envelope namespace URI `namespace_uri`, if it is one.
+    ///
+    pub fn from_namespace_uri(namespace_uri: &str) -> Option<Self> {
+        match namespace_uri {
+            SOAP_11_ENVELOPE_NS => Some(SoapVersion::V11),
+            SOAP_12_ENVELOPE_NS => Some(SoapVersion::V12),
+            _ => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Envelope {
+    ///
+    /// Construct an envelope from the document element of `document`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if the document has no document element, or it is not a SOAP
+    ///   `Envelope` element.
+    /// * `NOT_SUPPORTED_ERR`: Raised if `document` is not a `Document` node.
+    ///
+    pub fn from_document(document: &RefNode) -> Result<Self> {
+        let ref_document = as_document(document).map_err(|_| Error::NotSupported)?;
+        match ref_document.document_element() {
+            None => Err(Error::NotFound),
+            Some(element) => Self::from_element(&element),
+        }
+    }
+
+    ///
+    /// Construct an envelope from `element`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if `element` is not a SOAP `Envelope` element.
+    /// * `NOT_SUPPORTED_ERR`: Raised if `element` is not an `Element` node.
+    ///
+    pub fn from_element(element: &RefNode) -> Result<Self> {
+        if element.node_type() != NodeType::Element {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::NotSupported);
+        }
+        let name = element.node_name();
+        match element_namespace(element).and_then(|ns| SoapVersion::from_namespace_uri(&ns)) {
+            Some(version) if name.local_name() == ENVELOPE => Ok(Self {
+                version,
+                element: element.clone(),
+            }),
+            _ => Err(Error::NotFound),
+        }
+    }
+
+    ///
+    /// Returns the SOAP version of this envelope.
+    ///
+    pub fn version(&self) -> SoapVersion {
+        self.version
+    }
+
+    ///
+    /// Returns the `Envelope` element.
+    ///
+    pub fn element(&self) -> &RefNode {
+        &self.element
+    }
+
+    ///
+    /// Returns the `Header` element, if present.
+    ///
+    pub fn header(&self) -> Option<RefNode> {
+        self.child(&self.element, HEADER)
+    }
+
+    ///
+    /// Returns the `Body` element, if present.
+    ///
+    pub fn body(&self) -> Option<RefNode> {
+        self.child(&self.element, BODY)
+    }
+
+    ///
+    /// Returns the `Header` element, creating it as the first child of the `Envelope` if it is
+    /// not present. A new `Header` uses the same prefix as the `Envelope` element.
+    ///
+    pub fn header_or_insert(&self) -> Result<RefNode> {
+        if let Some(header) = self.header() {
+            return Ok(header);
+        }
+        let document_node = self.element.owner_document().ok_or(Error::NotFound)?;
+        let document = as_document(&document_node)?;
+        let qualified_name = match self.element.node_name().prefix() {
+            None => HEADER.to_string(),
+            Some(prefix) => format!("{}:{}", prefix, HEADER),
+        };
+        let header = document.create_element_ns(self.version.namespace_uri(), &qualified_name)?;
+        let mut envelope_node = self.element.clone();
+        let first_child = envelope_node.first_child();
+        envelope_node.insert_before(header, first_child)
+    }
+
+    ///
+    /// Returns all the header blocks in the `Header` element, in document order.
+    ///
+    pub fn headers(&self) -> Vec<HeaderBlock> {
+        let role_name = match self.version {
+            SoapVersion::V11 => ACTOR,
+            SoapVersion::V12 => ROLE,
+        };
+        match self.header() {
+            None => Vec::default(),
+            Some(header) => child_elements(&header)
+                .into_iter()
+                .map(|element| {
+                    let must_understand = matches!(
+                        self.attribute(&element, MUST_UNDERSTAND).as_deref(),
+                        Some("1") | Some("true")
+                    );
+                    let role = self.attribute(&element, role_name);
+                    HeaderBlock {
+                        element,
+                        must_understand,
+                        role,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    ///
+    /// Returns only those header blocks that have `mustUnderstand` set.
+    ///
+    pub fn must_understand_headers(&self) -> Vec<HeaderBlock> {
+        self.headers()
+            .into_iter()
+            .filter(|header| header.must_understand)
+            .collect()
+    }
+
+    ///
+    /// Returns the fault details if the `Body` element contains a `Fault`.
+    ///
+    pub fn fault(&self) -> Option<Fault> {
+        let element = self.child(&self.body()?, FAULT)?;
+        let fault = match self.version {
+            SoapVersion::V11 => Fault {
+                code: unqualified_child(&element, V11_FAULT_CODE).map(|e| text_content(&e)),
+                reason: unqualified_child(&element, V11_FAULT_STRING).map(|e| text_content(&e)),
+                actor: unqualified_child(&element, V11_FAULT_ACTOR).map(|e| text_content(&e)),
+                detail: unqualified_child(&element, V11_DETAIL),
+                element,
+            },
+            SoapVersion::V12 => Fault {
+                code: self
+                    .child(&element, V12_CODE)
+                    .and_then(|code| self.child(&code, V12_VALUE))
+                    .map(|e| text_content(&e)),
+                reason: self
+                    .child(&element, V12_REASON)
+                    .and_then(|reason| self.child(&reason, V12_TEXT))
+                    .map(|e| text_content(&e)),
+                actor: self.child(&element, V12_NODE).map(|e| text_content(&e)),
+                detail: self.child(&element, V12_DETAIL),
+                element,
+            },
+        };
+        Some(fault)
+    }
+
+    fn is_soap_name(&self, name: &Name, namespace_uri: Option<String>, local_name: &str) -> bool {
+        name.local_name() == local_name
+            && namespace_uri.as_deref() == Some(self.version.namespace_uri())
+    }
+
+    fn child(&self, parent: &RefNode, local_name: &str) -> Option<RefNode> {
+        child_elements(parent).into_iter().find(|element| {
+            self.is_soap_name(&element.node_name(), element_namespace(element), local_name)
+        })
+    }
+
+    fn attribute(&self, element: &RefNode, local_name: &str) -> Option<String> {
+        element
+            .attributes()
+            .iter()
+            .find(|(name, _)| {
+                let namespace_uri = match (name.namespace_uri(), name.prefix()) {
+                    (Some(namespace_uri), _) => Some(namespace_uri.clone()),
+                    (None, Some(prefix)) => resolve_declared_prefix(element, Some(prefix.as_str())),
+                    (None, None) => None,
+                };
+                self.is_soap_name(name, namespace_uri, local_name)
+            })
+            .and_then(|(_, attribute)| attribute.value())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const SOAP_11_ENVELOPE_NS: &str = "http://schemas.xmlsoap.org/soap/envelope/";
+const SOAP_12_ENVELOPE_NS: &str = "http://www.w3.org/2003/05/soap-envelope";
+
+const ENVELOPE: &str = "Envelope";
+const HEADER: &str = "Header";
+const BODY: &str = "Body";
+const FAULT: &str = "Fault";
+const MUST_UNDERSTAND: &str = "mustUnderstand";
+const ACTOR: &str = "actor";
+const ROLE: &str = "role";
+
+const V11_FAULT_CODE: &str = "faultcode";
+const V11_FAULT_STRING: &str = "faultstring";
+const V11_FAULT_ACTOR: &str = "faultactor";
+const V11_DETAIL: &str = "detail";
+
+const V12_CODE: &str = "Code";
+const V12_VALUE: &str = "Value";
+const V12_REASON: &str = "Reason";
+const V12_TEXT: &str = "Text";
+const V12_NODE: &str = "Node";
+const V12_DETAIL: &str = "Detail";
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) fn child_elements(parent: &RefNode) -> Vec<RefNode> {
+    parent
+        .child_nodes()
+        .into_iter()
+        .filter(|child| child.node_type() == NodeType::Element)
+        .collect()
+}
+
+fn unqualified_child(parent: &RefNode, local_name: &str) -> Option<RefNode> {
+    child_elements(parent).into_iter().find(|element| {
+        let name = element.node_name();
+        name.prefix().is_none() && name.local_name() == local_name
+    })
+}
+
+fn text_content(node: &RefNode) -> String {
+    match node.node_type() {
+        NodeType::Text | NodeType::CData => node.node_value().unwrap_or_default(),
+        _ => node
+            .child_nodes()
+            .iter()
+            .map(text_content)
+            .collect::<Vec<String>>()
+            .join(""),
+    }
+}
@@ -0,0 +1,236 @@
+/*!
+Provides the [`DocumentSplit`](../trait.DocumentSplit.html) extension, which produces a new,
+standalone, document for each element in a document matched by a
+[selector](../selector/index.html); for example to break a large export into one document per
+record.
+
+Each new document contains a deep copy of a matched element as its document element, with the
+same processing options as the source document. As the copy no longer has the ancestors that
+declared the namespaces it may use, every namespace declaration in scope at the matched element
+is added to the new document element, unless the element itself declares the same prefix. If
+[`SplitOptions::set_copy_prolog`](struct.SplitOptions.html#method.set_copy_prolog) is set the XML
+declaration, document type, and the comments, processing instructions, and unparsed markup
+preceding the document element are also copied into each new document.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::DocumentSplit;
+use xml_dom::level2::ext::split::SplitOptions;
+
+let document_node = xml_dom::parser::read_xml(
+    r#"<export xmlns:p="urn:people"><p:person>Ann</p:person><p:person>Bob</p:person></export>"#,
+)
+.unwrap();
+
+let documents = document_node
+    .split_document("p|person", &SplitOptions::default())
+    .unwrap();
+assert_eq!(documents.len(), 2);
+assert_eq!(
+    documents[1].to_string(),
+    r#"<p:person xmlns:p="urn:people">Bob</p:person>"#
+);
+# }
+```
+*/
+
+use crate::level2::convert::{
+    as_attribute, as_document, as_document_type, as_processing_instruction,
+};
+use crate::level2::ext::dom_impl::get_implementation_ext;
+use crate::level2::ext::dtd::set_internal_subset;
+use crate::level2::ext::traits::{DocumentDecl, DocumentSplit, DocumentUnparsed};
+use crate::level2::ext::workspace::{import_node, NameTable};
+use crate::level2::ext::{ProcessingOptions, Selector};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::traits::*;
+use crate::shared::display::internal_subset_text;
+use crate::shared::error::{Result, MSG_INVALID_EXTENSION};
+use crate::shared::name::Name;
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI};
+use std::collections::HashSet;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Options that control the documents produced by
+/// [`split_document`](../trait.DocumentSplit.html#tymethod.split_document).
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SplitOptions {
+    copy_prolog: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl SplitOptions {
+    ///
+    /// Construct a new set of options, by default the prolog is not copied.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Returns `true` if the prolog of the source document is copied into each new document.
+    ///
+    pub fn has_copy_prolog(&self) -> bool {
+        self.copy_prolog
+    }
+
+    ///
+    /// Copy the XML declaration, document type, and other prolog nodes of the source document
+    /// into each new document.
+    ///
+    pub fn set_copy_prolog(&mut self) {
+        self.copy_prolog = true;
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentSplit for RefNode {
+    fn split_document(&self, query: &str, options: &SplitOptions) -> Result<Vec<Self::NodeRef>> {
+        let document = as_document(self)?;
+        let selector = Selector::parse(query)?;
+        let processing_options = document_options(self);
+
+        let mut names: NameTable = Default::default();
+        let mut documents: Vec<RefNode> = Default::default();
+        for element in selector.select(self) {
+            let doc_type = match document.doc_type() {
+                Some(doc_type) if options.copy_prolog => Some(copy_doc_type(&doc_type)?),
+                _ => None,
+            };
+            let mut document_node = get_implementation_ext().create_document_with_options(
+                None,
+                None,
+                doc_type,
+                processing_options.clone(),
+            )?;
+            if options.copy_prolog {
+                copy_prolog(self, &mut document_node)?;
+            }
+            let mut root_node = import_node(&mut names, &document_node, &element, true)?;
+            declare_inherited_namespaces(&element, &mut root_node)?;
+            let _safe_to_ignore = document_node.append_child(root_node)?;
+            documents.push(document_node);
+        }
+        Ok(documents)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn document_options(document_node: &RefNode) -> ProcessingOptions {
+    let ref_document = document_node.borrow();
+    if let Extension::Document { i_options, .. } = &ref_document.i_extension {
+        i_options.clone()
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        ProcessingOptions::default()
+    }
+}
+
+fn copy_doc_type(doc_type: &RefNode) -> Result<RefNode> {
+    let source = as_document_type(doc_type)?;
+    let new_doc_type = get_implementation_ext().create_document_type(
+        &doc_type.node_name().to_string(),
+        source.public_id().as_deref(),
+        source.system_id().as_deref(),
+    )?;
+    set_internal_subset(&new_doc_type, internal_subset_text(doc_type).as_deref());
+    Ok(new_doc_type)
+}
+
+///
+/// Copy the XML declaration, and the prolog nodes other than the document type, of `source` into
+/// `document_node`, keeping the position of each relative to the document type.
+///
+fn copy_prolog(source: &RefNode, document_node: &mut RefNode) -> Result<()> {
+    if let Some(xml_decl) = source.xml_declaration() {
+        document_node.set_xml_declaration(xml_decl)?;
+    }
+    for node in source.prolog_nodes() {
+        let position = match source.prolog_position(&node) {
+            Some(position) => position,
+            None => continue,
+        };
+        let _safe_to_ignore = match node.node_type() {
+            NodeType::Comment => document_node
+                .add_prolog_comment(&node.node_value().unwrap_or_default(), position)?,
+            NodeType::ProcessingInstruction => {
+                let processing_instruction = as_processing_instruction(&node)?;
+                document_node.add_prolog_pi(
+                    &processing_instruction.target(),
+                    processing_instruction.data().as_deref(),
+                    position,
+                )?
+            }
+            NodeType::UnparsedMarkup => document_node
+                .add_prolog_unparsed_markup(&node.node_value().unwrap_or_default(), position)?,
+            _ => continue,
+        };
+    }
+    Ok(())
+}
+
+///
+/// Add to `imported` each namespace declaration in scope at the ancestors of `element`, the
+/// nearest declaration of a prefix wins, that is not declared by `element` itself. An inherited
+/// un-declaration of the default namespace is not needed in a standalone document.
+///
+fn declare_inherited_namespaces(element: &RefNode, imported: &mut RefNode) -> Result<()> {
+    let mut declared: HashSet<Option<String>> = element
+        .attributes()
+        .into_keys()
+        .filter_map(|name| declared_prefix(&name))
+        .collect();
+    let mut current = element.parent_node();
+    while let Some(ancestor) = current {
+        if ancestor.node_type() != NodeType::Element {
+            break;
+        }
+        let mut declarations: Vec<(Name, RefNode)> = ancestor
+            .attributes()
+            .into_iter()
+            .filter(|(name, _)| declared_prefix(name).is_some())
+            .collect();
+        declarations.sort_by_key(|(name, _)| name.to_string());
+        for (name, attribute) in declarations {
+            let prefix = declared_prefix(&name).unwrap();
+            if !declared.insert(prefix.clone()) {
+                continue;
+            }
+            let namespace_uri = as_attribute(&attribute)?.value().unwrap_or_default();
+            if prefix.is_none() && namespace_uri.is_empty() {
+                continue;
+            }
+            imported.set_attribute_ns(XMLNS_NS_URI, &name.to_string(), &namespace_uri)?;
+        }
+        current = ancestor.parent_node();
+    }
+    Ok(())
+}
+
+///
+/// If `name` is a namespace declaration, returns the declared prefix, `Some(None)` for the
+/// default namespace.
+///
+fn declared_prefix(name: &Name) -> Option<Option<String>> {
+    match name.prefix().as_deref() {
+        None if name.local_name() == XMLNS_NS_ATTRIBUTE => Some(None),
+        Some(XMLNS_NS_ATTRIBUTE) => Some(Some(name.local_name().clone())),
+        _ => None,
+    }
+}
@@ -0,0 +1,233 @@
+/*!
+Provides [`SyncDocument`](struct.SyncDocument.html), a facade that allows a document to be shared
+between threads.
+
+The `RefNode` type uses reference-counted, single-threaded, cells and so neither a document nor
+any of its nodes may be sent to another thread. A `SyncDocument` instead moves the document to a
+dedicated thread that owns it, and all access is performed by sending closures to that thread
+where they are run one at a time, in the order they were received. As a result no locks are held
+on individual nodes, and a client cannot observe a document part-way through another client's
+mutation.
+
+Closures passed to [`query`](struct.SyncDocument.html#method.query) and
+[`mutate`](struct.SyncDocument.html#method.mutate) may only return owned, `Send`, data; they cannot
+return a `RefNode`, so no reference into the document ever escapes the owning thread. A closure
+that attempts to call back into the same `SyncDocument` would wait forever for itself to complete,
+this is detected and reported as an error instead.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::SyncDocument;
+use std::thread;
+
+let document = SyncDocument::spawn(|| {
+    get_implementation().create_document(None, Some("log"), None)
+})
+.unwrap();
+
+let workers: Vec<thread::JoinHandle<()>> = (0..4)
+    .map(|id| {
+        let document = document.clone();
+        thread::spawn(move || {
+            document
+                .mutate(move |document_node| {
+                    let document = as_document(document_node).unwrap();
+                    let mut root_node = document.document_element().unwrap();
+                    let mut entry_node = document.create_element("entry")?;
+                    entry_node.set_attribute("worker", &id.to_string())?;
+                    let _safe_to_ignore = root_node.append_child(entry_node)?;
+                    Ok(())
+                })
+                .unwrap();
+        })
+    })
+    .collect();
+for worker in workers {
+    worker.join().unwrap();
+}
+
+let entries = document
+    .query(|document_node| {
+        let document = as_document(document_node).unwrap();
+        document.get_elements_by_tag_name("entry").len()
+    })
+    .unwrap();
+assert_eq!(entries, 4);
+```
+*/
+
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Node, NodeType};
+use crate::shared::error::{
+    Error, Result, MSG_DOCUMENT_THREAD_UNAVAILABLE, MSG_INVALID_NODE_TYPE, MSG_REENTRANT_CALL,
+};
+use std::sync::mpsc::{channel, Sender};
+use std::thread::{self, ThreadId};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A handle to a document owned by a dedicated thread; see the [module](index.html) documentation.
+///
+/// Handles are cheap to clone and may be sent to, and shared between, threads. The owning thread
+/// exits, dropping the document, once all handles have been dropped.
+///
+#[derive(Clone, Debug)]
+pub struct SyncDocument {
+    commands: Sender<Command>,
+    owner: ThreadId,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl SyncDocument {
+    ///
+    /// Start a new thread that will own the document returned by `build`. The document must be
+    /// created on the owning thread, and so it is constructed by the closure rather than passed
+    /// in.
+    ///
+    /// **Exceptions**
+    ///
+    /// * Any error returned by `build`.
+    /// * `INVALID_STATE_ERR`: Raised if `build` returns a node that is not a `Document`, if the
+    ///   thread could not be started, or if `build` panics.
+    ///
+    pub fn spawn<F>(build: F) -> Result<Self>
+    where
+        F: FnOnce() -> Result<RefNode> + Send + 'static,
+    {
+        let (commands, owner) = spawn_owner(build)?;
+        Ok(Self { commands, owner })
+    }
+
+    ///
+    /// Run `query` against the document, on the owning thread, and return its result. The query
+    /// runs after all previously submitted queries and mutations have completed.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if called from within another query or mutation on this
+    ///   document, or if the owning thread has exited because a previous closure panicked.
+    ///
+    pub fn query<F, T>(&self, query: F) -> Result<T>
+    where
+        F: FnOnce(&RefNode) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.execute(move |document_node| query(document_node))
+    }
+
+    ///
+    /// Run `mutation` against the document, on the owning thread, and return its result. The
+    /// mutation runs after all previously submitted queries and mutations have completed, and no
+    /// other query or mutation will run until it has completed.
+    ///
+    /// **Exceptions**
+    ///
+    /// * Any error returned by `mutation`.
+    /// * `INVALID_STATE_ERR`: Raised if called from within another query or mutation on this
+    ///   document, or if the owning thread has exited because a previous closure panicked.
+    ///
+    pub fn mutate<F, T>(&self, mutation: F) -> Result<T>
+    where
+        F: FnOnce(&mut RefNode) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.execute(mutation)?
+    }
+
+    fn execute<F, T>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce(&mut RefNode) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        if thread::current().id() == self.owner {
+            warn!("{}", MSG_REENTRANT_CALL);
+            return Err(Error::InvalidState);
+        }
+        let (reply_sender, reply_receiver) = channel::<T>();
+        self.commands
+            .send(Box::new(move |document_node: &mut RefNode| {
+                let _safe_to_ignore = reply_sender.send(operation(document_node));
+            }))
+            .map_err(|_| {
+                warn!("{}", MSG_DOCUMENT_THREAD_UNAVAILABLE);
+                Error::InvalidState
+            })?;
+        reply_receiver.recv().map_err(|_| {
+            warn!("{}", MSG_DOCUMENT_THREAD_UNAVAILABLE);
+            Error::InvalidState
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const THREAD_NAME: &str = "xml_dom-document";
+
+///
+/// A closure to be run against the document on the owning thread.
+///
+pub(crate) type Command = Box<dyn FnOnce(&mut RefNode) + Send>;
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Start a new thread that owns the document returned by `build` and runs each command it
+/// receives, in order, until all senders have been dropped. Returns the command sender and the
+/// owning thread's identifier.
+///
+pub(crate) fn spawn_owner<F>(build: F) -> Result<(Sender<Command>, ThreadId)>
+where
+    F: FnOnce() -> Result<RefNode> + Send + 'static,
+{
+    let (commands, receiver) = channel::<Command>();
+    let (ready_sender, ready_receiver) = channel::<Result<()>>();
+    let owner = thread::Builder::new()
+        .name(THREAD_NAME.to_string())
+        .spawn(move || {
+            let mut document_node = match build() {
+                Ok(document_node) if document_node.node_type() == NodeType::Document => {
+                    document_node
+                }
+                Ok(_) => {
+                    warn!("{}", MSG_INVALID_NODE_TYPE);
+                    let _safe_to_ignore = ready_sender.send(Err(Error::InvalidState));
+                    return;
+                }
+                Err(e) => {
+                    let _safe_to_ignore = ready_sender.send(Err(e));
+                    return;
+                }
+            };
+            let _safe_to_ignore = ready_sender.send(Ok(()));
+            for command in receiver {
+                command(&mut document_node);
+            }
+        })
+        .map_err(|e| {
+            warn!("{}: '{}'", MSG_DOCUMENT_THREAD_UNAVAILABLE, e);
+            Error::InvalidState
+        })?
+        .thread()
+        .id();
+    match ready_receiver.recv() {
+        Ok(Ok(())) => Ok((commands, owner)),
+        Ok(Err(e)) => Err(e),
+        Err(_) => {
+            warn!("{}", MSG_DOCUMENT_THREAD_UNAVAILABLE);
+            Err(Error::InvalidState)
+        }
+    }
+}
@@ -1,10 +1,31 @@
+use crate::level2::convert::{as_attribute, as_document};
 use crate::level2::dom_impl::Implementation;
 use crate::level2::ext::decl::*;
+use crate::level2::ext::dtd::{
+    add_replacement_nodes, element_declarations, new_parameter_entity, AttributeDefinition,
+    ConditionalSection,
+};
+use crate::level2::ext::normalize::is_space_preserved;
 use crate::level2::ext::options::ProcessingOptions;
 use crate::level2::ext::traits::*;
 use crate::level2::node_impl::*;
-use crate::level2::trait_impls::create_document_with_options;
+use crate::level2::trait_impls::{
+    add_default_attributes, create_document_with_options, is_id_attribute_node,
+    set_user_id_attribute,
+};
+use crate::level2::traits::{Document, DocumentType, Element, Node, NodeType};
 use crate::shared::error::*;
+use crate::shared::name::Name;
+use crate::shared::syntax::{
+    XML_ELEMENT_DECL_ANY, XML_ELEMENT_DECL_EMPTY, XML_ELEMENT_DECL_PCDATA, XML_ENTITYREF_END,
+    XML_ENTITYREF_START, XML_ESC_AMP_CHAR, XML_ESC_APOS_CHAR, XML_ESC_GT_CHAR, XML_ESC_LT_CHAR,
+    XML_ESC_QUOT_CHAR, XML_NS_SEPARATOR,
+};
+use crate::shared::text::{expand_references, is_xml_space, EntityResolver};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::str::FromStr;
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
@@ -37,6 +58,294 @@ impl DocumentDecl for RefNode {
             Err(Error::InvalidState)
         }
     }
+
+    fn xml_version(&self) -> XmlVersion {
+        self.xml_declaration()
+            .map(|xml_decl| xml_decl.version())
+            .unwrap_or(XmlVersion::V10)
+    }
+
+    fn set_xml_version(&mut self, version: XmlVersion) -> Result<()> {
+        let xml_decl = self.xml_declaration().unwrap_or_default();
+        self.set_xml_declaration(XmlDecl::new(
+            version,
+            xml_decl.encoding(),
+            xml_decl.standalone(),
+        ))
+    }
+
+    fn xml_encoding(&self) -> Option<String> {
+        self.xml_declaration()
+            .and_then(|xml_decl| xml_decl.encoding())
+    }
+
+    fn set_xml_encoding(&mut self, encoding: Option<&str>) -> Result<()> {
+        if let Some(encoding) = encoding {
+            if !is_encoding(encoding) {
+                warn!("{}: '{}'", MSG_INVALID_ENCODING, encoding);
+                return Err(Error::Syntax);
+            }
+        }
+        let xml_decl = self.xml_declaration().unwrap_or_default();
+        self.set_xml_declaration(XmlDecl::new(
+            xml_decl.version(),
+            encoding.map(String::from),
+            xml_decl.standalone(),
+        ))
+    }
+
+    fn xml_standalone(&self) -> bool {
+        self.xml_declaration()
+            .and_then(|xml_decl| xml_decl.standalone())
+            .unwrap_or_default()
+    }
+
+    fn set_xml_standalone(&mut self, standalone: bool) -> Result<()> {
+        let xml_decl = self.xml_declaration().unwrap_or_default();
+        self.set_xml_declaration(XmlDecl::new(
+            xml_decl.version(),
+            xml_decl.encoding(),
+            Some(standalone),
+        ))
+    }
+
+    fn prolog_nodes(&self) -> Vec<Self::NodeRef> {
+        self.child_nodes()
+            .into_iter()
+            .take_while(|child| child.node_type() != NodeType::Element)
+            .collect()
+    }
+
+    fn epilog_nodes(&self) -> Vec<Self::NodeRef> {
+        self.child_nodes()
+            .into_iter()
+            .skip_while(|child| child.node_type() != NodeType::Element)
+            .skip(1)
+            .collect()
+    }
+
+    fn prolog_position(&self, node: &Self::NodeRef) -> Option<PrologPosition> {
+        //
+        // A node is only placed before the document type if it, and every node preceding it,
+        // was added there; otherwise the document type would be split from the XML declaration.
+        //
+        let mut position = PrologPosition::BeforeDocType;
+        for child in self.prolog_nodes() {
+            if position == PrologPosition::BeforeDocType && !is_before_doc_type(self, &child) {
+                position = PrologPosition::AfterDocType;
+            }
+            if &child == node {
+                return Some(position);
+            }
+        }
+        None
+    }
+
+    fn add_prolog_comment(
+        &mut self,
+        data: &str,
+        position: PrologPosition,
+    ) -> Result<Self::NodeRef> {
+        let comment_node = self.create_comment(data);
+        add_prolog_node(self, comment_node, position)
+    }
+
+    fn add_prolog_pi(
+        &mut self,
+        target: &str,
+        data: Option<&str>,
+        position: PrologPosition,
+    ) -> Result<Self::NodeRef> {
+        let pi_node = self.create_processing_instruction(target, data)?;
+        add_prolog_node(self, pi_node, position)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentTypeDecl for RefNode {
+    fn create_notation(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+    ) -> Result<Self::NodeRef> {
+        let name = Name::from_str(name)?;
+        if public_id.is_none() && system_id.is_none() {
+            warn!("{}: '{}'", MSG_MISSING_EXTERNAL_ID, name);
+            return Err(Error::Syntax);
+        }
+        let owner_document = self.borrow().i_owner_document.clone();
+        let mut mut_self = self.borrow_mut();
+        if let Extension::DocumentType { i_notations, .. } = &mut mut_self.i_extension {
+            if i_notations.contains_key(&name) {
+                warn!("{}: '{}'", MSG_DUPLICATE_DECLARATION, name);
+                return Err(Error::Syntax);
+            }
+            let notation_node = RefNode::new(NodeImpl::new_notation(
+                owner_document,
+                name.clone(),
+                public_id,
+                system_id,
+            ));
+            #[cfg(feature = "bench")]
+            crate::level2::ext::bench::count_map_insert(i_notations, &name);
+            let _safe_to_ignore = i_notations.insert(name, notation_node.clone());
+            Ok(notation_node)
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+
+    fn create_entity(&mut self, name: &str, value: &str) -> Result<Self::NodeRef> {
+        add_entity(self, name, Some(value), None, None, None)
+    }
+
+    fn create_external_entity(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: &str,
+    ) -> Result<Self::NodeRef> {
+        add_entity(self, name, None, public_id, Some(system_id), None)
+    }
+
+    fn create_unparsed_entity(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: &str,
+        notation_name: &str,
+    ) -> Result<Self::NodeRef> {
+        let notation = Name::from_str(notation_name)?;
+        if !self.notations().contains_key(&notation) {
+            warn!("{}: '{}'", MSG_UNDECLARED_NOTATION, notation);
+            return Err(Error::NotFound);
+        }
+        add_entity(
+            self,
+            name,
+            None,
+            public_id,
+            Some(system_id),
+            Some(notation_name),
+        )
+    }
+
+    fn parameter_entities(&self) -> HashMap<Name, Self::NodeRef> {
+        let ref_self = self.borrow();
+        if let Extension::DocumentType {
+            i_parameter_entities,
+            ..
+        } = &ref_self.i_extension
+        {
+            i_parameter_entities.clone()
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            HashMap::default()
+        }
+    }
+
+    fn create_parameter_entity(&mut self, name: &str, value: &str) -> Result<Self::NodeRef> {
+        add_parameter_entity(self, name, Some(value), None, None)
+    }
+
+    fn create_external_parameter_entity(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: &str,
+    ) -> Result<Self::NodeRef> {
+        add_parameter_entity(self, name, None, public_id, Some(system_id))
+    }
+
+    fn conditional_sections(&self) -> Vec<ConditionalSection> {
+        let ref_self = self.borrow();
+        if let Extension::DocumentType {
+            i_conditional_sections,
+            ..
+        } = &ref_self.i_extension
+        {
+            i_conditional_sections.clone()
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Vec::default()
+        }
+    }
+
+    fn add_conditional_section(&mut self, section: ConditionalSection) -> Result<()> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::DocumentType {
+            i_conditional_sections,
+            ..
+        } = &mut mut_self.i_extension
+        {
+            i_conditional_sections.push(section);
+            Ok(())
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+    fn attribute_definitions(&self) -> Vec<AttributeDefinition> {
+        let ref_self = self.borrow();
+        if let Extension::DocumentType {
+            i_attribute_definitions,
+            ..
+        } = &ref_self.i_extension
+        {
+            i_attribute_definitions.clone()
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Vec::default()
+        }
+    }
+    fn add_attribute_definition(&mut self, definition: AttributeDefinition) -> Result<()> {
+        let is_binding = !self.attribute_definitions().iter().any(|existing| {
+            existing.element_name() == definition.element_name()
+                && existing.attribute_name() == definition.attribute_name()
+        });
+        {
+            let mut mut_self = self.borrow_mut();
+            if let Extension::DocumentType {
+                i_attribute_definitions,
+                i_internal_subset,
+                ..
+            } = &mut mut_self.i_extension
+            {
+                let mut internal_subset = i_internal_subset.take().unwrap_or_default();
+                internal_subset.push_str(&definition.to_string());
+                *i_internal_subset = Some(internal_subset);
+                i_attribute_definitions.push(definition.clone());
+            } else {
+                warn!("{}", MSG_INVALID_EXTENSION);
+                return Err(Error::InvalidState);
+            }
+        }
+        match self.owner_document() {
+            Some(document_node)
+                if is_binding
+                    && definition.default_value().is_some()
+                    && document_node.doc_type().as_ref() == Some(self) =>
+            {
+                let attribute_name = definition.attribute_name();
+                let document = as_document(&document_node)?;
+                for element in
+                    document.get_elements_by_tag_name(&definition.element_name().to_string())
+                {
+                    if element
+                        .get_attribute_node(&attribute_name.to_string())
+                        .is_none()
+                    {
+                        add_default_attributes(&document_node, &element, Some(attribute_name));
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -49,6 +358,455 @@ impl DOMImplementation for Implementation {
         doc_type: Option<Self::NodeRef>,
         options: ProcessingOptions,
     ) -> Result<Self::NodeRef> {
-        create_document_with_options(namespace_uri, qualified_name, doc_type, options)
+        create_document_with_options(
+            Rc::new(self.clone()),
+            namespace_uri,
+            qualified_name,
+            doc_type,
+            options,
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodeBorrow for RefNode {
+    fn with_name<R>(&self, f: impl FnOnce(&Name) -> R) -> R {
+        let ref_self = self.borrow();
+        f(&ref_self.i_name)
+    }
+
+    fn with_value<R>(&self, f: impl FnOnce(Option<&str>) -> R) -> R {
+        let ref_self = self.borrow();
+        f(ref_self.i_value.as_deref())
+    }
+
+    fn with_attribute<R>(&self, name: &str, f: impl FnOnce(Option<&str>) -> R) -> R {
+        let (prefix, local_name) = match name.split_once(XML_NS_SEPARATOR) {
+            Some((prefix, local_name)) => (Some(prefix), local_name),
+            None => (None, name),
+        };
+        let attribute_node = {
+            let ref_self = self.borrow();
+            match &ref_self.i_extension {
+                Extension::Element { i_attributes, .. } => i_attributes
+                    .iter()
+                    .find(|(attribute_name, _)| {
+                        attribute_name.prefix().as_deref() == prefix
+                            && attribute_name.local_name() == local_name
+                    })
+                    .map(|(_, attribute_node)| attribute_node.clone()),
+                _ => None,
+            }
+        };
+        let attribute_node = match attribute_node {
+            None => return f(None),
+            Some(attribute_node) => attribute_node,
+        };
+        {
+            //
+            // The common case, a single text child whose data needs neither normalization nor
+            // escaping, is the value.
+            //
+            let ref_attribute = attribute_node.borrow();
+            if let [child_node] = ref_attribute.i_child_nodes.as_slice() {
+                let ref_child = child_node.borrow();
+                if ref_child.i_node_type == NodeType::Text {
+                    if let Some(data) = &ref_child.i_value {
+                        if is_plain_attribute_value(data) {
+                            return f(Some(data));
+                        }
+                    }
+                }
+            }
+        }
+        match as_attribute(&attribute_node) {
+            Ok(attribute) => f(attribute.value().as_deref()),
+            Err(_) => f(None),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ElementId for RefNode {
+    fn set_id_attribute(&mut self, name: &str, is_id: bool) -> Result<()> {
+        match self.get_attribute_node(name) {
+            Some(id_attribute) => self.set_id_attribute_node(id_attribute, is_id),
+            None => {
+                warn!("{}", MSG_ATTRIBUTE_NOT_FOUND);
+                Err(Error::NotFound)
+            }
+        }
+    }
+
+    fn set_id_attribute_ns(
+        &mut self,
+        namespace_uri: &str,
+        local_name: &str,
+        is_id: bool,
+    ) -> Result<()> {
+        match self.get_attribute_node_ns(namespace_uri, local_name) {
+            Some(id_attribute) => self.set_id_attribute_node(id_attribute, is_id),
+            None => {
+                warn!("{}", MSG_ATTRIBUTE_NOT_FOUND);
+                Err(Error::NotFound)
+            }
+        }
+    }
+
+    fn set_id_attribute_node(&mut self, id_attribute: Self::NodeRef, is_id: bool) -> Result<()> {
+        let is_owned = as_attribute(&id_attribute)
+            .ok()
+            .and_then(|attribute| attribute.owner_element())
+            .as_ref()
+            == Some(self);
+        if is_owned && self.attributes().values().any(|node| node == &id_attribute) {
+            set_user_id_attribute(self, &id_attribute, is_id);
+            Ok(())
+        } else {
+            warn!("{}", MSG_ATTRIBUTE_NOT_FOUND);
+            Err(Error::NotFound)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl AttributeId for RefNode {
+    fn is_id(&self) -> bool {
+        is_id_attribute_node(self)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentEntities for RefNode {
+    fn entity(&self, name: &str) -> Option<Self::NodeRef> {
+        declared_node(self, name, false)
+    }
+
+    fn notation(&self, name: &str) -> Option<Self::NodeRef> {
+        declared_node(self, name, true)
+    }
+
+    fn has_entity(&self, name: &str) -> bool {
+        self.entity(name).is_some()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl TextWhitespace for RefNode {
+    fn is_element_content_whitespace(&self) -> bool {
+        if self.node_type() != NodeType::Text
+            || !self
+                .node_value()
+                .unwrap_or_default()
+                .chars()
+                .all(is_xml_space)
+        {
+            return false;
+        }
+        match self.parent_node() {
+            Some(parent) if parent.node_type() == NodeType::Element => {
+                match declared_element_content(&parent) {
+                    Some(is_element_content) => is_element_content,
+                    None => {
+                        parent
+                            .child_nodes()
+                            .iter()
+                            .any(|child| child.node_type() == NodeType::Element)
+                            && !is_space_preserved(&parent)
+                    }
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodeOwner for RefNode {
+    fn verify_owner(&self, document: &RefNode) -> Result<()> {
+        let owner_document = if self.node_type() == NodeType::Document {
+            Some(self.clone())
+        } else {
+            self.owner_document()
+        };
+        if owner_document.as_ref() != Some(document) {
+            warn!("{}", MSG_WRONG_DOCUMENT);
+            return Err(Error::WrongDocument);
+        }
+        for attribute in self.attributes().values() {
+            attribute.verify_owner(document)?;
+        }
+        for child_node in self.child_nodes() {
+            child_node.verify_owner(document)?;
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ElementText for RefNode {
+    fn normalized_text(&self) -> String {
+        let resolver = DocumentEntityResolver {
+            document: self.owner_document(),
+            expanding: Default::default(),
+        };
+        let mut result = String::new();
+        for child_node in self.child_nodes() {
+            resolver.append_text(&child_node, &mut result);
+        }
+        result
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl EntityResolver for DocumentEntityResolver {
+    fn resolve(&self, entity: &str) -> Option<String> {
+        let name = &entity[1..entity.len() - 1];
+        if self
+            .expanding
+            .borrow()
+            .iter()
+            .any(|expanding| expanding == name)
+        {
+            return None;
+        }
+        let entity = self.document.as_ref()?.entity(name)?;
+        self.expanding.borrow_mut().push(name.to_string());
+        let mut result = String::new();
+        if entity.has_child_nodes() {
+            for child_node in entity.child_nodes() {
+                self.append_text(&child_node, &mut result);
+            }
+        } else if let Some(value) = entity.node_value() {
+            result = expand_references(&value, self);
+        }
+        let _safe_to_ignore = self.expanding.borrow_mut().pop();
+        Some(result)
+    }
+}
+
+impl DocumentEntityResolver {
+    fn append_text(&self, node: &RefNode, result: &mut String) {
+        match node.node_type() {
+            NodeType::Text | NodeType::CData => {
+                result.push_str(&node.node_value().unwrap_or_default())
+            }
+            NodeType::EntityReference if !node.has_child_nodes() => {
+                let reference = format!(
+                    "{}{}{}",
+                    XML_ENTITYREF_START,
+                    node.node_name(),
+                    XML_ENTITYREF_END
+                );
+                result.push_str(&self.resolve(&reference).unwrap_or(reference));
+            }
+            NodeType::Element | NodeType::EntityReference => {
+                for child_node in node.child_nodes() {
+                    self.append_text(&child_node, result);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Resolves references to the entities declared by the document type of `document`, keeping the
+/// names of the entities being expanded so that a recursive reference is left as written.
+///
+struct DocumentEntityResolver {
+    document: Option<RefNode>,
+    expanding: RefCell<Vec<String>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Is `data` unchanged by attribute value normalization and escaping.
+///
+fn is_plain_attribute_value(data: &str) -> bool {
+    !data.starts_with(' ')
+        && !data.ends_with(' ')
+        && !data.contains(|c| {
+            matches!(
+                c,
+                XML_ESC_AMP_CHAR
+                    | XML_ESC_APOS_CHAR
+                    | XML_ESC_GT_CHAR
+                    | XML_ESC_LT_CHAR
+                    | XML_ESC_QUOT_CHAR
+                    | '%'
+                    | '\t'
+                    | '\n'
+                    | '\r'
+            )
+        })
+}
+
+fn add_entity(
+    doc_type: &mut RefNode,
+    name: &str,
+    value: Option<&str>,
+    public_id: Option<&str>,
+    system_id: Option<&str>,
+    notation_name: Option<&str>,
+) -> Result<RefNode> {
+    let name = Name::from_str(name)?;
+    let owner_document = doc_type.borrow().i_owner_document.clone();
+    let mut mut_doc_type = doc_type.borrow_mut();
+    if let Extension::DocumentType { i_entities, .. } = &mut mut_doc_type.i_extension {
+        if i_entities.contains_key(&name) {
+            warn!("{}: '{}'", MSG_DUPLICATE_DECLARATION, name);
+            return Err(Error::Syntax);
+        }
+        let node_impl = match (value, system_id, notation_name) {
+            (Some(value), _, _) => {
+                NodeImpl::new_internal_entity(owner_document, name.clone(), value)
+            }
+            (None, Some(system_id), Some(notation_name)) => NodeImpl::new_unparsed_entity(
+                owner_document,
+                name.clone(),
+                public_id,
+                system_id,
+                notation_name,
+            ),
+            _ => NodeImpl::new_entity(owner_document, name.clone(), public_id, system_id),
+        };
+        let entity_node = RefNode::new(node_impl);
+        let _safe_to_ignore = i_entities.insert(name, entity_node.clone());
+        drop(mut_doc_type);
+        add_replacement_nodes(&entity_node);
+        Ok(entity_node)
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        Err(Error::InvalidState)
     }
 }
+
+fn add_parameter_entity(
+    doc_type: &mut RefNode,
+    name: &str,
+    value: Option<&str>,
+    public_id: Option<&str>,
+    system_id: Option<&str>,
+) -> Result<RefNode> {
+    let name = Name::from_str(name)?;
+    let owner_document = doc_type.borrow().i_owner_document.clone();
+    let mut mut_doc_type = doc_type.borrow_mut();
+    if let Extension::DocumentType {
+        i_parameter_entities,
+        ..
+    } = &mut mut_doc_type.i_extension
+    {
+        if i_parameter_entities.contains_key(&name) {
+            warn!("{}: '{}'", MSG_DUPLICATE_DECLARATION, name);
+            return Err(Error::Syntax);
+        }
+        let entity_node =
+            new_parameter_entity(owner_document, name.clone(), value, public_id, system_id);
+        let _safe_to_ignore = i_parameter_entities.insert(name, entity_node.clone());
+        Ok(entity_node)
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        Err(Error::InvalidState)
+    }
+}
+
+fn is_before_doc_type(document: &RefNode, node: &RefNode) -> bool {
+    let ref_document = document.borrow();
+    if let Extension::Document {
+        i_before_doc_type, ..
+    } = &ref_document.i_extension
+    {
+        i_before_doc_type
+            .iter()
+            .any(|weak| weak.clone().upgrade().as_ref() == Some(node))
+    } else {
+        false
+    }
+}
+
+pub(crate) fn add_prolog_node(
+    document: &mut RefNode,
+    new_child: RefNode,
+    position: PrologPosition,
+) -> Result<RefNode> {
+    let ref_child = match position {
+        PrologPosition::BeforeDocType => document
+            .child_nodes()
+            .into_iter()
+            .find(|child| document.prolog_position(child) != Some(PrologPosition::BeforeDocType)),
+        PrologPosition::AfterDocType => document.document_element(),
+    };
+    let new_child = document.insert_before(new_child, ref_child)?;
+    if position == PrologPosition::BeforeDocType {
+        let mut mut_document = document.borrow_mut();
+        if let Extension::Document {
+            i_before_doc_type, ..
+        } = &mut mut_document.i_extension
+        {
+            i_before_doc_type.retain(|weak| weak.clone().upgrade().is_some());
+            i_before_doc_type.push(new_child.clone().downgrade());
+        }
+    }
+    Ok(new_child)
+}
+
+///
+/// Returns the entity, or notation, named `name` declared by the document type of `document`.
+///
+fn declared_node(document: &RefNode, name: &str, notation: bool) -> Option<RefNode> {
+    let name = Name::from_str(name).ok()?;
+    let doc_type = document.doc_type()?;
+    let ref_doc_type = doc_type.borrow();
+    if let Extension::DocumentType {
+        i_entities,
+        i_notations,
+        ..
+    } = &ref_doc_type.i_extension
+    {
+        if notation {
+            i_notations.get(&name).cloned()
+        } else {
+            i_entities.get(&name).cloned()
+        }
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        None
+    }
+}
+
+///
+/// Returns `Some(true)` if the first declaration of the type of `element`, in the internal subset
+/// of its document's type, allows only child elements, `Some(false)` if it allows text, and `None`
+/// if the element type is not declared.
+///
+fn declared_element_content(element: &RefNode) -> Option<bool> {
+    let internal_subset = element
+        .owner_document()?
+        .doc_type()
+        .and_then(|doc_type| doc_type.internal_subset())?;
+    let element_name = element.node_name().to_string();
+    element_declarations(&internal_subset)
+        .into_iter()
+        .find(|(name, _)| *name == element_name)
+        .map(|(_, content_spec)| {
+            content_spec == XML_ELEMENT_DECL_EMPTY
+                || (content_spec != XML_ELEMENT_DECL_ANY
+                    && !content_spec.contains(XML_ELEMENT_DECL_PCDATA))
+        })
+}
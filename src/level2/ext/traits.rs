@@ -1,8 +1,25 @@
-use crate::level2::ext::decl::XmlDecl;
+use crate::level2::ext::audit::AttributeChange;
+use crate::level2::ext::binary::BinaryEncoding;
+use crate::level2::ext::decl::{PrologPosition, XmlDecl, XmlVersion};
+use crate::level2::ext::dtd::{AttributeDefinition, ConditionalSection};
+use crate::level2::ext::extension::NodeExtension;
+use crate::level2::ext::links::{Link, LinkConfig};
+use crate::level2::ext::named_node_map::NamedNodeMap;
 use crate::level2::ext::namespaced::NamespacePrefix;
+use crate::level2::ext::node_list::NodeList;
+use crate::level2::ext::normalize::DOMConfiguration;
 use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::outline::Outline;
+use crate::level2::ext::resolver::UriResolver;
+use crate::level2::ext::split::SplitOptions;
+use crate::level2::ext::user_data::UserDataHandler;
+use crate::level2::ext::xsd::{XsdDate, XsdDateTime, XsdDecimal, XsdDuration, XsdType};
 use crate::level2::traits as base;
 use crate::shared::error::Result;
+use crate::shared::name::Name;
+use std::any::Any;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 // ------------------------------------------------------------------------------------------------
 // Public Traits
@@ -10,7 +27,10 @@ use crate::shared::error::Result;
 
 ///
 /// This interface extends the DOM standard `Document` and allows the setting, and retrieval,
-/// of the XML declaration from the document prolog.
+/// of the XML declaration from the document prolog. It also provides access to the comments and
+/// processing instructions that make up the rest of the prolog, and the epilog following the
+/// document element, including control over their placement relative to the document type
+/// declaration.
 ///
 /// # Specification
 ///  
@@ -32,6 +52,375 @@ pub trait DocumentDecl: base::Document {
     /// Note that it is not possible to unset (set to `None`) this value.
     ///
     fn set_xml_declaration(&mut self, xml_decl: XmlDecl) -> Result<()>;
+    ///
+    /// The version of XML declared by this document, corresponding to the DOM Level 3
+    /// `xmlVersion` attribute; `XmlVersion::V10` if there is no XML declaration.
+    ///
+    fn xml_version(&self) -> XmlVersion;
+    ///
+    /// Set the version of XML declared by this document, adding an XML declaration if there is
+    /// none; the encoding and standalone values of an existing declaration are kept.
+    ///
+    fn set_xml_version(&mut self, version: XmlVersion) -> Result<()>;
+    ///
+    /// The encoding declared by this document, corresponding to the DOM Level 3 `xmlEncoding`
+    /// attribute; `None` if there is no XML declaration, or it has no encoding.
+    ///
+    fn xml_encoding(&self) -> Option<String>;
+    ///
+    /// Set, or with `None` remove, the encoding declared by this document, adding an XML
+    /// declaration if there is none; the version and standalone values of an existing declaration
+    /// are kept. This only changes the declaration, the serializer always writes UTF-8.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `encoding` is not a valid encoding name, `EncName`.
+    ///
+    fn set_xml_encoding(&mut self, encoding: Option<&str>) -> Result<()>;
+    ///
+    /// Returns `true` if this document is declared to be standalone, corresponding to the DOM
+    /// Level 3 `xmlStandalone` attribute; `false` if there is no XML declaration, or it has no
+    /// standalone value.
+    ///
+    fn xml_standalone(&self) -> bool;
+    ///
+    /// Set the standalone value declared by this document, adding an XML declaration if there is
+    /// none; the version and encoding values of an existing declaration are kept.
+    ///
+    fn set_xml_standalone(&mut self, standalone: bool) -> Result<()>;
+    ///
+    /// Returns the children of this document that precede the document element, in document
+    /// order. If there is no document element all children are returned.
+    ///
+    fn prolog_nodes(&self) -> Vec<Self::NodeRef>;
+    ///
+    /// Returns the children of this document that follow the document element, in document
+    /// order.
+    ///
+    fn epilog_nodes(&self) -> Vec<Self::NodeRef>;
+    ///
+    /// Returns the position of `node`, relative to the document type declaration, if it is a
+    /// member of this document's prolog; else `None`.
+    ///
+    fn prolog_position(&self, node: &Self::NodeRef) -> Option<PrologPosition>;
+    ///
+    /// Create a new `Comment` node and add it to the document prolog at `position`, the new
+    /// node is returned. Nodes are added after any existing prolog nodes at the same position.
+    ///
+    /// **Parameters**
+    ///
+    /// * `data` of type `DOMString`: The data for the node.
+    /// * `position` of type `PrologPosition`: Where to place the node relative to the document
+    ///   type declaration.
+    ///
+    fn add_prolog_comment(&mut self, data: &str, position: PrologPosition)
+        -> Result<Self::NodeRef>;
+    ///
+    /// Create a new `ProcessingInstruction` node and add it to the document prolog at
+    /// `position`, the new node is returned. Nodes are added after any existing prolog nodes at
+    /// the same position.
+    ///
+    /// **Parameters**
+    ///
+    /// * `target` of type `DOMString`: The target part of the processing instruction.
+    /// * `data` of type `DOMString`: The data for the node.
+    /// * `position` of type `PrologPosition`: Where to place the node relative to the document
+    ///   type declaration.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified target contains an illegal character.
+    /// * `SYNTAX_ERR`: Raised if the specified data contains the string `"?>"`.
+    ///
+    fn add_prolog_pi(
+        &mut self,
+        target: &str,
+        data: Option<&str>,
+        position: PrologPosition,
+    ) -> Result<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with borrow-scoped accessors, each passes a
+/// value to a closure rather than returning a clone of it, for read paths where the cost of
+/// allocating a `String` for every call matters.
+///
+/// The node is borrowed while the closure runs, and so the closure **must not** modify the
+/// node, doing so will panic.
+///
+pub trait NodeBorrow: base::Node {
+    ///
+    /// Call `f` with the name of this node, as returned by `node_name`.
+    ///
+    fn with_name<R>(&self, f: impl FnOnce(&Name) -> R) -> R;
+    ///
+    /// Call `f` with the value of this node, as returned by `node_value`; for example the data
+    /// of a `Text`, `CDATASection`, or `Comment` node.
+    ///
+    fn with_value<R>(&self, f: impl FnOnce(Option<&str>) -> R) -> R;
+    ///
+    /// Call `f` with the value of the attribute `name` of this node, as returned by
+    /// `Element::get_attribute`; `f` is called with `None` if this node is not an element, or
+    /// does not have the attribute. The value is only copied if it must first be normalized or
+    /// escaped.
+    ///
+    fn with_attribute<R>(&self, name: &str, f: impl FnOnce(Option<&str>) -> R) -> R;
+}
+
+///
+/// This interface extends the DOM standard `Document` with the creation of unparsed markup
+/// nodes, a node type that is not defined by the DOM specification; see the
+/// [`unparsed`](unparsed/index.html) module.
+///
+pub trait DocumentUnparsed: base::Document {
+    ///
+    /// Creates an unparsed markup node, holding `markup` which will be written verbatim when the
+    /// node is serialized. The markup is not checked, and so the serialized document may not be
+    /// well-formed.
+    ///
+    /// **Parameters**
+    ///
+    /// * `markup` of type `DOMString`: The raw markup for the node.
+    ///
+    fn create_unparsed_markup(&self, markup: &str) -> Self::NodeRef;
+    ///
+    /// Create a new unparsed markup node and add it to the document prolog at `position`, the
+    /// new node is returned. Nodes are added after any existing prolog nodes at the same
+    /// position.
+    ///
+    /// **Parameters**
+    ///
+    /// * `markup` of type `DOMString`: The raw markup for the node.
+    /// * `position` of type `PrologPosition`: Where to place the node relative to the document
+    ///   type declaration.
+    ///
+    fn add_prolog_unparsed_markup(
+        &mut self,
+        markup: &str,
+        position: PrologPosition,
+    ) -> Result<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Document` with the ability to splice the content of
+/// an external document, or fragment, fetched by a [`UriResolver`](resolver/trait.UriResolver.html),
+/// into the document; see the [`resolver`](resolver/index.html) module.
+///
+pub trait DocumentImport: base::Document {
+    ///
+    /// Fetch the resource `uri` using `resolver`, and append a copy of its content to the
+    /// children of `at_node`, returning the copied nodes. The content of a document is all of its
+    /// children other than the document type; a fragment may contain any number of elements and
+    /// text. Namespace declarations are added to the copied elements where necessary, so that
+    /// their names keep the namespaces they had in the resource.
+    ///
+    /// **Parameters**
+    ///
+    /// * `uri` of type `DOMString`: The URI of the resource, interpreted by `resolver`.
+    /// * `at_node` of type `Node`: The `Element`, or `DocumentFragment`, owned by this document
+    ///   or this document itself, under which the content is added.
+    /// * `resolver` of type `UriResolver`: Used to fetch the resource.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if the resolver could not fetch the resource.
+    /// * `SYNTAX_ERR`: Raised if the resource could not be parsed.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `at_node` is not owned by this document.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if `at_node` may not have children of the type in the
+    ///   resource.
+    ///
+    fn import_from_uri(
+        &mut self,
+        uri: &str,
+        at_node: &mut Self::NodeRef,
+        resolver: &dyn UriResolver,
+    ) -> Result<Vec<Self::NodeRef>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `DocumentType` and allows the addition of the
+/// declarations that make up the document type's internal subset.
+///
+/// The DOM Level 2 specification does not support editing `DocumentType` nodes, however without
+/// some way to add declarations the `entities` and `notations` maps on the standard
+/// `DocumentType` would always be empty. Parameter entities and conditional sections are also
+/// represented, as are the attribute definitions of attribute-list declarations, see the
+/// [`dtd`](dtd/index.html) module.
+///
+/// # Specification
+///
+/// From XML 1.1 [§4.7 Notation Declarations](https://www.w3.org/TR/xml11/#Notations)
+/// -- Notations identify by name the format of unparsed entities, the format of elements which
+/// bear a notation attribute, or the application to which a processing instruction is addressed.
+///
+pub trait DocumentTypeDecl: base::DocumentType {
+    ///
+    /// Create a new `Notation` node and add it to the set of notations declared by this document
+    /// type, the new node is returned.
+    ///
+    /// **Parameters**
+    ///
+    /// * `name` of type `DOMString`: The name of the notation to declare.
+    /// * `public_id` of type `DOMString`: The public identifier of the notation.
+    /// * `system_id` of type `DOMString`: The system identifier of the notation.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified name contains an illegal character.
+    /// * `SYNTAX_ERR`: Raised if neither `public_id` or `system_id` are specified, or if a
+    ///   notation with the same name has already been declared.
+    ///
+    fn create_notation(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+    ) -> Result<Self::NodeRef>;
+
+    ///
+    /// Create a new internal general entity, `<!ENTITY name "value">`, and add it to the set of
+    /// entities declared by this document type, returned by `DocumentType::entities`; the new
+    /// node is returned.
+    ///
+    /// **Parameters**
+    ///
+    /// * `name` of type `DOMString`: The name of the entity to declare.
+    /// * `value` of type `DOMString`: The replacement text of the entity.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified name contains an illegal character.
+    /// * `SYNTAX_ERR`: Raised if an entity with the same name has already been declared.
+    ///
+    fn create_entity(&mut self, name: &str, value: &str) -> Result<Self::NodeRef>;
+
+    ///
+    /// Create a new external general entity, `<!ENTITY name SYSTEM "system_id">`, and add it to
+    /// the set of entities declared by this document type, the new node is returned.
+    ///
+    /// **Parameters**
+    ///
+    /// * `name` of type `DOMString`: The name of the entity to declare.
+    /// * `public_id` of type `DOMString`: The public identifier of the entity.
+    /// * `system_id` of type `DOMString`: The system identifier of the entity.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified name contains an illegal character.
+    /// * `SYNTAX_ERR`: Raised if an entity with the same name has already been declared.
+    ///
+    fn create_external_entity(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: &str,
+    ) -> Result<Self::NodeRef>;
+
+    ///
+    /// Create a new unparsed entity, `<!ENTITY name SYSTEM "system_id" NDATA notation_name>`,
+    /// and add it to the set of entities declared by this document type, the new node is
+    /// returned; the entity's `notation_name` is `notation_name`.
+    ///
+    /// **Parameters**
+    ///
+    /// * `name` of type `DOMString`: The name of the entity to declare.
+    /// * `public_id` of type `DOMString`: The public identifier of the entity.
+    /// * `system_id` of type `DOMString`: The system identifier of the entity.
+    /// * `notation_name` of type `DOMString`: The name of the notation of the entity's data,
+    ///   which must already be declared by this document type.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified name, or notation name, contains an
+    ///   illegal character.
+    /// * `NOT_FOUND_ERR`: Raised if the notation named `notation_name` has not been declared.
+    /// * `SYNTAX_ERR`: Raised if an entity with the same name has already been declared.
+    ///
+    fn create_unparsed_entity(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: &str,
+        notation_name: &str,
+    ) -> Result<Self::NodeRef>;
+
+    ///
+    /// A `HashMap` containing the parameter entities declared in the document type, these are
+    /// `Entity` nodes that are not included in the `entities` map as they may only be
+    /// referenced within the DTD itself.
+    ///
+    fn parameter_entities(&self) -> HashMap<Name, Self::NodeRef>;
+
+    ///
+    /// Create a new internal parameter entity, `<!ENTITY % name "value">`, and add it to the set
+    /// of parameter entities declared by this document type, the new node is returned.
+    ///
+    /// **Parameters**
+    ///
+    /// * `name` of type `DOMString`: The name of the parameter entity to declare.
+    /// * `value` of type `DOMString`: The replacement text of the parameter entity.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified name contains an illegal character.
+    /// * `SYNTAX_ERR`: Raised if a parameter entity with the same name has already been declared.
+    ///
+    fn create_parameter_entity(&mut self, name: &str, value: &str) -> Result<Self::NodeRef>;
+
+    ///
+    /// Create a new external parameter entity, `<!ENTITY % name SYSTEM "system_id">`, and add it
+    /// to the set of parameter entities declared by this document type, the new node is returned.
+    ///
+    /// **Parameters**
+    ///
+    /// * `name` of type `DOMString`: The name of the parameter entity to declare.
+    /// * `public_id` of type `DOMString`: The public identifier of the parameter entity.
+    /// * `system_id` of type `DOMString`: The system identifier of the parameter entity.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified name contains an illegal character.
+    /// * `SYNTAX_ERR`: Raised if a parameter entity with the same name has already been declared.
+    ///
+    fn create_external_parameter_entity(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: &str,
+    ) -> Result<Self::NodeRef>;
+
+    ///
+    /// The conditional sections of the document type's internal subset, in document order.
+    ///
+    fn conditional_sections(&self) -> Vec<ConditionalSection>;
+
+    ///
+    /// Add a conditional section to the end of the document type's internal subset.
+    ///
+    fn add_conditional_section(&mut self, section: ConditionalSection) -> Result<()>;
+
+    ///
+    /// The attribute definitions of the attribute-list declarations in the document type's
+    /// internal subset, in document order; declarations within conditional sections are not
+    /// included. Attributes defined with the type `ID` are recognized by
+    /// `Document::get_element_by_id`.
+    ///
+    fn attribute_definitions(&self) -> Vec<AttributeDefinition>;
+
+    ///
+    /// Add an attribute-list declaration, for the single attribute `definition`, to the end of
+    /// the document type's internal subset. If this is the first definition of the attribute for
+    /// its element type, and it has a default value, the default is added, as an attribute that is
+    /// not `specified`, to each element of that type in the owning document that does not already
+    /// have the attribute.
+    ///
+    fn add_attribute_definition(&mut self, definition: AttributeDefinition) -> Result<()>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -113,9 +502,42 @@ pub trait Namespaced: base::Element {
     ///
     /// Returns the namespace URI associated with the provided `prefix` for this element by looking
     /// up the DOM tree through `parent_node` links. Returns `None` if the prefix is not mapped to a
-    /// URI on this, or any parent, element.
+    /// URI on this, or any parent, element, or if the nearest mapping is an un-declaration.
     ///  
     fn resolve_namespace(&self, prefix: Option<&str>) -> Option<String>;
+    ///
+    /// Returns the namespace URI associated with the provided `prefix`, or the default namespace
+    /// if `None`, for this element; this corresponds to the DOM Level 3 `lookupNamespaceURI`
+    /// method. If the element's own name has the prefix its namespace URI is returned, otherwise
+    /// the `xmlns` attributes of this element are used, and then those of its ancestors in turn.
+    /// Unlike `resolve_namespace` this does not depend on the `add_namespaces` processing option.
+    ///
+    /// A declaration with an empty value, such as `xmlns=""`, un-declares the default namespace
+    /// for the element and its descendants and so `None` is returned.
+    ///
+    /// As in DOM Level 3 this, `lookup_prefix`, and `is_default_namespace` may be called on any
+    /// node; a document uses its document element, an attribute its owner element, and other
+    /// nodes their nearest ancestor element. Document types, document fragments, entities, and
+    /// notations have no namespace context and so `None` is returned.
+    ///
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String>;
+
+    ///
+    /// Returns a prefix associated with the provided `namespace_uri`, this corresponds to the
+    /// DOM Level 3 `lookupPrefix` method. The prefix of this element's own name is preferred,
+    /// then the prefixes declared by its `xmlns:` attributes, in order, and then those of its
+    /// ancestors in turn. A prefix is only returned if it is not re-declared, with a different
+    /// namespace, closer to this element. The default namespace is never returned as a prefix,
+    /// and `None` is returned for an empty `namespace_uri`.
+    ///
+    fn lookup_prefix(&self, namespace_uri: &str) -> Option<String>;
+
+    ///
+    /// Returns `true` if `namespace_uri` is the default namespace in scope at this element, this
+    /// corresponds to the DOM Level 3 `isDefaultNamespace` method. An empty `namespace_uri`
+    /// matches an element with no default namespace in scope.
+    ///
+    fn is_default_namespace(&self, namespace_uri: &str) -> bool;
 
     ///
     /// Returns `true` if this, and only this, element has a URI mapping for the provided
@@ -133,4 +555,598 @@ pub trait Namespaced: base::Element {
     /// with a prefix for this, or any parent, element.
     ///  
     fn resolve_prefix(&self, namespace_uri: &str) -> NamespacePrefix;
+
+    ///
+    /// Declare each of the `(prefix, namespace_uri)` pairs in `declarations` on this element, as
+    /// `xmlns:prefix` attributes, or `xmlns` where the prefix is `None`, in one call. The element's
+    /// namespace mappings are updated as for any other namespace attribute. A declaration that
+    /// this element, or an earlier pair, already makes is ignored. All declarations are checked
+    /// before any is added, so that on error the element is unchanged.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if a prefix is not a valid name.
+    /// * `NAMESPACE_ERR`: Raised if a prefix is already declared by this element, or an earlier
+    ///   pair, with a different namespace URI, or if a declaration uses a reserved prefix or
+    ///   namespace.
+    ///
+    fn declare_namespaces(&mut self, declarations: &[(Option<&str>, &str)]) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that stores binary content in the text of an element, so that elements
+/// carrying binary payloads do not require callers to manage the encoding and line wrapping of
+/// that content.
+///
+pub trait BinaryContent: base::Element {
+    ///
+    /// Returns the binary content of this element, decoded from the concatenation of its `Text`
+    /// and `CDATASection` children using `encoding`; any XML white space is ignored.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if the content is not valid for `encoding`.
+    ///
+    fn binary_content(&self, encoding: BinaryEncoding) -> Result<Vec<u8>>;
+    ///
+    /// Replace all children of this element with a single `Text` node containing `data` encoded
+    /// using `encoding`. If `data` is empty this element will have no children.
+    ///
+    fn set_binary_content(&mut self, data: &[u8], encoding: BinaryEncoding) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that converts the text content and attribute values of an element to
+/// and from values of the XML Schema built-in types, see the [`xsd`](xsd/index.html) module for
+/// the supported types.
+///
+/// The `get_attribute_xsd_*` methods are shorthand for
+/// [`get_attribute_xsd`](#tymethod.get_attribute_xsd) with each of the supported types.
+///
+pub trait XsdContent: base::Element {
+    ///
+    /// Returns `true` if this element has an `xsi:nil` attribute with the value `true`.
+    ///
+    fn is_nil(&self) -> bool;
+    ///
+    /// If `nil` is `true`, remove all children of this element and add the attribute
+    /// `xsi:nil="true"`, declaring the `xsi` prefix if it is not already in scope. If `nil` is
+    /// `false`, remove any `xsi:nil` attribute.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NAMESPACE_ERR`: Raised if the `xsi` prefix is in scope but bound to a different
+    ///   namespace.
+    ///
+    fn set_nil(&mut self, nil: bool) -> Result<()>;
+    ///
+    /// Returns the value of the concatenation of the `Text` and `CDATASection` children of this
+    /// element, or `None` if this element is nil.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if the content is not in the lexical space of `T`.
+    ///
+    fn text_xsd<T: XsdType>(&self) -> Result<Option<T>>;
+    ///
+    /// Replace all children of this element with a single `Text` node containing the canonical
+    /// representation of `value`; if this element is nil the `xsi:nil` attribute is removed.
+    ///
+    fn set_text_xsd<T: XsdType>(&mut self, value: &T) -> Result<()>;
+    ///
+    /// Returns the value of the attribute `name`, or `None` if this element has no such attribute.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if the attribute value is not in the lexical space of `T`.
+    ///
+    fn get_attribute_xsd<T: XsdType>(&self, name: &str) -> Result<Option<T>>;
+    ///
+    /// Set the attribute `name` to the canonical representation of `value`.
+    ///
+    fn set_attribute_xsd<T: XsdType>(&mut self, name: &str, value: &T) -> Result<()>;
+
+    ///
+    /// Returns the value of the attribute `name` as an `xs:dateTime`.
+    ///
+    fn get_attribute_xsd_datetime(&self, name: &str) -> Result<Option<XsdDateTime>> {
+        self.get_attribute_xsd(name)
+    }
+    ///
+    /// Returns the value of the attribute `name` as an `xs:date`.
+    ///
+    fn get_attribute_xsd_date(&self, name: &str) -> Result<Option<XsdDate>> {
+        self.get_attribute_xsd(name)
+    }
+    ///
+    /// Returns the value of the attribute `name` as an `xs:duration`.
+    ///
+    fn get_attribute_xsd_duration(&self, name: &str) -> Result<Option<XsdDuration>> {
+        self.get_attribute_xsd(name)
+    }
+    ///
+    /// Returns the value of the attribute `name` as an `xs:decimal`.
+    ///
+    fn get_attribute_xsd_decimal(&self, name: &str) -> Result<Option<XsdDecimal>> {
+        self.get_attribute_xsd(name)
+    }
+    ///
+    /// Returns the value of the attribute `name` as an `xs:boolean`.
+    ///
+    fn get_attribute_xsd_boolean(&self, name: &str) -> Result<Option<bool>> {
+        self.get_attribute_xsd(name)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that finds elements using CSS-style selectors such as
+/// `ns|chapter > title.heading`, see the [`selector`](selector/index.html) module for the
+/// supported syntax.
+///
+pub trait Selectable: base::Element {
+    ///
+    /// Returns all elements below this node that match `selector`, in document order. This node
+    /// may also be a `Document` or `DocumentFragment`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `selector` cannot be parsed.
+    /// * `INVALID_STATE_ERR`: Raised if this node is not an element, document, or fragment.
+    ///
+    fn select(&self, selector: &str) -> Result<Vec<Self::NodeRef>>;
+    ///
+    /// Returns the first element below this node, in document order, that matches `selector`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `selector` cannot be parsed.
+    /// * `INVALID_STATE_ERR`: Raised if this node is not an element, document, or fragment.
+    ///
+    fn select_first(&self, selector: &str) -> Result<Option<Self::NodeRef>>;
+    ///
+    /// Returns `true` if this element matches `selector`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `selector` cannot be parsed.
+    ///
+    fn matches_selector(&self, selector: &str) -> Result<bool>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that produces a structural summary of a document, a form of `head` for
+/// XML, see the [`outline`](outline/index.html) module for details.
+///
+pub trait DocumentOutline: base::Document {
+    ///
+    /// Returns an outline of the document element and its descendants, including at most `depth`
+    /// levels of elements below the document element. Returns `None` if this document has no
+    /// document element.
+    ///
+    fn outline(&self, depth: usize) -> Option<Outline>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that collects the links, URI valued attributes, in a document, see the
+/// [`links`](links/index.html) module for details.
+///
+pub trait DocumentLinks: base::Document {
+    ///
+    /// Returns the links found on the document element and its descendants, in document order,
+    /// with their values resolved against the base URI in effect for each element. The
+    /// attributes of an element are visited in order of their qualified names.
+    ///
+    fn extract_links(&self, config: &LinkConfig) -> Vec<Link>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that splits a document into a new, standalone, document for each
+/// element matched by a selector, see the [`split`](split/index.html) module for details.
+///
+pub trait DocumentSplit: base::Document {
+    ///
+    /// Returns a new document for each element matched by the selector `query`, in document
+    /// order, with a copy of the element as its document element.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `query` cannot be parsed as a selector.
+    ///
+    fn split_document(&self, query: &str, options: &SplitOptions) -> Result<Vec<Self::NodeRef>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that records the changes made to the attributes of an element, see the
+/// [`audit`](audit/index.html) module for details.
+///
+pub trait ElementAudit: base::Element {
+    ///
+    /// Start, or stop, recording changes to the attributes of this element. Stopping discards any
+    /// history already recorded.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if this node is not an element.
+    ///
+    fn set_audited(&mut self, audited: bool) -> Result<()>;
+
+    ///
+    /// Returns `true` if changes to the attributes of this element are being recorded.
+    ///
+    fn is_audited(&self) -> bool;
+
+    ///
+    /// Returns the recorded changes to the attribute with the qualified name `name`, oldest
+    /// first; the name is matched on prefix and local name.
+    ///
+    fn attribute_history(&self, name: &str) -> Vec<AttributeChange>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that identifies who is responsible for the attribute changes recorded on
+/// audited elements in a document, see the [`audit`](audit/index.html) module for details.
+///
+pub trait DocumentAudit: base::Document {
+    ///
+    /// Run `f`, recording `actor` against every attribute change made to an audited element of
+    /// this document while it runs. Transactions may be nested, the previous actor is restored
+    /// when `f` returns.
+    ///
+    fn audit_transaction<R>(&self, actor: &str, f: impl FnOnce() -> R) -> R;
+
+    ///
+    /// Returns the actor of the transaction currently running on this document, if any.
+    ///
+    fn audit_actor(&self) -> Option<String>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that provides the DOM Level 3 `normalizeDocument` method, see the
+/// [`normalize`](normalize/index.html) module for details.
+///
+pub trait DocumentNormalize: base::Document {
+    ///
+    /// Merge adjacent text nodes throughout this document, and then apply the parameters set in
+    /// `config`, as if the document had been saved and loaded.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if this node is not a document.
+    /// * `NAMESPACE_ERR`: Raised if a namespace declaration added for a name is not allowed.
+    ///
+    fn normalize_document(&mut self, config: &DOMConfiguration) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that attaches an application defined
+/// [`NodeExtension`](extension/trait.NodeExtension.html) to a node, see the
+/// [`extension`](extension/index.html) module for details.
+///
+pub trait NodeExtended: base::Node {
+    ///
+    /// Attach `extension` to this node, returning any extension it replaces.
+    ///
+    fn set_extension(
+        &mut self,
+        extension: Box<dyn NodeExtension>,
+    ) -> Option<Box<dyn NodeExtension>>;
+
+    ///
+    /// Remove, and return, the extension attached to this node.
+    ///
+    fn take_extension(&mut self) -> Option<Box<dyn NodeExtension>>;
+
+    ///
+    /// Returns `true` if an extension is attached to this node.
+    ///
+    fn has_extension(&self) -> bool;
+
+    ///
+    /// Call `f` with the extension attached to this node, returning its result, or `None` if no
+    /// extension is attached or it is not of type `T`.
+    ///
+    fn with_extension<T: NodeExtension + 'static, R>(&self, f: impl FnOnce(&T) -> R) -> Option<R>;
+
+    ///
+    /// Call `f` with the extension attached to this node, which it may modify, returning its
+    /// result, or `None` if no extension is attached or it is not of type `T`.
+    ///
+    fn with_extension_mut<T: NodeExtension + 'static, R>(
+        &mut self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<R>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that associates application values with a node, each under its own key,
+/// see the [`user_data`](user_data/index.html) module for details.
+///
+pub trait NodeUserData: base::Node {
+    ///
+    /// Associate `data` with `key` on this node, returning any value it replaces. If `handler`
+    /// is provided it is called when this node is cloned, imported, or renamed.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `setUserData` method.
+    ///
+    fn set_user_data(
+        &mut self,
+        key: &str,
+        data: Box<dyn Any>,
+        handler: Option<Rc<dyn UserDataHandler>>,
+    ) -> Option<Rc<dyn Any>>;
+
+    ///
+    /// Returns the value associated with `key` on this node, if any, which may be downcast to
+    /// its concrete type.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `getUserData` method.
+    ///
+    fn get_user_data(&self, key: &str) -> Option<Rc<dyn Any>>;
+
+    ///
+    /// Remove, and return, the value associated with `key` on this node; its handler, if any, is
+    /// also removed.
+    ///
+    fn remove_user_data(&mut self, key: &str) -> Option<Rc<dyn Any>>;
+
+    ///
+    /// Returns the keys of all values associated with this node, sorted.
+    ///
+    fn user_data_keys(&self) -> Vec<String>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that provides the DOM Level 3 `renameNode` method, see the
+/// [`user_data`](user_data/index.html) module for details of the handlers it calls.
+///
+pub trait DocumentRename: base::Document {
+    ///
+    /// Rename the element or attribute `node`, owned by this document, in place, returning it.
+    /// An attribute is removed from, and added back to, its owner element so that namespace
+    /// declarations and audit history are maintained. The
+    /// [`UserDataOperation::Renamed`](user_data/enum.UserDataOperation.html) handlers of the node
+    /// are then called.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if this node is not a document.
+    /// * `NOT_SUPPORTED_ERR`: Raised if `node` is not an element or attribute.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `node` was created from a different document.
+    /// * `INVALID_CHARACTER_ERR`: Raised if `qualified_name` is not a valid name.
+    /// * `NAMESPACE_ERR`: Raised if `qualified_name` is malformed, or has a prefix that is not
+    ///   allowed with `namespace_uri`.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `renameNode` method, although DOM Level 3 also allows
+    /// an implementation to return a new node rather than renaming `node` in place.
+    ///
+    fn rename_node(
+        &self,
+        node: Self::NodeRef,
+        namespace_uri: Option<&str>,
+        qualified_name: &str,
+    ) -> Result<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that provides the DOM Level 3 `setIdAttribute`, `setIdAttributeNS`, and
+/// `setIdAttributeNode` methods, which mark a specific attribute of an element as an ID attribute
+/// for `Document::get_element_by_id`, in documents without a document type declaring it.
+///
+pub trait ElementId: base::Element {
+    ///
+    /// If `is_id` is `true` mark the attribute named `name` as an ID attribute of this element,
+    /// otherwise remove any such mark. An attribute that is an ID attribute by its name, or by
+    /// its definition in the document type, remains one regardless of this mark.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if the specified node is not an attribute of this element.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `setIdAttribute` method.
+    ///
+    fn set_id_attribute(&mut self, name: &str, is_id: bool) -> Result<()>;
+
+    ///
+    /// As [`set_id_attribute`](#tymethod.set_id_attribute), for the attribute identified by
+    /// `namespace_uri` and `local_name`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if the specified node is not an attribute of this element.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `setIdAttributeNS` method.
+    ///
+    fn set_id_attribute_ns(
+        &mut self,
+        namespace_uri: &str,
+        local_name: &str,
+        is_id: bool,
+    ) -> Result<()>;
+
+    ///
+    /// As [`set_id_attribute`](#tymethod.set_id_attribute), for the attribute node
+    /// `id_attribute`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if the specified node is not an attribute of this element.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `setIdAttributeNode` method.
+    ///
+    fn set_id_attribute_node(&mut self, id_attribute: Self::NodeRef, is_id: bool) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that provides the DOM Level 3 `isId` attribute.
+///
+pub trait AttributeId: base::Attribute {
+    ///
+    /// Returns `true` if this attribute is an ID attribute of its owner element; because it was
+    /// marked as one with [`ElementId`](trait.ElementId.html), because of its name, such as
+    /// `xml:id`, or because of its definition in the document type.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `isId` attribute.
+    ///
+    fn is_id(&self) -> bool;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that looks up the entities and notations declared by the document type
+/// of a document, by name, without copying the maps returned by `DocumentType::entities` and
+/// `DocumentType::notations`.
+///
+pub trait DocumentEntities: base::Document {
+    ///
+    /// Returns the general entity named `name` declared by this document's type, if any.
+    /// Parameter entities are not included, see
+    /// [`DocumentTypeDecl::parameter_entities`](trait.DocumentTypeDecl.html#tymethod.parameter_entities).
+    ///
+    fn entity(&self, name: &str) -> Option<Self::NodeRef>;
+
+    ///
+    /// Returns the notation named `name` declared by this document's type, if any.
+    ///
+    fn notation(&self, name: &str) -> Option<Self::NodeRef>;
+
+    ///
+    /// Returns `true` if this document's type declares a general entity named `name`, and so an
+    /// entity reference with that name can be expanded. The predefined entities, such as `amp`,
+    /// are not included unless they are also declared.
+    ///
+    fn has_entity(&self, name: &str) -> bool;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that classifies white space only text, so that it may be ignored when
+/// writing or normalizing a document.
+///
+pub trait TextWhitespace: base::Text {
+    ///
+    /// Returns `true` if this text node contains only white space, and is in element content; that
+    /// is the content of an element whose declaration in the internal subset of the document type
+    /// allows only child elements. Where the element is not declared, the text is considered to be
+    /// in element content if the element also contains elements, and is not within an
+    /// `xml:space="preserve"` region. A CDATA section is never element content white space.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `isElementContentWhitespace` attribute.
+    ///
+    fn is_element_content_whitespace(&self) -> bool;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that checks that a node, and its subtree, belongs to a document; see
+/// also the [`ProcessingOptions`](options/struct.ProcessingOptions.html) `strict_owner_document`
+/// and `auto_import` options, which apply this check when nodes are inserted.
+///
+pub trait NodeOwner: base::Node {
+    ///
+    /// Returns `Ok(())` if this node, each of its attributes, and each node in its subtree, is
+    /// owned by `document`; a document is its own owner.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if any node is owned by a different document, or has no
+    ///   owner document.
+    ///
+    fn verify_owner(&self, document: &Self::NodeRef) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that returns the text of an element with its entity references
+/// expanded, without changing the tree. The [`parser`](../../../parser/index.html) constructs a
+/// reference to a declared entity as an `EntityReference` node, this returns the text that a
+/// parser replacing each reference with the entity's replacement text would produce.
+///
+pub trait ElementText: base::Element {
+    ///
+    /// Returns the concatenated text of the text and CDATA section descendants of this element, in
+    /// document order. Each entity reference is replaced by its children or, where it has none, by
+    /// the children or replacement text of the entity of the same name declared by the document
+    /// type; references within replacement text, to declared entities and to characters, are
+    /// expanded in turn. References to undeclared, or recursive, entities are left as written.
+    /// Comments and processing instructions are not included.
+    ///
+    fn normalized_text(&self) -> String;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that returns live lists of nodes, which reflect the tree as it is when
+/// each is accessed, rather than the snapshots returned by `child_nodes`,
+/// `get_elements_by_tag_name`, and `attributes`; see the [`node_list`](node_list/index.html) and
+/// [`named_node_map`](named_node_map/index.html) modules.
+///
+pub trait NodeLists: base::Node {
+    ///
+    /// Returns a live list of the children of this node.
+    ///
+    fn live_child_nodes(&self) -> NodeList;
+    ///
+    /// Returns a live list of the elements that `get_elements_by_tag_name` would return, for an
+    /// element or a document; for any other node the list is always empty.
+    ///
+    fn live_elements_by_tag_name(&self, tag_name: &str) -> NodeList;
+    ///
+    /// Returns a live list of the elements that `get_elements_by_tag_name_ns` would return, for an
+    /// element or a document; for any other node the list is always empty.
+    ///
+    fn live_elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> NodeList;
+    ///
+    /// Returns a live map of the attributes of an element, in the order they were added; for any
+    /// other node the map is always empty.
+    ///
+    fn live_attributes(&self) -> NamedNodeMap;
 }
@@ -0,0 +1,432 @@
+/*!
+Provides [`TreeBuilder`](struct.TreeBuilder.html), the construction path used by the
+[`parser`](../../../parser/index.html), so that other front-ends, for example reading documents
+described by a schema language or stored as database rows, can construct a DOM from a sequence of
+events without first producing, and then parsing, XML text.
+
+Events are pushed to the builder in document order and [`finish`](struct.TreeBuilder.html#method.finish)
+returns the completed document. Names, and the placement of each node in the tree, are always
+checked by the DOM as each node is created; in addition, and unless it is turned off with
+[`set_validate`](struct.TreeBuilder.html#method.set_validate), the builder checks the sequence of
+events against the following rules.
+
+1. The XML declaration is the first event.
+1. At most one document type declaration is received, before the document element.
+1. Each `end_element` names the element it ends.
+1. All elements are ended, and the document has a document element, when it is finished.
+
+Without validation the XML declaration and document type may be set at any time, `end_element`
+ends the innermost open element regardless of its name, and `finish` ends any open elements and
+returns the document even if it has no document element.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::tree_builder::TreeBuilder;
+
+let rows = [("1", "pen"), ("2", "ink")];
+
+let mut builder = TreeBuilder::new();
+builder.start_element("order", &[]).unwrap();
+for (id, name) in rows.iter() {
+    builder.start_element("item", &[("id", id)]).unwrap();
+    builder.text(name).unwrap();
+    builder.end_element("item").unwrap();
+}
+builder.end_element("order").unwrap();
+let document_node = builder.finish().unwrap();
+
+assert_eq!(
+    document_node.to_string(),
+    r#"<order><item id="1">pen</item><item id="2">ink</item></order>"#
+);
+```
+*/
+
+use crate::level2::dom_impl::{get_implementation, Implementation};
+use crate::level2::ext::decl::XmlDecl;
+use crate::level2::ext::dtd::{adopt_document_type, set_internal_subset};
+use crate::level2::ext::traits::{DocumentDecl, DocumentUnparsed};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::traits::*;
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_EXTENSION, MSG_MISPLACED_CONTENT, MSG_NO_DOCUMENT_ELEMENT,
+    MSG_UNCLOSED_ELEMENTS, MSG_UNMATCHED_END_ELEMENT,
+};
+use crate::shared::name::Name;
+use crate::shared::text::is_xml_space;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Constructs a document from a sequence of events, see the [module](index.html) documentation.
+///
+#[derive(Clone, Debug)]
+pub struct TreeBuilder {
+    document: RefNode,
+    open_elements: Vec<RefNode>,
+    validate: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for TreeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreeBuilder {
+    ///
+    /// Construct a new, validating, builder with an empty document created by the default
+    /// implementation.
+    ///
+    pub fn new() -> Self {
+        Self::from_document(
+            get_implementation()
+                .create_document(None, None, None)
+                .unwrap(),
+        )
+    }
+
+    ///
+    /// Construct a new, validating, builder with an empty document created by `implementation`,
+    /// and so with its processing options, ID attributes, and resolver.
+    ///
+    pub fn with_implementation(implementation: &Implementation) -> Result<Self> {
+        Ok(Self::from_document(
+            implementation.create_document(None, None, None)?,
+        ))
+    }
+
+    ///
+    /// Turn the checks on the sequence of events, described in the [module](index.html)
+    /// documentation, on or off; they are on by default.
+    ///
+    pub fn set_validate(&mut self, validate: bool) {
+        self.validate = validate;
+    }
+
+    ///
+    /// Returns `true` if the sequence of events is checked.
+    ///
+    pub fn is_validating(&self) -> bool {
+        self.validate
+    }
+
+    ///
+    /// Returns the number of elements that have been started but not yet ended.
+    ///
+    pub fn depth(&self) -> usize {
+        self.open_elements.len()
+    }
+
+    ///
+    /// Set the XML declaration of the document.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised, when validating, if any other event has been received.
+    ///
+    pub fn xml_declaration(&mut self, xml_decl: XmlDecl) -> Result<()> {
+        if self.validate
+            && (self.document.has_child_nodes()
+                || self.document.doc_type().is_some()
+                || self.document.xml_declaration().is_some())
+        {
+            warn!("{}", MSG_MISPLACED_CONTENT);
+            return Err(Error::Syntax);
+        }
+        self.document.set_xml_declaration(xml_decl)
+    }
+
+    ///
+    /// Set the document type of the document; the internal subset is parsed as by the parser, and
+    /// any comments and processing instructions already received are placed before it in the
+    /// document prolog.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if `name` is not a valid name.
+    /// * `SYNTAX_ERR`: Raised, when validating, if a document type has already been set, or the
+    ///   document element has been started.
+    ///
+    pub fn document_type(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+        internal_subset: Option<&str>,
+    ) -> Result<()> {
+        let children = self.document.child_nodes();
+        if self.validate
+            && (self.document.doc_type().is_some()
+                || children
+                    .iter()
+                    .any(|child| child.node_type() == NodeType::Element))
+        {
+            warn!("{}", MSG_MISPLACED_CONTENT);
+            return Err(Error::Syntax);
+        }
+        let doc_type = get_implementation().create_document_type(name, public_id, system_id)?;
+        set_internal_subset(&doc_type, internal_subset);
+        adopt_document_type(&self.document, &doc_type);
+        let mut mut_document = self.document.borrow_mut();
+        if let Extension::Document {
+            i_document_type,
+            i_before_doc_type,
+            ..
+        } = &mut mut_document.i_extension
+        {
+            *i_document_type = Some(doc_type);
+            *i_before_doc_type = children
+                .into_iter()
+                .map(|child| child.downgrade())
+                .collect();
+            Ok(())
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+
+    ///
+    /// Start a new element, named by the qualified name `name`, with the attributes `attributes`
+    /// given as qualified name and unescaped value pairs; namespace declarations are included as
+    /// attributes. The element is added to the element most recently started and not yet ended,
+    /// or to the document.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the element, or an attribute, name is not valid.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if the document already has a document element.
+    ///
+    pub fn start_element(&mut self, name: &str, attributes: &[(&str, &str)]) -> Result<()> {
+        let element = self.document.create_element(name)?;
+        let attributes = attributes
+            .iter()
+            .map(|(name, value)| self.document.create_attribute_with(name, value))
+            .collect::<Result<Vec<RefNode>>>()?;
+        self.start(element, attributes)
+    }
+
+    ///
+    /// As [`start_element`](#method.start_element), but an element or attribute whose name has
+    /// a namespace URI is created with that namespace, as by `create_element_ns`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the element, or an attribute, name is not valid.
+    /// * `NAMESPACE_ERR`: Raised if a name is not valid for its namespace URI.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if the document already has a document element.
+    ///
+    pub fn start_element_ns(&mut self, name: &Name, attributes: &[(Name, String)]) -> Result<()> {
+        let element = match name.namespace_uri() {
+            Some(namespace_uri) => self
+                .document
+                .create_element_ns(namespace_uri, &name.to_string())?,
+            None => self.document.create_element(&name.to_string())?,
+        };
+        let mut attribute_nodes = Vec::with_capacity(attributes.len());
+        for (name, value) in attributes {
+            attribute_nodes.push(match name.namespace_uri() {
+                Some(namespace_uri) => {
+                    let mut attribute = self
+                        .document
+                        .create_attribute_ns(namespace_uri, &name.to_string())?;
+                    attribute.set_value(value)?;
+                    attribute
+                }
+                None => self
+                    .document
+                    .create_attribute_with(&name.to_string(), value)?,
+            });
+        }
+        self.start(element, attribute_nodes)
+    }
+
+    ///
+    /// End the element most recently started and not yet ended, which should be named `name`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if there is no open element, or, when validating, if the
+    ///   open element is not named `name`.
+    ///
+    pub fn end_element(&mut self, name: &str) -> Result<()> {
+        match self.open_elements.last() {
+            Some(element) if !self.validate || element.node_name().to_string() == name => {
+                let _safe_to_ignore = self.open_elements.pop();
+                Ok(())
+            }
+            _ => {
+                warn!("{}: '{}'", MSG_UNMATCHED_END_ELEMENT, name);
+                Err(Error::InvalidState)
+            }
+        }
+    }
+
+    ///
+    /// Add a text node, containing the unescaped `text`, to the open element. White space outside
+    /// the document element is ignored.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `text` is not white space, and there is no open element.
+    ///
+    pub fn text(&mut self, text: &str) -> Result<()> {
+        if self.open_elements.is_empty() {
+            if text.chars().all(is_xml_space) {
+                return Ok(());
+            }
+            warn!("{}", MSG_MISPLACED_CONTENT);
+            return Err(Error::Syntax);
+        }
+        let text = self.document.create_text_node(text);
+        self.append(text).map(|_| ())
+    }
+
+    ///
+    /// Add a CDATA section, containing `text`, to the open element.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if there is no open element.
+    ///
+    pub fn cdata_section(&mut self, text: &str) -> Result<()> {
+        if self.open_elements.is_empty() {
+            warn!("{}", MSG_MISPLACED_CONTENT);
+            return Err(Error::Syntax);
+        }
+        let cdata = self.document.create_cdata_section(text)?;
+        self.append(cdata).map(|_| ())
+    }
+
+    ///
+    /// Add a reference to the general entity `name` to the open element; the reference has a
+    /// copy of the children of the entity, if it is declared by the document type.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if `name` is not a valid name.
+    /// * `SYNTAX_ERR`: Raised if there is no open element.
+    ///
+    pub fn entity_reference(&mut self, name: &str) -> Result<()> {
+        if self.open_elements.is_empty() {
+            warn!("{}", MSG_MISPLACED_CONTENT);
+            return Err(Error::Syntax);
+        }
+        let entity_reference = self.document.create_entity_reference(name)?;
+        self.append(entity_reference).map(|_| ())
+    }
+
+    ///
+    /// Add an unparsed markup node to the open element, or to the document; see the
+    /// [`unparsed`](../unparsed/index.html) module.
+    ///
+    pub fn unparsed_markup(&mut self, markup: &str) -> Result<()> {
+        let unparsed = self.document.create_unparsed_markup(markup);
+        self.append(unparsed).map(|_| ())
+    }
+
+    ///
+    /// Add a comment to the open element, or to the document.
+    ///
+    pub fn comment(&mut self, text: &str) -> Result<()> {
+        let comment = self.document.create_comment(text);
+        self.append(comment).map(|_| ())
+    }
+
+    ///
+    /// Add a processing instruction to the open element, or to the document.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if `target` is not a valid name.
+    ///
+    pub fn processing_instruction(&mut self, target: &str, data: Option<&str>) -> Result<()> {
+        let pi = self.document.create_processing_instruction(target, data)?;
+        self.append(pi).map(|_| ())
+    }
+
+    ///
+    /// Complete the document; the node returned is a `Document` node.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised, when validating, if any element has been started but not
+    ///   ended.
+    /// * `SYNTAX_ERR`: Raised, when validating, if the document has no document element.
+    ///
+    pub fn finish(self) -> Result<RefNode> {
+        if self.validate {
+            if !self.open_elements.is_empty() {
+                warn!("{}", MSG_UNCLOSED_ELEMENTS);
+                return Err(Error::InvalidState);
+            }
+            if self.document.document_element().is_none() {
+                warn!("{}", MSG_NO_DOCUMENT_ELEMENT);
+                return Err(Error::Syntax);
+            }
+        }
+        Ok(self.document)
+    }
+
+    ///
+    /// The document being constructed.
+    ///
+    #[cfg(feature = "quick_parser")]
+    pub(crate) fn document(&self) -> &RefNode {
+        &self.document
+    }
+
+    ///
+    /// Construct a new, validating, builder that adds content to `element`, which remains open
+    /// until the builder is dropped.
+    ///
+    #[cfg(feature = "quick_parser")]
+    pub(crate) fn within(element: &RefNode) -> Result<Self> {
+        let document = element.owner_document().ok_or(Error::InvalidState)?;
+        let mut builder = Self::from_document(document);
+        builder.open_elements.push(element.clone());
+        Ok(builder)
+    }
+
+    ///
+    /// The innermost element that has been started but not yet ended.
+    ///
+    #[cfg(feature = "quick_parser")]
+    pub(crate) fn open_element(&self) -> Option<&RefNode> {
+        self.open_elements.last()
+    }
+
+    fn from_document(document: RefNode) -> Self {
+        Self {
+            document,
+            open_elements: Default::default(),
+            validate: true,
+        }
+    }
+
+    fn start(&mut self, element: RefNode, attributes: Vec<RefNode>) -> Result<()> {
+        let mut element = self.append(element)?;
+        for attribute in attributes {
+            let _safe_to_ignore = element.set_attribute_node(attribute)?;
+        }
+        self.open_elements.push(element);
+        Ok(())
+    }
+
+    fn append(&mut self, new_child: RefNode) -> Result<RefNode> {
+        match self.open_elements.last_mut() {
+            Some(parent) => parent.append_child(new_child),
+            None => self.document.append_child(new_child),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+/*!
+This module provides support for unparsed markup nodes, created by the
+[`DocumentUnparsed`](../trait.DocumentUnparsed.html) trait.
+
+An unparsed markup node is an extension, its node type `NodeType::UnparsedMarkup` is not defined
+by the DOM specification. The node holds a raw string that is written verbatim when the node is
+serialized, so that constructs the model does not represent, such as conditional sections or
+unusual declarations, can be carried through a round trip rather than dropped. The node's name
+is `#unparsed-markup`, its value is the raw markup, and it has no children.
+
+Unparsed markup may be added anywhere a comment may be added, including the document prolog. It
+is written by `Display`, the [`serializer`](../serializer/index.html), and the
+[`normalize`](../normalize/index.html) module, and reported to a SAX `ContentHandler` by its
+`unparsed_markup` method; it is not written in canonical form. The markup is never checked, it is
+the responsibility of the client to ensure that the serialized document is well-formed.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::ext::decl::PrologPosition;
+use xml_dom::level2::ext::unparsed::is_unparsed_markup;
+
+let implementation = get_implementation();
+let mut document_node = implementation.create_document(None, Some("doc"), None).unwrap();
+let unparsed_node = document_node
+    .add_prolog_unparsed_markup("<!-- generated -->", PrologPosition::BeforeDocType)
+    .unwrap();
+assert!(is_unparsed_markup(&unparsed_node));
+
+let mut root_node = convert::as_document(&document_node).unwrap().document_element().unwrap();
+let _safe_to_ignore = root_node
+    .append_child(document_node.create_unparsed_markup("<![IGNORE[ <x/> ]]>"))
+    .unwrap();
+
+assert_eq!(
+    document_node.to_string(),
+    "<!-- generated --><doc><![IGNORE[ <x/> ]]></doc>"
+);
+```
+*/
+
+use crate::level2::ext::decl::PrologPosition;
+use crate::level2::ext::trait_impls::add_prolog_node;
+use crate::level2::ext::traits::DocumentUnparsed;
+use crate::level2::node_impl::{NodeImpl, RefNode};
+use crate::level2::traits::{Node, NodeType};
+use crate::shared::error::Result;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns `true` if `node` is an unparsed markup node, else `false`.
+///
+pub fn is_unparsed_markup(node: &RefNode) -> bool {
+    node.node_type() == NodeType::UnparsedMarkup
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentUnparsed for RefNode {
+    fn create_unparsed_markup(&self, markup: &str) -> Self::NodeRef {
+        let node_impl = NodeImpl::new_unparsed_markup(self.clone().downgrade(), markup);
+        RefNode::new(node_impl)
+    }
+
+    fn add_prolog_unparsed_markup(
+        &mut self,
+        markup: &str,
+        position: PrologPosition,
+    ) -> Result<Self::NodeRef> {
+        let unparsed_node = self.create_unparsed_markup(markup);
+        add_prolog_node(self, unparsed_node, position)
+    }
+}
@@ -0,0 +1,308 @@
+/*!
+Provides the [`NodeUserData`](../trait.NodeUserData.html) extension, corresponding to the DOM
+Level 3 `setUserData` and `getUserData` methods, which associate any number of application values
+with a node, each under its own key; and the [`DocumentRename`](../trait.DocumentRename.html)
+extension, corresponding to the DOM Level 3 `renameNode` method.
+
+Unlike a [`NodeExtension`](../extension/trait.NodeExtension.html), user data is never copied with
+its node. Instead a [`UserDataHandler`](trait.UserDataHandler.html) may be registered with each
+value and is called, with the source and destination nodes, when the node is cloned by
+`clone_node`, imported by `Workspace::import_node`, or renamed by `rename_node`; the handler may
+then set whatever data is appropriate on the destination node. Handlers are called for each node
+in a deep copy, in document order, and for each key of a node in key order.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::NodeUserData;
+use xml_dom::level2::ext::user_data::UserDataOperation;
+use std::any::Any;
+use std::rc::Rc;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("order"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+
+let copy_line = Rc::new(
+    |operation: UserDataOperation,
+     key: &str,
+     data: &Rc<dyn Any>,
+     _: &RefNode,
+     destination: &RefNode| {
+        if operation == UserDataOperation::Cloned {
+            let line = *data.downcast_ref::<usize>().unwrap();
+            let _previous = destination.clone().set_user_data(key, Box::new(line), None);
+        }
+    },
+);
+let _previous = root_node.set_user_data("line", Box::new(12_usize), Some(copy_line));
+let _previous = root_node.set_user_data("selected", Box::new(true), None);
+
+let copy_node = root_node.clone_node(false).unwrap();
+let line = copy_node.get_user_data("line").unwrap();
+assert_eq!(line.downcast_ref::<usize>(), Some(&12));
+assert!(copy_node.get_user_data("selected").is_none());
+```
+*/
+
+use crate::level2::convert::as_attribute;
+use crate::level2::ext::namespaced::check_reserved_namespace;
+use crate::level2::ext::traits::{DocumentRename, NodeUserData};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::trait_impls::ordered_attributes;
+use crate::level2::traits::*;
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE, MSG_WRONG_DOCUMENT};
+use crate::shared::name::Name;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::rc::Rc;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The operation that caused a [`UserDataHandler`](trait.UserDataHandler.html) to be called.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserDataOperation {
+    /// The node was copied by `clone_node`.
+    Cloned,
+    /// The node was copied into another document by `Workspace::import_node`.
+    Imported,
+    /// The node was renamed by `rename_node`, the source and destination are the same node.
+    Renamed,
+}
+
+///
+/// Called when a node with user data is copied or renamed, corresponding to the DOM Level 3
+/// `UserDataHandler` interface; this trait is implemented for any closure with the same
+/// signature as `handle`.
+///
+pub trait UserDataHandler {
+    ///
+    /// Called with the `operation` performed, the `key` and `data` registered with this handler,
+    /// the node that holds the data, and the node that resulted from the operation. The source
+    /// node is not borrowed while the handler runs.
+    ///
+    fn handle(
+        &self,
+        operation: UserDataOperation,
+        key: &str,
+        data: &Rc<dyn Any>,
+        source: &RefNode,
+        destination: &RefNode,
+    );
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<F> UserDataHandler for F
+where
+    F: Fn(UserDataOperation, &str, &Rc<dyn Any>, &RefNode, &RefNode),
+{
+    fn handle(
+        &self,
+        operation: UserDataOperation,
+        key: &str,
+        data: &Rc<dyn Any>,
+        source: &RefNode,
+        destination: &RefNode,
+    ) {
+        self(operation, key, data, source, destination)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodeUserData for RefNode {
+    fn set_user_data(
+        &mut self,
+        key: &str,
+        data: Box<dyn Any>,
+        handler: Option<Rc<dyn UserDataHandler>>,
+    ) -> Option<Rc<dyn Any>> {
+        self.borrow_mut()
+            .i_user_data
+            .0
+            .insert(key.to_string(), (Rc::from(data), handler))
+            .map(|(previous, _)| previous)
+    }
+
+    fn get_user_data(&self, key: &str) -> Option<Rc<dyn Any>> {
+        self.borrow()
+            .i_user_data
+            .0
+            .get(key)
+            .map(|(data, _)| data.clone())
+    }
+
+    fn remove_user_data(&mut self, key: &str) -> Option<Rc<dyn Any>> {
+        self.borrow_mut()
+            .i_user_data
+            .0
+            .remove(key)
+            .map(|(data, _)| data)
+    }
+
+    fn user_data_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.borrow().i_user_data.0.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentRename for RefNode {
+    fn rename_node(
+        &self,
+        node: Self::NodeRef,
+        namespace_uri: Option<&str>,
+        qualified_name: &str,
+    ) -> Result<Self::NodeRef> {
+        if self.node_type() != NodeType::Document {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let node_type = node.node_type();
+        if node_type != NodeType::Element && node_type != NodeType::Attribute {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::NotSupported);
+        }
+        if node.owner_document().as_ref() != Some(self) {
+            warn!("{}", MSG_WRONG_DOCUMENT);
+            return Err(Error::WrongDocument);
+        }
+        let name = match namespace_uri {
+            Some(namespace_uri) => Name::new_ns(namespace_uri, qualified_name)?,
+            None => Name::from_str(qualified_name)?,
+        };
+        if node_type == NodeType::Element {
+            check_reserved_namespace(
+                &node,
+                name.prefix().as_deref(),
+                namespace_uri.unwrap_or_default(),
+            )?;
+            node.borrow_mut().i_name = name;
+        } else {
+            match as_attribute(&node)?.owner_element() {
+                Some(mut element) => {
+                    let previous = node.node_name();
+                    let position = ordered_attributes(&element)
+                        .iter()
+                        .position(|(attribute_name, _)| *attribute_name == previous);
+                    let _safe_to_ignore = element.remove_attribute_node(node.clone())?;
+                    node.borrow_mut().i_name = name;
+                    let result = element.set_attribute_node(node.clone());
+                    if result.is_err() {
+                        node.borrow_mut().i_name = previous;
+                        let _safe_to_ignore = element.set_attribute_node(node.clone())?;
+                    }
+                    //
+                    // The renamed attribute keeps its position in the attribute order.
+                    //
+                    if let (
+                        Some(position),
+                        Extension::Element {
+                            i_attribute_order, ..
+                        },
+                    ) = (position, &mut element.borrow_mut().i_extension)
+                    {
+                        if let Some(attribute_name) = i_attribute_order.pop() {
+                            i_attribute_order.insert(position, attribute_name);
+                        }
+                    }
+                    let _safe_to_ignore = result?;
+                }
+                None => node.borrow_mut().i_name = name,
+            }
+        }
+        notify_user_data(UserDataOperation::Renamed, &node, &node);
+        Ok(node)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Clone for UserData {
+    fn clone(&self) -> Self {
+        Default::default()
+    }
+}
+
+impl Debug for UserData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The user data of a node, keyed by name, with the handler registered for each value. Cloning
+/// the map produces an empty map, as user data is never copied with its node.
+///
+#[derive(Default)]
+pub(crate) struct UserData(HashMap<String, UserDataEntry>);
+
+///
+/// A value, and the handler registered with it, if any.
+///
+type UserDataEntry = (Rc<dyn Any>, Option<Rc<dyn UserDataHandler>>);
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Call the handler registered with each value of the user data of `source`, in key order.
+///
+pub(crate) fn notify_user_data(
+    operation: UserDataOperation,
+    source: &RefNode,
+    destination: &RefNode,
+) {
+    let mut handled: Vec<(String, UserDataEntry)> = source
+        .borrow()
+        .i_user_data
+        .0
+        .iter()
+        .filter(|(_, (_, handler))| handler.is_some())
+        .map(|(key, entry)| (key.clone(), entry.clone()))
+        .collect();
+    handled.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+    for (key, (data, handler)) in handled {
+        if let Some(handler) = handler {
+            handler.handle(operation, &key, &data, source, destination);
+        }
+    }
+}
+
+///
+/// Call the handlers of `source`, and of each of its descendants paired with those of the deep
+/// copy `destination`.
+///
+pub(crate) fn notify_user_data_deep(
+    operation: UserDataOperation,
+    source: &RefNode,
+    destination: &RefNode,
+) {
+    notify_user_data(operation, source, destination);
+    for (source_child, destination_child) in source
+        .child_nodes()
+        .iter()
+        .zip(destination.child_nodes().iter())
+    {
+        notify_user_data_deep(operation, source_child, destination_child);
+    }
+}
@@ -0,0 +1,609 @@
+/*!
+Provides [`Workspace`](struct.Workspace.html), a container for a set of related documents, for
+example a main document together with the documents it includes and the schemas it references.
+
+Each document in a workspace is identified by its system identifier, usually the URI it was read
+from; the first document added is the *main* document unless another is chosen with
+[`set_main`](struct.Workspace.html#method.set_main). A workspace brings together the state that
+tools working with many documents would otherwise have to manage themselves:
+
+* **Creation** all documents created by the workspace share the same
+  [`ProcessingOptions`](../options/struct.ProcessingOptions.html).
+* **Import** nodes may be copied from any document into any other with
+  [`import_node`](struct.Workspace.html#method.import_node), which creates new nodes owned by the
+  target document so that they may be inserted into it.
+* **Names** element and attribute names created or imported through the workspace are parsed and
+  validated once, and then taken from a single table shared by all of its documents.
+* **Resolution** a single configuration maps public identifiers, and system identifiers relative
+  to the referring document, onto the documents in the workspace, see
+  [`resolve`](struct.Workspace.html#method.resolve).
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::Workspace;
+
+let mut workspace = Workspace::new();
+let main_node = workspace
+    .create_document("http://example.com/docs/main.xml", None, Some("book"))
+    .unwrap();
+let chapter_node = workspace
+    .create_document("http://example.com/docs/chapters/one.xml", None, Some("chapter"))
+    .unwrap();
+
+// find the included document relative to the main one, and copy its content across.
+let included = workspace
+    .resolve(None, Some("chapters/one.xml"), Some("http://example.com/docs/main.xml"))
+    .cloned()
+    .unwrap();
+assert_eq!(included, chapter_node);
+
+let chapter = as_document(&included).unwrap().document_element().unwrap();
+let mut book = as_document(&main_node).unwrap().document_element().unwrap();
+let imported = workspace.append_imported(&mut book, &chapter, true).unwrap();
+assert_eq!(imported.owner_document(), Some(main_node));
+assert_eq!(workspace.system_id_of(&imported), Some("http://example.com/docs/main.xml"));
+```
+*/
+
+use crate::level2::convert::{
+    as_attribute, as_document, as_document_type, as_entity, as_notation, as_processing_instruction,
+};
+use crate::level2::ext::dom_impl::get_implementation_ext;
+use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::user_data::{notify_user_data, UserDataOperation};
+use crate::level2::node_impl::{NodeImpl, RefNode};
+use crate::level2::trait_impls::{add_default_attributes, ordered_attributes};
+use crate::level2::traits::*;
+use crate::shared::error::{
+    Error, Result, MSG_DOCUMENT_NOT_FOUND, MSG_DUPLICATE_DOCUMENT, MSG_INVALID_NODE_TYPE,
+};
+use crate::shared::name::Name;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A set of related documents, identified by their system identifiers; see the
+/// [module](index.html) documentation.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Workspace {
+    documents: Vec<(String, RefNode)>,
+    options: ProcessingOptions,
+    public_ids: HashMap<String, String>,
+    names: NameTable,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Workspace {
+    ///
+    /// Construct a new, empty, workspace whose documents are created with the default processing
+    /// options.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Construct a new, empty, workspace whose documents are created with `options`.
+    ///
+    pub fn with_options(options: ProcessingOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Returns the processing options used for documents created by this workspace.
+    ///
+    pub fn options(&self) -> &ProcessingOptions {
+        &self.options
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    ///
+    /// Create a new document, using this workspace's processing options, and add it to the
+    /// workspace as `system_id`. The remaining arguments are as for `DOMImplementation::create_document`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `InvalidState` if a document with this system identifier is already in the workspace.
+    ///
+    pub fn create_document(
+        &mut self,
+        system_id: &str,
+        namespace_uri: Option<&str>,
+        qualified_name: Option<&str>,
+    ) -> Result<RefNode> {
+        self.check_unused(system_id)?;
+        let document_node = get_implementation_ext().create_document_with_options(
+            namespace_uri,
+            qualified_name,
+            None,
+            self.options.clone(),
+        )?;
+        self.documents
+            .push((system_id.to_string(), document_node.clone()));
+        Ok(document_node)
+    }
+
+    ///
+    /// Add an existing document, for example one returned by the parser, to the workspace as
+    /// `system_id`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `InvalidState` if `document_node` is not a `Document` node, if a document with this
+    ///   system identifier is already in the workspace, or if `document_node` is already in the
+    ///   workspace under another system identifier.
+    ///
+    pub fn insert(&mut self, system_id: &str, document_node: RefNode) -> Result<()> {
+        if document_node.node_type() != NodeType::Document {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        self.check_unused(system_id)?;
+        if let Some(existing) = self.system_id_of(&document_node) {
+            warn!("{}: '{}'", MSG_DUPLICATE_DOCUMENT, existing);
+            return Err(Error::InvalidState);
+        }
+        self.documents.push((system_id.to_string(), document_node));
+        Ok(())
+    }
+
+    ///
+    /// Remove the document `system_id` from the workspace, returning it if it was present. If
+    /// it was the main document, the document added after it becomes the main document.
+    ///
+    pub fn remove(&mut self, system_id: &str) -> Option<RefNode> {
+        let index = self.index_of(system_id)?;
+        Some(self.documents.remove(index).1)
+    }
+
+    ///
+    /// Returns the document `system_id`, if it is in the workspace.
+    ///
+    pub fn get(&self, system_id: &str) -> Option<&RefNode> {
+        self.index_of(system_id)
+            .map(|index| &self.documents[index].1)
+    }
+
+    ///
+    /// Returns the main document, if the workspace is not empty.
+    ///
+    pub fn main(&self) -> Option<&RefNode> {
+        self.documents
+            .first()
+            .map(|(_, document_node)| document_node)
+    }
+
+    ///
+    /// Make the document `system_id` the main document.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NotFound` if there is no document with this system identifier in the workspace.
+    ///
+    pub fn set_main(&mut self, system_id: &str) -> Result<()> {
+        let index = self.existing_index_of(system_id)?;
+        let document = self.documents.remove(index);
+        self.documents.insert(0, document);
+        Ok(())
+    }
+
+    ///
+    /// Returns the number of documents in the workspace.
+    ///
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    ///
+    /// Returns `true` if there are no documents in the workspace.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    ///
+    /// Returns an iterator over the system identifiers and documents in the workspace, starting
+    /// with the main document and then in the order they were added.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &RefNode)> {
+        self.documents
+            .iter()
+            .map(|(system_id, document_node)| (system_id.as_str(), document_node))
+    }
+
+    ///
+    /// Returns the system identifier of the workspace document that owns `node`, or of `node`
+    /// itself if it is a `Document` node. A document type passed to `create_document` has no
+    /// owner document, and so is found as the document type of a workspace document.
+    ///
+    pub fn system_id_of(&self, node: &RefNode) -> Option<&str> {
+        let document_node = match (node.node_type(), node.owner_document()) {
+            (NodeType::Document, _) => Some(node.clone()),
+            (_, Some(document_node)) => Some(document_node),
+            (NodeType::DocumentType, None) => None,
+            _ => return None,
+        };
+        self.documents
+            .iter()
+            .find(|(_, existing)| match &document_node {
+                Some(document_node) => existing == document_node,
+                None => as_document(existing)
+                    .map(|document| document.doc_type().as_ref() == Some(node))
+                    .unwrap_or_default(),
+            })
+            .map(|(system_id, _)| system_id.as_str())
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    ///
+    /// Returns the name `qualified_name`, in the namespace `namespace_uri` if provided, from the
+    /// workspace's name table; the name is only parsed and validated the first time it is
+    /// requested.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `InvalidCharacter`, `Namespace`, or `Syntax` as for `Document::create_element_ns`.
+    ///
+    pub fn name(&mut self, namespace_uri: Option<&str>, qualified_name: &str) -> Result<Name> {
+        let key = (namespace_uri.map(String::from), qualified_name.to_string());
+        if let Some(name) = self.names.get(&key) {
+            return Ok(name.clone());
+        }
+        let name = match namespace_uri {
+            Some(namespace_uri) => Name::new_ns(namespace_uri, qualified_name)?,
+            None => Name::from_str(qualified_name)?,
+        };
+        let _safe_to_ignore = self.names.insert(key, name.clone());
+        Ok(name)
+    }
+
+    ///
+    /// Returns the number of distinct names in the workspace's name table.
+    ///
+    pub fn interned_names(&self) -> usize {
+        self.names.len()
+    }
+
+    ///
+    /// Create a new element, named using the workspace's name table, owned by the document
+    /// `system_id`; the new element is not yet part of the document's tree.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NotFound` if there is no document with this system identifier in the workspace.
+    /// * `InvalidCharacter`, `Namespace`, or `Syntax` as for `Document::create_element_ns`.
+    ///
+    pub fn create_element(
+        &mut self,
+        system_id: &str,
+        namespace_uri: Option<&str>,
+        qualified_name: &str,
+    ) -> Result<RefNode> {
+        let index = self.existing_index_of(system_id)?;
+        let name = self.name(namespace_uri, qualified_name)?;
+        let document_node = &self.documents[index].1;
+        let element = RefNode::new(NodeImpl::new_element(
+            document_node.clone().downgrade(),
+            name,
+        ));
+        add_default_attributes(document_node, &element, None);
+        Ok(element)
+    }
+
+    ///
+    /// Copy `node`, from any document, into the document `system_id`; this corresponds to the
+    /// DOM `Document::importNode` method. The copy is owned by the target document but is not yet
+    /// part of its tree. The specified attributes of an element are always copied, and it is given
+    /// the default attributes declared by the target document; its children, and the children of
+    /// a document fragment, are only copied if `deep` is `true`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NotFound` if there is no document with this system identifier in the workspace.
+    /// * `NotSupported` if `node` is a `Document`, `DocumentType`, `Entity`, or `Notation` node,
+    ///   these cannot be imported.
+    ///
+    pub fn import_node(&mut self, node: &RefNode, system_id: &str, deep: bool) -> Result<RefNode> {
+        let index = self.existing_index_of(system_id)?;
+        let document_node = self.documents[index].1.clone();
+        import_node(&mut self.names, &document_node, node, deep)
+    }
+
+    ///
+    /// Copy `node` into the document that owns `parent`, as for
+    /// [`import_node`](#method.import_node), and append the copy to the children of `parent`,
+    /// returning the copy. The document owning `parent` need not be in the workspace.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NotSupported` if `node` cannot be imported, see `import_node`.
+    /// * Any error returned by `Node::append_child`.
+    ///
+    pub fn append_imported(
+        &mut self,
+        parent: &mut RefNode,
+        node: &RefNode,
+        deep: bool,
+    ) -> Result<RefNode> {
+        let document_node = if parent.node_type() == NodeType::Document {
+            parent.clone()
+        } else {
+            parent.owner_document().ok_or(Error::NotFound)?
+        };
+        let imported = import_node(&mut self.names, &document_node, node, deep)?;
+        parent.append_child(imported)
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    ///
+    /// Map `public_id` to the document `system_id`, so that references with this public
+    /// identifier are resolved to that document regardless of their own system identifier.
+    ///
+    pub fn map_public_id(&mut self, public_id: &str, system_id: &str) {
+        let _safe_to_ignore = self
+            .public_ids
+            .insert(public_id.to_string(), system_id.to_string());
+    }
+
+    ///
+    /// Resolve `reference` against the URI `base`, if provided; absolute references, including
+    /// those with a URI scheme, are returned unchanged. Only the path of `base` is used, and `.`
+    /// and `..` segments in the result are removed.
+    ///
+    pub fn resolve_uri(&self, reference: &str, base: Option<&str>) -> String {
+        resolve_uri(reference, base)
+    }
+
+    ///
+    /// Find the workspace document for an external reference with the identifiers `public_id`
+    /// and `system_id`, made from the document with system identifier `base`. A mapped public
+    /// identifier takes precedence over the system identifier, which is resolved against `base`
+    /// using [`resolve_uri`](#method.resolve_uri).
+    ///
+    pub fn resolve(
+        &self,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+        base: Option<&str>,
+    ) -> Option<&RefNode> {
+        if let Some(document_node) = public_id
+            .and_then(|public_id| self.public_ids.get(public_id))
+            .and_then(|system_id| self.get(system_id))
+        {
+            return Some(document_node);
+        }
+        system_id.and_then(|system_id| self.get(&resolve_uri(system_id, base)))
+    }
+
+    ///
+    /// Find the workspace document referenced by the identifiers of `node`, which must be a
+    /// `DocumentType`, `Entity`, or `Notation` node; relative system identifiers are resolved
+    /// against the system identifier of the document that owns `node`.
+    ///
+    pub fn resolve_external(&self, node: &RefNode) -> Option<&RefNode> {
+        let (public_id, system_id) = match node.node_type() {
+            NodeType::DocumentType => {
+                let doc_type = as_document_type(node).ok()?;
+                (doc_type.public_id(), doc_type.system_id())
+            }
+            NodeType::Entity => {
+                let entity = as_entity(node).ok()?;
+                (entity.public_id(), entity.system_id())
+            }
+            NodeType::Notation => {
+                let notation = as_notation(node).ok()?;
+                (notation.public_id(), notation.system_id())
+            }
+            _ => return None,
+        };
+        self.resolve(
+            public_id.as_deref(),
+            system_id.as_deref(),
+            self.system_id_of(node),
+        )
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    fn index_of(&self, system_id: &str) -> Option<usize> {
+        self.documents
+            .iter()
+            .position(|(existing, _)| existing == system_id)
+    }
+
+    fn existing_index_of(&self, system_id: &str) -> Result<usize> {
+        self.index_of(system_id).ok_or_else(|| {
+            warn!("{}: '{}'", MSG_DOCUMENT_NOT_FOUND, system_id);
+            Error::NotFound
+        })
+    }
+
+    fn check_unused(&self, system_id: &str) -> Result<()> {
+        if self.index_of(system_id).is_some() {
+            warn!("{}: '{}'", MSG_DUPLICATE_DOCUMENT, system_id);
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Names keyed by namespace URI and qualified name.
+///
+pub(crate) type NameTable = HashMap<(Option<String>, String), Name>;
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn intern(names: &mut NameTable, name: Name) -> Name {
+    let key = (name.namespace_uri().clone(), name.to_string());
+    names.entry(key).or_insert(name).clone()
+}
+
+///
+/// Copy `node` into the document `document_node`, as described for
+/// [`Workspace::import_node`](struct.Workspace.html#method.import_node), taking element and
+/// attribute names from `names`.
+///
+pub(crate) fn import_node(
+    names: &mut NameTable,
+    document_node: &RefNode,
+    node: &RefNode,
+    deep: bool,
+) -> Result<RefNode> {
+    let document = as_document(document_node)?;
+    let owner_document = document_node.clone().downgrade();
+    let mut imported = match node.node_type() {
+        NodeType::Element => {
+            let name = intern(names, node.node_name());
+            let mut element = RefNode::new(NodeImpl::new_element(owner_document, name));
+            add_default_attributes(document_node, &element, None);
+            for (_, attribute) in ordered_attributes(node) {
+                //
+                // Default attributes are not copied, those of this document are used instead.
+                //
+                if as_attribute(&attribute)?.specified() {
+                    let attribute = import_node(names, document_node, &attribute, true)?;
+                    let _safe_to_ignore = element.set_attribute_node(attribute)?;
+                }
+            }
+            element
+        }
+        NodeType::Attribute => {
+            let name = intern(names, node.node_name());
+            let value = as_attribute(node)?.value();
+            RefNode::new(NodeImpl::new_attribute(
+                owner_document,
+                name,
+                value.as_deref(),
+            ))
+        }
+        NodeType::Text => document.create_text_node(&node.node_value().unwrap_or_default()),
+        NodeType::CData => document.create_cdata_section(&node.node_value().unwrap_or_default())?,
+        NodeType::Comment => document.create_comment(&node.node_value().unwrap_or_default()),
+        NodeType::ProcessingInstruction => {
+            let processing_instruction = as_processing_instruction(node)?;
+            document.create_processing_instruction(
+                &processing_instruction.target(),
+                processing_instruction.data().as_deref(),
+            )?
+        }
+        NodeType::EntityReference => {
+            return document.create_entity_reference(&node.node_name().to_string())
+        }
+        NodeType::UnparsedMarkup => RefNode::new(NodeImpl::new_unparsed_markup(
+            owner_document,
+            &node.node_value().unwrap_or_default(),
+        )),
+        NodeType::DocumentFragment => document.create_document_fragment()?,
+        _ => {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::NotSupported);
+        }
+    };
+    imported.borrow_mut().i_user_extension = node.borrow().i_user_extension.clone();
+    notify_user_data(UserDataOperation::Imported, node, &imported);
+    if deep && node.node_type() != NodeType::Attribute {
+        for child_node in node.child_nodes() {
+            let child_node = import_node(names, document_node, &child_node, true)?;
+            let _safe_to_ignore = imported.append_child(child_node)?;
+        }
+    }
+    Ok(imported)
+}
+
+///
+/// A URI scheme must be more than one character long, so that a Windows drive letter is not
+/// mistaken for one.
+///
+fn has_scheme(reference: &str) -> bool {
+    match reference.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.len() > 1
+                && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        }
+        None => false,
+    }
+}
+
+///
+/// Resolve `reference` against `base`, see
+/// [`Workspace::resolve_uri`](struct.Workspace.html#method.resolve_uri).
+///
+pub(crate) fn resolve_uri(reference: &str, base: Option<&str>) -> String {
+    let base = match base {
+        Some(base) if !has_scheme(reference) => base,
+        _ => return reference.to_string(),
+    };
+    // the scheme and authority of `base`, which are never changed.
+    let authority_end = match base.find("://") {
+        Some(index) => base[index + 3..]
+            .find('/')
+            .map(|end| index + 3 + end)
+            .unwrap_or(base.len()),
+        None => 0,
+    };
+    let (authority, base_path) = base.split_at(authority_end);
+    let path = if reference.starts_with('/') {
+        reference.to_string()
+    } else {
+        match base_path.rfind('/') {
+            Some(index) => format!("{}{}", &base_path[..=index], reference),
+            None if authority.is_empty() => reference.to_string(),
+            None => format!("/{}", reference),
+        }
+    };
+    let absolute = path.starts_with('/');
+    let mut segments: Vec<&str> = Vec::new();
+    let parts: Vec<&str> = path.split('/').collect();
+    for (index, segment) in parts.iter().enumerate() {
+        match *segment {
+            "." => {}
+            ".." => {
+                if matches!(segments.last(), None | Some(&"..")) {
+                    if !absolute {
+                        segments.push("..");
+                    }
+                } else {
+                    let _safe_to_ignore = segments.pop();
+                }
+            }
+            "" if index == 0 && absolute => {}
+            segment => segments.push(segment),
+        }
+        if (*segment == "." || *segment == "..") && index == parts.len() - 1 {
+            segments.push("");
+        }
+    }
+    format!(
+        "{}{}{}",
+        authority,
+        if absolute { "/" } else { "" },
+        segments.join("/")
+    )
+}
@@ -0,0 +1,367 @@
+/*!
+Provides helpers to insert the standard OASIS WS-Security header structures into a SOAP message.
+
+Each helper locates, or creates, the `wsse:Security` header block of an
+[`Envelope`](../soap/struct.Envelope.html) and adds a new child element to it. Every element added
+is given a unique `wsu:Id` attribute, returned by [`wsu_id`](fn.wsu_id.html), so that it may be
+referenced by a signature.
+
+This module is only available when the `soap` feature is enabled.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::soap::{Envelope, SoapVersion};
+use xml_dom::level2::ext::wsse::{insert_timestamp, insert_username_token, wsu_id, UsernameToken};
+
+let implementation = get_implementation();
+let document_node = implementation
+    .create_document(Some(SoapVersion::V11.namespace_uri()), Some("soap:Envelope"), None)
+    .unwrap();
+let envelope = Envelope::from_document(&document_node).unwrap();
+
+let timestamp = insert_timestamp(&envelope, "2020-01-01T00:00:00Z", Some("2020-01-01T00:05:00Z"))
+    .unwrap();
+assert_eq!(wsu_id(&timestamp), Some("TS-1".to_string()));
+
+let token = insert_username_token(&envelope, &UsernameToken::new("alice").with_password("secret"))
+    .unwrap();
+assert_eq!(wsu_id(&token), Some("UsernameToken-1".to_string()));
+assert_eq!(envelope.must_understand_headers().len(), 1);
+```
+*/
+
+use crate::level2::convert::{as_document, as_element_mut};
+use crate::level2::ext::namespaced::element_namespace;
+use crate::level2::ext::soap::{child_elements, Envelope, SoapVersion};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::*;
+use crate::shared::error::{Error, Result};
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The WS-Security extension namespace.
+///
+pub const WSSE_NS: &str =
+    "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-secext-1.0.xsd";
+
+///
+/// The WS-Security utility namespace.
+///
+pub const WSU_NS: &str =
+    "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-utility-1.0.xsd";
+
+///
+/// The `ValueType` of an X.509 v3 certificate `BinarySecurityToken`.
+///
+pub const X509_V3_VALUE_TYPE: &str =
+    "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-x509-token-profile-1.0#X509v3";
+
+///
+/// The form in which the password of a `UsernameToken` is sent.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PasswordType {
+    /// The password is sent as-is.
+    Text,
+    /// The password is a digest computed by the caller, `Base64(SHA-1(nonce + created +
+    /// password))`.
+    Digest,
+}
+
+///
+/// The content of a `UsernameToken` to insert with
+/// [`insert_username_token`](fn.insert_username_token.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsernameToken {
+    /// The user name.
+    pub username: String,
+    /// The password, if any, and its type.
+    pub password: Option<(String, PasswordType)>,
+    /// A Base64 encoded nonce, if any.
+    pub nonce: Option<String>,
+    /// The creation time of the token, as an `xsd:dateTime`, if any.
+    pub created: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the `wsse:Security` header block of `envelope`, creating it, and the SOAP `Header`,
+/// if necessary. A new header block is marked `mustUnderstand`.
+///
+pub fn security_header(envelope: &Envelope) -> Result<RefNode> {
+    let mut header = envelope.header_or_insert()?;
+    if let Some(security) = child_elements(&header).into_iter().find(|element| {
+        element.node_name().local_name() == SECURITY
+            && element_namespace(element).as_deref() == Some(WSSE_NS)
+    }) {
+        return Ok(security);
+    }
+
+    let mut security = create_element(&header, WSSE_NS, WSSE_PREFIX, SECURITY)?;
+    {
+        let element = as_element_mut(&mut security)?;
+        declare(element, WSSE_PREFIX, WSSE_NS)?;
+        declare(element, WSU_PREFIX, WSU_NS)?;
+        let envelope_prefix = envelope.element().node_name().prefix().clone();
+        let envelope_prefix = match envelope_prefix {
+            Some(prefix) => prefix,
+            None => {
+                declare(element, ENV_PREFIX, envelope.version().namespace_uri())?;
+                ENV_PREFIX.to_string()
+            }
+        };
+        element.set_attribute_ns(
+            envelope.version().namespace_uri(),
+            &format!("{}:{}", envelope_prefix, MUST_UNDERSTAND),
+            match envelope.version() {
+                SoapVersion::V11 => "1",
+                SoapVersion::V12 => "true",
+            },
+        )?;
+    }
+    header.append_child(security)
+}
+
+///
+/// Insert a `wsu:Timestamp` as the first child of the `wsse:Security` header block. The values of
+/// `created` and `expires` must be `xsd:dateTime` values in UTC.
+///
+pub fn insert_timestamp(
+    envelope: &Envelope,
+    created: &str,
+    expires: Option<&str>,
+) -> Result<RefNode> {
+    let mut security = security_header(envelope)?;
+    let mut timestamp = create_element(&security, WSU_NS, WSU_PREFIX, TIMESTAMP)?;
+    set_wsu_id(&mut timestamp, &security, TIMESTAMP_ID_PREFIX)?;
+    let _safe_to_ignore =
+        append_text_element(&mut timestamp, WSU_NS, WSU_PREFIX, CREATED, created)?;
+    if let Some(expires) = expires {
+        let _safe_to_ignore =
+            append_text_element(&mut timestamp, WSU_NS, WSU_PREFIX, EXPIRES, expires)?;
+    }
+    let first_child = security.first_child();
+    security.insert_before(timestamp, first_child)
+}
+
+///
+/// Append a `wsse:UsernameToken` to the `wsse:Security` header block.
+///
+pub fn insert_username_token(envelope: &Envelope, token: &UsernameToken) -> Result<RefNode> {
+    let mut security = security_header(envelope)?;
+    let mut element = create_element(&security, WSSE_NS, WSSE_PREFIX, USERNAME_TOKEN)?;
+    set_wsu_id(&mut element, &security, USERNAME_TOKEN)?;
+    let _safe_to_ignore = append_text_element(
+        &mut element,
+        WSSE_NS,
+        WSSE_PREFIX,
+        USERNAME,
+        &token.username,
+    )?;
+    if let Some((password, password_type)) = &token.password {
+        let mut password_element =
+            append_text_element(&mut element, WSSE_NS, WSSE_PREFIX, PASSWORD, password)?;
+        as_element_mut(&mut password_element)?.set_attribute(
+            TYPE,
+            match password_type {
+                PasswordType::Text => PASSWORD_TEXT_TYPE,
+                PasswordType::Digest => PASSWORD_DIGEST_TYPE,
+            },
+        )?;
+    }
+    if let Some(nonce) = &token.nonce {
+        let mut nonce_element =
+            append_text_element(&mut element, WSSE_NS, WSSE_PREFIX, NONCE, nonce)?;
+        as_element_mut(&mut nonce_element)?.set_attribute(ENCODING_TYPE, BASE64_ENCODING_TYPE)?;
+    }
+    if let Some(created) = &token.created {
+        let _safe_to_ignore =
+            append_text_element(&mut element, WSU_NS, WSU_PREFIX, CREATED, created)?;
+    }
+    security.append_child(element)
+}
+
+///
+/// Append a `wsse:BinarySecurityToken`, such as an X.509 certificate, to the `wsse:Security`
+/// header block. The `value` must already be Base64 encoded.
+///
+pub fn insert_binary_security_token(
+    envelope: &Envelope,
+    value_type: &str,
+    value: &str,
+) -> Result<RefNode> {
+    let mut security = security_header(envelope)?;
+    let mut element = create_element(&security, WSSE_NS, WSSE_PREFIX, BINARY_SECURITY_TOKEN)?;
+    set_wsu_id(&mut element, &security, BINARY_TOKEN_ID_PREFIX)?;
+    {
+        let ref_element = as_element_mut(&mut element)?;
+        ref_element.set_attribute(ENCODING_TYPE, BASE64_ENCODING_TYPE)?;
+        ref_element.set_attribute(VALUE_TYPE, value_type)?;
+    }
+    let document_node = security.owner_document().ok_or(Error::NotFound)?;
+    let text = as_document(&document_node)?.create_text_node(value);
+    let _safe_to_ignore = element.append_child(text)?;
+    security.append_child(element)
+}
+
+///
+/// Returns the value of the `wsu:Id` attribute of `element`, if present.
+///
+pub fn wsu_id(element: &RefNode) -> Option<String> {
+    element
+        .attributes()
+        .iter()
+        .find(|(name, _)| {
+            name.local_name() == ID
+                && (name.namespace_uri().as_deref() == Some(WSU_NS)
+                    || name.prefix().as_deref() == Some(WSU_PREFIX))
+        })
+        .and_then(|(_, attribute)| attribute.value())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl UsernameToken {
+    ///
+    /// Construct a new token with only a user name.
+    ///
+    pub fn new(username: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            password: None,
+            nonce: None,
+            created: None,
+        }
+    }
+
+    ///
+    /// Add a plain text password to this token.
+    ///
+    pub fn with_password(self, password: &str) -> Self {
+        Self {
+            password: Some((password.to_string(), PasswordType::Text)),
+            ..self
+        }
+    }
+
+    ///
+    /// Add a password digest, and the nonce and creation time used to compute it, to this token.
+    ///
+    pub fn with_password_digest(self, digest: &str, nonce: &str, created: &str) -> Self {
+        Self {
+            password: Some((digest.to_string(), PasswordType::Digest)),
+            nonce: Some(nonce.to_string()),
+            created: Some(created.to_string()),
+            ..self
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const WSSE_PREFIX: &str = "wsse";
+const WSU_PREFIX: &str = "wsu";
+const ENV_PREFIX: &str = "env";
+
+const SECURITY: &str = "Security";
+const TIMESTAMP: &str = "Timestamp";
+const CREATED: &str = "Created";
+const EXPIRES: &str = "Expires";
+const USERNAME_TOKEN: &str = "UsernameToken";
+const USERNAME: &str = "Username";
+const PASSWORD: &str = "Password";
+const NONCE: &str = "Nonce";
+const BINARY_SECURITY_TOKEN: &str = "BinarySecurityToken";
+const MUST_UNDERSTAND: &str = "mustUnderstand";
+const ID: &str = "Id";
+const TYPE: &str = "Type";
+const ENCODING_TYPE: &str = "EncodingType";
+const VALUE_TYPE: &str = "ValueType";
+
+const TIMESTAMP_ID_PREFIX: &str = "TS";
+const BINARY_TOKEN_ID_PREFIX: &str = "X509";
+
+const PASSWORD_TEXT_TYPE: &str =
+    "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-username-token-profile-1.0#PasswordText";
+const PASSWORD_DIGEST_TYPE: &str =
+    "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-username-token-profile-1.0#PasswordDigest";
+const BASE64_ENCODING_TYPE: &str =
+    "http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-soap-message-security-1.0#Base64Binary";
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn create_element(
+    context: &RefNode,
+    namespace_uri: &str,
+    prefix: &str,
+    local_name: &str,
+) -> Result<RefNode> {
+    let document_node = context.owner_document().ok_or(Error::NotFound)?;
+    let document = as_document(&document_node)?;
+    document.create_element_ns(namespace_uri, &format!("{}:{}", prefix, local_name))
+}
+
+fn append_text_element(
+    parent: &mut RefNode,
+    namespace_uri: &str,
+    prefix: &str,
+    local_name: &str,
+    text: &str,
+) -> Result<RefNode> {
+    let mut element = create_element(parent, namespace_uri, prefix, local_name)?;
+    let document_node = parent.owner_document().ok_or(Error::NotFound)?;
+    let text = as_document(&document_node)?.create_text_node(text);
+    let _safe_to_ignore = element.append_child(text)?;
+    parent.append_child(element)
+}
+
+fn declare(
+    element: &mut dyn Element<NodeRef = RefNode>,
+    prefix: &str,
+    namespace_uri: &str,
+) -> Result<()> {
+    element.set_attribute_ns(
+        XMLNS_NS_URI,
+        &format!("{}:{}", XMLNS_NS_ATTRIBUTE, prefix),
+        namespace_uri,
+    )
+}
+
+///
+/// Set a `wsu:Id` on `element` of the form `{prefix}-{n}`, where `n` is the lowest number that
+/// results in an ID not already used within the `wsse:Security` header block.
+///
+fn set_wsu_id(element: &mut RefNode, security: &RefNode, prefix: &str) -> Result<()> {
+    let mut used: Vec<String> = Vec::new();
+    collect_ids(security, &mut used);
+    let id = (1..)
+        .map(|n| format!("{}-{}", prefix, n))
+        .find(|id| !used.contains(id))
+        .unwrap();
+    as_element_mut(element)?.set_attribute_ns(WSU_NS, &format!("{}:{}", WSU_PREFIX, ID), &id)
+}
+
+fn collect_ids(node: &RefNode, ids: &mut Vec<String>) {
+    if let Some(id) = wsu_id(node) {
+        ids.push(id);
+    }
+    for child in child_elements(node) {
+        collect_ids(&child, ids);
+    }
+}
@@ -0,0 +1,432 @@
+/*!
+Provides a bridge to the [xml-rs](https://crates.io/crates/xml-rs) crate, so that a pipeline
+already reading or writing XML with xml-rs may construct a DOM for a document, or write a DOM
+through its existing writer, without first producing, and then parsing, XML text.
+
+* [`read_event_reader`](fn.read_event_reader.html) reads all remaining events from an
+  `xml::reader::EventReader`, which may have been configured by the caller, into a new document.
+* [`XmlRsBuilder`](struct.XmlRsBuilder.html) constructs a document incrementally from reader
+  events, for clients that pull events from the reader themselves.
+* [`write_event_writer`](fn.write_event_writer.html) writes the tree rooted at a node through an
+  `xml::writer::EventWriter`, with the writer's own configuration for indentation and escaping.
+
+The reader always reports an XML declaration, so a document read this way has one even if the
+text did not; and as neither the reader nor the writer has an event for a document type, it is
+read with `EventReader::doctype` once the reader has passed it, and written as markup directly to
+the writer's output. Comments are only read if the reader is configured not to ignore them.
+
+The namespace declarations made at each element are added to it as `xmlns` attributes, and
+element and attribute names are given the namespace URI their prefix resolves to, as by the
+[`parser`](../../../parser/index.html); the writer writes declarations from those attributes.
+
+This module is only available when the `xml_rs` feature is enabled.
+
+# Example
+
+```rust
+use xml::reader::EventReader;
+use xml::writer::EmitterConfig;
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::xml_rs::{read_event_reader, write_event_writer};
+
+let mut reader = EventReader::from_str(r#"<order><item id="1">pen</item></order>"#);
+let document_node = read_event_reader(&mut reader).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut item_node = document.document_element().unwrap().first_child().unwrap();
+item_node.set_attribute("count", "2").unwrap();
+
+let mut writer = EmitterConfig::new()
+    .write_document_declaration(false)
+    .create_writer(Vec::new());
+write_event_writer(&item_node, &mut writer).unwrap();
+assert_eq!(
+    String::from_utf8(writer.into_inner()).unwrap(),
+    r#"<item id="1" count="2">pen</item>"#
+);
+```
+*/
+
+use crate::level2::ext::decl::{PrologPosition, XmlDecl, XmlVersion};
+use crate::level2::ext::dtd::doc_type_parts;
+use crate::level2::ext::mutation::raw_value;
+use crate::level2::ext::namespaced::resolve_names;
+use crate::level2::ext::normalize::split_cdata_end;
+use crate::level2::ext::traits::DocumentDecl;
+use crate::level2::ext::tree_builder::TreeBuilder;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::ordered_attributes;
+use crate::level2::traits::*;
+use crate::shared::display::separate_hyphens;
+use crate::shared::error::{Error, Result, MSG_MISPLACED_CONTENT, MSG_XML_RS_FAILED};
+use crate::shared::name::Name;
+use crate::shared::syntax::{
+    XMLNS_NS_ATTRIBUTE, XML_DOCTYPE_END, XML_DOCTYPE_START, XML_NS_ATTRIBUTE,
+};
+use std::borrow::Cow;
+use std::io::{Read, Write};
+use xml::attribute::Attribute as XmlRsAttribute;
+use xml::common::XmlVersion as XmlRsVersion;
+use xml::name::Name as XmlRsName;
+use xml::namespace::{Namespace, NS_EMPTY_URI, NS_NO_PREFIX};
+use xml::reader::{EventReader, XmlEvent as ReaderEvent};
+use xml::writer::{EventWriter, XmlEvent as WriterEvent};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Incrementally constructs a document from the events produced by an xml-rs `EventReader`. Each
+/// event is passed to a [`TreeBuilder`](../tree_builder/struct.TreeBuilder.html), and so is
+/// checked in the same way.
+///
+#[derive(Clone, Debug)]
+pub struct XmlRsBuilder {
+    builder: TreeBuilder,
+    scopes: Vec<Namespace>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Read all remaining events from `reader` into a new document using an
+/// [`XmlRsBuilder`](struct.XmlRsBuilder.html); if the result is OK, the result returned can be
+/// safely assumed to be a `Document` node.
+///
+/// **Exceptions**
+///
+/// * `SYNTAX_ERR`: Raised if the reader returns an error, or the document is not complete.
+/// * `INVALID_CHARACTER_ERR`: Raised if a name is not valid.
+/// * `INVALID_STATE_ERR`: Raised if the elements read are not balanced.
+///
+pub fn read_event_reader<R: Read>(reader: &mut EventReader<R>) -> Result<RefNode> {
+    let mut builder = XmlRsBuilder::new();
+    let mut doc_type_read = false;
+    loop {
+        match reader.next() {
+            Ok(ReaderEvent::EndDocument) => return builder.finish(),
+            Ok(event) => {
+                //
+                // The reader has no event for the document type, it is available once the event
+                // following it has been read, and is added before that event.
+                //
+                if !doc_type_read {
+                    if let Some(doc_type) = reader.doctype() {
+                        doc_type_read = true;
+                        builder.document_type(doc_type)?;
+                    }
+                }
+                builder.handle_event(event)?;
+            }
+            Err(e) => {
+                warn!("{}: '{}'", MSG_XML_RS_FAILED, e);
+                return Err(Error::Syntax);
+            }
+        }
+    }
+}
+
+///
+/// Write the tree rooted at `node` through `writer`. If `node` is a document its XML declaration,
+/// if any, is written as a `StartDocument` event; otherwise the writer writes a declaration first
+/// if it is configured to.
+///
+/// Comments are written with a space after any hyphen that would end them, and CDATA sections
+/// are split where their content contains `]]>`. Entity references and unparsed markup are written
+/// as they are displayed, as there is no event for them. Attribute, entity, and notation nodes are
+/// not content and write nothing.
+///
+/// **Exceptions**
+///
+/// * `INVALID_STATE_ERR`: Raised if the writer returns an error.
+///
+pub fn write_event_writer<W: Write>(node: &RefNode, writer: &mut EventWriter<W>) -> Result<()> {
+    match node.node_type() {
+        NodeType::Element => {
+            let name = node.node_name().to_string();
+            let mut declarations = Namespace::empty();
+            let mut attributes: Vec<(String, String)> = Vec::new();
+            for (attribute_name, attribute) in ordered_attributes(node) {
+                if is_namespace_declaration(&attribute_name) {
+                    let prefix = match attribute_name.prefix() {
+                        Some(_) => attribute_name.local_name().to_string(),
+                        None => NS_NO_PREFIX.to_string(),
+                    };
+                    let _safe_to_ignore = declarations.put(prefix, raw_value(&attribute));
+                } else {
+                    attributes.push((attribute_name.to_string(), raw_value(&attribute)));
+                }
+            }
+            let attributes: Vec<XmlRsAttribute<'_>> = attributes
+                .iter()
+                .map(|(name, value)| XmlRsAttribute::new(XmlRsName::from(name.as_str()), value))
+                .collect();
+            write_event(
+                writer,
+                WriterEvent::StartElement {
+                    name: XmlRsName::from(name.as_str()),
+                    attributes: Cow::Owned(attributes),
+                    namespace: Cow::Owned(declarations),
+                },
+            )?;
+            write_children(node, writer)?;
+            write_event(
+                writer,
+                WriterEvent::EndElement {
+                    name: Some(XmlRsName::from(name.as_str())),
+                },
+            )
+        }
+        NodeType::Text => write_event(
+            writer,
+            WriterEvent::Characters(&node.node_value().unwrap_or_default()),
+        ),
+        NodeType::CData => {
+            for section in split_cdata_end(&node.node_value().unwrap_or_default()) {
+                write_event(writer, WriterEvent::CData(section))?;
+            }
+            Ok(())
+        }
+        NodeType::Comment => write_event(
+            writer,
+            WriterEvent::Comment(&separate_hyphens(&node.node_value().unwrap_or_default())),
+        ),
+        NodeType::ProcessingInstruction => write_event(
+            writer,
+            WriterEvent::ProcessingInstruction {
+                name: &node.node_name().to_string(),
+                data: node.node_value().as_deref(),
+            },
+        ),
+        NodeType::Document => {
+            if let Some(xml_decl) = node.xml_declaration() {
+                write_event(
+                    writer,
+                    WriterEvent::StartDocument {
+                        version: match xml_decl.version() {
+                            XmlVersion::V10 => XmlRsVersion::Version10,
+                            XmlVersion::V11 => XmlRsVersion::Version11,
+                        },
+                        encoding: xml_decl.encoding().as_deref(),
+                        standalone: xml_decl.standalone(),
+                    },
+                )?;
+            }
+            let mut doc_type = node.doc_type();
+            for child in node.child_nodes() {
+                if node.prolog_position(&child) != Some(PrologPosition::BeforeDocType) {
+                    if let Some(doc_type) = doc_type.take() {
+                        write_markup(writer, &doc_type)?;
+                    }
+                }
+                write_event_writer(&child, writer)?;
+            }
+            if let Some(doc_type) = doc_type {
+                write_markup(writer, &doc_type)?;
+            }
+            Ok(())
+        }
+        NodeType::DocumentFragment => write_children(node, writer),
+        NodeType::DocumentType | NodeType::EntityReference | NodeType::UnparsedMarkup => {
+            write_markup(writer, node)
+        }
+        NodeType::Attribute | NodeType::Entity | NodeType::Notation => Ok(()),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for XmlRsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XmlRsBuilder {
+    ///
+    /// Construct a new builder with an empty document.
+    ///
+    pub fn new() -> Self {
+        Self {
+            builder: TreeBuilder::new(),
+            scopes: Default::default(),
+        }
+    }
+
+    ///
+    /// Add the content of `event` to the document being constructed. `EndDocument` events are
+    /// ignored, call [`finish`](#method.finish) to complete the document.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if the event is not valid at the current position.
+    /// * `INVALID_CHARACTER_ERR`: Raised if a name is not valid.
+    /// * `INVALID_STATE_ERR`: Raised if an `EndElement` event does not end the open element.
+    ///
+    pub fn handle_event(&mut self, event: ReaderEvent) -> Result<()> {
+        let builder = &mut self.builder;
+        match event {
+            ReaderEvent::StartDocument {
+                version,
+                encoding,
+                standalone,
+            } => builder.xml_declaration(XmlDecl::new(
+                match version {
+                    XmlRsVersion::Version10 => XmlVersion::V10,
+                    XmlRsVersion::Version11 => XmlVersion::V11,
+                },
+                Some(encoding),
+                standalone,
+            )),
+            ReaderEvent::StartElement {
+                name,
+                attributes,
+                namespace,
+            } => {
+                let mut declarations: Vec<(String, String)> = Vec::new();
+                for (prefix, namespace_uri) in &namespace {
+                    let in_scope = self
+                        .scopes
+                        .last()
+                        .and_then(|scope| scope.get(prefix))
+                        .unwrap_or(NS_EMPTY_URI);
+                    if prefix != XML_NS_ATTRIBUTE
+                        && prefix != XMLNS_NS_ATTRIBUTE
+                        && namespace_uri != in_scope
+                    {
+                        let prefix = Some(prefix).filter(|prefix| *prefix != NS_NO_PREFIX);
+                        declarations.push((
+                            Name::for_namespace(prefix).to_string(),
+                            namespace_uri.to_string(),
+                        ));
+                    }
+                }
+                let attributes: Vec<(String, String)> = declarations
+                    .into_iter()
+                    .chain(
+                        attributes
+                            .into_iter()
+                            .map(|attribute| (attribute.name.borrow().to_repr(), attribute.value)),
+                    )
+                    .collect();
+                let attributes: Vec<(&str, &str)> = attributes
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str()))
+                    .collect();
+                builder.start_element(&name.borrow().to_repr(), &attributes)?;
+                self.scopes.push(namespace);
+                Ok(())
+            }
+            ReaderEvent::EndElement { name } => {
+                builder.end_element(&name.borrow().to_repr())?;
+                let _safe_to_ignore = self.scopes.pop();
+                Ok(())
+            }
+            ReaderEvent::ProcessingInstruction { name, data } => {
+                builder.processing_instruction(&name, data.as_deref())
+            }
+            ReaderEvent::CData(data) => builder.cdata_section(&data),
+            ReaderEvent::Comment(data) => builder.comment(&data),
+            ReaderEvent::Characters(data) | ReaderEvent::Whitespace(data) => builder.text(&data),
+            ReaderEvent::EndDocument => Ok(()),
+        }
+    }
+
+    ///
+    /// Set the document type of the document from `doc_type`, the document type declaration as
+    /// returned by `EventReader::doctype`; this is only available from the reader once it has
+    /// returned the event that follows the declaration, and so should be called before that event
+    /// is handled.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `doc_type` is not a document type declaration, or if a document
+    ///   type has already been set, or the document element has been started.
+    /// * `INVALID_CHARACTER_ERR`: Raised if the document type name is not valid.
+    ///
+    pub fn document_type(&mut self, doc_type: &str) -> Result<()> {
+        let content = doc_type
+            .strip_prefix(XML_DOCTYPE_START)
+            .map(|content| content.strip_suffix(XML_DOCTYPE_END).unwrap_or(content));
+        match content.and_then(doc_type_parts) {
+            Some(parts) => self.builder.document_type(
+                &parts.name,
+                parts.public_id.as_deref(),
+                parts.system_id.as_deref(),
+                parts.internal_subset.as_deref(),
+            ),
+            None => {
+                warn!("{}", MSG_MISPLACED_CONTENT);
+                Err(Error::Syntax)
+            }
+        }
+    }
+
+    ///
+    /// Returns the number of elements that have been started but not yet ended.
+    ///
+    pub fn depth(&self) -> usize {
+        self.builder.depth()
+    }
+
+    ///
+    /// Complete the document; if the result is OK, the result returned can be safely assumed to
+    /// be a `Document` node.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if any element has been started but not ended.
+    /// * `SYNTAX_ERR`: Raised if the document has no document element.
+    ///
+    pub fn finish(self) -> Result<RefNode> {
+        let document_node = self.builder.finish()?;
+        resolve_names(&document_node)?;
+        Ok(document_node)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn write_children<W: Write>(node: &RefNode, writer: &mut EventWriter<W>) -> Result<()> {
+    for child in node.child_nodes() {
+        write_event_writer(&child, writer)?;
+    }
+    Ok(())
+}
+
+fn write_event<W: Write>(writer: &mut EventWriter<W>, event: WriterEvent<'_>) -> Result<()> {
+    writer.write(event).map_err(|e| {
+        warn!("{}: '{}'", MSG_XML_RS_FAILED, e);
+        Error::InvalidState
+    })
+}
+
+///
+/// Write the displayed markup of `node` directly to the output of `writer`. An empty
+/// `Characters` event is written first so that the writer completes any start tag, or document
+/// declaration, it has yet to write.
+///
+fn write_markup<W: Write>(writer: &mut EventWriter<W>, node: &RefNode) -> Result<()> {
+    write_event(writer, WriterEvent::Characters(""))?;
+    writer
+        .inner_mut()
+        .write_all(node.to_string().as_bytes())
+        .map_err(|e| {
+            warn!("{}: '{}'", MSG_XML_RS_FAILED, e);
+            Error::InvalidState
+        })
+}
+
+fn is_namespace_declaration(name: &Name) -> bool {
+    match name.prefix() {
+        Some(prefix) => prefix == XMLNS_NS_ATTRIBUTE,
+        None => name.local_name() == XMLNS_NS_ATTRIBUTE,
+    }
+}
@@ -0,0 +1,300 @@
+/*!
+Provides support for XML-binary Optimized Packaging ([XOP](https://www.w3.org/TR/xop10/)), as
+used by MTOM, where large Base64 encoded content is moved out of a document into separate binary
+parts and replaced with `xop:Include` elements that reference those parts.
+
+* [`optimize`](fn.optimize.html) replaces the Base64 content of elements with `xop:Include`
+  elements, returning the binary [`Attachment`](struct.Attachment.html)s to be sent alongside the
+  document.
+* [`inline`](fn.inline.html) replaces each `xop:Include` element with the Base64 encoded content of
+  the part it references, using an [`AttachmentRegistry`](struct.AttachmentRegistry.html) of
+  providers to locate the binary content.
+
+Both functions modify the tree in place; to keep the original tree intact, optimize a deep clone
+before serializing it.
+
+This module is only available when the `xop` feature is enabled.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::xop::{inline, optimize, AttachmentRegistry, OptimizeOptions};
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("image"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _safe_to_ignore = root_node
+    .append_child(document.create_text_node("aGVsbG8gd29ybGQ="))
+    .unwrap();
+
+let options = OptimizeOptions { min_size: 0, ..Default::default() };
+let attachments = optimize(&root_node, &options).unwrap();
+assert_eq!(attachments.len(), 1);
+assert_eq!(attachments[0].data, b"hello world".to_vec());
+
+let mut registry = AttachmentRegistry::default();
+registry.register(Box::new(attachments));
+assert_eq!(inline(&root_node, &registry).unwrap(), 1);
+assert_eq!(root_node.to_string(), "<image>aGVsbG8gd29ybGQ=</image>");
+```
+*/
+
+use crate::level2::convert::{as_document, as_element_mut};
+use crate::level2::ext::namespaced::element_namespace;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::*;
+use crate::shared::error::{Error, Result, MSG_ATTACHMENT_NOT_FOUND};
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI};
+use crate::shared::text::is_xml_space;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The XOP include namespace.
+///
+pub const XOP_NS: &str = "http://www.w3.org/2004/08/xop/include";
+
+///
+/// A binary part of an XOP package.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attachment {
+    /// The content ID of this part, without the `cid:` URI scheme.
+    pub content_id: String,
+    /// The binary content of this part.
+    pub data: Vec<u8>,
+}
+
+///
+/// Implemented by types that can provide the binary content of an XOP part given its content ID.
+///
+pub trait AttachmentProvider {
+    ///
+    /// Returns the binary content of the part with the content ID `content_id`, if known to this
+    /// provider.
+    ///
+    fn attachment(&self, content_id: &str) -> Option<Vec<u8>>;
+}
+
+///
+/// An ordered set of attachment providers; each is asked in the order in which it was
+/// registered until one returns the content of a part.
+///
+#[derive(Default)]
+pub struct AttachmentRegistry {
+    providers: Vec<Box<dyn AttachmentProvider>>,
+}
+
+///
+/// Options that control the behavior of [`optimize`](fn.optimize.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptimizeOptions {
+    /// Content smaller than this, in decoded bytes, is left in the document; the default is
+    /// 1024.
+    pub min_size: usize,
+    /// The domain part used to generate content IDs, the default is `xml_dom`.
+    pub content_id_domain: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Replace the content of each element within the tree rooted at `node` whose content is a single
+/// `Text` node of valid Base64 with an `xop:Include` element. The decoded binary content of each
+/// replaced element is returned as an `Attachment` with a newly generated content ID.
+///
+/// Elements that are already `xop:Include` elements are not changed.
+///
+pub fn optimize(node: &RefNode, options: &OptimizeOptions) -> Result<Vec<Attachment>> {
+    let mut attachments = Vec::new();
+    for element in elements(node) {
+        let children = element.child_nodes();
+        if children.len() != 1 || children[0].node_type() != NodeType::Text {
+            continue;
+        }
+        let text: String = children[0]
+            .node_value()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !is_xml_space(*c))
+            .collect();
+        let data = match base64::decode(&text) {
+            Ok(data) if !text.is_empty() && data.len() >= options.min_size => data,
+            _ => continue,
+        };
+        let content_id = format!(
+            "{}.{}@{}",
+            PART_PREFIX,
+            attachments.len() + 1,
+            options.content_id_domain
+        );
+        let include = create_include(&element, &content_id)?;
+        let mut element = element.clone();
+        let _safe_to_ignore = element.replace_child(include, children[0].clone())?;
+        attachments.push(Attachment { content_id, data });
+    }
+    Ok(attachments)
+}
+
+///
+/// Replace each `xop:Include` element within the tree rooted at `node` with the Base64 encoded
+/// content of the part it references; returns the number of elements replaced.
+///
+/// **Exceptions**
+///
+/// * `NOT_FOUND_ERR`: Raised if an `xop:Include` element has no `href` attribute, or no provider
+///   in `registry` has the content of the referenced part.
+///
+pub fn inline(node: &RefNode, registry: &AttachmentRegistry) -> Result<usize> {
+    let includes: Vec<RefNode> = elements(node).into_iter().filter(is_include).collect();
+    for include in &includes {
+        let href = include.get_attribute(HREF).unwrap_or_default();
+        let content_id = href.strip_prefix(CID_SCHEME).unwrap_or(&href);
+        let data = match registry.attachment(content_id) {
+            Some(data) => data,
+            None => {
+                warn!("{}: '{}'", MSG_ATTACHMENT_NOT_FOUND, href);
+                return Err(Error::NotFound);
+            }
+        };
+        let document_node = include.owner_document().ok_or(Error::NotFound)?;
+        let text = as_document(&document_node)?.create_text_node(&base64::encode(&data));
+        match include.parent_node() {
+            None => return Err(Error::NotFound),
+            Some(mut parent) => {
+                let _safe_to_ignore = parent.replace_child(text, include.clone())?;
+            }
+        }
+    }
+    Ok(includes.len())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl AttachmentProvider for Vec<Attachment> {
+    fn attachment(&self, content_id: &str) -> Option<Vec<u8>> {
+        self.iter()
+            .find(|attachment| attachment.content_id == content_id)
+            .map(|attachment| attachment.data.clone())
+    }
+}
+
+impl AttachmentProvider for HashMap<String, Vec<u8>> {
+    fn attachment(&self, content_id: &str) -> Option<Vec<u8>> {
+        self.get(content_id).cloned()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl AttachmentRegistry {
+    ///
+    /// Add `provider` to the end of this registry.
+    ///
+    pub fn register(&mut self, provider: Box<dyn AttachmentProvider>) {
+        self.providers.push(provider);
+    }
+
+    ///
+    /// Returns the number of providers in this registry.
+    ///
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    ///
+    /// Returns `true` if this registry has no providers.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+impl Debug for AttachmentRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AttachmentRegistry")
+            .field("providers", &self.providers.len())
+            .finish()
+    }
+}
+
+impl AttachmentProvider for AttachmentRegistry {
+    fn attachment(&self, content_id: &str) -> Option<Vec<u8>> {
+        self.providers
+            .iter()
+            .find_map(|provider| provider.attachment(content_id))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self {
+            min_size: 1024,
+            content_id_domain: DEFAULT_DOMAIN.to_string(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const XOP_PREFIX: &str = "xop";
+const INCLUDE: &str = "Include";
+const HREF: &str = "href";
+const CID_SCHEME: &str = "cid:";
+const PART_PREFIX: &str = "part";
+const DEFAULT_DOMAIN: &str = "xml_dom";
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// All elements in the tree rooted at `node`, in document order, including `node` itself.
+///
+fn elements(node: &RefNode) -> Vec<RefNode> {
+    let mut result = Vec::new();
+    if node.node_type() == NodeType::Element {
+        result.push(node.clone());
+    }
+    for child in node.child_nodes() {
+        result.extend(elements(&child));
+    }
+    result
+}
+
+fn is_include(element: &RefNode) -> bool {
+    element.node_name().local_name() == INCLUDE
+        && element_namespace(element).as_deref() == Some(XOP_NS)
+}
+
+fn create_include(context: &RefNode, content_id: &str) -> Result<RefNode> {
+    let document_node = context.owner_document().ok_or(Error::NotFound)?;
+    let document = as_document(&document_node)?;
+    let mut include = document.create_element_ns(XOP_NS, &format!("{}:{}", XOP_PREFIX, INCLUDE))?;
+    {
+        let element = as_element_mut(&mut include)?;
+        element.set_attribute_ns(
+            XMLNS_NS_URI,
+            &format!("{}:{}", XMLNS_NS_ATTRIBUTE, XOP_PREFIX),
+            XOP_NS,
+        )?;
+        element.set_attribute(HREF, &format!("{}{}", CID_SCHEME, content_id))?;
+    }
+    Ok(include)
+}
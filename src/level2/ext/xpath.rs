@@ -0,0 +1,1788 @@
+/*!
+Provides an [XPath 1.0](https://www.w3.org/TR/1999/REC-xpath-19991116/) query engine over
+`RefNode` trees.
+
+An expression may be evaluated directly with [`evaluate`](fn.evaluate.html), or compiled once with
+[`XPath::compile`](struct.XPath.html#method.compile) and evaluated many times. The result is a
+[`Value`](enum.Value.html): a node-set, in document order, a string, a number, or a boolean.
+
+All axes except the `namespace` axis are supported, along with the abbreviated syntax, predicates,
+variables, and the complete core function library.
+
+# Names and Namespaces
+
+A name test with a prefix, such as `dc:title`, matches by namespace URI if the prefix is bound in
+the [`XPathContext`](struct.XPathContext.html) used to evaluate the expression; otherwise it
+matches nodes with the same prefix and local name. As an implementation defined extension a name
+test without a prefix matches any node with no prefix and the same local name, regardless of any
+default namespace, in the same way as `get_elements_by_tag_name`.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::xpath::{evaluate, select, Value};
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("library"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for (id, title) in &[("a", "Emma"), ("b", "Persuasion")] {
+    let mut book_node = document.create_element("book").unwrap();
+    book_node.set_attribute("id", id).unwrap();
+    let mut title_node = document.create_element("title").unwrap();
+    let _safe_to_ignore = title_node.append_child(document.create_text_node(title)).unwrap();
+    let _safe_to_ignore = book_node.append_child(title_node).unwrap();
+    let _safe_to_ignore = root_node.append_child(book_node).unwrap();
+}
+
+let titles = select(&document_node, "//book[@id='b']/title").unwrap();
+assert_eq!(titles.len(), 1);
+assert_eq!(
+    evaluate(&document_node, "string(//book[@id='b']/title)").unwrap(),
+    Value::String("Persuasion".to_string())
+);
+assert_eq!(
+    evaluate(&document_node, "count(//book)").unwrap(),
+    Value::Number(2.0)
+);
+```
+*/
+
+use crate::level2::convert::{as_attribute, as_document, as_element};
+use crate::level2::ext::compare::sort_document_order;
+use crate::level2::ext::mutation::raw_value;
+use crate::level2::ext::namespaced::resolve_declared_prefix;
+use crate::level2::node_impl::{NodeImpl, RefNode};
+use crate::level2::traits::*;
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_XPATH, MSG_XPATH_FUNCTION, MSG_XPATH_NOT_NODE_SET,
+    MSG_XPATH_VARIABLE,
+};
+use crate::shared::name::Name;
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XML_NS_ATTRIBUTE, XML_NS_ATTR_LANG, XML_NS_URI};
+use crate::shared::text::is_xml_space;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The result of evaluating an XPath expression.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A set of nodes, without duplicates, in document order.
+    NodeSet(Vec<RefNode>),
+    /// A boolean value.
+    Boolean(bool),
+    /// A number, an IEEE 754 double.
+    Number(f64),
+    /// A string value.
+    String(String),
+}
+
+///
+/// The namespace and variable bindings available to an expression.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XPathContext {
+    /// Maps prefixes used in name tests to namespace URIs.
+    pub namespaces: HashMap<String, String>,
+    /// Maps variable names, without the leading `$`, to values.
+    pub variables: HashMap<String, Value>,
+}
+
+///
+/// A compiled XPath expression.
+///
+/// The `Display` implementation writes the original expression.
+///
+#[derive(Clone, Debug)]
+pub struct XPath {
+    source: String,
+    expression: Expr,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Compile and evaluate `expression` with `node` as the context node, and no namespace or
+/// variable bindings.
+///
+/// **Exceptions**
+///
+/// * `SYNTAX_ERR`: see [`XPath::evaluate`](struct.XPath.html#method.evaluate).
+///
+pub fn evaluate(node: &RefNode, expression: &str) -> Result<Value> {
+    XPath::compile(expression)?.evaluate(node, &XPathContext::default())
+}
+
+///
+/// Compile and evaluate `expression` with `node` as the context node, returning the resulting
+/// node-set.
+///
+/// **Exceptions**
+///
+/// * `SYNTAX_ERR`: see [`XPath::evaluate`](struct.XPath.html#method.evaluate), also raised if the
+///   expression does not result in a node-set.
+///
+pub fn select(node: &RefNode, expression: &str) -> Result<Vec<RefNode>> {
+    match evaluate(node, expression)? {
+        Value::NodeSet(nodes) => Ok(nodes),
+        _ => {
+            warn!("{}: '{}'", MSG_XPATH_NOT_NODE_SET, expression);
+            Err(Error::Syntax)
+        }
+    }
+}
+
+///
+/// As [`select`](fn.select.html), except that the resulting nodes are **not** in document order,
+/// see [`XPath::evaluate_unordered_fast`](struct.XPath.html#method.evaluate_unordered_fast).
+///
+/// **Exceptions**
+///
+/// * `SYNTAX_ERR`: see [`select`](fn.select.html).
+///
+pub fn select_unordered_fast(node: &RefNode, expression: &str) -> Result<Vec<RefNode>> {
+    match XPath::compile(expression)?.evaluate_unordered_fast(node, &XPathContext::default())? {
+        Value::NodeSet(nodes) => Ok(nodes),
+        _ => {
+            warn!("{}: '{}'", MSG_XPATH_NOT_NODE_SET, expression);
+            Err(Error::Syntax)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.as_string())
+    }
+}
+
+impl Value {
+    ///
+    /// Convert this value to a boolean, as the XPath `boolean()` function.
+    ///
+    pub fn as_boolean(&self) -> bool {
+        match self {
+            Value::NodeSet(nodes) => !nodes.is_empty(),
+            Value::Boolean(value) => *value,
+            Value::Number(value) => *value != 0.0 && !value.is_nan(),
+            Value::String(value) => !value.is_empty(),
+        }
+    }
+
+    ///
+    /// Convert this value to a number, as the XPath `number()` function.
+    ///
+    pub fn as_number(&self) -> f64 {
+        match self {
+            Value::Number(value) => *value,
+            Value::Boolean(value) => {
+                if *value {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            _ => string_to_number(&self.as_string()),
+        }
+    }
+
+    ///
+    /// Convert this value to a string, as the XPath `string()` function. The string value of a
+    /// node-set is that of its first node.
+    ///
+    pub fn as_string(&self) -> String {
+        match self {
+            Value::NodeSet(nodes) => nodes.first().map(string_value).unwrap_or_default(),
+            Value::Boolean(value) => value.to_string(),
+            Value::Number(value) => number_to_string(*value),
+            Value::String(value) => value.clone(),
+        }
+    }
+
+    ///
+    /// Returns the nodes in this value, if it is a node-set.
+    ///
+    pub fn as_node_set(&self) -> Option<&Vec<RefNode>> {
+        match self {
+            Value::NodeSet(nodes) => Some(nodes),
+            _ => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for XPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl XPath {
+    ///
+    /// Compile `expression` for later evaluation.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `expression` is not a valid XPath 1.0 expression, or calls an
+    ///   unknown function or a function with the wrong number of arguments.
+    ///
+    pub fn compile(expression: &str) -> Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, index: 0 };
+        let compiled = parser.parse_expr()?;
+        if parser.index != parser.tokens.len() {
+            warn!("{}: '{}'", MSG_INVALID_XPATH, expression);
+            return Err(Error::Syntax);
+        }
+        Ok(Self {
+            source: expression.to_string(),
+            expression: compiled,
+        })
+    }
+
+    ///
+    /// Evaluate this expression with `node` as the context node, using the namespace and
+    /// variable bindings in `context`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if the expression references a variable not bound in `context`, or
+    ///   applies a location step or predicate to a value that is not a node-set.
+    ///
+    pub fn evaluate(&self, node: &RefNode, context: &XPathContext) -> Result<Value> {
+        let evaluator = Evaluator { context };
+        evaluator.evaluate(
+            &self.expression,
+            &Focus {
+                node: node.clone(),
+                position: 1,
+                size: 1,
+            },
+        )
+    }
+
+    ///
+    /// As [`evaluate`](#method.evaluate), except that a resulting node-set is **not** in document
+    /// order; this avoids sorting the results of the expression's outermost location path or
+    /// union, and so is faster for large results where order does not matter. The same nodes are
+    /// returned, without duplicates.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: see [`evaluate`](#method.evaluate).
+    ///
+    pub fn evaluate_unordered_fast(&self, node: &RefNode, context: &XPathContext) -> Result<Value> {
+        let evaluator = Evaluator { context };
+        evaluator.evaluate_unordered(
+            &self.expression,
+            &Focus {
+                node: node.clone(),
+                position: 1,
+                size: 1,
+            },
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Parser {
+    fn parse_expr(&mut self) -> Result<Expr> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_and()?;
+        while self.next_is(&Token::Or) {
+            let rhs = self.parse_and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_equality()?;
+        while self.next_is(&Token::And) {
+            let rhs = self.parse_equality()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_equality(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_relational()?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Equal) => Comparison::Equal,
+                Some(Token::NotEqual) => Comparison::NotEqual,
+                _ => return Ok(lhs),
+            };
+            self.index += 1;
+            let rhs = self.parse_relational()?;
+            lhs = Expr::Compare(operator, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_relational(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_additive()?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Less) => Comparison::Less,
+                Some(Token::LessOrEqual) => Comparison::LessOrEqual,
+                Some(Token::Greater) => Comparison::Greater,
+                Some(Token::GreaterOrEqual) => Comparison::GreaterOrEqual,
+                _ => return Ok(lhs),
+            };
+            self.index += 1;
+            let rhs = self.parse_additive()?;
+            lhs = Expr::Compare(operator, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_multiplicative()?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Plus) => Arithmetic::Add,
+                Some(Token::Minus) => Arithmetic::Subtract,
+                _ => return Ok(lhs),
+            };
+            self.index += 1;
+            let rhs = self.parse_multiplicative()?;
+            lhs = Expr::Arithmetic(operator, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Multiply) => Arithmetic::Multiply,
+                Some(Token::Div) => Arithmetic::Divide,
+                Some(Token::Mod) => Arithmetic::Modulo,
+                _ => return Ok(lhs),
+            };
+            self.index += 1;
+            let rhs = self.parse_unary()?;
+            lhs = Expr::Arithmetic(operator, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.next_is(&Token::Minus) {
+            Ok(Expr::Negate(Box::new(self.parse_unary()?)))
+        } else {
+            self.parse_union()
+        }
+    }
+
+    fn parse_union(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_path()?;
+        while self.next_is(&Token::Pipe) {
+            let rhs = self.parse_path()?;
+            lhs = Expr::Union(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_path(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(Token::Variable(_))
+            | Some(Token::LeftParen)
+            | Some(Token::Literal(_))
+            | Some(Token::Number(_))
+            | Some(Token::FunctionName(_)) => {
+                let primary = self.parse_primary()?;
+                let predicates = self.parse_predicates()?;
+                let filter = if predicates.is_empty() {
+                    primary
+                } else {
+                    Expr::Filter(Box::new(primary), predicates)
+                };
+                let mut steps: Vec<Step> = Vec::new();
+                if self.next_is(&Token::Slash) {
+                    self.parse_relative_path(&mut steps)?;
+                } else if self.next_is(&Token::DoubleSlash) {
+                    steps.push(Step::descendant_or_self());
+                    self.parse_relative_path(&mut steps)?;
+                } else {
+                    return Ok(filter);
+                }
+                Ok(Expr::Path(PathStart::Filter(Box::new(filter)), steps))
+            }
+            Some(Token::Slash) => {
+                self.index += 1;
+                let mut steps: Vec<Step> = Vec::new();
+                if self.starts_step() {
+                    self.parse_relative_path(&mut steps)?;
+                }
+                Ok(Expr::Path(PathStart::Root, steps))
+            }
+            Some(Token::DoubleSlash) => {
+                self.index += 1;
+                let mut steps = vec![Step::descendant_or_self()];
+                self.parse_relative_path(&mut steps)?;
+                Ok(Expr::Path(PathStart::Root, steps))
+            }
+            _ => {
+                let mut steps: Vec<Step> = Vec::new();
+                self.parse_relative_path(&mut steps)?;
+                Ok(Expr::Path(PathStart::Context, steps))
+            }
+        }
+    }
+
+    fn parse_relative_path(&mut self, steps: &mut Vec<Step>) -> Result<()> {
+        steps.push(self.parse_step()?);
+        loop {
+            if self.next_is(&Token::Slash) {
+                steps.push(self.parse_step()?);
+            } else if self.next_is(&Token::DoubleSlash) {
+                steps.push(Step::descendant_or_self());
+                steps.push(self.parse_step()?);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn starts_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::NameTest(_, _))
+                | Some(Token::NodeType(_))
+                | Some(Token::AxisName(_))
+                | Some(Token::At)
+                | Some(Token::Dot)
+                | Some(Token::DotDot)
+        )
+    }
+
+    fn parse_step(&mut self) -> Result<Step> {
+        if self.next_is(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::SelfNode,
+                test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+        if self.next_is(&Token::DotDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+        let axis = if self.next_is(&Token::At) {
+            Axis::Attribute
+        } else if let Some(Token::AxisName(name)) = self.peek() {
+            let axis = Axis::from_name(name)?;
+            self.index += 1;
+            self.expect(&Token::ColonColon)?;
+            axis
+        } else {
+            Axis::Child
+        };
+        let test = match self.advance() {
+            Some(Token::NameTest(prefix, local_name)) => NodeTest::Name(prefix, local_name),
+            Some(Token::NodeType(node_type)) => {
+                self.expect(&Token::LeftParen)?;
+                let test = match node_type.as_str() {
+                    NODE_TYPE_COMMENT => NodeTest::Comment,
+                    NODE_TYPE_TEXT => NodeTest::Text,
+                    NODE_TYPE_NODE => NodeTest::Node,
+                    _ => match self.peek() {
+                        Some(Token::Literal(target)) => {
+                            let target = target.clone();
+                            self.index += 1;
+                            NodeTest::ProcessingInstruction(Some(target))
+                        }
+                        _ => NodeTest::ProcessingInstruction(None),
+                    },
+                };
+                self.expect(&Token::RightParen)?;
+                test
+            }
+            _ => return invalid_expression(),
+        };
+        Ok(Step {
+            axis,
+            test,
+            predicates: self.parse_predicates()?,
+        })
+    }
+
+    fn parse_predicates(&mut self) -> Result<Vec<Expr>> {
+        let mut predicates: Vec<Expr> = Vec::new();
+        while self.next_is(&Token::LeftBracket) {
+            predicates.push(self.parse_expr()?);
+            self.expect(&Token::RightBracket)?;
+        }
+        Ok(predicates)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        match self.advance() {
+            Some(Token::Variable(name)) => Ok(Expr::Variable(name)),
+            Some(Token::LeftParen) => {
+                let expr = self.parse_expr()?;
+                self.expect(&Token::RightParen)?;
+                Ok(expr)
+            }
+            Some(Token::Literal(value)) => Ok(Expr::Literal(value)),
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::FunctionName(name)) => {
+                self.expect(&Token::LeftParen)?;
+                let mut arguments: Vec<Expr> = Vec::new();
+                if !self.next_is(&Token::RightParen) {
+                    loop {
+                        arguments.push(self.parse_expr()?);
+                        if self.next_is(&Token::RightParen) {
+                            break;
+                        }
+                        self.expect(&Token::Comma)?;
+                    }
+                }
+                let function = Function::from_name(&name, arguments.len())?;
+                Ok(Expr::Function(function, arguments))
+            }
+            _ => invalid_expression(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        if token.is_some() {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn next_is(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<()> {
+        if self.next_is(token) {
+            Ok(())
+        } else {
+            invalid_expression()
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Step {
+    fn descendant_or_self() -> Self {
+        Self {
+            axis: Axis::DescendantOrSelf,
+            test: NodeTest::Node,
+            predicates: Vec::new(),
+        }
+    }
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Result<Self> {
+        Ok(match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "namespace" => Axis::Namespace,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::SelfNode,
+            _ => return invalid_expression(),
+        })
+    }
+
+    fn is_reverse(&self) -> bool {
+        matches!(
+            self,
+            Axis::Ancestor | Axis::AncestorOrSelf | Axis::Preceding | Axis::PrecedingSibling
+        )
+    }
+}
+
+impl Function {
+    fn from_name(name: &str, arguments: usize) -> Result<Self> {
+        let (function, min, max) = match name {
+            "last" => (Function::Last, 0, 0),
+            "position" => (Function::Position, 0, 0),
+            "count" => (Function::Count, 1, 1),
+            "id" => (Function::Id, 1, 1),
+            "local-name" => (Function::LocalName, 0, 1),
+            "namespace-uri" => (Function::NamespaceUri, 0, 1),
+            "name" => (Function::Name, 0, 1),
+            "string" => (Function::String, 0, 1),
+            "concat" => (Function::Concat, 2, usize::MAX),
+            "starts-with" => (Function::StartsWith, 2, 2),
+            "contains" => (Function::Contains, 2, 2),
+            "substring-before" => (Function::SubstringBefore, 2, 2),
+            "substring-after" => (Function::SubstringAfter, 2, 2),
+            "substring" => (Function::Substring, 2, 3),
+            "string-length" => (Function::StringLength, 0, 1),
+            "normalize-space" => (Function::NormalizeSpace, 0, 1),
+            "translate" => (Function::Translate, 3, 3),
+            "boolean" => (Function::Boolean, 1, 1),
+            "not" => (Function::Not, 1, 1),
+            "true" => (Function::True, 0, 0),
+            "false" => (Function::False, 0, 0),
+            "lang" => (Function::Lang, 1, 1),
+            "number" => (Function::Number, 0, 1),
+            "sum" => (Function::Sum, 1, 1),
+            "floor" => (Function::Floor, 1, 1),
+            "ceiling" => (Function::Ceiling, 1, 1),
+            "round" => (Function::Round, 1, 1),
+            _ => {
+                warn!("{}: '{}'", MSG_XPATH_FUNCTION, name);
+                return Err(Error::Syntax);
+            }
+        };
+        if arguments < min || arguments > max {
+            warn!("{}: '{}'", MSG_XPATH_FUNCTION, name);
+            Err(Error::Syntax)
+        } else {
+            Ok(function)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Evaluator<'_> {
+    fn evaluate(&self, expr: &Expr, focus: &Focus) -> Result<Value> {
+        match expr {
+            Expr::Or(lhs, rhs) => Ok(Value::Boolean(
+                self.evaluate(lhs, focus)?.as_boolean() || self.evaluate(rhs, focus)?.as_boolean(),
+            )),
+            Expr::And(lhs, rhs) => Ok(Value::Boolean(
+                self.evaluate(lhs, focus)?.as_boolean() && self.evaluate(rhs, focus)?.as_boolean(),
+            )),
+            Expr::Compare(operator, lhs, rhs) => Ok(Value::Boolean(compare_values(
+                *operator,
+                &self.evaluate(lhs, focus)?,
+                &self.evaluate(rhs, focus)?,
+            ))),
+            Expr::Arithmetic(operator, lhs, rhs) => {
+                let lhs = self.evaluate(lhs, focus)?.as_number();
+                let rhs = self.evaluate(rhs, focus)?.as_number();
+                Ok(Value::Number(match operator {
+                    Arithmetic::Add => lhs + rhs,
+                    Arithmetic::Subtract => lhs - rhs,
+                    Arithmetic::Multiply => lhs * rhs,
+                    Arithmetic::Divide => lhs / rhs,
+                    Arithmetic::Modulo => lhs % rhs,
+                }))
+            }
+            Expr::Negate(operand) => Ok(Value::Number(-self.evaluate(operand, focus)?.as_number())),
+            Expr::Union(lhs, rhs) => {
+                let mut nodes = self.evaluate_node_set(lhs, focus)?;
+                nodes.extend(self.evaluate_node_set(rhs, focus)?);
+                Ok(Value::NodeSet(document_order(nodes)))
+            }
+            Expr::Path(start, steps) => {
+                let mut nodes = match start {
+                    PathStart::Root => vec![root_of(&focus.node)],
+                    PathStart::Context => vec![focus.node.clone()],
+                    PathStart::Filter(filter) => self.evaluate_node_set(filter, focus)?,
+                };
+                for step in steps {
+                    nodes = self.evaluate_step(step, &nodes, true)?;
+                }
+                Ok(Value::NodeSet(nodes))
+            }
+            Expr::Filter(primary, predicates) => {
+                let mut nodes = self.evaluate_node_set(primary, focus)?;
+                for predicate in predicates {
+                    nodes = self.filter(nodes, predicate)?;
+                }
+                Ok(Value::NodeSet(nodes))
+            }
+            Expr::Literal(value) => Ok(Value::String(value.clone())),
+            Expr::Number(value) => Ok(Value::Number(*value)),
+            Expr::Variable(name) => match self.context.variables.get(name) {
+                Some(value) => Ok(value.clone()),
+                None => {
+                    warn!("{}: '{}'", MSG_XPATH_VARIABLE, name);
+                    Err(Error::Syntax)
+                }
+            },
+            Expr::Function(function, arguments) => {
+                self.evaluate_function(*function, arguments, focus)
+            }
+        }
+    }
+
+    fn evaluate_node_set(&self, expr: &Expr, focus: &Focus) -> Result<Vec<RefNode>> {
+        match self.evaluate(expr, focus)? {
+            Value::NodeSet(nodes) => Ok(nodes),
+            _ => {
+                warn!("{}", MSG_XPATH_NOT_NODE_SET);
+                Err(Error::Syntax)
+            }
+        }
+    }
+
+    ///
+    /// As `evaluate`, except that if the result of `expr` is a node-set its nodes are in no
+    /// particular order. Only the outermost union and location path of `expr` are affected, any
+    /// nested expression, such as a predicate or function argument, is still evaluated in document
+    /// order.
+    ///
+    fn evaluate_unordered(&self, expr: &Expr, focus: &Focus) -> Result<Value> {
+        match expr {
+            Expr::Union(lhs, rhs) => {
+                let mut nodes = self.evaluate_unordered_node_set(lhs, focus)?;
+                nodes.extend(self.evaluate_unordered_node_set(rhs, focus)?);
+                Ok(Value::NodeSet(distinct(nodes)))
+            }
+            Expr::Path(start, steps) => {
+                let mut nodes = match start {
+                    PathStart::Root => vec![root_of(&focus.node)],
+                    PathStart::Context => vec![focus.node.clone()],
+                    PathStart::Filter(filter) => self.evaluate_node_set(filter, focus)?,
+                };
+                for step in steps {
+                    nodes = self.evaluate_step(step, &nodes, false)?;
+                }
+                Ok(Value::NodeSet(nodes))
+            }
+            _ => self.evaluate(expr, focus),
+        }
+    }
+
+    fn evaluate_unordered_node_set(&self, expr: &Expr, focus: &Focus) -> Result<Vec<RefNode>> {
+        match self.evaluate_unordered(expr, focus)? {
+            Value::NodeSet(nodes) => Ok(nodes),
+            _ => {
+                warn!("{}", MSG_XPATH_NOT_NODE_SET);
+                Err(Error::Syntax)
+            }
+        }
+    }
+
+    ///
+    /// Evaluate `step` for each of `nodes`, returning the union of the results; in document order
+    /// if `ordered` is `true`.
+    ///
+    fn evaluate_step(&self, step: &Step, nodes: &[RefNode], ordered: bool) -> Result<Vec<RefNode>> {
+        let mut results: Vec<RefNode> = Vec::new();
+        for node in nodes {
+            let mut selected: Vec<RefNode> = axis_nodes(&step.axis, node)
+                .into_iter()
+                .filter(|candidate| self.matches(&step.test, &step.axis, candidate))
+                .collect();
+            for predicate in &step.predicates {
+                selected = self.filter(selected, predicate)?;
+            }
+            results.extend(selected);
+        }
+        if !ordered {
+            Ok(if nodes.len() > 1 {
+                distinct(results)
+            } else {
+                results
+            })
+        } else if nodes.len() > 1 || step.axis.is_reverse() {
+            Ok(document_order(results))
+        } else {
+            Ok(results)
+        }
+    }
+
+    ///
+    /// Filter `nodes`, which are in the order of the axis that selected them, by `predicate`.
+    ///
+    fn filter(&self, nodes: Vec<RefNode>, predicate: &Expr) -> Result<Vec<RefNode>> {
+        let size = nodes.len();
+        let mut results: Vec<RefNode> = Vec::new();
+        for (index, node) in nodes.into_iter().enumerate() {
+            let focus = Focus {
+                node,
+                position: index + 1,
+                size,
+            };
+            let keep = match self.evaluate(predicate, &focus)? {
+                Value::Number(position) => position == focus.position as f64,
+                value => value.as_boolean(),
+            };
+            if keep {
+                results.push(focus.node);
+            }
+        }
+        Ok(results)
+    }
+
+    fn matches(&self, test: &NodeTest, axis: &Axis, node: &RefNode) -> bool {
+        let node_type = node.node_type();
+        match test {
+            NodeTest::Node => true,
+            NodeTest::Text => node_type == NodeType::Text || node_type == NodeType::CData,
+            NodeTest::Comment => node_type == NodeType::Comment,
+            NodeTest::ProcessingInstruction(target) => {
+                node_type == NodeType::ProcessingInstruction
+                    && target
+                        .as_ref()
+                        .map(|target| &node.node_name().to_string() == target)
+                        .unwrap_or(true)
+            }
+            NodeTest::Name(prefix, local_name) => {
+                let principal = if *axis == Axis::Attribute {
+                    NodeType::Attribute
+                } else {
+                    NodeType::Element
+                };
+                if node_type != principal {
+                    return false;
+                }
+                let name = node.node_name();
+                if let Some(local_name) = local_name {
+                    if name.local_name() != local_name {
+                        return false;
+                    }
+                }
+                match prefix {
+                    None => local_name.is_none() || name.prefix().is_none(),
+                    Some(prefix) => match self.context.namespaces.get(prefix) {
+                        Some(namespace_uri) => {
+                            node_namespace_uri(node).as_ref() == Some(namespace_uri)
+                        }
+                        None => name.prefix().as_ref() == Some(prefix),
+                    },
+                }
+            }
+        }
+    }
+
+    fn evaluate_function(
+        &self,
+        function: Function,
+        arguments: &[Expr],
+        focus: &Focus,
+    ) -> Result<Value> {
+        let string_argument = |index: usize| -> Result<String> {
+            match arguments.get(index) {
+                Some(argument) => Ok(self.evaluate(argument, focus)?.as_string()),
+                None => Ok(string_value(&focus.node)),
+            }
+        };
+        let node_argument = |index: usize| -> Result<Option<RefNode>> {
+            match arguments.get(index) {
+                Some(argument) => Ok(self.evaluate_node_set(argument, focus)?.into_iter().next()),
+                None => Ok(Some(focus.node.clone())),
+            }
+        };
+        let number_argument = |index: usize| -> Result<f64> {
+            Ok(self.evaluate(&arguments[index], focus)?.as_number())
+        };
+        Ok(match function {
+            Function::Last => Value::Number(focus.size as f64),
+            Function::Position => Value::Number(focus.position as f64),
+            Function::Count => {
+                Value::Number(self.evaluate_node_set(&arguments[0], focus)?.len() as f64)
+            }
+            Function::Id => {
+                let ids = match self.evaluate(&arguments[0], focus)? {
+                    Value::NodeSet(nodes) => nodes
+                        .iter()
+                        .map(string_value)
+                        .collect::<Vec<String>>()
+                        .join(" "),
+                    value => value.as_string(),
+                };
+                let document_node = root_of(&focus.node);
+                let mut nodes: Vec<RefNode> = Vec::new();
+                if let Ok(document) = as_document(&document_node) {
+                    for id in ids.split(is_xml_space).filter(|id| !id.is_empty()) {
+                        if let Some(element) = document.get_element_by_id(id) {
+                            nodes.push(element);
+                        }
+                    }
+                }
+                Value::NodeSet(document_order(nodes))
+            }
+            Function::LocalName => Value::String(
+                node_argument(0)?
+                    .filter(has_expanded_name)
+                    .map(|node| node.node_name().local_name().clone())
+                    .unwrap_or_default(),
+            ),
+            Function::NamespaceUri => Value::String(
+                node_argument(0)?
+                    .filter(has_expanded_name)
+                    .and_then(|node| node_namespace_uri(&node))
+                    .unwrap_or_default(),
+            ),
+            Function::Name => Value::String(
+                node_argument(0)?
+                    .filter(has_expanded_name)
+                    .map(|node| node.node_name().to_string())
+                    .unwrap_or_default(),
+            ),
+            Function::String => Value::String(string_argument(0)?),
+            Function::Concat => {
+                let mut result = String::new();
+                for index in 0..arguments.len() {
+                    result.push_str(&string_argument(index)?);
+                }
+                Value::String(result)
+            }
+            Function::StartsWith => {
+                Value::Boolean(string_argument(0)?.starts_with(&string_argument(1)?))
+            }
+            Function::Contains => {
+                Value::Boolean(string_argument(0)?.contains(&string_argument(1)?))
+            }
+            Function::SubstringBefore => {
+                let value = string_argument(0)?;
+                Value::String(match value.find(&string_argument(1)?) {
+                    Some(index) => value[..index].to_string(),
+                    None => String::new(),
+                })
+            }
+            Function::SubstringAfter => {
+                let value = string_argument(0)?;
+                let pattern = string_argument(1)?;
+                Value::String(match value.find(&pattern) {
+                    Some(index) => value[index + pattern.len()..].to_string(),
+                    None => String::new(),
+                })
+            }
+            Function::Substring => {
+                let value = string_argument(0)?;
+                let start = round(number_argument(1)?);
+                let end = if arguments.len() > 2 {
+                    start + round(number_argument(2)?)
+                } else {
+                    f64::INFINITY
+                };
+                Value::String(
+                    value
+                        .chars()
+                        .enumerate()
+                        .filter(|(index, _)| {
+                            let position = (index + 1) as f64;
+                            position >= start && position < end
+                        })
+                        .map(|(_, c)| c)
+                        .collect(),
+                )
+            }
+            Function::StringLength => Value::Number(string_argument(0)?.chars().count() as f64),
+            Function::NormalizeSpace => Value::String(
+                string_argument(0)?
+                    .split(is_xml_space)
+                    .filter(|word| !word.is_empty())
+                    .collect::<Vec<&str>>()
+                    .join(" "),
+            ),
+            Function::Translate => {
+                let from: Vec<char> = string_argument(1)?.chars().collect();
+                let to: Vec<char> = string_argument(2)?.chars().collect();
+                Value::String(
+                    string_argument(0)?
+                        .chars()
+                        .filter_map(|c| match from.iter().position(|f| *f == c) {
+                            Some(index) => to.get(index).copied(),
+                            None => Some(c),
+                        })
+                        .collect(),
+                )
+            }
+            Function::Boolean => Value::Boolean(self.evaluate(&arguments[0], focus)?.as_boolean()),
+            Function::Not => Value::Boolean(!self.evaluate(&arguments[0], focus)?.as_boolean()),
+            Function::True => Value::Boolean(true),
+            Function::False => Value::Boolean(false),
+            Function::Lang => {
+                let language = string_argument(0)?.to_lowercase();
+                Value::Boolean(match node_language(&focus.node) {
+                    Some(node_language) => {
+                        let node_language = node_language.to_lowercase();
+                        node_language == language
+                            || node_language.starts_with(&format!("{}-", language))
+                    }
+                    None => false,
+                })
+            }
+            Function::Number => Value::Number(match arguments.first() {
+                Some(argument) => self.evaluate(argument, focus)?.as_number(),
+                None => string_to_number(&string_value(&focus.node)),
+            }),
+            Function::Sum => Value::Number(
+                self.evaluate_node_set(&arguments[0], focus)?
+                    .iter()
+                    .map(|node| string_to_number(&string_value(node)))
+                    .sum(),
+            ),
+            Function::Floor => Value::Number(number_argument(0)?.floor()),
+            Function::Ceiling => Value::Number(number_argument(0)?.ceil()),
+            Function::Round => Value::Number(round(number_argument(0)?)),
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const NODE_TYPE_COMMENT: &str = "comment";
+const NODE_TYPE_TEXT: &str = "text";
+const NODE_TYPE_NODE: &str = "node";
+const NODE_TYPE_PI: &str = "processing-instruction";
+
+const OPERATOR_AND: &str = "and";
+const OPERATOR_OR: &str = "or";
+const OPERATOR_MOD: &str = "mod";
+const OPERATOR_DIV: &str = "div";
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    And,
+    Or,
+    Mod,
+    Div,
+    Multiply,
+    Literal(String),
+    Number(f64),
+    Variable(String),
+    /// An optional prefix, and a local name which is `None` for the wild card `*`.
+    NameTest(Option<String>, Option<String>),
+    NodeType(String),
+    FunctionName(String),
+    AxisName(String),
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(Comparison, Box<Expr>, Box<Expr>),
+    Arithmetic(Arithmetic, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Union(Box<Expr>, Box<Expr>),
+    Path(PathStart, Vec<Step>),
+    Filter(Box<Expr>, Vec<Expr>),
+    Literal(String),
+    Number(f64),
+    Variable(String),
+    Function(Function, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Arithmetic {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+#[derive(Clone, Debug)]
+enum PathStart {
+    Root,
+    Context,
+    Filter(Box<Expr>),
+}
+
+#[derive(Clone, Debug)]
+struct Step {
+    axis: Axis,
+    test: NodeTest,
+    predicates: Vec<Expr>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Namespace,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    SelfNode,
+}
+
+#[derive(Clone, Debug)]
+enum NodeTest {
+    Name(Option<String>, Option<String>),
+    Node,
+    Text,
+    Comment,
+    ProcessingInstruction(Option<String>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Last,
+    Position,
+    Count,
+    Id,
+    LocalName,
+    NamespaceUri,
+    Name,
+    String,
+    Concat,
+    StartsWith,
+    Contains,
+    SubstringBefore,
+    SubstringAfter,
+    Substring,
+    StringLength,
+    NormalizeSpace,
+    Translate,
+    Boolean,
+    Not,
+    True,
+    False,
+    Lang,
+    Number,
+    Sum,
+    Floor,
+    Ceiling,
+    Round,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+struct Evaluator<'a> {
+    context: &'a XPathContext,
+}
+
+///
+/// The context node, position, and size used to evaluate an expression.
+///
+struct Focus {
+    node: RefNode,
+    position: usize,
+    size: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn invalid_expression<T>() -> Result<T> {
+    warn!("{}", MSG_INVALID_XPATH);
+    Err(Error::Syntax)
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        //
+        // If there is a preceding token, and it is not one of these, then `*` is the multiply
+        // operator and a name is an operator name (XPath 1.0 §3.7).
+        //
+        let operator_position = match tokens.last() {
+            None => false,
+            Some(token) => !matches!(
+                token,
+                Token::At
+                    | Token::ColonColon
+                    | Token::LeftParen
+                    | Token::LeftBracket
+                    | Token::Comma
+                    | Token::And
+                    | Token::Or
+                    | Token::Mod
+                    | Token::Div
+                    | Token::Multiply
+                    | Token::Slash
+                    | Token::DoubleSlash
+                    | Token::Pipe
+                    | Token::Plus
+                    | Token::Minus
+                    | Token::Equal
+                    | Token::NotEqual
+                    | Token::Less
+                    | Token::LessOrEqual
+                    | Token::Greater
+                    | Token::GreaterOrEqual
+            ),
+        };
+        let token = match c {
+            c if is_xml_space(c) => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            '|' => Token::Pipe,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '=' => Token::Equal,
+            ':' if next_char_is(&mut chars, ':') => Token::ColonColon,
+            '/' if next_char_is(&mut chars, '/') => Token::DoubleSlash,
+            '/' => Token::Slash,
+            '!' if next_char_is(&mut chars, '=') => Token::NotEqual,
+            '<' if next_char_is(&mut chars, '=') => Token::LessOrEqual,
+            '<' => Token::Less,
+            '>' if next_char_is(&mut chars, '=') => Token::GreaterOrEqual,
+            '>' => Token::Greater,
+            '*' if operator_position => Token::Multiply,
+            '*' => Token::NameTest(None, None),
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, end)) if end == c => break,
+                        Some((_, c)) => value.push(c),
+                        None => return invalid_expression(),
+                    }
+                }
+                Token::Literal(value)
+            }
+            '.' if next_char_is(&mut chars, '.') => Token::DotDot,
+            '.' if !matches!(chars.peek(), Some((_, c)) if c.is_ascii_digit()) => Token::Dot,
+            c if c == '.' || c.is_ascii_digit() => {
+                let mut end = start + c.len_utf8();
+                while let Some((index, c)) = chars.peek() {
+                    if c.is_ascii_digit() || *c == '.' {
+                        end = index + c.len_utf8();
+                        let _safe_to_ignore = chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let number = string_to_number(&expression[start..end]);
+                if number.is_nan() {
+                    return invalid_expression();
+                }
+                Token::Number(number)
+            }
+            '$' => {
+                let name = match chars.next() {
+                    Some((start, c)) if is_name_start_char(c) => {
+                        scan_qname(expression, &mut chars, start, c)
+                    }
+                    _ => return invalid_expression(),
+                };
+                match name {
+                    (name, false) => Token::Variable(name),
+                    _ => return invalid_expression(),
+                }
+            }
+            c if is_name_start_char(c) => {
+                let (name, wild_card) = scan_qname(expression, &mut chars, start, c);
+                if operator_position {
+                    match name.as_str() {
+                        OPERATOR_AND => Token::And,
+                        OPERATOR_OR => Token::Or,
+                        OPERATOR_MOD => Token::Mod,
+                        OPERATOR_DIV => Token::Div,
+                        _ => return invalid_expression(),
+                    }
+                } else if wild_card {
+                    Token::NameTest(Some(name), None)
+                } else {
+                    let rest = expression[start + name.len()..].trim_start_matches(is_xml_space);
+                    if rest.starts_with("::") {
+                        Token::AxisName(name)
+                    } else if rest.starts_with('(') {
+                        match name.as_str() {
+                            NODE_TYPE_COMMENT | NODE_TYPE_TEXT | NODE_TYPE_NODE | NODE_TYPE_PI => {
+                                Token::NodeType(name)
+                            }
+                            _ => Token::FunctionName(name),
+                        }
+                    } else {
+                        match name.split_once(':') {
+                            Some((prefix, local_name)) => Token::NameTest(
+                                Some(prefix.to_string()),
+                                Some(local_name.to_string()),
+                            ),
+                            None => Token::NameTest(None, Some(name)),
+                        }
+                    }
+                }
+            }
+            _ => return invalid_expression(),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn next_char_is(chars: &mut Peekable<CharIndices<'_>>, expected: char) -> bool {
+    if matches!(chars.peek(), Some((_, c)) if *c == expected) {
+        let _safe_to_ignore = chars.next();
+        true
+    } else {
+        false
+    }
+}
+
+fn is_name_start_char(c: char) -> bool {
+    c != ':' && crate::shared::text::is_xml_name_start_char(c)
+}
+
+fn is_name_char(c: char) -> bool {
+    c != ':' && crate::shared::text::is_xml_name_char(c)
+}
+
+///
+/// Scan a QName, or an NCName followed by `:*`, starting with `first` at `start`. Returns the
+/// name, which for the wild card form is only the prefix, and `true` for the wild card form.
+///
+fn scan_qname(
+    expression: &str,
+    chars: &mut Peekable<CharIndices<'_>>,
+    start: usize,
+    first: char,
+) -> (String, bool) {
+    let mut end = start + first.len_utf8();
+    let mut seen_colon = false;
+    while let Some((index, c)) = chars.peek().copied() {
+        if is_name_char(c) {
+            end = index + c.len_utf8();
+            let _safe_to_ignore = chars.next();
+        } else if c == ':' && !seen_colon {
+            //
+            // Only consume the colon if it is followed by a name or `*`, and is not part of `::`.
+            //
+            let rest = &expression[index + 1..];
+            match rest.chars().next() {
+                Some('*') => {
+                    let _safe_to_ignore = chars.next();
+                    let _safe_to_ignore = chars.next();
+                    return (expression[start..end].to_string(), true);
+                }
+                Some(next) if is_name_start_char(next) => {
+                    seen_colon = true;
+                    end = index + 1;
+                    let _safe_to_ignore = chars.next();
+                }
+                _ => break,
+            }
+        } else {
+            break;
+        }
+    }
+    (expression[start..end].to_string(), false)
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the nodes on `axis` from `node`, in axis order; reverse axes are in reverse document
+/// order.
+///
+fn axis_nodes(axis: &Axis, node: &RefNode) -> Vec<RefNode> {
+    match axis {
+        Axis::SelfNode => vec![node.clone()],
+        Axis::Child => node.child_nodes(),
+        Axis::Parent => parent_of(node).into_iter().collect(),
+        Axis::Attribute => attributes_of(node),
+        Axis::Namespace => Vec::new(),
+        Axis::Ancestor | Axis::AncestorOrSelf => {
+            let mut nodes: Vec<RefNode> = Vec::new();
+            if *axis == Axis::AncestorOrSelf {
+                nodes.push(node.clone());
+            }
+            let mut current = parent_of(node);
+            while let Some(ancestor) = current {
+                current = parent_of(&ancestor);
+                nodes.push(ancestor);
+            }
+            nodes
+        }
+        Axis::Descendant | Axis::DescendantOrSelf => {
+            let mut nodes: Vec<RefNode> = Vec::new();
+            if *axis == Axis::DescendantOrSelf {
+                nodes.push(node.clone());
+            }
+            push_descendants(node, &mut nodes);
+            nodes
+        }
+        Axis::FollowingSibling | Axis::PrecedingSibling => {
+            if node.node_type() == NodeType::Attribute {
+                return Vec::new();
+            }
+            match parent_of(node) {
+                None => Vec::new(),
+                Some(parent) => {
+                    let siblings = parent.child_nodes();
+                    let index = siblings.iter().position(|sibling| sibling == node).unwrap();
+                    if *axis == Axis::FollowingSibling {
+                        siblings[index + 1..].to_vec()
+                    } else {
+                        siblings[..index].iter().rev().cloned().collect()
+                    }
+                }
+            }
+        }
+        Axis::Following => {
+            let mut nodes: Vec<RefNode> = Vec::new();
+            let mut current = node.clone();
+            if current.node_type() == NodeType::Attribute {
+                match parent_of(&current) {
+                    Some(element) => {
+                        push_descendants(&element, &mut nodes);
+                        current = element;
+                    }
+                    None => return nodes,
+                }
+            }
+            loop {
+                for sibling in axis_nodes(&Axis::FollowingSibling, &current) {
+                    nodes.push(sibling.clone());
+                    push_descendants(&sibling, &mut nodes);
+                }
+                match parent_of(&current) {
+                    Some(parent) => current = parent,
+                    None => return nodes,
+                }
+            }
+        }
+        Axis::Preceding => {
+            let mut nodes: Vec<RefNode> = Vec::new();
+            let mut current = match node.node_type() {
+                NodeType::Attribute => match parent_of(node) {
+                    Some(element) => element,
+                    None => return nodes,
+                },
+                _ => node.clone(),
+            };
+            loop {
+                for sibling in axis_nodes(&Axis::PrecedingSibling, &current) {
+                    let mut descendants: Vec<RefNode> = vec![sibling];
+                    push_descendants(&descendants[0].clone(), &mut descendants);
+                    nodes.extend(descendants.into_iter().rev());
+                }
+                match parent_of(&current) {
+                    Some(parent) => current = parent,
+                    None => return nodes,
+                }
+            }
+        }
+    }
+}
+
+fn push_descendants(node: &RefNode, nodes: &mut Vec<RefNode>) {
+    for child in node.child_nodes() {
+        nodes.push(child.clone());
+        push_descendants(&child, nodes);
+    }
+}
+
+///
+/// The XPath parent of `node`; for an attribute this is its owner element.
+///
+fn parent_of(node: &RefNode) -> Option<RefNode> {
+    match as_attribute(node) {
+        Ok(attribute) => attribute.owner_element(),
+        Err(_) => node.parent_node(),
+    }
+}
+
+///
+/// The attributes of `node`, excluding namespace declarations, sorted by name.
+///
+fn attributes_of(node: &RefNode) -> Vec<RefNode> {
+    if node.node_type() != NodeType::Element {
+        return Vec::new();
+    }
+    let mut attributes: Vec<(Name, RefNode)> = node
+        .attributes()
+        .into_iter()
+        .filter(|(name, _)| !is_namespace_declaration(name))
+        .collect();
+    attributes.sort_by_key(|(name, _)| name.to_string());
+    attributes
+        .into_iter()
+        .map(|(_, attribute)| attribute)
+        .collect()
+}
+
+fn is_namespace_declaration(name: &Name) -> bool {
+    match name.prefix() {
+        Some(prefix) => prefix == XMLNS_NS_ATTRIBUTE,
+        None => name.local_name() == XMLNS_NS_ATTRIBUTE,
+    }
+}
+
+fn root_of(node: &RefNode) -> RefNode {
+    let mut current = node.clone();
+    while let Some(parent) = parent_of(&current) {
+        current = parent;
+    }
+    current
+}
+
+///
+/// Sort `nodes` into document order, removing any duplicates.
+///
+fn document_order(mut nodes: Vec<RefNode>) -> Vec<RefNode> {
+    sort_document_order(&mut nodes);
+    nodes
+}
+
+///
+/// Remove any duplicates from `nodes`, retaining the first occurrence of each.
+///
+fn distinct(nodes: Vec<RefNode>) -> Vec<RefNode> {
+    let mut seen: HashSet<*const RefCell<NodeImpl>> = HashSet::with_capacity(nodes.len());
+    nodes
+        .into_iter()
+        .filter(|node| seen.insert(node.as_inner().as_ptr() as *const _))
+        .collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The XPath string-value of `node`.
+///
+fn string_value(node: &RefNode) -> String {
+    match node.node_type() {
+        NodeType::Document | NodeType::DocumentFragment | NodeType::Element => {
+            let mut descendants: Vec<RefNode> = Vec::new();
+            push_descendants(node, &mut descendants);
+            descendants
+                .iter()
+                .filter(|descendant| {
+                    matches!(descendant.node_type(), NodeType::Text | NodeType::CData)
+                })
+                .filter_map(|text| text.node_value())
+                .collect()
+        }
+        NodeType::Attribute => raw_value(node),
+        _ => node.node_value().unwrap_or_default(),
+    }
+}
+
+fn has_expanded_name(node: &RefNode) -> bool {
+    matches!(
+        node.node_type(),
+        NodeType::Element | NodeType::Attribute | NodeType::ProcessingInstruction
+    )
+}
+
+fn node_namespace_uri(node: &RefNode) -> Option<String> {
+    let name = node.node_name();
+    if let Some(namespace_uri) = name.namespace_uri() {
+        return Some(namespace_uri.clone());
+    }
+    match (node.node_type(), name.prefix()) {
+        (_, Some(prefix)) if prefix == XML_NS_ATTRIBUTE => Some(XML_NS_URI.to_string()),
+        (NodeType::Element, prefix) => resolve_declared_prefix(node, prefix.as_deref()),
+        (NodeType::Attribute, Some(prefix)) => {
+            parent_of(node).and_then(|element| resolve_declared_prefix(&element, Some(prefix)))
+        }
+        _ => None,
+    }
+}
+
+///
+/// The value of the nearest `xml:lang` attribute on `node` or its ancestors.
+///
+fn node_language(node: &RefNode) -> Option<String> {
+    let mut current = Some(node.clone());
+    while let Some(node) = current {
+        if let Ok(element) = as_element(&node) {
+            let language = element.attributes().into_iter().find(|(name, _)| {
+                name.local_name() == XML_NS_ATTR_LANG
+                    && name.prefix().as_deref() == Some(XML_NS_ATTRIBUTE)
+            });
+            if let Some((_, attribute)) = language {
+                return Some(raw_value(&attribute));
+            }
+        }
+        current = parent_of(&node);
+    }
+    None
+}
+
+// ------------------------------------------------------------------------------------------------
+
+fn compare_values(operator: Comparison, lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::NodeSet(lhs), Value::NodeSet(rhs)) => {
+            let rhs: Vec<String> = rhs.iter().map(string_value).collect();
+            lhs.iter().map(string_value).any(|lhs| {
+                rhs.iter().any(|rhs| {
+                    compare_atomic(
+                        operator,
+                        &Value::String(lhs.clone()),
+                        &Value::String(rhs.clone()),
+                    )
+                })
+            })
+        }
+        (Value::NodeSet(nodes), Value::Boolean(_)) => {
+            compare_atomic(operator, &Value::Boolean(!nodes.is_empty()), rhs)
+        }
+        (Value::Boolean(_), Value::NodeSet(nodes)) => {
+            compare_atomic(operator, lhs, &Value::Boolean(!nodes.is_empty()))
+        }
+        (Value::NodeSet(nodes), other) => nodes
+            .iter()
+            .any(|node| compare_atomic(operator, &node_as(other, node), other)),
+        (other, Value::NodeSet(nodes)) => nodes
+            .iter()
+            .any(|node| compare_atomic(operator, other, &node_as(other, node))),
+        _ => compare_atomic(operator, lhs, rhs),
+    }
+}
+
+///
+/// Convert the string-value of `node` to the same type as `other`, for comparison.
+///
+fn node_as(other: &Value, node: &RefNode) -> Value {
+    match other {
+        Value::Number(_) => Value::Number(string_to_number(&string_value(node))),
+        _ => Value::String(string_value(node)),
+    }
+}
+
+fn compare_atomic(operator: Comparison, lhs: &Value, rhs: &Value) -> bool {
+    match operator {
+        Comparison::Equal | Comparison::NotEqual => {
+            let equal = match (lhs, rhs) {
+                (Value::Boolean(_), _) | (_, Value::Boolean(_)) => {
+                    lhs.as_boolean() == rhs.as_boolean()
+                }
+                (Value::Number(_), _) | (_, Value::Number(_)) => lhs.as_number() == rhs.as_number(),
+                _ => lhs.as_string() == rhs.as_string(),
+            };
+            equal == (operator == Comparison::Equal)
+        }
+        _ => {
+            let ordering = lhs.as_number().partial_cmp(&rhs.as_number());
+            match (operator, ordering) {
+                (_, None) => false,
+                (Comparison::Less, Some(ordering)) => ordering == Ordering::Less,
+                (Comparison::LessOrEqual, Some(ordering)) => ordering != Ordering::Greater,
+                (Comparison::Greater, Some(ordering)) => ordering == Ordering::Greater,
+                (_, Some(ordering)) => ordering != Ordering::Less,
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Convert a string to a number following the XPath `Number` production, with optional leading
+/// minus sign and surrounding white space; any other string is `NaN`.
+///
+fn string_to_number(value: &str) -> f64 {
+    let value = value.trim_matches(is_xml_space);
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let mut parts = digits.splitn(2, '.');
+    let whole = parts.next().unwrap_or_default();
+    let fraction = parts.next();
+    let valid = whole.chars().all(|c| c.is_ascii_digit())
+        && fraction
+            .into_iter()
+            .all(|f| f.chars().all(|c| c.is_ascii_digit()))
+        && !(whole.is_empty() && fraction.into_iter().all(str::is_empty));
+    if valid {
+        value.parse::<f64>().unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
+
+fn number_to_string(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 {
+            "Infinity".to_string()
+        } else {
+            "-Infinity".to_string()
+        }
+    } else if value == 0.0 {
+        "0".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn round(value: f64) -> f64 {
+    if value.is_nan() || value.is_infinite() {
+        value
+    } else if (-0.5..0.0).contains(&value) {
+        -0.0
+    } else {
+        (value + 0.5).floor()
+    }
+}
@@ -0,0 +1,886 @@
+/*!
+This module provides support types for the [`XsdContent`](trait.XsdContent.html) trait, which
+converts the text content and attribute values of elements to and from values of the XML Schema
+built-in types `dateTime`, `date`, `duration`, `decimal`, and `boolean`. It also manages the
+`xsi:nil` attribute, which marks an element as having no value; the text of a nil element is read
+as `None`, as data-binding layers interpret it.
+
+Parsing follows the lexical space of each type as defined in
+[XML Schema Part 2: Datatypes](https://www.w3.org/TR/xmlschema-2/#built-in-primitive-datatypes),
+after collapsing surrounding white space; formatting always produces the canonical
+representation.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::ext::xsd::{XsdDateTime, XsdDecimal};
+use std::str::FromStr;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("order"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+
+root_node.set_attribute("placed", "2002-10-10T12:00:00-05:00").unwrap();
+let placed = root_node.get_attribute_xsd_datetime("placed").unwrap().unwrap();
+assert_eq!(placed.hour, 12);
+assert_eq!(placed.timezone, Some(-300));
+
+root_node.set_text_xsd(&XsdDecimal::from_str("+010.50").unwrap()).unwrap();
+assert_eq!(root_node.to_string(), r#"<order placed="2002-10-10T12:00:00-05:00">10.5</order>"#);
+```
+*/
+
+use crate::level2::ext::namespaced::resolve_declared_prefix;
+use crate::level2::ext::traits::XsdContent;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Attribute, Document, Element, Node, NodeType};
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_NODE_TYPE, MSG_INVALID_XSD_VALUE, MSG_PREFIX_REBOUND,
+};
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI};
+use crate::shared::text::is_xml_space;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The XML Schema instance namespace, which defines the `xsi:nil` attribute.
+///
+pub const XSI_NS: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+///
+/// Implemented by the types that represent values of an XML Schema built-in type, converting
+/// between the value and its lexical representation.
+///
+pub trait XsdType: Sized {
+    ///
+    /// Parse `value`, which must be in the lexical space of the type; leading and trailing white
+    /// space is ignored.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `value` is not in the lexical space of the type.
+    ///
+    fn from_lexical(value: &str) -> Result<Self>;
+    ///
+    /// Returns the canonical lexical representation of this value.
+    ///
+    fn to_lexical(&self) -> String;
+}
+
+///
+/// A value of the XML Schema type [`date`](https://www.w3.org/TR/xmlschema-2/#date).
+///
+/// ```ebnf
+/// date ::= '-'? yyyy '-' mm '-' dd zzzzzz?
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct XsdDate {
+    /// The year, which may be negative and may have more than four digits.
+    pub year: i32,
+    /// The month, `1` to `12`.
+    pub month: u8,
+    /// The day of the month, `1` to the number of days in `month`.
+    pub day: u8,
+    /// The timezone as an offset from UTC in minutes, if present.
+    pub timezone: Option<i16>,
+}
+
+///
+/// A value of the XML Schema type [`dateTime`](https://www.w3.org/TR/xmlschema-2/#dateTime).
+///
+/// ```ebnf
+/// dateTime ::= '-'? yyyy '-' mm '-' dd 'T' hh ':' mm ':' ss ('.' s+)? zzzzzz?
+/// ```
+///
+/// The end-of-day value `24:00:00` is not supported.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct XsdDateTime {
+    /// The year, which may be negative and may have more than four digits.
+    pub year: i32,
+    /// The month, `1` to `12`.
+    pub month: u8,
+    /// The day of the month, `1` to the number of days in `month`.
+    pub day: u8,
+    /// The hour, `0` to `23`.
+    pub hour: u8,
+    /// The minute, `0` to `59`.
+    pub minute: u8,
+    /// The second, `0` to `59`.
+    pub second: u8,
+    /// The fractional second, in nanoseconds; additional digits are truncated.
+    pub nanosecond: u32,
+    /// The timezone as an offset from UTC in minutes, if present.
+    pub timezone: Option<i16>,
+}
+
+///
+/// A value of the XML Schema type [`duration`](https://www.w3.org/TR/xmlschema-2/#duration).
+///
+/// ```ebnf
+/// duration ::= '-'? 'P' (n 'Y')? (n 'M')? (n 'D')? ('T' (n 'H')? (n 'M')? (n ('.' n)? 'S')?)?
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XsdDuration {
+    /// `true` if this is a negative duration.
+    pub negative: bool,
+    /// The number of years.
+    pub years: u32,
+    /// The number of months.
+    pub months: u32,
+    /// The number of days.
+    pub days: u32,
+    /// The number of hours.
+    pub hours: u32,
+    /// The number of minutes.
+    pub minutes: u32,
+    /// The number of whole seconds.
+    pub seconds: u32,
+    /// The fractional second, in nanoseconds; additional digits are truncated.
+    pub nanoseconds: u32,
+}
+
+///
+/// A value of the XML Schema type [`decimal`](https://www.w3.org/TR/xmlschema-2/#decimal); the
+/// digits are kept so that no precision is lost.
+///
+/// ```ebnf
+/// decimal ::= ('+' | '-')? (d+ ('.' d*)? | '.' d+)
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct XsdDecimal {
+    negative: bool,
+    integer: String,
+    fraction: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl XsdType for bool {
+    fn from_lexical(value: &str) -> Result<Self> {
+        match collapse(value) {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => invalid(value),
+        }
+    }
+
+    fn to_lexical(&self) -> String {
+        self.to_string()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for XsdDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_date(f, self.year, self.month, self.day)?;
+        write_timezone(f, self.timezone)
+    }
+}
+
+impl FromStr for XsdDate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let value = collapse(s);
+        let (year, month, day, rest) = match parse_date(value) {
+            Some(date) => date,
+            None => return invalid(s),
+        };
+        match parse_timezone(rest) {
+            Some(timezone) => Ok(Self {
+                year,
+                month,
+                day,
+                timezone,
+            }),
+            None => invalid(s),
+        }
+    }
+}
+
+impl XsdType for XsdDate {
+    fn from_lexical(value: &str) -> Result<Self> {
+        Self::from_str(value)
+    }
+
+    fn to_lexical(&self) -> String {
+        self.to_string()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for XsdDateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_date(f, self.year, self.month, self.day)?;
+        write!(f, "T{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        write_nanoseconds(f, self.nanosecond)?;
+        write_timezone(f, self.timezone)
+    }
+}
+
+impl FromStr for XsdDateTime {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let value = collapse(s);
+        let (year, month, day, rest) = match parse_date(value) {
+            Some(date) => date,
+            None => return invalid(s),
+        };
+        let rest = match rest.strip_prefix('T') {
+            Some(rest) => rest,
+            None => return invalid(s),
+        };
+        let time_end = rest.find(['Z', '+', '-']).unwrap_or(rest.len());
+        match (
+            parse_time(&rest[..time_end]),
+            parse_timezone(&rest[time_end..]),
+        ) {
+            (Some((hour, minute, second, nanosecond)), Some(timezone)) => Ok(Self {
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+                nanosecond,
+                timezone,
+            }),
+            _ => invalid(s),
+        }
+    }
+}
+
+impl XsdType for XsdDateTime {
+    fn from_lexical(value: &str) -> Result<Self> {
+        Self::from_str(value)
+    }
+
+    fn to_lexical(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl XsdDateTime {
+    ///
+    /// Returns the date component of this value.
+    ///
+    pub fn date(&self) -> XsdDate {
+        XsdDate {
+            year: self.year,
+            month: self.month,
+            day: self.day,
+            timezone: self.timezone,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for XsdDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.is_zero() {
+            return write!(f, "PT0S");
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "P")?;
+        for (value, designator) in &[(self.years, 'Y'), (self.months, 'M'), (self.days, 'D')] {
+            if *value > 0 {
+                write!(f, "{}{}", value, designator)?;
+            }
+        }
+        if self.hours > 0 || self.minutes > 0 || self.seconds > 0 || self.nanoseconds > 0 {
+            write!(f, "T")?;
+            for (value, designator) in &[(self.hours, 'H'), (self.minutes, 'M')] {
+                if *value > 0 {
+                    write!(f, "{}{}", value, designator)?;
+                }
+            }
+            if self.seconds > 0 || self.nanoseconds > 0 {
+                write!(f, "{}", self.seconds)?;
+                write_nanoseconds(f, self.nanoseconds)?;
+                write!(f, "S")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for XsdDuration {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let value = collapse(s);
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+        let value = match value.strip_prefix('P') {
+            Some(value) => value,
+            None => return invalid(s),
+        };
+        let (date, time) = match value.find('T') {
+            Some(index) => (&value[..index], Some(&value[index + 1..])),
+            None => (value, None),
+        };
+        let date = parse_components(date, &['Y', 'M', 'D']);
+        let time = match time {
+            Some(time) if !time.is_empty() => parse_components(time, &['H', 'M', 'S']),
+            Some(_) => None,
+            None => Some(Vec::new()),
+        };
+        match (date, time) {
+            (Some(date), Some(time)) if !date.is_empty() || !time.is_empty() => {
+                let mut duration = XsdDuration {
+                    negative,
+                    ..Default::default()
+                };
+                for (designator, whole, fraction) in date {
+                    match designator {
+                        'Y' => duration.years = whole,
+                        'M' => duration.months = whole,
+                        _ => duration.days = whole,
+                    }
+                    if fraction.is_some() {
+                        return invalid(s);
+                    }
+                }
+                for (designator, whole, fraction) in time {
+                    match designator {
+                        'H' => duration.hours = whole,
+                        'M' => duration.minutes = whole,
+                        _ => duration.seconds = whole,
+                    }
+                    if let Some(fraction) = fraction {
+                        if designator != 'S' {
+                            return invalid(s);
+                        }
+                        duration.nanoseconds = fraction;
+                    }
+                }
+                Ok(duration)
+            }
+            _ => invalid(s),
+        }
+    }
+}
+
+impl XsdType for XsdDuration {
+    fn from_lexical(value: &str) -> Result<Self> {
+        Self::from_str(value)
+    }
+
+    fn to_lexical(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl XsdDuration {
+    ///
+    /// Returns `true` if every component of this duration is zero.
+    ///
+    pub fn is_zero(&self) -> bool {
+        self.years == 0
+            && self.months == 0
+            && self.days == 0
+            && self.hours == 0
+            && self.minutes == 0
+            && self.seconds == 0
+            && self.nanoseconds == 0
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for XsdDecimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        if self.integer.is_empty() {
+            write!(f, "0")?;
+        } else {
+            write!(f, "{}", self.integer)?;
+        }
+        if !self.fraction.is_empty() {
+            write!(f, ".{}", self.fraction)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for XsdDecimal {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let value = collapse(s);
+        let (negative, value) = if let Some(value) = value.strip_prefix('-') {
+            (true, value)
+        } else if let Some(value) = value.strip_prefix('+') {
+            (false, value)
+        } else {
+            (false, value)
+        };
+        let (integer, fraction) = match value.find('.') {
+            Some(index) => (&value[..index], &value[index + 1..]),
+            None => (value, ""),
+        };
+        if (integer.is_empty() && fraction.is_empty())
+            || !integer.chars().all(|c| c.is_ascii_digit())
+            || !fraction.chars().all(|c| c.is_ascii_digit())
+        {
+            return invalid(s);
+        }
+        let integer = integer.trim_start_matches('0').to_string();
+        let fraction = fraction.trim_end_matches('0').to_string();
+        Ok(Self {
+            negative: negative && !(integer.is_empty() && fraction.is_empty()),
+            integer,
+            fraction,
+        })
+    }
+}
+
+impl XsdType for XsdDecimal {
+    fn from_lexical(value: &str) -> Result<Self> {
+        Self::from_str(value)
+    }
+
+    fn to_lexical(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl From<i64> for XsdDecimal {
+    fn from(value: i64) -> Self {
+        Self::from_str(&value.to_string()).unwrap()
+    }
+}
+
+impl XsdDecimal {
+    ///
+    /// Returns `true` if this value is less than zero.
+    ///
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    ///
+    /// Returns the closest `f64` to this value; precision may be lost.
+    ///
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or_default()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl XsdContent for RefNode {
+    fn is_nil(&self) -> bool {
+        nil_attribute(self)
+            .and_then(|attribute| attribute.value())
+            .map(|value| bool::from_lexical(&value) == Ok(true))
+            .unwrap_or(false)
+    }
+
+    fn set_nil(&mut self, nil: bool) -> Result<()> {
+        if self.node_type() != NodeType::Element {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        if let Some(attribute) = nil_attribute(self) {
+            let _safe_to_ignore = self.remove_attribute_node(attribute)?;
+        }
+        if nil {
+            match resolve_declared_prefix(self, Some(XSI_PREFIX)) {
+                None => self.set_attribute_ns(
+                    XMLNS_NS_URI,
+                    &format!("{}:{}", XMLNS_NS_ATTRIBUTE, XSI_PREFIX),
+                    XSI_NS,
+                )?,
+                Some(namespace_uri) if namespace_uri == XSI_NS => {}
+                Some(namespace_uri) => {
+                    warn!("{}: '{}'", MSG_PREFIX_REBOUND, namespace_uri);
+                    return Err(Error::Namespace);
+                }
+            }
+            for child in self.child_nodes() {
+                let _safe_to_ignore = self.remove_child(child)?;
+            }
+            self.set_attribute_ns(
+                XSI_NS,
+                &format!("{}:{}", XSI_PREFIX, XSI_NIL),
+                &true.to_lexical(),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn text_xsd<T: XsdType>(&self) -> Result<Option<T>> {
+        if self.node_type() != NodeType::Element {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        if self.is_nil() {
+            Ok(None)
+        } else {
+            T::from_lexical(&text_content(self)).map(Some)
+        }
+    }
+
+    fn set_text_xsd<T: XsdType>(&mut self, value: &T) -> Result<()> {
+        if self.node_type() != NodeType::Element {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let document = self.owner_document().ok_or(Error::NotFound)?;
+        self.set_nil(false)?;
+        for child in self.child_nodes() {
+            let _safe_to_ignore = self.remove_child(child)?;
+        }
+        let _safe_to_ignore = self.append_child(document.create_text_node(&value.to_lexical()))?;
+        Ok(())
+    }
+
+    fn get_attribute_xsd<T: XsdType>(&self, name: &str) -> Result<Option<T>> {
+        match self.get_attribute(name) {
+            None => Ok(None),
+            Some(value) => T::from_lexical(&value).map(Some),
+        }
+    }
+
+    fn set_attribute_xsd<T: XsdType>(&mut self, name: &str, value: &T) -> Result<()> {
+        self.set_attribute(name, &value.to_lexical())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const XSI_PREFIX: &str = "xsi";
+
+const XSI_NIL: &str = "nil";
+
+const NANOSECOND_DIGITS: usize = 9;
+
+const MAX_TIMEZONE_MINUTES: i16 = 14 * 60;
+
+fn invalid<T>(value: &str) -> Result<T> {
+    warn!("{}: '{}'", MSG_INVALID_XSD_VALUE, value);
+    Err(Error::Syntax)
+}
+
+fn collapse(value: &str) -> &str {
+    value.trim_matches(is_xml_space)
+}
+
+///
+/// The `xsi:nil` attribute of `element`, identified either by its namespace or by a prefix
+/// declared for the XML Schema instance namespace.
+///
+fn nil_attribute(element: &RefNode) -> Option<RefNode> {
+    element
+        .attributes()
+        .into_iter()
+        .find(|(name, _)| {
+            name.local_name() == XSI_NIL
+                && match (name.namespace_uri(), name.prefix()) {
+                    (Some(namespace_uri), _) => namespace_uri == XSI_NS,
+                    (None, Some(prefix)) => {
+                        resolve_declared_prefix(element, Some(prefix)).as_deref() == Some(XSI_NS)
+                    }
+                    (None, None) => false,
+                }
+        })
+        .map(|(_, attribute)| attribute)
+}
+
+fn text_content(node: &RefNode) -> String {
+    node.child_nodes()
+        .iter()
+        .filter(|child| child.node_type() == NodeType::Text || child.node_type() == NodeType::CData)
+        .filter_map(|child| child.node_value())
+        .collect()
+}
+
+fn parse_fixed(value: &str, length: usize) -> Option<u32> {
+    if value.len() == length && value.chars().all(|c| c.is_ascii_digit()) {
+        value.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    year.rem_euclid(4) == 0 && (year.rem_euclid(100) != 0 || year.rem_euclid(400) == 0)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+///
+/// Parse `'-'? yyyy '-' mm '-' dd`, returning the remainder of `value` following the date.
+///
+fn parse_date(value: &str) -> Option<(i32, u8, u8, &str)> {
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, value),
+    };
+    let year_length = unsigned.find(|c: char| !c.is_ascii_digit())?;
+    let year_digits = &unsigned[..year_length];
+    if year_length < 4 || (year_length > 4 && year_digits.starts_with('0')) {
+        return None;
+    }
+    let rest = &unsigned[year_length..];
+    if rest.len() < 6 || !rest.is_char_boundary(6) {
+        return None;
+    }
+    let (month_day, rest) = rest.split_at(6);
+    if !month_day.starts_with('-') || month_day.as_bytes()[3] != b'-' {
+        return None;
+    }
+    let year: i32 = year_digits.parse().ok()?;
+    let year = if negative { -year } else { year };
+    let month = parse_fixed(&month_day[1..3], 2)? as u8;
+    let day = parse_fixed(&month_day[4..6], 2)? as u8;
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    Some((year, month, day, rest))
+}
+
+///
+/// Parse `hh ':' mm ':' ss ('.' s+)?`.
+///
+fn parse_time(value: &str) -> Option<(u8, u8, u8, u32)> {
+    if value.len() < 8 || !value.is_char_boundary(8) {
+        return None;
+    }
+    let (time, fraction) = value.split_at(8);
+    let bytes = time.as_bytes();
+    if bytes[2] != b':' || bytes[5] != b':' {
+        return None;
+    }
+    let hour = parse_fixed(&time[0..2], 2)? as u8;
+    let minute = parse_fixed(&time[3..5], 2)? as u8;
+    let second = parse_fixed(&time[6..8], 2)? as u8;
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let nanosecond = if fraction.is_empty() {
+        0
+    } else {
+        parse_fraction(fraction.strip_prefix('.')?)?
+    };
+    Some((hour, minute, second, nanosecond))
+}
+
+///
+/// Parse the digits following a decimal point as a number of nanoseconds.
+///
+fn parse_fraction(digits: &str) -> Option<u32> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let digits: String = digits
+        .chars()
+        .chain(std::iter::repeat('0'))
+        .take(NANOSECOND_DIGITS)
+        .collect();
+    digits.parse().ok()
+}
+
+///
+/// Parse `(('+' | '-') hh ':' mm) | 'Z'`, or nothing, as an offset in minutes.
+///
+fn parse_timezone(value: &str) -> Option<Option<i16>> {
+    let (sign, offset) = match value.chars().next() {
+        None => return Some(None),
+        Some('Z') if value.len() == 1 => return Some(Some(0)),
+        Some('+') => (1, &value[1..]),
+        Some('-') => (-1, &value[1..]),
+        _ => return None,
+    };
+    if offset.len() != 5 || offset.as_bytes()[2] != b':' {
+        return None;
+    }
+    let hours = parse_fixed(&offset[0..2], 2)? as i16;
+    let minutes = parse_fixed(&offset[3..5], 2)? as i16;
+    let total = hours * 60 + minutes;
+    if minutes > 59 || total > MAX_TIMEZONE_MINUTES {
+        return None;
+    }
+    Some(Some(sign * total))
+}
+
+///
+/// Parse a sequence of `n designator` components, where the designators must appear in the order
+/// given by `designators`; only the last designator may have a fractional value.
+///
+fn parse_components(value: &str, designators: &[char]) -> Option<Vec<(char, u32, Option<u32>)>> {
+    let mut components = Vec::new();
+    let mut next_designator = 0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let (number, tail) = rest.split_at(end);
+        let designator = tail.chars().next()?;
+        let index = designators[next_designator..]
+            .iter()
+            .position(|d| *d == designator)?
+            + next_designator;
+        let (whole, fraction) = match number.find('.') {
+            Some(point) if index == designators.len() - 1 => (
+                &number[..point],
+                Some(parse_fraction(&number[point + 1..])?),
+            ),
+            Some(_) => return None,
+            None => (number, None),
+        };
+        if whole.is_empty() || !whole.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        components.push((designator, whole.parse().ok()?, fraction));
+        next_designator = index + 1;
+        rest = &tail[designator.len_utf8()..];
+    }
+    Some(components)
+}
+
+fn write_date(f: &mut Formatter<'_>, year: i32, month: u8, day: u8) -> FmtResult {
+    if year < 0 {
+        write!(f, "-")?;
+    }
+    write!(f, "{:04}-{:02}-{:02}", year.abs(), month, day)
+}
+
+fn write_nanoseconds(f: &mut Formatter<'_>, nanoseconds: u32) -> FmtResult {
+    if nanoseconds > 0 {
+        let digits = format!("{:09}", nanoseconds);
+        write!(f, ".{}", digits.trim_end_matches('0'))
+    } else {
+        Ok(())
+    }
+}
+
+fn write_timezone(f: &mut Formatter<'_>, timezone: Option<i16>) -> FmtResult {
+    match timezone {
+        None => Ok(()),
+        Some(0) => write!(f, "Z"),
+        Some(offset) => write!(
+            f,
+            "{}{:02}:{:02}",
+            if offset < 0 { '-' } else { '+' },
+            offset.abs() / 60,
+            offset.abs() % 60
+        ),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_time_lexical() {
+        let value = XsdDateTime::from_str(" -12345-02-29T23:59:59.1200Z\n");
+        assert_eq!(value, Err(Error::Syntax));
+
+        let value = XsdDateTime::from_str("2000-02-29T23:59:59.1200+14:00").unwrap();
+        assert_eq!(value.nanosecond, 120_000_000);
+        assert_eq!(value.to_string(), "2000-02-29T23:59:59.12+14:00");
+
+        for invalid in &[
+            "2001-02-29T00:00:00",
+            "2000-1-01T00:00:00",
+            "02000-01-01T00:00:00",
+            "2000-01-01T24:00:00",
+            "2000-01-01T00:00:00.",
+            "2000-01-01T00:00:00+14:01",
+            "2000-01-01",
+        ] {
+            assert!(XsdDateTime::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_date_lexical() {
+        let value = XsdDate::from_str("-0044-03-15-05:30").unwrap();
+        assert_eq!(value.year, -44);
+        assert_eq!(value.timezone, Some(-330));
+        assert_eq!(value.to_string(), "-0044-03-15-05:30");
+        assert!(XsdDate::from_str("2000-03-15T00:00:00").is_err());
+    }
+
+    #[test]
+    fn test_duration_lexical() {
+        let value = XsdDuration::from_str("-P1Y2M3DT10H30M0.5S").unwrap();
+        assert!(value.negative);
+        assert_eq!(value.nanoseconds, 500_000_000);
+        assert_eq!(value.to_string(), "-P1Y2M3DT10H30M0.5S");
+        assert_eq!(XsdDuration::from_str("P0D").unwrap().to_string(), "PT0S");
+        assert_eq!(XsdDuration::from_str("PT36H").unwrap().hours, 36);
+
+        for invalid in &["P", "PT", "P1D2Y", "P1.5D", "1Y", "P-1D", "PT1H1H"] {
+            assert!(XsdDuration::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_decimal_lexical() {
+        for (lexical, canonical) in &[
+            ("+0010.500", "10.5"),
+            ("-.5", "-0.5"),
+            ("-0.0", "0"),
+            ("3.", "3"),
+            (
+                "123456789012345678901234567890",
+                "123456789012345678901234567890",
+            ),
+        ] {
+            assert_eq!(
+                XsdDecimal::from_str(lexical).unwrap().to_string(),
+                *canonical
+            );
+        }
+        for invalid in &["", ".", "1e5", "--1", "1.2.3"] {
+            assert!(XsdDecimal::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_boolean_lexical() {
+        assert_eq!(bool::from_lexical(" 1 "), Ok(true));
+        assert_eq!(bool::from_lexical("false"), Ok(false));
+        assert_eq!(bool::from_lexical("TRUE"), Err(Error::Syntax));
+    }
+}
@@ -16,9 +16,9 @@ Implementation for DOM Core Level 2.
 | `Element`               | [`Element`](level2/trait.Element.html)                             |
 | `Entity`                | [`Entity`](level2/trait.Entity.html)                               |
 | `EntityReference`       | [`EntityReference`](level2/trait.EntityReference.html)             |
-| `NamedNodeMap`          | `HashMap<Name, RefNode>`                                    |
+| `NamedNodeMap`          | `HashMap<Name, RefNode>`, or the live [`NamedNodeMap`](level2/ext/named_node_map/struct.NamedNodeMap.html) |
 | `Node`                  | [`Node`](level2/trait.Node.html)                                   |
-| `NodeList`              | `Vec<Rc<RefNode>>`                                          |
+| `NodeList`              | `Vec<Rc<RefNode>>`, or the live [`NodeList`](level2/ext/node_list/struct.NodeList.html) |
 | `Notation`              | [`Notation`](level2/trait.Notation.html)                           |
 | `ProcessingInstruction` | [`ProcessingInstruction`](level2/trait.ProcessingInstruction.html) |
 | `Text`                  | [`Text`](level2/trait.Text.html)                                   |
@@ -27,7 +27,8 @@ Implementation for DOM Core Level 2.
 
 The `has_feature` method on [`DOMImplementation`](trait.DOMImplementation.html) and `is_supported` on
 [`Node`](trait.Node.html) will return true when the request is for support of the Core or XML
-feature and supports both version 1.0 and version 2.0 of Core and version 1.0 of XML.
+feature and supports both version 1.0 and version 2.0 of Core and version 1.0 of XML. Feature names
+are compared without regard to case, and an empty version matches any supported version.
 
 ```rust
 use xml_dom::level2::{DOMImplementation, get_implementation};
@@ -39,6 +40,32 @@ assert!(implementation.has_feature("XML", "1.0"));
 assert!(implementation.has_feature("XML", "2.0"));
 ```
 
+# Traversal
+
+The [`traversal`](traversal/index.html) module implements the `NodeIterator` and `TreeWalker`
+interfaces from the DOM Level 2 Traversal specification, and `has_feature` will also return true for the Traversal
+feature, version 2.0.
+
+# Node Handles
+
+A [`RefNode`](type.RefNode.html) is a reference counted handle, and a node remains valid for as
+long as any handle to it is held; no operation invalidates a handle, and no accessor panics because
+of what has happened to the tree since the handle was obtained. A parent holds its children, and an
+element its attributes, but a node holds only weak references to its parent and owner document.
+Handles therefore behave as follows.
+
+* A node removed from the tree is _detached_, not destroyed: its `parent_node` is `None`, it keeps
+  its own subtree, and it may be modified and re-inserted anywhere in its document.
+* When the last handle to a document is dropped the document, and any node held only by it, is
+  freed. Nodes still held by the application remain valid, along with their subtrees, but their
+  `owner_document` is `None` and they are no longer attached; they may be read, modified, and
+  inserted into another document, which they then adopt. Operations that need the owner document itself, such as `set_attribute`, return
+  `Error::InvalidState` rather than panicking.
+* [`Node::is_attached`](trait.Node.html#tymethod.is_attached) returns `true` only if a node can
+  be reached from its owner document, and that document is still alive, so an application holding
+  handles, for example the result of a query, may check each before use; see also
+  [Mutation During Iteration](ext/query/index.html#mutation-during-iteration).
+
 # Extensions
 
 The following extensions are provided beyond the DOM Level 2 specification, all extensions are in the
@@ -49,6 +76,10 @@ The following extensions are provided beyond the DOM Level 2 specification, all
    requirement from the specification: _"The DOM Level 2 API does not define a standard way to
    create DOMImplementation objects; DOM implementations must provide some proprietary way of
    bootstrapping these DOM interfaces, and then all other objects can be built from there."_.
+1. The [`Implementation`](dom_impl/struct.Implementation.html) type may also be configured, with
+   processing options, additional ID attributes, additional features reported by `has_feature`
+   and `is_supported`, and a resolver, and used to create documents directly; each document
+   carries the implementation that created it, returned by `Document::implementation`.
 1. The [`get_implementation_version`](dom_impl/fn.get_implementation_version.html) function in the
    [`dom_impl`](dom_impl/index.html) module returns a vendor-specific version identifier for the
    `DOMImplementation`.
@@ -57,15 +88,146 @@ The following extensions are provided beyond the DOM Level 2 specification, all
    and associated [`ProcessingOptions`](options/struct.ProcessingOptions.html) structure, that can set
    optional behavior for a given `Document` instance.
 1. The trait [`DocumentDecl`](trait.DocumentDecl.html) extends `Document` with the ability to set
-   and retrieve the XML declaration from the document's prolog.
+   and retrieve the XML declaration from the document's prolog, as a whole or by its version,
+   encoding, and standalone values.
+1. The trait [`DocumentTypeDecl`](trait.DocumentTypeDecl.html) extends `DocumentType` with the
+   ability to declare notations, entities (including unparsed entities), parameter entities, and
+   conditional sections in the document type's internal subset, and to read and add its attribute
+   definitions, including those of type `ID` used by `get_element_by_id`, and those with default
+   values, which are added to elements as attributes that are not `specified`; see the
+   [`dtd`](ext/dtd/index.html) module.
+1. The trait [`DocumentEntities`](trait.DocumentEntities.html) extends `Document` with the ability
+   to look up the entities and notations declared by its document type by name.
 1. The trait [`Namespaced`](trait.Namespaced.html) extends `Element` with the ability to look-up
-   namespace mappings (using the standard `xmlns` attribute).
+   namespace mappings (using the standard `xmlns` attribute), including the DOM Level 3
+   `lookupNamespaceURI`, `lookupPrefix`, and `isDefaultNamespace` methods, which may be called on
+   any node, and to declare a number of namespaces on an element in one call. The in-scope
+   declarations at a node may be captured as a [`NamespaceContext`](ext/namespaced/struct.NamespaceContext.html)
+   and supplied when serializing, or parsing, a fragment detached from its document.
+1. The trait [`NodeUserData`](trait.NodeUserData.html) provides the DOM Level 3 `setUserData`
+   and `getUserData` methods, with handlers called when a node is cloned, imported, or renamed by
+   [`DocumentRename`](trait.DocumentRename.html), see the [`user_data`](ext/user_data/index.html)
+   module.
+1. The traits [`ElementId`](trait.ElementId.html) and [`AttributeId`](trait.AttributeId.html)
+   provide the DOM Level 3 `setIdAttribute`, `setIdAttributeNS`, `setIdAttributeNode`, and
+   `isId` members, allowing specific attributes to be used by `get_element_by_id`.
+1. The trait [`TextWhitespace`](trait.TextWhitespace.html) provides the DOM Level 3
+   `isElementContentWhitespace` attribute, using the element declarations of the document type
+   where they are available.
+1. The trait [`NodeOwner`](trait.NodeOwner.html) extends `Node` with the ability to check that a
+   node, and its subtree, is owned by a given document; the `strict_owner_document` and
+   `auto_import` [`ProcessingOptions`](ext/options/struct.ProcessingOptions.html) apply this check
+   when nodes are inserted.
+1. The trait [`ElementText`](trait.ElementText.html) extends `Element` with the ability to return
+   its text with entity and character references expanded, as a parser with entity expansion
+   turned on would have produced it.
+1. The trait [`NodeLists`](trait.NodeLists.html) extends `Node` with the ability to return live
+   [`NodeList`](ext/node_list/struct.NodeList.html)s of children, and of elements by name, and a
+   live [`NamedNodeMap`](ext/named_node_map/struct.NamedNodeMap.html) of attributes in the order
+   they were added, which reflect changes to the tree made after they are returned.
+1. The trait [`NodeBorrow`](trait.NodeBorrow.html) extends `Node` with accessors that pass the
+   name, value, or an attribute value of a node to a closure, rather than returning a clone.
+1. The trait [`NodeExtended`](trait.NodeExtended.html) extends `Node` with the ability to attach
+   an application defined [`NodeExtension`](ext/extension/trait.NodeExtension.html), copied when
+   the node is cloned, see the [`extension`](ext/extension/index.html) module.
+1. The trait [`BinaryContent`](trait.BinaryContent.html) extends `Element` with the ability to
+   set and retrieve binary content stored as Base64 or hex encoded text.
+1. The trait [`XsdContent`](trait.XsdContent.html) extends `Element` with the ability to read and
+   write text content and attribute values as XML Schema `dateTime`, `date`, `duration`,
+   `decimal`, and `boolean` values, using the types in the [`xsd`](ext/xsd/index.html) module.
+1. The trait [`DocumentOutline`](trait.DocumentOutline.html) extends `Document` with the ability
+   to produce an [`Outline`](ext/outline/struct.Outline.html), a structural summary of element
+   names, repeated siblings, and attribute names.
+1. The trait [`DocumentLinks`](trait.DocumentLinks.html) extends `Document` with the ability to
+   collect `href`, `src`, and other link attributes, resolved against their `xml:base`, using the
+   [`links`](ext/links/index.html) module.
+1. The trait [`DocumentSplit`](trait.DocumentSplit.html) extends `Document` with the ability to
+   produce a standalone document for each element matched by a selector, carrying inherited
+   namespace declarations and optionally the prolog, see the [`split`](ext/split/index.html)
+   module.
+1. The traits [`ElementAudit`](trait.ElementAudit.html) and
+   [`DocumentAudit`](trait.DocumentAudit.html) record the history of attribute changes on
+   selected elements, with the time of each change and the actor of the enclosing transaction,
+   see the [`audit`](ext/audit/index.html) module.
+1. The trait [`DocumentNormalize`](trait.DocumentNormalize.html) provides the DOM Level 3
+   `normalize_document` method, driven by the parameters of a
+   [`DOMConfiguration`](ext/normalize/struct.DOMConfiguration.html), see the
+   [`normalize`](ext/normalize/index.html) module.
+1. The trait [`Selectable`](trait.Selectable.html) extends `Element` with the ability to find
+   elements using CSS-style selectors, parsed by the [`selector`](ext/selector/index.html) module.
+1. The trait [`DocumentUnparsed`](trait.DocumentUnparsed.html) extends `Document` with the
+   ability to create nodes of the non-standard `NodeType::UnparsedMarkup` type, described in the
+   [`unparsed`](ext/unparsed/index.html) module, that hold raw markup written verbatim when
+   serialized.
+1. The trait [`DocumentImport`](trait.DocumentImport.html) extends `Document` with the ability
+   to splice the content of an external document, fetched by the `UriResolver` types in the
+   [`resolver`](ext/resolver/index.html) module, under a node with namespace fix-up.
 1. The functions [`create_entity`](dom_impl/fn.create_entity.html),
-   [`create_internal_entity`](dom_impl/fn.create_internal_entity.html), and
+   [`create_internal_entity`](dom_impl/fn.create_internal_entity.html),
+   [`create_unparsed_entity`](dom_impl/fn.create_unparsed_entity.html), and
    [`create_notation`](dom_impl/fn.create_notation.html) in the
    [`dom_impl`](dom_impl/index.html) module provide the ability to create instances of these
    Level 2 extended interfaces. In general most clients using the DOM do not need to create these
    however parsers constructing the DOM may.
+1. The [`serializer`](ext/serializer/index.html) module provides a configurable alternative to
+   `Display` that checks namespace well-formedness, and can optionally fix-up missing namespace
+   declarations, as it writes, either to a `String` or directly to an `io::Write` sink. It also
+   provides inclusive and exclusive XML canonicalization.
+1. The [`sax`](ext/sax/index.html) module provides a SAX-style `ContentHandler` trait, a `walk`
+   function that invokes a handler for each node in an existing tree, and a `DocumentBuilder`
+   handler that constructs a new document from events.
+1. The [`tree_builder`](ext/tree_builder/index.html) module provides the `TreeBuilder` used by the
+   parser, so that other front-ends may construct a document from a sequence of events, with
+   optional checks on the sequence, without producing XML text.
+1. The [`sync`](ext/sync/index.html) module provides a `SyncDocument` handle that allows a
+   document, owned by a dedicated thread, to be queried and mutated from multiple threads.
+1. The [`actor`](ext/actor/index.html) module, enabled by the `actor` feature, provides a
+   `DocumentActor` that processes query and mutation messages on the document's owning thread,
+   returning each result as a future.
+1. The [`xpath`](ext/xpath/index.html) module provides an XPath 1.0 engine that evaluates
+   expressions against a tree, returning node-sets, strings, numbers, or booleans.
+1. The [`normalize`](ext/normalize/index.html) module writes a normalized, line-oriented, form of
+   a tree, with sorted attributes, collapsed white space, and consistent namespace prefixes, for
+   comparison with line-based diff tools.
+1. The [`diff`](ext/diff/index.html) module computes a line-based edit script between the
+   normalized forms of two trees, and renders it as a unified, optionally colored, diff.
+1. The [`QueryResult`](ext/query/struct.QueryResult.html) type wraps the results of
+   `get_elements_by_tag_name` queries with bulk operations such as `texts`, `remove_all`, and
+   `into_fragment`.
+1. The [`compare`](ext/compare/index.html) module provides a semantic comparison of two trees that
+   ignores attribute order, insignificant whitespace, and the CDATA/text distinction.
+1. The [`roundtrip`](ext/roundtrip/index.html) module, enabled by the `quick_parser` feature,
+   provides a `roundtrip_check` function that parses, serializes, re-parses, and compares a
+   document, as an entry point for fuzzing and conformance testing.
+1. The [`conformance`](ext/conformance/index.html) module, enabled by the `quick_parser` feature,
+   runs the test descriptions of the W3C DOM Level 1 and Level 2 Core test suites against this
+   implementation, reporting whether each test passed, failed, or uses unsupported features.
+1. The [`lazy`](ext/lazy/index.html) module, enabled by the `quick_parser` feature, provides
+   `read_xml_lazy`, which defers the content of selected elements, by depth or by name, and only
+   parses it into nodes when it is first accessed.
+1. The [`mutation`](ext/mutation/index.html) module provides a `Recorder` that logs each change
+   made to a document, and a `MutationLog` that can be written as text and replayed to rebuild the
+   document.
+1. The [`references`](ext/references/index.html) module checks that ID values are unique and that
+   IDREF and IDREFS attributes refer to declared IDs, reporting problems with node paths.
+1. The [`completion`](ext/completion/index.html) module provides a `Schema`, built from the
+   element type and attribute-list declarations of a DTD, that lists the elements and attributes
+   that may be added to an element, for editors offering completions.
+1. The [`workspace`](ext/workspace/index.html) module provides a `Workspace` that holds a set of
+   related documents by system identifier, with shared creation options and name table, import
+   of nodes between documents, and resolution of external references to other documents.
+1. The [`bench`](ext/bench/index.html) module, enabled by the `bench` feature, counts the node
+   allocations and map rehashes made by the node backend, for use in benchmarks.
+1. The [`catalog`](ext/catalog/index.html) module, enabled by the `entity_catalog` feature, provides
+   the XHTML named character entity sets for decoding input and encoding output.
+1. The [`instrument`](ext/instrument/index.html) module, enabled by the `instrumentation` feature,
+   reports the time spent in node creation, insertion, queries, and serialization to an
+   `Instrument`, such as the provided `Counters`.
+1. The [`soap`](ext/soap/index.html) module, enabled by the `soap` feature, provides navigation of
+   SOAP 1.1 and 1.2 envelopes, headers, and faults; the [`wsse`](ext/wsse/index.html) module,
+   enabled by the same feature, inserts WS-Security header structures into a SOAP envelope.
+1. The [`xop`](ext/xop/index.html) module, enabled by the `xop` feature, moves Base64 content out
+   of a document into XOP attachments, and inlines attachments back into a document.
 
 */
 
@@ -83,6 +245,8 @@ pub use node_impl::RefNode;
 
 pub mod ext;
 
+pub mod traversal;
+
 // ------------------------------------------------------------------------------------------------
 // Re-Export
 // ------------------------------------------------------------------------------------------------
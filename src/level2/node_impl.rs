@@ -1,11 +1,19 @@
+use crate::level2::dom_impl::Implementation;
+use crate::level2::ext::audit::AttributeHistory;
+use crate::level2::ext::dtd::{AttributeDefinition, ConditionalSection};
+use crate::level2::ext::extension::ExtensionSlot;
+#[cfg(feature = "quick_parser")]
+use crate::level2::ext::lazy::DeferredContent;
+use crate::level2::ext::user_data::UserData;
 use crate::level2::ext::ProcessingOptions;
 use crate::level2::ext::XmlDecl;
-use crate::level2::traits::{Node, NodeType};
-use crate::level2::{get_implementation, DOMImplementation};
+use crate::level2::traits::NodeType;
+use crate::level2::DOMImplementation;
 use crate::shared::name::Name;
 use crate::shared::rc_cell::{RcRefCell, WeakRefCell};
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -41,29 +49,42 @@ pub(crate) enum Extension {
     None,
     Attribute {
         i_owner_element: Option<WeakRefNode>,
+        i_is_id: bool,
+        i_specified: bool,
     },
     Document {
-        i_implementation: &'static dyn DOMImplementation<NodeRef = RefNode>,
+        i_implementation: Rc<Implementation>,
         i_xml_declaration: Option<XmlDecl>,
         i_document_type: Option<RefNode>,
+        i_before_doc_type: Vec<WeakRefNode>,
         i_id_map: HashMap<String, WeakRefNode>,
         i_options: ProcessingOptions,
+        i_document_uri: Option<String>,
+        i_audit_actor: Option<String>,
     },
     DocumentType {
         i_entities: HashMap<Name, RefNode>,
         i_notations: HashMap<Name, RefNode>,
+        i_parameter_entities: HashMap<Name, RefNode>,
+        i_conditional_sections: Vec<ConditionalSection>,
+        i_attribute_definitions: Vec<AttributeDefinition>,
         i_public_id: Option<String>,
         i_system_id: Option<String>,
         i_internal_subset: Option<String>,
     },
     Element {
         i_attributes: HashMap<Name, RefNode>,
+        i_attribute_order: Vec<Name>,
         i_namespaces: HashMap<Option<String>, String>,
+        i_attribute_history: Option<AttributeHistory>,
+        #[cfg(feature = "quick_parser")]
+        i_deferred: Option<DeferredContent>,
     },
     Entity {
         i_public_id: Option<String>,
         i_system_id: Option<String>,
         i_notation_name: Option<String>,
+        i_parameter: bool,
     },
     Notation {
         i_public_id: Option<String>,
@@ -86,6 +107,16 @@ pub struct NodeImpl {
     pub(crate) i_owner_document: Option<WeakRefNode>,
     pub(crate) i_child_nodes: Vec<RefNode>,
     pub(crate) i_extension: Extension,
+    pub(crate) i_user_extension: ExtensionSlot,
+    pub(crate) i_user_data: UserData,
+    pub(crate) i_read_only: bool,
+}
+
+macro_rules! count_node_allocation {
+    () => {
+        #[cfg(feature = "bench")]
+        crate::level2::ext::bench::count_node_allocation();
+    };
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -102,6 +133,7 @@ impl Debug for &'static dyn DOMImplementation<NodeRef = RefNode> {
 
 impl NodeImpl {
     pub(crate) fn new_element(owner_document: WeakRefNode, name: Name) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::Element,
             i_name: name,
@@ -109,9 +141,16 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
             i_extension: Extension::Element {
                 i_attributes: Default::default(),
+                i_attribute_order: Default::default(),
                 i_namespaces: Default::default(),
+                i_attribute_history: None,
+                #[cfg(feature = "quick_parser")]
+                i_deferred: None,
             },
         }
     }
@@ -120,6 +159,7 @@ impl NodeImpl {
         name: Name,
         value: Option<&str>,
     ) -> Self {
+        count_node_allocation!();
         let children = if let Some(value) = value {
             vec![RefNode::new(Self::new_text(owner_document.clone(), value))]
         } else {
@@ -132,12 +172,18 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: children,
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
             i_extension: Extension::Attribute {
                 i_owner_element: None,
+                i_is_id: false,
+                i_specified: true,
             },
         }
     }
     pub(crate) fn new_text(owner_document: WeakRefNode, data: &str) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::Text,
             i_name: Name::for_text(),
@@ -145,10 +191,14 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
             i_extension: Extension::None,
         }
     }
     pub(crate) fn new_cdata(owner_document: WeakRefNode, data: &str) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::CData,
             i_name: Name::for_cdata(),
@@ -156,6 +206,9 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
             i_extension: Extension::None,
         }
     }
@@ -164,6 +217,7 @@ impl NodeImpl {
         target: Name,
         data: Option<&str>,
     ) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::ProcessingInstruction,
             i_name: target,
@@ -171,10 +225,14 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
             i_extension: Extension::None,
         }
     }
     pub(crate) fn new_comment(owner_document: WeakRefNode, data: &str) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::Comment,
             i_name: Name::for_comment(),
@@ -182,10 +240,33 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
+            i_extension: Extension::None,
+        }
+    }
+    pub(crate) fn new_unparsed_markup(owner_document: WeakRefNode, markup: &str) -> Self {
+        count_node_allocation!();
+        Self {
+            i_node_type: NodeType::UnparsedMarkup,
+            i_name: Name::for_unparsed_markup(),
+            i_value: Some(markup.to_string()),
+            i_parent_node: None,
+            i_owner_document: Some(owner_document),
+            i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
             i_extension: Extension::None,
         }
     }
-    pub(crate) fn new_document(doc_type: Option<RefNode>, options: ProcessingOptions) -> Self {
+    pub(crate) fn new_document(
+        implementation: Rc<Implementation>,
+        doc_type: Option<RefNode>,
+        options: ProcessingOptions,
+    ) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::Document,
             i_name: Name::for_document(),
@@ -193,16 +274,23 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: None,
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
             i_extension: Extension::Document {
-                i_implementation: get_implementation(),
+                i_implementation: implementation,
                 i_xml_declaration: None,
                 i_document_type: doc_type,
+                i_before_doc_type: Default::default(),
                 i_id_map: Default::default(),
                 i_options: options,
+                i_document_uri: None,
+                i_audit_actor: None,
             },
         }
     }
     pub(crate) fn new_document_fragment(owner_document: WeakRefNode) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::DocumentFragment,
             i_name: Name::for_document_fragment(),
@@ -210,6 +298,9 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: false,
             i_extension: Extension::None,
         }
     }
@@ -219,6 +310,7 @@ impl NodeImpl {
         public_id: Option<&str>,
         system_id: Option<&str>,
     ) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::DocumentType,
             i_name: name,
@@ -226,9 +318,15 @@ impl NodeImpl {
             i_parent_node: owner_document.clone(),
             i_owner_document: owner_document,
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: true,
             i_extension: Extension::DocumentType {
                 i_entities: Default::default(),
                 i_notations: Default::default(),
+                i_parameter_entities: Default::default(),
+                i_conditional_sections: Default::default(),
+                i_attribute_definitions: Default::default(),
                 i_public_id: public_id.map(String::from),
                 i_system_id: system_id.map(String::from),
                 i_internal_subset: None,
@@ -236,6 +334,7 @@ impl NodeImpl {
         }
     }
     pub(crate) fn new_entity_reference(owner_document: WeakRefNode, name: Name) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::EntityReference,
             i_name: name,
@@ -243,6 +342,9 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: true,
             i_extension: Extension::None,
         }
     }
@@ -252,6 +354,7 @@ impl NodeImpl {
         public_id: Option<&str>,
         system_id: Option<&str>,
     ) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::Entity,
             i_name: notation_name,
@@ -259,18 +362,39 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: owner_document,
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: true,
             i_extension: Extension::Entity {
                 i_public_id: public_id.map(String::from),
                 i_system_id: system_id.map(String::from),
                 i_notation_name: None,
+                i_parameter: false,
             },
         }
     }
+    pub(crate) fn new_unparsed_entity(
+        owner_document: Option<WeakRefNode>,
+        name: Name,
+        public_id: Option<&str>,
+        system_id: &str,
+        notation_name: &str,
+    ) -> Self {
+        let mut new_self = Self::new_entity(owner_document, name, public_id, Some(system_id));
+        if let Extension::Entity {
+            i_notation_name, ..
+        } = &mut new_self.i_extension
+        {
+            *i_notation_name = Some(notation_name.to_string());
+        }
+        new_self
+    }
     pub(crate) fn new_internal_entity(
         owner_document: Option<WeakRefNode>,
         notation_name: Name,
         value: &str,
     ) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::Entity,
             i_name: notation_name,
@@ -278,10 +402,14 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: owner_document,
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: true,
             i_extension: Extension::Entity {
                 i_public_id: None,
                 i_system_id: None,
                 i_notation_name: None,
+                i_parameter: false,
             },
         }
     }
@@ -291,6 +419,7 @@ impl NodeImpl {
         public_id: Option<&str>,
         system_id: Option<&str>,
     ) -> Self {
+        count_node_allocation!();
         Self {
             i_node_type: NodeType::Notation,
             i_name: notation_name,
@@ -298,51 +427,88 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: owner_document,
             i_child_nodes: vec![],
+            i_user_extension: Default::default(),
+            i_user_data: Default::default(),
+            i_read_only: true,
             i_extension: Extension::Notation {
                 i_public_id: public_id.map(String::from),
                 i_system_id: system_id.map(String::from),
             },
         }
     }
-    #[allow(clippy::clone_double_ref)]
+    #[allow(suspicious_double_ref_op)]
     pub(crate) fn clone_node(&self, deep: bool) -> Self {
+        count_node_allocation!();
+        let read_only = matches!(
+            self.i_node_type,
+            NodeType::DocumentType
+                | NodeType::Entity
+                | NodeType::EntityReference
+                | NodeType::Notation
+        );
         let extension = match &self.i_extension {
             Extension::None => Extension::None,
-            Extension::Attribute { i_owner_element } => Extension::Attribute {
+            Extension::Attribute {
+                i_owner_element,
+                i_is_id,
+                i_specified,
+            } => Extension::Attribute {
                 i_owner_element: i_owner_element.clone(),
+                i_is_id: *i_is_id,
+                i_specified: *i_specified,
             },
             Extension::Document {
                 i_implementation,
                 i_xml_declaration,
                 i_document_type,
+                i_before_doc_type,
                 i_id_map,
                 i_options,
+                i_document_uri,
+                ..
             } => Extension::Document {
                 i_implementation: i_implementation.clone(),
                 i_xml_declaration: i_xml_declaration.clone(),
                 i_document_type: i_document_type.clone(),
+                i_before_doc_type: i_before_doc_type.clone(),
                 i_id_map: i_id_map.clone(),
                 i_options: i_options.clone(),
+                i_document_uri: i_document_uri.clone(),
+                i_audit_actor: None,
             },
             Extension::DocumentType {
                 i_entities,
                 i_notations,
+                i_parameter_entities,
+                i_conditional_sections,
+                i_attribute_definitions,
                 i_public_id,
                 i_system_id,
                 i_internal_subset,
             } => Extension::DocumentType {
                 i_entities: i_entities.clone(),
                 i_notations: i_notations.clone(),
+                i_parameter_entities: i_parameter_entities.clone(),
+                i_conditional_sections: i_conditional_sections.clone(),
+                i_attribute_definitions: i_attribute_definitions.clone(),
                 i_public_id: i_public_id.clone(),
                 i_system_id: i_system_id.clone(),
                 i_internal_subset: i_internal_subset.clone(),
             },
             Extension::Element {
                 i_attributes,
+                i_attribute_order,
                 i_namespaces,
+                #[cfg(feature = "quick_parser")]
+                i_deferred,
+                ..
             } => Extension::Element {
                 i_attributes: i_attributes.clone(),
+                i_attribute_order: i_attribute_order.clone(),
                 i_namespaces: i_namespaces.clone(),
+                i_attribute_history: None,
+                #[cfg(feature = "quick_parser")]
+                i_deferred: if deep { i_deferred.clone() } else { None },
             },
             entity @ Extension::Entity { .. } => entity.clone(),
             notation @ Extension::Notation { .. } => notation.clone(),
@@ -356,12 +522,51 @@ impl NodeImpl {
             i_child_nodes: if deep {
                 self.i_child_nodes
                     .iter()
-                    .map(|node| node.clone_node(deep).unwrap())
+                    .map(|node| {
+                        let mut child = node.borrow().clone_node(deep);
+                        if read_only {
+                            child.set_read_only();
+                        }
+                        RefNode::new(child)
+                    })
                     .collect()
             } else {
                 vec![]
             },
             i_extension: extension,
+            i_user_extension: self.i_user_extension.clone(),
+            i_user_data: Default::default(),
+            i_read_only: read_only,
+        }
+    }
+
+    ///
+    /// Mark this node, its attributes, and all of its descendants, as read-only.
+    ///
+    pub(crate) fn set_read_only(&mut self) {
+        self.i_read_only = true;
+        for child_node in &self.i_child_nodes {
+            child_node.borrow_mut().set_read_only();
+        }
+        if let Extension::Element { i_attributes, .. } = &self.i_extension {
+            for attribute_node in i_attributes.values() {
+                attribute_node.borrow_mut().set_read_only();
+            }
         }
     }
 }
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Construct the children of `node` if they were deferred when it was parsed, see the `lazy`
+/// module; this is called before the child list of a node is read or changed.
+///
+#[cfg(feature = "quick_parser")]
+pub(crate) use crate::level2::ext::lazy::hydrate_children;
+
+#[cfg(not(feature = "quick_parser"))]
+#[inline]
+pub(crate) fn hydrate_children(_node: &RefNode) {}
@@ -1,9 +1,16 @@
 use crate::level2::convert::*;
 use crate::level2::dom_impl::{get_implementation, Implementation};
+use crate::level2::ext::audit;
 use crate::level2::ext::convert::as_element_namespaced_mut;
+use crate::level2::ext::dtd::adopt_document_type;
+use crate::level2::ext::namespaced::check_reserved_namespace;
 use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::traits::{DocumentEntities, NodeOwner};
+use crate::level2::ext::user_data::{self, UserDataOperation};
+use crate::level2::ext::workspace::{import_node, resolve_uri};
 use crate::level2::node_impl::*;
 use crate::level2::traits::*;
+use crate::level2::traversal;
 use crate::shared::error::*;
 use crate::shared::name::Name;
 use crate::shared::syntax::*;
@@ -11,6 +18,8 @@ use crate::shared::{display, text};
 use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Range;
+use std::rc::Rc;
 use std::str::FromStr;
 
 // ------------------------------------------------------------------------------------------------
@@ -62,6 +71,15 @@ macro_rules! unwrap_extension_field {
     }};
 }
 
+macro_rules! instrument_operation {
+    ($($operation:tt)+) => {
+        #[cfg(feature = "instrumentation")]
+        let _timer = crate::level2::ext::instrument::Timer::start(
+            crate::level2::ext::instrument::Operation::$($operation)+,
+        );
+    };
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
@@ -91,28 +109,51 @@ impl Attribute for RefNode {
                     //
                     let ref_node = child_node.borrow();
                     if let Some(data) = &ref_node.i_value {
-                        result.push_str(&data);
+                        result.push_str(data);
                     }
                 }
             }
-            let normalized = text::normalize_attribute_value(&result, self, false);
-            Some(text::escape(&normalized))
+            text::normalize_attribute_value(&result, self, false)
+                .map(|normalized| text::escape(&normalized))
         } else {
             None
         }
     }
     fn set_value(&mut self, value: &str) -> Result<()> {
-        self.unset_value()?;
-        let document_node = self.owner_document().unwrap();
+        check_not_read_only(self)?;
+        let audited_element = audited_owner_element(self);
+        let previous = audited_element.as_ref().and_then(|_| self.value());
+        self.borrow_mut().i_child_nodes.clear();
+        let document_node = live_owner_document(self)?;
+        set_specified(self, true);
         let document = as_document(&document_node).unwrap();
         let _safe_to_ignore = self.append_child(document.create_text_node(value))?;
+        if let Some(element) = audited_element {
+            audit::record_attribute_change(&element, &self.node_name(), previous, self.value());
+        }
         Ok(())
     }
     fn unset_value(&mut self) -> Result<()> {
-        let mut mut_self = self.borrow_mut();
-        mut_self.i_child_nodes.clear();
+        check_not_read_only(self)?;
+        let audited_element = audited_owner_element(self);
+        let previous = audited_element.as_ref().and_then(|_| self.value());
+        self.borrow_mut().i_child_nodes.clear();
+        set_specified(self, true);
+        if let Some(element) = audited_element {
+            audit::record_attribute_change(&element, &self.node_name(), previous, self.value());
+        }
         Ok(())
     }
+    fn specified(&self) -> bool {
+        self.owner_element().is_none()
+            || matches!(
+                self.borrow().i_extension,
+                Extension::Attribute {
+                    i_specified: true,
+                    ..
+                }
+            )
+    }
     fn owner_element(&self) -> Option<Self::NodeRef> {
         unwrap_extension_field!(
             self,
@@ -141,10 +182,20 @@ impl CDataSection for RefNode {}
 // ------------------------------------------------------------------------------------------------
 
 impl CharacterData for RefNode {
+    fn length(&self) -> usize {
+        let byte_offsets = has_byte_offsets(self);
+        match &self.borrow().i_value {
+            None => 0,
+            Some(data) if byte_offsets => data.len(),
+            Some(data) => data.encode_utf16().count(),
+        }
+    }
+
     fn substring_data(&self, offset: usize, count: usize) -> Result<String> {
-        if offset + count == offset {
+        if count == 0 {
             return Ok(String::new());
         }
+        let byte_offsets = has_byte_offsets(self);
         let ref_self = self.borrow();
         match &ref_self.i_value {
             None => {
@@ -152,31 +203,63 @@ impl CharacterData for RefNode {
                 Err(Error::IndexSize)
             }
             Some(data) => {
-                if offset >= data.len() {
-                    warn!("{}", MSG_INDEX_ERROR);
-                    Err(Error::IndexSize)
-                } else if offset + count >= data.len() {
-                    Ok(data[offset..].to_string())
-                } else {
-                    Ok(data[offset..offset + count].to_string())
-                }
+                let range = data_range(data, offset, count, byte_offsets)?;
+                Ok(data[range].to_owned())
             }
         }
     }
 
     fn append_data(&mut self, new_data: &str) -> Result<()> {
+        check_not_read_only(self)?;
         if new_data.is_empty() {
             return Ok(());
         }
+        {
+            let mut mut_self = self.borrow_mut();
+            match &mut mut_self.i_value {
+                None => mut_self.i_value = Some(new_data.to_string()),
+                Some(old_data) => old_data.push_str(new_data),
+            }
+        }
+        chunk_text_in_parent(self)
+    }
+
+    fn append_many<I, S>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        check_not_read_only(self)?;
+        let data: Vec<S> = data.into_iter().collect();
+        let additional = data.iter().map(|s| s.as_ref().len()).sum();
+        if additional == 0 {
+            return Ok(());
+        }
+        self.reserve(additional)?;
+        {
+            let mut mut_self = self.borrow_mut();
+            let value = mut_self.i_value.get_or_insert_with(String::new);
+            for new_data in &data {
+                value.push_str(new_data.as_ref());
+            }
+        }
+        chunk_text_in_parent(self)
+    }
+
+    fn reserve(&mut self, additional: usize) -> Result<()> {
+        if additional == 0 {
+            return Ok(());
+        }
         let mut mut_self = self.borrow_mut();
-        match &mut_self.i_value {
-            None => mut_self.i_value = Some(new_data.to_string()),
-            Some(old_data) => mut_self.i_value = Some(format!("{}{}", old_data, new_data)),
+        match &mut mut_self.i_value {
+            None => mut_self.i_value = Some(String::with_capacity(additional)),
+            Some(data) => data.reserve(additional),
         }
         Ok(())
     }
 
     fn insert_data(&mut self, offset: usize, new_data: &str) -> Result<()> {
+        check_not_read_only(self)?;
         if new_data.is_empty() {
             return Ok(());
         }
@@ -184,7 +267,8 @@ impl CharacterData for RefNode {
     }
 
     fn delete_data(&mut self, offset: usize, count: usize) -> Result<()> {
-        if offset + count == offset {
+        check_not_read_only(self)?;
+        if count == 0 {
             return Ok(());
         }
         const NOTHING: &str = "";
@@ -192,33 +276,29 @@ impl CharacterData for RefNode {
     }
 
     fn replace_data(&mut self, offset: usize, count: usize, replace_data: &str) -> Result<()> {
-        let mut mut_self = self.borrow_mut();
-        match &mut_self.i_value {
-            None => {
-                if offset + count != 0 {
-                    warn!("{}", MSG_INDEX_ERROR);
-                    Err(Error::IndexSize)
-                } else {
+        check_not_read_only(self)?;
+        let byte_offsets = has_byte_offsets(self);
+        {
+            let mut mut_self = self.borrow_mut();
+            match &mut mut_self.i_value {
+                None => {
+                    if offset != 0 || count != 0 {
+                        warn!("{}", MSG_INDEX_ERROR);
+                        return Err(Error::IndexSize);
+                    }
                     mut_self.i_value = Some(replace_data.to_string());
-                    Ok(())
                 }
-            }
-            Some(old_data) => {
-                if offset >= old_data.len() {
-                    warn!("{}", MSG_INDEX_ERROR);
-                    Err(Error::IndexSize)
-                } else {
-                    let mut new_data = old_data.clone();
-                    if offset + count >= old_data.len() {
-                        new_data.replace_range(offset.., replace_data);
-                    } else {
-                        new_data.replace_range(offset..offset + count, replace_data);
-                    }
-                    mut_self.i_value = Some(new_data);
-                    Ok(())
+                Some(old_data) => {
+                    let range = data_range(old_data, offset, count, byte_offsets)?;
+                    old_data.replace_range(range, replace_data);
                 }
             }
         }
+        if replace_data.is_empty() {
+            Ok(())
+        } else {
+            chunk_text_in_parent(self)
+        }
     }
 }
 
@@ -234,73 +314,121 @@ impl Document for RefNode {
     }
 
     fn document_element(&self) -> Option<RefNode> {
-        self.child_nodes().first().map(Clone::clone)
+        self.child_nodes()
+            .into_iter()
+            .find(|child| child.node_type() == NodeType::Element)
     }
 
-    fn implementation(&self) -> &dyn DOMImplementation<NodeRef = RefNode> {
+    fn implementation(&self) -> Rc<dyn DOMImplementation<NodeRef = RefNode>> {
         let ref_self = self.borrow();
         if let Extension::Document {
             i_implementation, ..
         } = &ref_self.i_extension
         {
-            *i_implementation
+            i_implementation.clone()
         } else {
             panic!("{}", MSG_INVALID_EXTENSION);
         }
     }
 
+    fn document_uri(&self) -> Option<String> {
+        unwrap_extension_field!(self, Document, i_document_uri)
+    }
+
+    fn set_document_uri(&mut self, document_uri: &str) {
+        replace_document_uri(self, Some(document_uri.to_string()));
+    }
+
+    fn unset_document_uri(&mut self) {
+        replace_document_uri(self, None);
+    }
+
     fn create_attribute(&self, name: &str) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::Attribute));
         let name = Name::from_str(name)?;
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, None);
         Ok(RefNode::new(node_impl))
     }
 
     fn create_attribute_with(&self, name: &str, value: &str) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::Attribute));
         let name = Name::from_str(name)?;
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, Some(value));
         Ok(RefNode::new(node_impl))
     }
 
     fn create_attribute_ns(&self, namespace_uri: &str, qualified_name: &str) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::Attribute));
         let name = Name::new_ns(namespace_uri, qualified_name)?;
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, None);
         Ok(RefNode::new(node_impl))
     }
 
     fn create_cdata_section(&self, data: &str) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::CData));
         let node_impl = NodeImpl::new_cdata(self.clone().downgrade(), data);
         Ok(RefNode::new(node_impl))
     }
 
     fn create_document_fragment(&self) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::DocumentFragment));
         let node_impl = NodeImpl::new_document_fragment(self.clone().downgrade());
         Ok(RefNode::new(node_impl))
     }
 
     fn create_entity_reference(&self, name: &str) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::EntityReference));
         let name = Name::from_str(name)?;
-        let node_impl = NodeImpl::new_entity_reference(self.clone().downgrade(), name);
-        Ok(RefNode::new(node_impl))
+        let entity = self.entity(&name.to_string());
+        let entity_reference = RefNode::new(NodeImpl::new_entity_reference(
+            self.clone().downgrade(),
+            name,
+        ));
+        if let Some(entity) = entity {
+            //
+            // The children of the entity are copied, and like the reference are read-only.
+            //
+            let children: Vec<RefNode> = entity
+                .child_nodes()
+                .iter()
+                .map(|child_node| {
+                    let mut node_impl = child_node.borrow().clone_node(true);
+                    node_impl.set_read_only();
+                    node_impl.i_parent_node = Some(entity_reference.clone().downgrade());
+                    RefNode::new(node_impl)
+                })
+                .collect();
+            entity_reference.borrow_mut().i_child_nodes = children;
+        }
+        Ok(entity_reference)
     }
 
     fn create_comment(&self, data: &str) -> RefNode {
+        instrument_operation!(Create(NodeType::Comment));
         let node_impl = NodeImpl::new_comment(self.clone().downgrade(), data);
         RefNode::new(node_impl)
     }
 
     fn create_element(&self, tag_name: &str) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::Element));
         let name = Name::from_str(tag_name)?;
         let node_impl = NodeImpl::new_element(self.clone().downgrade(), name);
-        Ok(RefNode::new(node_impl))
+        let element = RefNode::new(node_impl);
+        add_default_attributes(self, &element, None);
+        Ok(element)
     }
 
     fn create_element_ns(&self, namespace_uri: &str, qualified_name: &str) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::Element));
         let name = Name::new_ns(namespace_uri, qualified_name)?;
         let node_impl = NodeImpl::new_element(self.clone().downgrade(), name);
-        Ok(RefNode::new(node_impl))
+        let element = RefNode::new(node_impl);
+        add_default_attributes(self, &element, None);
+        Ok(element)
     }
 
     fn create_processing_instruction(&self, target: &str, data: Option<&str>) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::ProcessingInstruction));
         //
         // Ensure:
         //
@@ -309,6 +437,12 @@ impl Document for RefNode {
         if target.to_ascii_lowercase() == XML_PI_RESERVED {
             return Err(Error::Syntax);
         }
+        if let Some(data) = data {
+            if !text::is_processing_instruction_data(data) {
+                warn!("{}", MSG_INVALID_PI_DATA);
+                return Err(Error::Syntax);
+            }
+        }
         let target = Name::from_str(target)?;
         let node_impl =
             NodeImpl::new_processing_instruction(self.clone().downgrade(), target, data);
@@ -316,30 +450,36 @@ impl Document for RefNode {
     }
 
     fn create_text_node(&self, data: &str) -> RefNode {
+        instrument_operation!(Create(NodeType::Text));
         let node_impl = NodeImpl::new_text(self.clone().downgrade(), data);
         RefNode::new(node_impl)
     }
 
     fn get_element_by_id(&self, id: &str) -> Option<RefNode> {
-        let ref_self = self.borrow();
-        if let Extension::Document { i_id_map, .. } = &ref_self.i_extension {
-            match i_id_map.get(&id.to_string()) {
-                None => None,
-                Some(weak_ref) => match weak_ref.clone().upgrade() {
-                    None => {
-                        warn!("{}", MSG_WEAK_REF);
-                        None
-                    }
-                    Some(ref_element) => Some(ref_element),
-                },
+        instrument_operation!(Query);
+        //
+        // The ID map is only a hint, the element may since have been removed from the tree, or
+        // its ID changed, and in a copy of the document the map refers to the original elements.
+        //
+        let mapped = {
+            let ref_self = self.borrow();
+            if let Extension::Document { i_id_map, .. } = &ref_self.i_extension {
+                i_id_map.get(id).cloned()
+            } else {
+                warn!("{}", MSG_INVALID_EXTENSION);
+                return None;
+            }
+        };
+        if let Some(element) = mapped.and_then(|weak_ref| weak_ref.upgrade()) {
+            if is_in_document(self, &element) && has_id(self, &element, id) {
+                return Some(element);
             }
-        } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
-            None
         }
+        find_element_by_id(self, &self.document_element()?, id)
     }
 
     fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<RefNode> {
+        instrument_operation!(Query);
         //
         // Delegate this call to the document element
         //
@@ -351,6 +491,7 @@ impl Document for RefNode {
     }
 
     fn get_elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> Vec<RefNode> {
+        instrument_operation!(Query);
         //
         // Delegate this call to the document element
         //
@@ -401,9 +542,13 @@ impl DOMImplementation for Implementation {
         qualified_name: Option<&str>,
         doc_type: Option<RefNode>,
     ) -> Result<RefNode> {
-        let mut options = ProcessingOptions::new();
-        options.set_add_namespaces();
-        create_document_with_options(namespace_uri, qualified_name, doc_type, options)
+        create_document_with_options(
+            Rc::new(self.clone()),
+            namespace_uri,
+            qualified_name,
+            doc_type,
+            self.options(),
+        )
     }
 
     fn create_document_type(
@@ -412,14 +557,20 @@ impl DOMImplementation for Implementation {
         public_id: Option<&str>,
         system_id: Option<&str>,
     ) -> Result<RefNode> {
+        instrument_operation!(Create(NodeType::DocumentType));
         let name = Name::from_str(qualified_name)?;
         let node_impl = NodeImpl::new_document_type(None, name, public_id, system_id);
         Ok(RefNode::new(node_impl))
     }
 
     fn has_feature(&self, feature: &str, version: &str) -> bool {
-        (feature == XML_FEATURE_CORE || feature == XML_FEATURE_XML)
-            && (version == XML_FEATURE_V1 || version == XML_FEATURE_V2)
+        let any_version = version.is_empty();
+        ((feature.eq_ignore_ascii_case(XML_FEATURE_CORE)
+            || feature.eq_ignore_ascii_case(XML_FEATURE_XML))
+            && (any_version || version == XML_FEATURE_V1 || version == XML_FEATURE_V2))
+            || (feature.eq_ignore_ascii_case(XML_FEATURE_TRAVERSAL)
+                && (any_version || version == XML_FEATURE_V2))
+            || self.is_added_feature(feature, version)
     }
 }
 
@@ -440,16 +591,20 @@ impl Element for RefNode {
     }
 
     fn set_attribute(&mut self, name: &str, value: &str) -> Result<()> {
+        check_not_read_only(self)?;
+        let _safe_to_ignore = live_owner_document(self)?;
         let attr_name = Name::from_str(name)?;
-        let attr_node = {
+        let mut attr_node = {
             let ref_self = &self.borrow_mut();
             let document = ref_self.i_owner_document.as_ref().unwrap();
             NodeImpl::new_attribute(document.clone(), attr_name, Some(value))
         };
+        keep_user_id_attribute(self, &mut attr_node);
         self.set_attribute_node(RefNode::new(attr_node)).map(|_| ())
     }
 
     fn remove_attribute(&mut self, name: &str) -> Result<()> {
+        check_not_read_only(self)?;
         match self.get_attribute_node(name) {
             None => Ok(()),
             Some(attribute_node) => self.remove_attribute_node(attribute_node).map(|_| ()),
@@ -462,10 +617,12 @@ impl Element for RefNode {
                 Ok(name) => {
                     let ref_self = self.borrow();
                     if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
-                        let node_name = name.to_string();
                         i_attributes
                             .iter()
-                            .find(|(name, _)| name.to_string() == node_name)
+                            .find(|(attribute_name, _)| {
+                                attribute_name.prefix() == name.prefix()
+                                    && attribute_name.local_name() == name.local_name()
+                            })
                             .map(|(_, node)| node.clone())
                     } else {
                         warn!("{}", MSG_INVALID_EXTENSION);
@@ -484,9 +641,22 @@ impl Element for RefNode {
     }
 
     fn set_attribute_node(&mut self, new_attribute: RefNode) -> Result<RefNode> {
+        check_not_read_only(self)?;
         if is_element(self) && is_attribute(&new_attribute) {
+            let document = live_owner_document(&new_attribute)?;
             check_same_document(self, &new_attribute)?;
 
+            let name: Name = new_attribute.node_name();
+            if name.is_namespace_attribute() {
+                let declared_prefix = name.prefix().as_ref().map(|_| name.local_name().as_str());
+                let namespace_uri = as_attribute(&new_attribute).unwrap().value();
+                check_reserved_namespace(
+                    self,
+                    declared_prefix,
+                    &namespace_uri.unwrap_or_default(),
+                )?;
+            }
+
             //
             // Set the attribute's owner. This is *not* the same as parent which remains `None`.
             //
@@ -502,62 +672,126 @@ impl Element for RefNode {
                 }
             }
 
-            let name: Name = new_attribute.node_name();
             if name.is_namespace_attribute() {
                 //
                 // Add to the element's namespace mapping hash
                 //
                 let attribute = as_attribute(&new_attribute).unwrap();
-                let namespace_uri = attribute.value().unwrap();
+                let namespace_uri = attribute.value().unwrap_or_default();
 
                 let as_namespaced = as_element_namespaced_mut(self).unwrap();
                 let _ignore = match &name.prefix() {
                     None => as_namespaced.insert_mapping(None, &namespace_uri),
-                    Some(prefix) => as_namespaced.insert_mapping(Some(prefix), &namespace_uri),
+                    Some(_) => {
+                        as_namespaced.insert_mapping(Some(name.local_name()), &namespace_uri)
+                    }
                 }?;
             }
 
-            let mut mut_self = self.borrow_mut();
-            if let Extension::Element { i_attributes, .. } = &mut mut_self.i_extension {
-                let _safe_to_ignore =
-                    i_attributes.insert(new_attribute.node_name(), new_attribute.clone());
+            let previous = if audit::is_audited(self) {
+                Some(
+                    self.attributes()
+                        .get(&name)
+                        .and_then(|attribute| as_attribute(attribute).ok()?.value()),
+                )
+            } else {
+                None
+            };
+
+            //
+            // Check the owning document's ID map for a different element with the same ID; it is
+            // an error if that element is in the tree, unless the ID attribute is only declared
+            // in the document type as duplicates are then a validity, not a well-formedness,
+            // error. Otherwise the ID is ambiguous and is removed from the map so that
+            // `get_element_by_id` searches in document order.
+            //
+            let element_name = self.node_name();
+            let id_entry = if is_id_attribute_of(&document, &element_name, &name)
+                || is_user_id_attribute(&new_attribute)
+            {
+                let id_value = id_value_of(&new_attribute);
+                let mapped =
+                    if let Extension::Document { i_id_map, .. } = &document.borrow().i_extension {
+                        i_id_map
+                            .get(&id_value)
+                            .and_then(|weak_ref| weak_ref.clone().upgrade())
+                    } else {
+                        warn!("{}", MSG_INVALID_EXTENSION);
+                        None
+                    };
+                let is_ambiguous = match mapped {
+                    Some(element) if element != *self && has_id(&document, &element, &id_value) => {
+                        if is_in_document(&document, &element)
+                            && is_intrinsic_id_attribute(&document, &name)
+                        {
+                            warn!("{}", MSG_DUPLICATE_ID);
+                            return Err(Error::Syntax);
+                        }
+                        true
+                    }
+                    _ => false,
+                };
+                Some((id_value, is_ambiguous))
+            } else {
+                None
+            };
+
+            let result = {
+                let mut mut_self = self.borrow_mut();
+                if let Extension::Element {
+                    i_attributes,
+                    i_attribute_order,
+                    ..
+                } = &mut mut_self.i_extension
                 {
+                    let attribute_name = new_attribute.node_name();
+                    #[cfg(feature = "bench")]
+                    crate::level2::ext::bench::count_map_insert(i_attributes, &attribute_name);
                     //
-                    // Add to the owning document's id_map hash
+                    // Remove any attribute with the same expanded name first, as `insert` would
+                    // retain the existing key, and so its prefix. A replaced attribute keeps its
+                    // position in the attribute order.
                     //
-                    let attribute = as_attribute(&new_attribute).unwrap();
-                    let document = attribute.owner_document().unwrap();
-                    let mut mut_document = document.borrow_mut();
-                    let lax =
-                        if let Extension::Document { i_options, .. } = &mut_document.i_extension {
-                            i_options.has_assume_ids()
-                        } else {
-                            warn!("{}", MSG_INVALID_EXTENSION);
-                            false
-                        };
-                    if name.is_id_attribute(lax) {
+                    let _safe_to_ignore = i_attributes.remove(&attribute_name);
+                    match i_attribute_order
+                        .iter_mut()
+                        .find(|name| **name == attribute_name)
+                    {
+                        Some(name) => *name = attribute_name.clone(),
+                        None => i_attribute_order.push(attribute_name.clone()),
+                    }
+                    let _safe_to_ignore =
+                        i_attributes.insert(attribute_name, new_attribute.clone());
+                    if let Some((id_value, is_ambiguous)) = id_entry {
                         //
                         // Update the document ID mapping
                         //
-                        if let Extension::Document { i_id_map, .. } = &mut mut_document.i_extension
+                        if let Extension::Document { i_id_map, .. } =
+                            &mut document.borrow_mut().i_extension
                         {
-                            let id_value = attribute.value().unwrap();
-                            if i_id_map.contains_key(&id_value) {
-                                warn!("{}", MSG_DUPLICATE_ID);
-                                return Err(Error::Syntax);
+                            if is_ambiguous {
+                                let _safe_to_ignore = i_id_map.remove(&id_value);
+                            } else {
+                                #[cfg(feature = "bench")]
+                                crate::level2::ext::bench::count_map_insert(i_id_map, &id_value);
+                                let _safe_to_ignore =
+                                    i_id_map.insert(id_value, self.clone().downgrade());
                             }
-                            let _safe_to_ignore =
-                                i_id_map.insert(id_value, self.clone().downgrade());
                         } else {
                             warn!("{}", MSG_INVALID_EXTENSION);
                         }
                     }
+                    Ok(new_attribute)
+                } else {
+                    warn!("{}", MSG_INVALID_EXTENSION);
+                    Err(Error::Syntax)
                 }
-                Ok(new_attribute)
-            } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
-                Err(Error::Syntax)
+            };
+            if let (Ok(attribute), Some(previous)) = (&result, previous) {
+                let value = as_attribute(attribute).unwrap().value();
+                audit::record_attribute_change(self, &name, previous, value);
             }
+            result
         } else {
             warn!("{}", MSG_INVALID_NODE_TYPE);
             Err(Error::InvalidState)
@@ -565,20 +799,49 @@ impl Element for RefNode {
     }
 
     fn remove_attribute_node(&mut self, old_attribute: RefNode) -> Result<RefNode> {
+        check_not_read_only(self)?;
         if is_element(self) {
-            let mut mut_self = self.borrow_mut();
-            if let Extension::Element { i_attributes, .. } = &mut mut_self.i_extension {
-                let _safe_to_ignore = i_attributes.remove(&old_attribute.node_name());
-                let mut_old = old_attribute.clone();
-                let mut mut_old = mut_old.borrow_mut();
+            let name = old_attribute.node_name();
+            let removed = {
+                let mut mut_self = self.borrow_mut();
+                if let Extension::Element {
+                    i_attributes,
+                    i_attribute_order,
+                    ..
+                } = &mut mut_self.i_extension
+                {
+                    i_attribute_order.retain(|ordered| *ordered != name);
+                    i_attributes.remove(&name)
+                } else {
+                    warn!("{}", MSG_INVALID_EXTENSION);
+                    return Err(Error::Syntax);
+                }
+            };
+            {
+                let mut mut_old = old_attribute.borrow_mut();
                 mut_old.i_parent_node = None;
+                if let Extension::Attribute {
+                    i_owner_element, ..
+                } = &mut mut_old.i_extension
+                {
+                    *i_owner_element = None;
+                }
                 // TODO: remove from Element::namespaces
                 // TODO: remove from Document::id_map
-                Ok(old_attribute)
-            } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
-                Err(Error::Syntax)
             }
+            if let Some(removed) = removed {
+                if audit::is_audited(self) {
+                    let previous = as_attribute(&removed).unwrap().value();
+                    audit::record_attribute_change(self, &name, previous, None);
+                }
+                //
+                // An attribute with a default value in the DTD is replaced by its default.
+                //
+                if let Some(document) = self.owner_document() {
+                    add_default_attributes(&document, self, Some(&name));
+                }
+            }
+            Ok(old_attribute)
         } else {
             warn!("{}", MSG_INVALID_NODE_TYPE);
             Err(Error::InvalidState)
@@ -586,21 +849,14 @@ impl Element for RefNode {
     }
 
     fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<RefNode> {
+        instrument_operation!(Query);
         let mut results = Vec::default();
         if is_element(self) {
-            let tag_name = tag_name.to_string();
-            let ref_self = self.borrow();
-            if tag_name_match(&ref_self.i_name.to_string(), &tag_name) {
-                results.push(self.clone());
-            }
-            for child_node in &ref_self.i_child_nodes {
-                match as_element(child_node) {
-                    Ok(ref_child) => results.extend(ref_child.get_elements_by_tag_name(&tag_name)),
-                    Err(_) => {
-                        warn!("{}", MSG_INVALID_NODE_TYPE);
-                    }
-                }
-            }
+            collect_elements(
+                self,
+                &|name| tag_name == WILD_CARD || name.matches_qualified_name(tag_name),
+                &mut results,
+            );
         }
         results
     }
@@ -624,16 +880,20 @@ impl Element for RefNode {
         qualified_name: &str,
         value: &str,
     ) -> Result<()> {
+        check_not_read_only(self)?;
+        let _safe_to_ignore = live_owner_document(self)?;
         let attr_name = Name::new_ns(namespace_uri, qualified_name)?;
-        let attr_node = {
+        let mut attr_node = {
             let ref_self = &self.borrow_mut();
             let document = ref_self.i_owner_document.as_ref().unwrap();
             NodeImpl::new_attribute(document.clone(), attr_name, Some(value))
         };
+        keep_user_id_attribute(self, &mut attr_node);
         self.set_attribute_node(RefNode::new(attr_node)).map(|_| ())
     }
 
     fn remove_attribute_ns(&mut self, namespace_uri: &str, local_name: &str) -> Result<()> {
+        check_not_read_only(self)?;
         match self.get_attribute_node_ns(namespace_uri, local_name) {
             None => Ok(()),
             Some(attribute_node) => self.remove_attribute_node(attribute_node).map(|_| ()),
@@ -676,31 +936,21 @@ impl Element for RefNode {
     }
 
     fn get_elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> Vec<RefNode> {
+        instrument_operation!(Query);
         let mut results = Vec::default();
         if is_element(self) {
-            let namespace_uri = namespace_uri.to_string();
-            let local_name = local_name.to_string();
-            let ref_self = self.borrow();
-            if namespaced_name_match(
-                match ref_self.i_name.namespace_uri() {
-                    None => None,
-                    Some(s) => Some(s.as_str()),
+            collect_elements(
+                self,
+                &|name| {
+                    namespaced_name_match(
+                        name.namespace_uri().as_deref(),
+                        name.local_name(),
+                        namespace_uri,
+                        local_name,
+                    )
                 },
-                &ref_self.i_name.local_name(),
-                &namespace_uri,
-                &local_name,
-            ) {
-                results.push(self.clone());
-            }
-            for child_node in &ref_self.i_child_nodes {
-                match as_element(child_node) {
-                    Ok(ref_child) => results
-                        .extend(ref_child.get_elements_by_tag_name_ns(&namespace_uri, &local_name)),
-                    Err(_) => {
-                        warn!("{}", MSG_INVALID_NODE_TYPE);
-                    }
-                }
-            }
+                &mut results,
+            );
         }
         results
     }
@@ -711,9 +961,9 @@ impl Element for RefNode {
                 Ok(name) => {
                     let ref_self = self.borrow();
                     if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
-                        i_attributes
-                            .keys()
-                            .any(|n| n.to_string() == name.to_string())
+                        i_attributes.keys().any(|n| {
+                            n.prefix() == name.prefix() && n.local_name() == name.local_name()
+                        })
                     } else {
                         warn!("{}", MSG_INVALID_EXTENSION);
                         false
@@ -755,6 +1005,32 @@ impl Element for RefNode {
             false
         }
     }
+
+    fn attribute_in_scope(&self, namespace_uri: Option<&str>, local_name: &str) -> Option<String> {
+        let mut current = Some(self.clone());
+        while let Some(element_node) = current {
+            if !is_element(&element_node) {
+                break;
+            }
+            let found = {
+                let ref_element = element_node.borrow();
+                if let Extension::Element { i_attributes, .. } = &ref_element.i_extension {
+                    i_attributes
+                        .iter()
+                        .find(|(name, _)| namespaced_name_in(name, namespace_uri, local_name))
+                        .map(|(_, attribute)| attribute.clone())
+                } else {
+                    warn!("{}", MSG_INVALID_EXTENSION);
+                    None
+                }
+            };
+            if let Some(attribute) = found {
+                return attribute.value();
+            }
+            current = element_node.parent_node();
+        }
+        None
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -793,12 +1069,14 @@ impl Node for RefNode {
     }
 
     fn set_node_value(&mut self, value: &str) -> Result<()> {
+        check_not_read_only(self)?;
         let mut mut_self = self.borrow_mut();
         mut_self.i_value = Some(value.to_string());
         Ok(())
     }
 
     fn unset_node_value(&mut self) -> Result<()> {
+        check_not_read_only(self)?;
         let mut mut_self = self.borrow_mut();
         mut_self.i_value = None;
         Ok(())
@@ -821,16 +1099,19 @@ impl Node for RefNode {
     }
 
     fn child_nodes(&self) -> Vec<RefNode> {
+        hydrate_children(self);
         let ref_self = self.borrow();
         ref_self.i_child_nodes.clone()
     }
 
     fn first_child(&self) -> Option<RefNode> {
+        hydrate_children(self);
         let ref_self = self.borrow();
         ref_self.i_child_nodes.first().cloned()
     }
 
     fn last_child(&self) -> Option<RefNode> {
+        hydrate_children(self);
         let ref_self = self.borrow();
         ref_self.i_child_nodes.last().cloned()
     }
@@ -915,6 +1196,7 @@ impl Node for RefNode {
     }
 
     fn insert_before(&mut self, new_child: RefNode, ref_child: Option<RefNode>) -> Result<RefNode> {
+        instrument_operation!(Insert);
         fn insert_or_append(
             parent_node: &mut RefNode,
             new_child: &RefNode,
@@ -928,10 +1210,16 @@ impl Node for RefNode {
             }
         }
 
+        check_not_read_only(self)?;
+        if let Some(parent_node) = new_child.parent_node() {
+            check_not_read_only(&parent_node)?;
+        }
+        hydrate_children(self);
         if !is_child_allowed(self, &new_child) {
             warn!("The child you tried to add is not valid for this parent.");
             return Err(Error::HierarchyRequest);
         }
+        check_not_ancestor(self, &new_child)?;
 
         //
         // Special case for Document only.
@@ -966,7 +1254,7 @@ impl Node for RefNode {
             },
         };
 
-        check_same_document(self, &new_child)?;
+        let new_child = owned_child(self, new_child)?;
 
         //
         // Remove from it's current parent
@@ -996,23 +1284,33 @@ impl Node for RefNode {
         // Special case
         //
         if is_document_fragment(&new_child) {
-            for (index, child) in new_child.child_nodes().iter().enumerate() {
+            let children = new_child.child_nodes();
+            for (index, child) in children.iter().enumerate() {
                 match insert_position {
                     None => insert_or_append(self, child, None),
                     Some(position) => insert_or_append(self, child, Some(position + index)),
                 }
             }
+            for child in &children {
+                chunk_text(self, child)?;
+            }
         } else {
-            insert_or_append(self, &new_child, insert_position)
+            insert_or_append(self, &new_child, insert_position);
+            chunk_text(self, &new_child)?;
         }
 
         Ok(new_child)
     }
 
     fn replace_child(&mut self, new_child: RefNode, old_child: RefNode) -> Result<RefNode> {
+        instrument_operation!(Insert);
+        check_not_read_only(self)?;
         if !is_child_allowed(self, &new_child) {
             return Err(Error::HierarchyRequest);
         }
+        check_not_ancestor(self, &new_child)?;
+        let new_child = owned_child(self, new_child)?;
+        hydrate_children(self);
         let exists = {
             let ref_self = self.borrow();
             ref_self.i_child_nodes.contains(&old_child.clone())
@@ -1029,6 +1327,8 @@ impl Node for RefNode {
     }
 
     fn remove_child(&mut self, old_child: Self::NodeRef) -> Result<Self::NodeRef> {
+        check_not_read_only(self)?;
+        hydrate_children(self);
         let position = {
             let ref_self = self.borrow();
             ref_self
@@ -1042,6 +1342,7 @@ impl Node for RefNode {
                 Err(Error::NotFound)
             }
             Some(position) => {
+                traversal::pre_remove(&old_child);
                 let removed = {
                     let mut mut_self = self.borrow_mut();
                     mut_self.i_child_nodes.remove(position)
@@ -1062,12 +1363,22 @@ impl Node for RefNode {
     }
 
     fn clone_node(&self, deep: bool) -> Option<RefNode> {
-        let ref_self = self.borrow();
-        let new_node = ref_self.clone_node(deep);
-        Some(RefNode::new(new_node))
+        let new_node = RefNode::new(self.borrow().clone_node(deep));
+        if is_attribute(self) {
+            set_specified(&new_node, true);
+        }
+        if deep {
+            user_data::notify_user_data_deep(UserDataOperation::Cloned, self, &new_node);
+        } else {
+            user_data::notify_user_data(UserDataOperation::Cloned, self, &new_node);
+        }
+        Some(new_node)
     }
 
     fn normalize(&mut self) {
+        if self.borrow().i_read_only {
+            return;
+        }
         for child_node in self.child_nodes() {
             if is_text(&child_node) {
                 if CharacterData::length(&child_node) == 0 {
@@ -1076,13 +1387,14 @@ impl Node for RefNode {
                     }
                 } else if let Some(last_child_node) = child_node.previous_sibling() {
                     let last_child_node = &mut last_child_node.clone();
-                    if is_text(&last_child_node) {
-                        if last_child_node
-                            .append_data(&child_node.node_value().unwrap())
-                            .is_err()
-                        {
-                            panic!("Could not merge text nodes");
-                        }
+                    if is_text(last_child_node) {
+                        // merged directly, as `append_data` would split the merged node again
+                        // if it is longer than `max_text_length`.
+                        last_child_node
+                            .borrow_mut()
+                            .i_value
+                            .get_or_insert_with(String::new)
+                            .push_str(&child_node.node_value().unwrap());
                         if self.remove_child(child_node).is_err() {
                             panic!("Could not remove unnecessary text node");
                         }
@@ -1093,11 +1405,89 @@ impl Node for RefNode {
     }
 
     fn is_supported(&self, feature: &str, version: &str) -> bool {
-        get_implementation().has_feature(feature, version)
+        let document = if self.node_type() == NodeType::Document {
+            Some(self.clone())
+        } else {
+            self.owner_document()
+        };
+        match document {
+            Some(document) => as_document(&document)
+                .map(|document| document.implementation().has_feature(feature, version))
+                .unwrap_or_default(),
+            None => get_implementation().has_feature(feature, version),
+        }
     }
 
     fn has_attributes(&self) -> bool {
-        !self.attributes().is_empty()
+        self.attribute_count() > 0
+    }
+
+    fn attribute_count(&self) -> usize {
+        let ref_self = self.borrow();
+        if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
+            i_attributes.len()
+        } else {
+            0
+        }
+    }
+
+    fn attribute_names(&self) -> Vec<Name> {
+        let ref_self = self.borrow();
+        if let Extension::Element {
+            i_attribute_order, ..
+        } = &ref_self.i_extension
+        {
+            i_attribute_order.clone()
+        } else {
+            Vec::default()
+        }
+    }
+
+    fn is_attached(&self) -> bool {
+        let document = match self.node_type() {
+            NodeType::Document => return true,
+            _ => match self.owner_document() {
+                None => return false,
+                Some(document) => document,
+            },
+        };
+        let mut current = self.clone();
+        loop {
+            let next = if current.node_type() == NodeType::Attribute {
+                as_attribute(&current)
+                    .ok()
+                    .and_then(|attribute| attribute.owner_element())
+            } else {
+                current.parent_node()
+            };
+            match next {
+                None => return current == document,
+                Some(next) => current = next,
+            }
+        }
+    }
+
+    fn base_uri(&self) -> Option<String> {
+        match self.node_type() {
+            NodeType::Document => self.document_uri(),
+            NodeType::Attribute => match as_attribute(self).ok()?.owner_element() {
+                Some(element) => element.base_uri(),
+                None => owner_document_uri(self),
+            },
+            node_type => {
+                let parent_base_uri = match self.parent_node() {
+                    Some(parent) => parent.base_uri(),
+                    None => owner_document_uri(self),
+                };
+                match node_type {
+                    NodeType::Element => match xml_base(self) {
+                        Some(base) => Some(resolve_uri(&base, parent_base_uri.as_deref())),
+                        None => parent_base_uri,
+                    },
+                    _ => parent_base_uri,
+                }
+            }
+        }
     }
 }
 
@@ -1121,6 +1511,7 @@ impl ProcessingInstruction for RefNode {}
 
 impl Text for RefNode {
     fn split(&mut self, offset: usize) -> Result<RefNode> {
+        check_not_read_only(self)?;
         let new_data = {
             let text = as_character_data_mut(self)?;
             let length = text.length();
@@ -1167,6 +1558,7 @@ impl Text for RefNode {
 
 impl Display for RefNode {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        instrument_operation!(Serialize);
         display::fmt_node(self, f)
     }
 }
@@ -1175,13 +1567,391 @@ impl Display for RefNode {
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
-const WILD_CARD: &str = "*";
+pub(crate) const WILD_CARD: &str = "*";
+
+///
+/// Returns the owner element of `attribute` if it is being audited.
+///
+fn audited_owner_element(attribute: &RefNode) -> Option<RefNode> {
+    attribute.owner_element().filter(audit::is_audited)
+}
+
+///
+/// If the owner document has a maximum text length, and `text_node` is a `Text` node longer than
+/// that, truncate `text_node` to the first chunk and insert the remaining chunks as following
+/// siblings in `parent_node`.
+///
+fn chunk_text(parent_node: &mut RefNode, text_node: &RefNode) -> Result<()> {
+    if !is_text(text_node) {
+        return Ok(());
+    }
+    let max_text_length = match text_node.owner_document() {
+        None => return Ok(()),
+        Some(document) => {
+            let ref_document = document.borrow();
+            if let Extension::Document { i_options, .. } = &ref_document.i_extension {
+                match i_options.max_text_length() {
+                    None => return Ok(()),
+                    Some(max_text_length) => max_text_length,
+                }
+            } else {
+                warn!("{}", MSG_INVALID_EXTENSION);
+                return Ok(());
+            }
+        }
+    };
+
+    let mut chunks = {
+        let ref_text = text_node.borrow();
+        match &ref_text.i_value {
+            //
+            // A UTF-8 string never has more UTF-16 code units than bytes.
+            //
+            Some(data) if data.len() > max_text_length => text_chunks(data, max_text_length),
+            _ => return Ok(()),
+        }
+    };
+    if chunks.len() < 2 {
+        return Ok(());
+    }
+    let rest = chunks.split_off(1);
+    let owner_document = {
+        let mut mut_text = text_node.borrow_mut();
+        mut_text.i_value = chunks.pop();
+        mut_text.i_owner_document.clone().unwrap()
+    };
+    let next_node = text_node.next_sibling();
+    for chunk in rest {
+        let chunk_node = RefNode::new(NodeImpl::new_text(owner_document.clone(), &chunk));
+        let _safe_to_ignore = parent_node.insert_before(chunk_node, next_node.clone())?;
+    }
+    Ok(())
+}
+
+///
+/// As `chunk_text`, for a `Text` node whose data has been changed in place; a node without a
+/// parent is chunked when it is inserted.
+///
+fn chunk_text_in_parent(text_node: &RefNode) -> Result<()> {
+    match text_node.parent_node() {
+        Some(mut parent_node) => chunk_text(&mut parent_node, text_node),
+        None => Ok(()),
+    }
+}
+
+///
+/// Returns `true` if the owner document of `node` measures character data in bytes rather than
+/// UTF-16 code units.
+///
+fn has_byte_offsets(node: &RefNode) -> bool {
+    node.owner_document()
+        .is_some_and(|document| document_options(&document).has_byte_offsets())
+}
+
+///
+/// Returns the byte range of `data` selected by `offset` and `count`, which are measured in bytes
+/// if `byte_offsets` is `true`, else in UTF-16 code units; a range that extends past the end of
+/// `data` is truncated.
+///
+/// **Exceptions**
+///
+/// * `INDEX_SIZE_ERR`: Raised if `offset` is greater than the length of `data`, or if either end
+///   of the range does not fall between two characters.
+///
+fn data_range(data: &str, offset: usize, count: usize, byte_offsets: bool) -> Result<Range<usize>> {
+    let length = if byte_offsets {
+        data.len()
+    } else {
+        data.encode_utf16().count()
+    };
+    if offset > length {
+        warn!("{}", MSG_INDEX_ERROR);
+        return Err(Error::IndexSize);
+    }
+    let end = offset.saturating_add(count).min(length);
+    let (start, end) = if byte_offsets {
+        (Some(offset), Some(end))
+    } else {
+        (utf16_byte_index(data, offset), utf16_byte_index(data, end))
+    };
+    match (start, end) {
+        (Some(start), Some(end)) if data.is_char_boundary(start) && data.is_char_boundary(end) => {
+            Ok(start..end)
+        }
+        _ => {
+            warn!("{}", MSG_SPLIT_CHARACTER);
+            Err(Error::IndexSize)
+        }
+    }
+}
 
-fn tag_name_match(test: &str, against: &str) -> bool {
-    (test == against) || test == WILD_CARD || against == WILD_CARD
+///
+/// Returns the byte index in `data` of the UTF-16 code unit `units`, or `None` if that falls
+/// within a surrogate pair; `units` is not greater than the UTF-16 length of `data`.
+///
+fn utf16_byte_index(data: &str, units: usize) -> Option<usize> {
+    let mut position = 0;
+    for (index, c) in data.char_indices() {
+        if position >= units {
+            return (position == units).then_some(index);
+        }
+        position += c.len_utf16();
+    }
+    (position == units).then_some(data.len())
 }
 
-fn namespaced_name_match(
+///
+/// Split `data` into chunks of at most `max_length` UTF-16 code units, only splitting between
+/// characters.
+///
+fn text_chunks(data: &str, max_length: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut start = 0;
+    let mut length = 0;
+    for (index, c) in data.char_indices() {
+        let char_length = c.len_utf16();
+        if length > 0 && length + char_length > max_length {
+            chunks.push(data[start..index].to_string());
+            start = index;
+            length = 0;
+        }
+        length += char_length;
+    }
+    chunks.push(data[start..].to_string());
+    chunks
+}
+
+///
+/// Push `element`, if its name is accepted by `matches`, and then all matching descendant elements
+/// onto `results` in document order; no intermediate strings or vectors are allocated.
+///
+fn collect_elements<F>(element: &RefNode, matches: &F, results: &mut Vec<RefNode>)
+where
+    F: Fn(&Name) -> bool,
+{
+    hydrate_children(element);
+    let ref_element = element.borrow();
+    if matches(&ref_element.i_name) {
+        results.push(element.clone());
+    }
+    for child_node in &ref_element.i_child_nodes {
+        if is_element(child_node) {
+            collect_elements(child_node, matches, results);
+        }
+    }
+}
+
+///
+/// Returns `true` if an attribute named `name`, of an element named `element_name`, is an ID
+/// attribute in the document `document`; according to its processing options, implementation,
+/// and the attribute definitions of its document type.
+///
+fn is_id_attribute_of(document: &RefNode, element_name: &Name, name: &Name) -> bool {
+    is_intrinsic_id_attribute(document, name) || {
+        let ref_document = document.borrow();
+        if let Extension::Document {
+            i_document_type, ..
+        } = &ref_document.i_extension
+        {
+            i_document_type
+                .as_ref()
+                .is_some_and(|doc_type| is_declared_id_attribute(doc_type, element_name, name))
+        } else {
+            false
+        }
+    }
+}
+
+///
+/// Returns `true` if an attribute named `name` is an ID attribute in the document `document`,
+/// according to its processing options and implementation, regardless of its element.
+///
+fn is_intrinsic_id_attribute(document: &RefNode, name: &Name) -> bool {
+    let ref_document = document.borrow();
+    if let Extension::Document {
+        i_options,
+        i_implementation,
+        ..
+    } = &ref_document.i_extension
+    {
+        name.is_id_attribute(i_options.has_assume_ids()) || i_implementation.is_id_attribute(name)
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        name.is_id_attribute(false)
+    }
+}
+
+///
+/// Returns `true` if the first definition of the attribute `name` for the element type
+/// `element_name` in `doc_type` has the type `ID`.
+///
+fn is_declared_id_attribute(doc_type: &RefNode, element_name: &Name, name: &Name) -> bool {
+    let ref_doc_type = doc_type.borrow();
+    if let Extension::DocumentType {
+        i_attribute_definitions,
+        ..
+    } = &ref_doc_type.i_extension
+    {
+        i_attribute_definitions
+            .iter()
+            .find(|definition| {
+                //
+                // Declarations are not namespace aware, compare the qualified names only.
+                //
+                is_same_qualified_name(definition.element_name(), element_name)
+                    && is_same_qualified_name(definition.attribute_name(), name)
+            })
+            .is_some_and(|definition| definition.is_id())
+    } else {
+        false
+    }
+}
+
+///
+/// Returns `true` if `attribute` has been marked as an ID attribute by the user, with
+/// `set_id_attribute` or one of its variants.
+///
+fn is_user_id_attribute(attribute: &RefNode) -> bool {
+    matches!(
+        attribute.borrow().i_extension,
+        Extension::Attribute { i_is_id: true, .. }
+    )
+}
+
+///
+/// If `element` has an attribute with the same name as `new_attribute`, that has been marked as
+/// an ID attribute by the user, mark `new_attribute` also; as setting the value of an attribute
+/// replaces its node.
+///
+fn keep_user_id_attribute(element: &RefNode, new_attribute: &mut NodeImpl) {
+    let is_user_id = element
+        .attributes()
+        .get(&new_attribute.i_name)
+        .is_some_and(is_user_id_attribute);
+    if let Extension::Attribute { i_is_id, .. } = &mut new_attribute.i_extension {
+        *i_is_id = is_user_id;
+    }
+}
+
+///
+/// Add to `element` an attribute, that is not `specified`, for each attribute definition of its
+/// element type with a default value in the document type of `document`; or only for the
+/// attribute named `only`.
+///
+pub(crate) fn add_default_attributes(document: &RefNode, element: &RefNode, only: Option<&Name>) {
+    let element_name = element.node_name();
+    let mut defaults: Vec<(Name, String)> = Vec::new();
+    if let Some(doc_type) = document.doc_type() {
+        if let Extension::DocumentType {
+            i_attribute_definitions,
+            ..
+        } = &doc_type.borrow().i_extension
+        {
+            let mut seen: Vec<&Name> = Vec::new();
+            for definition in i_attribute_definitions.iter().filter(|definition| {
+                is_same_qualified_name(definition.element_name(), &element_name)
+            }) {
+                //
+                // Only the first definition of an attribute is binding.
+                //
+                let name = definition.attribute_name();
+                if seen.iter().any(|seen| is_same_qualified_name(seen, name)) {
+                    continue;
+                }
+                seen.push(name);
+                if let Some(default_value) = definition.default_value() {
+                    let wanted = match only {
+                        None => true,
+                        Some(only) => is_same_qualified_name(only, name),
+                    };
+                    if wanted {
+                        defaults.push((name.clone(), default_value.to_string()));
+                    }
+                }
+            }
+        }
+    }
+    for (name, default_value) in defaults {
+        let attribute =
+            NodeImpl::new_attribute(document.clone().downgrade(), name, Some(&default_value));
+        let attribute = RefNode::new(attribute);
+        set_specified(&attribute, false);
+        let _safe_to_ignore = element.clone().set_attribute_node(attribute);
+    }
+}
+
+///
+/// Set the `specified` flag of the attribute `attribute`.
+///
+fn set_specified(attribute: &RefNode, specified: bool) {
+    if let Extension::Attribute { i_specified, .. } = &mut attribute.borrow_mut().i_extension {
+        *i_specified = specified;
+    }
+}
+
+fn is_same_qualified_name(lhs: &Name, rhs: &Name) -> bool {
+    lhs.prefix() == rhs.prefix() && lhs.local_name() == rhs.local_name()
+}
+
+///
+/// The value of an ID attribute, unescaped, with leading and trailing spaces removed and other
+/// sequences of spaces replaced by a single space, as required by `xml:id` §4.
+///
+fn id_value_of(attribute: &RefNode) -> String {
+    let value: String = attribute
+        .child_nodes()
+        .iter()
+        .filter_map(|child_node| child_node.node_value())
+        .collect();
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+///
+/// Returns `true` if `element` has an ID attribute with the value `id`.
+///
+fn has_id(document: &RefNode, element: &RefNode, id: &str) -> bool {
+    let ref_element = element.borrow();
+    if let Extension::Element { i_attributes, .. } = &ref_element.i_extension {
+        i_attributes.iter().any(|(name, attribute)| {
+            (is_user_id_attribute(attribute)
+                || is_id_attribute_of(document, &ref_element.i_name, name))
+                && id_value_of(attribute) == id
+        })
+    } else {
+        false
+    }
+}
+
+///
+/// Returns `true` if `node` is a descendant of the document node `document`.
+///
+fn is_in_document(document: &RefNode, node: &RefNode) -> bool {
+    let mut parent_node = node.parent_node();
+    while let Some(node) = parent_node {
+        if node == *document {
+            return true;
+        }
+        parent_node = node.parent_node();
+    }
+    false
+}
+
+///
+/// Returns `element`, or the first of its descendant elements in document order, with an ID
+/// attribute with the value `id`.
+///
+fn find_element_by_id(document: &RefNode, element: &RefNode, id: &str) -> Option<RefNode> {
+    if has_id(document, element, id) {
+        return Some(element.clone());
+    }
+    element
+        .child_nodes()
+        .iter()
+        .filter(|child_node| is_element(child_node))
+        .find_map(|child_node| find_element_by_id(document, child_node, id))
+}
+
+pub(crate) fn namespaced_name_match(
     test_ns: Option<&str>,
     test_local: &str,
     against_ns: &str,
@@ -1203,44 +1973,194 @@ fn namespaced_name_match(
     }
 }
 
+fn replace_document_uri(document: &RefNode, document_uri: Option<String>) {
+    let mut mut_document = document.borrow_mut();
+    if let Extension::Document { i_document_uri, .. } = &mut mut_document.i_extension {
+        *i_document_uri = document_uri;
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+    }
+}
+
+fn owner_document_uri(node: &RefNode) -> Option<String> {
+    node.owner_document()?.document_uri()
+}
+
+//
+// The value of the `xml:base` attribute on `element` itself, not inherited from its ancestors.
+//
+fn xml_base(element: &RefNode) -> Option<String> {
+    let attribute = {
+        let ref_element = element.borrow();
+        if let Extension::Element { i_attributes, .. } = &ref_element.i_extension {
+            i_attributes
+                .iter()
+                .find(|(name, _)| namespaced_name_in(name, Some(XML_NS_URI), XML_NS_ATTR_BASE))
+                .map(|(_, attribute)| attribute.clone())
+        } else {
+            None
+        }
+    };
+    attribute?.value()
+}
+
+//
+// Does `name` identify the attribute `{namespace_uri}local_name`; the `xml` prefix is always bound
+// to the XML namespace, even on names created without a namespace URI.
+//
+fn namespaced_name_in(name: &Name, namespace_uri: Option<&str>, local_name: &str) -> bool {
+    if name.local_name() != local_name {
+        return false;
+    }
+    match (name.namespace_uri(), namespace_uri) {
+        (Some(name_ns), Some(namespace_uri)) => name_ns == namespace_uri,
+        (None, Some(namespace_uri)) => {
+            namespace_uri == XML_NS_URI && name.prefix().as_deref() == Some(XML_NS_ATTRIBUTE)
+        }
+        (None, None) => name.prefix().is_none(),
+        (Some(_), None) => false,
+    }
+}
+
 //
 // CHECK: Raise `Error::WrongDocument` if `newChild` was created from a different
-// document than the one that created this node.
+// document than the one that created this node; a document is its own owner, and a node with no
+// owner document may be added anywhere. With the `strict_owner_document` option every node in the
+// subtree of `new_child` must be owned by the document, and a node with no owner is rejected.
 //
 fn check_same_document(self_node: &RefNode, new_child: &RefNode) -> Result<()> {
-    {
-        if self_node.node_type() == NodeType::Document {
-            let child_document = &new_child.borrow().i_owner_document;
-            if !match child_document {
-                None => true,
-                Some(child_document) => {
-                    let child_document = child_document.clone().upgrade().unwrap();
-                    self_node == &child_document
-                }
-            } {
-                warn!("Error::WrongDocument: child could not be added to the document node.");
-                return Err(Error::WrongDocument);
+    let self_document = if self_node.node_type() == NodeType::Document {
+        Some(self_node.clone())
+    } else {
+        self_node.owner_document()
+    };
+    match self_document {
+        Some(self_document) if document_options(&self_document).has_strict_owner_document() => {
+            new_child.verify_owner(&self_document)
+        }
+        self_document => match (self_document, new_child.owner_document()) {
+            (_, None) => Ok(()),
+            (Some(self_document), Some(child_document)) if self_document == child_document => {
+                Ok(())
             }
-        } else {
-            let self_document = &self_node.borrow().i_owner_document;
-            let child_document = &new_child.borrow().i_owner_document;
-            if !match (self_document, child_document) {
-                (None, None) => true,
-                (Some(_), None) => true,
-                (None, Some(_)) => false,
-                (Some(self_document), Some(child_document)) => {
-                    let self_document = self_document.clone().upgrade().unwrap();
-                    let child_document = child_document.clone().upgrade().unwrap();
-                    self_document == child_document
+            _ => {
+                warn!("{}", MSG_WRONG_DOCUMENT);
+                Err(Error::WrongDocument)
+            }
+        },
+    }
+}
+
+///
+/// Returns the attributes of `element` in the order they were added, an attribute replaced by one
+/// with the same name keeps the position of the original; empty if `element` is not an element.
+///
+pub(crate) fn ordered_attributes(element: &RefNode) -> Vec<(Name, RefNode)> {
+    let ref_element = element.borrow();
+    if let Extension::Element {
+        i_attributes,
+        i_attribute_order,
+        ..
+    } = &ref_element.i_extension
+    {
+        i_attribute_order
+            .iter()
+            .filter_map(|name| {
+                i_attributes
+                    .get(name)
+                    .map(|attribute| (name.clone(), attribute.clone()))
+            })
+            .collect()
+    } else {
+        Vec::default()
+    }
+}
+
+///
+/// Returns the owner document of `node`, or `node` itself if it is a document.
+///
+/// **Exceptions**
+///
+/// * `INVALID_STATE_ERR`: Raised if the owner document of `node` has been dropped, as the
+///   operation requires it; see [Node Handles](../index.html#node-handles).
+///
+pub(crate) fn live_owner_document(node: &RefNode) -> Result<RefNode> {
+    if node.node_type() == NodeType::Document {
+        return Ok(node.clone());
+    }
+    node.owner_document().ok_or_else(|| {
+        warn!("{}", MSG_DOCUMENT_DROPPED);
+        Error::InvalidState
+    })
+}
+
+//
+// CHECK: Raise `Error::NoModificationAllowed` if `node` is read-only; document types, entities,
+// entity references, and notations, are read-only, as are the descendants of entities and entity
+// references.
+//
+fn check_not_read_only(node: &RefNode) -> Result<()> {
+    if node.borrow().i_read_only {
+        warn!("{}", MSG_READ_ONLY_NODE);
+        Err(Error::NoModificationAllowed)
+    } else {
+        Ok(())
+    }
+}
+
+///
+/// Check that `new_child` may be inserted into `self_node` as for `check_same_document`; returning
+/// `new_child`, or, with the `auto_import` option, a copy imported into the document of
+/// `self_node` where `new_child` is owned by a different document.
+///
+fn owned_child(self_node: &RefNode, new_child: RefNode) -> Result<RefNode> {
+    match check_same_document(self_node, &new_child) {
+        Err(Error::WrongDocument) => {
+            let self_document = if self_node.node_type() == NodeType::Document {
+                Some(self_node.clone())
+            } else {
+                self_node.owner_document()
+            };
+            match self_document {
+                Some(self_document) if document_options(&self_document).has_auto_import() => {
+                    import_node(&mut Default::default(), &self_document, &new_child, true)
                 }
-            } {
-                warn!("Error::WrongDocument: child could not be added to the current node.");
-                return Err(Error::WrongDocument);
+                _ => Err(Error::WrongDocument),
             }
         }
+        Err(e) => Err(e),
+        Ok(()) => Ok(new_child),
+    }
+}
+
+///
+/// Returns the processing options of the document `document`.
+///
+fn document_options(document: &RefNode) -> ProcessingOptions {
+    if let Extension::Document { i_options, .. } = &document.borrow().i_extension {
+        i_options.clone()
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        Default::default()
+    }
+}
+
+//
+// CHECK: Raise `Error::HierarchyRequest` if `new_child` is `self_node`, or one of its ancestors;
+// inserting it would create a cycle in the tree.
+//
+fn check_not_ancestor(self_node: &RefNode, new_child: &RefNode) -> Result<()> {
+    let mut current = Some(self_node.clone());
+    while let Some(node) = current {
+        if &node == new_child {
+            warn!("{}", MSG_ANCESTOR_INSERTION);
+            return Err(Error::HierarchyRequest);
+        }
+        current = node.parent_node();
     }
     Ok(())
 }
+
 //
 // From [https://www.w3.org/TR/DOM-Level-2-Core/core.html#ID-1590626202]
 //
@@ -1262,6 +2182,10 @@ fn check_same_document(self_node: &RefNode, new_child: &RefNode) -> Result<()> {
 // * Entity -- Element, ProcessingInstruction, Comment, Text, CDATASection, EntityReference
 // * Notation -- no children
 //
+// In addition, the unparsed markup extension may be a child of any node that may have a
+// `Comment` child, and has no children itself.
+//
+#[allow(clippy::match_like_matches_macro)]
 fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
     let self_node_type = { &parent.borrow().i_node_type };
     let child_node_type = { &child.borrow().i_node_type };
@@ -1272,7 +2196,8 @@ fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
             | NodeType::Comment
             | NodeType::ProcessingInstruction
             | NodeType::CData
-            | NodeType::EntityReference => true,
+            | NodeType::EntityReference
+            | NodeType::UnparsedMarkup => true,
             _ => false,
         },
         NodeType::Attribute => match child_node_type {
@@ -1287,7 +2212,8 @@ fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
             | NodeType::Comment
             | NodeType::ProcessingInstruction
             | NodeType::CData
-            | NodeType::EntityReference => true,
+            | NodeType::EntityReference
+            | NodeType::UnparsedMarkup => true,
             _ => false,
         },
         NodeType::Entity => match child_node_type {
@@ -1296,13 +2222,17 @@ fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
             | NodeType::Comment
             | NodeType::ProcessingInstruction
             | NodeType::CData
-            | NodeType::EntityReference => true,
+            | NodeType::EntityReference
+            | NodeType::UnparsedMarkup => true,
             _ => false,
         },
         NodeType::ProcessingInstruction => false,
         NodeType::Comment => false,
         NodeType::Document => match child_node_type {
-            NodeType::Element | NodeType::Comment | NodeType::ProcessingInstruction => true,
+            NodeType::Element
+            | NodeType::Comment
+            | NodeType::ProcessingInstruction
+            | NodeType::UnparsedMarkup => true,
             _ => false,
         },
         NodeType::DocumentType => false,
@@ -1312,21 +2242,28 @@ fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
             | NodeType::Comment
             | NodeType::ProcessingInstruction
             | NodeType::CData
-            | NodeType::EntityReference => true,
+            | NodeType::EntityReference
+            | NodeType::UnparsedMarkup => true,
             _ => false,
         },
         NodeType::Notation => false,
+        NodeType::UnparsedMarkup => false,
     }
 }
 
 pub(crate) fn create_document_with_options(
+    implementation: Rc<Implementation>,
     namespace_uri: Option<&str>,
     qualified_name: Option<&str>,
     doc_type: Option<RefNode>,
     options: ProcessingOptions,
 ) -> Result<RefNode> {
-    let node_impl = NodeImpl::new_document(doc_type, options);
+    instrument_operation!(Create(NodeType::Document));
+    let node_impl = NodeImpl::new_document(implementation, doc_type.clone(), options);
     let mut document_node = RefNode::new(node_impl);
+    if let Some(doc_type) = doc_type {
+        adopt_document_type(&document_node, &doc_type);
+    }
 
     //
     // If specified, create a new root element
@@ -1353,3 +2290,58 @@ pub(crate) fn create_document_with_options(
 
     Ok(document_node)
 }
+
+///
+/// Returns `true` if `attribute` is an ID attribute; either because it has been marked as one by
+/// the user, or because of its name, the configuration of its document, and the attribute
+/// definitions of the document type for its owner element.
+///
+pub(crate) fn is_id_attribute_node(attribute: &RefNode) -> bool {
+    if is_user_id_attribute(attribute) {
+        return true;
+    }
+    let name = attribute.node_name();
+    match (attribute.owner_document(), as_attribute(attribute)) {
+        (Some(document), Ok(attribute)) => match attribute.owner_element() {
+            Some(element) => is_id_attribute_of(&document, &element.node_name(), &name),
+            None => is_intrinsic_id_attribute(&document, &name),
+        },
+        _ => false,
+    }
+}
+
+///
+/// Mark, or unmark, the attribute `attribute` of `element` as an ID attribute, updating the ID
+/// map of the owning document. A user-determined ID that is already used by a different element is
+/// not an error, the ID is ambiguous and `get_element_by_id` searches in document order.
+///
+pub(crate) fn set_user_id_attribute(element: &RefNode, attribute: &RefNode, is_id: bool) {
+    if let Extension::Attribute { i_is_id, .. } = &mut attribute.borrow_mut().i_extension {
+        *i_is_id = is_id;
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        return;
+    }
+    if let (true, Some(document)) = (is_id, element.owner_document()) {
+        let id_value = id_value_of(attribute);
+        let mapped = if let Extension::Document { i_id_map, .. } = &document.borrow().i_extension {
+            i_id_map
+                .get(&id_value)
+                .and_then(|weak_ref| weak_ref.clone().upgrade())
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            return;
+        };
+        let is_ambiguous = matches!(
+            mapped,
+            Some(mapped) if mapped != *element && has_id(&document, &mapped, &id_value)
+        );
+        if let Extension::Document { i_id_map, .. } = &mut document.borrow_mut().i_extension {
+            if is_ambiguous {
+                let _safe_to_ignore = i_id_map.remove(&id_value);
+            } else {
+                let _safe_to_ignore = i_id_map.insert(id_value, element.clone().downgrade());
+            }
+        }
+    }
+}
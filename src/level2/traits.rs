@@ -1,7 +1,8 @@
-use crate::shared::error::Result;
+use crate::shared::error::{Error, Result, MSG_INVALID_PI_DATA};
 use crate::shared::name::Name;
 use crate::shared::text;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 // ------------------------------------------------------------------------------------------------
 // Public Traits
@@ -61,9 +62,7 @@ pub trait Attribute: Node {
     /// * If the `ownerElement` attribute is `null` (i.e. because it was just created or was set to
     ///   `null` by the various removal and cloning operations) specified is `true`.
     ///
-    fn specified(&self) -> bool {
-        true
-    }
+    fn specified(&self) -> bool;
     ///
     /// The `Element` node this attribute is attached to or `null` if this attribute is not in use.
     ///
@@ -129,10 +128,16 @@ pub trait CharacterData: Node {
     ///
     /// **Note:** This implementation drops the `_data` suffix from the methods for clarity.
     ///
+    /// **Note:** the length, and the offsets and counts of the methods below, are measured in
+    /// UTF-16 code units of the node value, unless the owner document was created with the
+    /// [`ProcessingOptions::set_byte_offsets`](ext/options/struct.ProcessingOptions.html#method.set_byte_offsets)
+    /// option, in which case they are measured in bytes. An offset, or the end of a range, that
+    /// falls within a character, including within a surrogate pair, raises `INDEX_SIZE_ERR`.
+    ///
     fn length(&self) -> usize {
-        match self.data() {
+        match self.node_value() {
             None => 0,
-            Some(s) => s.len(),
+            Some(s) => s.encode_utf16().count(),
         }
     }
     ///
@@ -195,7 +200,8 @@ pub trait CharacterData: Node {
     /// **Exceptions**
     ///
     /// * `INDEX_SIZE_ERR`: Raised if the specified `offset` is negative or greater than the
-    ///   number of 16-bit units in data, or if the specified `count` is negative.
+    ///   number of 16-bit units in data, or if the specified `count` is negative. In this
+    ///   implementation, also raised if `offset`, or the end of the range, falls within a character.
     /// * `DOMSTRING_SIZE_ERR`: Raised if the specified range of text does not fit into a `DOMString`.
     ///
     fn substring_data(&self, offset: usize, count: usize) -> Result<String>;
@@ -215,6 +221,21 @@ pub trait CharacterData: Node {
     ///
     fn append_data(&mut self, data: &str) -> Result<()>;
     ///
+    /// Implementation defined extension: this is the same as calling `append_data` for each
+    /// string in `data`, in order, except that storage for the combined data is allocated once
+    /// rather than for each string.
+    ///
+    fn append_many<I, S>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        Self: Sized;
+    ///
+    /// Implementation defined extension: reserve storage for at least `additional` more bytes of
+    /// character data, so that subsequent appends up to that length do not reallocate.
+    ///
+    fn reserve(&mut self, additional: usize) -> Result<()>;
+    ///
     /// Insert a string at the specified 16-bit unit offset.
     ///
     /// # Specification
@@ -227,7 +248,8 @@ pub trait CharacterData: Node {
     /// **Exceptions**
     ///
     /// * `INDEX_SIZE_ERR`: Raised if the specified `offset` is negative or greater than the number
-    ///   of 16-bit units in data.
+    ///   of 16-bit units in data. In this implementation, also raised if `offset` falls within a
+    ///   character.
     /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
     ///
     fn insert_data(&mut self, offset: usize, data: &str) -> Result<()>;
@@ -246,7 +268,8 @@ pub trait CharacterData: Node {
     /// **Exceptions**
     ///
     /// * `INDEX_SIZE_ERR`: Raised if the specified `offset` is negative or greater than the number
-    ///   of 16-bit units in data, or if the specified `count` is negative.
+    ///   of 16-bit units in data, or if the specified `count` is negative. In this implementation,
+    ///   also raised if `offset`, or the end of the range, falls within a character.
     /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
     ///
     fn delete_data(&mut self, offset: usize, count: usize) -> Result<()>;
@@ -263,10 +286,12 @@ pub trait CharacterData: Node {
     ///   replaced; (i.e., the effect is the same as a remove method call with the same range,
     ///   followed by an append method invocation).
     /// * `arg` of type `DOMString`: The `DOMString` with which the range must be replaced.
-    /// Exceptions
+    ///
+    /// **Exceptions**
     ///
     /// INDEX_SIZE_ERR: Raised if the specified `offset` is negative or greater than the number
-    ///   of 16-bit units in data, or if the specified `count` is negative.
+    ///   of 16-bit units in data, or if the specified `count` is negative. In this implementation,
+    ///   also raised if `offset`, or the end of the range, falls within a character.
     /// NO_MODIFICATION_ALLOWED_ERR: Raised if this node is readonly.
     ///
     fn replace_data(&mut self, offset: usize, count: usize, data: &str) -> Result<()>;
@@ -314,7 +339,10 @@ pub trait Document: Node {
     ///
     fn document_element(&self) -> Option<Self::NodeRef>;
     ///
-    /// The DOMImplementation object that handles this document.
+    /// The DOMImplementation object that handles this document. This is the implementation that
+    /// created the document, and so any configuration it carries, or the shared default
+    /// implementation for documents created by other means; see the
+    /// [`dom_impl`](dom_impl/index.html) module.
     ///
     /// Note: this function will panic if for some reason an implementation is not associated
     /// with the document instance.
@@ -323,7 +351,26 @@ pub trait Document: Node {
     ///
     /// A DOM application may use objects from multiple implementations.
     ///
-    fn implementation(&self) -> &dyn DOMImplementation<NodeRef = Self::NodeRef>;
+    fn implementation(&self) -> Rc<dyn DOMImplementation<NodeRef = Self::NodeRef>>;
+    ///
+    /// The location of the document, used as the base URI of the document and, unless changed
+    /// by an `xml:base` attribute, of the nodes within it; see [`Node::base_uri`](trait.Node.html#tymethod.base_uri).
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `documentURI` attribute. No lexical checking is
+    /// performed when setting this attribute; this could result in a `null` value returned when
+    /// using `Node.baseURI`.
+    ///
+    fn document_uri(&self) -> Option<String>;
+    ///
+    /// Set the location of the document, see [`document_uri`](#tymethod.document_uri).
+    ///
+    fn set_document_uri(&mut self, document_uri: &str);
+    ///
+    /// Remove the location of the document, see [`document_uri`](#tymethod.document_uri).
+    ///
+    fn unset_document_uri(&mut self);
     ///
     /// Creates an [`Attribute`](trait.Attribute.html) of the given name. Note that the `Attr`
     /// instance can then be set on an [`Element`](trait.Element.html) using the `setAttributeNode`
@@ -575,7 +622,11 @@ pub trait Document: Node {
     /// XML namespace will be treated as identifiers. If the
     /// [`ProcessingOptions::set_assume_ids`](struct.ProcessingOptions.html#method.set_assume_ids)
     /// method is used when constructing a document any attribute with the local name `id` will
-    /// be treated as identifiers.
+    /// be treated as identifiers. Any attribute whose first definition in the internal subset of
+    /// the document type has the type `ID` is also treated as an identifier. Only elements in the
+    /// document tree are returned; if more than one has the ID, the first in document order is
+    /// returned. The value of an `xml:id` attribute is normalized, as required by the `xml:id`
+    /// recommendation, before comparison.
     ///
     /// # Specification
     ///
@@ -910,7 +961,7 @@ pub trait Element: Node {
     /// **Return Value**
     ///
     /// * `DOMString`: The `Attr` value as a string, or the empty string if that attribute does not
-    /// have a specified or default value.
+    ///   have a specified or default value.
     ///
     fn get_attribute(&self, name: &str) -> Option<String>;
     ///
@@ -931,7 +982,7 @@ pub trait Element: Node {
     /// **Parameters**
     ///
     /// * `name` of type `DOMString`: The name of the attribute to create or alter.
-    /// `value` of type `DOMString`: Value to set in string form.
+    /// * `value` of type `DOMString`: Value to set in string form.
     ///
     /// **Exceptions**
     ///
@@ -972,7 +1023,7 @@ pub trait Element: Node {
     /// **Return Value**
     ///
     /// * `Attr`: The `Attr` node with the specified name (`nodeName`) or null if there is no such
-    /// attribute.
+    ///   attribute.
     ///
     fn get_attribute_node(&self, name: &str) -> Option<Self::NodeRef>;
     ///
@@ -1092,7 +1143,7 @@ pub trait Element: Node {
     ///   prefix and the `namespaceURI` is null, if the `qualifiedName` has a prefix that is "xml"
     ///   and the `namespaceURI` is different from "http://www.w3.org/XML/1998/namespace", or if
     ///   the `qualifiedName` is "xmlns" and the `namespaceURI` is different from
-    /// "http://www.w3.org/2000/xmlns/".
+    ///   "http://www.w3.org/2000/xmlns/".
     ///
     fn set_attribute_ns(
         &mut self,
@@ -1221,7 +1272,7 @@ pub trait Element: Node {
     /// **Parameters**
     ///
     /// * `namespaceURI` of type `DOMString`: The namespace URI of the attribute to look for.
-    /// `localName` of type `DOMString`: The local name of the attribute to look for.
+    /// * `localName` of type `DOMString`: The local name of the attribute to look for.
     ///
     /// **Return Value**
     ///
@@ -1229,6 +1280,27 @@ pub trait Element: Node {
     ///   specified or has a default value on this element, `false` otherwise.
     ///
     fn has_attribute_ns(&self, namespace_uri: &str, local_name: &str) -> bool;
+    ///
+    /// Implementation defined extension: retrieves the value of an attribute by namespace URI and
+    /// local name from this element or, if not present, from the nearest ancestor element that
+    /// has it. This is the lookup required for inherited attributes such as `xml:lang`,
+    /// `xml:space`, and `xml:base`, or any application-defined attribute with similar semantics.
+    ///
+    /// Attributes named with the prefix `xml` are treated as being in the XML namespace even if
+    /// they were created without a namespace URI.
+    ///
+    /// **Parameters**
+    ///
+    /// * `namespace_uri` of type `DOMString`: The namespace URI of the attribute to retrieve, or
+    ///   `None` for an attribute with no namespace.
+    /// * `local_name` of type `DOMString`: The local name of the attribute to retrieve.
+    ///
+    /// **Return Value**
+    ///
+    /// * `DOMString`: The value of the closest attribute in scope, or `None` if neither this
+    ///   element or any ancestor has such an attribute.
+    ///
+    fn attribute_in_scope(&self, namespace_uri: Option<&str>, local_name: &str) -> Option<String>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -1377,7 +1449,7 @@ pub trait Node {
     /// **Exceptions on retrieval**
     ///
     /// * `DOMSTRING_SIZE_ERR`: Raised when it would return more characters than fit in a DOMString
-    /// variable on the implementation platform.
+    ///   variable on the implementation platform.
     ///
     fn node_value(&self) -> Option<String>;
     ///
@@ -1402,6 +1474,11 @@ pub trait Node {
     /// A `Vec` that contains all children of this node. If there are no children,
     /// this is a `Vec` containing no nodes.
     ///
+    /// Unlike the live DOM `NodeList` this is a snapshot of the children at the time of the call,
+    /// so this node may be modified while iterating over it; see
+    /// [Mutation During Iteration](../ext/query/index.html#mutation-during-iteration). A live list
+    /// is returned by [`NodeLists::live_child_nodes`](trait.NodeLists.html#tymethod.live_child_nodes).
+    ///
     fn child_nodes(&self) -> Vec<Self::NodeRef>;
     ///
     /// The first child of this node. If there is no such node, this returns `None`.
@@ -1421,7 +1498,8 @@ pub trait Node {
     fn next_sibling(&self) -> Option<Self::NodeRef>;
     ///
     /// A `HashMap` containing the attributes of this node (if it is an `Element`) or
-    /// `None` otherwise.
+    /// `None` otherwise. The map is unordered, `attribute_names` returns the names in the order
+    /// the attributes were added.
     ///
     fn attributes(&self) -> HashMap<Name, Self::NodeRef>;
     ///
@@ -1633,6 +1711,53 @@ pub trait Node {
     ///
     fn has_attributes(&self) -> bool;
     ///
+    /// Implementation defined extension: returns the number of attributes on this node (if it is
+    /// an element), without copying the attribute map as `attributes().len()` would.
+    ///
+    /// **Return Value**
+    ///
+    /// * `usize`: the number of attributes, `0` if this node is not an element.
+    ///
+    fn attribute_count(&self) -> usize;
+    ///
+    /// Implementation defined extension: returns the names of all attributes on this node (if it
+    /// is an element), without copying the attribute nodes themselves. The names are returned in
+    /// the order the attributes were added, an attribute replaced by one with the same name keeps
+    /// the position of the original.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Vec<Name>`: the attribute names, empty if this node is not an element.
+    ///
+    fn attribute_names(&self) -> Vec<Name>;
+    ///
+    /// Implementation defined extension: returns whether this node is attached to a live
+    /// document, that is it is a document, or it can be reached from its owner document through
+    /// its ancestors (and, for an attribute, its owner element), and that document has not been
+    /// dropped; see [Node Handles](index.html#node-handles).
+    ///
+    /// **Return Value**
+    ///
+    /// * `boolean`: `true` if this node is in the tree of its owner document, `false` if it has
+    ///   not yet been inserted, has been removed, or its owner document has been dropped.
+    ///
+    fn is_attached(&self) -> bool;
+    ///
+    /// The absolute base URI of this node, against which relative references within it are
+    /// resolved, or `None` if none can be determined.
+    ///
+    /// # Specification
+    ///
+    /// This corresponds to the DOM Level 3 `baseURI` attribute, computed as described in
+    /// [XML Base](https://www.w3.org/TR/xmlbase/). The base URI of the document is its
+    /// `document_uri`. The base URI of an element is the value of its `xml:base` attribute,
+    /// resolved against the base URI of its parent, or if it has no such attribute the base URI
+    /// of its parent. An attribute has the base URI of its owner element, and all other nodes
+    /// the base URI of their parent. A node with no parent, such as one not yet inserted into
+    /// the document, uses the `document_uri` of its owner document.
+    ///
+    fn base_uri(&self) -> Option<String>;
+    ///
     /// The namespace URI of this node, or null if it is unspecified.
     ///
     /// # Specification
@@ -1747,10 +1872,13 @@ pub trait ProcessingInstruction: Node {
     ///
     /// The number of 16-bit units that are available through `data`.
     ///
+    /// **Note:** unlike the byte length of the Rust `String` returned by `data`, this counts the
+    /// UTF-16 code units required to encode the data, as the specification requires.
+    ///
     fn length(&self) -> usize {
         match self.data() {
             None => 0,
-            Some(s) => s.len(),
+            Some(s) => s.encode_utf16().count(),
         }
     }
     ///
@@ -1764,6 +1892,8 @@ pub trait ProcessingInstruction: Node {
     /// **Exceptions on setting**
     ///
     /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised when the node is readonly.
+    /// * `SYNTAX_ERR`: Raised if the data contains the sequence `'?>'` which would terminate the
+    ///   processing instruction.
     ///
     fn data(&self) -> Option<String> {
         Node::node_value(self)
@@ -1772,6 +1902,10 @@ pub trait ProcessingInstruction: Node {
     /// Set the `data` for the node; see [`data`](#tymethod.data).
     ///
     fn set_data(&mut self, data: &str) -> Result<()> {
+        if !text::is_processing_instruction_data(data) {
+            warn!("{}", MSG_INVALID_PI_DATA);
+            return Err(Error::Syntax);
+        }
         Node::set_node_value(self, data)
     }
     ///
@@ -1837,7 +1971,9 @@ pub trait Text: CharacterData {
     /// **Exceptions**
     ///
     /// * `INDEX_SIZE_ERR`: Raised if the specified offset is negative or greater than the number
-    ///   of 16-bit units in data.
+    ///   of 16-bit units in data. In this implementation, raised if `offset` falls within a
+    ///   character; an `offset` greater than the length is treated as equal to the length, and the
+    ///   new node has no data.
     /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
     ///
     fn split(&mut self, offset: usize) -> Result<Self::NodeRef>;
@@ -1846,9 +1982,10 @@ pub trait Text: CharacterData {
 // ------------------------------------------------------------------------------------------------
 
 ///
-/// This corresponds to the DOM `NodeType` set of constants.
+/// This corresponds to the DOM `NodeType` set of constants, with the addition of the
+/// `UnparsedMarkup` extension.
 ///
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(u16)]
 pub enum NodeType {
     /// The node is an [`Element`](trait.Element.html)
@@ -1875,4 +2012,8 @@ pub enum NodeType {
     DocumentFragment,
     /// The node is a `Notation`
     Notation,
+    /// The node holds markup that is written verbatim when serialized, see the
+    /// [`unparsed`](ext/unparsed/index.html) module. This is an extension, it is not one of the
+    /// node types defined by the DOM specification.
+    UnparsedMarkup,
 }
@@ -0,0 +1,890 @@
+/*!
+Implementation for DOM Level 2 Traversal.
+
+This provides the [`NodeIterator`](struct.NodeIterator.html) and
+[`TreeWalker`](struct.TreeWalker.html) interfaces, created by the
+[`DocumentTraversal`](trait.DocumentTraversal.html) trait implemented for documents, to navigate
+the subtree below a root node while only _seeing_ the nodes selected by a `what_to_show` mask and
+an optional [`NodeFilter`](trait.NodeFilter.html).
+
+A `NodeIterator` presents the subtree as a flat list in document order; it remains usable while
+the tree is modified, when a node is removed any iterator positioned within it is moved to an
+adjacent node that is still part of the subtree.
+
+# Interface Mapping
+
+| IDL Interface       | Rust Mapping                                                   |
+|---------------------|----------------------------------------------------------------|
+| `DocumentTraversal` | [`DocumentTraversal`](trait.DocumentTraversal.html)           |
+| `NodeFilter`        | [`NodeFilter`](trait.NodeFilter.html), [`FilterResult`](enum.FilterResult.html), and the `SHOW_*` constants |
+| `NodeIterator`      | [`NodeIterator`](struct.NodeIterator.html)                     |
+| `TreeWalker`        | [`TreeWalker`](struct.TreeWalker.html)                         |
+
+Any closure of the form `Fn(&RefNode) -> FilterResult` may be used as a `NodeFilter`.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::traversal::*;
+use std::rc::Rc;
+
+let implementation = get_implementation();
+let document_node = implementation.create_document(None, Some("doc"), None).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for name in &["a", "b", "c"] {
+    let mut element_node = document.create_element(name).unwrap();
+    let _safe_to_ignore = element_node.append_child(document.create_text_node(name)).unwrap();
+    let _safe_to_ignore = root_node.append_child(element_node).unwrap();
+}
+
+let not_b = |node: &RefNode| {
+    if node.node_name().to_string() == "b" {
+        FilterResult::Reject
+    } else {
+        FilterResult::Accept
+    }
+};
+let mut walker = document_node.create_tree_walker(
+    root_node.clone(),
+    SHOW_ELEMENT,
+    Some(Rc::new(not_b)),
+    false,
+);
+
+let mut names: Vec<String> = Vec::new();
+while let Some(node) = walker.next_node() {
+    names.push(node.node_name().to_string());
+}
+assert_eq!(names, vec!["a", "c"]);
+assert_eq!(walker.previous_node().unwrap().node_name().to_string(), "a");
+
+let mut iterator = document_node.create_node_iterator(root_node.clone(), SHOW_TEXT, None, false);
+assert_eq!(iterator.next_node().unwrap().unwrap().node_value().unwrap(), "a");
+let _safe_to_ignore = root_node.remove_child(root_node.first_child().unwrap()).unwrap();
+assert_eq!(iterator.next_node().unwrap().unwrap().node_value().unwrap(), "b");
+iterator.detach();
+assert!(iterator.next_node().is_err());
+```
+*/
+
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Document, Node, NodeType};
+use crate::shared::error::{Error, Result, MSG_ITERATOR_DETACHED};
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::rc::{Rc, Weak};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// Show all nodes.
+pub const SHOW_ALL: u32 = 0xFFFF_FFFF;
+/// Show `Element` nodes.
+pub const SHOW_ELEMENT: u32 = 0x0000_0001;
+/// Show `Attr` nodes; this is only meaningful when the root is an attribute.
+pub const SHOW_ATTRIBUTE: u32 = 0x0000_0002;
+/// Show `Text` nodes.
+pub const SHOW_TEXT: u32 = 0x0000_0004;
+/// Show `CDATASection` nodes.
+pub const SHOW_CDATA_SECTION: u32 = 0x0000_0008;
+/// Show `EntityReference` nodes.
+pub const SHOW_ENTITY_REFERENCE: u32 = 0x0000_0010;
+/// Show `Entity` nodes; this is only meaningful when the root is an entity.
+pub const SHOW_ENTITY: u32 = 0x0000_0020;
+/// Show `ProcessingInstruction` nodes.
+pub const SHOW_PROCESSING_INSTRUCTION: u32 = 0x0000_0040;
+/// Show `Comment` nodes.
+pub const SHOW_COMMENT: u32 = 0x0000_0080;
+/// Show `Document` nodes.
+pub const SHOW_DOCUMENT: u32 = 0x0000_0100;
+/// Show `DocumentType` nodes.
+pub const SHOW_DOCUMENT_TYPE: u32 = 0x0000_0200;
+/// Show `DocumentFragment` nodes.
+pub const SHOW_DOCUMENT_FRAGMENT: u32 = 0x0000_0400;
+/// Show `Notation` nodes; this is only meaningful when the root is a notation.
+pub const SHOW_NOTATION: u32 = 0x0000_0800;
+/// Show unparsed markup nodes; this is an extension, and is not defined by the DOM specification.
+pub const SHOW_UNPARSED_MARKUP: u32 = 0x0000_1000;
+
+///
+/// The values returned by [`NodeFilter::accept_node`](trait.NodeFilter.html#tymethod.accept_node).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum FilterResult {
+    /// Accept the node. Navigation methods will return this node.
+    Accept = 1,
+    /// Reject the node. Navigation methods will not return this node; a `TreeWalker` will also
+    /// not return any of its children, a `NodeIterator` treats this the same as `Skip`.
+    Reject,
+    /// Skip this single node. Navigation methods will not return this node, but its children are
+    /// still considered.
+    Skip,
+}
+
+///
+/// Filters are objects that know how to "filter out" nodes. If a `NodeIterator` or `TreeWalker`
+/// is given a `NodeFilter`, it applies the filter before it returns the next node; if the filter says to
+/// accept the node the traversal logic returns it, otherwise traversal looks for the next node
+/// and pretends that the node that was rejected was not there.
+///
+/// The filter is only called for nodes whose type is included in the `what_to_show` mask.
+///
+pub trait NodeFilter {
+    ///
+    /// Test whether a specified node is visible in the logical view of a `TreeWalker` or
+    /// `NodeIterator`.
+    ///
+    fn accept_node(&self, node: &RefNode) -> FilterResult;
+}
+
+///
+/// `DocumentTraversal` contains methods that create traversal objects for the nodes of a
+/// document.
+///
+pub trait DocumentTraversal: Document {
+    ///
+    /// Create a new `NodeIterator` over the subtree rooted at `root`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `root` of type `Node`: The node which will be iterated together with its children. The
+    ///   iterator is initially positioned just before this node. The `what_to_show` flags and the
+    ///   filter, if any, are not considered when setting this position.
+    /// * `what_to_show` of type `unsigned long`: This flag specifies which node types may appear
+    ///   in the logical view of the tree presented by the iterator. See the `SHOW_*` constants
+    ///   for the set of possible values. These flags can be combined using `|`.
+    /// * `filter` of type `NodeFilter`: The `NodeFilter` to be used with this `NodeIterator`, or
+    ///   `None` to indicate no filter.
+    /// * `expand_entity_references` of type `boolean`: The value of this flag determines whether
+    ///   entity reference nodes are expanded.
+    ///
+    fn create_node_iterator(
+        &self,
+        root: RefNode,
+        what_to_show: u32,
+        filter: Option<Rc<dyn NodeFilter>>,
+        expand_entity_references: bool,
+    ) -> NodeIterator;
+
+    ///
+    /// Create a new `TreeWalker` over the subtree rooted at `root`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `root` of type `Node`: The node which will serve as the root for the `TreeWalker`. The
+    ///   `what_to_show` flags and the `NodeFilter` are not considered when setting this value; any
+    ///   node type will be accepted as the root. The `current_node` of the `TreeWalker` is
+    ///   initialized to this node, whether or not it is visible.
+    /// * `what_to_show` of type `unsigned long`: This flag specifies which node types may appear
+    ///   in the logical view of the tree presented by the tree-walker. See the `SHOW_*` constants
+    ///   for the set of possible values. These flags can be combined using `|`.
+    /// * `filter` of type `NodeFilter`: The `NodeFilter` to be used with this `TreeWalker`, or
+    ///   `None` to indicate no filter.
+    /// * `expand_entity_references` of type `boolean`: If this flag is `false`, the contents of
+    ///   `EntityReference` nodes are not presented in the logical view.
+    ///
+    fn create_tree_walker(
+        &self,
+        root: RefNode,
+        what_to_show: u32,
+        filter: Option<Rc<dyn NodeFilter>>,
+        expand_entity_references: bool,
+    ) -> TreeWalker;
+}
+
+///
+/// `NodeIterator`s are used to step through the nodes of a subtree in document order, presenting
+/// a flattened view of those nodes selected by the `what_to_show` flags and filter (if any).
+///
+/// The iterator is positioned between two nodes of this flattened view, rather than on a node.
+/// When a node is removed from the tree the iterator's position is moved so that it is still
+/// between two nodes of the subtree, this means that iterating after removing the node last
+/// returned by `next_node` continues with the node that followed it.
+///
+/// Once `detach` has been called any further call to `next_node` or `previous_node` will return
+/// `Err(Error::InvalidState)`.
+///
+pub struct NodeIterator {
+    state: Rc<RefCell<IteratorState>>,
+    what_to_show: u32,
+    filter: Option<Rc<dyn NodeFilter>>,
+}
+
+///
+/// `TreeWalker` objects are used to navigate a document tree or subtree using the view of the
+/// document defined by their `what_to_show` flags and filter (if any). Any function which performs
+/// navigation using a `TreeWalker` will automatically support any view defined by a `TreeWalker`.
+///
+/// Omitting nodes from the logical view of a subtree can result in a structure that is
+/// substantially different from the same subtree in the complete, unfiltered document. Nodes
+/// that are siblings in the `TreeWalker` view may be children of different, widely separated,
+/// nodes in the original view.
+///
+/// Navigation methods return `None` when there is no visible node in the requested direction,
+/// in which case `current_node` is not changed.
+///
+pub struct TreeWalker {
+    root: RefNode,
+    what_to_show: u32,
+    filter: Option<Rc<dyn NodeFilter>>,
+    expand_entity_references: bool,
+    current_node: RefNode,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<F> NodeFilter for F
+where
+    F: Fn(&RefNode) -> FilterResult,
+{
+    fn accept_node(&self, node: &RefNode) -> FilterResult {
+        self(node)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentTraversal for RefNode {
+    fn create_node_iterator(
+        &self,
+        root: RefNode,
+        what_to_show: u32,
+        filter: Option<Rc<dyn NodeFilter>>,
+        expand_entity_references: bool,
+    ) -> NodeIterator {
+        let state = Rc::new(RefCell::new(IteratorState {
+            reference: root.clone(),
+            root,
+            pointer_before_reference: true,
+            expand_entity_references,
+            detached: false,
+        }));
+        LIVE_ITERATORS.with(|iterators| iterators.borrow_mut().push(Rc::downgrade(&state)));
+        NodeIterator {
+            state,
+            what_to_show,
+            filter,
+        }
+    }
+
+    fn create_tree_walker(
+        &self,
+        root: RefNode,
+        what_to_show: u32,
+        filter: Option<Rc<dyn NodeFilter>>,
+        expand_entity_references: bool,
+    ) -> TreeWalker {
+        TreeWalker {
+            current_node: root.clone(),
+            root,
+            what_to_show,
+            filter,
+            expand_entity_references,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for NodeIterator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = self.state.borrow();
+        f.debug_struct("NodeIterator")
+            .field("root", &state.root.node_name())
+            .field("what_to_show", &format!("{:#010X}", self.what_to_show))
+            .field("filter", &self.filter.is_some())
+            .field("expand_entity_references", &state.expand_entity_references)
+            .field("reference_node", &state.reference.node_name())
+            .field("pointer_before_reference", &state.pointer_before_reference)
+            .field("detached", &state.detached)
+            .finish()
+    }
+}
+
+impl NodeIterator {
+    ///
+    /// The root node of the `NodeIterator`, as specified when it was created.
+    ///
+    pub fn root(&self) -> RefNode {
+        self.state.borrow().root.clone()
+    }
+
+    ///
+    /// This attribute determines which node types are presented via the iterator.
+    ///
+    pub fn what_to_show(&self) -> u32 {
+        self.what_to_show
+    }
+
+    ///
+    /// The filter used to screen nodes.
+    ///
+    pub fn filter(&self) -> Option<Rc<dyn NodeFilter>> {
+        self.filter.clone()
+    }
+
+    ///
+    /// The value of this flag determines whether the children of entity reference nodes are
+    /// visible to the iterator.
+    ///
+    pub fn expand_entity_references(&self) -> bool {
+        self.state.borrow().expand_entity_references
+    }
+
+    ///
+    /// Returns the next node in the set and advances the position of the iterator in the set.
+    /// After a `NodeIterator` is created, the first call to `next_node` returns the first node
+    /// in the set, `None` is returned when there are no more nodes in the set.
+    ///
+    /// # Specification
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if this method is called after the `detach` method was
+    ///   invoked.
+    ///
+    pub fn next_node(&mut self) -> Result<Option<RefNode>> {
+        self.traverse(true)
+    }
+
+    ///
+    /// Returns the previous node in the set and moves the position of the `NodeIterator`
+    /// backwards in the set, `None` is returned when there are no more nodes in the set.
+    ///
+    /// # Specification
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if this method is called after the `detach` method was
+    ///   invoked.
+    ///
+    pub fn previous_node(&mut self) -> Result<Option<RefNode>> {
+        self.traverse(false)
+    }
+
+    ///
+    /// Detaches the `NodeIterator` from the set which it iterated over, releasing any
+    /// computational resources and placing the iterator in the `INVALID` state. After `detach`
+    /// has been invoked, calls to `next_node` or `previous_node` will return an error.
+    ///
+    pub fn detach(&mut self) {
+        self.state.borrow_mut().detached = true;
+        let detached = Rc::downgrade(&self.state);
+        LIVE_ITERATORS.with(|iterators| {
+            iterators
+                .borrow_mut()
+                .retain(|state| !Weak::ptr_eq(state, &detached))
+        });
+    }
+
+    fn traverse(&mut self, next: bool) -> Result<Option<RefNode>> {
+        let (root, mut node, mut before, expand) = {
+            let state = self.state.borrow();
+            if state.detached {
+                warn!("{}", MSG_ITERATOR_DETACHED);
+                return Err(Error::InvalidState);
+            }
+            (
+                state.root.clone(),
+                state.reference.clone(),
+                state.pointer_before_reference,
+                state.expand_entity_references,
+            )
+        };
+        loop {
+            if next {
+                if before {
+                    before = false;
+                } else {
+                    node = match first_child_of(&node, expand) {
+                        Some(child) => child,
+                        None => match following_outside(&root, &node) {
+                            Some(following) => following,
+                            None => return Ok(None),
+                        },
+                    };
+                }
+            } else if before {
+                if node == root {
+                    return Ok(None);
+                }
+                node = match node.previous_sibling() {
+                    Some(sibling) => last_descendant(&sibling, expand),
+                    None => match node.parent_node() {
+                        Some(parent) => parent,
+                        None => return Ok(None),
+                    },
+                };
+            } else {
+                before = true;
+            }
+            if accept_node(self.what_to_show, &self.filter, &node) == FilterResult::Accept {
+                break;
+            }
+        }
+        let mut state = self.state.borrow_mut();
+        state.reference = node.clone();
+        state.pointer_before_reference = before;
+        Ok(Some(node))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for TreeWalker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("TreeWalker")
+            .field("root", &self.root.node_name())
+            .field("what_to_show", &format!("{:#010X}", self.what_to_show))
+            .field("filter", &self.filter.is_some())
+            .field("expand_entity_references", &self.expand_entity_references)
+            .field("current_node", &self.current_node.node_name())
+            .finish()
+    }
+}
+
+impl TreeWalker {
+    ///
+    /// The root node of the `TreeWalker`, as specified when it was created.
+    ///
+    pub fn root(&self) -> RefNode {
+        self.root.clone()
+    }
+
+    ///
+    /// This attribute determines which node types are presented via the `TreeWalker`.
+    ///
+    pub fn what_to_show(&self) -> u32 {
+        self.what_to_show
+    }
+
+    ///
+    /// The filter used to screen nodes.
+    ///
+    pub fn filter(&self) -> Option<Rc<dyn NodeFilter>> {
+        self.filter.clone()
+    }
+
+    ///
+    /// The value of this flag determines whether the children of entity reference nodes are
+    /// visible to the `TreeWalker`.
+    ///
+    pub fn expand_entity_references(&self) -> bool {
+        self.expand_entity_references
+    }
+
+    ///
+    /// The node at which the `TreeWalker` is currently positioned.
+    ///
+    pub fn current_node(&self) -> RefNode {
+        self.current_node.clone()
+    }
+
+    ///
+    /// Set the node at which the `TreeWalker` is currently positioned. Alterations to the DOM
+    /// tree may cause the current node to no longer be accepted by the `TreeWalker`'s associated
+    /// filter; `current_node` may also be explicitly set to any node, whether or not it is within
+    /// the subtree specified by the root node or would be accepted by the filter and
+    /// `what_to_show` flags. Further traversal occurs relative to `current_node` even if it is not
+    /// part of the current view, by applying the filters in the requested direction; if no
+    /// traversal is possible, `current_node` is not changed.
+    ///
+    pub fn set_current_node(&mut self, current_node: RefNode) {
+        self.current_node = current_node;
+    }
+
+    ///
+    /// Moves to and returns the closest visible ancestor node of the current node. If the search
+    /// for `parent_node` attempts to step upward from the `TreeWalker`'s root node, or if it fails
+    /// to find a visible ancestor node, this method retains the current position and returns
+    /// `None`.
+    ///
+    pub fn parent_node(&mut self) -> Option<RefNode> {
+        let mut node = self.current_node.clone();
+        while node != self.root {
+            node = node.parent_node()?;
+            if self.accept(&node) == FilterResult::Accept {
+                self.current_node = node.clone();
+                return Some(node);
+            }
+        }
+        None
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the first visible child of the current node, and returns the new
+    /// node. If the current node has no visible children, returns `None`, and retains the current
+    /// node.
+    ///
+    pub fn first_child(&mut self) -> Option<RefNode> {
+        self.traverse_children(true)
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the last visible child of the current node, and returns the new
+    /// node. If the current node has no visible children, returns `None`, and retains the current
+    /// node.
+    ///
+    pub fn last_child(&mut self) -> Option<RefNode> {
+        self.traverse_children(false)
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the previous sibling of the current node, and returns the new
+    /// node. If the current node has no visible previous sibling, returns `None`, and retains the
+    /// current node.
+    ///
+    pub fn previous_sibling(&mut self) -> Option<RefNode> {
+        self.traverse_siblings(false)
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the next sibling of the current node, and returns the new node.
+    /// If the current node has no visible next sibling, returns `None`, and retains the current
+    /// node.
+    ///
+    pub fn next_sibling(&mut self) -> Option<RefNode> {
+        self.traverse_siblings(true)
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the previous visible node in document order relative to the
+    /// current node, and returns the new node. If the current node has no previous node, or if
+    /// the search for `previous_node` attempts to step upward from the `TreeWalker`'s root node,
+    /// returns `None`, and retains the current node.
+    ///
+    pub fn previous_node(&mut self) -> Option<RefNode> {
+        let mut node = self.current_node.clone();
+        while node != self.root {
+            let mut sibling = node.previous_sibling();
+            while let Some(previous) = sibling {
+                node = previous;
+                let mut result = self.accept(&node);
+                while result != FilterResult::Reject {
+                    match self.last_child_of(&node) {
+                        Some(child) => {
+                            node = child;
+                            result = self.accept(&node);
+                        }
+                        None => break,
+                    }
+                }
+                if result == FilterResult::Accept {
+                    self.current_node = node.clone();
+                    return Some(node);
+                }
+                sibling = node.previous_sibling();
+            }
+            node = node.parent_node()?;
+            if self.accept(&node) == FilterResult::Accept {
+                self.current_node = node.clone();
+                return Some(node);
+            }
+        }
+        None
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the next visible node in document order relative to the current
+    /// node, and returns the new node. If the current node has no next node, or if the search for
+    /// `next_node` attempts to step upward from the `TreeWalker`'s root node, returns `None`, and
+    /// retains the current node.
+    ///
+    pub fn next_node(&mut self) -> Option<RefNode> {
+        let mut node = self.current_node.clone();
+        let mut result = FilterResult::Accept;
+        loop {
+            while result != FilterResult::Reject {
+                match self.first_child_of(&node) {
+                    Some(child) => {
+                        node = child;
+                        result = self.accept(&node);
+                        if result == FilterResult::Accept {
+                            self.current_node = node.clone();
+                            return Some(node);
+                        }
+                    }
+                    None => break,
+                }
+            }
+            let mut temporary = Some(node.clone());
+            let mut following = None;
+            while let Some(ancestor) = temporary {
+                if ancestor == self.root {
+                    return None;
+                }
+                following = ancestor.next_sibling();
+                if following.is_some() {
+                    break;
+                }
+                temporary = ancestor.parent_node();
+            }
+            node = following?;
+            result = self.accept(&node);
+            if result == FilterResult::Accept {
+                self.current_node = node.clone();
+                return Some(node);
+            }
+        }
+    }
+
+    fn traverse_children(&mut self, first: bool) -> Option<RefNode> {
+        let mut node = if first {
+            self.first_child_of(&self.current_node)
+        } else {
+            self.last_child_of(&self.current_node)
+        };
+        while let Some(current) = node {
+            match self.accept(&current) {
+                FilterResult::Accept => {
+                    self.current_node = current.clone();
+                    return Some(current);
+                }
+                FilterResult::Skip => {
+                    let child = if first {
+                        self.first_child_of(&current)
+                    } else {
+                        self.last_child_of(&current)
+                    };
+                    if child.is_some() {
+                        node = child;
+                        continue;
+                    }
+                }
+                FilterResult::Reject => {}
+            }
+            let mut current = current;
+            node = loop {
+                let sibling = if first {
+                    current.next_sibling()
+                } else {
+                    current.previous_sibling()
+                };
+                if sibling.is_some() {
+                    break sibling;
+                }
+                match current.parent_node() {
+                    Some(parent) if parent != self.root && parent != self.current_node => {
+                        current = parent;
+                    }
+                    _ => return None,
+                }
+            };
+        }
+        None
+    }
+
+    fn traverse_siblings(&mut self, next: bool) -> Option<RefNode> {
+        let mut node = self.current_node.clone();
+        if node == self.root {
+            return None;
+        }
+        loop {
+            let mut sibling = if next {
+                node.next_sibling()
+            } else {
+                node.previous_sibling()
+            };
+            while let Some(current) = sibling {
+                node = current;
+                let result = self.accept(&node);
+                if result == FilterResult::Accept {
+                    self.current_node = node.clone();
+                    return Some(node);
+                }
+                sibling = if next {
+                    self.first_child_of(&node)
+                } else {
+                    self.last_child_of(&node)
+                };
+                if result == FilterResult::Reject || sibling.is_none() {
+                    sibling = if next {
+                        node.next_sibling()
+                    } else {
+                        node.previous_sibling()
+                    };
+                }
+            }
+            node = node.parent_node()?;
+            if node == self.root || self.accept(&node) == FilterResult::Accept {
+                return None;
+            }
+        }
+    }
+
+    ///
+    /// Apply the `what_to_show` mask, and then the filter, to `node`.
+    ///
+    fn accept(&self, node: &RefNode) -> FilterResult {
+        accept_node(self.what_to_show, &self.filter, node)
+    }
+
+    fn first_child_of(&self, node: &RefNode) -> Option<RefNode> {
+        if self.hides_children(node) {
+            None
+        } else {
+            node.first_child()
+        }
+    }
+
+    fn last_child_of(&self, node: &RefNode) -> Option<RefNode> {
+        if self.hides_children(node) {
+            None
+        } else {
+            node.last_child()
+        }
+    }
+
+    fn hides_children(&self, node: &RefNode) -> bool {
+        !self.expand_entity_references && node.node_type() == NodeType::EntityReference
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The state of a `NodeIterator` that is shared with the live iterator registry, the iterator is
+/// positioned either just before or just after the reference node.
+///
+struct IteratorState {
+    root: RefNode,
+    reference: RefNode,
+    pointer_before_reference: bool,
+    expand_entity_references: bool,
+    detached: bool,
+}
+
+thread_local! {
+    static LIVE_ITERATORS: RefCell<Vec<Weak<RefCell<IteratorState>>>> = const { RefCell::new(Vec::new()) };
+}
+
+impl IteratorState {
+    fn pre_remove(&mut self, removed: &RefNode) {
+        if self.detached
+            || !is_inclusive_ancestor(removed, &self.reference)
+            || is_inclusive_ancestor(removed, &self.root)
+        {
+            return;
+        }
+        if self.pointer_before_reference {
+            if let Some(following) = following_outside(&self.root, removed) {
+                self.reference = following;
+                return;
+            }
+            self.pointer_before_reference = false;
+        }
+        if let Some(reference) = removed
+            .previous_sibling()
+            .map(|sibling| last_descendant(&sibling, self.expand_entity_references))
+            .or_else(|| removed.parent_node())
+        {
+            self.reference = reference;
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Called by `remove_child` before `removed` is taken out of the tree, so that any live
+/// `NodeIterator` positioned within it can be moved to a node that remains.
+///
+pub(crate) fn pre_remove(removed: &RefNode) {
+    LIVE_ITERATORS.with(|iterators| {
+        let mut iterators = iterators.borrow_mut();
+        iterators.retain(|state| state.strong_count() > 0);
+        for state in iterators.iter().filter_map(Weak::upgrade) {
+            state.borrow_mut().pre_remove(removed);
+        }
+    });
+}
+
+///
+/// Is `ancestor` either `node` or one of its ancestors.
+///
+pub(crate) fn is_inclusive_ancestor(ancestor: &RefNode, node: &RefNode) -> bool {
+    let mut node = Some(node.clone());
+    while let Some(current) = node {
+        if &current == ancestor {
+            return true;
+        }
+        node = current.parent_node();
+    }
+    false
+}
+
+fn first_child_of(node: &RefNode, expand_entity_references: bool) -> Option<RefNode> {
+    if !expand_entity_references && node.node_type() == NodeType::EntityReference {
+        None
+    } else {
+        node.first_child()
+    }
+}
+
+///
+/// Returns the last node, in document order, of the subtree rooted at `node`.
+///
+fn last_descendant(node: &RefNode, expand_entity_references: bool) -> RefNode {
+    let mut node = node.clone();
+    while expand_entity_references || node.node_type() != NodeType::EntityReference {
+        match node.last_child() {
+            Some(child) => node = child,
+            None => break,
+        }
+    }
+    node
+}
+
+///
+/// Returns the first node following `node` in document order that is not one of its
+/// descendants, without leaving the subtree rooted at `root`.
+///
+fn following_outside(root: &RefNode, node: &RefNode) -> Option<RefNode> {
+    let mut node = node.clone();
+    loop {
+        if &node == root {
+            return None;
+        }
+        if let Some(sibling) = node.next_sibling() {
+            return Some(sibling);
+        }
+        node = node.parent_node()?;
+    }
+}
+
+///
+/// Returns the `SHOW_*` flag corresponding to `node_type`.
+///
+fn show_flag(node_type: NodeType) -> u32 {
+    1 << (node_type as u32 - 1)
+}
+
+fn accept_node(
+    what_to_show: u32,
+    filter: &Option<Rc<dyn NodeFilter>>,
+    node: &RefNode,
+) -> FilterResult {
+    if what_to_show & show_flag(node.node_type()) == 0 {
+        FilterResult::Skip
+    } else {
+        match filter {
+            None => FilterResult::Accept,
+            Some(filter) => filter.accept_node(node),
+        }
+    }
+}
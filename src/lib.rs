@@ -16,8 +16,8 @@ module.
 
 ## Features
 
-Currently only one feature, `quick_parser`, is provided which provides a new module `parser` with the
-single public function. This feature is enabled by default.
+The feature `quick_parser` provides a new module `parser` with the single public function. This
+feature is enabled by default.
 
 ``` rust,ignore
 pub fn read_xml(xml: &str) -> Result<RefNode>;
@@ -25,6 +25,51 @@ pub fn read_xml(xml: &str) -> Result<RefNode>;
 
 This will parse the document and return a new `RefNode` that corresponds to the `Document` trait.
 
+For large inputs, or where the quick-xml `Reader` needs to be configured, the `parser::DomBuilder`
+type constructs a document incrementally from the reader's event stream, and the function
+`parser::read_events` drives a caller-provided reader through a builder. The feature also adds
+`From` and `TryFrom` conversions in `level2::convert` between nodes and sequences of quick-xml
+events, in both directions.
+
+The feature `actor` provides a new module `level2::ext::actor` with a `DocumentActor` that owns a
+document on a dedicated thread and processes query and mutation messages from other threads,
+returning results as futures. This feature is not enabled by default.
+
+The feature `bench` provides a new module `level2::ext::bench` that counts the node allocations
+and map rehashes made by the node backend, and enables the Criterion benches in the `benches`
+directory, run with `cargo bench --features bench`. This feature is not enabled by default.
+
+The feature `cli` enables the `xml-dom` example, a small command-line tool built only on the
+public API of this crate, with the sub-commands `validate`, `pretty`, `canonicalize`, and `query`;
+run with `cargo run --features cli --example xml-dom -- help`. This feature is not enabled by
+default.
+
+The feature `encoding` adds the functions `serialize_encoded` and `write_encoded` to the module
+`level2::ext::serializer`, which write a document in another character encoding, such as UTF-16 or
+ISO-8859-1, using character references for any character in text or attribute values that the
+encoding cannot represent. This feature is not enabled by default.
+
+The feature `entity_catalog` provides a new module `level2::ext::catalog` containing the XHTML
+named character entity sets, such as `&nbsp;` and `&alpha;`, for decoding input and encoding
+output. This feature is not enabled by default.
+
+The feature `instrumentation` provides a new module `level2::ext::instrument` that reports the
+time spent creating, inserting, querying, and serializing nodes to a client-provided instrument,
+such as the included `Counters`. This feature is not enabled by default.
+
+The feature `soap` provides a new module `level2::ext::soap` with helpers to locate the envelope,
+header, and body of SOAP 1.1 and 1.2 messages, enumerate header blocks, and extract fault details.
+It also provides `level2::ext::wsse`, with helpers to insert WS-Security timestamps and tokens.
+This feature is not enabled by default.
+
+The feature `xml_rs` provides a new module `level2::ext::xml_rs` that builds a document from an
+xml-rs `EventReader`, and writes a document through an xml-rs `EventWriter`, so that existing
+xml-rs pipelines can adopt this DOM incrementally. This feature is not enabled by default.
+
+The feature `xop` provides a new module `level2::ext::xop` that replaces Base64 content with XOP
+`xop:Include` references to binary attachments, and inlines them back from registered attachment
+providers. This feature is not enabled by default.
+
 # Example
 
 ```rust
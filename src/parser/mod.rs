@@ -3,29 +3,60 @@ Provides a basic parser from text to DOM using the [quick-xml](https://crates.io
 crate.
 
 The parsing capability of quick-xml is limited in some ways, it does not support DTD handling other
-than returning the entire DTD content as a string; therefore entities, notations, or entity
-references are not constructed in the DOM. It does parse `Text`, `CDataSection`, and `Comment` nodes
-but does limited entity processing or escaping.
+than returning the entire DTD content as a string; therefore the document type is constructed with
+its name, public and system identifiers, and the entity and notation declarations of any internal
+subset, but no external subset is read. It does parse `Text`, `CDataSection`, `Comment`, and
+`ProcessingInstruction` nodes; a reference in text to a general entity declared in the internal
+subset is constructed as an `EntityReference` node, and in an attribute value is replaced by the
+text of the entity.
+
+The [`DomBuilder`](struct.DomBuilder.html) type is used by all of the functions in this module, and
+may also be used directly to construct a document from quick-xml events read by the caller.
+
+When a document is finished each element, and each prefixed attribute, is given the namespace URI
+its prefix, or the default namespace for an un-prefixed element, resolves to by the `xmlns`
+attributes in scope; an undeclared prefix is left unresolved.
+
+The [`read_fragment`](fn.read_fragment.html) function parses well-formed content, rather than a
+complete document, into a document fragment of an existing document; the prefixes used in the
+content are resolved against the declarations it makes and a supplied
+[`NamespaceContext`](../level2/ext/namespaced/struct.NamespaceContext.html), and the namespace URI
+of each prefixed node is recorded in its name so that the fragment may be processed on its own.
+
+White space within elements is retained as it appears in the input, including text that is only
+white space; a reader configured with `trim_text` may be passed to
+[`read_events`](fn.read_events.html) to remove it.
+
+As required of an XML processor, line breaks are normalized to a single line feed, and each white
+space character in an attribute value is replaced by a space; white space given by a character
+reference is kept.
 
 # Example
 
 ```rust
 use xml_dom::parser::read_xml;
 
-let dom = read_xml(r#"<?xml version="1.0"?><xml/>"#);
+let dom = read_xml(r#"<?xml version="1.0"?><!DOCTYPE xml SYSTEM "xml.dtd"><xml/>"#);
 assert!(dom.is_ok());
 ```
 
 */
 
-use crate::level2::convert::as_document_mut;
+use crate::level2::convert::{as_document, as_document_type};
+use crate::level2::ext::dtd::doc_type_parts;
+use crate::level2::ext::namespaced::{resolve_names, NamespaceContext};
+use crate::level2::ext::query::text_content;
+use crate::level2::ext::tree_builder::TreeBuilder;
+use crate::level2::ext::workspace::{import_node, NameTable};
 use crate::level2::ext::{XmlDecl, XmlVersion};
-use crate::level2::node_impl::Extension;
 use crate::level2::*;
 use crate::shared::error::Error as DOMError;
-use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use crate::shared::syntax::{XML_ENTITYREF_END, XML_ENTITYREF_START};
+use crate::shared::text::{is_xml_space, normalize_end_of_lines};
+use quick_xml::escape::{unescape, unescape_with};
+use quick_xml::events::{BytesCData, BytesDecl, BytesStart, BytesText, Event};
 use quick_xml::Reader;
-use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::io::BufRead;
 use std::str::FromStr;
@@ -58,6 +89,41 @@ pub enum Error {
 ///
 pub type Result<T> = std::result::Result<T, Error>;
 
+///
+/// Incrementally constructs a document from the events produced by a quick-xml
+/// [`Reader`](https://docs.rs/quick-xml/0.26.0/quick_xml/reader/struct.Reader.html). This allows
+/// clients that drive the reader themselves, for example to configure it, to interleave other
+/// processing, or to read very large inputs without recursion, to construct a DOM without writing
+/// their own bridge between the event stream and the `Document::create_*` methods. Each event is
+/// decoded and passed to a [`TreeBuilder`](../level2/ext/tree_builder/struct.TreeBuilder.html),
+/// which may also be used directly by front-ends that do not read XML text.
+///
+/// # Example
+///
+/// ```rust
+/// use quick_xml::events::Event;
+/// use quick_xml::Reader;
+/// use xml_dom::level2::Node;
+/// use xml_dom::parser::DomBuilder;
+///
+/// let mut reader = Reader::from_str("<list><item>1</item><item>2</item></list>");
+/// let mut builder = DomBuilder::new();
+/// loop {
+///     match reader.read_event().unwrap() {
+///         Event::Eof => break,
+///         event => builder.handle_event(&reader, event).unwrap(),
+///     }
+/// }
+/// let document = builder.finish().unwrap();
+/// assert_eq!(document.first_child().unwrap().child_nodes().len(), 2);
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct DomBuilder {
+    builder: TreeBuilder,
+    entities: HashMap<String, String>,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
@@ -67,7 +133,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// can be safely assumed to be a `Document` node.
 ///
 pub fn read_xml(xml: &str) -> Result<RefNode> {
-    inner_read(&mut Reader::from_str(xml))
+    read_events(&mut Reader::from_str(xml))
 }
 
 ///
@@ -75,7 +141,120 @@ pub fn read_xml(xml: &str) -> Result<RefNode> {
 /// can be safely assumed to be a `Document` node.
 ///
 pub fn read_reader<B: BufRead>(reader: B) -> Result<RefNode> {
-    inner_read(&mut Reader::from_reader(reader))
+    read_events(&mut Reader::from_reader(reader))
+}
+
+///
+/// Read all remaining events from `reader`, which may have been configured by the caller, into a
+/// new DOM structure using a [`DomBuilder`](struct.DomBuilder.html); if the result is OK, the
+/// result returned can be safely assumed to be a `Document` node.
+///
+/// As the configuration of `reader` is not changed, text within elements is trimmed, and white
+/// space only text dropped, only if `trim_text` has been set.
+///
+pub fn read_events<B: BufRead>(reader: &mut Reader<B>) -> Result<RefNode> {
+    let mut builder = DomBuilder::new();
+    let mut event_buffer: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut event_buffer) {
+            Ok(Event::Eof) => return builder.finish(),
+            Ok(event) => builder.handle_event(reader, event)?,
+            Err(err) => {
+                error!("Unexpected parser error: {:?}", err);
+                return Error::from(err).into();
+            }
+        }
+        event_buffer.clear();
+    }
+}
+
+///
+/// Parse the provided string, the content of an element rather than a complete document, into a
+/// new `DocumentFragment` node owned by `document_node`, which must be a `Document` node. Prefixes
+/// in the content that are not declared within it are resolved against `context`, typically
+/// captured from the element the content is to be placed within by the `NamespaceContext`
+/// [`from_node`](../level2/ext/namespaced/struct.NamespaceContext.html#method.from_node) method.
+///
+/// The content may not contain an XML declaration or document type; undeclared prefixes are
+/// left unresolved, as for [`read_xml`](fn.read_xml.html).
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::ext::namespaced::NamespaceContext;
+/// use xml_dom::parser::read_fragment;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("catalog"), None)
+///     .unwrap();
+/// let context =
+///     NamespaceContext::new().with_namespace(Some("dc"), "http://purl.org/dc/elements/1.1/");
+/// let fragment = read_fragment(&document_node, "<dc:title>Dune</dc:title><dc:creator/>", &context)
+///     .unwrap();
+///
+/// assert_eq!(fragment.node_type(), NodeType::DocumentFragment);
+/// assert_eq!(fragment.child_nodes().len(), 2);
+/// assert_eq!(
+///     fragment.first_child().unwrap().namespace_uri().as_deref(),
+///     Some("http://purl.org/dc/elements/1.1/")
+/// );
+/// ```
+///
+pub fn read_fragment(
+    document_node: &RefNode,
+    xml: &str,
+    context: &NamespaceContext,
+) -> Result<RefNode> {
+    let document = as_document(document_node)?;
+    let mut reader = Reader::from_str(xml);
+
+    //
+    // The content is read into a wrapper element, in a separate document, that carries the
+    // declarations of `context`; names are resolved, as for any document, while the wrapper is in
+    // place, and the content then imported into the fragment.
+    //
+    let declarations: Vec<(String, String)> = context
+        .declarations()
+        .into_iter()
+        .map(|(prefix, namespace_uri)| {
+            (
+                Name::for_namespace(prefix.as_deref()).to_string(),
+                namespace_uri,
+            )
+        })
+        .collect();
+    let declarations: Vec<(&str, &str)> = declarations
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let mut builder = DomBuilder::new();
+    builder
+        .builder
+        .start_element(FRAGMENT_WRAPPER, &declarations)?;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => builder.handle_event(&reader, event)?,
+            Err(err) => {
+                error!("Unexpected parser error: {:?}", err);
+                return Error::from(err).into();
+            }
+        }
+    }
+    builder.builder.end_element(FRAGMENT_WRAPPER)?;
+    let content_document_node = builder.finish()?;
+    let wrapper = as_document(&content_document_node)?
+        .document_element()
+        .ok_or(Error::Malformed)?;
+
+    let mut names = NameTable::default();
+    let mut fragment = document.create_document_fragment()?;
+    for child in wrapper.child_nodes() {
+        let child = import_node(&mut names, document_node, &child, true)?;
+        let _safe_to_ignore = fragment.append_child(child)?;
+    }
+    Ok(fragment)
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -103,9 +282,127 @@ impl Display for Error {
 
 impl std::error::Error for Error {}
 
-impl<T> Into<Result<T>> for Error {
-    fn into(self) -> Result<T> {
-        Err(self)
+// ------------------------------------------------------------------------------------------------
+
+impl Default for DomBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DomBuilder {
+    ///
+    /// Construct a new builder with an empty document.
+    ///
+    pub fn new() -> Self {
+        Self {
+            builder: TreeBuilder::new(),
+            entities: Default::default(),
+        }
+    }
+
+    ///
+    /// Add the content of `event` to the document being constructed; `reader` is the reader that
+    /// produced the event, and is used to decode its content. `Eof` events are ignored, call
+    /// [`finish`](#method.finish) to complete the document.
+    ///
+    /// The events are checked against the following productions, events that are not valid at
+    /// the current position return `Error::Malformed`.
+    ///
+    /// ```ebnf
+    /// document          ::= prolog element Misc* - Char* RestrictedChar Char*
+    ///
+    /// prolog            ::= XMLDecl Misc* (doctypedecl Misc*)?
+    ///
+    /// XMLDecl           ::= '<?xml' VersionInfo EncodingDecl? SDDecl? S?'?>'
+    ///
+    /// Misc              ::= Comment | PI | S
+    ///
+    /// element           ::= EmptyElemTag | STag content ETag
+    ///
+    /// content           ::= CharData? ((element | Reference | CDSect | PI | Comment) CharData?)*
+    ///
+    /// S                 ::= (#x20 | #x9 | #xD | #xA)+
+    /// ```
+    ///
+    pub fn handle_event<B>(&mut self, reader: &Reader<B>, event: Event<'_>) -> Result<()> {
+        let builder = &mut self.builder;
+        match event {
+            Event::Decl(ev) => builder.xml_declaration(make_decl(reader, ev)?)?,
+            Event::Start(ev) => {
+                let _safe_to_ignore = handle_start(reader, builder, &self.entities, ev)?;
+            }
+            Event::Empty(ev) => {
+                let name = handle_start(reader, builder, &self.entities, ev)?;
+                builder.end_element(&name)?;
+            }
+            Event::End(ev) => {
+                let name = ev.name();
+                builder.end_element(&reader.decoder().decode(name.as_ref())?)?;
+            }
+            Event::Comment(ev) => builder.comment(&normalize_end_of_lines(
+                &reader.decoder().decode(ev.as_ref())?,
+            ))?,
+            Event::PI(ev) => handle_pi(reader, builder, ev)?,
+            Event::DocType(ev) => {
+                let text = reader.decoder().decode(ev.as_ref())?;
+                let parts = doc_type_parts(&text).ok_or_else(|| {
+                    error!("Malformed document type declaration: {}", text);
+                    Error::Malformed
+                })?;
+                builder.document_type(
+                    &parts.name,
+                    parts.public_id.as_deref(),
+                    parts.system_id.as_deref(),
+                    parts.internal_subset.as_deref(),
+                )?;
+                self.entities = declared_entities(builder.document());
+            }
+            Event::Text(ev) => handle_text(reader, builder, &self.entities, ev)?,
+            Event::CData(ev) => builder.cdata_section(&make_cdata(reader, ev)?)?,
+            Event::Eof => {}
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns the number of elements that have been started but not yet ended.
+    ///
+    pub fn depth(&self) -> usize {
+        self.builder.depth()
+    }
+
+    ///
+    /// Complete the document; if the result is OK, the result returned can be safely assumed to
+    /// be a `Document` node.
+    ///
+    pub fn finish(self) -> Result<RefNode> {
+        let document_node = self.builder.finish()?;
+        resolve_names(&document_node)?;
+        Ok(document_node)
+    }
+
+    ///
+    /// Construct a new builder that adds content to `element`, with the general entities declared
+    /// by its owner document; the caller resolves the names of the content.
+    ///
+    pub(crate) fn within(element: &RefNode) -> Result<Self> {
+        let builder = TreeBuilder::within(element)?;
+        let entities = declared_entities(builder.document());
+        Ok(Self { builder, entities })
+    }
+
+    ///
+    /// The innermost element that has been started but not yet ended.
+    ///
+    pub(crate) fn open_element(&self) -> Option<&RefNode> {
+        self.builder.open_element()
+    }
+}
+
+impl<T> From<Error> for Result<T> {
+    fn from(err: Error) -> Self {
+        Err(err)
     }
 }
 
@@ -127,283 +424,70 @@ impl From<quick_xml::Error> for Error {
         match err {
             quick_xml::Error::InvalidAttr(_) => Error::Malformed,
             quick_xml::Error::Io(_) => Error::IO,
-            quick_xml::Error::Utf8(_) => Error::Encoding,
+            quick_xml::Error::NonDecodable(_) => Error::Encoding,
             quick_xml::Error::UnexpectedEof(_) => Error::Malformed,
             quick_xml::Error::EndEventMismatch { .. } => Error::Malformed,
             quick_xml::Error::UnexpectedToken(_) => Error::Malformed,
             quick_xml::Error::UnexpectedBang(_) => Error::Malformed,
             quick_xml::Error::TextNotFound => Error::Malformed,
             quick_xml::Error::XmlDeclWithoutVersion(_) => Error::Malformed,
-            //            quick_xml::Error::NameWithQuote(_) => Error::Malformed,
-            //            quick_xml::Error::NoEqAfterName(_) => Error::Malformed,
-            //            quick_xml::Error::UnquotedValue(_) => Error::Malformed,
-            //            quick_xml::Error::DuplicatedAttribute(_, _) => Error::Malformed,
+            quick_xml::Error::UnknownPrefix(_) => Error::Malformed,
             quick_xml::Error::EscapeError(_) => Error::InvalidCharacter,
         }
     }
 }
 
 // ------------------------------------------------------------------------------------------------
-// Private Functions
+// Private Types
 // ------------------------------------------------------------------------------------------------
 
-fn inner_read<T: BufRead>(reader: &mut Reader<T>) -> Result<RefNode> {
-    let _safe_to_ignore = reader.trim_text(true);
-
-    let mut event_buffer: Vec<u8> = Vec::new();
-
-    document(reader, &mut event_buffer)
-}
-
-///
-/// This only needs to deal with the events that could start a document.
-///
-/// ```ebnf
-/// document          ::= prolog element Misc* - Char* RestrictedChar Char*
-///
-/// prolog            ::= XMLDecl Misc* (doctypedecl Misc*)?
-///
-/// XMLDecl           ::= '<?xml' VersionInfo EncodingDecl? SDDecl? S?'?>'
-///
-/// doctypedecl       ::= '<!DOCTYPE' S Name (S ExternalID)? S? ('[' intSubset ']' S?)? '>'
-///
-/// Misc              ::= Comment | PI | S
 ///
-/// Char              ::= [#x1-#xD7FF] | [#xE000-#xFFFD] | [#x10000-#x10FFFF]
+/// The name of the element that temporarily contains the content read by `read_fragment`.
 ///
-/// RestrictedChar    ::= [#x1-#x8] | [#xB-#xC] | [#xE-#x1F] | [#x7F-#x84] | [#x86-#x9F]
-///
-/// S                 ::= (#x20 | #x9 | #xD | #xA)+
-/// ```
-///
-fn document<T: BufRead>(reader: &mut Reader<T>, event_buffer: &mut Vec<u8>) -> Result<RefNode> {
-    let mut document = get_implementation()
-        .create_document(None, None, None)
-        .unwrap();
+const FRAGMENT_WRAPPER: &str = "fragment";
 
-    loop {
-        match reader.read_event(event_buffer) {
-            Ok(Event::Decl(ev)) => {
-                let mut mut_document = document.borrow_mut();
-                if let Extension::Document {
-                    i_xml_declaration, ..
-                } = &mut mut_document.i_extension
-                {
-                    if i_xml_declaration.is_some() {
-                        error!("XML declaration must be first");
-                        return Error::Malformed.into();
-                    } else {
-                        let (version, encoding, standalone) = make_decl(reader, ev)?;
-                        *i_xml_declaration = Some(XmlDecl::new(
-                            XmlVersion::from_str(&version).unwrap(),
-                            encoding,
-                            standalone,
-                        ));
-                    }
-                }
-            }
-            Ok(Event::Start(ev)) => {
-                let mut new_element = handle_start(reader, &mut document, None, ev)?;
-                let _safe_to_ignore =
-                    element(reader, event_buffer, &mut document, &mut new_element);
-            }
-            Ok(Event::Empty(ev)) => {
-                let _safe_to_ignore = handle_start(reader, &mut document, None, ev)?;
-            }
-            Ok(Event::End(ev)) => {
-                let _safe_to_ignore = handle_end(reader, &mut document, None, ev)?;
-            }
-            Ok(Event::Comment(ev)) => {
-                let _safe_to_ignore = handle_comment(reader, &mut document, None, ev)?;
-            }
-            Ok(Event::PI(ev)) => {
-                let _safe_to_ignore = handle_pi(reader, &mut document, None, ev)?;
-            }
-            // Ok(Event::DocType(ev)) => {
-            //     if prolog_pre_nodes
-            //         .iter()
-            //         .find(|n| n.kind == PreNodeKind::DocType)
-            //         .is_some()
-            //     {
-            //         error!("only one document type allowed");
-            //         return Error::Malformed.into();
-            //     }
-            //     prolog_pre_nodes.push(make_doc_type(reader, ev)?);
-            // }
-            Ok(Event::Eof) => return Ok(document),
-            Ok(ev) => {
-                error!("Unexpected parser event: {:?}", ev);
-                return Error::Malformed.into();
-            }
-            Err(err) => {
-                error!("Unexpected parser error: {:?}", err);
-                return Error::from(err).into();
-            }
-        }
-    }
-}
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
 
 ///
-/// Given a document that has been started, add to it.
-///
-/// ```ebnf
-/// element           ::= EmptyElemTag | STag content ETag
-/// STag              ::= '<' Name (S Attribute)* S? '>'
-/// Attribute         ::= Name Eq AttValue
-/// content           ::= CharData? ((element | Reference | CDSect | PI | Comment) CharData?)*
-/// EmptyElemTag      ::= '<' Name (S Attribute)* S? '/>'
-/// ```
+/// Start the element described by `ev`, returning its name. Attribute values are normalized as
+/// described in XML 1.0 §3.3.3, for an attribute declared as, or treated as, CDATA; each line
+/// break, and each other white space character, becomes a space while white space given by a
+/// character reference is kept.
 ///
-fn element<T: BufRead>(
-    reader: &mut Reader<T>,
-    event_buffer: &mut Vec<u8>,
-    document: &mut RefNode,
-    parent_element: &mut RefNode,
-) -> Result<RefNode> {
-    loop {
-        match reader.read_event(event_buffer) {
-            Ok(Event::Start(ev)) => {
-                let mut new_element = handle_start(reader, document, Some(parent_element), ev)?;
-                let _safe_to_ignore = element(reader, event_buffer, document, &mut new_element)?;
-            }
-            Ok(Event::Empty(ev)) => {
-                let _safe_to_ignore = handle_start(reader, document, Some(parent_element), ev)?;
-            }
-            Ok(Event::End(ev)) => {
-                let _safe_to_ignore = handle_end(reader, document, Some(parent_element), ev)?;
-                return Ok(parent_element.clone());
-            }
-            Ok(Event::Comment(ev)) => {
-                let _safe_to_ignore = handle_comment(reader, document, Some(parent_element), ev)?;
-            }
-            Ok(Event::PI(ev)) => {
-                let _safe_to_ignore = handle_pi(reader, document, Some(parent_element), ev)?;
-            }
-            Ok(Event::Text(ev)) => {
-                let _safe_to_ignore = handle_text(reader, document, Some(parent_element), ev)?;
-            }
-            Ok(Event::CData(ev)) => {
-                let _safe_to_ignore = handle_cdata(reader, document, Some(parent_element), ev)?;
-            }
-            Ok(ev) => {
-                error!("Unexpected parser event: {:?}", ev);
-                return Error::Malformed.into();
-            }
-            Err(err) => {
-                error!("Unexpected parser error: {:?}", err);
-                return Error::from(err).into();
-            }
-        }
-    }
-}
-
-// ------------------------------------------------------------------------------------------------
-
-fn handle_start<T: BufRead>(
-    reader: &mut Reader<T>,
-    document: &mut RefNode,
-    parent_node: Option<&mut RefNode>,
+fn handle_start<T>(
+    reader: &Reader<T>,
+    builder: &mut TreeBuilder,
+    entities: &HashMap<String, String>,
     ev: BytesStart<'_>,
-) -> Result<RefNode> {
-    let mut element = {
-        let mut_document = as_document_mut(document).unwrap();
-        let name = ev.name();
-        let name = reader.decode(name)?;
-        let new_node = mut_document.create_element(&name).unwrap();
-        let mut actual_parent = match parent_node {
-            None => document.clone(),
-            Some(actual) => actual.clone(),
-        };
-        actual_parent.append_child(new_node)?
-    };
-
+) -> Result<String> {
+    let name = reader.decoder().decode(ev.name().as_ref())?.to_string();
+    let mut attributes: Vec<(String, String)> = Vec::new();
     for attribute in ev.attributes() {
-        let attribute = attribute.unwrap();
-        let value = attribute.unescape_and_decode_value(reader)?;
-        let name = reader.decode(attribute.key)?;
-        let attribute_node = document.create_attribute_with(name, &value)?;
-
-        let _safe_to_ignore = element.set_attribute_node(attribute_node)?;
+        let attribute = attribute.map_err(quick_xml::Error::InvalidAttr)?;
+        let value = normalize_white_space(&reader.decoder().decode(attribute.value.as_ref())?);
+        let value = unescape_with(&value, |name| entities.get(name).map(String::as_str))
+            .map_err(quick_xml::Error::EscapeError)?;
+        let name = reader.decoder().decode(attribute.key.as_ref())?;
+        attributes.push((name.to_string(), value.to_string()));
     }
-
-    Ok(element)
-}
-
-fn handle_end<T: BufRead>(
-    _reader: &mut Reader<T>,
-    document: &mut RefNode,
-    parent_node: Option<&mut RefNode>,
-    _ev: BytesEnd<'_>,
-) -> Result<RefNode> {
-    Ok(match parent_node {
-        None => document,
-        Some(actual) => actual,
-    }
-    .clone())
-}
-
-fn handle_comment<T: BufRead>(
-    reader: &mut Reader<T>,
-    document: &mut RefNode,
-    parent_node: Option<&mut RefNode>,
-    ev: BytesText<'_>,
-) -> Result<RefNode> {
-    let mut_document = as_document_mut(document).unwrap();
-    let text = make_text(reader, ev)?;
-    let new_node = mut_document.create_comment(&text);
-    let actual_parent = match parent_node {
-        None => document,
-        Some(actual) => actual,
-    };
-    actual_parent.append_child(new_node).map_err(|e| e.into())
-}
-
-fn handle_text<T: BufRead>(
-    reader: &mut Reader<T>,
-    document: &mut RefNode,
-    parent_node: Option<&mut RefNode>,
-    ev: BytesText<'_>,
-) -> Result<RefNode> {
-    let mut_document = as_document_mut(document).unwrap();
-    let text = make_text(reader, ev)?;
-    let new_node = mut_document.create_text_node(&text);
-    let actual_parent = match parent_node {
-        None => document,
-        Some(actual) => actual,
-    };
-    actual_parent.append_child(new_node).map_err(|e| e.into())
+    let attributes: Vec<(&str, &str)> = attributes
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    builder.start_element(&name, &attributes)?;
+    Ok(name)
 }
 
-fn handle_cdata<T: BufRead>(
-    reader: &mut Reader<T>,
-    document: &mut RefNode,
-    parent_node: Option<&mut RefNode>,
-    ev: BytesCData<'_>,
-) -> Result<RefNode> {
-    let mut_document = as_document_mut(document).unwrap();
-    let text = make_cdata(reader, ev)?;
-    let new_node = mut_document.create_cdata_section(text.as_ref()).unwrap();
-    let actual_parent = match parent_node {
-        None => document,
-        Some(actual) => actual,
-    };
-    actual_parent.append_child(new_node).map_err(|e| e.into())
-}
-
-fn handle_pi<T: BufRead>(
-    reader: &mut Reader<T>,
-    document: &mut RefNode,
-    parent_node: Option<&mut RefNode>,
-    ev: BytesText<'_>,
-) -> Result<RefNode> {
-    let mut_document = as_document_mut(document).unwrap();
+fn handle_pi<T>(reader: &Reader<T>, builder: &mut TreeBuilder, ev: BytesText<'_>) -> Result<()> {
     let (target, data) = {
-        let text = ev.unescape_and_decode(&reader)?;
-        let parts = text.splitn(2, ' ').collect::<Vec<&str>>();
+        let text = normalize_end_of_lines(&reader.decoder().decode(ev.as_ref())?);
+        let parts = text.splitn(2, is_xml_space).collect::<Vec<&str>>();
         match parts.len() {
             1 => (parts[0].to_string(), None),
             2 => {
-                let data = parts[1].trim();
+                let data = parts[1].trim_start();
                 if data.is_empty() {
                     (parts[0].to_string(), None)
                 } else {
@@ -413,58 +497,115 @@ fn handle_pi<T: BufRead>(
             _ => return Error::Malformed.into(),
         }
     };
-    let new_node = match data {
-        None => mut_document
-            .create_processing_instruction(&target, None)
-            .unwrap(),
-        Some(s) => mut_document
-            .create_processing_instruction(&target, Some(s.as_str()))
-            .unwrap(),
-    };
-    let actual_parent = match parent_node {
-        None => document,
-        Some(actual) => actual,
-    };
-    actual_parent.append_child(new_node).map_err(|e| e.into())
+    Ok(builder.processing_instruction(&target, data.as_deref())?)
 }
 
 // ------------------------------------------------------------------------------------------------
 
-fn make_text<T: BufRead>(reader: &mut Reader<T>, ev: BytesText<'_>) -> Result<String> {
-    Ok(ev.unescape_and_decode(&reader)?)
+///
+/// Add the text described by `ev`, with line breaks normalized as described in XML 1.0 §2.11;
+/// each reference to a declared general entity within it is added as an entity reference node
+/// between the text nodes before and after it.
+///
+fn handle_text<T>(
+    reader: &Reader<T>,
+    builder: &mut TreeBuilder,
+    entities: &HashMap<String, String>,
+    ev: BytesText<'_>,
+) -> Result<()> {
+    let text = normalize_end_of_lines(&reader.decoder().decode(ev.as_ref())?);
+    let mut rest: &str = &text;
+    while let Some((before, name, after)) = split_entity_reference(rest, entities) {
+        if !before.is_empty() {
+            builder.text(&unescape(before).map_err(quick_xml::Error::EscapeError)?)?;
+        }
+        builder.entity_reference(name)?;
+        rest = after;
+    }
+    if !rest.is_empty() {
+        builder.text(&unescape(rest).map_err(quick_xml::Error::EscapeError)?)?;
+    }
+    Ok(())
+}
+
+///
+/// Split `text` at the first reference to an entity in `entities`, returning the text before the
+/// reference, the entity name, and the text after the reference.
+///
+fn split_entity_reference<'a>(
+    text: &'a str,
+    entities: &HashMap<String, String>,
+) -> Option<(&'a str, &'a str, &'a str)> {
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find(XML_ENTITYREF_START) {
+        let start = offset + start;
+        let end = start + text[start..].find(XML_ENTITYREF_END)?;
+        let name = &text[start + XML_ENTITYREF_START.len()..end];
+        if entities.contains_key(name) {
+            return Some((&text[..start], name, &text[end + XML_ENTITYREF_END.len()..]));
+        }
+        offset = end;
+    }
+    None
+}
+
+///
+/// The general entities declared by the document type of `document_node`, and the text of the
+/// replacement nodes of each with white space normalized; this is the value of a reference to the
+/// entity in an attribute.
+///
+fn declared_entities(document_node: &RefNode) -> HashMap<String, String> {
+    match document_node.doc_type() {
+        Some(doc_type) => as_document_type(&doc_type)
+            .map(|doc_type| {
+                doc_type
+                    .entities()
+                    .iter()
+                    .map(|(name, entity)| {
+                        (
+                            name.to_string(),
+                            normalize_white_space(&text_content(entity)),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        None => Default::default(),
+    }
 }
 
-fn make_cdata<T: BufRead>(reader: &mut Reader<T>, ev: BytesCData<'_>) -> Result<String> {
+fn make_cdata<T>(reader: &Reader<T>, ev: BytesCData<'_>) -> Result<String> {
     let cdata_bytes = ev.into_inner();
-    let decoded_string = reader.decode(cdata_bytes.as_ref())?;
-    Ok(decoded_string.to_string())
+    let decoded_string = reader.decoder().decode(cdata_bytes.as_ref())?;
+    Ok(normalize_end_of_lines(&decoded_string))
 }
 
-fn make_decl<T: BufRead>(
-    reader: &mut Reader<T>,
-    ev: BytesDecl<'_>,
-) -> Result<(String, Option<String>, Option<bool>)> {
-    let version = ev.version().unwrap();
-    let version = version.borrow();
-    let version = reader.decode(version).unwrap();
-    let version = unquote(version.to_string())?;
-    let encoding = if let Some(ev_value) = ev.encoding() {
-        let encoding = ev_value.unwrap();
-        let encoding = encoding.borrow();
-        let encoding = reader.decode(encoding).unwrap();
-        Some(encoding.to_string())
-    } else {
-        None
+///
+/// Normalize line breaks in `value`, and then replace each white space character with a space.
+///
+fn normalize_white_space(value: &str) -> String {
+    normalize_end_of_lines(value)
+        .chars()
+        .map(|c| if is_xml_space(c) { ' ' } else { c })
+        .collect()
+}
+
+fn make_decl<T>(reader: &Reader<T>, ev: BytesDecl<'_>) -> Result<XmlDecl> {
+    let version = ev.version()?;
+    let version = unquote(reader.decoder().decode(&version)?.to_string())?;
+    let encoding = match ev.encoding() {
+        Some(encoding) => Some(reader.decoder().decode(&encoding?)?.to_string()),
+        None => None,
     };
-    let standalone = if let Some(ev_value) = ev.standalone() {
-        let standalone = ev_value.unwrap();
-        let standalone = standalone.borrow();
-        let standalone = reader.decode(standalone).unwrap();
-        Some(standalone == "yes")
-    } else {
-        None
+    let standalone = match ev.standalone() {
+        Some(standalone) => Some(reader.decoder().decode(&standalone?)? == "yes"),
+        None => None,
     };
-    Ok((version, encoding, standalone))
+    let version = XmlVersion::from_str(&version).map_err(|_| {
+        error!("Unsupported XML version: {}", version);
+        Error::Malformed
+    })?;
+    Ok(XmlDecl::new(version, encoding, standalone))
 }
 
 #[allow(clippy::if_same_then_else)]
@@ -487,6 +628,8 @@ fn unquote(s: String) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::level2::convert::as_element;
+    use quick_xml::events::BytesEnd;
 
     fn test_good_xml(xml: &str) {
         let dom = read_xml(xml);
@@ -553,4 +696,224 @@ mod tests {
 "###,
         );
     }
+
+    #[test]
+    fn test_doc_type() {
+        use crate::level2::convert::as_document_type;
+        use crate::level2::ext::{DocumentDecl, PrologPosition};
+
+        let dom = read_xml(
+            r#"<!-- first --><!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN"
+  'http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd' [ <!ENTITY copy "&#169;"> ]>
+<!-- second --><html/>"#,
+        )
+        .unwrap();
+        let doc_type = dom.doc_type().unwrap();
+        let doc_type = as_document_type(&doc_type).unwrap();
+        assert_eq!(doc_type.node_name().to_string(), "html");
+        assert_eq!(
+            doc_type.public_id(),
+            Some("-//W3C//DTD XHTML 1.0 Strict//EN".to_string())
+        );
+        assert_eq!(
+            doc_type.system_id(),
+            Some("http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd".to_string())
+        );
+        assert_eq!(doc_type.internal_subset(), None);
+        let entities = doc_type.entities();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(
+            entities.values().next().unwrap().node_value(),
+            Some("&#169;".to_string())
+        );
+
+        let children = dom.child_nodes();
+        assert_eq!(children.len(), 3);
+        assert_eq!(
+            dom.prolog_position(&children[0]),
+            Some(PrologPosition::BeforeDocType)
+        );
+        assert_eq!(
+            dom.prolog_position(&children[1]),
+            Some(PrologPosition::AfterDocType)
+        );
+
+        let dom = read_xml("<!DOCTYPE note SYSTEM \"note.dtd\"><note/>").unwrap();
+        let doc_type = dom.doc_type().unwrap();
+        let doc_type = as_document_type(&doc_type).unwrap();
+        assert_eq!(doc_type.public_id(), None);
+        assert_eq!(doc_type.system_id(), Some("note.dtd".to_string()));
+        assert_eq!(doc_type.internal_subset(), None);
+    }
+
+    #[test]
+    fn test_content() {
+        let dom =
+            read_xml("<root a=\"1 &amp; 2\"><?target  some data ?>x &lt; y<![CDATA[<z/>]]></root>")
+                .unwrap();
+        let root = dom.first_child().unwrap();
+        assert_eq!(root.get_attribute("a"), Some("1 &#38; 2".to_string()));
+        let children = root.child_nodes();
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[0].node_name().to_string(), "target");
+        assert_eq!(children[0].node_value(), Some("some data ".to_string()));
+        assert_eq!(children[1].node_value(), Some("x < y".to_string()));
+        assert_eq!(children[2].node_type(), NodeType::CData);
+        assert_eq!(children[2].node_value(), Some("<z/>".to_string()));
+    }
+
+    #[test]
+    fn test_namespaces() {
+        let dom = read_xml(
+            "<p:a xmlns:p=\"u:p\" xmlns=\"u:d\" p:x=\"1\" y=\"2\"><p:b/><c xmlns=\"\"/></p:a>",
+        )
+        .unwrap();
+        let root = dom.first_child().unwrap();
+        assert_eq!(root.namespace_uri().as_deref(), Some("u:p"));
+        assert_eq!(root.local_name(), "a");
+        let children = root.child_nodes();
+        assert_eq!(children[0].namespace_uri().as_deref(), Some("u:p"));
+        assert_eq!(children[1].namespace_uri(), None);
+        assert_eq!(
+            root.get_attribute_node_ns("u:p", "x")
+                .and_then(|attribute| attribute.namespace_uri()),
+            Some("u:p".to_string())
+        );
+        assert!(root.get_attribute_node_ns("u:d", "y").is_none());
+        assert_eq!(
+            root.to_string(),
+            "<p:a xmlns:p=\"u:p\" xmlns=\"u:d\" p:x=\"1\" y=\"2\">\
+             <p:b></p:b><c xmlns=\"\"></c></p:a>"
+        );
+    }
+
+    #[test]
+    fn test_entity_references() {
+        let dom = read_xml(
+            "<!DOCTYPE r [<!ENTITY e \"v\"><!ENTITY copy \"&#169;\">]>\
+             <r a=\"&e;-&copy;\">x&e;&lt;&e;</r>",
+        )
+        .unwrap();
+        let root = dom.first_child().unwrap();
+        assert_eq!(root.get_attribute("a"), Some("v-\u{A9}".to_string()));
+        let children = root.child_nodes();
+        assert_eq!(
+            children
+                .iter()
+                .map(|child| child.node_type())
+                .collect::<Vec<NodeType>>(),
+            vec![
+                NodeType::Text,
+                NodeType::EntityReference,
+                NodeType::Text,
+                NodeType::EntityReference
+            ]
+        );
+        assert_eq!(children[1].node_name().to_string(), "e");
+        assert_eq!(
+            children[1].first_child().unwrap().node_value(),
+            Some("v".to_string())
+        );
+        assert_eq!(children[2].node_value(), Some("<".to_string()));
+        assert_eq!(root.to_string(), "<r a=\"v-\u{A9}\">x&e;&#60;&e;</r>");
+
+        assert!(read_xml("<r>&e;</r>").is_err());
+        assert!(read_xml("<r a=\"&e;\"/>").is_err());
+    }
+
+    #[test]
+    fn test_white_space() {
+        let dom = read_xml("<r>x <b/> y<c>\n  </c></r>").unwrap();
+        let root = dom.first_child().unwrap();
+        assert_eq!(root.to_string(), "<r>x <b></b> y<c>\n  </c></r>");
+        let text = root.last_child().unwrap().first_child().unwrap();
+        assert_eq!(text.node_type(), NodeType::Text);
+        assert_eq!(text.node_value(), Some("\n  ".to_string()));
+
+        let document_node = read_xml("<a/>").unwrap();
+        let fragment = read_fragment(&document_node, "x <b/> ", &NamespaceContext::new()).unwrap();
+        assert_eq!(fragment.child_nodes().len(), 3);
+    }
+
+    #[test]
+    fn test_normalization() {
+        let dom = read_xml(
+            "<!DOCTYPE r [<!ENTITY e \"1&#10;2\">]>\
+             <r a=\"x\ty\r\nz\" b=\"&#10;\" c=\"&e;\">1\r\n2\r</r>",
+        )
+        .unwrap();
+        let root_node = as_document(&dom).unwrap().document_element().unwrap();
+        let root = as_element(&root_node).unwrap();
+        assert_eq!(root.get_attribute("a"), Some("x y z".to_string()));
+        assert_eq!(root.get_attribute("c"), Some("1 2".to_string()));
+        assert_eq!(
+            root.first_child().unwrap().node_value(),
+            Some("1\n2\n".to_string())
+        );
+        assert_eq!(
+            root_node.to_string(),
+            "<r a=\"x y z\" b=\"&#10;\" c=\"1 2\">1\n2\n</r>"
+        );
+    }
+
+    #[test]
+    fn test_malformed_documents() {
+        for xml in &[
+            "",
+            "<!-- only a comment -->",
+            "<a></b>",
+            "<a>",
+            "text<a/>",
+            "<a/><!DOCTYPE a>",
+            "<!DOCTYPE a><!DOCTYPE a><a/>",
+            "<!DOCTYPE a SYSTEM><a/>",
+            "<!DOCTYPE a SYSTEM \"a.dtd\" junk><a/>",
+            "<?xml version=\"2.0\"?><a/>",
+        ] {
+            assert!(read_xml(xml).is_err(), "{}", xml);
+        }
+    }
+
+    #[test]
+    fn test_read_events() {
+        let xml = "<list>\n  <item>1</item>\n  <item>2</item>\n</list>";
+
+        let mut reader = Reader::from_str(xml);
+        let dom = read_events(&mut reader).unwrap();
+        let root = dom.first_child().unwrap();
+        assert_eq!(root.child_nodes().len(), 5);
+        assert_eq!(dom.to_string(), xml);
+
+        let mut reader = Reader::from_str(xml);
+        let _safe_to_ignore = reader.trim_text(true);
+        let dom = read_events(&mut reader).unwrap();
+        assert_eq!(dom.first_child().unwrap().child_nodes().len(), 2);
+    }
+
+    #[test]
+    fn test_builder_events() {
+        let reader = Reader::from_str("");
+        let mut builder = DomBuilder::new();
+        builder
+            .handle_event(&reader, Event::Start(BytesStart::new("a")))
+            .unwrap();
+        builder
+            .handle_event(&reader, Event::Empty(BytesStart::new("b")))
+            .unwrap();
+        assert_eq!(builder.depth(), 1);
+        assert!(builder.clone().finish().is_err());
+        builder
+            .handle_event(&reader, Event::End(BytesEnd::new("a")))
+            .unwrap();
+        assert_eq!(builder.depth(), 0);
+        assert!(builder
+            .handle_event(&reader, Event::End(BytesEnd::new("a")))
+            .is_err());
+        assert!(builder
+            .handle_event(&reader, Event::CData(BytesCData::new("c")))
+            .is_err());
+        assert_eq!(builder.finish().unwrap().to_string(), "<a><b></b></a>");
+
+        assert!(DomBuilder::default().finish().is_err());
+    }
 }
@@ -1,8 +1,13 @@
 use crate::level2::convert::*;
 use crate::level2::ext::convert::{as_document_decl, RefDocumentDecl};
+use crate::level2::ext::dtd::is_parameter_entity;
+use crate::level2::ext::mutation::raw_value;
+use crate::level2::ext::{DocumentTypeDecl, PrologPosition};
 use crate::level2::*;
 use crate::shared::syntax::*;
-use std::fmt::{Formatter, Result as FmtResult};
+use crate::shared::text::{escape, to_entity};
+use std::collections::HashMap;
+use std::fmt::{Error as FmtError, Formatter, Result as FmtResult};
 
 // ------------------------------------------------------------------------------------------------
 // Public Functions
@@ -10,12 +15,15 @@ use std::fmt::{Formatter, Result as FmtResult};
 
 pub(crate) fn fmt_element(element: RefElement<'_>, f: &mut Formatter<'_>) -> FmtResult {
     write!(f, "{}{}", XML_ELEMENT_START_START, element.node_name())?;
-    for attr in element.attributes().values() {
-        write!(f, " {}", attr.to_string())?;
+    let attributes = element.attributes();
+    for name in element.attribute_names() {
+        if let Some(attr) = attributes.get(&name) {
+            write!(f, " {}", attr)?;
+        }
     }
     write!(f, "{}", XML_ELEMENT_START_END)?;
     for child in element.child_nodes() {
-        write!(f, "{}", child.to_string())?;
+        write!(f, "{}", child)?;
     }
     write!(
         f,
@@ -26,26 +34,32 @@ pub(crate) fn fmt_element(element: RefElement<'_>, f: &mut Formatter<'_>) -> Fmt
     )
 }
 
-pub(crate) fn fmt_attribute(attribute: RefAttribute<'_>, f: &mut Formatter<'_>) -> FmtResult {
+pub(crate) fn fmt_attribute(attribute: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
     write!(
         f,
         "{}=\"{}\"",
         attribute.node_name(),
-        attribute.value().unwrap_or_default()
+        escape_attribute_whitespace(&escape(&raw_value(attribute)))
     )
 }
 
 pub(crate) fn fmt_text(character_data: RefCharacterData<'_>, f: &mut Formatter<'_>) -> FmtResult {
     match character_data.data() {
         None => Ok(()),
-        Some(data) => write!(f, "{}", data),
+        Some(data) => write!(f, "{}", escape_carriage_return(&data)),
     }
 }
 
 pub(crate) fn fmt_cdata(character_data: RefCharacterData<'_>, f: &mut Formatter<'_>) -> FmtResult {
     match character_data.data() {
         None => Ok(()),
-        Some(data) => write!(f, "{} {} {}", XML_CDATA_START, data, XML_CDATA_END),
+        Some(data) => write!(
+            f,
+            "{}{}{}",
+            XML_CDATA_START,
+            split_cdata_end(&data),
+            XML_CDATA_END
+        ),
     }
 }
 
@@ -63,9 +77,26 @@ pub(crate) fn fmt_comment(
     character_data: RefCharacterData<'_>,
     f: &mut Formatter<'_>,
 ) -> FmtResult {
-    match character_data.data() {
+    //
+    // Markup is not recognized in a comment, so the value is written as is, not escaped as `data`
+    // would return it.
+    //
+    match character_data.node_value() {
+        None => Ok(()),
+        Some(data) => write!(
+            f,
+            "{}{}{}",
+            XML_COMMENT_START,
+            separate_hyphens(&data),
+            XML_COMMENT_END
+        ),
+    }
+}
+
+pub(crate) fn fmt_unparsed_markup(node: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
+    match node.node_value() {
         None => Ok(()),
-        Some(data) => write!(f, "{}{}{}", XML_COMMENT_START, data, XML_COMMENT_END),
+        Some(markup) => write!(f, "{}", markup),
     }
 }
 
@@ -73,16 +104,23 @@ pub(crate) fn fmt_document(document: RefDocumentDecl<'_>, f: &mut Formatter<'_>)
     if let Some(xml_declaration) = &document.xml_declaration() {
         write!(f, "{}", xml_declaration)?;
     }
-    if let Some(doc_type) = &document.doc_type() {
-        write!(f, "{}", doc_type)?;
-    }
+    let mut doc_type = document.doc_type();
     for child in document.child_nodes() {
-        write!(f, "{}", child.to_string())?;
+        if document.prolog_position(&child) != Some(PrologPosition::BeforeDocType) {
+            if let Some(doc_type) = doc_type.take() {
+                write!(f, "{}", doc_type)?;
+            }
+        }
+        write!(f, "{}", child)?;
+    }
+    if let Some(doc_type) = doc_type {
+        write!(f, "{}", doc_type)?;
     }
     Ok(())
 }
 
-pub(crate) fn fmt_document_type(doc_type: RefDocumentType<'_>, f: &mut Formatter<'_>) -> FmtResult {
+pub(crate) fn fmt_document_type(node: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
+    let doc_type = as_document_type(node).map_err(|_| FmtError)?;
     write!(f, "{} {}", XML_DOCTYPE_START, doc_type.node_name())?;
     if let Some(id) = &doc_type.public_id() {
         write!(f, " {} \"{}\"", XML_DOCTYPE_PUBLIC, id)?;
@@ -90,37 +128,70 @@ pub(crate) fn fmt_document_type(doc_type: RefDocumentType<'_>, f: &mut Formatter
     if let Some(id) = &doc_type.system_id() {
         write!(f, " {} \"{}\"", XML_DOCTYPE_SYSTEM, id)?;
     }
-    if (doc_type.entities().len() + doc_type.notations().len() > 0)
-        || doc_type.internal_subset().is_some()
-    {
-        write!(f, "{}", XML_DOCTYPE_ENTITY_START)?;
-        for (_, entity) in doc_type.entities() {
-            write!(f, "{}", entity)?;
-        }
-        for (_, notation) in doc_type.notations() {
-            write!(f, "{}", notation)?;
-        }
-        if let Some(internal_subset) = doc_type.internal_subset() {
-            write!(f, "{}", internal_subset)?;
-        }
-        write!(f, "{}", XML_DOCTYPE_ENTITY_END)?;
+    if let Some(internal_subset) = internal_subset_text(node) {
+        write!(
+            f,
+            "{}{}{}",
+            XML_DOCTYPE_ENTITY_START, internal_subset, XML_DOCTYPE_ENTITY_END
+        )?;
     }
     write!(f, "{}", XML_DOCTYPE_END)
 }
 
+///
+/// Returns the complete internal subset of `doc_type`, the declared entities, parameter entities,
+/// notations, and conditional sections followed by any remaining internal subset text; `None`
+/// is returned if there is nothing to write.
+///
+pub(crate) fn internal_subset_text(doc_type: &RefNode) -> Option<String> {
+    let entities = doc_type.entities();
+    let parameter_entities = doc_type.parameter_entities();
+    let notations = doc_type.notations();
+    let conditional_sections = doc_type.conditional_sections();
+    let internal_subset = doc_type.internal_subset();
+    if entities.is_empty()
+        && parameter_entities.is_empty()
+        && notations.is_empty()
+        && conditional_sections.is_empty()
+        && internal_subset.is_none()
+    {
+        return None;
+    }
+    let mut text = String::new();
+    for declaration in sorted_by_name(entities)
+        .into_iter()
+        .chain(sorted_by_name(parameter_entities))
+        .chain(sorted_by_name(notations))
+    {
+        text.push_str(&declaration.to_string());
+    }
+    for section in conditional_sections {
+        text.push_str(&section.to_string());
+    }
+    if let Some(internal_subset) = internal_subset {
+        text.push_str(&internal_subset);
+    }
+    Some(text)
+}
+
 pub(crate) fn fmt_document_fragment(
     fragment: RefDocumentFragment<'_>,
     f: &mut Formatter<'_>,
 ) -> FmtResult {
     write!(f, "{}{} ", XML_CDATA_START, fragment.node_name())?;
     for child in fragment.child_nodes() {
-        write!(f, "{}", child.to_string())?;
+        write!(f, "{}", child)?;
     }
     write!(f, "{}", XML_CDATA_END)
 }
 
-pub(crate) fn fmt_entity(entity: RefEntity<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    write!(f, "{} {}", XML_ENTITY_START, entity.node_name())?;
+pub(crate) fn fmt_entity(node: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
+    let entity = as_entity(node).map_err(|_| FmtError)?;
+    write!(f, "{}", XML_ENTITY_START)?;
+    if is_parameter_entity(node) {
+        write!(f, " {}", XML_ENTITY_PARAMETER)?;
+    }
+    write!(f, " {}", entity.node_name())?;
     if entity.public_id().is_none() && entity.system_id().is_none() {
         write!(f, " \"{}\"", entity.node_value().unwrap_or_default())?;
     } else if let Some(public_id) = entity.public_id() {
@@ -131,8 +202,8 @@ pub(crate) fn fmt_entity(entity: RefEntity<'_>, f: &mut Formatter<'_>) -> FmtRes
     } else if let Some(system_id) = entity.system_id() {
         write!(f, " {} \"{}\"", XML_DOCTYPE_SYSTEM, system_id)?;
     }
-    if let Some(entity_name) = entity.notation_name() {
-        write!(f, " {}", entity_name)?;
+    if let Some(notation_name) = entity.notation_name() {
+        write!(f, " {} {}", XML_ENTITY_NOTATION, notation_name)?;
     }
     write!(f, "{}", XML_ENTITY_END)
 }
@@ -166,7 +237,7 @@ pub(crate) fn fmt_notation(notation: RefNotation<'_>, f: &mut Formatter<'_>) ->
 pub(crate) fn fmt_node(node: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
     match node.node_type() {
         NodeType::Element => fmt_element(as_element(node).unwrap(), f),
-        NodeType::Attribute => fmt_attribute(as_attribute(node).unwrap(), f),
+        NodeType::Attribute => fmt_attribute(node, f),
         NodeType::Text => fmt_text(as_character_data(node).unwrap(), f),
         NodeType::CData => fmt_cdata(as_character_data(node).unwrap(), f),
         NodeType::ProcessingInstruction => {
@@ -174,10 +245,75 @@ pub(crate) fn fmt_node(node: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
         }
         NodeType::Comment => fmt_comment(as_character_data(node).unwrap(), f),
         NodeType::Document => fmt_document(as_document_decl(node).unwrap(), f),
-        NodeType::DocumentType => fmt_document_type(as_document_type(node).unwrap(), f),
+        NodeType::DocumentType => fmt_document_type(node, f),
         NodeType::DocumentFragment => fmt_document_fragment(as_document_fragment(node).unwrap(), f),
-        NodeType::Entity => fmt_entity(as_entity(node).unwrap(), f),
+        NodeType::Entity => fmt_entity(node, f),
         NodeType::EntityReference => fmt_entity_reference(as_entity_reference(node).unwrap(), f),
         NodeType::Notation => fmt_notation(as_notation(node).unwrap(), f),
+        NodeType::UnparsedMarkup => fmt_unparsed_markup(node, f),
     }
 }
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A literal carriage return in content is read as a line feed, so it is written as a character
+/// reference.
+///
+fn escape_carriage_return(data: &str) -> String {
+    data.replace('\r', &to_entity('\r'))
+}
+
+///
+/// A parser normalizes each tab, newline, or carriage return in an attribute value to a space, so
+/// these are written as character references to be read back unchanged.
+///
+fn escape_attribute_whitespace(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\t' | '\n' | '\r' => result.push_str(&to_entity(c)),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+///
+/// A CDATA section cannot contain its own end delimiter, so each `"]]>"` in `data` ends the
+/// section after `"]]"` and starts a new section before `">"`.
+///
+fn split_cdata_end(data: &str) -> String {
+    data.replace(
+        XML_CDATA_END,
+        &format!("]]{}{}>", XML_CDATA_END, XML_CDATA_START),
+    )
+}
+
+///
+/// A comment cannot contain `"--"`, or end with `"-"`, and there is no escape mechanism within a
+/// comment, so a space is written after any hyphen that would be followed by another, or end the
+/// comment.
+///
+pub(crate) fn separate_hyphens(data: &str) -> String {
+    let mut result = String::with_capacity(data.len());
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        result.push(c);
+        if c == '-' && matches!(chars.peek(), Some('-') | None) {
+            result.push(' ');
+        }
+    }
+    result
+}
+
+///
+/// Declarations are held in a `HashMap`, sort them so that output is stable between runs.
+///
+fn sorted_by_name(declarations: HashMap<Name, RefNode>) -> Vec<RefNode> {
+    let mut declarations: Vec<(Name, RefNode)> = declarations.into_iter().collect();
+    declarations.sort_by_key(|(name, _)| name.to_string());
+    declarations.into_iter().map(|(_, node)| node).collect()
+}
@@ -85,16 +85,33 @@ pub(crate) const MSG_INVALID_EXTENSION: &str =
 ///
 pub(crate) const MSG_INVALID_NAME: &str = "The provided value could not be parsed into a `Name`.";
 ///
+/// Error message: "The provided value may not be empty."
+///
+pub(crate) const MSG_EMPTY_NAME_PART: &str = "The provided value may not be empty.";
+///
+/// Error message: "The provided value may not contain a ':' character."
+///
+pub(crate) const MSG_NAME_PART_COLON: &str = "The provided value may not contain a ':' character.";
+///
+/// Error message: "The provided value contains a character not allowed in this position."
+///
+pub(crate) const MSG_NAME_PART_CHARACTER: &str =
+    "The provided value contains a character not allowed in this position.";
+///
 /// Error message: "This node is missing a `parent_node` value."
 ///
 pub(crate) const MSG_NO_PARENT_NODE: &str = "This node is missing a `parent_node` value.";
 ///
 /// Error message: "Cannot append or insert a child node created in a different document."
 ///
-#[allow(dead_code)]
 pub(crate) const MSG_WRONG_DOCUMENT: &str =
     "Cannot append or insert a child node created in a different document.";
 ///
+/// Error message: "Cannot insert a node as a child of itself, or of one of its descendants."
+///
+pub(crate) const MSG_ANCESTOR_INSERTION: &str =
+    "Cannot insert a node as a child of itself, or of one of its descendants.";
+///
 /// Error message: "Either `offset` or `count` invalid for string operation."
 ///
 pub(crate) const MSG_INDEX_ERROR: &str = "Either `offset` or `count` invalid for string operation.";
@@ -107,6 +124,226 @@ pub(crate) const MSG_WEAK_REF: &str = "Could not upgrade a weak reference.";
 ///
 pub(crate) const MSG_DUPLICATE_ID: &str =
     "Violation of `xml:id` §4, attempt to insert duplicate ID value.";
+///
+/// Error message: "A declaration requires at least one of a public or system identifier."
+///
+pub(crate) const MSG_MISSING_EXTERNAL_ID: &str =
+    "A declaration requires at least one of a public or system identifier.";
+///
+/// Error message: "A declaration with this name already exists."
+///
+pub(crate) const MSG_DUPLICATE_DECLARATION: &str = "A declaration with this name already exists.";
+///
+/// Error message: "A conditional section must have the keyword INCLUDE, IGNORE, or a parameter entity reference, and balanced content."
+///
+pub(crate) const MSG_INVALID_CONDITIONAL_SECTION: &str =
+    "A conditional section must have the keyword INCLUDE, IGNORE, or a parameter entity reference, and balanced content.";
+///
+/// Error message: "The prefix 'xml' may only be bound to the XML namespace, and the prefix 'xmlns' may not be declared."
+///
+pub(crate) const MSG_RESERVED_PREFIX: &str =
+    "The prefix 'xml' may only be bound to the XML namespace, and the prefix 'xmlns' may not be declared.";
+///
+/// Error message: "Only the default namespace may be un-declared with an empty value."
+///
+pub(crate) const MSG_PREFIX_UNDECLARED: &str =
+    "Only the default namespace may be un-declared with an empty value.";
+///
+/// Error message: "The XML and XMLNS namespaces may not be bound to any other prefix."
+///
+pub(crate) const MSG_RESERVED_NAMESPACE: &str =
+    "The XML and XMLNS namespaces may not be bound to any other prefix.";
+///
+/// Error message: "Processing instruction data may not contain the sequence '?>'."
+///
+pub(crate) const MSG_INVALID_PI_DATA: &str =
+    "Processing instruction data may not contain the sequence '?>'.";
+///
+/// Error message: "The prefix used by this node has no in-scope namespace declaration."
+///
+pub(crate) const MSG_UNDECLARED_PREFIX: &str =
+    "The prefix used by this node has no in-scope namespace declaration.";
+///
+/// Error message: "Could not parse a recorded mutation."
+///
+pub(crate) const MSG_INVALID_MUTATION: &str = "Could not parse a recorded mutation.";
+///
+/// Error message: "A mutation log must begin with the creation of the document."
+///
+pub(crate) const MSG_MISSING_DOCUMENT_MUTATION: &str =
+    "A mutation log must begin with the creation of the document.";
+///
+/// Error message: "No registered provider has the content of the referenced attachment."
+///
+#[cfg(feature = "xop")]
+pub(crate) const MSG_ATTACHMENT_NOT_FOUND: &str =
+    "No registered provider has the content of the referenced attachment.";
+///
+/// Error message: "The content of this element is not valid for the requested binary encoding."
+///
+pub(crate) const MSG_INVALID_BINARY_CONTENT: &str =
+    "The content of this element is not valid for the requested binary encoding.";
+///
+/// Error message: "The value is not in the lexical space of the requested XML Schema type."
+///
+pub(crate) const MSG_INVALID_XSD_VALUE: &str =
+    "The value is not in the lexical space of the requested XML Schema type.";
+///
+/// Error message: "The prefix is already bound to a different namespace."
+///
+pub(crate) const MSG_PREFIX_REBOUND: &str = "The prefix is already bound to a different namespace.";
+///
+/// Error message: "The column path is not a valid sequence of element and attribute steps."
+///
+pub(crate) const MSG_INVALID_COLUMN_PATH: &str =
+    "The column path is not a valid sequence of element and attribute steps.";
+///
+/// Error message: "Could not write to the output."
+///
+pub(crate) const MSG_WRITE_FAILED: &str = "Could not write to the output.";
+///
+/// Error message: "One or more elements were started but not ended."
+///
+pub(crate) const MSG_UNCLOSED_ELEMENTS: &str = "One or more elements were started but not ended.";
+///
+/// Error message: "An element was ended that had not been started."
+///
+pub(crate) const MSG_UNMATCHED_END_ELEMENT: &str =
+    "An element was ended that had not been started.";
+///
+/// Error message: "The thread that owns the document is not available."
+///
+pub(crate) const MSG_DOCUMENT_THREAD_UNAVAILABLE: &str =
+    "The thread that owns the document is not available.";
+///
+/// Error message: "Cannot call a document operation from within another operation."
+///
+pub(crate) const MSG_REENTRANT_CALL: &str =
+    "Cannot call a document operation from within another operation.";
+///
+/// Error message: "The XPath expression could not be parsed."
+///
+pub(crate) const MSG_INVALID_XPATH: &str = "The XPath expression could not be parsed.";
+///
+/// Error message: "The XPath function is unknown, or called with the wrong number of arguments."
+///
+pub(crate) const MSG_XPATH_FUNCTION: &str =
+    "The XPath function is unknown, or called with the wrong number of arguments.";
+///
+/// Error message: "The XPath expression requires a node-set where another type was provided."
+///
+pub(crate) const MSG_XPATH_NOT_NODE_SET: &str =
+    "The XPath expression requires a node-set where another type was provided.";
+///
+/// Error message: "The XPath variable is not bound."
+///
+pub(crate) const MSG_XPATH_VARIABLE: &str = "The XPath variable is not bound.";
+///
+/// Error message: "The selector could not be parsed."
+///
+pub(crate) const MSG_INVALID_SELECTOR: &str = "The selector could not be parsed.";
+///
+/// Error message: "A document with this system identifier is already in the workspace."
+///
+pub(crate) const MSG_DUPLICATE_DOCUMENT: &str =
+    "A document with this system identifier is already in the workspace.";
+///
+/// Error message: "No document with this system identifier is in the workspace."
+///
+pub(crate) const MSG_DOCUMENT_NOT_FOUND: &str =
+    "No document with this system identifier is in the workspace.";
+///
+/// Error message: "The resource could not be found, or could not be read."
+///
+pub(crate) const MSG_RESOURCE_NOT_FOUND: &str =
+    "The resource could not be found, or could not be read.";
+///
+/// Error message: "Parsing a resource requires the `quick_parser` feature."
+///
+#[allow(dead_code)]
+pub(crate) const MSG_PARSER_UNAVAILABLE: &str =
+    "Parsing a resource requires the `quick_parser` feature.";
+///
+/// Error message: "The node iterator has been detached."
+///
+pub(crate) const MSG_ITERATOR_DETACHED: &str = "The node iterator has been detached.";
+///
+/// Error message: "The content model of an element type declaration is not valid."
+///
+pub(crate) const MSG_INVALID_CONTENT_MODEL: &str =
+    "The content model of an element type declaration is not valid.";
+///
+/// Error message: "The configuration parameter is not recognized by this implementation."
+///
+pub(crate) const MSG_UNKNOWN_PARAMETER: &str =
+    "The configuration parameter is not recognized by this implementation.";
+///
+/// Error message: "The attribute is not an attribute of this element."
+///
+pub(crate) const MSG_ATTRIBUTE_NOT_FOUND: &str =
+    "The attribute is not an attribute of this element.";
+///
+/// Error message: "The content is not allowed at this position in the document."
+///
+pub(crate) const MSG_MISPLACED_CONTENT: &str =
+    "The content is not allowed at this position in the document.";
+///
+/// Error message: "The document has no document element."
+///
+pub(crate) const MSG_NO_DOCUMENT_ELEMENT: &str = "The document has no document element.";
+
+///
+/// Error message: "The replacement text of an entity could not be parsed."
+///
+pub(crate) const MSG_INVALID_REPLACEMENT_TEXT: &str =
+    "The replacement text of an entity could not be parsed.";
+
+///
+/// Error message: "The notation of an unparsed entity has not been declared."
+///
+pub(crate) const MSG_UNDECLARED_NOTATION: &str =
+    "The notation of an unparsed entity has not been declared.";
+
+///
+/// Error message: "This node is read-only and may not be modified."
+///
+pub(crate) const MSG_READ_ONLY_NODE: &str = "This node is read-only and may not be modified.";
+///
+/// Error message: "The owner document of this node has been dropped."
+///
+pub(crate) const MSG_DOCUMENT_DROPPED: &str = "The owner document of this node has been dropped.";
+///
+/// Error message: "Either `offset` or `count` does not fall between two characters."
+///
+pub(crate) const MSG_SPLIT_CHARACTER: &str =
+    "Either `offset` or `count` does not fall between two characters.";
+///
+/// Error message: "A CDATA section contains the end delimiter ']]>'."
+///
+pub(crate) const MSG_CDATA_END: &str = "A CDATA section contains the end delimiter ']]>'.";
+///
+/// Error message: "The value is not a valid XML encoding name."
+///
+pub(crate) const MSG_INVALID_ENCODING: &str = "The value is not a valid XML encoding name.";
+
+///
+/// Error message: "The encoding cannot be used for output."
+///
+#[cfg(feature = "encoding")]
+pub(crate) const MSG_UNSUPPORTED_ENCODING: &str = "The encoding cannot be used for output.";
+
+///
+/// Error message: "A character in markup cannot be represented in the output encoding."
+///
+#[cfg(feature = "encoding")]
+pub(crate) const MSG_UNENCODABLE_CHARACTER: &str =
+    "A character in markup cannot be represented in the output encoding.";
+
+///
+/// Error message: "The xml-rs event reader or writer returned an error."
+///
+#[cfg(feature = "xml_rs")]
+pub(crate) const MSG_XML_RS_FAILED: &str = "The xml-rs event reader or writer returned an error.";
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
@@ -136,8 +373,8 @@ impl Display for Error {
 
 impl std::error::Error for Error {}
 
-impl<T> Into<Result<T>> for Error {
-    fn into(self) -> Result<T> {
-        Err(self)
+impl<T> From<Error> for Result<T> {
+    fn from(val: Error) -> Self {
+        Err(val)
     }
 }
@@ -3,6 +3,7 @@ use crate::shared::syntax::*;
 use crate::shared::text::is_xml_name;
 use std::convert::TryFrom;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
 use std::result::Result as StdResult;
 use std::str::{from_utf8, FromStr};
 
@@ -55,17 +56,106 @@ use std::str::{from_utf8, FromStr};
 /// >   "http://www.w3.org/2000/xmlns/", or if this node is an attribute and the qualifiedName of
 /// >   this node is "xmlns".
 ///
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// # Equality
+///
+/// Two names with a namespace URI are equal, and hash equally, if they have the same namespace URI
+/// and local name, regardless of prefix; that is they have the same _expanded name_ as defined by
+/// [Namespaces in XML 1.0 §2.1](https://www.w3.org/TR/xml-names/#dt-expname). So `a:x` and `b:x`,
+/// with both prefixes bound to the same namespace, name the same attribute. Names without a
+/// namespace URI, as created by DOM Level 1 methods, are equal only if they have the same prefix
+/// and local name, and are never equal to a name with a namespace URI. Compare the `prefix` of
+/// each name, or their string forms, where the qualified name itself matters.
+///
+#[derive(Clone, Debug)]
 pub struct Name {
     pub(crate) namespace_uri: Option<String>,
     pub(crate) prefix: Option<String>,
     pub(crate) local_name: String,
 }
 
+///
+/// The productions from [Namespaces in XML 1.0](https://www.w3.org/TR/xml-names/#ns-qualnames)
+/// that are checked by the `Name` constructors and validators; the production that failed is
+/// included in the log message for any error.
+///
+/// ```ebnf
+/// QName          ::= PrefixedName | UnprefixedName
+/// PrefixedName   ::= Prefix ':' LocalPart
+/// UnprefixedName ::= LocalPart
+/// Prefix         ::= NCName
+/// LocalPart      ::= NCName
+/// NCName         ::= Name - (Char* ':' Char*)
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameProduction {
+    /// A name with no colon, production `[4] NCName`.
+    NCName,
+    /// A qualified name, production `[7] QName`.
+    QName,
+    /// The prefix of a qualified name, production `[10] Prefix`.
+    Prefix,
+    /// The local part of a qualified name, production `[11] LocalPart`.
+    LocalPart,
+}
+
+///
+/// Constructs a [`Name`](struct.Name.html) from its separate parts, each of which is checked when
+/// the name is built; as the parts are borrowed, and the setters are `const`, a builder may be
+/// held as a constant.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::{Name, NameBuilder};
+///
+/// const XSI_NIL: NameBuilder<'static> = Name::builder("nil")
+///     .prefix("xsi")
+///     .namespace_uri("http://www.w3.org/2001/XMLSchema-instance");
+///
+/// let name = XSI_NIL.build().unwrap();
+/// assert_eq!(name.to_string(), "xsi:nil");
+/// assert_eq!(
+///     name,
+///     Name::builder("nil")
+///         .prefix("i")
+///         .namespace_uri("http://www.w3.org/2001/XMLSchema-instance")
+///         .build()
+///         .unwrap()
+/// );
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NameBuilder<'a> {
+    local_name: &'a str,
+    prefix: Option<&'a str>,
+    namespace_uri: Option<&'a str>,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.local_name == other.local_name
+            && self.namespace_uri == other.namespace_uri
+            && (self.namespace_uri.is_some() || self.prefix == other.prefix)
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.namespace_uri.hash(state);
+        if self.namespace_uri.is_none() {
+            self.prefix.hash(state);
+        }
+        self.local_name.hash(state);
+    }
+}
+
 impl Display for Name {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match &self.prefix {
@@ -77,6 +167,23 @@ impl Display for Name {
 
 // ------------------------------------------------------------------------------------------------
 
+impl Display for NameProduction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                NameProduction::NCName => "NCName",
+                NameProduction::QName => "QName",
+                NameProduction::Prefix => "Prefix",
+                NameProduction::LocalPart => "LocalPart",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
 impl FromStr for Name {
     type Err = Error;
 
@@ -84,18 +191,16 @@ impl FromStr for Name {
         if value.is_empty() {
             Err(Error::Syntax)
         } else {
-            let parts = value
-                .split(XML_NS_SEPARATOR)
-                .map(|s| s.to_string())
-                .collect::<Vec<String>>();
-            match parts.len() {
-                1 => Name::new(Name::check_part(parts.get(0).unwrap())?, None, None),
-                2 => Name::new(
-                    Name::check_part(parts.get(1).unwrap())?,
-                    Some(Name::check_part(parts.get(0).unwrap())?),
+            match value.split_once(XML_NS_SEPARATOR) {
+                None => Name::new(Name::check_part(value)?, None, None),
+                Some((_, local_name)) if local_name.contains(XML_NS_SEPARATOR) => {
+                    Err(Error::Syntax)
+                }
+                Some((prefix, local_name)) => Name::new(
+                    Name::check_part(local_name)?,
+                    Some(Name::check_part(prefix)?),
                     None,
                 ),
-                _ => Err(Error::Syntax),
             }
         }
     }
@@ -120,6 +225,18 @@ impl TryFrom<&[u8]> for Name {
 // ------------------------------------------------------------------------------------------------
 
 impl Name {
+    ///
+    /// Returns a [`NameBuilder`](struct.NameBuilder.html) for a name with the local name
+    /// `local_name`, to which a prefix and namespace URI may be added.
+    ///
+    pub const fn builder(local_name: &str) -> NameBuilder<'_> {
+        NameBuilder {
+            local_name,
+            prefix: None,
+            namespace_uri: None,
+        }
+    }
+
     ///
     /// Construct a new `Name` from the specified namespace URI and qualified name.
     ///
@@ -168,10 +285,112 @@ impl Name {
         })
     }
 
+    ///
+    /// Construct a new unprefixed `Name`, with no namespace URI, from a value that must match
+    /// the `NCName` production; unlike `Name::from_str` a value containing a colon is not
+    /// treated as a prefixed name.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `local_name` is empty.
+    /// * `NAMESPACE_ERR`: Raised if `local_name` contains a colon.
+    /// * `INVALID_CHARACTER_ERR`: Raised if `local_name` contains a character that is not
+    ///   allowed in a name.
+    ///
+    pub fn ncname(local_name: &str) -> Result<Self> {
+        Name::new(
+            Name::check_ncname(local_name, NameProduction::NCName)?,
+            None,
+            None,
+        )
+    }
+
+    ///
+    /// Construct a new `Name`, with no namespace URI, from a separate prefix and local name;
+    /// each of which must match the `NCName` production.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `prefix` or `local_name` is empty.
+    /// * `NAMESPACE_ERR`: Raised if `prefix` or `local_name` contains a colon.
+    /// * `INVALID_CHARACTER_ERR`: Raised if `prefix` or `local_name` contains a character that
+    ///   is not allowed in a name.
+    ///
+    pub fn qname(prefix: Option<&str>, local_name: &str) -> Result<Self> {
+        let prefix = match prefix {
+            Some(prefix) => Some(Name::check_ncname(prefix, NameProduction::Prefix)?),
+            None => None,
+        };
+        Name::new(
+            Name::check_ncname(local_name, NameProduction::LocalPart)?,
+            prefix,
+            None,
+        )
+    }
+
+    ///
+    /// Check that `value` matches `production`, returning the same error as the corresponding
+    /// constructor if it does not. `NameProduction::QName` accepts a value with at most one colon
+    /// separating a `Prefix` and a `LocalPart`, all other productions are a single `NCName`.
+    ///
+    pub fn validate(value: &str, production: NameProduction) -> Result<()> {
+        match production {
+            NameProduction::QName => match value.split_once(XML_NS_SEPARATOR) {
+                None => Name::check_ncname(value, NameProduction::LocalPart),
+                Some((prefix, local_name)) => Name::check_ncname(prefix, NameProduction::Prefix)
+                    .and_then(|_| Name::check_ncname(local_name, NameProduction::LocalPart)),
+            },
+            production => Name::check_ncname(value, production),
+        }
+        .map(|_| ())
+    }
+
+    ///
+    /// Construct a new `Name`, with no namespace URI, from a known-valid constant such as
+    /// `"xsi:nil"`, without the cost of validating each character of the name.
+    ///
+    /// **Note:** the name is only validated in debug builds, where an invalid name will panic;
+    /// use `Name::from_str` for any name that is not a compile-time constant.
+    ///
+    pub fn from_static(qualified_name: &'static str) -> Self {
+        debug_assert!(
+            Name::from_str(qualified_name).is_ok(),
+            "invalid static name '{}'",
+            qualified_name
+        );
+        match qualified_name.split_once(XML_NS_SEPARATOR) {
+            None => Self {
+                namespace_uri: None,
+                prefix: None,
+                local_name: qualified_name.to_string(),
+            },
+            Some((prefix, local_name)) => Self {
+                namespace_uri: None,
+                prefix: Some(prefix.to_string()),
+                local_name: local_name.to_string(),
+            },
+        }
+    }
+
+    fn check_ncname(part: &str, production: NameProduction) -> Result<String> {
+        if part.is_empty() {
+            warn!("{} [{}]", MSG_EMPTY_NAME_PART, production);
+            Err(Error::Syntax)
+        } else if part.contains(XML_NS_SEPARATOR) {
+            warn!("{} [{}]: '{}'", MSG_NAME_PART_COLON, production, part);
+            Err(Error::Namespace)
+        } else if is_name_part(part) {
+            Ok(part.to_string())
+        } else {
+            warn!("{} [{}]: '{}'", MSG_NAME_PART_CHARACTER, production, part);
+            Err(Error::InvalidCharacter)
+        }
+    }
+
     fn check_part(part: &str) -> Result<String> {
         if part.is_empty() {
             Err(Error::Syntax)
-        } else if is_xml_name(part) {
+        } else if is_name_part(part) {
             Ok(part.to_string())
         } else {
             Err(Error::InvalidCharacter)
@@ -258,6 +477,18 @@ impl Name {
         }
     }
 
+    ///
+    /// Return the reserved name for unparsed markup nodes, this is an extension and not a name
+    /// defined by the DOM specification.
+    ///
+    pub fn for_unparsed_markup() -> Self {
+        Self {
+            namespace_uri: None,
+            prefix: None,
+            local_name: XML_NAME_UNPARSED_MARKUP.to_string(),
+        }
+    }
+
     ///
     /// Return the reserved name for `DocumentType` `public_id` attribute
     ///
@@ -296,11 +527,9 @@ impl Name {
     /// Does this appear to be an `xmlns` attribute.
     ///
     pub fn is_namespace_attribute(&self) -> bool {
-        let xmlns_ns = Some(XMLNS_NS_URI.to_string());
-        let xmlns_attribute = XMLNS_NS_ATTRIBUTE.to_string();
-        self.namespace_uri == xmlns_ns
-            && ((self.local_name == xmlns_attribute && self.prefix == None)
-                || self.prefix == Some(xmlns_attribute))
+        self.namespace_uri.as_deref() == Some(XMLNS_NS_URI)
+            && ((self.local_name == XMLNS_NS_ATTRIBUTE && self.prefix.is_none())
+                || self.prefix.as_deref() == Some(XMLNS_NS_ATTRIBUTE))
     }
 
     ///
@@ -319,19 +548,17 @@ impl Name {
     /// Does this appear to be an `id` attribute.
     ///
     pub fn is_id_attribute(&self, lax: bool) -> bool {
-        let id_attribute = XML_NS_ATTR_ID.to_string();
         if lax {
             //
             // any attribute with the local_name 'id'
             //
-            self.local_name == id_attribute
+            self.local_name == XML_NS_ATTR_ID
         } else {
-            let xml_ns = XML_NS_URI.to_string();
-            let xml_prefix = XML_NS_ATTRIBUTE.to_string();
             //
             // has to be 'xml:id', either by the prefix 'xml' or using the correct namespace
-            self.local_name == id_attribute
-                && (self.namespace_uri == Some(xml_ns) || self.prefix == Some(xml_prefix))
+            self.local_name == XML_NS_ATTR_ID
+                && (self.namespace_uri.as_deref() == Some(XML_NS_URI)
+                    || self.prefix.as_deref() == Some(XML_NS_ATTRIBUTE))
         }
     }
 
@@ -346,6 +573,21 @@ impl Name {
         }
     }
 
+    ///
+    /// Returns `true` if the qualified form of this name, `prefix:local_name` or `local_name`,
+    /// is equal to `qualified_name`. This is equivalent to comparing `qualified_name` with
+    /// `self.to_string()` without constructing a new string.
+    ///
+    pub fn matches_qualified_name(&self, qualified_name: &str) -> bool {
+        match (&self.prefix, qualified_name.split_once(XML_NS_SEPARATOR)) {
+            (Some(prefix), Some((other_prefix, other_local_name))) => {
+                prefix == other_prefix && self.local_name == other_local_name
+            }
+            (None, None) => self.local_name == qualified_name,
+            _ => false,
+        }
+    }
+
     ///
     /// Return this name's namespace URI.
     ///
@@ -368,14 +610,122 @@ impl Name {
     }
 
     ///
-    /// Set this name's prefix.
+    /// Set this name's prefix, which must match the `Prefix` production.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if `new_prefix` is empty.
+    /// * `NAMESPACE_ERR`: Raised if `new_prefix` contains a colon.
+    /// * `INVALID_CHARACTER_ERR`: Raised if `new_prefix` contains a character that is not allowed
+    ///   in a name.
     ///
     pub fn set_prefix(&mut self, new_prefix: Option<&str>) -> Result<()> {
-        self.prefix = new_prefix.map(String::from);
+        self.prefix = match new_prefix {
+            Some(new_prefix) => Some(Name::check_ncname(new_prefix, NameProduction::Prefix)?),
+            None => None,
+        };
         Ok(())
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> NameBuilder<'a> {
+    ///
+    /// Set the prefix of the name.
+    ///
+    pub const fn prefix(self, prefix: &'a str) -> Self {
+        Self {
+            prefix: Some(prefix),
+            ..self
+        }
+    }
+
+    ///
+    /// Set the namespace URI of the name.
+    ///
+    pub const fn namespace_uri(self, namespace_uri: &'a str) -> Self {
+        Self {
+            namespace_uri: Some(namespace_uri),
+            ..self
+        }
+    }
+
+    ///
+    /// Construct the name, checking each part as `Name::qname` and `Name::new_ns` do.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if the local name, prefix, or namespace URI is empty.
+    /// * `NAMESPACE_ERR`: Raised if the local name or prefix contains a colon, or if the prefix is
+    ///   `xml` or `xmlns`, or the local name is `xmlns`, with a namespace URI other than the one
+    ///   reserved for it.
+    /// * `INVALID_CHARACTER_ERR`: Raised if the local name or prefix contains a character that is
+    ///   not allowed in a name.
+    ///
+    pub fn build(&self) -> Result<Name> {
+        let mut name = Name::qname(self.prefix, self.local_name)?;
+        if let Some(namespace_uri) = self.namespace_uri {
+            name.namespace_uri = Some(Name::check_namespace_uri(
+                namespace_uri,
+                &name.prefix,
+                &name.local_name,
+            )?);
+        }
+        Ok(name)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const NAME_START: u8 = 0b01;
+const NAME_CHAR: u8 = 0b10;
+
+///
+/// The `NameStartChar` and `NameChar` classes for each ASCII character, so that the common case
+/// of an all-ASCII name is checked with a table look-up per byte.
+///
+const ASCII_NAME_CLASSES: [u8; 128] = ascii_name_classes();
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const fn ascii_name_classes() -> [u8; 128] {
+    let mut classes = [0u8; 128];
+    let mut c = 0;
+    while c < 128 {
+        let b = c as u8;
+        if b == b':' || b == b'_' || b.is_ascii_alphabetic() {
+            classes[c] = NAME_START | NAME_CHAR;
+        } else if b == b'-' || b == b'.' || b.is_ascii_digit() {
+            classes[c] = NAME_CHAR;
+        }
+        c += 1;
+    }
+    classes
+}
+
+///
+/// Equivalent to `is_xml_name`, with a fast path for ASCII names.
+///
+fn is_name_part(part: &str) -> bool {
+    if part.is_ascii() {
+        let mut bytes = part.bytes();
+        match bytes.next() {
+            Some(first) => {
+                ASCII_NAME_CLASSES[first as usize] & NAME_START != 0
+                    && bytes.all(|b| ASCII_NAME_CLASSES[b as usize] & NAME_CHAR != 0)
+            }
+            None => false,
+        }
+    } else {
+        is_xml_name(part)
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Unit Tests
 // ------------------------------------------------------------------------------------------------
@@ -383,8 +733,9 @@ impl Name {
 #[cfg(test)]
 mod tests {
     use crate::shared::error::Error;
-    use crate::shared::name::Name;
+    use crate::shared::name::{is_name_part, Name, NameProduction};
     use crate::shared::syntax::{XMLNS_NS_URI, XML_NS_URI};
+    use crate::shared::text::is_xml_name;
     use std::str::FromStr;
 
     #[test]
@@ -411,6 +762,149 @@ mod tests {
         assert!(name.namespace_uri().is_none());
     }
 
+    #[test]
+    fn test_ascii_fast_path() {
+        for c in (0u8..128).map(char::from) {
+            let name = c.to_string();
+            assert_eq!(is_name_part(&name), is_xml_name(&name), "{:?}", c);
+            let name = format!("a{}", c);
+            assert_eq!(is_name_part(&name), is_xml_name(&name), "{:?}", c);
+        }
+        assert!(is_name_part("\u{E9}t\u{E9}"));
+        assert!(!is_name_part("a\u{D7}"));
+    }
+
+    #[test]
+    fn test_from_static() {
+        let name = Name::from_static("xsi:nil");
+        assert_eq!(name, Name::from_str("xsi:nil").unwrap());
+        let name = Name::from_static("nil");
+        assert_eq!(name, Name::from_str("nil").unwrap());
+    }
+
+    #[test]
+    fn test_parse_non_ascii() {
+        let name = Name::from_str("\u{E9}:\u{E9}t\u{E9}").unwrap();
+        assert_eq!(name.prefix(), &Some("\u{E9}".to_string()));
+        assert_eq!(name.local_name, "\u{E9}t\u{E9}".to_string());
+        assert_eq!(Name::from_str("a:b:c"), Err(Error::Syntax));
+        assert_eq!(Name::from_str("a:"), Err(Error::Syntax));
+        assert_eq!(Name::from_str(":a"), Err(Error::Syntax));
+        assert_eq!(Name::from_str("1a"), Err(Error::InvalidCharacter));
+    }
+
+    #[test]
+    fn test_ncname() {
+        let name = Name::ncname("hello").unwrap();
+        assert_eq!(name, Name::from_str("hello").unwrap());
+        assert_eq!(Name::ncname(""), Err(Error::Syntax));
+        assert_eq!(Name::ncname("x:hello"), Err(Error::Namespace));
+        assert_eq!(Name::ncname("1hello"), Err(Error::InvalidCharacter));
+    }
+
+    #[test]
+    fn test_qname() {
+        let name = Name::qname(Some("x"), "hello").unwrap();
+        assert_eq!(name, Name::from_str("x:hello").unwrap());
+        let name = Name::qname(None, "hello").unwrap();
+        assert_eq!(name, Name::from_str("hello").unwrap());
+        assert_eq!(Name::qname(Some(""), "hello"), Err(Error::Syntax));
+        assert_eq!(Name::qname(Some("x"), "a:b"), Err(Error::Namespace));
+        assert_eq!(Name::qname(Some("x:y"), "b"), Err(Error::Namespace));
+        assert_eq!(Name::qname(Some("x y"), "b"), Err(Error::InvalidCharacter));
+
+        let mut name = Name::ncname("hello").unwrap();
+        assert_eq!(name.set_prefix(Some("x:y")), Err(Error::Namespace));
+        name.set_prefix(Some("x")).unwrap();
+        assert_eq!(name.to_string(), "x:hello");
+        name.set_prefix(None).unwrap();
+        assert_eq!(name.to_string(), "hello");
+    }
+
+    #[test]
+    fn test_builder() {
+        let name = Name::builder("hello").build().unwrap();
+        assert_eq!(name, Name::from_str("hello").unwrap());
+        let name = Name::builder("hello")
+            .prefix("x")
+            .namespace_uri("urn:x")
+            .build()
+            .unwrap();
+        assert_eq!(name, Name::new_ns("urn:x", "x:hello").unwrap());
+        assert_eq!(name.prefix(), &Some("x".to_string()));
+        assert_eq!(Name::builder("").build(), Err(Error::Syntax));
+        assert_eq!(Name::builder("a:b").build(), Err(Error::Namespace));
+        assert_eq!(
+            Name::builder("hello").namespace_uri("").build(),
+            Err(Error::Syntax)
+        );
+        assert_eq!(
+            Name::builder("hello")
+                .prefix("xml")
+                .namespace_uri("urn:x")
+                .build(),
+            Err(Error::Namespace)
+        );
+    }
+
+    #[test]
+    fn test_expanded_name_equality() {
+        use std::collections::HashMap;
+
+        let a = Name::new_ns("urn:x", "a:hello").unwrap();
+        let b = Name::new_ns("urn:x", "b:hello").unwrap();
+        let default = Name::new_ns("urn:x", "hello").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a, default);
+        assert_ne!(a, Name::new_ns("urn:y", "a:hello").unwrap());
+        assert_ne!(a, Name::from_str("a:hello").unwrap());
+        assert_ne!(
+            Name::from_str("a:hello").unwrap(),
+            Name::from_str("b:hello").unwrap()
+        );
+
+        let mut map: HashMap<Name, u8> = HashMap::new();
+        let _safe_to_ignore = map.insert(a, 1);
+        assert_eq!(map.get(&b), Some(&1));
+        assert_eq!(map.get(&Name::from_str("b:hello").unwrap()), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Name::validate("x:hello", NameProduction::QName).is_ok());
+        assert!(Name::validate("hello", NameProduction::QName).is_ok());
+        assert_eq!(
+            Name::validate("x:a:b", NameProduction::QName),
+            Err(Error::Namespace)
+        );
+        assert_eq!(
+            Name::validate(":a", NameProduction::QName),
+            Err(Error::Syntax)
+        );
+        assert_eq!(
+            Name::validate("x:hello", NameProduction::NCName),
+            Err(Error::Namespace)
+        );
+        assert_eq!(
+            Name::validate("-x", NameProduction::Prefix),
+            Err(Error::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn test_matches_qualified_name() {
+        let name = Name::from_str("x:hello").unwrap();
+        assert!(name.matches_qualified_name("x:hello"));
+        assert!(!name.matches_qualified_name("hello"));
+        assert!(!name.matches_qualified_name("y:hello"));
+        assert!(!name.matches_qualified_name("x:hello:world"));
+
+        let name = Name::from_str("hello").unwrap();
+        assert!(name.matches_qualified_name("hello"));
+        assert!(!name.matches_qualified_name("x:hello"));
+        assert!(!name.matches_qualified_name(""));
+    }
+
     #[test]
     fn test_parse_namespaced() {
         let name = Name::new_ns("http://example.org/schema/x", "x:hello").unwrap();
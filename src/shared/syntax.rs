@@ -32,6 +32,15 @@ pub(crate) const XML_ELEMENT_END_END: &str = ">";
 pub(crate) const XML_ENTITY_START: &str = "<!ENTITY";
 pub(crate) const XML_ENTITY_END: &str = ">";
 pub(crate) const XML_ENTITY_NOTATION: &str = "NDATA";
+pub(crate) const XML_ENTITY_PARAMETER: &str = "%";
+
+pub(crate) const XML_PE_REFERENCE_START: &str = "%";
+pub(crate) const XML_PE_REFERENCE_END: &str = ";";
+
+pub(crate) const XML_CONDITIONAL_START: &str = "<![";
+pub(crate) const XML_CONDITIONAL_END: &str = "]]>";
+pub(crate) const XML_CONDITIONAL_INCLUDE: &str = "INCLUDE";
+pub(crate) const XML_CONDITIONAL_IGNORE: &str = "IGNORE";
 
 pub(crate) const XML_ENTITYREF_START: &str = "&";
 pub(crate) const XML_NUMBERED_ENTITYREF_START: &str = "&#";
@@ -41,6 +50,21 @@ pub(crate) const XML_ENTITYREF_END: &str = ";";
 pub(crate) const XML_NOTATION_START: &str = "<!NOTATION";
 pub(crate) const XML_NOTATION_END: &str = ">";
 
+pub(crate) const XML_ELEMENT_DECL_START: &str = "<!ELEMENT";
+pub(crate) const XML_ELEMENT_DECL_END: &str = ">";
+pub(crate) const XML_ELEMENT_DECL_EMPTY: &str = "EMPTY";
+pub(crate) const XML_ELEMENT_DECL_ANY: &str = "ANY";
+pub(crate) const XML_ELEMENT_DECL_PCDATA: &str = "#PCDATA";
+
+pub(crate) const XML_ATTLIST_START: &str = "<!ATTLIST";
+pub(crate) const XML_ATTLIST_END: &str = ">";
+pub(crate) const XML_ATTLIST_NOTATION: &str = "NOTATION";
+pub(crate) const XML_ATTLIST_FIXED: &str = "#FIXED";
+pub(crate) const XML_ATTLIST_IMPLIED: &str = "#IMPLIED";
+pub(crate) const XML_ATTLIST_ID: &str = "ID";
+pub(crate) const XML_ATTLIST_IDREF: &str = "IDREF";
+pub(crate) const XML_ATTLIST_IDREFS: &str = "IDREFS";
+
 pub(crate) const XML_NS_SEPARATOR: &str = ":";
 
 pub(crate) const XML_EMPTY: &str = "";
@@ -83,6 +107,16 @@ pub(crate) const XML_NS_ATTR_SPACE_PRESERVE: &str = "preserve";
 pub(crate) const XMLNS_NS_URI: &str = "http://www.w3.org/2000/xmlns/";
 pub(crate) const XMLNS_NS_ATTRIBUTE: &str = "xmlns";
 
+// ------------------------------------------------------------------------------------------------
+// Link Support
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const XLINK_NS_URI: &str = "http://www.w3.org/1999/xlink";
+pub(crate) const XLINK_ATTR_HREF: &str = "href";
+
+pub(crate) const LINK_ATTR_HREF: &str = "href";
+pub(crate) const LINK_ATTR_SRC: &str = "src";
+
 // ------------------------------------------------------------------------------------------------
 // DOM Node Names
 // ------------------------------------------------------------------------------------------------
@@ -92,6 +126,7 @@ pub(crate) const XML_NAME_COMMENT: &str = "#comment";
 pub(crate) const XML_NAME_DOCUMENT: &str = "#document";
 pub(crate) const XML_NAME_DOCUMENT_FRAGMENT: &str = "#document-fragment";
 pub(crate) const XML_NAME_TEXT: &str = "#text";
+pub(crate) const XML_NAME_UNPARSED_MARKUP: &str = "#unparsed-markup";
 
 // ------------------------------------------------------------------------------------------------
 // DOM Features
@@ -99,6 +134,7 @@ pub(crate) const XML_NAME_TEXT: &str = "#text";
 
 pub(crate) const XML_FEATURE_CORE: &str = "Core"; // DOM Level-2 "Fundamental Interfaces"
 pub(crate) const XML_FEATURE_XML: &str = "XML"; // DOM Level-2 "Extended Interfaces"
+pub(crate) const XML_FEATURE_TRAVERSAL: &str = "Traversal"; // DOM Level-2 Traversal
 
 pub(crate) const XML_FEATURE_V1: &str = "1.0";
 pub(crate) const XML_FEATURE_V2: &str = "2.0";
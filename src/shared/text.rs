@@ -1,3 +1,10 @@
+#![allow(
+    clippy::derivable_impls,
+    clippy::from_str_radix_10,
+    clippy::manual_range_contains,
+    clippy::unnecessary_to_owned
+)]
+
 use crate::shared::syntax::*;
 use std::convert::TryFrom;
 use std::fmt::{Display, Formatter, Result as FmtResult};
@@ -7,6 +14,7 @@ use std::str::FromStr;
 //  Public Types
 // ------------------------------------------------------------------------------------------------
 
+#[allow(dead_code)]
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum SpaceHandling {
     Default,
@@ -60,11 +68,14 @@ pub(crate) trait EntityResolver {
 /// It is an error if an attribute value contains a reference to an entity for which no declaration
 /// has been read.
 ///
+/// Returns `None` if `value` contains a reference to an entity `resolver` does not know, or a
+/// character reference to a code point that is not a character.
+///
 pub(crate) fn normalize_attribute_value(
     value: &str,
     resolver: &dyn EntityResolver,
     is_cdata: bool,
-) -> String {
+) -> Option<String> {
     let step_1 = normalize_end_of_lines(value);
     let step_3 = if step_1.is_empty() {
         step_1
@@ -80,18 +91,17 @@ pub(crate) fn normalize_attribute_value(
                 //
                 // TODO: this does not yet deal with entity references.
                 //
-                let replacement = match resolver.resolve(a_match.as_str()) {
-                    None => panic!("unknown entity reference {}", a_match.as_str()),
-                    Some(replacement) => {
-                        normalize_attribute_value(&replacement, resolver, is_cdata)
-                    }
-                };
+                let replacement = normalize_attribute_value(
+                    &resolver.resolve(a_match.as_str())?,
+                    resolver,
+                    is_cdata,
+                )?;
                 (a_match.start(), a_match.end(), replacement)
             } else if let Some(a_match) = capture.name("char") {
-                let replacement = char_from_entity(a_match.as_str());
+                let replacement = char_from_entity(a_match.as_str())?;
                 (a_match.start(), a_match.end(), replacement)
             } else if let Some(a_match) = capture.name("char_hex") {
-                let replacement = char_from_entity(a_match.as_str());
+                let replacement = char_from_entity(a_match.as_str())?;
                 (a_match.start(), a_match.end(), replacement)
             } else if let Some(a_match) = capture.name("ws") {
                 (a_match.start(), a_match.end(), "\u{20}".to_string())
@@ -110,9 +120,9 @@ pub(crate) fn normalize_attribute_value(
         step_2
     };
     if is_cdata {
-        step_3
+        Some(step_3)
     } else {
-        step_3.trim_matches(' ').to_string()
+        Some(step_3.trim_matches(' ').to_string())
     }
 }
 
@@ -146,6 +156,44 @@ pub(crate) fn normalize_end_of_lines(value: &str) -> String {
     }
 }
 
+///
+/// Expand the character and entity references in `value`, the replacement text of an entity or
+/// other markup. Character references are replaced by the character they reference, references to
+/// the predefined entities (`&amp;`, `&lt;`, `&gt;`, `&apos;`, and `&quot;`) by their character,
+/// and any other entity reference by the value returned from `resolver`, which is expected to have
+/// expanded any references it contains. References that cannot be expanded, a character reference
+/// to a code point that is not a character or an entity reference the resolver does not know, are
+/// left as written.
+///
+pub(crate) fn expand_references(value: &str, resolver: &dyn EntityResolver) -> String {
+    if !value.contains(XML_ENTITYREF_START) {
+        return value.to_string();
+    }
+    let find = regex::Regex::new(r"&#\d+;|&#x[0-9a-fA-F]+;|&[\pL_:][\pL\.\d_:\-]*;").unwrap();
+    let mut result = String::with_capacity(value.len());
+    let mut last_end = 0;
+    for a_match in find.find_iter(value) {
+        let reference = a_match.as_str();
+        let replacement = if reference.starts_with(XML_NUMBERED_ENTITYREF_START) {
+            char_from_entity(reference)
+        } else {
+            match &reference[1..reference.len() - 1] {
+                "amp" => Some(XML_ESC_AMP_CHAR.to_string()),
+                "apos" => Some(XML_ESC_APOS_CHAR.to_string()),
+                "gt" => Some(XML_ESC_GT_CHAR.to_string()),
+                "lt" => Some(XML_ESC_LT_CHAR.to_string()),
+                "quot" => Some(XML_ESC_QUOT_CHAR.to_string()),
+                _ => resolver.resolve(reference),
+            }
+        };
+        result.push_str(&value[last_end..a_match.start()]);
+        result.push_str(replacement.as_deref().unwrap_or(reference));
+        last_end = a_match.end();
+    }
+    result.push_str(&value[last_end..]);
+    result
+}
+
 ///
 /// Escape character data according to XML 1.1
 /// [§2.4 Character Data and Markup](https://www.w3.org/TR/xml11/#dt-chardata). This is the
@@ -209,18 +257,18 @@ pub(crate) fn to_entity_hex(c: char) -> String {
     )
 }
 
-fn char_from_entity(entity: &str) -> String {
+fn char_from_entity(entity: &str) -> Option<String> {
     assert!(entity.starts_with("&#"));
     assert!(entity.ends_with(';'));
     let code_point = if &entity[2..3] == "x" {
         let code_point = &entity[3..entity.len() - 1];
-        u32::from_str_radix(code_point, 16).unwrap()
+        u32::from_str_radix(code_point, 16).ok()?
     } else {
         let code_point = &entity[2..entity.len() - 1];
-        u32::from_str_radix(code_point, 10).unwrap()
+        u32::from_str_radix(code_point, 10).ok()?
     };
-    let character = char::try_from(code_point).unwrap();
-    character.to_string()
+    let character = char::try_from(code_point).ok()?;
+    Some(character.to_string())
 }
 
 ///
@@ -380,7 +428,8 @@ pub(crate) fn is_xml_name_char(c: char) -> bool {
 /// ```
 ///
 pub(crate) fn is_xml_name(s: &str) -> bool {
-    !s.is_empty() && s.starts_with(is_xml_name_start_char) && s[1..].chars().all(is_xml_name_char)
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if is_xml_name_start_char(c)) && chars.all(is_xml_name_char)
 }
 
 ///
@@ -413,6 +462,15 @@ pub(crate) fn is_xml_nmtokens(s: &str) -> bool {
     !s.is_empty() && s.split(' ').all(is_xml_nmtoken)
 }
 
+///
+/// ```ebnf
+/// PI   ::=  '<?' PITarget (S (Char* - (Char* '?>' Char*)))? '?>'
+/// ```
+///
+pub(crate) fn is_processing_instruction_data(s: &str) -> bool {
+    !s.contains(XML_PI_END)
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
@@ -536,9 +594,12 @@ mod tests {
         let resolver = resolver.borrow();
         assert_eq!(
             normalize_attribute_value("  abc ", resolver, true),
-            "  abc "
+            Some("  abc ".to_string())
+        );
+        assert_eq!(
+            normalize_attribute_value("  abc ", resolver, false),
+            Some("abc".to_string())
         );
-        assert_eq!(normalize_attribute_value("  abc ", resolver, false), "abc");
     }
 
     struct TestResolver {
@@ -584,15 +645,41 @@ mod tests {
         let resolver = resolver.borrow();
         assert_eq!(
             normalize_attribute_value("10$ in &pound;s please", resolver, true),
-            "10$ in £s please"
+            Some("10$ in £s please".to_string())
         );
         assert_eq!(
             normalize_attribute_value("&yen; to &euro;", resolver, false),
-            "¥ to €"
+            Some("¥ to €".to_string())
         );
         assert_eq!(
             normalize_attribute_value("&currency;", resolver, false),
-            "$, £, €, and ¥"
+            Some("$, £, €, and ¥".to_string())
+        );
+        assert_eq!(
+            normalize_attribute_value("&unknown; &pound;", resolver, false),
+            None
+        );
+        assert_eq!(
+            normalize_attribute_value("&#xD800; &pound;", resolver, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_expand_references() {
+        let resolver = test_resolver();
+        let resolver = resolver.borrow();
+        assert_eq!(
+            expand_references("no references", resolver),
+            "no references"
+        );
+        assert_eq!(
+            expand_references("&#65;&#x42; &lt;&amp;&gt; &pound;", resolver),
+            "AB <&> £"
+        );
+        assert_eq!(
+            expand_references("&unknown; &#xD800; & alone", resolver),
+            "&unknown; &#xD800; & alone"
         );
     }
 }
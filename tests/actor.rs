@@ -0,0 +1,107 @@
+#![cfg(feature = "actor")]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::actor::DocumentActor;
+use xml_dom::level2::{get_implementation, Element, Error, Node};
+
+pub mod common;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match Pin::as_mut(&mut future).poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+fn create_actor() -> DocumentActor {
+    DocumentActor::spawn(|| get_implementation().create_document(None, Some("root"), None)).unwrap()
+}
+
+#[test]
+fn test_actor_futures() {
+    let actor = create_actor();
+    let replies: Vec<_> = (0..5)
+        .map(|id| {
+            actor.mutate(move |document_node| {
+                let document = as_document(document_node).unwrap();
+                let mut root_node = document.document_element().unwrap();
+                let mut item_node = document.create_element("item")?;
+                item_node.set_attribute("id", &id.to_string())?;
+                let _safe_to_ignore = root_node.append_child(item_node)?;
+                Ok(id * 10)
+            })
+        })
+        .collect();
+
+    let results = block_on(async {
+        let mut results = Vec::new();
+        for reply in replies {
+            results.push(reply.await.unwrap());
+        }
+        results
+    });
+    assert_eq!(results, vec![0, 10, 20, 30, 40]);
+
+    let ids = block_on(actor.query(|document_node| {
+        let document = as_document(document_node).unwrap();
+        document
+            .document_element()
+            .unwrap()
+            .child_nodes()
+            .iter()
+            .map(|item| item.get_attribute("id").unwrap())
+            .collect::<Vec<String>>()
+    }));
+    assert_eq!(ids.unwrap(), vec!["0", "1", "2", "3", "4"]);
+}
+
+#[test]
+fn test_actor_errors() {
+    let actor = create_actor();
+    assert_eq!(
+        actor
+            .mutate(|document_node| {
+                let document = as_document(document_node).unwrap();
+                document.create_element("").map(|_| ())
+            })
+            .wait(),
+        Err(Error::Syntax)
+    );
+
+    //
+    // A closure may send further messages, they are processed once it completes.
+    //
+    let inner = actor.clone();
+    let reply = actor
+        .query(move |_| inner.query(|document_node| document_node.to_string()))
+        .wait()
+        .unwrap();
+    assert_eq!(reply.wait(), Ok("<root></root>".to_string()));
+
+    let reply = actor.query(|_| -> () { panic!("query failed") });
+    assert_eq!(reply.wait(), Err(Error::InvalidState));
+    assert_eq!(
+        actor
+            .query(|document_node| document_node.to_string())
+            .wait(),
+        Err(Error::InvalidState)
+    );
+}
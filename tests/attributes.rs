@@ -1,4 +1,6 @@
 use xml_dom::level2::convert::{as_attribute, as_attribute_mut, as_document, as_element_mut};
+use xml_dom::level2::ext::dom_impl::get_implementation_ext;
+use xml_dom::level2::ext::{NodeBorrow, ProcessingOptions, TreeBuilder};
 use xml_dom::level2::*;
 pub mod common;
 
@@ -243,3 +245,209 @@ fn test_normalization_entity_not_found() {
         Some("hello£world".to_string())
     );
 }
+
+#[test]
+fn test_attribute_count_and_names() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    let element = as_element_mut(&mut element_node).unwrap();
+    assert_eq!(element.attribute_count(), 0);
+    assert!(element.attribute_names().is_empty());
+    assert!(!element.has_attributes());
+
+    assert!(element.set_attribute("test-1", "1").is_ok());
+    assert!(element.set_attribute("test-2", "2").is_ok());
+    assert_eq!(element.attribute_count(), 2);
+    assert!(element.has_attributes());
+
+    let mut names: Vec<String> = element
+        .attribute_names()
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["test-1".to_string(), "test-2".to_string()]);
+
+    let text_node = document.create_text_node("text");
+    assert_eq!(text_node.attribute_count(), 0);
+    assert!(text_node.attribute_names().is_empty());
+}
+
+#[test]
+fn test_reserved_namespaces() {
+    const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+    const XMLNS_NS: &str = "http://www.w3.org/2000/xmlns/";
+    const EX_NS: &str = "http://example.org/xmlns/example";
+
+    let declare = |document_node: &RefNode, qualified_name: &str, value: &str| {
+        let document = as_document(document_node).unwrap();
+        let mut element = document.document_element().unwrap();
+        let attribute = document
+            .create_attribute_ns(XMLNS_NS, qualified_name)
+            .unwrap();
+        as_attribute_mut(&mut attribute.clone())
+            .unwrap()
+            .set_value(value)
+            .unwrap();
+        element.set_attribute_node(attribute).map(|_| ())
+    };
+
+    let document_node = common::create_empty_rdf_document();
+    assert!(declare(&document_node, "xmlns:ex", EX_NS).is_ok());
+    assert!(declare(&document_node, "xmlns", EX_NS).is_ok());
+    assert_eq!(
+        declare(&document_node, "xmlns:xmlns", XMLNS_NS),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        declare(&document_node, "xmlns:ex", XML_NS),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        declare(&document_node, "xmlns", XMLNS_NS),
+        Err(Error::Namespace)
+    );
+    let element = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(
+        element.get_attribute_ns(XMLNS_NS, "ex"),
+        Some(EX_NS.to_string())
+    );
+
+    let mut options = ProcessingOptions::new();
+    options.set_lenient_namespaces();
+    let document_node = get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    assert!(declare(&document_node, "xmlns:xmlns", XMLNS_NS).is_ok());
+    assert!(declare(&document_node, "xmlns:ex", XML_NS).is_ok());
+}
+
+#[test]
+fn test_with_attribute() {
+    let document_node = common::create_example_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element = document.document_element().unwrap();
+    element.set_attribute("plain", "some value").unwrap();
+    element.set_attribute("escaped", "a < b").unwrap();
+    element.set_attribute("padded", " some value ").unwrap();
+    element.set_attribute("ex:lines", "one\ntwo").unwrap();
+
+    for name in &[
+        "plain",
+        "escaped",
+        "padded",
+        "ex:lines",
+        "missing",
+        "rdf:missing",
+    ] {
+        let expected = element.get_attribute(name);
+        assert_eq!(
+            element.with_attribute(name, |value| value.map(String::from)),
+            expected
+        );
+    }
+    assert_eq!(
+        element.with_attribute("plain", |value| value.map(str::len)),
+        Some(10)
+    );
+    assert!(element.with_attribute("escaped", |value| value == Some("a &#60; b")));
+
+    let text_node = document.create_text_node("some value");
+    assert!(text_node.with_attribute("plain", |value| value.is_none()));
+}
+
+#[test]
+fn test_specified_defaults() {
+    let mut builder = TreeBuilder::new();
+    builder
+        .document_type(
+            "memo",
+            None,
+            None,
+            Some(r#"<!ATTLIST memo status (draft|final) "draft" lang CDATA #IMPLIED><!ATTLIST memo status CDATA "ignored" version CDATA #FIXED "1">"#),
+        )
+        .unwrap();
+    builder.start_element("memo", &[("version", "1")]).unwrap();
+    builder.end_element("memo").unwrap();
+    let document_node = builder.finish().unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut memo_node = document.document_element().unwrap();
+
+    let status_node = memo_node.get_attribute_node("status").unwrap();
+    assert_eq!(memo_node.get_attribute("status").as_deref(), Some("draft"));
+    assert!(!as_attribute(&status_node).unwrap().specified());
+    let version_node = memo_node.get_attribute_node("version").unwrap();
+    assert!(as_attribute(&version_node).unwrap().specified());
+    assert!(memo_node.get_attribute_node("lang").is_none());
+
+    let mut status_node = status_node;
+    as_attribute_mut(&mut status_node)
+        .unwrap()
+        .set_value("draft")
+        .unwrap();
+    assert!(as_attribute(&status_node).unwrap().specified());
+
+    memo_node.set_attribute("status", "final").unwrap();
+    let status_node = memo_node.get_attribute_node("status").unwrap();
+    assert!(as_attribute(&status_node).unwrap().specified());
+
+    memo_node.remove_attribute("status").unwrap();
+    assert!(as_attribute(&status_node)
+        .unwrap()
+        .owner_element()
+        .is_none());
+    assert!(as_attribute(&status_node).unwrap().specified());
+    let default_node = memo_node.get_attribute_node("status").unwrap();
+    assert_ne!(default_node, status_node);
+    assert_eq!(memo_node.get_attribute("status").as_deref(), Some("draft"));
+    assert!(!as_attribute(&default_node).unwrap().specified());
+    assert!(as_attribute(&default_node.clone_node(false).unwrap())
+        .unwrap()
+        .specified());
+
+    let other_node = document.create_element("memo").unwrap();
+    assert_eq!(other_node.get_attribute("status").as_deref(), Some("draft"));
+    assert_eq!(other_node.get_attribute("version").as_deref(), Some("1"));
+    assert!(document
+        .create_element("note")
+        .unwrap()
+        .attributes()
+        .is_empty());
+}
+
+#[test]
+fn test_set_attribute_same_expanded_name() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    root_node
+        .set_attribute_ns("urn:example:a", "a:x", "first")
+        .unwrap();
+    root_node
+        .set_attribute_ns("urn:example:a", "b:x", "second")
+        .unwrap();
+    assert_eq!(root_node.attribute_count(), 1);
+    assert_eq!(
+        root_node.get_attribute_ns("urn:example:a", "x").as_deref(),
+        Some("second")
+    );
+    assert_eq!(root_node.get_attribute("a:x"), None);
+    assert_eq!(root_node.get_attribute("b:x").as_deref(), Some("second"));
+    let name = Name::builder("x")
+        .prefix("c")
+        .namespace_uri("urn:example:a")
+        .build()
+        .unwrap();
+    assert!(root_node.attributes().contains_key(&name));
+
+    root_node.set_attribute("a:y", "first").unwrap();
+    root_node.set_attribute("b:y", "second").unwrap();
+    assert_eq!(root_node.attribute_count(), 3);
+}
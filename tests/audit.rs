@@ -0,0 +1,102 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::{as_attribute_mut, as_document, as_element_mut};
+use xml_dom::level2::ext::{DocumentAudit, ElementAudit};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_audit_disabled_by_default() {
+    let document_node = xml_dom::parser::read_xml(r#"<config limit="10"/>"#).unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert!(!root_node.is_audited());
+
+    root_node.set_attribute("limit", "20").unwrap();
+    assert!(root_node.attribute_history("limit").is_empty());
+}
+
+#[test]
+fn test_audit_attribute_changes() {
+    let document_node = xml_dom::parser::read_xml(r#"<config limit="10"/>"#).unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    root_node.set_audited(true).unwrap();
+    assert!(root_node.is_audited());
+
+    root_node.set_attribute("limit", "20").unwrap();
+    root_node.set_attribute("mode", "fast").unwrap();
+    {
+        let element = as_element_mut(&mut root_node).unwrap();
+        let mut attribute_node = element.get_attribute_node("limit").unwrap();
+        let attribute = as_attribute_mut(&mut attribute_node).unwrap();
+        attribute.set_value("30").unwrap();
+    }
+    root_node.remove_attribute("limit").unwrap();
+
+    let history = root_node.attribute_history("limit");
+    let values: Vec<(Option<&str>, Option<&str>)> = history
+        .iter()
+        .map(|change| (change.previous.as_deref(), change.value.as_deref()))
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            (Some("10"), Some("20")),
+            (Some("20"), Some("30")),
+            (Some("30"), None),
+        ]
+    );
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+
+    let history = root_node.attribute_history("mode");
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].previous, None);
+    assert_eq!(history[0].value.as_deref(), Some("fast"));
+
+    root_node.set_audited(false).unwrap();
+    assert!(root_node.attribute_history("mode").is_empty());
+}
+
+#[test]
+fn test_audit_transaction_actor() {
+    let document_node = xml_dom::parser::read_xml(r#"<config limit="10"/>"#).unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    root_node.set_audited(true).unwrap();
+
+    root_node.set_attribute("limit", "11").unwrap();
+    document_node.audit_transaction("alice", || {
+        assert_eq!(document_node.audit_actor().as_deref(), Some("alice"));
+        root_node.clone().set_attribute("limit", "12").unwrap();
+        document_node.audit_transaction("bob", || {
+            root_node.clone().set_attribute("limit", "13").unwrap();
+        });
+        root_node.clone().set_attribute("limit", "14").unwrap();
+    });
+    assert_eq!(document_node.audit_actor(), None);
+
+    let actors: Vec<Option<String>> = root_node
+        .attribute_history("limit")
+        .into_iter()
+        .map(|change| change.actor)
+        .collect();
+    assert_eq!(
+        actors,
+        vec![
+            None,
+            Some("alice".to_string()),
+            Some("bob".to_string()),
+            Some("alice".to_string()),
+        ]
+    );
+}
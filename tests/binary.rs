@@ -0,0 +1,77 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{BinaryContent, BinaryEncoding};
+use xml_dom::level2::{Error, Node};
+
+pub mod common;
+
+#[test]
+fn test_base64_round_trip() {
+    let (_document_node, mut root_node) = common::make_element("data");
+    let data: Vec<u8> = (0..=255).collect();
+    root_node
+        .set_binary_content(&data, BinaryEncoding::Base64)
+        .unwrap();
+
+    let text = root_node.first_child().unwrap().node_value().unwrap();
+    let lines: Vec<&str> = text.split('\n').collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines.iter().all(|line| line.len() <= 76));
+    assert_eq!(lines[0].len(), 76);
+
+    assert_eq!(
+        root_node.binary_content(BinaryEncoding::Base64).unwrap(),
+        data
+    );
+}
+
+#[test]
+fn test_hex_round_trip() {
+    let (document_node, mut root_node) = common::make_element("data");
+    let document = as_document(&document_node).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("replaced"))
+        .unwrap();
+
+    root_node
+        .set_binary_content(&[0x00, 0x7F, 0xAB, 0xFF], BinaryEncoding::Hex)
+        .unwrap();
+    assert_eq!(root_node.to_string(), "<data>007fabff</data>");
+
+    let mut other_node = document.create_element("other").unwrap();
+    let _safe_to_ignore = other_node
+        .append_child(document.create_text_node("00 7F\n"))
+        .unwrap();
+    let _safe_to_ignore = other_node
+        .append_child(document.create_cdata_section("AB ff").unwrap())
+        .unwrap();
+    assert_eq!(
+        other_node.binary_content(BinaryEncoding::Hex).unwrap(),
+        vec![0x00, 0x7F, 0xAB, 0xFF]
+    );
+}
+
+#[test]
+fn test_invalid_content() {
+    let (document_node, mut root_node) = common::make_element("data");
+    root_node
+        .set_binary_content(&[], BinaryEncoding::Hex)
+        .unwrap();
+    assert!(!root_node.has_child_nodes());
+    assert_eq!(
+        root_node.binary_content(BinaryEncoding::Base64).unwrap(),
+        Vec::<u8>::new()
+    );
+
+    let document = as_document(&document_node).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("abc!"))
+        .unwrap();
+    assert_eq!(
+        root_node.binary_content(BinaryEncoding::Hex),
+        Err(Error::Syntax)
+    );
+    assert_eq!(
+        root_node.binary_content(BinaryEncoding::Base64),
+        Err(Error::Syntax)
+    );
+}
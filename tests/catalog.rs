@@ -0,0 +1,53 @@
+#![cfg(feature = "entity_catalog")]
+
+use xml_dom::level2::ext::catalog::{CharacterOutput, EntityCatalog, EntitySet};
+
+#[test]
+fn test_builtin_sets() {
+    assert_eq!(EntityCatalog::with_set(EntitySet::XhtmlLat1).len(), 96);
+    assert_eq!(EntityCatalog::with_set(EntitySet::XhtmlSpecial).len(), 33);
+    assert_eq!(EntityCatalog::with_set(EntitySet::XhtmlSymbol).len(), 124);
+
+    let catalog = EntityCatalog::xhtml();
+    assert_eq!(catalog.get("nbsp"), Some("\u{A0}"));
+    assert_eq!(catalog.get("euro"), Some("€"));
+    assert_eq!(catalog.get("infin"), Some("∞"));
+    assert_eq!(catalog.name_for('é'), Some("eacute"));
+    assert_eq!(catalog.get("unknown"), None);
+}
+
+#[test]
+fn test_decode() {
+    let catalog = EntityCatalog::xhtml();
+    assert_eq!(catalog.decode("a&nbsp;b"), "a\u{A0}b");
+    assert_eq!(
+        catalog.decode("&lt;&#945;&unknown; & &alpha"),
+        "&lt;&#945;&unknown; & &alpha"
+    );
+    assert_eq!(catalog.decode("&mdash;&mdash;"), "——");
+}
+
+#[test]
+fn test_encode() {
+    let catalog = EntityCatalog::xhtml();
+    let text = "&lt;café — ✓";
+    assert_eq!(catalog.encode(text, CharacterOutput::Literal), text);
+    assert_eq!(
+        catalog.encode(text, CharacterOutput::Named),
+        "&lt;caf&eacute; &mdash; ✓"
+    );
+    assert_eq!(
+        catalog.encode(text, CharacterOutput::Hex),
+        "&lt;caf&#xE9; &#x2014; &#x2713;"
+    );
+}
+
+#[test]
+fn test_custom_entities() {
+    let mut catalog = EntityCatalog::default();
+    assert!(catalog.is_empty());
+    assert!(catalog.insert("company", "Example Corp."));
+    assert!(!catalog.insert("not valid", "x"));
+    assert_eq!(catalog.decode("&company;"), "Example Corp.");
+    assert_eq!(catalog.len(), 1);
+}
@@ -91,6 +91,28 @@ pub fn create_example_rdf_document_options(options: ProcessingOptions) -> RefNod
     document_node
 }
 
+pub fn create_html_document() -> RefNode {
+    let implementation = get_implementation();
+    let document_type = implementation
+        .create_document_type("html", None, Some("about:legacy-compat"))
+        .unwrap();
+    implementation
+        .create_document(None, Some("html"), Some(document_type))
+        .unwrap()
+}
+
+pub fn make_element(name: &str) -> (RefNode, RefNode) {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some(name), None)
+        .unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    (document_node, root_node)
+}
+
 #[allow(unused_must_use)]
 pub fn create_element_with(document: RefDocument, ns: &str, qn: &str, content: &str) -> RefNode {
     let mut node = document.create_element_ns(ns, qn).unwrap();
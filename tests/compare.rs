@@ -0,0 +1,176 @@
+use xml_dom::level2::convert::{as_document, as_element_mut};
+use xml_dom::level2::ext::compare::{
+    compare_document_position, semantic_eq, sort_document_order, CompareOptions,
+    DOCUMENT_POSITION_CONTAINED_BY, DOCUMENT_POSITION_CONTAINS, DOCUMENT_POSITION_DISCONNECTED,
+    DOCUMENT_POSITION_FOLLOWING, DOCUMENT_POSITION_IMPLEMENTATION_SPECIFIC,
+    DOCUMENT_POSITION_PRECEDING,
+};
+use xml_dom::level2::*;
+
+pub mod common;
+
+fn create_document_with(children: &[(NodeType, &str)], attributes: &[(&str, &str)]) -> RefNode {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let root = as_element_mut(&mut root_node).unwrap();
+    for (name, value) in attributes {
+        assert!(root.set_attribute(name, value).is_ok());
+    }
+    for (node_type, data) in children {
+        let child = match node_type {
+            NodeType::Text => document.create_text_node(data),
+            NodeType::CData => document.create_cdata_section(data).unwrap(),
+            NodeType::Comment => document.create_comment(data),
+            _ => document.create_element(data).unwrap(),
+        };
+        assert!(root.append_child(child).is_ok());
+    }
+    document_node
+}
+
+#[test]
+fn test_semantic_eq_identical() {
+    let a = common::create_example_rdf_document();
+    let b = common::create_example_rdf_document();
+    assert!(semantic_eq(&a, &a, &CompareOptions::default()));
+    assert!(semantic_eq(&a, &b, &CompareOptions::default()));
+
+    let c = common::create_empty_rdf_document();
+    assert!(!semantic_eq(&a, &c, &CompareOptions::default()));
+}
+
+#[test]
+fn test_semantic_eq_attribute_order() {
+    let a = create_document_with(&[], &[("one", "1"), ("two", "2"), ("three", "3")]);
+    let b = create_document_with(&[], &[("three", "3"), ("one", "1"), ("two", "2")]);
+    assert!(semantic_eq(&a, &b, &CompareOptions::default()));
+
+    let c = create_document_with(&[], &[("three", "3"), ("one", "1"), ("two", "two")]);
+    assert!(!semantic_eq(&a, &c, &CompareOptions::default()));
+}
+
+#[test]
+fn test_semantic_eq_whitespace() {
+    let a = create_document_with(
+        &[
+            (NodeType::Text, "\n  "),
+            (NodeType::Element, "child"),
+            (NodeType::Text, "\n"),
+        ],
+        &[],
+    );
+    let b = create_document_with(&[(NodeType::Element, "child")], &[]);
+    assert!(semantic_eq(&a, &b, &CompareOptions::default()));
+
+    let options = CompareOptions {
+        ignore_whitespace: false,
+        ..Default::default()
+    };
+    assert!(!semantic_eq(&a, &b, &options));
+}
+
+#[test]
+fn test_semantic_eq_cdata_and_text() {
+    let a = create_document_with(&[(NodeType::Text, "hello <world>")], &[]);
+    let b = create_document_with(
+        &[(NodeType::Text, "hello "), (NodeType::CData, "<world>")],
+        &[],
+    );
+    assert!(semantic_eq(&a, &b, &CompareOptions::default()));
+
+    let options = CompareOptions {
+        cdata_as_text: false,
+        ..Default::default()
+    };
+    assert!(!semantic_eq(&a, &b, &options));
+}
+
+#[test]
+fn test_semantic_eq_comments() {
+    let a = create_document_with(
+        &[
+            (NodeType::Comment, "a comment"),
+            (NodeType::Element, "child"),
+        ],
+        &[],
+    );
+    let b = create_document_with(&[(NodeType::Element, "child")], &[]);
+    assert!(!semantic_eq(&a, &b, &CompareOptions::default()));
+
+    let options = CompareOptions {
+        ignore_comments: true,
+        ..Default::default()
+    };
+    assert!(semantic_eq(&a, &b, &options));
+}
+
+#[test]
+fn test_compare_document_position() {
+    let document_node = common::create_example_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let description = root_node.first_child().unwrap();
+    let creator = description.first_child().unwrap();
+    let title = creator.next_sibling().unwrap();
+    let id = root_node.get_attribute_node("id").unwrap();
+
+    assert_eq!(compare_document_position(&title, &title), 0);
+    assert_eq!(
+        compare_document_position(&description, &title),
+        DOCUMENT_POSITION_CONTAINED_BY | DOCUMENT_POSITION_FOLLOWING
+    );
+    assert_eq!(
+        compare_document_position(&title, &root_node),
+        DOCUMENT_POSITION_CONTAINS | DOCUMENT_POSITION_PRECEDING
+    );
+    assert_eq!(
+        compare_document_position(&creator, &title),
+        DOCUMENT_POSITION_FOLLOWING
+    );
+    assert_eq!(
+        compare_document_position(&title, &creator),
+        DOCUMENT_POSITION_PRECEDING
+    );
+
+    // an attribute follows its element and precedes the element's children.
+    assert_eq!(
+        compare_document_position(&id, &root_node),
+        DOCUMENT_POSITION_CONTAINS | DOCUMENT_POSITION_PRECEDING
+    );
+    assert_eq!(
+        compare_document_position(&id, &description),
+        DOCUMENT_POSITION_FOLLOWING
+    );
+
+    let other_node = common::create_example_rdf_document();
+    let forward = compare_document_position(&document_node, &other_node);
+    let backward = compare_document_position(&other_node, &document_node);
+    assert_ne!(forward & DOCUMENT_POSITION_DISCONNECTED, 0);
+    assert_ne!(forward & DOCUMENT_POSITION_IMPLEMENTATION_SPECIFIC, 0);
+    assert_eq!(
+        forward & (DOCUMENT_POSITION_PRECEDING | DOCUMENT_POSITION_FOLLOWING),
+        (backward ^ DOCUMENT_POSITION_PRECEDING ^ DOCUMENT_POSITION_FOLLOWING)
+            & (DOCUMENT_POSITION_PRECEDING | DOCUMENT_POSITION_FOLLOWING)
+    );
+}
+
+#[test]
+fn test_sort_document_order() {
+    let document_node = common::create_example_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let description = root_node.first_child().unwrap();
+    let creator = description.first_child().unwrap();
+    let date = description.last_child().unwrap();
+
+    let mut nodes = vec![
+        date.clone(),
+        creator.clone(),
+        root_node.clone(),
+        date.clone(),
+        description.clone(),
+    ];
+    sort_document_order(&mut nodes);
+    assert_eq!(nodes, vec![root_node, description, creator, date]);
+}
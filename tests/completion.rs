@@ -0,0 +1,103 @@
+use std::str::FromStr;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::completion::Schema;
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[cfg(feature = "quick_parser")]
+const BOOK: &str = r#"<!DOCTYPE book [
+<!-- a small book -->
+<!ELEMENT book (title, (chapter | appendix)+, index?)>
+<!ELEMENT title (#PCDATA)>
+<!ELEMENT chapter (#PCDATA | em | code)*>
+<!ELEMENT appendix ANY>
+<!ELEMENT index EMPTY>
+<!ELEMENT em (#PCDATA)>
+<!ELEMENT code (#PCDATA)>
+<!ATTLIST book lang CDATA #IMPLIED id ID #REQUIRED>
+<!ATTLIST chapter status (draft|final) "draft">
+]><book id="b1"><title>Guide</title></book>"#;
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_allowed_children_content_model() {
+    let document_node = xml_dom::parser::read_xml(BOOK).unwrap();
+    let document = as_document(&document_node).unwrap();
+    let schema = Schema::from_doc_type(&document.doc_type().unwrap()).unwrap();
+
+    assert!(schema.allowed_children(&document_node).is_empty());
+
+    let mut book_node = document.document_element().unwrap();
+    assert_eq!(
+        schema.allowed_children(&book_node),
+        vec!["appendix", "chapter"]
+    );
+
+    let chapter_node = book_node
+        .append_child(document.create_element("chapter").unwrap())
+        .unwrap();
+    assert_eq!(
+        schema.allowed_children(&book_node),
+        vec!["appendix", "chapter", "index"]
+    );
+    assert_eq!(schema.allowed_children(&chapter_node), vec!["code", "em"]);
+    assert!(schema.allows_text(&chapter_node));
+    assert!(!schema.allows_text(&book_node));
+
+    let _safe_to_ignore = book_node
+        .append_child(document.create_element("index").unwrap())
+        .unwrap();
+    assert!(schema.allowed_children(&book_node).is_empty());
+
+    let appendix_node = document.create_element("appendix").unwrap();
+    assert_eq!(schema.allowed_children(&appendix_node).len(), 7);
+}
+
+#[test]
+fn test_allowed_children_invalid_content() {
+    let schema = Schema::from_str("<!ELEMENT memo (to, from)>").unwrap();
+    let document_node = get_implementation()
+        .create_document(None, Some("memo"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut memo_node = document.document_element().unwrap();
+    assert_eq!(schema.allowed_children(&memo_node), vec!["to"]);
+
+    let _safe_to_ignore = memo_node
+        .append_child(document.create_element("from").unwrap())
+        .unwrap();
+    assert!(schema.allowed_children(&memo_node).is_empty());
+
+    let undeclared_node = document.create_element("note").unwrap();
+    assert!(schema.allowed_children(&undeclared_node).is_empty());
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_allowed_attributes() {
+    let document_node = xml_dom::parser::read_xml(BOOK).unwrap();
+    let document = as_document(&document_node).unwrap();
+    let schema = Schema::from_doc_type(&document.doc_type().unwrap()).unwrap();
+
+    let book_node = document.document_element().unwrap();
+    let attributes: Vec<(String, String)> = schema
+        .allowed_attributes(&book_node)
+        .into_iter()
+        .map(|attribute| (attribute.name, attribute.attribute_type))
+        .collect();
+    assert_eq!(attributes, vec![("lang".to_string(), "CDATA".to_string())]);
+
+    let chapter_node = document.create_element("chapter").unwrap();
+    let attributes = schema.allowed_attributes(&chapter_node);
+    assert_eq!(attributes.len(), 1);
+    assert_eq!(attributes[0].attribute_type, "(draft|final)");
+}
+
+#[test]
+fn test_invalid_content_model() {
+    assert!(Schema::from_str("<!ELEMENT memo (to, from | cc)>").is_err());
+    assert!(Schema::from_str("<!ELEMENT memo (to, from>").is_err());
+    assert!(Schema::from_str("<!ELEMENT memo (#PCDATA | em)>").is_err());
+    assert!(Schema::from_str("<!ELEMENT memo (#PCDATA)>").is_ok());
+}
@@ -0,0 +1,220 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::ext::conformance::{run_directory, run_test, TestOutcome};
+use xml_dom::level2::ext::resolver::{Resource, UriResolver};
+use xml_dom::level2::{Error, RefNode, Result};
+
+pub mod common;
+
+const STAFF: &str = r#"<staff>
+<employee id="1"><employeeId>EMP0001</employeeId><name>Margaret Martin</name><address domestic="Yes" street="No">1230 North Ave.</address></employee>
+<employee id="2"><employeeId>EMP0002</employeeId><name>Martha Raynolds</name><address domestic="Yes">PO Box 27</address></employee>
+</staff>"#;
+
+#[derive(Debug)]
+struct Samples;
+
+impl UriResolver for Samples {
+    fn fetch(&self, uri: &str, _referrer: &RefNode) -> Result<Resource> {
+        match uri {
+            "staff.xml" => Ok(Resource::Text(STAFF.to_string())),
+            _ => Err(Error::NotFound),
+        }
+    }
+}
+
+fn test(name: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<test xmlns="http://www.w3.org/2001/DOM-Test-Suite/Level-1" name="{}">
+<metadata><title>{}</title></metadata>
+{}
+</test>"#,
+        name, name, body
+    )
+}
+
+#[test]
+fn test_conformance_passed() {
+    let result = run_test(
+        &test(
+            "staffnames",
+            r#"<var name="doc" type="Document"/>
+<var name="employees" type="NodeList"/>
+<var name="nameList" type="NodeList"/>
+<var name="nameNode" type="Node"/>
+<var name="child" type="Node"/>
+<var name="value" type="DOMString"/>
+<var name="names" type="Collection"/>
+<var name="expected" type="Collection">
+  <member>"Martha Raynolds"</member>
+  <member>"Margaret Martin"</member>
+</var>
+<load var="doc" href="staff" willBeModified="true"/>
+<getElementsByTagName interface="Document" obj="doc" tagname="&quot;employee&quot;" var="employees"/>
+<assertSize size="2" collection="employees" id="employeeCount"/>
+<getElementsByTagName interface="Document" obj="doc" tagname="&quot;name&quot;" var="nameList"/>
+<for-each collection="nameList" member="nameNode">
+  <firstChild interface="Node" obj="nameNode" var="child"/>
+  <nodeValue obj="child" var="value"/>
+  <append collection="names" item="value"/>
+</for-each>
+<assertEquals actual="names" expected="expected" id="names" ignoreCase="false"/>
+<item interface="NodeList" obj="nameList" index="0" var="nameNode"/>
+<if><equals actual="value" expected="&quot;Martha Raynolds&quot;" ignoreCase="false"/>
+  <setAttribute obj="nameNode" name="&quot;title&quot;" value="&quot;Dr&quot;"/>
+  <else><fail id="lastName"/></else>
+</if>
+<getAttribute obj="nameNode" name="&quot;title&quot;" var="value"/>
+<assertEquals actual="value" expected="&quot;dr&quot;" id="title" ignoreCase="true"/>
+<assertDOMException id="removeNotChild">
+  <NOT_FOUND_ERR><removeChild obj="nameNode" oldChild="nameNode" var="child"/></NOT_FOUND_ERR>
+</assertDOMException>"#,
+        ),
+        &Samples,
+    );
+    assert_eq!(result.name, "staffnames");
+    assert_eq!(result.outcome, TestOutcome::Passed);
+    assert!(result.is_passed());
+}
+
+#[test]
+fn test_conformance_failed() {
+    let result = run_test(
+        &test(
+            "attrcount",
+            r#"<var name="doc" type="Document"/>
+<var name="addresses" type="NodeList"/>
+<var name="address" type="Node"/>
+<var name="attributes" type="NamedNodeMap"/>
+<var name="street" type="Attr"/>
+<load var="doc" href="staff" willBeModified="false"/>
+<getElementsByTagName interface="Document" obj="doc" tagname="&quot;address&quot;" var="addresses"/>
+<item interface="NodeList" obj="addresses" index="1" var="address"/>
+<attributes obj="address" var="attributes"/>
+<getNamedItem obj="attributes" name="&quot;street&quot;" var="street"/>
+<assertNotNull actual="street" id="streetAttr"/>"#,
+        ),
+        &Samples,
+    );
+    assert_eq!(
+        result.outcome,
+        TestOutcome::Failed("'streetAttr' unexpected value null".to_string())
+    );
+
+    let result = run_test(
+        &test(
+            "appendself",
+            r#"<var name="doc" type="Document"/>
+<var name="root" type="Element"/>
+<load var="doc" href="staff" willBeModified="true"/>
+<documentElement obj="doc" var="root"/>
+<appendChild obj="root" newChild="root" var="root"/>"#,
+        ),
+        &Samples,
+    );
+    assert_eq!(
+        result.outcome,
+        TestOutcome::Failed(
+            "unexpected exception HIERARCHY_REQUEST_ERR raised by 'appendChild'".to_string()
+        )
+    );
+    assert_eq!(
+        result.to_string(),
+        "appendself: FAILED: unexpected exception HIERARCHY_REQUEST_ERR raised by 'appendChild'"
+    );
+}
+
+#[test]
+fn test_conformance_unsupported_and_errors() {
+    let result = run_test(
+        &test(
+            "validating",
+            r#"<implementationAttribute name="validating" value="true"/>
+<var name="doc" type="Document"/>
+<load var="doc" href="staff" willBeModified="false"/>"#,
+        ),
+        &Samples,
+    );
+    assert!(matches!(result.outcome, TestOutcome::Unsupported(_)));
+
+    let result = run_test(
+        &test(
+            "events",
+            r#"<var name="doc" type="Document"/>
+<var name="event" type="Event"/>
+<load var="doc" href="staff" willBeModified="false"/>
+<createEvent obj="doc" var="event" eventType="&quot;Events&quot;"/>"#,
+        ),
+        &Samples,
+    );
+    assert_eq!(
+        result.outcome,
+        TestOutcome::Unsupported("method 'createEvent'".to_string())
+    );
+
+    let result = run_test(
+        &test(
+            "missing",
+            r#"<var name="doc" type="Document"/>
+<load var="doc" href="hc_staff" willBeModified="false"/>"#,
+        ),
+        &Samples,
+    );
+    assert!(matches!(result.outcome, TestOutcome::Error(_)));
+
+    let result = run_test("<suite/>", &Samples);
+    assert!(matches!(result.outcome, TestOutcome::Error(_)));
+}
+
+#[test]
+fn test_conformance_directory() {
+    let directory = std::env::temp_dir().join("xml_dom_test_conformance");
+    let files = directory.join("files");
+    std::fs::create_dir_all(&files).unwrap();
+    std::fs::write(files.join("staff.xml"), STAFF).unwrap();
+    std::fs::write(
+        directory.join("alltests.xml"),
+        r#"<suite name="alltests"><suite.member href="one.xml"/></suite>"#,
+    )
+    .unwrap();
+    std::fs::write(
+        directory.join("one.xml"),
+        test(
+            "one",
+            r#"<var name="doc" type="Document"/>
+<var name="root" type="Element"/>
+<var name="tagName" type="DOMString"/>
+<load var="doc" href="staff" willBeModified="false"/>
+<documentElement obj="doc" var="root"/>
+<tagName obj="root" var="tagName"/>
+<assertEquals actual="tagName" expected="&quot;staff&quot;" id="tagName" ignoreCase="false"/>"#,
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        directory.join("two.xml"),
+        test(
+            "two",
+            r#"<var name="doc" type="Document"/>
+<var name="root" type="Element"/>
+<load var="doc" href="staff" willBeModified="false"/>
+<documentElement obj="doc" var="root"/>
+<assertNull actual="root" id="root"/>"#,
+        ),
+    )
+    .unwrap();
+
+    let report = run_directory(&directory, &files).unwrap();
+    assert_eq!(report.results.len(), 2);
+    assert_eq!(report.passed(), 1);
+    assert_eq!(report.failed(), 1);
+    assert_eq!(report.unsupported(), 0);
+    assert_eq!(report.errors(), 0);
+    assert_eq!(
+        report.to_string(),
+        "one: passed
+two: FAILED: 'root' unexpected value node 'staff'
+2 tests: 1 passed, 1 failed, 0 unsupported, 0 errors"
+    );
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "quick_parser")]
+
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use std::convert::TryFrom;
+use xml_dom::level2::convert::{as_document, as_element_mut};
+use xml_dom::level2::*;
+use xml_dom::parser::{read_xml, Error as ParserError};
+
+pub mod common;
+
+const ORDER: &str = r#"<?xml version="1.0" encoding="UTF-8"?><!DOCTYPE order SYSTEM "order.dtd"><!--orders--><order id="7"><item sku="a&amp;b">Pen &lt;blue&gt;</item><?audit checked?><note><![CDATA[x <y>]]></note><empty/></order>"#;
+
+fn read_events(xml: &str) -> Vec<Event<'static>> {
+    let mut reader = Reader::from_str(xml);
+    let mut events = Vec::new();
+    loop {
+        match reader.read_event().unwrap() {
+            Event::Eof => break,
+            event => events.push(event.into_owned()),
+        }
+    }
+    events
+}
+
+fn write_events(events: Vec<Event<'_>>) -> String {
+    let mut writer = Writer::new(Vec::new());
+    for event in events {
+        writer.write_event(event).unwrap();
+    }
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_events_round_trip() {
+    let events = read_events(ORDER);
+    let document_node = RefNode::try_from(events.as_slice()).unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        read_xml(ORDER).unwrap().to_string()
+    );
+
+    let events: Vec<Event<'_>> = Vec::from(&document_node);
+    assert!(matches!(events.first(), Some(Event::Decl(_))));
+    assert!(matches!(events.get(1), Some(Event::DocType(_))));
+    assert_eq!(write_events(events), ORDER);
+}
+
+#[test]
+fn test_events_edit_element() {
+    let events = vec![
+        Event::Start(BytesStart::new("list")),
+        Event::Start(BytesStart::new("item")),
+        Event::Text(BytesText::new("1 < 2")),
+        Event::End(BytesEnd::new("item")),
+        Event::End(BytesEnd::new("list")),
+        Event::Eof,
+    ];
+    let document_node = RefNode::try_from(events).unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut item_node = root_node.first_child().unwrap();
+    {
+        let item = as_element_mut(&mut item_node).unwrap();
+        item.set_attribute("quote", "\"").unwrap();
+    }
+    let comment = document.create_comment("a--b-");
+    let _safe_to_ignore = root_node.append_child(comment).unwrap();
+    let cdata = document.create_cdata_section("]]>").unwrap();
+    let _safe_to_ignore = root_node.append_child(cdata).unwrap();
+
+    assert_eq!(
+        write_events(Vec::from(&root_node)),
+        r#"<list><item quote="&quot;">1 &lt; 2</item><!--a- -b- --><![CDATA[]]]]><![CDATA[>]]></list>"#
+    );
+
+    let attribute = item_node.get_attribute_node("quote").unwrap();
+    assert!(Vec::<Event<'_>>::from(&attribute).is_empty());
+}
+
+#[test]
+fn test_events_malformed() {
+    let events = vec![
+        Event::Start(BytesStart::new("list")),
+        Event::End(BytesEnd::new("item")),
+    ];
+    assert!(matches!(
+        RefNode::try_from(events),
+        Err(ParserError::Malformed)
+    ));
+
+    let events = vec![Event::Start(BytesStart::new("list"))];
+    assert!(matches!(
+        RefNode::try_from(events),
+        Err(ParserError::Malformed)
+    ));
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::ext::diff::{diff_lines, render_diff, DiffLine, DiffOptions};
+
+pub mod common;
+
+const OLD: &str = r#"<list><a/><b/><c/><d/><e/><f/><g/><h/><i/><j/></list>"#;
+const NEW: &str = r#"<list><a/><x/><c/><d/><e/><f/><g/><h/><i/><j/><k/></list>"#;
+
+#[test]
+fn test_diff_lines() {
+    let old_node = xml_dom::parser::read_xml(OLD).unwrap();
+    let new_node = xml_dom::parser::read_xml(NEW).unwrap();
+    let script = diff_lines(&old_node, &new_node, &DiffOptions::default()).unwrap();
+    assert_eq!(script.len(), 14);
+    assert_eq!(script[2], DiffLine::Removed("  <b></b>".to_string()));
+    assert_eq!(script[3], DiffLine::Added("  <x></x>".to_string()));
+    assert_eq!(script[12], DiffLine::Added("  <k></k>".to_string()));
+    assert_eq!(script[13], DiffLine::Context("</list>".to_string()));
+}
+
+#[test]
+fn test_render_diff_hunks() {
+    let old_node = xml_dom::parser::read_xml(OLD).unwrap();
+    let new_node = xml_dom::parser::read_xml(NEW).unwrap();
+    let options = DiffOptions {
+        context: 1,
+        ..Default::default()
+    };
+    assert_eq!(
+        render_diff(&old_node, &new_node, &options).unwrap(),
+        "--- old\n+++ new\n@@ -2,3 +2,3 @@\n   <a></a>\n-  <b></b>\n+  <x></x>\n   <c></c>\n@@ -11,2 +11,3 @@\n   <j></j>\n+  <k></k>\n </list>\n"
+    );
+
+    assert_eq!(
+        render_diff(&old_node, &old_node, &options).unwrap(),
+        String::new()
+    );
+}
+
+#[test]
+fn test_render_diff_color() {
+    let old_node = xml_dom::parser::read_xml("<a/>").unwrap();
+    let new_node = xml_dom::parser::read_xml("<b/>").unwrap();
+    let options = DiffOptions {
+        color: true,
+        old_label: "a.xml".to_string(),
+        new_label: "b.xml".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(
+        render_diff(&old_node, &new_node, &options).unwrap(),
+        "\u{1b}[1m--- a.xml\u{1b}[0m\n\u{1b}[1m+++ b.xml\u{1b}[0m\n\u{1b}[36m@@ -1 +1 @@\u{1b}[0m\n\u{1b}[31m-<a></a>\u{1b}[0m\n\u{1b}[32m+<b></b>\u{1b}[0m\n"
+    );
+}
@@ -1,10 +1,10 @@
 use xml_dom::level2::convert::{
     as_attribute_mut, as_document, as_document_fragment_mut, as_element_mut,
 };
-use xml_dom::level2::ext::convert::as_document_decl_mut;
+use xml_dom::level2::ext::convert::{as_document_decl_mut, as_document_type_decl_mut};
 use xml_dom::level2::ext::dom_impl as ext_dom_impl;
 use xml_dom::level2::ext::{XmlDecl, XmlVersion};
-use xml_dom::level2::get_implementation;
+use xml_dom::level2::{get_implementation, Element, Node};
 
 pub mod common;
 
@@ -87,7 +87,7 @@ fn test_display_cdata() {
         .unwrap();
 
     let result = format!("{}", test_node);
-    assert_eq!(result, "<![CDATA[ this is textual test data ]]>");
+    assert_eq!(result, "<![CDATA[this is textual test data]]>");
 }
 
 #[test]
@@ -197,15 +197,15 @@ fn test_display_document_type() {
 #[test]
 fn test_display_document_fragment() {
     let implementation = get_implementation();
-    let mut document_node = implementation
+    let document_node = implementation
         .create_document(Some(common::RDF_NS), Some("rdf:RDF"), None)
         .unwrap();
-    let document = as_document(&mut document_node).unwrap();
+    let document = as_document(&document_node).unwrap();
 
     let mut test_node = document.create_document_fragment().unwrap();
     let mut_fragment = as_document_fragment_mut(&mut test_node).unwrap();
 
-    for name in vec!["one", "two", "three"] {
+    for name in ["one", "two", "three"] {
         let node = document.create_element(name).unwrap();
         let _safe_to_ignore = mut_fragment.append_child(node).unwrap();
     }
@@ -270,29 +270,20 @@ fn test_display_entity() {
     );
 
     // ------------------------------------------------------------
-    /*
-            let mut test_node = dom_impl::create_entity(
-                document_node.clone(),
-                "name",
-                Some("foo-bar"),
-                Some("file-name.xml"),
-            )
-            .unwrap();
-            {
-                if let Extension::Entity {
-                    i_notation_name, ..
-                } = &mut test_node.i_extension
-                {
-                    *i_notation_name = Some("GIF".to_string());
-                }
-            }
-
-            let result = format!("{}", test_node);
-            assert_eq!(
-                result,
-                "<!ENTITY name PUBLIC \"foo-bar\" \"file-name.xml\" GIF>"
-            );
-    */
+    let test_node = ext_dom_impl::create_unparsed_entity(
+        document_node.clone(),
+        "name",
+        Some("foo-bar"),
+        "file-name.xml",
+        "GIF",
+    )
+    .unwrap();
+
+    let result = format!("{}", test_node);
+    assert_eq!(
+        result,
+        "<!ENTITY name PUBLIC \"foo-bar\" \"file-name.xml\" NDATA GIF>"
+    );
 }
 
 #[test]
@@ -334,3 +325,81 @@ fn test_display_notation() {
         "<!NOTATION name PUBLIC \"foo-bar\" \"file-name.xml\">"
     );
 }
+
+#[test]
+fn test_display_document_type_notations() {
+    let implementation = get_implementation();
+    let mut test_node = implementation
+        .create_document_type("book", None, Some("book.dtd"))
+        .unwrap();
+    {
+        let document_type = as_document_type_decl_mut(&mut test_node).unwrap();
+        assert!(document_type
+            .create_notation("png", None, Some("image/png"))
+            .is_ok());
+        assert!(document_type
+            .create_notation("gif", Some("-//IETF//NOTATION GIF//EN"), None)
+            .is_ok());
+    }
+
+    let result = format!("{}", test_node);
+    assert_eq!(
+        result,
+        "<!DOCTYPE book SYSTEM \"book.dtd\"[<!NOTATION gif PUBLIC \"-//IETF//NOTATION GIF//EN\"><!NOTATION png SYSTEM \"image/png\">]>"
+    );
+}
+
+#[test]
+fn test_display_escaping() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    root_node
+        .set_attribute("title", r#"a "b" <c> & 'd'"#)
+        .unwrap();
+    for node in [
+        document.create_text_node("x < y & z > \"w\"\r\n"),
+        document.create_cdata_section("if a]]>b").unwrap(),
+        document.create_comment("a <b> -- c -"),
+        document.create_text_node("&#38;"),
+    ] {
+        let _safe_to_ignore = root_node.append_child(node).unwrap();
+    }
+
+    assert_eq!(
+        root_node.to_string(),
+        "<root title=\"a &#34;b&#34; &#60;c&#62; &#38; &#39;d&#39;\">\
+         x &#60; y &#38; z &#62; &#34;w&#34;&#13;\n\
+         <![CDATA[if a]]]]><![CDATA[>b]]>\
+         <!--a <b> - - c - -->\
+         &#38;#38;</root>"
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_display_escaping_round_trip() {
+    use xml_dom::parser::read_xml;
+
+    let xml = r#"<root a="1 &lt; 2 &amp; &quot;3&quot;">&lt;tag&gt; &amp;amp; 'q'<!--a < b & c--></root>"#;
+    let document_node = read_xml(xml).unwrap();
+    let written = document_node.to_string();
+    let reread_node = read_xml(&written).unwrap();
+    assert_eq!(reread_node.to_string(), written);
+
+    let root_node = as_document(&reread_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(
+        root_node.first_child().unwrap().node_value().as_deref(),
+        Some("<tag> &amp; 'q'")
+    );
+    assert_eq!(
+        root_node.child_nodes()[1].node_value().as_deref(),
+        Some("a < b & c")
+    );
+}
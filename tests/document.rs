@@ -1,8 +1,9 @@
 use std::str::FromStr;
 use xml_dom::level2::convert::{
     as_attribute, as_cdata_section, as_comment, as_document, as_document_fragment, as_document_mut,
-    as_element, as_entity_reference, as_processing_instruction, as_text,
+    as_element, as_entity, as_entity_reference, as_notation, as_processing_instruction, as_text,
 };
+use xml_dom::level2::ext::convert::as_document_type_decl_mut;
 use xml_dom::level2::{get_implementation, Error, Name};
 
 pub mod common;
@@ -319,3 +320,104 @@ fn test_replace_root() {
     let expected_name = Name::from_str("should_work").unwrap();
     assert_eq!(element.node_name(), expected_name);
 }
+
+#[test]
+fn test_create_notation() {
+    let implementation = get_implementation();
+    let mut document_type_node = implementation
+        .create_document_type("book", None, Some("book.dtd"))
+        .unwrap();
+    let document_type = as_document_type_decl_mut(&mut document_type_node).unwrap();
+    assert!(document_type.notations().is_empty());
+
+    let notation_node = document_type
+        .create_notation("png", None, Some("image/png"))
+        .unwrap();
+    let notation = as_notation(&notation_node).unwrap();
+    assert_eq!(notation.node_name(), Name::from_str("png").unwrap());
+    assert!(notation.public_id().is_none());
+    assert_eq!(notation.system_id(), Some("image/png".to_string()));
+    assert_eq!(document_type.notations().len(), 1);
+
+    let result = document_type.create_notation("png", Some("-//png"), None);
+    assert_eq!(result.err(), Some(Error::Syntax));
+
+    let result = document_type.create_notation("jpeg", None, None);
+    assert_eq!(result.err(), Some(Error::Syntax));
+
+    let result = document_type.create_notation("not valid", None, Some("x"));
+    assert_eq!(result.err(), Some(Error::InvalidCharacter));
+    assert_eq!(document_type.notations().len(), 1);
+}
+
+#[test]
+fn test_create_unparsed_entity() {
+    let implementation = get_implementation();
+    let mut document_type_node = implementation
+        .create_document_type("book", None, None)
+        .unwrap();
+    let document_type = as_document_type_decl_mut(&mut document_type_node).unwrap();
+
+    let result = document_type.create_unparsed_entity("cover", None, "cover.png", "png");
+    assert_eq!(result.err(), Some(Error::NotFound));
+    assert!(document_type.entities().is_empty());
+
+    let _safe_to_ignore = document_type
+        .create_notation("png", None, Some("image/png"))
+        .unwrap();
+    let entity_node = document_type
+        .create_unparsed_entity("cover", None, "cover.png", "png")
+        .unwrap();
+    let entity = as_entity(&entity_node).unwrap();
+    assert_eq!(entity.notation_name(), Some("png".to_string()));
+    assert_eq!(entity.system_id(), Some("cover.png".to_string()));
+    assert!(!entity.has_child_nodes());
+    assert_eq!(
+        entity_node.to_string(),
+        r#"<!ENTITY cover SYSTEM "cover.png" NDATA png>"#
+    );
+
+    let text_node = document_type.create_entity("title", "Cover").unwrap();
+    assert_eq!(as_entity(&text_node).unwrap().notation_name(), None);
+
+    let result = document_type.create_unparsed_entity("cover", None, "other.png", "png");
+    assert_eq!(result.err(), Some(Error::Syntax));
+    let result = document_type.create_unparsed_entity("back", None, "back.png", "not valid");
+    assert_eq!(result.err(), Some(Error::InvalidCharacter));
+}
+
+#[test]
+fn test_xml_declaration_accessors() {
+    use xml_dom::level2::ext::serializer::{serialize, WriteOptions};
+    use xml_dom::level2::ext::{DocumentDecl, XmlVersion};
+
+    let mut document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    assert!(document_node.xml_declaration().is_none());
+    assert_eq!(document_node.xml_version(), XmlVersion::V10);
+    assert_eq!(document_node.xml_encoding(), None);
+    assert!(!document_node.xml_standalone());
+
+    document_node.set_xml_encoding(Some("UTF-8")).unwrap();
+    document_node.set_xml_standalone(true).unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><root></root>"#
+    );
+
+    document_node.set_xml_version(XmlVersion::V11).unwrap();
+    assert_eq!(document_node.xml_version(), XmlVersion::V11);
+    assert_eq!(document_node.xml_encoding().as_deref(), Some("UTF-8"));
+    assert!(document_node.xml_standalone());
+
+    assert_eq!(
+        document_node.set_xml_encoding(Some("not valid")),
+        Err(Error::Syntax)
+    );
+    document_node.set_xml_encoding(None).unwrap();
+    assert_eq!(
+        serialize(&document_node, &WriteOptions::default()).unwrap(),
+        r#"<?xml version="1.1" standalone="yes"?><root></root>"#
+    );
+}
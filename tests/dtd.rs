@@ -0,0 +1,444 @@
+#![cfg(feature = "quick_parser")]
+
+use std::str::FromStr;
+use xml_dom::level2::convert::{as_attribute, as_document, as_entity, as_notation};
+use xml_dom::level2::ext::dtd::{
+    is_parameter_entity, AttributeDefinition, ConditionalKeyword, ConditionalSection,
+};
+use xml_dom::level2::ext::sax::walk;
+use xml_dom::level2::ext::{DocumentBuilder, DocumentEntities, DocumentTypeDecl, ElementText};
+use xml_dom::level2::{
+    get_implementation, DocumentType, Element, Error, Name, Node, NodeType, RefNode,
+};
+
+pub mod common;
+
+const DOCUMENT: &str = r#"<!DOCTYPE doc [<!ENTITY % draft "INCLUDE"><!ENTITY % final 'IGNORE'><!ENTITY % shared SYSTEM "shared.ent"><![%draft;[<![ IGNORE [<!ENTITY status "old">]]><!ENTITY status "draft">]]><![%final;[<!ENTITY status "final">]]><!ELEMENT doc ANY>%shared;]><doc/>"#;
+
+fn document_type(xml: &str) -> RefNode {
+    let document_node = xml_dom::parser::read_xml(xml).unwrap();
+    as_document(&document_node).unwrap().doc_type().unwrap()
+}
+
+fn reference(name: &str) -> ConditionalKeyword {
+    ConditionalKeyword::Reference(Name::from_str(name).unwrap())
+}
+
+#[test]
+fn test_parse_internal_subset() {
+    let doc_type = document_type(DOCUMENT);
+
+    let parameter_entities = doc_type.parameter_entities();
+    assert_eq!(parameter_entities.len(), 3);
+    let draft = parameter_entities
+        .get(&Name::from_str("draft").unwrap())
+        .unwrap();
+    assert!(is_parameter_entity(draft));
+    assert_eq!(draft.node_value(), Some("INCLUDE".to_string()));
+    let shared = parameter_entities
+        .get(&Name::from_str("shared").unwrap())
+        .unwrap();
+    assert_eq!(
+        as_entity(shared).unwrap().system_id(),
+        Some("shared.ent".to_string())
+    );
+    assert!(doc_type.entities().is_empty());
+
+    let sections = doc_type.conditional_sections();
+    assert_eq!(sections.len(), 2);
+    assert_eq!(sections[0].keyword(), &reference("draft"));
+    assert_eq!(
+        sections[0].content(),
+        r#"<![ IGNORE [<!ENTITY status "old">]]><!ENTITY status "draft">"#
+    );
+    assert_eq!(sections[0].is_included(&doc_type), Some(true));
+    assert_eq!(sections[1].is_included(&doc_type), Some(false));
+
+    assert_eq!(
+        doc_type.internal_subset(),
+        Some("<!ELEMENT doc ANY>%shared;".to_string())
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let doc_type = document_type(DOCUMENT);
+    let expected = r#"<!DOCTYPE doc[<!ENTITY % draft "INCLUDE"><!ENTITY % final "IGNORE"><!ENTITY % shared SYSTEM "shared.ent"><![%draft;[<![ IGNORE [<!ENTITY status "old">]]><!ENTITY status "draft">]]><![%final;[<!ENTITY status "final">]]><!ELEMENT doc ANY>%shared;]>"#;
+    assert_eq!(doc_type.to_string(), expected);
+
+    let reparsed = document_type(&format!("{}<doc/>", expected));
+    assert_eq!(reparsed.to_string(), expected);
+
+    let mut builder = DocumentBuilder::new();
+    walk(&xml_dom::parser::read_xml(DOCUMENT).unwrap(), &mut builder).unwrap();
+    let built = as_document(&builder.into_document().unwrap())
+        .unwrap()
+        .doc_type()
+        .unwrap();
+    assert_eq!(built.parameter_entities().len(), 3);
+    assert_eq!(built.conditional_sections().len(), 2);
+    assert_eq!(built.to_string(), expected);
+}
+
+#[test]
+fn test_duplicate_parameter_entity() {
+    let doc_type = document_type(
+        r#"<!DOCTYPE doc [<!ENTITY % a "1"><!ENTITY % a "2"><!ENTITY b "3">]><doc/>"#,
+    );
+    let parameter_entities = doc_type.parameter_entities();
+    assert_eq!(parameter_entities.len(), 1);
+    assert_eq!(
+        parameter_entities
+            .get(&Name::from_str("a").unwrap())
+            .unwrap()
+            .node_value(),
+        Some("1".to_string())
+    );
+    assert_eq!(
+        doc_type.internal_subset(),
+        Some(r#"<!ENTITY % a "2">"#.to_string())
+    );
+    assert_eq!(doc_type.entities().len(), 1);
+}
+
+#[test]
+fn test_create_declarations() {
+    let implementation = get_implementation();
+    let mut doc_type = implementation
+        .create_document_type("doc", None, None)
+        .unwrap();
+
+    let entity = doc_type.create_parameter_entity("mode", "IGNORE").unwrap();
+    assert!(is_parameter_entity(&entity));
+    assert_eq!(
+        doc_type.create_parameter_entity("mode", "INCLUDE"),
+        Err(Error::Syntax)
+    );
+    assert_eq!(
+        doc_type.create_parameter_entity("bad name", "INCLUDE"),
+        Err(Error::InvalidCharacter)
+    );
+    let _safe_to_ignore = doc_type
+        .create_external_parameter_entity("ext", Some("-//EXAMPLE//ENTITIES//EN"), "ext.ent")
+        .unwrap();
+
+    let section = ConditionalSection::new(reference("mode"), "<!ELEMENT x ANY>").unwrap();
+    assert_eq!(section.is_included(&doc_type), Some(false));
+    doc_type.add_conditional_section(section).unwrap();
+    let section = ConditionalSection::new(reference("ext"), "").unwrap();
+    assert_eq!(section.is_included(&doc_type), None);
+    let section = ConditionalSection::new(reference("missing"), "").unwrap();
+    assert_eq!(section.is_included(&doc_type), None);
+
+    assert_eq!(
+        doc_type.to_string(),
+        r#"<!DOCTYPE doc[<!ENTITY % ext PUBLIC "-//EXAMPLE//ENTITIES//EN" "ext.ent"><!ENTITY % mode "IGNORE"><![%mode;[<!ELEMENT x ANY>]]>]>"#
+    );
+}
+
+#[test]
+fn test_conditional_sections() {
+    assert_eq!(
+        ConditionalKeyword::from_str(" INCLUDE "),
+        Ok(ConditionalKeyword::Include)
+    );
+    assert_eq!(ConditionalKeyword::from_str("%x;"), Ok(reference("x")));
+    assert_eq!(ConditionalKeyword::from_str("include"), Err(Error::Syntax));
+    assert_eq!(reference("x").to_string(), "%x;");
+
+    let section = ConditionalSection::from_str("<![IGNORE[<![INCLUDE[]]>]]>").unwrap();
+    assert_eq!(section.keyword(), &ConditionalKeyword::Ignore);
+    assert_eq!(section.content(), "<![INCLUDE[]]>");
+    assert_eq!(section.to_string(), "<![IGNORE[<![INCLUDE[]]>]]>");
+
+    assert!(ConditionalSection::new(ConditionalKeyword::Include, "]]>").is_err());
+    assert!(ConditionalSection::new(ConditionalKeyword::Include, "<![IGNORE[").is_err());
+    assert!(ConditionalSection::from_str("<![IGNORE[]]>]]>").is_err());
+    assert!(ConditionalSection::from_str("<![OTHER[]]>").is_err());
+}
+
+#[test]
+fn test_attribute_definitions() {
+    let doc_type = document_type(
+        r#"<!DOCTYPE book [<!ATTLIST chapter key ID #REQUIRED ref IDREF #IMPLIED><![IGNORE[<!ATTLIST book key ID #IMPLIED>]]><!ATTLIST chapter key CDATA #IMPLIED>]><book/>"#,
+    );
+    let definitions = doc_type.attribute_definitions();
+    assert_eq!(
+        definitions,
+        vec![
+            AttributeDefinition::new("chapter", "key", "ID").unwrap(),
+            AttributeDefinition::new("chapter", "ref", "IDREF").unwrap(),
+            AttributeDefinition::new("chapter", "key", "CDATA").unwrap(),
+        ]
+    );
+    assert!(definitions[0].is_id());
+    assert!(!definitions[1].is_id());
+    assert_eq!(definitions[1].attribute_name().to_string(), "ref");
+    assert_eq!(
+        doc_type.internal_subset().as_deref(),
+        Some("<!ATTLIST chapter key ID #REQUIRED ref IDREF #IMPLIED><!ATTLIST chapter key CDATA #IMPLIED>")
+    );
+
+    assert_eq!(
+        AttributeDefinition::new("chapter", "not valid", "ID"),
+        Err(Error::InvalidCharacter)
+    );
+}
+
+#[test]
+fn test_document_entities() {
+    let mut doc_type_node = get_implementation()
+        .create_document_type("doc", None, None)
+        .unwrap();
+    let _safe_to_ignore = doc_type_node
+        .create_notation("png", None, Some("image/png"))
+        .unwrap();
+    let _safe_to_ignore = doc_type_node.create_entity("status", "draft").unwrap();
+    let _safe_to_ignore = doc_type_node
+        .create_external_entity("chapter", None, "chapter.xml")
+        .unwrap();
+    let _safe_to_ignore = doc_type_node
+        .create_parameter_entity("shared", "shared")
+        .unwrap();
+    assert_eq!(
+        doc_type_node.create_entity("status", "final"),
+        Err(Error::Syntax)
+    );
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), Some(doc_type_node.clone()))
+        .unwrap();
+
+    assert!(document_node.has_entity("status"));
+    assert!(document_node.has_entity("chapter"));
+    assert!(!document_node.has_entity("shared"));
+    assert!(!document_node.has_entity("amp"));
+    assert!(!document_node.has_entity("not valid"));
+    assert_eq!(
+        document_node.entity("status").unwrap().node_value(),
+        Some("draft".to_string())
+    );
+    assert_eq!(
+        as_entity(&document_node.entity("chapter").unwrap())
+            .unwrap()
+            .system_id(),
+        Some("chapter.xml".to_string())
+    );
+    let notation = document_node.notation("png").unwrap();
+    assert_eq!(notation.node_name().to_string(), "png");
+    assert!(document_node.notation("gif").is_none());
+    assert_eq!(
+        doc_type_node.to_string(),
+        r#"<!DOCTYPE doc[<!ENTITY chapter SYSTEM "chapter.xml"><!ENTITY status "draft"><!ENTITY % shared "shared"><!NOTATION png SYSTEM "image/png">]>"#
+    );
+
+    let other_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    assert!(other_node.entity("status").is_none());
+    assert!(other_node.notation("png").is_none());
+}
+
+#[test]
+fn test_add_attribute_definition() {
+    let doc_type_node = get_implementation()
+        .create_document_type("memo", None, None)
+        .unwrap();
+    let document_node = get_implementation()
+        .create_document(None, Some("memo"), Some(doc_type_node))
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut doc_type_node = document.doc_type().unwrap();
+    let mut memo_node = document.document_element().unwrap();
+    memo_node.set_attribute("lang", "en").unwrap();
+
+    doc_type_node
+        .add_attribute_definition(
+            AttributeDefinition::new("memo", "status", "(draft|final)")
+                .unwrap()
+                .with_default_value("draft"),
+        )
+        .unwrap();
+    doc_type_node
+        .add_attribute_definition(
+            AttributeDefinition::new("memo", "lang", "CDATA")
+                .unwrap()
+                .with_default_value("fr"),
+        )
+        .unwrap();
+    doc_type_node
+        .add_attribute_definition(
+            AttributeDefinition::new("memo", "status", "CDATA")
+                .unwrap()
+                .with_default_value("final"),
+        )
+        .unwrap();
+    doc_type_node
+        .add_attribute_definition(AttributeDefinition::new("memo", "to", "CDATA").unwrap())
+        .unwrap();
+    assert_eq!(doc_type_node.attribute_definitions().len(), 4);
+    assert_eq!(
+        doc_type_node.internal_subset().as_deref(),
+        Some(
+            r#"<!ATTLIST memo status (draft|final) "draft"><!ATTLIST memo lang CDATA "fr"><!ATTLIST memo status CDATA "final"><!ATTLIST memo to CDATA #IMPLIED>"#
+        )
+    );
+
+    assert_eq!(memo_node.attributes().len(), 2);
+    assert_eq!(memo_node.get_attribute("status").as_deref(), Some("draft"));
+    assert_eq!(memo_node.get_attribute("lang").as_deref(), Some("en"));
+    assert_eq!(memo_node.get_attribute("to"), None);
+    let status_node = memo_node.get_attribute_node("status").unwrap();
+    assert!(!as_attribute(&status_node).unwrap().specified());
+
+    let other_node = document.create_element("memo").unwrap();
+    assert_eq!(other_node.get_attribute("status").as_deref(), Some("draft"));
+    assert_eq!(other_node.get_attribute("lang").as_deref(), Some("fr"));
+}
+
+#[test]
+fn test_entity_replacement_nodes() {
+    let mut doc_type_node = get_implementation()
+        .create_document_type("doc", None, None)
+        .unwrap();
+    let status_node = doc_type_node.create_entity("status", "draft").unwrap();
+    assert!(status_node.child_nodes().is_empty());
+
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), Some(doc_type_node.clone()))
+        .unwrap();
+    assert_eq!(status_node.owner_document(), Some(document_node.clone()));
+    let children = status_node.child_nodes();
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0].node_type(), NodeType::Text);
+    assert_eq!(children[0].node_value().as_deref(), Some("draft"));
+
+    let sign_node = doc_type_node
+        .create_entity("sign", "<b>Signed</b>, <i>J.</i>")
+        .unwrap();
+    let children = sign_node.child_nodes();
+    assert_eq!(children.len(), 3);
+    assert_eq!(children[0].to_string(), "<b>Signed</b>");
+    assert_eq!(children[1].node_value().as_deref(), Some(", "));
+    assert_eq!(children[2].node_name().to_string(), "i");
+    assert_eq!(children[2].owner_document(), Some(document_node));
+    assert_eq!(
+        sign_node.node_value().as_deref(),
+        Some("<b>Signed</b>, <i>J.</i>")
+    );
+
+    let broken_node = doc_type_node.create_entity("broken", "<b>").unwrap();
+    assert!(broken_node.child_nodes().is_empty());
+    let chapter_node = doc_type_node
+        .create_external_entity("chapter", None, "chapter.xml")
+        .unwrap();
+    assert!(chapter_node.child_nodes().is_empty());
+}
+
+#[test]
+fn test_element_normalized_text() {
+    let mut doc_type_node = get_implementation()
+        .create_document_type("doc", None, None)
+        .unwrap();
+    let _safe_to_ignore = doc_type_node.create_entity("name", "Jane").unwrap();
+    let _safe_to_ignore = doc_type_node
+        .create_entity("signed", "&name; &#169;&#x21;")
+        .unwrap();
+    let _safe_to_ignore = doc_type_node.create_entity("loop", "x&loop;").unwrap();
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), Some(doc_type_node))
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("By "))
+        .unwrap();
+    for name in ["signed", "loop", "undeclared"].iter() {
+        let _safe_to_ignore = root_node
+            .append_child(document.create_entity_reference(name).unwrap())
+            .unwrap();
+    }
+    let mut em_node = document.create_element("em").unwrap();
+    let _safe_to_ignore = em_node
+        .append_child(document.create_cdata_section("<now>").unwrap())
+        .unwrap();
+    let _safe_to_ignore = em_node
+        .append_child(document.create_comment("ignored"))
+        .unwrap();
+    let _safe_to_ignore = root_node.append_child(em_node).unwrap();
+
+    assert_eq!(
+        root_node.normalized_text(),
+        "By Jane ©!x&loop;&undeclared;<now>"
+    );
+    assert_eq!(root_node.child_nodes().len(), 5);
+}
+
+#[test]
+fn test_parsed_element_normalized_text() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<!DOCTYPE doc [
+  <!ENTITY name "Jane">
+  <!ENTITY signed "&#169; Jane">
+]>
+<doc>By &name;, <em>&signed;</em><![CDATA[ &name; ]]><!--ignored--></doc>"#,
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+
+    assert_eq!(root_node.normalized_text(), "By Jane, \u{A9} Jane &name; ");
+    assert_eq!(root_node.child_nodes().len(), 6);
+}
+
+#[test]
+fn test_parse_entities_and_notations() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<!DOCTYPE doc [
+<!NOTATION gif PUBLIC "-//IETF//NOTATION GIF//EN">
+<!NOTATION png SYSTEM "image/png">
+<!ENTITY sign "<b>Signed</b>">
+<!ENTITY chapter PUBLIC "-//DOC//Chapter" "chapter.xml">
+<!ENTITY logo SYSTEM "logo.png" NDATA png>
+<!ENTITY sign "ignored">
+<!ENTITY broken SYSTEM>
+<!ELEMENT doc ANY>
+]><doc/>"#,
+    )
+    .unwrap();
+    let doc_type = as_document(&document_node).unwrap().doc_type().unwrap();
+    assert_eq!(doc_type.entities().len(), 3);
+    assert_eq!(doc_type.notations().len(), 2);
+
+    let sign = document_node.entity("sign").unwrap();
+    assert_eq!(sign.node_value().as_deref(), Some("<b>Signed</b>"));
+    assert_eq!(sign.owner_document(), Some(document_node.clone()));
+    assert_eq!(sign.first_child().unwrap().to_string(), "<b>Signed</b>");
+
+    let chapter_node = document_node.entity("chapter").unwrap();
+    let chapter = as_entity(&chapter_node).unwrap();
+    assert_eq!(chapter.public_id().as_deref(), Some("-//DOC//Chapter"));
+    assert_eq!(chapter.system_id().as_deref(), Some("chapter.xml"));
+    assert_eq!(chapter.notation_name(), None);
+
+    let logo = document_node.entity("logo").unwrap();
+    assert_eq!(
+        as_entity(&logo).unwrap().notation_name().as_deref(),
+        Some("png")
+    );
+
+    let gif = document_node.notation("gif").unwrap();
+    assert_eq!(
+        as_notation(&gif).unwrap().public_id().as_deref(),
+        Some("-//IETF//NOTATION GIF//EN")
+    );
+    assert_eq!(as_notation(&gif).unwrap().system_id(), None);
+
+    assert_eq!(
+        doc_type.internal_subset(),
+        Some(
+            "\n\n\n\n\n\n<!ENTITY sign \"ignored\">\n<!ENTITY broken SYSTEM>\n<!ELEMENT doc ANY>\n"
+                .to_string()
+        )
+    );
+}
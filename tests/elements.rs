@@ -122,3 +122,51 @@ fn test_get_attribute() {
     assert!(element.get_attribute_ns(common::DC_NS, "three").is_none());
     assert!(element.get_attribute_ns(common::XMLNS_NS, "two").is_none());
 }
+
+#[test]
+fn test_attribute_in_scope() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let root = as_element_mut(&mut root_node).unwrap();
+    assert!(root
+        .set_attribute_ns(common::XML_NS_URI, "xml:lang", "en")
+        .is_ok());
+    assert!(root.set_attribute("version", "1").is_ok());
+
+    let mut child_node = document.create_element("child").unwrap();
+    assert!(root.append_child(child_node.clone()).is_ok());
+    let child = as_element_mut(&mut child_node).unwrap();
+    assert!(child.set_attribute("xml:space", "preserve").is_ok());
+
+    let mut grand_child_node = document.create_element("grand-child").unwrap();
+    assert!(child.append_child(grand_child_node.clone()).is_ok());
+    let grand_child = as_element_mut(&mut grand_child_node).unwrap();
+    assert!(grand_child.set_attribute("version", "2").is_ok());
+
+    assert_eq!(
+        grand_child.attribute_in_scope(Some(common::XML_NS_URI), "lang"),
+        Some("en".to_string())
+    );
+    assert_eq!(
+        grand_child.attribute_in_scope(Some(common::XML_NS_URI), "space"),
+        Some("preserve".to_string())
+    );
+    assert_eq!(
+        grand_child.attribute_in_scope(None, "version"),
+        Some("2".to_string())
+    );
+    assert_eq!(
+        child.attribute_in_scope(None, "version"),
+        Some("1".to_string())
+    );
+    assert_eq!(
+        root.attribute_in_scope(Some(common::XML_NS_URI), "space"),
+        None
+    );
+    assert_eq!(grand_child.attribute_in_scope(None, "lang"), None);
+    assert_eq!(
+        grand_child.attribute_in_scope(Some(common::XML_NS_URI), "base"),
+        None
+    );
+}
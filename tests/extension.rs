@@ -0,0 +1,140 @@
+use std::any::Any;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::extension::NodeExtension;
+use xml_dom::level2::ext::serializer::{serialize, WriteOptions};
+use xml_dom::level2::ext::{NodeExtended, Workspace};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Layout {
+    x: i32,
+    y: i32,
+}
+
+impl NodeExtension for Layout {
+    fn clone_extension(&self) -> Option<Box<dyn NodeExtension>> {
+        Some(Box::new(self.clone()))
+    }
+    fn serialize(&self) -> Option<String> {
+        Some(format!("x=\"{}\" y=\"{}\"", self.x, self.y))
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Transient;
+
+impl NodeExtension for Transient {
+    fn clone_extension(&self) -> Option<Box<dyn NodeExtension>> {
+        None
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn make_document() -> RefNode {
+    get_implementation()
+        .create_document(None, Some("diagram"), None)
+        .unwrap()
+}
+
+#[test]
+fn test_extension_access() {
+    let document_node = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut box_node = document.create_element("box").unwrap();
+    assert!(!box_node.has_extension());
+    assert!(box_node
+        .set_extension(Box::new(Layout { x: 1, y: 2 }))
+        .is_none());
+    assert!(box_node.has_extension());
+
+    assert_eq!(box_node.with_extension(|layout: &Layout| layout.x), Some(1));
+    assert_eq!(box_node.with_extension(|_: &Transient| ()), None);
+    assert_eq!(
+        box_node.with_extension_mut(|layout: &mut Layout| {
+            layout.y = 5;
+            layout.y
+        }),
+        Some(5)
+    );
+
+    let previous = box_node.set_extension(Box::new(Transient)).unwrap();
+    assert_eq!(
+        previous.as_any().downcast_ref::<Layout>(),
+        Some(&Layout { x: 1, y: 5 })
+    );
+    assert!(box_node.take_extension().is_some());
+    assert!(!box_node.has_extension());
+}
+
+#[test]
+fn test_extension_clone() {
+    let document_node = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut box_node = document.create_element("box").unwrap();
+    let _safe_to_ignore = box_node.set_extension(Box::new(Layout { x: 3, y: 4 }));
+    let mut label_node = document.create_text_node("label");
+    let _safe_to_ignore = label_node.set_extension(Box::new(Transient));
+    let _safe_to_ignore = box_node.append_child(label_node).unwrap();
+    let _safe_to_ignore = root_node.append_child(box_node).unwrap();
+
+    let copy_node = root_node.clone_node(true).unwrap();
+    let box_copy = copy_node.first_child().unwrap();
+    assert_eq!(
+        box_copy.with_extension(|layout: &Layout| layout.clone()),
+        Some(Layout { x: 3, y: 4 })
+    );
+    assert!(!box_copy.first_child().unwrap().has_extension());
+}
+
+#[test]
+fn test_extension_import() {
+    let document_node = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut box_node = document.create_element("box").unwrap();
+    let _safe_to_ignore = box_node.set_extension(Box::new(Layout { x: 7, y: 8 }));
+
+    let mut workspace = Workspace::new();
+    let _safe_to_ignore = workspace
+        .create_document("copy.xml", None, Some("diagram"))
+        .unwrap();
+    let imported = workspace.import_node(&box_node, "copy.xml", true).unwrap();
+    assert_eq!(imported.with_extension(|layout: &Layout| layout.x), Some(7));
+}
+
+#[test]
+fn test_extension_serialize() {
+    let document_node = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut box_node = document.create_element("box").unwrap();
+    let _safe_to_ignore = box_node.set_extension(Box::new(Layout { x: 3, y: 4 }));
+    let _safe_to_ignore = root_node.append_child(box_node).unwrap();
+
+    assert_eq!(
+        serialize(&document_node, &WriteOptions::default()).unwrap(),
+        "<diagram><box></box></diagram>"
+    );
+    let options = WriteOptions {
+        extension_target: Some("layout".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        serialize(&document_node, &options).unwrap(),
+        "<diagram><?layout x=\"3\" y=\"4\"?><box></box></diagram>"
+    );
+    assert_eq!(document_node.to_string(), "<diagram><box></box></diagram>");
+}
@@ -1,5 +1,5 @@
 use xml_dom::level2::convert::{as_document, as_element, as_element_mut};
-use xml_dom::level2::ext::ProcessingOptions;
+use xml_dom::level2::ext::{AttributeId, ElementId, ProcessingOptions};
 use xml_dom::level2::*;
 
 pub mod common;
@@ -96,3 +96,174 @@ fn test_no_duplicates() {
     assert!(result.is_err());
     assert_eq!(result.err().unwrap(), Error::Syntax);
 }
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_get_element_by_id_parsed() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<book><chapter xml:id=" intro "/><chapter xml:id="a&amp;b"/><chapter id="plain"/></book>"#,
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+
+    let intro = document.get_element_by_id("intro").unwrap();
+    assert_eq!(intro, root_node.first_child().unwrap());
+    let escaped = document.get_element_by_id("a&b").unwrap();
+    assert_eq!(escaped, root_node.child_nodes()[1]);
+    assert!(document.get_element_by_id("plain").is_none());
+}
+
+#[test]
+fn test_get_element_by_id_tracks_changes() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let mut element = document.create_element("section").unwrap();
+    element.set_attribute("xml:id", "one").unwrap();
+    assert!(document.get_element_by_id("one").is_none());
+    let _safe_to_ignore = root_node.append_child(element.clone()).unwrap();
+    assert_eq!(document.get_element_by_id("one"), Some(element.clone()));
+
+    // Setting the same ID again is not a duplicate.
+    element.set_attribute("xml:id", "one").unwrap();
+
+    element.set_attribute("xml:id", "two").unwrap();
+    assert!(document.get_element_by_id("one").is_none());
+    assert_eq!(document.get_element_by_id("two"), Some(element.clone()));
+
+    element.remove_attribute("xml:id").unwrap();
+    assert!(document.get_element_by_id("two").is_none());
+
+    element.set_attribute("xml:id", "three").unwrap();
+    let _safe_to_ignore = root_node.remove_child(element.clone()).unwrap();
+    assert!(document.get_element_by_id("three").is_none());
+
+    // A removed element does not hold its ID.
+    let mut other = document.create_element("section").unwrap();
+    other.set_attribute("xml:id", "three").unwrap();
+    let _safe_to_ignore = root_node.append_child(other.clone()).unwrap();
+    assert_eq!(document.get_element_by_id("three"), Some(other));
+}
+
+#[test]
+fn test_get_element_by_id_first_in_document_order() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let mut first = document.create_element("first").unwrap();
+    let mut second = document.create_element("second").unwrap();
+    first.set_attribute("xml:id", "same").unwrap();
+    second.set_attribute("xml:id", "same").unwrap();
+    let _safe_to_ignore = root_node.append_child(first.clone()).unwrap();
+    let _safe_to_ignore = root_node.append_child(second).unwrap();
+    assert_eq!(document.get_element_by_id("same"), Some(first));
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_get_element_by_id_declared() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<!DOCTYPE book [<!ATTLIST chapter key ID #REQUIRED><!ATTLIST chapter key CDATA #IMPLIED><!ATTLIST section key CDATA #IMPLIED>]>
+<book xmlns="urn:books"><chapter key="one"/><section key="two"/></book>"#,
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let chapter = document.get_element_by_id("one").unwrap();
+    assert_eq!(chapter.node_name().to_string(), "chapter");
+    assert!(document.get_element_by_id("two").is_none());
+
+    let mut new_chapter = document.create_element("chapter").unwrap();
+    new_chapter.set_attribute("key", "three").unwrap();
+    let _safe_to_ignore = root_node.append_child(new_chapter.clone()).unwrap();
+    assert_eq!(document.get_element_by_id("three"), Some(new_chapter));
+
+    let mut duplicate = document.create_element("chapter").unwrap();
+    let _safe_to_ignore = root_node.append_child(duplicate.clone()).unwrap();
+    duplicate.set_attribute("key", "one").unwrap();
+    assert_eq!(document.get_element_by_id("one"), Some(chapter));
+}
+
+#[test]
+fn test_set_id_attribute() {
+    let document_node = get_implementation()
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut item_node = document.create_element("item").unwrap();
+    item_node.set_attribute("key", "k-1").unwrap();
+    let _safe_to_ignore = root_node.append_child(item_node.clone()).unwrap();
+    let key_node = item_node.get_attribute_node("key").unwrap();
+    assert!(!key_node.is_id());
+    assert!(document.get_element_by_id("k-1").is_none());
+
+    item_node.set_id_attribute("key", true).unwrap();
+    assert!(key_node.is_id());
+    assert_eq!(document.get_element_by_id("k-1"), Some(item_node.clone()));
+
+    item_node.set_attribute("key", "k-2").unwrap();
+    assert!(document.get_element_by_id("k-1").is_none());
+    assert_eq!(document.get_element_by_id("k-2"), Some(item_node.clone()));
+
+    let key_node = item_node.get_attribute_node("key").unwrap();
+    assert!(key_node.is_id());
+    item_node.set_id_attribute("key", false).unwrap();
+    assert!(!key_node.is_id());
+    assert!(document.get_element_by_id("k-2").is_none());
+
+    assert_eq!(
+        item_node.set_id_attribute("missing", true),
+        Err(Error::NotFound)
+    );
+    let other_node = document.create_attribute("key").unwrap();
+    assert_eq!(
+        item_node.set_id_attribute_node(other_node, true),
+        Err(Error::NotFound)
+    );
+}
+
+#[test]
+fn test_set_id_attribute_ns() {
+    let document_node = get_implementation()
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut first_node = document.create_element("item").unwrap();
+    first_node
+        .set_attribute_ns("urn:refs", "r:key", "shared")
+        .unwrap();
+    let mut second_node = document.create_element("item").unwrap();
+    second_node
+        .set_attribute_ns("urn:refs", "r:key", "shared")
+        .unwrap();
+    let _safe_to_ignore = root_node.append_child(first_node.clone()).unwrap();
+    let _safe_to_ignore = root_node.append_child(second_node.clone()).unwrap();
+
+    second_node
+        .set_id_attribute_ns("urn:refs", "key", true)
+        .unwrap();
+    assert_eq!(document.get_element_by_id("shared"), Some(second_node));
+
+    // a duplicate user-determined ID is not an error, the first in document order is found.
+    let key_node = first_node.get_attribute_node_ns("urn:refs", "key").unwrap();
+    first_node.set_id_attribute_node(key_node, true).unwrap();
+    assert_eq!(
+        document.get_element_by_id("shared"),
+        Some(first_node.clone())
+    );
+
+    let copy_node = first_node.clone_node(false).unwrap();
+    assert!(copy_node
+        .get_attribute_node_ns("urn:refs", "key")
+        .unwrap()
+        .is_id());
+
+    let xml_id_node = document.create_attribute("xml:id").unwrap();
+    assert!(xml_id_node.is_id());
+}
@@ -1,6 +1,11 @@
+use std::rc::Rc;
 use std::str::FromStr;
 use xml_dom::level2::convert::{as_document, as_document_type, as_element};
-use xml_dom::level2::{get_implementation, Name};
+use xml_dom::level2::dom_impl::Implementation;
+use xml_dom::level2::ext::dom_impl::document_implementation;
+use xml_dom::level2::ext::resolver::FileResolver;
+use xml_dom::level2::ext::{DOMImplementation as _, ProcessingOptions};
+use xml_dom::level2::{get_implementation, DOMImplementation, Element, Error, Name, Node};
 
 pub mod common;
 
@@ -88,3 +93,127 @@ fn test_create_document_type() {
     let stored_doc_type = document.doc_type().unwrap();
     assert_eq!(&document_type_node, &stored_doc_type);
 }
+
+#[test]
+fn test_document_implementation_default() {
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let implementation = document_implementation(&document_node).unwrap();
+    assert!(implementation.options().has_add_namespaces());
+    assert!(!implementation.is_id_attribute(&Name::from_str("id").unwrap()));
+    assert!(implementation.resolver().is_none());
+
+    let document = as_document(&document_node).unwrap();
+    assert!(document.implementation().has_feature("Core", "2.0"));
+
+    let root_node = document.document_element().unwrap();
+    assert_eq!(
+        document_implementation(&root_node).err(),
+        Some(Error::InvalidState)
+    );
+}
+
+#[test]
+fn test_document_implementation_configured() {
+    let mut options = ProcessingOptions::new();
+    options.set_assume_ids();
+    let mut implementation = Implementation::with_options(options.clone());
+    implementation.add_id_attribute("ref:key");
+    implementation.add_id_attribute("ref:key");
+    implementation.set_resolver(Rc::new(FileResolver::new(".")));
+
+    let document_node = implementation
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let carried = document_implementation(&document_node).unwrap();
+    assert_eq!(carried.options(), options);
+    assert!(carried.is_id_attribute(&Name::from_str("ref:key").unwrap()));
+    assert!(carried.resolver().is_some());
+
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut item_node = document.create_element("item").unwrap();
+    item_node.set_attribute("ref:key", "k-1").unwrap();
+    item_node.set_attribute("id", "i-1").unwrap();
+    let _safe_to_ignore = root_node.append_child(item_node.clone()).unwrap();
+    assert_eq!(document.get_element_by_id("k-1"), Some(item_node.clone()));
+    assert_eq!(document.get_element_by_id("i-1"), Some(item_node));
+
+    // documents from other implementations are unaffected.
+    let other_node = implementation
+        .create_document_with_options(None, Some("other"), None, ProcessingOptions::new())
+        .unwrap();
+    let other = as_document(&other_node).unwrap();
+    let mut item_node = other.create_element("item").unwrap();
+    item_node.set_attribute("id", "i-1").unwrap();
+    let _safe_to_ignore = other
+        .document_element()
+        .unwrap()
+        .append_child(item_node)
+        .unwrap();
+    assert!(other.get_element_by_id("i-1").is_none());
+
+    let default_node = get_implementation()
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let default = as_document(&default_node).unwrap();
+    let mut item_node = default.create_element("item").unwrap();
+    item_node.set_attribute("ref:key", "k-1").unwrap();
+    let _safe_to_ignore = default
+        .document_element()
+        .unwrap()
+        .append_child(item_node)
+        .unwrap();
+    assert!(default.get_element_by_id("k-1").is_none());
+}
+
+#[test]
+fn test_has_feature() {
+    let implementation = get_implementation();
+    assert!(implementation.has_feature("Core", "1.0"));
+    assert!(implementation.has_feature("core", "2.0"));
+    assert!(implementation.has_feature("XML", ""));
+    assert!(implementation.has_feature("Traversal", "2.0"));
+    assert!(!implementation.has_feature("Traversal", "1.0"));
+    assert!(!implementation.has_feature("Core", "3.0"));
+    assert!(!implementation.has_feature("org.example.catalog", ""));
+}
+
+#[test]
+fn test_registered_features() {
+    let mut implementation = Implementation::new();
+    implementation.add_feature("org.example.catalog", "1.0");
+    implementation.add_feature("org.example.catalog", "1.1");
+    implementation.add_feature("org.example.catalog", "1.0");
+    assert_eq!(
+        implementation.features(),
+        vec![
+            ("org.example.catalog".to_string(), "1.0".to_string()),
+            ("org.example.catalog".to_string(), "1.1".to_string()),
+        ]
+    );
+    assert!(implementation.has_feature("org.example.catalog", "1.1"));
+    assert!(implementation.has_feature("ORG.EXAMPLE.CATALOG", "1.0"));
+    assert!(implementation.has_feature("org.example.catalog", ""));
+    assert!(!implementation.has_feature("org.example.catalog", "2.0"));
+    assert!(implementation.has_feature("Core", "2.0"));
+
+    let document_node = implementation
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert!(document
+        .implementation()
+        .has_feature("org.example.catalog", "1.0"));
+    assert!(document_node.is_supported("org.example.catalog", "1.1"));
+    let item_node = document.create_element("item").unwrap();
+    assert!(item_node.is_supported("org.example.catalog", ""));
+    assert!(!item_node.is_supported("org.example.catalog", "2.0"));
+
+    let default_node = get_implementation()
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    assert!(!default_node.is_supported("org.example.catalog", "1.0"));
+    assert!(default_node.is_supported("XML", "2.0"));
+}
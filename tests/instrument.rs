@@ -0,0 +1,77 @@
+#![cfg(feature = "instrumentation")]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use xml_dom::level2::convert::{as_document, as_element};
+use xml_dom::level2::ext::instrument::{
+    clear_instrument, set_instrument, Counters, Instrument, Operation,
+};
+use xml_dom::level2::{Node, NodeType};
+
+pub mod common;
+
+#[derive(Debug, Default)]
+struct Operations(RefCell<Vec<Operation>>);
+
+impl Instrument for Operations {
+    fn record(&self, operation: Operation, _elapsed: Duration) {
+        self.0.borrow_mut().push(operation);
+    }
+}
+
+#[test]
+fn test_outermost_operations_recorded() {
+    let operations = Rc::new(Operations::default());
+    assert!(set_instrument(operations.clone()).is_none());
+
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("text"))
+        .unwrap();
+    let _results = document.get_elements_by_tag_name("*");
+    let _xml = document_node.to_string();
+
+    assert!(clear_instrument().is_some());
+    let _results = document.get_elements_by_tag_name("*");
+
+    assert_eq!(
+        *operations.0.borrow(),
+        vec![
+            Operation::Create(NodeType::Document),
+            Operation::Create(NodeType::Text),
+            Operation::Insert,
+            Operation::Query,
+            Operation::Serialize,
+        ]
+    );
+}
+
+#[test]
+fn test_counters() {
+    let counters = Rc::new(Counters::default());
+    let _previous = set_instrument(counters.clone());
+
+    let document_node = common::create_example_rdf_document();
+    let _results = as_document(&document_node)
+        .unwrap()
+        .get_elements_by_tag_name_ns(common::DC_NS, "*");
+    let root_node = document_node.first_child().unwrap();
+    let _results = as_element(&root_node)
+        .unwrap()
+        .get_elements_by_tag_name("dc:title");
+    let _counters = clear_instrument();
+
+    assert_eq!(counters.get(&Operation::Query).count, 2);
+    assert_eq!(counters.get(&Operation::Serialize).count, 0);
+    assert!(counters.get(&Operation::Insert).count > 0);
+    assert!(counters
+        .to_string()
+        .lines()
+        .any(|line| line.starts_with("query\t2\t")));
+
+    counters.reset();
+    assert!(counters.snapshot().is_empty());
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::{as_document, as_element};
+use xml_dom::level2::ext::lazy::{hydrate, is_hydrated, read_xml_lazy, LazyOptions};
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+pub mod common;
+
+const RECORDS: &str = "<records xmlns:p=\"urn:p\">\
+    <record id=\"1\"><name>one</name><p:note>a &amp; b</p:note></record>\
+    <record id=\"2\"><name>two</name></record>\
+    <record id=\"3\"/>\
+    </records>";
+
+fn by_name() -> LazyOptions {
+    LazyOptions {
+        elements: vec!["record".to_string()],
+        ..Default::default()
+    }
+}
+
+fn records(document_node: &RefNode) -> Vec<RefNode> {
+    let document = as_document(document_node).unwrap();
+    document.document_element().unwrap().child_nodes()
+}
+
+#[test]
+fn test_lazy_by_name() {
+    let document_node = read_xml_lazy(RECORDS, &by_name()).unwrap();
+    let records = records(&document_node);
+    assert_eq!(records.len(), 3);
+    assert!(!is_hydrated(&records[0]));
+    assert!(!is_hydrated(&records[1]));
+    assert!(is_hydrated(&records[2]));
+    assert_eq!(records[0].get_attribute("id"), Some("1".to_string()));
+
+    let children = records[0].child_nodes();
+    assert!(is_hydrated(&records[0]));
+    assert!(!is_hydrated(&records[1]));
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0].node_name().to_string(), "name");
+    assert_eq!(children[1].local_name(), "note");
+    assert_eq!(children[1].namespace_uri(), Some("urn:p".to_string()));
+    assert_eq!(
+        children[1].first_child().unwrap().node_value(),
+        Some("a & b".to_string())
+    );
+}
+
+#[test]
+fn test_lazy_by_depth() {
+    let document_node = read_xml_lazy(
+        "<a><b><c><d/></c></b><b><c/></b></a>",
+        &LazyOptions {
+            depth: Some(2),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let a = document.document_element().unwrap();
+    assert!(is_hydrated(&a));
+    let bs = a.child_nodes();
+    assert!(bs.iter().all(|b| !is_hydrated(b)));
+    let c = bs[0].first_child().unwrap();
+    assert!(is_hydrated(&c));
+    assert_eq!(c.first_child().unwrap().node_name().to_string(), "d");
+}
+
+#[test]
+fn test_lazy_serialize_matches_read_xml() {
+    let document_node = read_xml_lazy(RECORDS, &by_name()).unwrap();
+    let expected = read_xml(RECORDS).unwrap();
+    assert_eq!(document_node.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_lazy_queries_hydrate() {
+    let document_node = read_xml_lazy(RECORDS, &by_name()).unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert_eq!(document.get_elements_by_tag_name("name").len(), 2);
+    let document_node = read_xml_lazy(RECORDS, &by_name()).unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert_eq!(
+        document
+            .get_elements_by_tag_name_ns("urn:p", "note")
+            .first()
+            .map(|note| note.local_name()),
+        Some("note".to_string())
+    );
+}
+
+#[test]
+fn test_lazy_mutation_hydrates() {
+    let document_node = read_xml_lazy(RECORDS, &by_name()).unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut record = records(&document_node).remove(1);
+    let _safe_to_ignore = record
+        .append_child(document.create_element("extra").unwrap())
+        .unwrap();
+    assert_eq!(
+        record.to_string(),
+        r#"<record id="2"><name>two</name><extra></extra></record>"#
+    );
+}
+
+#[test]
+fn test_lazy_entity_references() {
+    let xml = r#"<!DOCTYPE r [<!ENTITY e "hello">]><r><record>&e; world</record></r>"#;
+    let document_node = read_xml_lazy(xml, &by_name()).unwrap();
+    let expected = read_xml(xml).unwrap();
+    assert_eq!(document_node.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_lazy_deep_clone() {
+    let document_node = read_xml_lazy(RECORDS, &by_name()).unwrap();
+    let record = records(&document_node).remove(0);
+    let copy = record.clone_node(true).unwrap();
+    assert_eq!(copy.to_string(), record.to_string());
+    let shallow = record.clone_node(false).unwrap();
+    assert!(is_hydrated(&shallow));
+    assert!(shallow.child_nodes().is_empty());
+}
+
+#[test]
+fn test_lazy_malformed_content() {
+    let document_node = read_xml_lazy(
+        r#"<records><record><name a="1" a="2"/></record></records>"#,
+        &by_name(),
+    )
+    .unwrap();
+    let record = records(&document_node).remove(0);
+    assert!(hydrate(&record).is_err());
+    assert!(!is_hydrated(&record));
+    assert!(record.child_nodes().is_empty());
+    assert!(as_element(&record).is_ok());
+}
@@ -0,0 +1,107 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::links::LinkConfig;
+use xml_dom::level2::ext::DocumentLinks;
+use xml_dom::level2::*;
+
+pub mod common;
+
+const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+
+#[cfg(feature = "quick_parser")]
+const DOCUMENT: &str = r#"<map xmlns:x="http://www.w3.org/1999/xlink" xmlns:o="urn:other" xml:base="http://example.com/maps/">
+<area x:href="north.xml" o:href="ignored.xml" data="data.bin"/>
+<group xml:base="/regions/">
+<area href="south.xml" src="south.png"/>
+<area x:href="urn:isbn:0451450523"/>
+</group>
+</map>"#;
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_extract_default_links() {
+    let document_node = xml_dom::parser::read_xml(DOCUMENT).unwrap();
+    let links = document_node.extract_links(&LinkConfig::default());
+    assert_eq!(
+        links
+            .iter()
+            .map(|link| link.uri.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "http://example.com/maps/north.xml",
+            "http://example.com/regions/south.xml",
+            "http://example.com/regions/south.png",
+            "urn:isbn:0451450523",
+        ]
+    );
+
+    let link = &links[1];
+    assert_eq!(link.element.node_name().to_string(), "area");
+    assert_eq!(link.attribute.node_name().to_string(), "href");
+    assert_eq!(link.value, "south.xml");
+    assert_eq!(
+        link.base_uri.as_deref(),
+        Some("http://example.com/regions/")
+    );
+    assert_eq!(links[0].attribute.node_name().to_string(), "x:href");
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_extract_configured_links() {
+    let document_node = xml_dom::parser::read_xml(DOCUMENT).unwrap();
+
+    let mut config = LinkConfig::empty();
+    config.add_attribute("data");
+    config.add_attribute_ns("urn:other", "href");
+    let links = document_node.extract_links(&config);
+    assert_eq!(
+        links
+            .iter()
+            .map(|link| link.uri.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "http://example.com/maps/data.bin",
+            "http://example.com/maps/ignored.xml",
+        ]
+    );
+
+    assert!(document_node.extract_links(&LinkConfig::empty()).is_empty());
+}
+
+#[test]
+fn test_extract_links_without_base() {
+    let document_node = get_implementation()
+        .create_document(None, Some("page"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut link_node = document.create_element("link").unwrap();
+    link_node
+        .set_attribute_ns(XLINK_NS, "xl:href", "../style.css")
+        .unwrap();
+    let _safe_to_ignore = root_node.append_child(link_node).unwrap();
+
+    let links = document_node.extract_links(&LinkConfig::default());
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].base_uri, None);
+    assert_eq!(links[0].uri, "../style.css");
+
+    let mut config = LinkConfig::default();
+    config.set_base_uri("site/pages/index.xml");
+    assert_eq!(config.base_uri(), Some(&"site/pages/index.xml".to_string()));
+    let links = document_node.extract_links(&config);
+    assert_eq!(links[0].uri, "site/style.css");
+
+    let mut document_node = document_node;
+    document_node.set_document_uri("http://example.com/pages/page.xml");
+    let links = document_node.extract_links(&LinkConfig::default());
+    assert_eq!(
+        links[0].base_uri.as_deref(),
+        Some("http://example.com/pages/page.xml")
+    );
+    assert_eq!(links[0].uri, "http://example.com/style.css");
+    let links = document_node.extract_links(&config);
+    assert_eq!(links[0].uri, "site/style.css");
+
+    assert!(root_node.extract_links(&config).is_empty());
+}
@@ -0,0 +1,211 @@
+use std::str::FromStr;
+use xml_dom::level2::convert::{as_document, as_element_mut};
+use xml_dom::level2::ext::compare::{semantic_eq, CompareOptions};
+use xml_dom::level2::ext::mutation::{Mutation, MutationLog, NewNode, Recorder};
+use xml_dom::level2::{get_implementation, Error, Node};
+
+pub mod common;
+
+#[test]
+fn test_record_and_replay() {
+    let implementation = get_implementation();
+    let document_type = implementation
+        .create_document_type("rdf:RDF", None, Some("rdf.dtd"))
+        .unwrap();
+    let document_node = implementation
+        .create_document(Some(common::RDF_NS), Some("rdf:RDF"), Some(document_type))
+        .unwrap();
+    let mut recorder = Recorder::new(&document_node).unwrap();
+
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+
+    //
+    // A subtree built outside the document is recorded when inserted.
+    //
+    let mut description_node = document
+        .create_element_ns(common::RDF_NS, "rdf:Description")
+        .unwrap();
+    {
+        let description = as_element_mut(&mut description_node).unwrap();
+        description
+            .set_attribute_ns(common::RDF_NS, "rdf:about", "http://example.org/\"quoted\"")
+            .unwrap();
+        let _safe_to_ignore = description
+            .append_child(document.create_text_node("line one\nline two"))
+            .unwrap();
+    }
+    let description_node = recorder.append_child(&root_node, description_node).unwrap();
+    let comment_node = recorder
+        .insert_before(
+            &root_node,
+            document.create_comment("first"),
+            Some(description_node.clone()),
+        )
+        .unwrap();
+    recorder
+        .set_attribute(&description_node, "lang", "en")
+        .unwrap();
+    recorder
+        .set_data(&description_node.first_child().unwrap(), "replaced")
+        .unwrap();
+    let _safe_to_ignore = recorder.remove_child(&root_node, comment_node).unwrap();
+
+    let text = recorder.log().to_string();
+    let log = MutationLog::from_str(&text).unwrap();
+    assert_eq!(&log, recorder.log());
+
+    let replayed_node = log.replay().unwrap();
+    assert!(semantic_eq(
+        &replayed_node,
+        &document_node,
+        &CompareOptions {
+            ignore_whitespace: false,
+            cdata_as_text: false,
+            ignore_comments: false,
+        }
+    ));
+}
+
+#[test]
+fn test_wrong_document() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let other_node = implementation
+        .create_document(None, Some("other"), None)
+        .unwrap();
+    let mut recorder = Recorder::new(&document_node).unwrap();
+
+    let other_root = as_document(&other_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(
+        recorder.set_attribute(&other_root, "id", "1"),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(recorder.log().len(), 2);
+}
+
+#[test]
+fn test_parse_mutations() {
+    let mutation = Mutation::from_str("insert /0/1 2 pi \"target\" -").unwrap();
+    assert_eq!(
+        mutation,
+        Mutation::Insert {
+            parent: vec![0, 1],
+            index: 2,
+            node: NewNode::ProcessingInstruction {
+                target: "target".to_string(),
+                data: None
+            }
+        }
+    );
+    assert_eq!(
+        mutation.to_string(),
+        "insert /0/1 2 pi \"target\" -".to_string()
+    );
+
+    assert_eq!(Mutation::from_str("remove 0"), Err(Error::Syntax));
+    assert_eq!(Mutation::from_str("remove /0 /1"), Err(Error::Syntax));
+    assert_eq!(Mutation::from_str("set-data /0 \"open"), Err(Error::Syntax));
+    assert_eq!(Mutation::from_str("rename /0 \"a\""), Err(Error::Syntax));
+
+    let log = MutationLog::from_str("remove /0").unwrap();
+    assert_eq!(log.replay(), Err(Error::InvalidState));
+}
+
+#[test]
+fn test_at_revision() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let mut recorder = Recorder::new(&document_node).unwrap();
+    assert_eq!(recorder.revision(), 1);
+
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let _safe_to_ignore = recorder
+        .append_child(&root_node, document.create_text_node("one"))
+        .unwrap();
+    let before = recorder.revision();
+    let _safe_to_ignore = recorder
+        .append_child(&root_node, document.create_text_node("two"))
+        .unwrap();
+    assert_eq!(recorder.revision(), before + 1);
+
+    assert_eq!(recorder.at_revision(0).unwrap().to_string(), "");
+    assert_eq!(
+        recorder.at_revision(before).unwrap().to_string(),
+        "<root>one</root>"
+    );
+    assert_eq!(
+        recorder
+            .at_revision(recorder.revision())
+            .unwrap()
+            .to_string(),
+        "<root>onetwo</root>"
+    );
+    assert_eq!(
+        recorder.at_revision(recorder.revision() + 1),
+        Err(Error::IndexSize)
+    );
+}
+
+#[test]
+fn test_snapshot_attribute_order() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    {
+        let root = as_element_mut(&mut root_node).unwrap();
+        root.set_attribute("z", "1").unwrap();
+        root.set_attribute_ns(common::XMLNS_NS, "xmlns:p", "urn:p")
+            .unwrap();
+        root.set_attribute_ns("urn:p", "p:x", "2").unwrap();
+        root.set_attribute("a", "3").unwrap();
+    }
+
+    let replayed_node = MutationLog::snapshot(&document_node)
+        .unwrap()
+        .replay()
+        .unwrap();
+    assert_eq!(replayed_node.to_string(), document_node.to_string());
+    assert_eq!(
+        replayed_node.to_string(),
+        r#"<root z="1" xmlns:p="urn:p" p:x="2" a="3"></root>"#
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_snapshot_entity_reference() {
+    use xml_dom::level2::NodeType;
+    use xml_dom::parser::read_xml;
+
+    let document_node =
+        read_xml(r#"<!DOCTYPE p [<!ENTITY e "hello <b>x</b>">]><p>&e;</p>"#).unwrap();
+
+    let log = MutationLog::snapshot(&document_node).unwrap();
+    assert!(log
+        .mutations()
+        .iter()
+        .all(|mutation| !matches!(mutation, Mutation::Insert { parent, .. } if parent.len() > 1)));
+
+    let replayed_node = log.replay().unwrap();
+    let root_node = as_document(&replayed_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let reference_node = root_node.first_child().unwrap();
+    assert_eq!(reference_node.node_type(), NodeType::EntityReference);
+    assert_eq!(reference_node.node_name().to_string(), "e");
+}
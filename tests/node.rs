@@ -1,5 +1,8 @@
 use xml_dom::level2::convert::*;
 use xml_dom::level2::ext::dom_impl as ext_dom_impl;
+use xml_dom::level2::ext::{
+    DocumentTypeDecl, DocumentUnparsed, Namespaced, NodeBorrow, NodeOwner, ProcessingOptions,
+};
 use xml_dom::level2::*;
 
 pub mod common;
@@ -27,28 +30,9 @@ fn test_is_child_allowed() {
         ),
         (NodeType::Text, vec![]),
         (NodeType::CData, vec![]),
-        (
-            NodeType::EntityReference,
-            vec![
-                NodeType::Element,
-                NodeType::Text,
-                NodeType::Comment,
-                NodeType::ProcessingInstruction,
-                NodeType::CData,
-                NodeType::EntityReference,
-            ],
-        ),
-        (
-            NodeType::Entity,
-            vec![
-                NodeType::Element,
-                NodeType::Text,
-                NodeType::Comment,
-                NodeType::ProcessingInstruction,
-                NodeType::CData,
-                NodeType::EntityReference,
-            ],
-        ),
+        // entity references and entities are read-only, see `test_read_only_nodes`.
+        (NodeType::EntityReference, vec![]),
+        (NodeType::Entity, vec![]),
         (NodeType::ProcessingInstruction, vec![]),
         (NodeType::Comment, vec![]),
         (NodeType::DocumentType, vec![]),
@@ -418,6 +402,406 @@ fn test_wrong_document() {
     assert_eq!(result, Err(Error::WrongDocument))
 }
 
+#[test]
+fn test_wrong_document_insert_and_replace() {
+    let mut document_1_node = make_sibling_document();
+    let document_2_node = make_sibling_document();
+    let mut root_1_node = as_document(&document_1_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let document_2 = as_document(&document_2_node).unwrap();
+
+    let foreign_node = document_2.create_element("foreign").unwrap();
+    let first_node = root_1_node.first_child();
+    let result = root_1_node.insert_before(foreign_node.clone(), first_node.clone());
+    assert_eq!(result, Err(Error::WrongDocument));
+    let result = root_1_node.replace_child(foreign_node, first_node.unwrap());
+    assert_eq!(result, Err(Error::WrongDocument));
+    assert_eq!(root_1_node.child_nodes().len(), 5);
+
+    let comment_node = document_2.create_comment("foreign");
+    let result = document_1_node.append_child(comment_node);
+    assert_eq!(result, Err(Error::WrongDocument));
+    assert_eq!(document_1_node.child_nodes().len(), 1);
+}
+
+#[test]
+fn test_verify_owner() {
+    let document_1_node = make_sibling_document();
+    let document_2_node = make_sibling_document();
+    let root_1_node = as_document(&document_1_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(document_1_node.verify_owner(&document_1_node), Ok(()));
+    assert_eq!(root_1_node.verify_owner(&document_1_node), Ok(()));
+    assert_eq!(
+        root_1_node.verify_owner(&document_2_node),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(
+        document_1_node.verify_owner(&document_2_node),
+        Err(Error::WrongDocument)
+    );
+    let doc_type_node = get_implementation()
+        .create_document_type("root", None, None)
+        .unwrap();
+    assert_eq!(
+        doc_type_node.verify_owner(&document_1_node),
+        Err(Error::WrongDocument)
+    );
+}
+
+#[test]
+fn test_owner_document_options() {
+    let mut options = ProcessingOptions::new();
+    options.set_strict_owner_document();
+    options.set_auto_import();
+    let document_1_node = ext_dom_impl::get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let document_1 = as_document(&document_1_node).unwrap();
+    let mut root_1_node = document_1.document_element().unwrap();
+    let _safe_to_ignore = append_element_node(&mut root_1_node, "local");
+
+    let document_2_node = make_sibling_document();
+    let mut foreign_node = as_document(&document_2_node)
+        .unwrap()
+        .create_element("foreign")
+        .unwrap();
+    foreign_node.set_attribute("kind", "copy").unwrap();
+    let _safe_to_ignore = append_element_node(&mut foreign_node, "child");
+
+    let imported = root_1_node.append_child(foreign_node.clone()).unwrap();
+    assert_ne!(imported, foreign_node);
+    assert_eq!(imported.owner_document(), Some(document_1_node.clone()));
+    assert_eq!(foreign_node.owner_document(), Some(document_2_node));
+    assert_eq!(imported.get_attribute("kind").as_deref(), Some("copy"));
+    assert_eq!(imported.child_nodes().len(), 1);
+    assert_eq!(imported.verify_owner(&document_1_node), Ok(()));
+    assert_eq!(root_1_node.verify_owner(&document_1_node), Ok(()));
+    assert_eq!(
+        root_1_node.to_string(),
+        r#"<root><local></local><foreign kind="copy"><child></child></foreign></root>"#
+    );
+}
+
+#[test]
+fn test_ancestor_insertion() {
+    let document_node = make_sibling_document();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let mut child_node = root_node.first_child().unwrap();
+    let mut grandchild_node = append_element_node(&mut child_node, "grandchild");
+
+    let result = root_node.clone().append_child(root_node.clone());
+    assert_eq!(result, Err(Error::HierarchyRequest));
+    let result = grandchild_node.append_child(root_node.clone());
+    assert_eq!(result, Err(Error::HierarchyRequest));
+    let result = grandchild_node.insert_before(child_node.clone(), None);
+    assert_eq!(result, Err(Error::HierarchyRequest));
+    let result = child_node.replace_child(root_node.clone(), grandchild_node.clone());
+    assert_eq!(result, Err(Error::HierarchyRequest));
+
+    // the tree is unchanged, and finite.
+    assert_eq!(grandchild_node.parent_node(), Some(child_node.clone()));
+    assert_eq!(child_node.parent_node(), Some(root_node.clone()));
+    assert_eq!(root_node.child_nodes().len(), 5);
+    assert!(document_node
+        .to_string()
+        .contains("<grandchild></grandchild>"));
+
+    // moving a node down into a sibling is still allowed.
+    let sibling_node = root_node.last_child().unwrap();
+    assert!(grandchild_node.append_child(sibling_node).is_ok());
+    assert_eq!(root_node.child_nodes().len(), 4);
+}
+
+#[test]
+fn test_with_name_and_value() {
+    let document_node = make_sibling_document();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    assert!(root_node.with_name(|name| name.local_name() == "root"));
+    assert_eq!(root_node.with_value(|value| value.map(String::from)), None);
+
+    for node in &[
+        document.create_text_node("a < b"),
+        document.create_comment("comment"),
+        document.create_cdata_section("<cdata/>").unwrap(),
+    ] {
+        assert_eq!(
+            node.with_value(|value| value.map(String::from)),
+            node.node_value()
+        );
+        assert_eq!(node.with_name(|name| name.clone()), node.node_name());
+    }
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_lookup_namespace_any_node() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<a:root xmlns:a="urn:a" xmlns="urn:d"><b:child xmlns:b="urn:b" xmlns:c="urn:a" id="1">text</b:child><leaf xmlns="" xmlns:a="urn:x"/></a:root>"#,
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let child_node = root_node.first_child().unwrap();
+    let text_node = child_node.first_child().unwrap();
+    let leaf_node = root_node.last_child().unwrap();
+    let attribute_node = child_node.get_attribute_node("id").unwrap();
+
+    assert_eq!(
+        text_node.lookup_namespace_uri(Some("b")),
+        Some("urn:b".to_string())
+    );
+    assert_eq!(
+        text_node.lookup_namespace_uri(Some("a")),
+        Some("urn:a".to_string())
+    );
+    assert_eq!(
+        attribute_node.lookup_namespace_uri(None),
+        Some("urn:d".to_string())
+    );
+    assert_eq!(
+        document_node.lookup_namespace_uri(Some("a")),
+        Some("urn:a".to_string())
+    );
+    assert_eq!(leaf_node.lookup_namespace_uri(None), None);
+    assert_eq!(
+        leaf_node.lookup_namespace_uri(Some("a")),
+        Some("urn:x".to_string())
+    );
+
+    let fragment_node = document.create_document_fragment().unwrap();
+    assert_eq!(fragment_node.lookup_namespace_uri(Some("a")), None);
+    assert_eq!(fragment_node.lookup_prefix("urn:a"), None);
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_lookup_prefix_and_default_namespace() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<a:root xmlns:a="urn:a" xmlns="urn:d"><b:child xmlns:b="urn:b" xmlns:c="urn:a">text</b:child><leaf xmlns="" xmlns:a="urn:x"/></a:root>"#,
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let child_node = root_node.first_child().unwrap();
+    let text_node = child_node.first_child().unwrap();
+    let leaf_node = root_node.last_child().unwrap();
+
+    assert_eq!(document_node.lookup_prefix("urn:a"), Some("a".to_string()));
+    assert_eq!(text_node.lookup_prefix("urn:b"), Some("b".to_string()));
+    assert_eq!(child_node.lookup_prefix("urn:a"), Some("c".to_string()));
+    // the prefix "a" is re-declared by the leaf, so is not in scope for "urn:a".
+    assert_eq!(leaf_node.lookup_prefix("urn:a"), None);
+    assert_eq!(leaf_node.lookup_prefix("urn:x"), Some("a".to_string()));
+    assert_eq!(root_node.lookup_prefix("urn:d"), None);
+    assert_eq!(root_node.lookup_prefix(""), None);
+
+    assert!(root_node.is_default_namespace("urn:d"));
+    assert!(text_node.is_default_namespace("urn:d"));
+    assert!(!root_node.is_default_namespace("urn:a"));
+    assert!(leaf_node.is_default_namespace(""));
+    assert!(!leaf_node.is_default_namespace("urn:d"));
+}
+
+#[test]
+fn test_read_only_nodes() {
+    let mut doc_type_node = get_implementation()
+        .create_document_type("doc", None, None)
+        .unwrap();
+    let mut entity_node = doc_type_node.create_entity("status", "draft").unwrap();
+    let mut notation_node = doc_type_node
+        .create_notation("png", None, Some("image/png"))
+        .unwrap();
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), Some(doc_type_node.clone()))
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let text_node = document.create_text_node("text");
+    assert_eq!(
+        doc_type_node.append_child(text_node.clone()),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        entity_node.append_child(text_node.clone()),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        entity_node.set_node_value("final"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        notation_node.set_node_value("x"),
+        Err(Error::NoModificationAllowed)
+    );
+    let mut replacement_node = entity_node.first_child().unwrap();
+    assert_eq!(
+        as_character_data_mut(&mut replacement_node)
+            .unwrap()
+            .append_data("!"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        entity_node.remove_child(replacement_node.clone()),
+        Err(Error::NoModificationAllowed)
+    );
+
+    let mut reference_node = document.create_entity_reference("status").unwrap();
+    let mut reference_text = reference_node.first_child().unwrap();
+    assert_eq!(reference_text.node_value().as_deref(), Some("draft"));
+    assert_ne!(reference_text, replacement_node);
+    assert_eq!(
+        reference_text.set_node_value("final"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        reference_node.remove_child(reference_text.clone()),
+        Err(Error::NoModificationAllowed)
+    );
+    // a read-only child may not be moved out of its parent.
+    assert_eq!(
+        root_node.append_child(reference_text.clone()),
+        Err(Error::NoModificationAllowed)
+    );
+
+    // the reference itself may be inserted, and a copy of its content is not read-only.
+    let _safe_to_ignore = root_node.append_child(reference_node.clone()).unwrap();
+    let mut copy_node = reference_text.clone_node(true).unwrap();
+    copy_node.set_node_value("final").unwrap();
+    let reference_copy = reference_node.clone_node(true).unwrap();
+    assert_eq!(
+        reference_copy
+            .first_child()
+            .unwrap()
+            .set_node_value("final"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        root_node.remove_child(reference_node.clone()),
+        Ok(reference_node)
+    );
+}
+
+#[test]
+fn test_declare_namespaces() {
+    let mut options = ProcessingOptions::new();
+    options.set_add_namespaces();
+    let document_node = ext_dom_impl::get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    root_node
+        .declare_namespaces(&[
+            (None, "urn:d"),
+            (Some("a"), "urn:a"),
+            (Some("b"), "urn:b"),
+            (Some("a"), "urn:a"),
+        ])
+        .unwrap();
+    assert_eq!(root_node.attributes().len(), 3);
+    assert_eq!(root_node.get_attribute("xmlns:b").as_deref(), Some("urn:b"));
+    assert_eq!(root_node.get_namespace(Some("a")).as_deref(), Some("urn:a"));
+    assert_eq!(root_node.get_namespace(None).as_deref(), Some("urn:d"));
+    assert_eq!(root_node.lookup_prefix("urn:b"), Some("b".to_string()));
+
+    // a repeated declaration is ignored, a conflicting one leaves the element unchanged.
+    root_node
+        .declare_namespaces(&[(Some("b"), "urn:b")])
+        .unwrap();
+    assert_eq!(
+        root_node.declare_namespaces(&[(Some("c"), "urn:c"), (Some("a"), "urn:x")]),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        root_node.declare_namespaces(&[(Some("c"), "urn:c"), (Some("c"), "urn:x")]),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        root_node.declare_namespaces(&[(Some("xmlns"), "urn:c")]),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        root_node.declare_namespaces(&[(Some("not valid"), "urn:c")]),
+        Err(Error::InvalidCharacter)
+    );
+    assert_eq!(root_node.attributes().len(), 3);
+    assert!(!root_node.contains_mapping(Some("c")));
+
+    // a child may re-declare a prefix of its ancestor.
+    let mut child_node = append_element_node(&mut root_node, "child");
+    child_node
+        .declare_namespaces(&[(Some("a"), "urn:x")])
+        .unwrap();
+    assert_eq!(
+        child_node.lookup_namespace_uri(Some("a")).as_deref(),
+        Some("urn:x")
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_base_uri() {
+    let mut document_node = xml_dom::parser::read_xml(
+        r#"<site xml:base="docs/"><page xml:base="guide/intro.xml"><?pi data?><img src="a.png"/></page><other xml:base="http://cdn.example.org/"/></site>"#,
+    )
+    .unwrap();
+    let root_node = document_node.document_element().unwrap();
+    let page_node = root_node.first_child().unwrap();
+    let pi_node = page_node.first_child().unwrap();
+    let img_node = page_node.last_child().unwrap();
+    let other_node = root_node.last_child().unwrap();
+    let src_node = img_node.get_attribute_node("src").unwrap();
+
+    assert_eq!(document_node.base_uri(), None);
+    assert_eq!(root_node.base_uri(), Some("docs/".to_string()));
+
+    document_node.set_document_uri("http://example.com/index.xml");
+    assert_eq!(
+        document_node.base_uri(),
+        Some("http://example.com/index.xml".to_string())
+    );
+    assert_eq!(
+        root_node.base_uri(),
+        Some("http://example.com/docs/".to_string())
+    );
+    for node in &[&page_node, &pi_node, &img_node, &src_node] {
+        assert_eq!(
+            node.base_uri(),
+            Some("http://example.com/docs/guide/intro.xml".to_string())
+        );
+    }
+    assert_eq!(
+        other_node.base_uri(),
+        Some("http://cdn.example.org/".to_string())
+    );
+
+    let detached_node = document_node.create_element("detached").unwrap();
+    assert_eq!(
+        detached_node.base_uri(),
+        Some("http://example.com/index.xml".to_string())
+    );
+    let copy_node = document_node.clone_node(false).unwrap();
+    assert_eq!(
+        copy_node.document_uri(),
+        Some("http://example.com/index.xml".to_string())
+    );
+
+    document_node.unset_document_uri();
+    assert_eq!(document_node.base_uri(), None);
+    assert_eq!(pi_node.base_uri(), Some("docs/guide/intro.xml".to_string()));
+}
+
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
@@ -454,6 +838,7 @@ fn make_node(document: RefNode, node_type: NodeType, prefix: &str) -> RefNode {
             ext_dom_impl::create_notation(document, &named("notation"), Some("file-name.xml"), None)
                 .unwrap()
         }
+        NodeType::UnparsedMarkup => document.create_unparsed_markup(&named("unparsed")),
     }
 }
 
@@ -472,7 +857,7 @@ const ALL_CHILDREN: [NodeType; 12] = [
     NodeType::Notation,
 ];
 
-fn test_parent(document: RefNode, parent_type: NodeType, allowed: &Vec<NodeType>) {
+fn test_parent(document: RefNode, parent_type: NodeType, allowed: &[NodeType]) {
     let mut parent_node = make_node(document.clone(), parent_type.clone(), "parent");
     for child_type in ALL_CHILDREN.iter() {
         common::sub_test(
@@ -481,13 +866,13 @@ fn test_parent(document: RefNode, parent_type: NodeType, allowed: &Vec<NodeType>
                 "{:?}.append_child({:?}) -> {}?",
                 parent_type,
                 child_type,
-                allowed.contains(&child_type)
+                allowed.contains(child_type)
             ),
         );
         let child_node = make_node(document.clone(), child_type.clone(), "child");
         assert_eq!(
             parent_node.append_child(child_node).is_ok(),
-            allowed.contains(&child_type)
+            allowed.contains(child_type)
         );
     }
 }
@@ -534,8 +919,62 @@ fn make_sibling_document() -> RefNode {
     document_node
 }
 
-fn compare_node_names(nodes: &Vec<RefNode>, expected_names: &[&str]) {
+fn compare_node_names(nodes: &[RefNode], expected_names: &[&str]) {
     let names: Vec<String> = nodes.iter().map(|n| n.node_name().to_string()).collect();
     let expected_names: Vec<String> = expected_names.iter().map(|s| String::from(*s)).collect();
     assert_eq!(names, expected_names);
 }
+
+#[test]
+fn test_node_handles() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("list"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("id", "top").unwrap();
+    let attribute_node = root_node.get_attribute_node("id").unwrap();
+    let item_node = document.create_element("item").unwrap();
+    assert!(document_node.is_attached());
+    assert!(root_node.is_attached());
+    assert!(attribute_node.is_attached());
+    assert!(!item_node.is_attached());
+
+    let mut item_node = root_node.append_child(item_node).unwrap();
+    assert!(item_node.is_attached());
+    let _safe_to_ignore = root_node.remove_child(item_node.clone()).unwrap();
+    assert!(!item_node.is_attached());
+    item_node.set_attribute("n", "1").unwrap();
+    let _safe_to_ignore = root_node.append_child(item_node.clone()).unwrap();
+    assert!(item_node.is_attached());
+
+    //
+    // Nodes outlive their document, detached, and may be adopted by another.
+    //
+    drop(document_node);
+    assert!(!item_node.is_attached());
+    assert!(!root_node.is_attached());
+    assert!(item_node.owner_document().is_none());
+    assert!(root_node.parent_node().is_none());
+    assert_eq!(item_node.parent_node(), Some(root_node.clone()));
+    assert_eq!(item_node.get_attribute("n").as_deref(), Some("1"));
+    assert_eq!(item_node.set_attribute("n", "2"), Err(Error::InvalidState));
+
+    let other_node = implementation
+        .create_document(None, Some("other"), None)
+        .unwrap();
+    let mut other_root = as_document(&other_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let mut item_node = other_root.append_child(item_node).unwrap();
+    assert!(item_node.is_attached());
+    assert!(!root_node.has_child_nodes());
+    assert_eq!(item_node.owner_document(), Some(other_node.clone()));
+    item_node.set_attribute("n", "2").unwrap();
+    assert_eq!(
+        other_node.to_string(),
+        r#"<other><item n="2"></item></other>"#
+    );
+}
@@ -0,0 +1,137 @@
+use xml_dom::level2::convert::{as_attribute, as_document};
+use xml_dom::level2::ext::NodeLists;
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_live_child_nodes() {
+    let document_node = get_implementation()
+        .create_document(None, Some("list"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let children = root_node.live_child_nodes();
+    let snapshot = root_node.child_nodes();
+    assert!(children.is_empty());
+
+    let first_node = root_node
+        .append_child(document.create_element("first").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("text"))
+        .unwrap();
+    assert!(snapshot.is_empty());
+    assert_eq!(children.length(), 2);
+    assert_eq!(children.item(0), Some(first_node.clone()));
+    assert_eq!(children.item(1).unwrap().node_type(), NodeType::Text);
+    assert_eq!(children.item(2), None);
+    assert_eq!(children.to_vec(), root_node.child_nodes());
+
+    let _safe_to_ignore = root_node.remove_child(first_node).unwrap();
+    assert_eq!(children.length(), 1);
+    let names: Vec<String> = (&children)
+        .into_iter()
+        .map(|node| node.node_name().to_string())
+        .collect();
+    assert_eq!(names, vec!["#text"]);
+}
+
+#[test]
+fn test_live_elements_by_tag_name() {
+    let document_node = get_implementation()
+        .create_document(None, Some("order"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let items = document_node.live_elements_by_tag_name("item");
+    let everything = document_node.live_elements_by_tag_name("*");
+    let namespaced = root_node.live_elements_by_tag_name_ns("urn:example:order", "item");
+    assert_eq!(items.length(), 0);
+    assert_eq!(everything.length(), 1);
+
+    let mut line_node = root_node
+        .append_child(document.create_element("line").unwrap())
+        .unwrap();
+    let first_item = line_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+    let second_item = root_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+    let ns_item = root_node
+        .append_child(
+            document
+                .create_element_ns("urn:example:order", "o:item")
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        items.to_vec(),
+        Document::get_elements_by_tag_name(&document_node, "item")
+    );
+    assert_eq!(items.to_vec(), vec![first_item.clone(), second_item]);
+    assert_eq!(everything.length(), 5);
+    assert_eq!(namespaced.to_vec(), vec![ns_item]);
+
+    let _safe_to_ignore = line_node.remove_child(first_item).unwrap();
+    assert_eq!(items.length(), 1);
+    assert_eq!(everything.item(1).unwrap().node_name().to_string(), "line");
+
+    let text_node = document.create_text_node("text");
+    assert!(text_node.live_elements_by_tag_name("*").is_empty());
+}
+
+#[test]
+fn test_live_attributes() {
+    let document_node = get_implementation()
+        .create_document(None, Some("item"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let attributes = root_node.live_attributes();
+    assert!(attributes.is_empty());
+
+    for name in ["zeta", "alpha", "mid", "beta"].iter() {
+        root_node.set_attribute(name, name).unwrap();
+    }
+    root_node
+        .set_attribute_ns("urn:x", "x:alpha", "namespaced")
+        .unwrap();
+    assert_eq!(attributes.length(), 5);
+    assert_eq!(
+        attributes
+            .names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>(),
+        vec!["zeta", "alpha", "mid", "beta", "x:alpha"]
+    );
+    assert_eq!(root_node.attribute_names(), attributes.names());
+
+    root_node.set_attribute("alpha", "replaced").unwrap();
+    root_node.remove_attribute("mid").unwrap();
+    assert_eq!(
+        attributes
+            .iter()
+            .map(|attribute| as_attribute(&attribute).unwrap().value().unwrap())
+            .collect::<Vec<String>>(),
+        vec!["zeta", "replaced", "beta", "namespaced"]
+    );
+    assert_eq!(attributes.get_named_item("beta"), attributes.item(2));
+    assert_eq!(
+        attributes.get_named_item_ns("urn:x", "alpha"),
+        attributes.item(3)
+    );
+    assert!(attributes.item(4).is_none());
+    assert_eq!(
+        root_node.to_string(),
+        r#"<item zeta="zeta" alpha="replaced" beta="beta" x:alpha="namespaced"></item>"#
+    );
+
+    let text_node = document.create_text_node("text");
+    assert!(text_node.live_attributes().is_empty());
+}
@@ -0,0 +1,275 @@
+use xml_dom::level2::convert::{as_document, as_element_mut};
+use xml_dom::level2::ext::normalize::{normalize_for_diff, DOMConfiguration, NormalizeOptions};
+use xml_dom::level2::ext::DocumentNormalize;
+use xml_dom::level2::{get_implementation, Element, Node, RefNode};
+
+pub mod common;
+
+const XMLNS_NS: &str = "http://www.w3.org/2000/xmlns/";
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+fn make_document(prefix: &str, attributes: &[(&str, &str)], padding: &str) -> RefNode {
+    let implementation = get_implementation();
+    let qualified_name = format!("{}:RDF", prefix);
+    let document_node = implementation
+        .create_document(Some(common::RDF_NS), Some(&qualified_name), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node
+        .set_attribute_ns(XMLNS_NS, &format!("xmlns:{}", prefix), common::RDF_NS)
+        .unwrap();
+
+    let mut description_node = document
+        .create_element_ns(common::RDF_NS, &format!("{}:Description", prefix))
+        .unwrap();
+    for (name, value) in attributes {
+        description_node.set_attribute(name, value).unwrap();
+    }
+    let _safe_to_ignore = description_node
+        .append_child(document.create_text_node(&format!("{}A  guide{}", padding, padding)))
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node(padding))
+        .unwrap();
+    let _safe_to_ignore = root_node.append_child(description_node).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("empty").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment(" note "))
+        .unwrap();
+    document_node
+}
+
+#[test]
+fn test_equivalent_documents() {
+    let one = make_document("rdf", &[("id", "1"), ("lang", "en")], "\n  ");
+    let two = make_document("r", &[("lang", "en"), ("id", "1")], " ");
+
+    let options = NormalizeOptions::default();
+    let normalized = normalize_for_diff(&one, &options).unwrap();
+    assert_eq!(normalized, normalize_for_diff(&two, &options).unwrap());
+    assert_eq!(
+        normalized,
+        format!(
+            "<ns1:RDF xmlns:ns1=\"{}\">\n  \
+             <ns1:Description id=\"1\" lang=\"en\">A guide</ns1:Description>\n  \
+             <empty></empty>\n  \
+             <!-- note -->\n\
+             </ns1:RDF>\n",
+            common::RDF_NS
+        )
+    );
+}
+
+#[test]
+fn test_preserve_and_prefixes() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let mut code_node = document.create_element("code").unwrap();
+    {
+        let code = as_element_mut(&mut code_node).unwrap();
+        code.set_attribute_ns(XML_NS, "xml:space", "preserve")
+            .unwrap();
+        let _safe_to_ignore = code
+            .append_child(document.create_text_node("  a\n  b "))
+            .unwrap();
+        let mut inner_node = document.create_element_ns(common::DC_NS, "dc:i").unwrap();
+        let _safe_to_ignore = inner_node
+            .append_child(document.create_text_node(" c "))
+            .unwrap();
+        let _safe_to_ignore = code.append_child(inner_node).unwrap();
+    }
+    let _safe_to_ignore = root_node.append_child(code_node).unwrap();
+
+    let mut other_node = document
+        .create_element_ns(common::DC_NS, "x:title")
+        .unwrap();
+    other_node.set_attribute("xmlns:x", common::DC_NS).unwrap();
+    let _safe_to_ignore = root_node.append_child(other_node).unwrap();
+
+    assert_eq!(
+        normalize_for_diff(&root_node, &NormalizeOptions::default()).unwrap(),
+        format!(
+            "<root>\n  \
+             <code xml:space=\"preserve\">  a\n  b <ns1:i xmlns:ns1=\"{0}\"> c </ns1:i></code>\n  \
+             <ns1:title xmlns:ns1=\"{0}\"></ns1:title>\n\
+             </root>\n",
+            common::DC_NS
+        )
+    );
+
+    let options = NormalizeOptions {
+        indent: "\t".to_string(),
+        normalize_prefixes: false,
+    };
+    let normalized = normalize_for_diff(&root_node, &options).unwrap();
+    assert!(normalized.ends_with(&format!(
+        "\t<x:title xmlns:x=\"{}\"></x:title>\n</root>\n",
+        common::DC_NS
+    )));
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_normalize_document_defaults() {
+    let mut document_node =
+        xml_dom::parser::read_xml("<list><!-- note --><item><![CDATA[a < b]]></item>\n</list>")
+            .unwrap();
+    let expected = document_node.to_string();
+    {
+        let document = as_document(&document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let _safe_to_ignore = root_node
+            .append_child(document.create_text_node(""))
+            .unwrap();
+    }
+
+    document_node
+        .normalize_document(&DOMConfiguration::default())
+        .unwrap();
+    assert_eq!(document_node.to_string(), expected);
+}
+
+#[test]
+fn test_normalize_document_parameters() {
+    let mut config = DOMConfiguration::default();
+    assert!(config.get_parameter("comments").unwrap());
+    config.set_parameter("comments", false).unwrap();
+    assert!(!config.comments);
+    assert!(config.can_set_parameter("element-content-whitespace"));
+    assert!(!config.can_set_parameter("validate"));
+    assert_eq!(config.parameter_names().len(), 6);
+    assert!(config.set_parameter("validate", true).is_err());
+    assert!(config.get_parameter("validate").is_err());
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_normalize_document_cleanup() {
+    let mut document_node = xml_dom::parser::read_xml(
+        r#"<!DOCTYPE list [<!ENTITY org " Example Corp">]><list><!-- first --><item>one<![CDATA[a < b]]></item><code xml:space="preserve"/></list>"#,
+    )
+    .unwrap();
+    {
+        let document = as_document(&document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let mut item_node = document.get_elements_by_tag_name("item").pop().unwrap();
+        let _safe_to_ignore = root_node
+            .insert_before(document.create_text_node("\n  "), Some(item_node.clone()))
+            .unwrap();
+        let entity_reference = document.create_entity_reference("org").unwrap();
+        assert_eq!(entity_reference.child_nodes().len(), 1);
+        let _safe_to_ignore = item_node.append_child(entity_reference).unwrap();
+
+        let mut code_node = document.get_elements_by_tag_name("code").pop().unwrap();
+        let _safe_to_ignore = code_node
+            .append_child(document.create_text_node(" "))
+            .unwrap();
+        let _safe_to_ignore = code_node
+            .append_child(document.create_element("b").unwrap())
+            .unwrap();
+    }
+
+    let config = DOMConfiguration {
+        entities: false,
+        comments: false,
+        cdata_sections: false,
+        element_content_whitespace: false,
+        ..Default::default()
+    };
+    document_node.normalize_document(&config).unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        r#"<!DOCTYPE list[<!ENTITY org " Example Corp">]><list><item>onea &#60; b Example Corp</item><code xml:space="preserve"> <b></b></code></list>"#
+    );
+
+    let document = as_document(&document_node).unwrap();
+    let item_node = document.get_elements_by_tag_name("item").pop().unwrap();
+    assert_eq!(item_node.child_nodes().len(), 1);
+}
+
+#[test]
+fn test_normalize_document_namespaces() {
+    let implementation = get_implementation();
+    let mut document_node = implementation
+        .create_document(Some(common::RDF_NS), Some("rdf:RDF"), None)
+        .unwrap();
+    {
+        let document = as_document(&document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let mut description_node = document
+            .create_element_ns(common::DC_NS, "dc:description")
+            .unwrap();
+        description_node
+            .set_attribute_ns(common::RDF_NS, "rdf:about", "urn:guide")
+            .unwrap();
+        let _safe_to_ignore = root_node.append_child(description_node).unwrap();
+    }
+
+    document_node
+        .normalize_document(&DOMConfiguration::default())
+        .unwrap();
+
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    assert_eq!(
+        root_node.get_attribute("xmlns:rdf").as_deref(),
+        Some(common::RDF_NS)
+    );
+    let description_node = root_node.first_child().unwrap();
+    assert_eq!(
+        description_node.get_attribute("xmlns:dc").as_deref(),
+        Some(common::DC_NS)
+    );
+    assert!(!description_node.has_attribute("xmlns:rdf"));
+}
+
+#[test]
+fn test_normalize_document_split_cdata_sections() {
+    let implementation = get_implementation();
+    let mut document_node = implementation
+        .create_document(None, Some("code"), None)
+        .unwrap();
+    {
+        let document = as_document(&document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let _safe_to_ignore = root_node
+            .append_child(
+                document
+                    .create_cdata_section("if (a[b[0]]>c]]>) {}")
+                    .unwrap(),
+            )
+            .unwrap();
+    }
+
+    let config = DOMConfiguration {
+        split_cdata_sections: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        document_node.normalize_document(&config),
+        Err(xml_dom::level2::Error::Syntax)
+    );
+
+    document_node
+        .normalize_document(&DOMConfiguration::default())
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    assert_eq!(
+        root_node
+            .child_nodes()
+            .iter()
+            .map(|child| child.node_value().unwrap())
+            .collect::<Vec<String>>(),
+        vec!["if (a[b[0]]", ">c]]", ">) {}"]
+    );
+    document_node.normalize_document(&config).unwrap();
+}
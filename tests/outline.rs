@@ -0,0 +1,95 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{DocumentOutline, Outline};
+use xml_dom::level2::{get_implementation, Element, Node};
+
+pub mod common;
+
+#[test]
+fn test_outline_example_document() {
+    let document_node = common::create_example_rdf_document();
+
+    let outline = document_node.outline(usize::MAX).unwrap();
+    assert_eq!(outline.name, "rdf:RDF");
+    assert_eq!(outline.children.len(), 1);
+    let description = &outline.children[0];
+    assert_eq!(description.name, "rdf:Description");
+    assert_eq!(description.count, 1);
+    assert!(!description.truncated);
+    assert_eq!(
+        description
+            .children
+            .iter()
+            .map(|child| child.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["dc:creator", "dc:title", "dc:Description", "dc:date"]
+    );
+
+    let outline = document_node.outline(0).unwrap();
+    assert!(outline.children.is_empty());
+    assert!(outline.truncated);
+}
+
+#[test]
+fn test_outline_merges_siblings() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("orders"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for (id, lines) in &[("1", 1), ("2", 2), ("3", 0)] {
+        let mut order_node = document.create_element("order").unwrap();
+        order_node.set_attribute("id", id).unwrap();
+        for _ in 0..*lines {
+            let mut line_node = document.create_element("line").unwrap();
+            line_node.set_attribute("sku", "A1").unwrap();
+            let _safe_to_ignore = order_node.append_child(line_node).unwrap();
+        }
+        if *lines == 0 {
+            order_node.set_attribute("status", "empty").unwrap();
+        }
+        let _safe_to_ignore = order_node
+            .append_child(document.create_text_node("ignored"))
+            .unwrap();
+        let _safe_to_ignore = root_node.append_child(order_node).unwrap();
+    }
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("summary").unwrap())
+        .unwrap();
+
+    let outline = document_node.outline(2).unwrap();
+    assert_eq!(
+        outline.children[0],
+        Outline {
+            name: "order".to_string(),
+            count: 3,
+            attributes: vec!["id".to_string(), "status".to_string()],
+            children: vec![Outline {
+                name: "line".to_string(),
+                count: 3,
+                attributes: vec!["sku".to_string()],
+                children: Vec::new(),
+                truncated: false,
+            }],
+            truncated: false,
+        }
+    );
+    assert_eq!(
+        outline.to_string(),
+        "orders\n  order x3 @id @status\n    line x3 @sku\n  summary\n"
+    );
+    assert_eq!(
+        document_node.outline(1).unwrap().to_string(),
+        "orders\n  order x3 @id @status ...\n  summary\n"
+    );
+}
+
+#[test]
+fn test_outline_without_document_element() {
+    let implementation = get_implementation();
+    let document_node = implementation.create_document(None, None, None).unwrap();
+    assert_eq!(document_node.outline(1), None);
+
+    let root_node = common::create_example_rdf_document().first_child().unwrap();
+    assert_eq!(root_node.outline(1), None);
+}
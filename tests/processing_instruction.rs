@@ -1,4 +1,7 @@
-use xml_dom::level2::convert::{as_document, as_processing_instruction_mut};
+use xml_dom::level2::convert::{
+    as_document, as_processing_instruction, as_processing_instruction_mut,
+};
+use xml_dom::level2::Error;
 
 pub mod common;
 
@@ -54,3 +57,41 @@ fn test_reserved_name() {
         .create_processing_instruction("xml-ok", Some("should-work"))
         .is_ok());
 }
+
+#[test]
+fn test_invalid_data() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let result = document.create_processing_instruction("test", Some("data?>more"));
+    assert_eq!(result.err(), Some(Error::Syntax));
+
+    let mut node = document
+        .create_processing_instruction("test", Some("some data"))
+        .unwrap();
+    let processing_instruction = as_processing_instruction_mut(&mut node).unwrap();
+    assert_eq!(
+        processing_instruction.set_data("not ?> allowed"),
+        Err(Error::Syntax)
+    );
+    assert_eq!(processing_instruction.data(), Some("some data".to_string()));
+    assert!(processing_instruction.set_data("? > is fine").is_ok());
+}
+
+#[test]
+fn test_length() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let node = document
+        .create_processing_instruction("test", None)
+        .unwrap();
+    let processing_instruction = as_processing_instruction(&node).unwrap();
+    assert_eq!(processing_instruction.length(), 0);
+
+    let node = document
+        .create_processing_instruction("test", Some("caf\u{e9} \u{1F600}"))
+        .unwrap();
+    let processing_instruction = as_processing_instruction(&node).unwrap();
+    assert_eq!(processing_instruction.length(), 7);
+}
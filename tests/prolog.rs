@@ -0,0 +1,85 @@
+use xml_dom::level2::convert::{as_document, as_document_mut};
+use xml_dom::level2::ext::convert::{as_document_decl, as_document_decl_mut};
+use xml_dom::level2::ext::PrologPosition;
+use xml_dom::level2::Node;
+
+pub mod common;
+
+#[test]
+fn test_prolog_ordering() {
+    let mut document_node = common::create_html_document();
+    let document = as_document_decl_mut(&mut document_node).unwrap();
+
+    let after = document
+        .add_prolog_comment("after", PrologPosition::AfterDocType)
+        .unwrap();
+    let before = document
+        .add_prolog_pi(
+            "xml-stylesheet",
+            Some("href=\"s.css\""),
+            PrologPosition::BeforeDocType,
+        )
+        .unwrap();
+    let before_2 = document
+        .add_prolog_comment("before", PrologPosition::BeforeDocType)
+        .unwrap();
+
+    assert_eq!(
+        document.prolog_nodes(),
+        vec![before.clone(), before_2.clone(), after.clone()]
+    );
+    assert_eq!(
+        document.prolog_position(&before),
+        Some(PrologPosition::BeforeDocType)
+    );
+    assert_eq!(
+        document.prolog_position(&before_2),
+        Some(PrologPosition::BeforeDocType)
+    );
+    assert_eq!(
+        document.prolog_position(&after),
+        Some(PrologPosition::AfterDocType)
+    );
+    assert_eq!(
+        document.document_element().unwrap().node_name().to_string(),
+        "html"
+    );
+
+    let result = format!("{}", document_node);
+    assert_eq!(
+        result,
+        "<?xml-stylesheet href=\"s.css\"?><!--before--><!DOCTYPE html SYSTEM \"about:legacy-compat\"><!--after--><html></html>"
+    );
+}
+
+#[test]
+fn test_epilog_nodes() {
+    let mut document_node = common::create_html_document();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let comment = document.create_comment("the end");
+    let _safe_to_ignore = document.append_child(comment.clone()).unwrap();
+
+    let document = as_document_decl(&document_node).unwrap();
+    assert!(document.prolog_nodes().is_empty());
+    assert_eq!(document.epilog_nodes(), vec![comment.clone()]);
+    assert_eq!(document.prolog_position(&comment), None);
+
+    let result = format!("{}", document_node);
+    assert_eq!(
+        result,
+        "<!DOCTYPE html SYSTEM \"about:legacy-compat\"><html></html><!--the end-->"
+    );
+}
+
+#[test]
+fn test_prolog_invalid_pi() {
+    let mut document_node = common::create_html_document();
+    let document = as_document_decl_mut(&mut document_node).unwrap();
+    assert!(document
+        .add_prolog_pi("target", Some("bad?>data"), PrologPosition::BeforeDocType)
+        .is_err());
+    assert!(as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .is_some());
+}
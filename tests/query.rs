@@ -0,0 +1,242 @@
+use xml_dom::level2::convert::{as_document, as_document_fragment};
+use xml_dom::level2::ext::compare::{compare_document_position, DOCUMENT_POSITION_FOLLOWING};
+use xml_dom::level2::ext::query::CsvConfig;
+use xml_dom::level2::ext::{xpath, QueryResult, Selector};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_query_first_and_texts() {
+    let document_node = common::create_example_rdf_document();
+
+    let result = QueryResult::by_tag_name(&document_node, "dc:title");
+    assert_eq!(result.len(), 1);
+    assert!(result.first().is_some());
+    assert_eq!(result.texts(), vec!["A Guide to Growing Roses".to_string()]);
+
+    let result = QueryResult::by_tag_name_ns(&document_node, common::DC_NS, "*");
+    assert_eq!(result.len(), 4);
+    assert_eq!(result.texts().get(3), Some(&"2001-01-20".to_string()));
+
+    let result = QueryResult::by_tag_name(&document_node, "unknown");
+    assert!(result.is_empty());
+    assert!(result.first().is_none());
+    assert!(result.texts().is_empty());
+}
+
+#[test]
+fn test_query_attribute_values() {
+    let document_node = common::create_example_rdf_document();
+
+    let result = QueryResult::by_tag_name_ns(&document_node, common::DC_NS, "*");
+    assert_eq!(
+        result.attribute_values("id"),
+        vec!["description".to_string()]
+    );
+    assert_eq!(result.attribute_values("xml:id"), vec!["title".to_string()]);
+}
+
+#[test]
+fn test_query_remove_all() {
+    let document_node = common::create_example_rdf_document();
+
+    let mut result = QueryResult::by_tag_name_ns(&document_node, common::DC_NS, "*");
+    assert_eq!(result.len(), 4);
+    assert!(result.remove_all().is_ok());
+    assert!(result.iter().all(|node| node.parent_node().is_none()));
+
+    let result = QueryResult::by_tag_name_ns(&document_node, common::DC_NS, "*");
+    assert!(result.is_empty());
+    let result = QueryResult::by_tag_name(&document_node, "rdf:Description");
+    assert_eq!(result.len(), 1);
+}
+
+#[test]
+fn test_query_into_fragment() {
+    let document_node = common::create_example_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let result = QueryResult::by_tag_name(&document_node, "*");
+    assert_eq!(result.len(), 6);
+    let root_node = document.document_element().unwrap();
+
+    let result = QueryResult::by_tag_name(&root_node, "rdf:Description");
+    let fragment_node = result.into_fragment().unwrap();
+    let fragment = as_document_fragment(&fragment_node).unwrap();
+    assert_eq!(fragment.child_nodes().len(), 1);
+    assert!(!root_node.has_child_nodes());
+
+    let result = QueryResult::by_tag_name(&fragment_node, "dc:date");
+    assert!(result.is_empty());
+
+    let result = QueryResult::from(Vec::new());
+    assert_eq!(result.into_fragment().err(), Some(Error::NotFound));
+}
+
+#[test]
+fn test_query_table() {
+    let document_node = common::create_example_rdf_document();
+
+    let result = QueryResult::by_tag_name(&document_node, "rdf:Description");
+    let table = result
+        .table(&[
+            "dc:title",
+            "dc:title/@xml:id",
+            "dc:Description/@id",
+            "dc:publisher",
+        ])
+        .unwrap();
+    assert_eq!(
+        table,
+        vec![vec![
+            Some("A Guide to Growing Roses".to_string()),
+            Some("title".to_string()),
+            Some("description".to_string()),
+            None,
+        ]]
+    );
+
+    let result = QueryResult::by_tag_name_ns(&document_node, common::DC_NS, "*");
+    let table = result.table(&[".", "@id"]).unwrap();
+    assert_eq!(table.len(), 4);
+    assert_eq!(
+        table[2],
+        vec![
+            Some(
+                "Describes process for planting & nurturing different kinds of rose bushes."
+                    .to_string()
+            ),
+            Some("description".to_string()),
+        ]
+    );
+    assert_eq!(table[3], vec![Some("2001-01-20".to_string()), None]);
+
+    assert_eq!(result.table(&["@id/dc:title"]), Err(Error::Syntax));
+    assert_eq!(result.table(&["dc:title//@id"]), Err(Error::Syntax));
+    assert_eq!(result.table(&["@"]), Err(Error::Syntax));
+}
+
+#[test]
+fn test_query_export_csv() {
+    let document_node = common::create_example_rdf_document();
+    let result = QueryResult::by_tag_name_ns(&document_node, common::DC_NS, "*");
+
+    let config = CsvConfig {
+        columns: vec![".".to_string(), "@id".to_string()],
+        header: true,
+        ..Default::default()
+    };
+    let mut output: Vec<u8> = Vec::new();
+    assert_eq!(result.export_csv(&mut output, &config), Ok(4));
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        ".,@id\r\nRose Bush,\r\nA Guide to Growing Roses,\r\n\
+         Describes process for planting & nurturing different kinds of rose bushes.,description\r\n\
+         2001-01-20,\r\n"
+    );
+
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut note_node = document.create_element("note").unwrap();
+    note_node.set_attribute("by", "Rose, Bush").unwrap();
+    let _safe_to_ignore = note_node
+        .append_child(document.create_text_node("one \"two\"\tthree"))
+        .unwrap();
+    let _safe_to_ignore = root_node.append_child(note_node).unwrap();
+
+    let result = QueryResult::by_tag_name(&document_node, "note");
+    let mut output: Vec<u8> = Vec::new();
+    assert_eq!(
+        result.export_csv(&mut output, &CsvConfig::tsv(&["@by", "."])),
+        Ok(1)
+    );
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Rose, Bush\t\"one \"\"two\"\"\tthree\"\n"
+    );
+
+    let config = CsvConfig {
+        columns: vec!["@".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(result.export_csv(Vec::new(), &config), Err(Error::Syntax));
+}
+
+#[test]
+fn test_query_document_order() {
+    fn assert_document_order(nodes: &[RefNode]) {
+        for pair in nodes.windows(2) {
+            assert_eq!(
+                compare_document_position(&pair[0], &pair[1]) & DOCUMENT_POSITION_FOLLOWING,
+                DOCUMENT_POSITION_FOLLOWING
+            );
+        }
+    }
+
+    let document_node = common::create_example_rdf_document();
+
+    let result = QueryResult::by_tag_name(&document_node, "*");
+    assert_eq!(result.len(), 6);
+    assert_document_order(&result.iter().cloned().collect::<Vec<RefNode>>());
+
+    let selected = Selector::parse("rdf|Description, dc|title, dc|date")
+        .map(|mut selector| {
+            selector.set_namespace("rdf", common::RDF_NS);
+            selector.set_namespace("dc", common::DC_NS);
+            selector.select(&document_node)
+        })
+        .unwrap();
+    assert_eq!(selected.len(), 3);
+    assert_document_order(&selected);
+
+    let selected = xpath::select(&document_node, "//dc:date | //dc:creator | //*").unwrap();
+    assert_eq!(selected.len(), 6);
+    assert_document_order(&selected);
+
+    let mut unordered =
+        xpath::select_unordered_fast(&document_node, "//dc:date | //dc:creator | //*").unwrap();
+    assert_eq!(unordered.len(), 6);
+    unordered.sort_by_key(|node| selected.iter().position(|other| other == node));
+    assert_eq!(unordered, selected);
+}
+
+#[test]
+fn test_query_mutation_during_iteration() {
+    let document_node = common::create_example_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut description = QueryResult::by_tag_name(&document_node, "rdf:Description")
+        .first()
+        .unwrap();
+
+    // removing, and inserting, children while iterating over `child_nodes`.
+    let children = description.child_nodes();
+    let mut visited = 0;
+    for child_node in description.child_nodes() {
+        visited += 1;
+        let _safe_to_ignore = description.remove_child(child_node.clone()).unwrap();
+        let new_node = document.create_element("added").unwrap();
+        let _safe_to_ignore = description.append_child(new_node).unwrap();
+    }
+    assert_eq!(visited, children.len());
+    assert!(children.iter().all(|node| node.parent_node().is_none()));
+    assert_eq!(description.child_nodes().len(), children.len());
+
+    // removed nodes are still visited, unless using `iter_connected`.
+    let root_node = document.document_element().unwrap();
+    let result = QueryResult::by_tag_name(&document_node, "*");
+    let mut names: Vec<String> = Vec::new();
+    for node in result.iter_connected(&root_node) {
+        names.push(node.node_name().to_string());
+        if node == description {
+            let mut parent_node = node.parent_node().unwrap();
+            let _safe_to_ignore = parent_node.remove_child(node.clone()).unwrap();
+        }
+    }
+    assert_eq!(names, vec!["rdf:RDF", "rdf:Description"]);
+    assert_eq!(result.iter().count(), 2 + children.len());
+    assert_eq!(
+        result.iter_connected(&description).count(),
+        1 + children.len()
+    );
+}
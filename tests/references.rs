@@ -0,0 +1,137 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::options::ProcessingOptions;
+use xml_dom::level2::ext::references::{
+    check_references, node_path, ReferenceConfig, ReferenceKind, ReferenceProblem,
+};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[cfg(feature = "quick_parser")]
+const DOCUMENT: &str = r#"<!DOCTYPE manual [
+<!ATTLIST topic key ID #REQUIRED title CDATA #IMPLIED>
+<!ATTLIST link target IDREF #IMPLIED kind (see|also) "see" format NOTATION (png) #IMPLIED>
+<!-- <!ATTLIST ignored ref IDREF #IMPLIED> -->
+<!ATTLIST group members IDREFS #FIXED "one">
+]>
+<manual>
+<topic key="one"/>
+<topic key="two"/>
+<link target="two"/>
+<link target="three"/>
+<group members=" one two four "/>
+<topic key="two"/>
+</manual>"#;
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_document_type_declarations() {
+    let document_node = xml_dom::parser::read_xml(DOCUMENT).unwrap();
+    let problems = check_references(&document_node, &ReferenceConfig::default());
+    assert_eq!(
+        problems,
+        vec![
+            ReferenceProblem::DuplicateId {
+                id: "two".to_string(),
+                path: "/manual/topic[3]/@key".to_string(),
+                first_path: "/manual/topic[2]/@key".to_string(),
+            },
+            ReferenceProblem::DanglingReference {
+                id: "three".to_string(),
+                path: "/manual/link[2]/@target".to_string(),
+            },
+            ReferenceProblem::DanglingReference {
+                id: "four".to_string(),
+                path: "/manual/group/@members".to_string(),
+            },
+        ]
+    );
+
+    let mut config = ReferenceConfig::default();
+    config.set_use_document_type(false);
+    assert!(check_references(&document_node, &config).is_empty());
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_configured_declarations() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<doc xmlns:r="urn:refs"><a name="x"/><b name="y" r:to="x"/><c r:to="y" name="z"/></doc>"#,
+    )
+    .unwrap();
+
+    let mut config = ReferenceConfig::empty();
+    config.declare_for_element("a", "name", ReferenceKind::Id);
+    config.declare_ns("urn:refs", "to", ReferenceKind::IdRef);
+    let problems = check_references(&document_node, &config);
+    assert_eq!(problems.len(), 1);
+    assert_eq!(
+        problems[0].to_string(),
+        "dangling reference 'y' at /doc/c/@r:to"
+    );
+
+    config.declare("name", ReferenceKind::Id);
+    assert!(check_references(&document_node, &config).is_empty());
+
+    // only the references within the subtree are checked.
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let c = root_node.last_child().unwrap();
+    assert_eq!(check_references(&c, &config).len(), 1);
+}
+
+#[test]
+fn test_assume_ids() {
+    let mut options = ProcessingOptions::new();
+    options.set_assume_ids();
+    let document_node = common::create_example_rdf_document_options(options);
+    assert!(check_references(&document_node, &ReferenceConfig::empty()).is_empty());
+
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut reference = document.create_element("see").unwrap();
+    reference.set_attribute("ref", "title").unwrap();
+    let _safe_to_ignore = root_node.append_child(reference).unwrap();
+    let mut reference = document.create_element("see").unwrap();
+    reference.set_attribute("ref", "missing").unwrap();
+    let _safe_to_ignore = root_node.append_child(reference).unwrap();
+
+    let mut config = ReferenceConfig::default();
+    config.declare("ref", ReferenceKind::IdRef);
+    assert_eq!(
+        check_references(&document_node, &config),
+        vec![ReferenceProblem::DanglingReference {
+            id: "missing".to_string(),
+            path: "/rdf:RDF/see[2]/@ref".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_node_path() {
+    let document_node = common::create_example_rdf_document();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(node_path(&document_node), "/");
+    assert_eq!(node_path(&root_node), "/rdf:RDF");
+    let title = root_node
+        .first_child()
+        .unwrap()
+        .first_child()
+        .unwrap()
+        .next_sibling()
+        .unwrap();
+    assert_eq!(node_path(&title), "/rdf:RDF/rdf:Description/dc:title");
+    assert_eq!(
+        node_path(&title.first_child().unwrap()),
+        "/rdf:RDF/rdf:Description/dc:title/text()"
+    );
+    assert_eq!(
+        node_path(&title.get_attribute_node("xml:id").unwrap()),
+        "/rdf:RDF/rdf:Description/dc:title/@xml:id"
+    );
+}
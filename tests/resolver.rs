@@ -0,0 +1,178 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::resolver::{Resource, UriResolver};
+use xml_dom::level2::ext::{DocumentImport, Workspace};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[derive(Debug)]
+struct TextResolver(&'static str);
+
+impl UriResolver for TextResolver {
+    fn fetch(&self, uri: &str, _referrer: &RefNode) -> Result<Resource> {
+        match uri {
+            "content.xml" => Ok(Resource::Text(self.0.to_string())),
+            _ => Err(Error::NotFound),
+        }
+    }
+}
+
+fn create_document() -> (RefNode, RefNode) {
+    let document_node = get_implementation()
+        .create_document(None, Some("book"), None)
+        .unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    (document_node, root_node)
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_import_document() {
+    let (mut document_node, mut root_node) = create_document();
+    let resolver = TextResolver(
+        r#"<?xml version="1.0"?><!DOCTYPE chapter><!-- one --><chapter n="1">One</chapter>"#,
+    );
+    let imported = document_node
+        .import_from_uri("content.xml", &mut root_node, &resolver)
+        .unwrap();
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0].node_type(), NodeType::Comment);
+    assert_eq!(imported[1].owner_document(), Some(document_node.clone()));
+    assert_eq!(
+        root_node.to_string(),
+        r#"<book><!-- one --><chapter n="1">One</chapter></book>"#
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_import_fragment() {
+    let (mut document_node, mut root_node) = create_document();
+    let resolver = TextResolver(r#"<?xml version="1.0"?><a/>text<b x="y">&amp;</b>"#);
+    let imported = document_node
+        .import_from_uri("content.xml", &mut root_node, &resolver)
+        .unwrap();
+    assert_eq!(imported.len(), 3);
+    assert_eq!(imported[1].node_type(), NodeType::Text);
+    assert_eq!(root_node.child_nodes().len(), 3);
+    assert_eq!(
+        root_node
+            .first_child()
+            .unwrap()
+            .next_sibling()
+            .unwrap()
+            .node_value(),
+        Some("text".to_string())
+    );
+
+    let resolver = TextResolver("<a>");
+    assert_eq!(
+        document_node
+            .import_from_uri("content.xml", &mut root_node, &resolver)
+            .err(),
+        Some(Error::Syntax)
+    );
+    assert_eq!(
+        document_node
+            .import_from_uri("other.xml", &mut root_node, &resolver)
+            .err(),
+        Some(Error::NotFound)
+    );
+    assert_eq!(root_node.child_nodes().len(), 3);
+}
+
+#[test]
+fn test_import_wrong_document() {
+    let (mut document_node, _) = create_document();
+    let (_, mut other_root_node) = create_document();
+    let resolver = TextResolver("<a/>");
+    assert_eq!(
+        document_node
+            .import_from_uri("content.xml", &mut other_root_node, &resolver)
+            .err(),
+        Some(Error::WrongDocument)
+    );
+}
+
+#[test]
+fn test_import_namespace_fixup() {
+    let mut workspace = Workspace::new();
+    let source_node = workspace
+        .create_document("http://example.com/lib/items.xml", None, Some("items"))
+        .unwrap();
+    let source = as_document(&source_node).unwrap();
+    let mut items = source.document_element().unwrap();
+    items.set_attribute("xmlns", common::DC_NS).unwrap();
+    let mut item = source.create_element_ns("urn:lib", "lib:item").unwrap();
+    item.set_attribute_ns(common::RDF_NS, "rdf:about", "x")
+        .unwrap();
+    let _safe_to_ignore = items.append_child(item).unwrap();
+
+    let mut target_node = workspace
+        .create_document("http://example.com/main.xml", None, Some("book"))
+        .unwrap();
+    let mut book = as_document(&target_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    book.set_attribute("xmlns", "urn:book").unwrap();
+    book.set_attribute_ns(common::XMLNS_NS, "xmlns:lib", "urn:other")
+        .unwrap();
+
+    let imported = target_node
+        .import_from_uri("lib/items.xml", &mut book, &workspace)
+        .unwrap();
+    assert_eq!(imported.len(), 1);
+    let items = &imported[0];
+    assert_eq!(
+        items.get_attribute("xmlns"),
+        Some(common::DC_NS.to_string())
+    );
+    assert_eq!(
+        items.get_attribute("xmlns:lib"),
+        Some("urn:lib".to_string())
+    );
+    assert_eq!(
+        items.get_attribute("xmlns:rdf"),
+        Some(common::RDF_NS.to_string())
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_file_resolver() {
+    let directory = std::env::temp_dir().join("xml_dom_test_file_resolver");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("chapter.xml"), "<chapter/>").unwrap();
+
+    let (mut document_node, mut root_node) = create_document();
+    let resolver = xml_dom::level2::ext::resolver::FileResolver::new(&directory);
+    let imported = document_node
+        .import_from_uri("chapter.xml", &mut root_node, &resolver)
+        .unwrap();
+    assert_eq!(imported.len(), 1);
+    let uri = format!("file://{}", directory.join("chapter.xml").display());
+    let _safe_to_ignore = document_node
+        .import_from_uri(&uri, &mut root_node, &resolver)
+        .unwrap();
+    assert_eq!(
+        root_node.to_string(),
+        "<book><chapter></chapter><chapter></chapter></book>"
+    );
+
+    assert_eq!(
+        document_node
+            .import_from_uri("missing.xml", &mut root_node, &resolver)
+            .err(),
+        Some(Error::NotFound)
+    );
+    assert_eq!(
+        document_node
+            .import_from_uri("http://example.com/chapter.xml", &mut root_node, &resolver)
+            .err(),
+        Some(Error::NotFound)
+    );
+}
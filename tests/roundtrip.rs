@@ -0,0 +1,95 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::ext::compare::CompareOptions;
+use xml_dom::level2::ext::roundtrip::{roundtrip_check, RoundtripFailure, RoundtripOptions};
+use xml_dom::level2::ext::serializer::WriteOptions;
+use xml_dom::level2::Error;
+
+pub mod common;
+
+#[test]
+fn test_roundtrip_ok() {
+    let inputs = [
+        r#"<?xml version="1.0" encoding="UTF-8"?><root/>"#,
+        r#"<!DOCTYPE note SYSTEM "note.dtd"><note lang="en">text</note>"#,
+        r#"<p:order xmlns:p="urn:orders" p:id="1"><p:item>a &amp; b</p:item></p:order>"#,
+        r#"<list><?target some data?><!-- comment --><item b="2" a="1">x</item></list>"#,
+        r#"<quote>"it's" &lt;here&gt;</quote>"#,
+    ];
+    for input in &inputs {
+        let report = roundtrip_check(input, &RoundtripOptions::default());
+        assert!(report.is_ok(), "{}: {:?}", input, report);
+        assert!(!report.is_bug());
+        assert!(report.serialized.is_some());
+    }
+}
+
+#[test]
+fn test_roundtrip_cdata() {
+    let inputs = [
+        "<r><![CDATA[x]]></r>",
+        "<r>a<![CDATA[<b> & c]]>d</r>",
+        "<r><![CDATA[if a]]]]><![CDATA[>b]]></r>",
+    ];
+    for input in &inputs {
+        let report = roundtrip_check(input, &RoundtripOptions::default());
+        assert!(report.is_ok(), "{}: {:?}", input, report);
+    }
+    let report = roundtrip_check("<r><![CDATA[x]]></r>", &RoundtripOptions::default());
+    assert_eq!(report.serialized.unwrap(), "<r><![CDATA[x]]></r>");
+}
+
+#[test]
+fn test_roundtrip_attribute_whitespace() {
+    let inputs = ["<r a=\"x&#10;y\"/>", "<r a=\"&#9;x&#13;&#10;y \"/>"];
+    for input in &inputs {
+        let report = roundtrip_check(input, &RoundtripOptions::default());
+        assert!(report.is_ok(), "{}: {:?}", input, report);
+    }
+    let report = roundtrip_check("<r a=\"x&#10;y\"/>", &RoundtripOptions::default());
+    assert_eq!(report.serialized.unwrap(), "<r a=\"x&#10;y\"></r>");
+}
+
+#[test]
+fn test_roundtrip_normalized_input() {
+    let input = "<r a=\"x\ty\r\nz\" b=\"&#9;\">one\r\ntwo\rthree<![CDATA[\r\n]]></r>";
+    let report = roundtrip_check(input, &RoundtripOptions::default());
+    assert!(report.is_ok(), "{:?}", report);
+    assert_eq!(
+        report.serialized.unwrap(),
+        "<r a=\"x y z\" b=\"&#9;\">one\ntwo\nthree<![CDATA[\n]]></r>"
+    );
+}
+
+#[test]
+fn test_roundtrip_parse_failure() {
+    for input in &["", "<open>", "<a></b>", "<a b=1/>"] {
+        let report = roundtrip_check(input, &RoundtripOptions::default());
+        assert!(
+            matches!(report.failure, Some(RoundtripFailure::Parse(_))),
+            "{}: {:?}",
+            input,
+            report
+        );
+        assert!(!report.is_ok());
+        assert!(!report.is_bug());
+        assert!(report.serialized.is_none());
+    }
+}
+
+#[test]
+fn test_roundtrip_serialize_failure() {
+    let input = r#"<ex:root/>"#;
+    assert!(roundtrip_check(input, &RoundtripOptions::default()).is_ok());
+
+    let options = RoundtripOptions {
+        write: WriteOptions::strict(),
+        compare: CompareOptions::default(),
+    };
+    let report = roundtrip_check(input, &options);
+    assert!(matches!(
+        report.failure,
+        Some(RoundtripFailure::Serialize(Error::Namespace))
+    ));
+    assert!(report.is_bug());
+}
@@ -0,0 +1,180 @@
+use std::str::FromStr;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::normalize::{normalize_for_diff, NormalizeOptions};
+use xml_dom::level2::ext::sax::walk;
+use xml_dom::level2::ext::{
+    ContentHandler, DocumentBuilder, DocumentDecl, PrologPosition, XmlDecl, XmlVersion,
+};
+use xml_dom::level2::{Element, Error, Name, Node, NodeType, RefNode, Result};
+
+pub mod common;
+
+#[derive(Debug, Default)]
+struct Events(Vec<String>);
+
+impl ContentHandler for Events {
+    fn start_document(&mut self) -> Result<()> {
+        self.0.push("start_document".to_string());
+        Ok(())
+    }
+
+    fn end_document(&mut self) -> Result<()> {
+        self.0.push("end_document".to_string());
+        Ok(())
+    }
+
+    fn document_type(
+        &mut self,
+        name: &Name,
+        _public_id: Option<&str>,
+        system_id: Option<&str>,
+        _internal_subset: Option<&str>,
+    ) -> Result<()> {
+        self.0
+            .push(format!("document_type {} {}", name, system_id.unwrap()));
+        Ok(())
+    }
+
+    fn start_element(&mut self, name: &Name, attributes: &[(Name, String)]) -> Result<()> {
+        let mut attributes: Vec<String> = attributes
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        attributes.sort();
+        self.0
+            .push(format!("start_element {} {}", name, attributes.join(",")));
+        Ok(())
+    }
+
+    fn end_element(&mut self, name: &Name) -> Result<()> {
+        self.0.push(format!("end_element {}", name));
+        Ok(())
+    }
+
+    fn characters(&mut self, text: &str) -> Result<()> {
+        self.0.push(format!("characters {}", text));
+        Ok(())
+    }
+
+    fn comment(&mut self, text: &str) -> Result<()> {
+        self.0.push(format!("comment {}", text));
+        Ok(())
+    }
+}
+
+fn create_html_document() -> RefNode {
+    let mut document_node = common::create_html_document();
+    document_node
+        .set_xml_declaration(XmlDecl::new(XmlVersion::V10, None, None))
+        .unwrap();
+    let _safe_to_ignore = document_node
+        .add_prolog_comment("before", PrologPosition::BeforeDocType)
+        .unwrap();
+    let _safe_to_ignore = document_node
+        .add_prolog_comment("after", PrologPosition::AfterDocType)
+        .unwrap();
+
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("title", "a & b").unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("x < y"))
+        .unwrap();
+    document_node
+}
+
+#[test]
+fn test_walk_events() {
+    let document_node = create_html_document();
+    let mut events = Events::default();
+    walk(&document_node, &mut events).unwrap();
+    assert_eq!(
+        events.0,
+        vec![
+            "start_document",
+            "comment before",
+            "document_type html about:legacy-compat",
+            "comment after",
+            "start_element html title=a & b",
+            "characters x < y",
+            "end_element html",
+            "end_document",
+        ]
+    );
+
+    let mut events = Events::default();
+    let root_node = document_node.first_child().unwrap();
+    assert_eq!(root_node.node_type(), NodeType::Comment);
+    walk(&root_node, &mut events).unwrap();
+    assert_eq!(events.0, vec!["comment before"]);
+}
+
+#[test]
+fn test_build_copy() {
+    for document_node in &[
+        common::create_example_rdf_document(),
+        create_html_document(),
+    ] {
+        let mut builder = DocumentBuilder::new();
+        walk(document_node, &mut builder).unwrap();
+        let copy_node = builder.into_document().unwrap();
+        let options = NormalizeOptions {
+            normalize_prefixes: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalize_for_diff(&copy_node, &options).unwrap(),
+            normalize_for_diff(document_node, &options).unwrap()
+        );
+    }
+
+    let copy_node = {
+        let mut builder = DocumentBuilder::new();
+        walk(&create_html_document(), &mut builder).unwrap();
+        builder.into_document().unwrap()
+    };
+    let first_node = copy_node.first_child().unwrap();
+    assert_eq!(
+        copy_node.prolog_position(&first_node),
+        Some(PrologPosition::BeforeDocType)
+    );
+    assert_eq!(
+        as_document(&copy_node)
+            .unwrap()
+            .document_element()
+            .unwrap()
+            .get_attribute("title"),
+        Some("a &#38; b".to_string())
+    );
+}
+
+#[test]
+fn test_builder_errors() {
+    let name = Name::from_str("a").unwrap();
+
+    let mut builder = DocumentBuilder::new();
+    builder.start_element(&name, &[]).unwrap();
+    assert_eq!(builder.depth(), 1);
+    assert_eq!(builder.into_document(), Err(Error::InvalidState));
+
+    let mut builder = DocumentBuilder::new();
+    assert_eq!(builder.end_element(&name), Err(Error::InvalidState));
+    builder.characters("\n  ").unwrap();
+    assert_eq!(builder.characters("text"), Err(Error::Syntax));
+    assert_eq!(builder.into_document(), Err(Error::Syntax));
+
+    let mut builder = DocumentBuilder::new();
+    builder.start_element(&name, &[]).unwrap();
+    assert_eq!(
+        builder.end_element(&Name::from_str("b").unwrap()),
+        Err(Error::InvalidState)
+    );
+    builder.end_element(&name).unwrap();
+    assert_eq!(
+        builder.xml_declaration(&XmlDecl::new(XmlVersion::V10, None, None)),
+        Err(Error::Syntax)
+    );
+    let document_node = builder.into_document().unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert!(document.document_element().is_some());
+}
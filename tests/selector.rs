@@ -0,0 +1,213 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{Selectable, Selector};
+use xml_dom::level2::{get_implementation, Element, Error, Node, NodeType, RefNode};
+
+pub mod common;
+
+// <book>
+//   <chapter id="c1" class="intro">
+//     <title class="heading">One</title>
+//     <para lang="en-US">First</para>
+//     <para/>
+//   </chapter>
+//   <!--break-->
+//   <chapter id="c2">
+//     <title class="heading numbered">Two</title>
+//     <note><title>Aside</title></note>
+//   </chapter>
+// </book>
+
+fn create_book_document() -> RefNode {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("book"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let element = |name: &str, attributes: &[(&str, &str)], text: Option<&str>| {
+        let mut element_node = document.create_element(name).unwrap();
+        for (name, value) in attributes {
+            element_node.set_attribute(name, value).unwrap();
+        }
+        if let Some(text) = text {
+            let _safe_to_ignore = element_node
+                .append_child(document.create_text_node(text))
+                .unwrap();
+        }
+        element_node
+    };
+
+    let mut chapter_node = element("chapter", &[("id", "c1"), ("class", "intro")], None);
+    let _safe_to_ignore = chapter_node
+        .append_child(element("title", &[("class", "heading")], Some("One")))
+        .unwrap();
+    let _safe_to_ignore = chapter_node
+        .append_child(element("para", &[("lang", "en-US")], Some("First")))
+        .unwrap();
+    let _safe_to_ignore = chapter_node
+        .append_child(element("para", &[], None))
+        .unwrap();
+    let _safe_to_ignore = root_node.append_child(chapter_node).unwrap();
+
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("break"))
+        .unwrap();
+
+    let mut chapter_node = element("chapter", &[("id", "c2")], None);
+    let _safe_to_ignore = chapter_node
+        .append_child(element(
+            "title",
+            &[("class", "heading numbered")],
+            Some("Two"),
+        ))
+        .unwrap();
+    let mut note_node = element("note", &[], None);
+    let _safe_to_ignore = note_node
+        .append_child(element("title", &[], Some("Aside")))
+        .unwrap();
+    let _safe_to_ignore = chapter_node.append_child(note_node).unwrap();
+    let _safe_to_ignore = root_node.append_child(chapter_node).unwrap();
+    document_node
+}
+
+fn texts(node: &RefNode, selector: &str) -> Vec<String> {
+    node.select(selector)
+        .unwrap()
+        .iter()
+        .map(|element| match element.first_child() {
+            Some(text) if text.node_type() == NodeType::Text => text.node_value().unwrap(),
+            _ => format!("<{}>", element.node_name()),
+        })
+        .collect()
+}
+
+#[test]
+fn test_select_combinators() {
+    let document_node = create_book_document();
+
+    assert_eq!(texts(&document_node, "title"), vec!["One", "Two", "Aside"]);
+    assert_eq!(texts(&document_node, "chapter > title"), vec!["One", "Two"]);
+    assert_eq!(
+        texts(&document_node, "chapter title"),
+        vec!["One", "Two", "Aside"]
+    );
+    assert_eq!(
+        texts(&document_node, "book>chapter>note>title"),
+        vec!["Aside"]
+    );
+    assert_eq!(texts(&document_node, "title + para"), vec!["First"]);
+    assert_eq!(
+        texts(&document_node, "title ~ para"),
+        vec!["First", "<para>"]
+    );
+    assert_eq!(
+        texts(&document_node, "chapter + chapter > title"),
+        vec!["Two"]
+    );
+    assert_eq!(
+        texts(&document_node, "note title, #c1 > title"),
+        vec!["One", "Aside"]
+    );
+
+    let chapter_node = document_node.select_first("#c2").unwrap().unwrap();
+    assert_eq!(texts(&chapter_node, "title"), vec!["Two", "Aside"]);
+    assert_eq!(texts(&chapter_node, "book > chapter > title"), vec!["Two"]);
+    assert!(chapter_node.select("chapter").unwrap().is_empty());
+}
+
+#[test]
+fn test_select_conditions() {
+    let document_node = create_book_document();
+
+    assert_eq!(texts(&document_node, "title.heading"), vec!["One", "Two"]);
+    assert_eq!(texts(&document_node, ".heading.numbered"), vec!["Two"]);
+    assert_eq!(
+        texts(&document_node, "*[class]"),
+        vec!["<chapter>", "One", "Two"]
+    );
+    assert_eq!(texts(&document_node, "[class='heading']"), vec!["One"]);
+    assert_eq!(texts(&document_node, "[class~=numbered]"), vec!["Two"]);
+    assert_eq!(texts(&document_node, "para[lang|=en]"), vec!["First"]);
+    assert_eq!(
+        texts(&document_node, "[id^=c]"),
+        vec!["<chapter>", "<chapter>"]
+    );
+    assert_eq!(texts(&document_node, "[class$=\"bered\"]"), vec!["Two"]);
+    assert_eq!(texts(&document_node, "[class*=ead]"), vec!["One", "Two"]);
+    assert_eq!(
+        texts(&document_node, "[id=c2] :first-child"),
+        vec!["Two", "Aside"]
+    );
+    assert_eq!(texts(&document_node, "para:last-child"), vec!["<para>"]);
+    assert_eq!(texts(&document_node, "title:only-child"), vec!["Aside"]);
+    assert_eq!(texts(&document_node, "*:empty"), vec!["<para>"]);
+    assert_eq!(texts(&document_node, ":root"), vec!["<book>"]);
+
+    let para_node = document_node.select_first("para").unwrap().unwrap();
+    assert!(para_node.matches_selector("#c1 > [lang]").unwrap());
+    assert!(!para_node.matches_selector("#c2 para").unwrap());
+}
+
+#[test]
+fn test_select_namespaces() {
+    let document_node = common::create_example_rdf_document();
+
+    assert_eq!(document_node.select("dc|title").unwrap().len(), 1);
+    assert_eq!(document_node.select("title").unwrap().len(), 1);
+    assert_eq!(document_node.select("*|Description").unwrap().len(), 2);
+    assert_eq!(
+        document_node
+            .select("rdf|Description > dc|*")
+            .unwrap()
+            .len(),
+        4
+    );
+    assert_eq!(document_node.select("[xml|id=title]").unwrap().len(), 1);
+    assert!(document_node.select("|title").unwrap().is_empty());
+
+    let mut selector = Selector::parse("purl|Description").unwrap();
+    assert!(selector.select(&document_node).is_empty());
+    selector.set_namespace("purl", common::DC_NS);
+    let selected = selector.select(&document_node);
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].node_name().to_string(), "dc:Description");
+    assert_eq!(selector.to_string(), "purl|Description");
+}
+
+#[test]
+fn test_select_errors() {
+    let document_node = create_book_document();
+    for selector in &[
+        "",
+        " ",
+        "title,",
+        "> title",
+        "title >",
+        "[",
+        "[class",
+        "[class=]",
+        "[class!=x]",
+        ".",
+        "#",
+        ":unknown",
+        "title::before",
+        "a|",
+        "[*]",
+    ] {
+        assert_eq!(
+            Selector::parse(selector).err(),
+            Some(Error::Syntax),
+            "{}",
+            selector
+        );
+    }
+
+    let text_node = document_node
+        .select_first("title")
+        .unwrap()
+        .unwrap()
+        .first_child()
+        .unwrap();
+    assert_eq!(text_node.select("title"), Err(Error::InvalidState));
+    assert!(!text_node.matches_selector("title").unwrap());
+}
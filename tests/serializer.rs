@@ -0,0 +1,504 @@
+use std::io::{Error as IoError, Result as IoResult, Write};
+use xml_dom::level2::convert::{as_document, as_element_mut};
+use xml_dom::level2::ext::serializer::{
+    canonicalize, serialize, write_canonical, write_document, CanonicalOptions, Canonicalization,
+    Profile, WriteOptions,
+};
+use xml_dom::level2::ext::Namespaced;
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_serialize_declared_prefix() {
+    let document_node = common::create_example_rdf_document();
+    let result = serialize(&document_node, &WriteOptions::default());
+    assert!(result.is_err());
+    assert_eq!(result.err().unwrap(), Error::Namespace);
+
+    let options = WriteOptions {
+        fix: true,
+        ..Default::default()
+    };
+    let result = serialize(&document_node, &options).unwrap();
+    assert!(result.starts_with("<rdf:RDF "));
+    assert!(result.contains(" xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\""));
+    assert!(result.contains("<dc:creator xmlns:dc=\"http://purl.org/dc/elements/1.1/\">"));
+    assert!(result.contains(" xml:id=\"title\""));
+    assert!(!result.contains("xmlns:xml"));
+}
+
+#[test]
+fn test_serialize_undeclared_prefix_strict() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let root = as_element_mut(&mut root_node).unwrap();
+    assert!(root
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:rdf", common::RDF_NS)
+        .is_ok());
+
+    let result = serialize(&document_node, &WriteOptions::strict());
+    assert_eq!(
+        result,
+        Ok(format!(
+            "<rdf:RDF xmlns:rdf=\"{}\"></rdf:RDF>",
+            common::RDF_NS
+        ))
+    );
+
+    //
+    // A prefix with no namespace URI cannot be fixed.
+    //
+    let child_node = document.create_element("dc:title").unwrap();
+    assert!(root.append_child(child_node).is_ok());
+    let options = WriteOptions {
+        profile: Profile::Strict,
+        fix: true,
+        extension_target: None,
+        namespace_context: None,
+    };
+    assert_eq!(serialize(&document_node, &options), Err(Error::Namespace));
+
+    let result = serialize(&document_node, &WriteOptions::lenient());
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), document_node.to_string());
+}
+
+#[test]
+fn test_serialize_default_namespace_fix() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(Some("http://www.w3.org/1999/xhtml"), Some("html"), None)
+        .unwrap();
+
+    let result = serialize(&document_node, &WriteOptions::strict());
+    assert_eq!(result, Ok("<html></html>".to_string()));
+
+    let options = WriteOptions {
+        fix: true,
+        ..Default::default()
+    };
+    let result = serialize(&document_node, &options);
+    assert_eq!(
+        result,
+        Ok("<html xmlns=\"http://www.w3.org/1999/xhtml\"></html>".to_string())
+    );
+}
+
+#[test]
+fn test_write_document() {
+    let document_node = common::create_example_rdf_document();
+    for options in &[
+        WriteOptions::lenient(),
+        WriteOptions {
+            fix: true,
+            ..Default::default()
+        },
+    ] {
+        let mut output: Vec<u8> = Vec::new();
+        write_document(&document_node, &mut output, options).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            serialize(&document_node, options).unwrap()
+        );
+    }
+
+    let mut output: Vec<u8> = Vec::new();
+    assert_eq!(
+        write_document(&document_node, &mut output, &WriteOptions::strict()),
+        Err(Error::Namespace)
+    );
+}
+
+#[test]
+fn test_write_document_failed_sink() {
+    struct FailingWriter(usize);
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+            if self.0 < buf.len() {
+                Err(IoError::other("sink full"))
+            } else {
+                self.0 -= buf.len();
+                Ok(buf.len())
+            }
+        }
+
+        fn flush(&mut self) -> IoResult<()> {
+            Ok(())
+        }
+    }
+
+    let document_node = common::create_example_rdf_document();
+    let mut writer = FailingWriter(16);
+    assert_eq!(
+        write_document(&document_node, &mut writer, &WriteOptions::lenient()),
+        Err(Error::InvalidState)
+    );
+}
+
+///
+/// The example from Exclusive XML Canonicalization 1.0, §2.2, without white space.
+///
+fn create_c14n_example_document() -> (RefNode, RefNode) {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(Some("foo:bar"), Some("n0:local"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:n0", "foo:bar")
+        .unwrap();
+    root_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:n3", "ftp://example.org")
+        .unwrap();
+
+    let mut element_node = document
+        .create_element_ns("http://example.net", "n1:elem2")
+        .unwrap();
+    element_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:n1", "http://example.net")
+        .unwrap();
+    element_node
+        .set_attribute_ns(common::XML_NS_URI, "xml:lang", "en")
+        .unwrap();
+    let mut stuff_node = document
+        .create_element_ns("ftp://example.org", "n3:stuff")
+        .unwrap();
+    stuff_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:n3", "ftp://example.org")
+        .unwrap();
+    let _safe_to_ignore = element_node.append_child(stuff_node).unwrap();
+    let element_node = root_node.append_child(element_node).unwrap();
+    (document_node, element_node)
+}
+
+#[test]
+fn test_canonicalize_subset() {
+    let (_document_node, element_node) = create_c14n_example_document();
+
+    assert_eq!(
+        canonicalize(&element_node, &CanonicalOptions::inclusive()).unwrap(),
+        "<n1:elem2 xmlns:n0=\"foo:bar\" xmlns:n1=\"http://example.net\" \
+         xmlns:n3=\"ftp://example.org\" xml:lang=\"en\"><n3:stuff></n3:stuff></n1:elem2>"
+    );
+    assert_eq!(
+        canonicalize(&element_node, &CanonicalOptions::exclusive(&[])).unwrap(),
+        "<n1:elem2 xmlns:n1=\"http://example.net\" xml:lang=\"en\">\
+         <n3:stuff xmlns:n3=\"ftp://example.org\"></n3:stuff></n1:elem2>"
+    );
+    assert_eq!(
+        canonicalize(&element_node, &CanonicalOptions::exclusive(&["n0", "n2"])).unwrap(),
+        "<n1:elem2 xmlns:n0=\"foo:bar\" xmlns:n1=\"http://example.net\" xml:lang=\"en\">\
+         <n3:stuff xmlns:n3=\"ftp://example.org\"></n3:stuff></n1:elem2>"
+    );
+
+    let stuff_node = element_node.first_child().unwrap();
+    assert_eq!(
+        canonicalize(&stuff_node, &CanonicalOptions::inclusive()).unwrap(),
+        "<n3:stuff xmlns:n0=\"foo:bar\" xmlns:n1=\"http://example.net\" \
+         xmlns:n3=\"ftp://example.org\" xml:lang=\"en\"></n3:stuff>"
+    );
+    assert_eq!(
+        canonicalize(&stuff_node, &CanonicalOptions::exclusive(&[])).unwrap(),
+        "<n3:stuff xmlns:n3=\"ftp://example.org\"></n3:stuff>"
+    );
+}
+
+#[test]
+fn test_canonicalize_document() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("b", "2\t\"").unwrap();
+    root_node.set_attribute("a", "1").unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("x < y\r\n"))
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_cdata_section("a > b").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("inner"))
+        .unwrap();
+    let mut prolog_node = document_node.clone();
+    let _safe_to_ignore = prolog_node
+        .append_child(document.create_comment("after"))
+        .unwrap();
+    let _safe_to_ignore = prolog_node
+        .append_child(
+            document
+                .create_processing_instruction("pi", Some("data"))
+                .unwrap(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        canonicalize(&document_node, &CanonicalOptions::inclusive()).unwrap(),
+        "<doc a=\"1\" b=\"2&#x9;&quot;\">x &lt; y&#xD;\na &gt; b</doc>\n<?pi data?>"
+    );
+
+    let options = CanonicalOptions {
+        algorithm: Canonicalization::Exclusive(Vec::new()),
+        with_comments: true,
+    };
+    assert_eq!(
+        options.algorithm_uri(),
+        "http://www.w3.org/2001/10/xml-exc-c14n#WithComments"
+    );
+    let mut output: Vec<u8> = Vec::new();
+    write_canonical(&document_node, &mut output, &options).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "<doc a=\"1\" b=\"2&#x9;&quot;\">x &lt; y&#xD;\na &gt; b<!--inner--></doc>\n\
+         <!--after-->\n<?pi data?>"
+    );
+}
+
+#[test]
+fn test_serialize_default_namespace_undeclared() {
+    const XMLNS: &str = "http://www.w3.org/2000/xmlns/";
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(Some("urn:example:a"), Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node
+        .set_attribute_ns(XMLNS, "xmlns", "urn:example:a")
+        .unwrap();
+    let mut child_node = document.create_element("child").unwrap();
+    child_node.set_attribute_ns(XMLNS, "xmlns", "").unwrap();
+    let leaf_node = document.create_element("leaf").unwrap();
+    let _safe_to_ignore = child_node.append_child(leaf_node.clone()).unwrap();
+    let _safe_to_ignore = root_node.append_child(child_node.clone()).unwrap();
+
+    assert_eq!(
+        root_node.lookup_namespace_uri(None),
+        Some("urn:example:a".to_string())
+    );
+    assert_eq!(child_node.lookup_namespace_uri(None), None);
+    assert_eq!(leaf_node.lookup_namespace_uri(None), None);
+
+    let expected = r#"<root xmlns="urn:example:a"><child xmlns=""><leaf></leaf></child></root>"#;
+    assert_eq!(
+        serialize(&document_node, &WriteOptions::strict()),
+        Ok(expected.to_string())
+    );
+    let options = WriteOptions {
+        fix: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        serialize(&document_node, &options),
+        Ok(expected.to_string())
+    );
+
+    // without the un-declaration the fix-up adds it.
+    child_node.remove_attribute_ns(XMLNS, "xmlns").unwrap();
+    assert_eq!(
+        serialize(&document_node, &options),
+        Ok(expected.to_string())
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_namespace_context_fragment() {
+    use xml_dom::level2::convert::as_element;
+    use xml_dom::level2::ext::NamespaceContext;
+    use xml_dom::parser::{read_fragment, read_xml};
+
+    let document_node = read_xml(
+        r#"<catalog xmlns="urn:example:catalog" xmlns:dc="http://purl.org/dc/elements/1.1/"><book><dc:title dc:lang="en">Dune</dc:title></book></catalog>"#,
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut catalog_node = document.document_element().unwrap();
+    let book_node = catalog_node.first_child().unwrap();
+
+    let context = NamespaceContext::from_node(&book_node);
+    assert_eq!(
+        context.declarations(),
+        vec![
+            (None, "urn:example:catalog".to_string()),
+            (
+                Some("dc".to_string()),
+                "http://purl.org/dc/elements/1.1/".to_string()
+            ),
+        ]
+    );
+
+    let book_node = catalog_node.remove_child(book_node).unwrap();
+    assert_eq!(
+        serialize(&book_node, &WriteOptions::default()),
+        Err(Error::Namespace)
+    );
+    let options = WriteOptions {
+        namespace_context: Some(context.clone()),
+        ..Default::default()
+    };
+    let expected = r#"<book><dc:title dc:lang="en">Dune</dc:title></book>"#;
+    assert_eq!(serialize(&book_node, &options), Ok(expected.to_string()));
+
+    let fragment = read_fragment(&document_node, expected, &context).unwrap();
+    assert_eq!(fragment.node_type(), NodeType::DocumentFragment);
+    assert_eq!(fragment.owner_document(), Some(document_node.clone()));
+    let book_node = fragment.first_child().unwrap();
+    assert_eq!(
+        book_node.namespace_uri().as_deref(),
+        Some("urn:example:catalog")
+    );
+    let title_node = book_node.first_child().unwrap();
+    assert_eq!(
+        title_node.namespace_uri().as_deref(),
+        Some("http://purl.org/dc/elements/1.1/")
+    );
+    assert_eq!(
+        as_element(&title_node)
+            .unwrap()
+            .get_attribute_node_ns("http://purl.org/dc/elements/1.1/", "lang")
+            .and_then(|attribute| attribute.value())
+            .as_deref(),
+        Some("en")
+    );
+    assert_eq!(serialize(&fragment, &options), Ok(expected.to_string()));
+
+    let context = NamespaceContext::new();
+    assert!(read_fragment(&document_node, "<?xml version=\"1.0\"?><book/>", &context).is_err());
+    assert!(read_fragment(&document_node, "<book>", &context).is_err());
+    assert!(read_fragment(&book_node, "<book/>", &context).is_err());
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_serialize_attribute_order() {
+    use xml_dom::parser::read_xml;
+
+    let xml = r#"<order zeta="1" xmlns:b="urn:b" alpha="2" b:mid="3" beta="4"><line qty="2" sku="Z-1" at="now"/></order>"#;
+    let document_node = read_xml(xml).unwrap();
+    assert_eq!(
+        serialize(&document_node, &WriteOptions::default()).unwrap(),
+        r#"<order zeta="1" xmlns:b="urn:b" alpha="2" b:mid="3" beta="4"><line qty="2" sku="Z-1" at="now"></line></order>"#
+    );
+    assert_eq!(
+        document_node.to_string(),
+        serialize(&document_node, &WriteOptions::default()).unwrap()
+    );
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_serialize_encoded() {
+    use xml_dom::level2::ext::serializer::serialize_encoded;
+    use xml_dom::level2::ext::DocumentDecl;
+
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("note"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("price", "€5").unwrap();
+    let text_node = document.create_text_node("café ☕");
+    let _safe_to_ignore = root_node.append_child(text_node).unwrap();
+    let options = WriteOptions::default();
+
+    let result = serialize_encoded(&document_node, &options, "UTF-16").unwrap();
+    let expected = r#"<?xml version="1.0" encoding="UTF-16"?><note price="€5">café ☕</note>"#;
+    let mut expected_bytes = vec![0xFE, 0xFF];
+    for unit in expected.encode_utf16() {
+        expected_bytes.extend_from_slice(&unit.to_be_bytes());
+    }
+    assert_eq!(result, expected_bytes);
+
+    let result = serialize_encoded(&document_node, &options, "utf-16le").unwrap();
+    assert_eq!(&result[..4], &[b'<', 0, b'?', 0]);
+
+    let result = serialize_encoded(&document_node, &options, "ISO-8859-1").unwrap();
+    let mut expected_bytes =
+        br#"<?xml version="1.0" encoding="ISO-8859-1"?><note price="&#8364;5">caf"#.to_vec();
+    expected_bytes.push(0xE9);
+    expected_bytes.extend_from_slice(b" &#9749;</note>");
+    assert_eq!(result, expected_bytes);
+
+    let result = serialize_encoded(&document_node, &options, "windows-1252").unwrap();
+    let mut expected_bytes =
+        br#"<?xml version="1.0" encoding="windows-1252"?><note price=""#.to_vec();
+    expected_bytes.push(0x80);
+    expected_bytes.extend_from_slice(b"5\">caf");
+    expected_bytes.push(0xE9);
+    expected_bytes.extend_from_slice(b" &#9749;</note>");
+    assert_eq!(result, expected_bytes);
+
+    let result = serialize_encoded(&root_node, &options, "UTF-8").unwrap();
+    assert_eq!(
+        String::from_utf8(result).unwrap(),
+        r#"<note price="€5">café ☕</note>"#
+    );
+
+    assert_eq!(
+        serialize_encoded(&document_node, &options, "x-unknown").err(),
+        Some(Error::NotSupported)
+    );
+    assert_eq!(
+        serialize_encoded(&document_node, &options, "replacement").err(),
+        Some(Error::NotSupported)
+    );
+    assert!(document_node.xml_declaration().is_none());
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_serialize_encoded_markup() {
+    use xml_dom::level2::ext::serializer::serialize_encoded;
+
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("note"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let cdata_node = document.create_cdata_section("1 €, 2 €").unwrap();
+    let _safe_to_ignore = root_node.append_child(cdata_node).unwrap();
+    let options = WriteOptions::default();
+
+    let result = serialize_encoded(&root_node, &options, "ISO-8859-1").unwrap();
+    assert_eq!(
+        String::from_utf8(result).unwrap(),
+        "<note><![CDATA[1 ]]>&#8364;<![CDATA[, 2 ]]>&#8364;<![CDATA[]]></note>"
+    );
+
+    let comment_node = root_node
+        .append_child(document.create_comment("€"))
+        .unwrap();
+    assert_eq!(
+        serialize_encoded(&root_node, &options, "ISO-8859-1").err(),
+        Some(Error::InvalidCharacter)
+    );
+    let _safe_to_ignore = root_node.remove_child(comment_node).unwrap();
+
+    let _safe_to_ignore = root_node
+        .append_child(
+            document
+                .create_processing_instruction("pi", Some("€"))
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        serialize_encoded(&root_node, &options, "ISO-8859-1").err(),
+        Some(Error::InvalidCharacter)
+    );
+
+    let element_node = document.create_element("r\u{101}").unwrap();
+    assert_eq!(
+        serialize_encoded(&element_node, &options, "windows-1252").err(),
+        Some(Error::InvalidCharacter)
+    );
+    assert!(serialize_encoded(&element_node, &options, "UTF-8").is_ok());
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "soap")]
+
+use xml_dom::level2::convert::{as_document, as_element_mut};
+use xml_dom::level2::ext::soap::{Envelope, SoapVersion};
+use xml_dom::level2::{get_implementation, Error, Node, RefNode};
+
+fn append_element(parent: &mut RefNode, name: &str, text: Option<&str>) -> RefNode {
+    let document_node = parent.owner_document().unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut element = document.create_element(name).unwrap();
+    if let Some(text) = text {
+        let _safe_to_ignore = element
+            .append_child(document.create_text_node(text))
+            .unwrap();
+    }
+    parent.append_child(element).unwrap()
+}
+
+#[test]
+fn test_soap_11_prefix_declared_by_attribute() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("s:Envelope"), None)
+        .unwrap();
+    let mut envelope_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    as_element_mut(&mut envelope_node)
+        .unwrap()
+        .set_attribute("xmlns:s", SoapVersion::V11.namespace_uri())
+        .unwrap();
+
+    let mut header_node = append_element(&mut envelope_node, "s:Header", None);
+    let mut auth_node = append_element(&mut header_node, "auth", Some("secret"));
+    {
+        let auth = as_element_mut(&mut auth_node).unwrap();
+        auth.set_attribute("s:mustUnderstand", "1").unwrap();
+        auth.set_attribute("s:actor", "http://example.org/gateway")
+            .unwrap();
+    }
+    let _safe_to_ignore = append_element(&mut header_node, "trace", None);
+
+    let mut body_node = append_element(&mut envelope_node, "s:Body", None);
+    let mut fault_node = append_element(&mut body_node, "s:Fault", None);
+    let _safe_to_ignore = append_element(&mut fault_node, "faultcode", Some("s:Client"));
+    let _safe_to_ignore = append_element(&mut fault_node, "faultstring", Some("Bad request"));
+    let _safe_to_ignore = append_element(&mut fault_node, "detail", None);
+
+    let envelope = Envelope::from_document(&document_node).unwrap();
+    assert_eq!(envelope.version(), SoapVersion::V11);
+    assert_eq!(envelope.header(), Some(header_node));
+
+    let headers = envelope.headers();
+    assert_eq!(headers.len(), 2);
+    assert!(headers[0].must_understand);
+    assert_eq!(
+        headers[0].role,
+        Some("http://example.org/gateway".to_string())
+    );
+    assert!(!headers[1].must_understand);
+    assert_eq!(envelope.must_understand_headers().len(), 1);
+
+    let fault = envelope.fault().unwrap();
+    assert_eq!(fault.element, fault_node);
+    assert_eq!(fault.code, Some("s:Client".to_string()));
+    assert_eq!(fault.reason, Some("Bad request".to_string()));
+    assert_eq!(fault.actor, None);
+    assert!(fault.detail.is_some());
+}
+
+#[test]
+fn test_soap_12_fault() {
+    let ns = SoapVersion::V12.namespace_uri();
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(Some(ns), Some("env:Envelope"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut envelope_node = document.document_element().unwrap();
+
+    let create = |parent: &mut RefNode, name: &str, text: Option<&str>| -> RefNode {
+        let mut element = document.create_element_ns(ns, name).unwrap();
+        if let Some(text) = text {
+            let _safe_to_ignore = element
+                .append_child(document.create_text_node(text))
+                .unwrap();
+        }
+        parent.append_child(element).unwrap()
+    };
+    let mut body_node = create(&mut envelope_node, "env:Body", None);
+    let mut fault_node = create(&mut body_node, "env:Fault", None);
+    let mut code_node = create(&mut fault_node, "env:Code", None);
+    let _safe_to_ignore = create(&mut code_node, "env:Value", Some("env:Sender"));
+    let mut reason_node = create(&mut fault_node, "env:Reason", None);
+    let _safe_to_ignore = create(&mut reason_node, "env:Text", Some("Invalid"));
+    let _safe_to_ignore = create(&mut fault_node, "env:Node", Some("urn:node"));
+
+    let envelope = Envelope::from_document(&document_node).unwrap();
+    assert_eq!(envelope.version(), SoapVersion::V12);
+    assert!(envelope.headers().is_empty());
+    let fault = envelope.fault().unwrap();
+    assert_eq!(fault.code, Some("env:Sender".to_string()));
+    assert_eq!(fault.reason, Some("Invalid".to_string()));
+    assert_eq!(fault.actor, Some("urn:node".to_string()));
+    assert!(fault.detail.is_none());
+}
+
+#[test]
+fn test_not_an_envelope() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("Envelope"), None)
+        .unwrap();
+    assert_eq!(
+        Envelope::from_document(&document_node).err(),
+        Some(Error::NotFound)
+    );
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::split::SplitOptions;
+use xml_dom::level2::ext::{DocumentDecl, DocumentSplit};
+use xml_dom::level2::*;
+
+pub mod common;
+
+const EXPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- nightly export -->
+<!DOCTYPE export [<!ENTITY org "Example Corp">]>
+<?stylesheet href="export.xsl"?>
+<export xmlns="urn:export" xmlns:p="urn:people"><batch xmlns:p="urn:people:v2" xmlns:x="urn:extra"><p:person id="1">Ann</p:person><p:person id="2" xmlns:x="urn:other"><p:person id="3">Cid</p:person></p:person></batch></export>"#;
+
+#[test]
+fn test_split_inherited_namespaces() {
+    let document_node = xml_dom::parser::read_xml(EXPORT).unwrap();
+    let documents = document_node
+        .split_document("person", &SplitOptions::new())
+        .unwrap();
+    assert_eq!(documents.len(), 3);
+
+    let root_node = as_document(&documents[0])
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(root_node.attribute_count(), 4);
+    assert_eq!(
+        root_node.get_attribute("xmlns"),
+        Some("urn:export".to_string())
+    );
+    assert_eq!(
+        root_node.get_attribute("xmlns:p"),
+        Some("urn:people:v2".to_string())
+    );
+    assert_eq!(
+        root_node.get_attribute("xmlns:x"),
+        Some("urn:extra".to_string())
+    );
+    assert_eq!(
+        root_node.first_child().unwrap().node_value(),
+        Some("Ann".to_string())
+    );
+    let document = as_document(&documents[1]).unwrap();
+    let root_node = document.document_element().unwrap();
+    assert_eq!(
+        root_node.get_attribute("xmlns:x"),
+        Some("urn:other".to_string())
+    );
+    assert_eq!(
+        root_node.get_attribute("xmlns:p"),
+        Some("urn:people:v2".to_string())
+    );
+    assert_eq!(root_node.child_nodes().len(), 1);
+    assert!(document.doc_type().is_none());
+    assert!(documents[1].xml_declaration().is_none());
+
+    // the source document is unchanged.
+    let source = as_document(&document_node).unwrap();
+    assert_eq!(source.get_elements_by_tag_name("p:person").len(), 3);
+    assert_eq!(documents[2].owner_document(), None);
+}
+
+#[test]
+fn test_split_copy_prolog() {
+    let document_node = xml_dom::parser::read_xml(EXPORT).unwrap();
+    let mut options = SplitOptions::new();
+    options.set_copy_prolog();
+    assert!(options.has_copy_prolog());
+    let documents = document_node
+        .split_document("batch > person", &options)
+        .unwrap();
+    assert_eq!(documents.len(), 2);
+
+    let text = documents[0].to_string();
+    assert!(text.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    let comment = text.find("<!-- nightly export -->").unwrap();
+    let doc_type = text.find("<!DOCTYPE export").unwrap();
+    let pi = text.find(r#"<?stylesheet href="export.xsl"?>"#).unwrap();
+    let element = text.find("<p:person").unwrap();
+    assert!(comment < doc_type && doc_type < pi && pi < element);
+    assert!(text.contains(r#"<!ENTITY org "Example Corp">"#));
+
+    let document = as_document(&documents[1]).unwrap();
+    assert_eq!(
+        document.doc_type().unwrap().node_name().to_string(),
+        "export"
+    );
+}
+
+#[test]
+fn test_split_no_matches() {
+    let document_node = common::create_example_rdf_document();
+    let documents = document_node
+        .split_document("missing", &SplitOptions::default())
+        .unwrap();
+    assert!(documents.is_empty());
+
+    let result = document_node.split_document("[", &SplitOptions::default());
+    assert_eq!(result, Err(Error::Syntax));
+}
@@ -0,0 +1,85 @@
+use std::thread;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::SyncDocument;
+use xml_dom::level2::{get_implementation, Element, Error, Node};
+
+pub mod common;
+
+#[test]
+fn test_sync_document_threads() {
+    let document = SyncDocument::spawn(|| Ok(common::create_example_rdf_document())).unwrap();
+
+    let workers: Vec<thread::JoinHandle<()>> = (0..8)
+        .map(|id| {
+            let document = document.clone();
+            thread::spawn(move || {
+                document
+                    .mutate(move |document_node| {
+                        let document = as_document(document_node).unwrap();
+                        let mut root_node = document.document_element().unwrap();
+                        let mut item_node = document.create_element("item")?;
+                        item_node.set_attribute("id", &id.to_string())?;
+                        let _safe_to_ignore = root_node.append_child(item_node)?;
+                        Ok(())
+                    })
+                    .unwrap();
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let mut ids = document
+        .query(|document_node| {
+            let document = as_document(document_node).unwrap();
+            document
+                .get_elements_by_tag_name("item")
+                .iter()
+                .map(|item| item.get_attribute("id").unwrap())
+                .collect::<Vec<String>>()
+        })
+        .unwrap();
+    ids.sort();
+    assert_eq!(ids, vec!["0", "1", "2", "3", "4", "5", "6", "7"]);
+}
+
+#[test]
+fn test_sync_document_errors() {
+    assert_eq!(
+        SyncDocument::spawn(|| Err(Error::NotSupported)).err(),
+        Some(Error::NotSupported)
+    );
+    assert_eq!(
+        SyncDocument::spawn(|| {
+            let document_node = get_implementation()
+                .create_document(None, Some("root"), None)
+                .unwrap();
+            Ok(document_node.first_child().unwrap())
+        })
+        .err(),
+        Some(Error::InvalidState)
+    );
+
+    let document =
+        SyncDocument::spawn(|| get_implementation().create_document(None, Some("root"), None))
+            .unwrap();
+    assert_eq!(
+        document.mutate(|document_node| {
+            let document = as_document(document_node).unwrap();
+            let mut root_node = document.document_element().unwrap();
+            root_node.set_attribute("", "empty")
+        }),
+        Err(Error::Syntax)
+    );
+
+    let inner = document.clone();
+    assert_eq!(
+        document.query(move |_| inner.query(|document_node| document_node.to_string())),
+        Ok(Err(Error::InvalidState))
+    );
+    assert_eq!(
+        document.query(|document_node| document_node.to_string()),
+        Ok("<root></root>".to_string())
+    );
+}
@@ -2,7 +2,9 @@ use xml_dom::level2::convert::{
     as_cdata_section, as_cdata_section_mut, as_document, as_document_mut, as_element,
     as_element_mut, as_text, as_text_mut,
 };
-use xml_dom::level2::Error;
+use xml_dom::level2::ext::dom_impl::get_implementation_ext;
+use xml_dom::level2::ext::{ProcessingOptions, TextWhitespace, TreeBuilder};
+use xml_dom::level2::{CharacterData, Element, Error, Node, NodeType, Text};
 
 pub mod common;
 
@@ -11,7 +13,7 @@ fn test_text_node_contents() {
     //
     // Note, this test character escaping, "&" should be "&#38;" in the tree.
     //
-    let text_values = vec![
+    let text_values = [
         "Rose Bush",
         "A Guide to Growing Roses",
         "Describes process for planting &#38; nurturing different kinds of rose bushes.",
@@ -27,11 +29,11 @@ fn test_text_node_contents() {
     let description_element = as_element(&description_node).unwrap();
 
     for (index, child) in description_element.child_nodes().iter().enumerate() {
-        let child_element = as_element(&child).unwrap();
+        let child_element = as_element(child).unwrap();
         let children = child_element.child_nodes();
         assert_eq!(children.len(), 1);
         let text = children.first().unwrap();
-        let text = as_text(&text).unwrap();
+        let text = as_text(text).unwrap();
         assert_eq!(text.data().unwrap(), text_values[index].to_string());
     }
 }
@@ -51,12 +53,16 @@ fn test_text_substring() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     assert_eq!(text.data(), Some("Hello cruel world!".to_string()));
     assert_eq!(text.substring_data(0, 0), Ok("".to_string()));
     assert_eq!(text.substring_data(6, 5), Ok("cruel".to_string()));
     assert_eq!(text.substring_data(12, 10), Ok("world!".to_string()));
+    assert_eq!(
+        text.substring_data(12, usize::MAX),
+        Ok("world!".to_string())
+    );
     assert_eq!(text.substring_data(20, 5), Err(Error::IndexSize));
 }
 
@@ -75,11 +81,11 @@ fn test_text_insert() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     assert_eq!(text.data(), Some("Hello cruel world!".to_string()));
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.insert_data(6, "my ");
     assert!(result.is_ok());
@@ -94,6 +100,35 @@ fn test_text_insert() {
     assert_eq!(text.data(), Some("¡Hello my cruel world!".to_string()));
 }
 
+#[test]
+fn test_text_append() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let mut text_node = document.create_text_node("Hello");
+    text_node.append_data(" cruel").unwrap();
+    text_node.append_many(vec![" ", "world", "!"]).unwrap();
+    assert_eq!(
+        text_node.node_value(),
+        Some("Hello cruel world!".to_string())
+    );
+
+    let chunks: Vec<String> = (0..3).map(|i| i.to_string()).collect();
+    text_node.reserve(3).unwrap();
+    text_node.append_many(&chunks).unwrap();
+    assert_eq!(
+        text_node.node_value(),
+        Some("Hello cruel world!012".to_string())
+    );
+
+    let mut cdata_node = document.create_cdata_section("").unwrap();
+    cdata_node.append_many(Vec::<&str>::new()).unwrap();
+    cdata_node.reserve(0).unwrap();
+    assert_eq!(cdata_node.node_value(), Some("".to_string()));
+    cdata_node.append_many(vec!["<", "&", ">"]).unwrap();
+    assert_eq!(cdata_node.node_value(), Some("<&>".to_string()));
+}
+
 #[test]
 fn test_text_replace() {
     let mut document_node = common::create_empty_rdf_document();
@@ -109,11 +144,11 @@ fn test_text_replace() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     assert_eq!(text.data(), Some("Hello cruel world!".to_string()));
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.replace_data(6, 6, "my happy ");
     assert!(result.is_ok());
@@ -146,11 +181,11 @@ fn test_text_delete() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     assert_eq!(text.data(), Some("Hello cruel world!".to_string()));
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.delete_data(6, 6);
     assert!(result.is_ok());
@@ -171,6 +206,10 @@ fn test_text_delete() {
     let result = text.delete_data(6, 20);
     assert!(result.is_ok());
     assert_eq!(text.data(), Some("Hello ".to_string()));
+
+    let result = text.delete_data(2, usize::MAX);
+    assert!(result.is_ok());
+    assert_eq!(text.data(), Some("He".to_string()));
 }
 
 #[test]
@@ -187,15 +226,15 @@ fn test_cdata_split() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let cdata = as_cdata_section_mut(&mut text_node).unwrap();
     let result = cdata.split(3);
     assert!(result.is_ok());
 
-    let expected = vec!["one", "two"];
+    let expected = ["one", "two"];
     for (index, child_node) in root_element.child_nodes().iter().enumerate() {
         // The following also ensures `node_type == NodeType::CData`
-        let text = as_cdata_section(&child_node).unwrap();
+        let text = as_cdata_section(child_node).unwrap();
         assert_eq!(text.data().unwrap(), expected[index].to_string());
     }
 }
@@ -208,7 +247,7 @@ fn test_text_split() {
     let mut root_node = document.document_element().unwrap();
     let root_element = as_element_mut(&mut root_node).unwrap();
 
-    for content in vec!["onetwo", "threefour", "fivesix"] {
+    for content in ["onetwo", "threefour", "fivesix"] {
         let text_node = document.create_text_node(content);
         let _ignore = root_element.append_child(text_node);
     }
@@ -216,7 +255,7 @@ fn test_text_split() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 3);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.split(3);
     assert!(result.is_ok());
@@ -240,10 +279,269 @@ fn test_text_split() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 6);
 
-    let expected = vec!["one", "two", "", "threefour", "fivesix", ""];
+    let expected = ["one", "two", "", "threefour", "fivesix", ""];
     for (index, child_node) in root_element.child_nodes().iter().enumerate() {
         // The following also ensures `node_type == NodeType::Text`
-        let text = as_text(&child_node).unwrap();
+        let text = as_text(child_node).unwrap();
         assert_eq!(text.data().unwrap(), expected[index].to_string());
     }
 }
+
+#[test]
+fn test_text_max_length() {
+    let mut options = ProcessingOptions::new();
+    options.set_max_text_length(4);
+    let implementation = get_implementation_ext();
+    let document_node = implementation
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("first").unwrap())
+        .unwrap();
+    let text_node = root_node
+        .append_child(document.create_text_node("Hel\u{1F600}lo world"))
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("last").unwrap())
+        .unwrap();
+
+    let children = root_node.child_nodes();
+    assert_eq!(children.len(), 6);
+    assert_eq!(&children[1], &text_node);
+    assert_eq!(
+        children[1..5]
+            .iter()
+            .map(|child| {
+                assert_eq!(child.node_type(), NodeType::Text);
+                child.node_value().unwrap()
+            })
+            .collect::<Vec<String>>(),
+        vec!["Hel", "\u{1F600}lo", " wor", "ld"]
+    );
+    assert_eq!(
+        root_node.to_string(),
+        "<root><first></first>Hel\u{1F600}lo world<last></last></root>"
+    );
+
+    root_node.normalize();
+    let children = root_node.child_nodes();
+    assert_eq!(children.len(), 3);
+    assert_eq!(
+        children[1].node_value(),
+        Some("Hel\u{1F600}lo world".to_string())
+    );
+
+    let cdata_node = root_node
+        .append_child(document.create_cdata_section("Hello world").unwrap())
+        .unwrap();
+    assert_eq!(cdata_node.node_value(), Some("Hello world".to_string()));
+    assert_eq!(root_node.child_nodes().len(), 4);
+}
+
+#[test]
+fn test_text_max_length_mutators() {
+    let mut options = ProcessingOptions::new();
+    options.set_max_text_length(4);
+    let implementation = get_implementation_ext();
+    let document_node = implementation
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let mut text_node = root_node
+        .append_child(document.create_text_node("Hel"))
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("last").unwrap())
+        .unwrap();
+
+    text_node.append_data("lo").unwrap();
+    text_node.insert_data(0, "Oh, ").unwrap();
+    let children = root_node.child_nodes();
+    assert_eq!(
+        children
+            .iter()
+            .filter(|child| child.node_type() == NodeType::Text)
+            .map(|child| child.node_value().unwrap())
+            .collect::<Vec<String>>(),
+        vec!["Oh, ", "Hell", "o"]
+    );
+    assert_eq!(root_node.to_string(), "<root>Oh, Hello<last></last></root>");
+
+    let mut orphan_node = document.create_text_node("Hel");
+    orphan_node.append_data("lo world").unwrap();
+    assert_eq!(orphan_node.node_value(), Some("Hello world".to_string()));
+}
+
+#[test]
+fn test_element_content_whitespace() {
+    let mut builder = TreeBuilder::new();
+    builder
+        .document_type(
+            "memo",
+            None,
+            None,
+            Some(
+                "<!ELEMENT memo (to, note)><!ELEMENT note (#PCDATA | em)*><!ELEMENT to (#PCDATA)>",
+            ),
+        )
+        .unwrap();
+    builder.start_element("memo", &[]).unwrap();
+    builder.text("\n  ").unwrap();
+    builder.start_element("to", &[]).unwrap();
+    builder.text(" ").unwrap();
+    builder.end_element("to").unwrap();
+    builder.start_element("note", &[]).unwrap();
+    builder.text(" ").unwrap();
+    builder.start_element("em", &[]).unwrap();
+    builder.end_element("em").unwrap();
+    builder.end_element("note").unwrap();
+    builder.start_element("list", &[]).unwrap();
+    builder.text("\t").unwrap();
+    builder.start_element("item", &[]).unwrap();
+    builder.text(" ").unwrap();
+    builder.end_element("item").unwrap();
+    builder.end_element("list").unwrap();
+    builder.end_element("memo").unwrap();
+    let document_node = builder.finish().unwrap();
+    let document = as_document(&document_node).unwrap();
+    let text_of = |name: &str| {
+        document
+            .get_elements_by_tag_name(name)
+            .first()
+            .unwrap()
+            .first_child()
+            .unwrap()
+    };
+
+    // declared element content, and mixed content
+    assert!(text_of("memo").is_element_content_whitespace());
+    assert!(!text_of("to").is_element_content_whitespace());
+    assert!(!text_of("note").is_element_content_whitespace());
+
+    // undeclared, with and without child elements
+    assert!(text_of("list").is_element_content_whitespace());
+    assert!(!text_of("item").is_element_content_whitespace());
+
+    let mut list_node = document.get_elements_by_tag_name("list").remove(0);
+    list_node
+        .set_attribute_ns(
+            "http://www.w3.org/XML/1998/namespace",
+            "xml:space",
+            "preserve",
+        )
+        .unwrap();
+    assert!(!text_of("list").is_element_content_whitespace());
+
+    let mut memo_node = document.document_element().unwrap();
+    let cdata_node = memo_node
+        .append_child(document.create_cdata_section(" ").unwrap())
+        .unwrap();
+    assert!(!cdata_node.is_element_content_whitespace());
+    let text_node = memo_node
+        .append_child(document.create_text_node("text"))
+        .unwrap();
+    assert!(!text_node.is_element_content_whitespace());
+    assert!(!document
+        .create_text_node(" ")
+        .is_element_content_whitespace());
+}
+
+#[test]
+fn test_text_utf16_offsets() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    // "é" is one UTF-16 code unit and two bytes, "𝄞" a surrogate pair and four bytes.
+    let mut text_node = document.create_text_node("café 𝄞 clef");
+    assert_eq!(text_node.length(), 12);
+    assert_eq!(text_node.substring_data(3, 4), Ok("é 𝄞".to_string()));
+    assert_eq!(text_node.substring_data(7, 20), Ok(" clef".to_string()));
+    assert_eq!(text_node.substring_data(6, 1), Err(Error::IndexSize));
+    assert_eq!(text_node.substring_data(5, 1), Err(Error::IndexSize));
+    assert_eq!(text_node.substring_data(13, 1), Err(Error::IndexSize));
+
+    text_node.insert_data(12, "!").unwrap();
+    text_node.replace_data(5, 2, "♪").unwrap();
+    text_node.delete_data(3, 1).unwrap();
+    assert_eq!(text_node.node_value(), Some("caf ♪ clef!".to_string()));
+    assert_eq!(text_node.insert_data(12, "!"), Err(Error::IndexSize));
+
+    let mut root_node = document.document_element().unwrap();
+    let mut text_node = root_node
+        .append_child(document.create_text_node("𝄞𝄞"))
+        .unwrap();
+    assert_eq!(text_node.split(1), Err(Error::IndexSize));
+    let new_node = text_node.split(2).unwrap();
+    assert_eq!(text_node.node_value(), Some("𝄞".to_string()));
+    assert_eq!(new_node.node_value(), Some("𝄞".to_string()));
+    let new_node = text_node.split(5).unwrap();
+    assert_eq!(text_node.node_value(), Some("𝄞".to_string()));
+    assert_eq!(new_node.node_value(), Some(String::new()));
+}
+
+#[test]
+fn test_text_byte_offsets() {
+    let mut options = ProcessingOptions::new();
+    options.set_byte_offsets();
+    let implementation = get_implementation_ext();
+    let document_node = implementation
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+
+    let mut text_node = document.create_text_node("café 𝄞 clef");
+    assert_eq!(text_node.length(), 15);
+    assert_eq!(text_node.substring_data(3, 7), Ok("é 𝄞".to_string()));
+    assert_eq!(text_node.substring_data(4, 1), Err(Error::IndexSize));
+
+    text_node.replace_data(6, 4, "♪").unwrap();
+    assert_eq!(text_node.node_value(), Some("café ♪ clef".to_string()));
+}
+
+#[test]
+fn test_text_offsets_never_split_characters() {
+    const DATA: &str = "aé€𝄞";
+    let mut options = ProcessingOptions::new();
+    options.set_byte_offsets();
+    let byte_document_node = get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let utf16_document_node = common::create_empty_rdf_document();
+
+    for document_node in [byte_document_node, utf16_document_node] {
+        let document = as_document(&document_node).unwrap();
+        let length = document.create_text_node(DATA).length();
+        for offset in 0..=length + 1 {
+            for count in 0..=length + 1 {
+                let text_node = document.create_text_node(DATA);
+                if let Ok(data) = text_node.substring_data(offset, count) {
+                    assert!(DATA.contains(&data));
+                }
+                let mut text_node = document.create_text_node(DATA);
+                match text_node.replace_data(offset, count, "-") {
+                    Ok(()) => assert!(text_node.node_value().unwrap().contains('-')),
+                    Err(e) => {
+                        assert_eq!(e, Error::IndexSize);
+                        assert_eq!(text_node.node_value(), Some(DATA.to_string()));
+                    }
+                }
+            }
+            let mut text_node = document.create_text_node(DATA);
+            match text_node.split(offset) {
+                Ok(new_node) => assert_eq!(
+                    text_node.node_value().unwrap() + &new_node.node_value().unwrap(),
+                    DATA
+                ),
+                Err(e) => {
+                    assert_eq!(e, Error::IndexSize);
+                    assert_eq!(text_node.node_value(), Some(DATA.to_string()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::rc::Rc;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::traversal::*;
+use xml_dom::level2::{get_implementation, Error, Node, RefNode};
+
+pub mod common;
+
+// <root>
+//   <a>one<x>two</x></a>
+//   <!--three-->
+//   <b><c>four</c><d/></b>
+//   <e>five</e>
+// </root>
+
+fn create_document() -> (RefNode, RefNode) {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let element = |name: &str, text: Option<&str>| {
+        let mut element_node = document.create_element(name).unwrap();
+        if let Some(text) = text {
+            let _safe_to_ignore = element_node
+                .append_child(document.create_text_node(text))
+                .unwrap();
+        }
+        element_node
+    };
+
+    let mut a_node = element("a", Some("one"));
+    let _safe_to_ignore = a_node.append_child(element("x", Some("two"))).unwrap();
+    let _safe_to_ignore = root_node.append_child(a_node).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("three"))
+        .unwrap();
+    let mut b_node = element("b", None);
+    let _safe_to_ignore = b_node.append_child(element("c", Some("four"))).unwrap();
+    let _safe_to_ignore = b_node.append_child(element("d", None)).unwrap();
+    let _safe_to_ignore = root_node.append_child(b_node).unwrap();
+    let _safe_to_ignore = root_node.append_child(element("e", Some("five"))).unwrap();
+    (document_node, root_node)
+}
+
+fn label(node: &RefNode) -> String {
+    match node.node_value() {
+        Some(value) => value,
+        None => node.node_name().to_string(),
+    }
+}
+
+fn forward(walker: &mut TreeWalker) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    while let Some(node) = walker.next_node() {
+        labels.push(label(&node));
+    }
+    labels
+}
+
+fn backward(walker: &mut TreeWalker) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    while let Some(node) = walker.previous_node() {
+        labels.push(label(&node));
+    }
+    labels
+}
+
+fn iterate(iterator: &mut NodeIterator) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    while let Some(node) = iterator.next_node().unwrap() {
+        labels.push(label(&node));
+    }
+    labels
+}
+
+fn iterate_back(iterator: &mut NodeIterator) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    while let Some(node) = iterator.previous_node().unwrap() {
+        labels.push(label(&node));
+    }
+    labels
+}
+
+fn named(name: &'static str, result: FilterResult) -> Option<Rc<dyn NodeFilter>> {
+    Some(Rc::new(move |node: &RefNode| {
+        if node.node_name().to_string() == name {
+            result
+        } else {
+            FilterResult::Accept
+        }
+    }))
+}
+
+#[test]
+fn test_has_feature() {
+    let implementation = get_implementation();
+    assert!(implementation.has_feature("Traversal", "2.0"));
+    assert!(!implementation.has_feature("Traversal", "1.0"));
+}
+
+#[test]
+fn test_document_order() {
+    let (document_node, root_node) = create_document();
+
+    let mut walker = document_node.create_tree_walker(root_node.clone(), SHOW_ALL, None, false);
+    assert_eq!(walker.root(), root_node);
+    assert_eq!(walker.what_to_show(), SHOW_ALL);
+    assert!(walker.filter().is_none());
+    assert_eq!(
+        forward(&mut walker),
+        vec!["a", "one", "x", "two", "three", "b", "c", "four", "d", "e", "five"]
+    );
+    assert_eq!(label(&walker.current_node()), "five");
+    assert_eq!(
+        backward(&mut walker),
+        vec!["e", "d", "four", "c", "b", "three", "two", "x", "one", "a", "root"]
+    );
+    assert_eq!(walker.current_node(), root_node);
+
+    let mut walker = document_node.create_tree_walker(root_node.clone(), SHOW_ELEMENT, None, false);
+    assert_eq!(forward(&mut walker), vec!["a", "x", "b", "c", "d", "e"]);
+
+    let mut walker =
+        document_node.create_tree_walker(root_node.clone(), SHOW_TEXT | SHOW_COMMENT, None, false);
+    assert_eq!(
+        forward(&mut walker),
+        vec!["one", "two", "three", "four", "five"]
+    );
+    assert_eq!(backward(&mut walker), vec!["four", "three", "two", "one"]);
+
+    let mut walker =
+        document_node.create_tree_walker(document_node.clone(), SHOW_ELEMENT, None, false);
+    assert_eq!(forward(&mut walker).len(), 7);
+}
+
+#[test]
+fn test_filters() {
+    let (document_node, root_node) = create_document();
+
+    let mut walker = document_node.create_tree_walker(
+        root_node.clone(),
+        SHOW_ELEMENT,
+        named("b", FilterResult::Reject),
+        false,
+    );
+    assert_eq!(forward(&mut walker), vec!["a", "x", "e"]);
+    assert_eq!(backward(&mut walker), vec!["x", "a", "root"]);
+
+    let mut walker = document_node.create_tree_walker(
+        root_node.clone(),
+        SHOW_ELEMENT,
+        named("b", FilterResult::Skip),
+        false,
+    );
+    assert_eq!(forward(&mut walker), vec!["a", "x", "c", "d", "e"]);
+    assert_eq!(backward(&mut walker), vec!["d", "c", "x", "a", "root"]);
+}
+
+#[test]
+fn test_navigation() {
+    let (document_node, root_node) = create_document();
+
+    let mut walker = document_node.create_tree_walker(
+        root_node.clone(),
+        SHOW_ELEMENT,
+        named("b", FilterResult::Skip),
+        false,
+    );
+    assert!(walker.parent_node().is_none());
+    assert!(walker.next_sibling().is_none());
+    assert_eq!(label(&walker.first_child().unwrap()), "a");
+    assert_eq!(label(&walker.next_sibling().unwrap()), "c");
+    assert_eq!(label(&walker.next_sibling().unwrap()), "d");
+    assert_eq!(label(&walker.next_sibling().unwrap()), "e");
+    assert!(walker.next_sibling().is_none());
+    assert_eq!(label(&walker.current_node()), "e");
+    assert!(walker.first_child().is_none());
+    assert_eq!(label(&walker.previous_sibling().unwrap()), "d");
+    assert_eq!(label(&walker.parent_node().unwrap()), "root");
+    assert_eq!(label(&walker.last_child().unwrap()), "e");
+
+    let x_node = root_node.first_child().unwrap().last_child().unwrap();
+    walker.set_current_node(x_node);
+    assert!(walker.previous_sibling().is_none());
+    assert_eq!(label(&walker.parent_node().unwrap()), "a");
+    assert!(walker.previous_sibling().is_none());
+
+    let a_node = root_node.first_child().unwrap();
+    let mut walker = document_node.create_tree_walker(a_node, SHOW_ALL, None, false);
+    assert_eq!(forward(&mut walker), vec!["one", "x", "two"]);
+    assert_eq!(label(&walker.parent_node().unwrap()), "x");
+    assert_eq!(label(&walker.parent_node().unwrap()), "a");
+    assert!(walker.parent_node().is_none());
+    assert!(walker.next_sibling().is_none());
+}
+
+#[test]
+fn test_iterator_order() {
+    let (document_node, root_node) = create_document();
+
+    let mut iterator = document_node.create_node_iterator(root_node.clone(), SHOW_ALL, None, false);
+    assert_eq!(iterator.root(), root_node);
+    assert_eq!(iterator.what_to_show(), SHOW_ALL);
+    assert!(iterator.filter().is_none());
+    assert!(!iterator.expand_entity_references());
+    assert_eq!(
+        iterate(&mut iterator),
+        vec!["root", "a", "one", "x", "two", "three", "b", "c", "four", "d", "e", "five"]
+    );
+    assert_eq!(
+        iterate_back(&mut iterator),
+        vec!["five", "e", "d", "four", "c", "b", "three", "two", "x", "one", "a", "root"]
+    );
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "root");
+
+    let mut iterator = document_node.create_node_iterator(
+        root_node.clone(),
+        SHOW_ELEMENT,
+        named("b", FilterResult::Reject),
+        false,
+    );
+    assert_eq!(
+        iterate(&mut iterator),
+        vec!["root", "a", "x", "c", "d", "e"]
+    );
+
+    let mut iterator =
+        document_node.create_node_iterator(root_node, SHOW_TEXT | SHOW_COMMENT, None, false);
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "one");
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "two");
+    assert_eq!(label(&iterator.previous_node().unwrap().unwrap()), "two");
+    assert_eq!(label(&iterator.previous_node().unwrap().unwrap()), "one");
+    assert!(iterator.previous_node().unwrap().is_none());
+}
+
+#[test]
+fn test_iterator_removal() {
+    let (document_node, mut root_node) = create_document();
+
+    let mut iterator = document_node.create_node_iterator(root_node.clone(), SHOW_ALL, None, false);
+    let mut a_iterator =
+        document_node.create_node_iterator(root_node.first_child().unwrap(), SHOW_ALL, None, false);
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "root");
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "a");
+    assert_eq!(label(&a_iterator.next_node().unwrap().unwrap()), "a");
+
+    // removing the reference node, the iterator moves back to the preceding node.
+    let _safe_to_ignore = root_node
+        .remove_child(root_node.first_child().unwrap())
+        .unwrap();
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "three");
+    assert_eq!(iterate(&mut a_iterator), vec!["one", "x", "two"]);
+
+    // removing the reference node, the iterator moves forward to the following node.
+    assert_eq!(label(&iterator.previous_node().unwrap().unwrap()), "three");
+    let _safe_to_ignore = root_node
+        .remove_child(root_node.first_child().unwrap())
+        .unwrap();
+    assert_eq!(
+        iterate(&mut iterator),
+        vec!["b", "c", "four", "d", "e", "five"]
+    );
+
+    // removing an ancestor of the reference node, with no following node.
+    assert_eq!(label(&iterator.previous_node().unwrap().unwrap()), "five");
+    let _safe_to_ignore = root_node
+        .remove_child(root_node.last_child().unwrap())
+        .unwrap();
+    assert!(iterator.next_node().unwrap().is_none());
+    assert_eq!(
+        iterate_back(&mut iterator),
+        vec!["d", "four", "c", "b", "root"]
+    );
+
+    // moving a node is a removal from its current parent.
+    let mut b_node = root_node.first_child().unwrap();
+    let d_node = b_node.last_child().unwrap();
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "root");
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "b");
+    assert_eq!(label(&iterator.next_node().unwrap().unwrap()), "c");
+    let _safe_to_ignore = b_node.insert_before(d_node, b_node.first_child()).unwrap();
+    assert_eq!(iterate(&mut iterator), vec!["four"]);
+}
+
+#[test]
+fn test_iterator_detach() {
+    let (document_node, root_node) = create_document();
+
+    let mut iterator = document_node.create_node_iterator(root_node, SHOW_ALL, None, false);
+    assert!(iterator.next_node().unwrap().is_some());
+    iterator.detach();
+    assert_eq!(iterator.next_node(), Err(Error::InvalidState));
+    assert_eq!(iterator.previous_node(), Err(Error::InvalidState));
+}
@@ -0,0 +1,135 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::dom_impl::Implementation;
+use xml_dom::level2::ext::tree_builder::TreeBuilder;
+use xml_dom::level2::ext::{DocumentDecl, PrologPosition, XmlDecl, XmlVersion};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_build_document() {
+    let mut builder = TreeBuilder::new();
+    assert!(builder.is_validating());
+    builder
+        .xml_declaration(XmlDecl::new(XmlVersion::V10, None, None))
+        .unwrap();
+    builder.comment("generated").unwrap();
+    builder
+        .document_type("order", None, Some("order.dtd"), None)
+        .unwrap();
+    builder.text("\n  ").unwrap();
+    builder.start_element("order", &[("id", "o-1")]).unwrap();
+    builder.start_element("note", &[]).unwrap();
+    assert_eq!(builder.depth(), 2);
+    builder.text("a < b").unwrap();
+    builder.cdata_section("<raw>").unwrap();
+    builder.end_element("note").unwrap();
+    builder.processing_instruction("audit", Some("on")).unwrap();
+    builder.end_element("order").unwrap();
+    let document_node = builder.finish().unwrap();
+
+    let document = as_document(&document_node).unwrap();
+    assert!(document_node.xml_declaration().is_some());
+    assert_eq!(
+        document.doc_type().unwrap().node_name().to_string(),
+        "order"
+    );
+    let comment = document_node.first_child().unwrap();
+    assert_eq!(
+        document_node.prolog_position(&comment),
+        Some(PrologPosition::BeforeDocType)
+    );
+    let root_node = document.document_element().unwrap();
+    assert_eq!(
+        root_node.to_string(),
+        r#"<order id="o-1"><note>a &#60; b<![CDATA[<raw>]]></note><?audit on?></order>"#
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_build_matches_parser() {
+    const XML: &str = r#"<?xml version="1.0"?><!DOCTYPE a [<!ENTITY % p "x">]><!-- c --><a k="v"><b>t</b><?pi d?></a>"#;
+    let parsed = xml_dom::parser::read_xml(XML).unwrap();
+
+    let mut builder = TreeBuilder::new();
+    builder
+        .xml_declaration(XmlDecl::new(XmlVersion::V10, None, None))
+        .unwrap();
+    builder
+        .document_type("a", None, None, Some(r#"<!ENTITY % p "x">"#))
+        .unwrap();
+    builder.comment(" c ").unwrap();
+    builder.start_element("a", &[("k", "v")]).unwrap();
+    builder.start_element("b", &[]).unwrap();
+    builder.text("t").unwrap();
+    builder.end_element("b").unwrap();
+    builder.processing_instruction("pi", Some("d")).unwrap();
+    builder.end_element("a").unwrap();
+    let built = builder.finish().unwrap();
+
+    assert_eq!(built.to_string(), parsed.to_string());
+}
+
+#[test]
+fn test_validation() {
+    let mut builder = TreeBuilder::new();
+    builder.start_element("a", &[]).unwrap();
+    assert_eq!(
+        builder.xml_declaration(XmlDecl::new(XmlVersion::V10, None, None)),
+        Err(Error::Syntax)
+    );
+    assert_eq!(
+        builder.document_type("a", None, None, None),
+        Err(Error::Syntax)
+    );
+    assert_eq!(builder.cdata_section("x"), Ok(()));
+    assert_eq!(builder.end_element("b"), Err(Error::InvalidState));
+    assert_eq!(builder.clone().finish(), Err(Error::InvalidState));
+    builder.end_element("a").unwrap();
+    assert_eq!(builder.end_element("a"), Err(Error::InvalidState));
+    assert_eq!(builder.text("x"), Err(Error::Syntax));
+    assert_eq!(builder.cdata_section("x"), Err(Error::Syntax));
+    assert_eq!(
+        builder.start_element("c", &[]),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(
+        builder.start_element("not valid", &[]),
+        Err(Error::InvalidCharacter)
+    );
+    assert!(builder.finish().is_ok());
+
+    assert_eq!(TreeBuilder::new().finish(), Err(Error::Syntax));
+}
+
+#[test]
+fn test_without_validation() {
+    let mut options = xml_dom::level2::ext::ProcessingOptions::new();
+    options.set_assume_ids();
+    let mut builder =
+        TreeBuilder::with_implementation(&Implementation::with_options(options)).unwrap();
+    builder.set_validate(false);
+    assert!(!builder.is_validating());
+    assert!(builder.clone().finish().is_ok());
+
+    builder.start_element("a", &[]).unwrap();
+    builder.start_element("b", &[("id", "b-1")]).unwrap();
+    builder.end_element("c").unwrap();
+    builder
+        .xml_declaration(XmlDecl::new(XmlVersion::V11, None, None))
+        .unwrap();
+    builder.start_element("d", &[]).unwrap();
+    let document_node = builder.finish().unwrap();
+
+    let document = as_document(&document_node).unwrap();
+    assert_eq!(
+        document.document_element().unwrap().to_string(),
+        "<a><b id=\"b-1\"></b><d></d></a>"
+    );
+    assert!(document.get_element_by_id("b-1").is_some());
+    assert_eq!(
+        document_node.xml_declaration().unwrap().version(),
+        XmlVersion::V11
+    );
+}
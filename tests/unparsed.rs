@@ -0,0 +1,110 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::sax::walk;
+use xml_dom::level2::ext::serializer::{serialize, WriteOptions};
+use xml_dom::level2::ext::unparsed::is_unparsed_markup;
+use xml_dom::level2::ext::{DocumentBuilder, DocumentDecl, DocumentUnparsed, PrologPosition};
+use xml_dom::level2::traversal::{DocumentTraversal, SHOW_UNPARSED_MARKUP};
+use xml_dom::level2::{get_implementation, Document, Error, Node, NodeType, RefNode};
+
+pub mod common;
+
+const CONDITIONAL: &str = "<![INCLUDE[ <!ELEMENT x ANY> ]]>";
+const MARKED: &str = "<![%draft;[ draft ]]>";
+
+fn create_document() -> RefNode {
+    let implementation = get_implementation();
+    let document_type = implementation
+        .create_document_type("doc", None, Some("doc.dtd"))
+        .unwrap();
+    let mut document_node = implementation
+        .create_document(None, Some("doc"), Some(document_type))
+        .unwrap();
+    let _safe_to_ignore = document_node
+        .add_prolog_unparsed_markup(CONDITIONAL, PrologPosition::AfterDocType)
+        .unwrap();
+    let _safe_to_ignore = document_node
+        .add_prolog_comment("first", PrologPosition::BeforeDocType)
+        .unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document_node.create_text_node("text"))
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document_node.create_unparsed_markup(MARKED))
+        .unwrap();
+    document_node
+}
+
+const EXPECTED: &str =
+    "<!--first--><!DOCTYPE doc SYSTEM \"doc.dtd\"><![INCLUDE[ <!ELEMENT x ANY> ]]><doc>text<![%draft;[ draft ]]></doc>";
+
+#[test]
+fn test_unparsed_node() {
+    let document_node = create_document();
+    let mut unparsed_node = document_node.create_unparsed_markup("<!FOO bar>");
+
+    assert!(is_unparsed_markup(&unparsed_node));
+    assert!(!is_unparsed_markup(&document_node));
+    assert_eq!(unparsed_node.node_type(), NodeType::UnparsedMarkup);
+    assert_eq!(unparsed_node.node_name().to_string(), "#unparsed-markup");
+    assert_eq!(unparsed_node.node_value(), Some("<!FOO bar>".to_string()));
+    assert_eq!(unparsed_node.owner_document(), Some(document_node.clone()));
+    assert_eq!(unparsed_node.to_string(), "<!FOO bar>");
+
+    unparsed_node.set_node_value("<!BAR foo>").unwrap();
+    assert_eq!(unparsed_node.to_string(), "<!BAR foo>");
+
+    let text_node = document_node.create_text_node("text");
+    assert_eq!(
+        unparsed_node.append_child(text_node),
+        Err(Error::HierarchyRequest)
+    );
+    let mut fragment_node = document_node.create_document_fragment().unwrap();
+    assert!(fragment_node.append_child(unparsed_node).is_ok());
+}
+
+#[test]
+fn test_unparsed_round_trip() {
+    let document_node = create_document();
+
+    assert_eq!(document_node.to_string(), EXPECTED);
+    assert_eq!(
+        serialize(&document_node, &WriteOptions::default()).unwrap(),
+        EXPECTED
+    );
+    assert_eq!(
+        document_node.prolog_position(&document_node.first_child().unwrap()),
+        Some(PrologPosition::BeforeDocType)
+    );
+
+    let cloned_node = document_node
+        .last_child()
+        .unwrap()
+        .clone_node(true)
+        .unwrap();
+    assert_eq!(
+        cloned_node.to_string(),
+        "<doc>text<![%draft;[ draft ]]></doc>"
+    );
+
+    let mut builder = DocumentBuilder::new();
+    walk(&document_node, &mut builder).unwrap();
+    let built_node = builder.into_document().unwrap();
+    assert_eq!(built_node.to_string(), EXPECTED);
+}
+
+#[test]
+fn test_unparsed_traversal() {
+    let document_node = create_document();
+
+    let mut walker =
+        document_node.create_tree_walker(document_node.clone(), SHOW_UNPARSED_MARKUP, None, false);
+    let mut markup: Vec<String> = Vec::new();
+    while let Some(node) = walker.next_node() {
+        markup.push(node.node_value().unwrap());
+    }
+    assert_eq!(markup, vec![CONDITIONAL, MARKED]);
+}
@@ -0,0 +1,190 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{
+    DocumentRename, NodeUserData, UserDataHandler, UserDataOperation, Workspace,
+};
+use xml_dom::level2::*;
+
+pub mod common;
+
+type Calls = Rc<RefCell<Vec<(UserDataOperation, String, String)>>>;
+
+fn recording_handler(calls: &Calls) -> Rc<dyn UserDataHandler> {
+    let calls = calls.clone();
+    Rc::new(
+        move |operation: UserDataOperation,
+              key: &str,
+              _: &Rc<dyn Any>,
+              _: &RefNode,
+              destination: &RefNode| {
+            calls.borrow_mut().push((
+                operation,
+                key.to_string(),
+                destination.node_name().to_string(),
+            ));
+        },
+    )
+}
+
+fn make_document() -> RefNode {
+    get_implementation()
+        .create_document(None, Some("diagram"), None)
+        .unwrap()
+}
+
+#[test]
+fn test_user_data_set_get() {
+    let document_node = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    assert!(root_node.get_user_data("position").is_none());
+
+    assert!(root_node
+        .set_user_data("position", Box::new((1, 2)), None)
+        .is_none());
+    assert!(root_node
+        .set_user_data("label", Box::new("root".to_string()), None)
+        .is_none());
+    assert_eq!(root_node.user_data_keys(), vec!["label", "position"]);
+
+    let position = root_node.get_user_data("position").unwrap();
+    assert_eq!(position.downcast_ref::<(i32, i32)>(), Some(&(1, 2)));
+    assert!(position.downcast_ref::<String>().is_none());
+
+    let previous = root_node
+        .set_user_data("position", Box::new((3, 4)), None)
+        .unwrap();
+    assert_eq!(previous.downcast_ref::<(i32, i32)>(), Some(&(1, 2)));
+
+    let removed = root_node.remove_user_data("label").unwrap();
+    assert_eq!(removed.downcast_ref::<String>().unwrap(), "root");
+    assert_eq!(root_node.user_data_keys(), vec!["position"]);
+}
+
+#[test]
+fn test_user_data_clone_handlers() {
+    let document_node = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut box_node = document.create_element("box").unwrap();
+    let mut label_node = document.create_element("label").unwrap();
+    let _safe_to_ignore = box_node.append_child(label_node.clone()).unwrap();
+
+    let calls: Calls = Default::default();
+    let _none = box_node.set_user_data("b", Box::new(1), Some(recording_handler(&calls)));
+    let _none = box_node.set_user_data("a", Box::new(2), Some(recording_handler(&calls)));
+    let _none = box_node.set_user_data("silent", Box::new(3), None);
+    let _none = label_node.set_user_data("c", Box::new(4), Some(recording_handler(&calls)));
+
+    let copy_node = box_node.clone_node(false).unwrap();
+    assert!(copy_node.user_data_keys().is_empty());
+    assert_eq!(
+        *calls.borrow(),
+        vec![
+            (
+                UserDataOperation::Cloned,
+                "a".to_string(),
+                "box".to_string()
+            ),
+            (
+                UserDataOperation::Cloned,
+                "b".to_string(),
+                "box".to_string()
+            ),
+        ]
+    );
+
+    calls.borrow_mut().clear();
+    let _copy_node = box_node.clone_node(true).unwrap();
+    assert_eq!(
+        calls
+            .borrow()
+            .iter()
+            .map(|(_, key, destination)| format!("{}:{}", key, destination))
+            .collect::<Vec<String>>(),
+        vec!["a:box", "b:box", "c:label"]
+    );
+}
+
+#[test]
+fn test_user_data_import_handler() {
+    let document_node = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut box_node = document.create_element("box").unwrap();
+    let copy_value = Rc::new(
+        |operation: UserDataOperation,
+         key: &str,
+         data: &Rc<dyn Any>,
+         _: &RefNode,
+         destination: &RefNode| {
+            assert_eq!(operation, UserDataOperation::Imported);
+            let value = *data.downcast_ref::<i32>().unwrap();
+            let _none = destination
+                .clone()
+                .set_user_data(key, Box::new(value + 1), None);
+        },
+    );
+    let _none = box_node.set_user_data("count", Box::new(41), Some(copy_value));
+
+    let mut workspace = Workspace::new();
+    let _safe_to_ignore = workspace
+        .create_document("copy.xml", None, Some("diagram"))
+        .unwrap();
+    let imported = workspace.import_node(&box_node, "copy.xml", true).unwrap();
+    let count = imported.get_user_data("count").unwrap();
+    assert_eq!(count.downcast_ref::<i32>(), Some(&42));
+}
+
+#[test]
+fn test_rename_node() {
+    let document_node = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut box_node = document.create_element("box").unwrap();
+    box_node.set_attribute("w", "10").unwrap();
+    let _safe_to_ignore = root_node.append_child(box_node.clone()).unwrap();
+
+    let calls: Calls = Default::default();
+    let _none = box_node.set_user_data("key", Box::new(()), Some(recording_handler(&calls)));
+
+    let renamed = document_node
+        .rename_node(box_node.clone(), Some("urn:shapes"), "s:rect")
+        .unwrap();
+    assert_eq!(renamed, box_node);
+    assert_eq!(box_node.node_name().to_string(), "s:rect");
+    assert_eq!(box_node.namespace_uri().as_deref(), Some("urn:shapes"));
+    assert_eq!(
+        *calls.borrow(),
+        vec![(
+            UserDataOperation::Renamed,
+            "key".to_string(),
+            "s:rect".to_string()
+        )]
+    );
+
+    let width_node = box_node.get_attribute_node("w").unwrap();
+    let _renamed = document_node
+        .rename_node(width_node, None, "width")
+        .unwrap();
+    assert_eq!(box_node.get_attribute("w"), None);
+    assert_eq!(box_node.get_attribute("width").as_deref(), Some("10"));
+
+    assert_eq!(
+        document_node.rename_node(document.create_text_node("text"), None, "x"),
+        Err(Error::NotSupported)
+    );
+    assert_eq!(
+        document_node.rename_node(box_node.clone(), None, "not valid"),
+        Err(Error::InvalidCharacter)
+    );
+    let other_node = make_document();
+    let other_element = as_document(&other_node)
+        .unwrap()
+        .create_element("other")
+        .unwrap();
+    assert_eq!(
+        document_node.rename_node(other_element, None, "x"),
+        Err(Error::WrongDocument)
+    );
+}
@@ -0,0 +1,247 @@
+use xml_dom::level2::convert::{as_attribute, as_document};
+use xml_dom::level2::ext::options::ProcessingOptions;
+use xml_dom::level2::ext::{TreeBuilder, Workspace};
+use xml_dom::level2::*;
+
+pub mod common;
+
+const MAIN: &str = "http://example.com/docs/main.xml";
+const SCHEMA: &str = "http://example.com/schemas/book.xsd";
+
+#[test]
+fn test_workspace_documents() {
+    let mut options = ProcessingOptions::new();
+    options.set_assume_ids();
+    let mut workspace = Workspace::with_options(options);
+    assert!(workspace.is_empty());
+    assert!(workspace.main().is_none());
+
+    assert!(workspace.options().has_assume_ids());
+    let main_node = workspace.create_document(MAIN, None, Some("book")).unwrap();
+    let document = as_document(&main_node).unwrap();
+    let mut book = document.document_element().unwrap();
+    book.set_attribute("id", "book").unwrap();
+    assert_eq!(document.get_element_by_id("book"), Some(book));
+    assert_eq!(
+        workspace.create_document(MAIN, None, Some("book")).err(),
+        Some(Error::InvalidState)
+    );
+
+    let rdf_node = common::create_example_rdf_document();
+    workspace.insert("rdf.xml", rdf_node.clone()).unwrap();
+    assert_eq!(
+        workspace.insert("copy.xml", rdf_node.clone()).err(),
+        Some(Error::InvalidState)
+    );
+    let root_node = as_document(&rdf_node).unwrap().document_element().unwrap();
+    assert_eq!(
+        workspace.insert("root.xml", root_node.clone()).err(),
+        Some(Error::InvalidState)
+    );
+    assert_eq!(workspace.len(), 2);
+    assert_eq!(workspace.system_id_of(&root_node), Some("rdf.xml"));
+
+    assert_eq!(workspace.main(), Some(&main_node));
+    workspace.set_main("rdf.xml").unwrap();
+    assert_eq!(workspace.main(), Some(&rdf_node));
+    assert_eq!(workspace.set_main("other.xml").err(), Some(Error::NotFound));
+    assert_eq!(
+        workspace
+            .iter()
+            .map(|(system_id, _)| system_id)
+            .collect::<Vec<&str>>(),
+        vec!["rdf.xml", MAIN]
+    );
+
+    assert_eq!(workspace.remove("rdf.xml"), Some(rdf_node));
+    assert_eq!(workspace.main(), Some(&main_node));
+    assert!(workspace.get("rdf.xml").is_none());
+}
+
+#[test]
+fn test_workspace_import() {
+    let mut workspace = Workspace::new();
+    let main_node = workspace.create_document(MAIN, None, Some("book")).unwrap();
+    workspace
+        .insert("rdf.xml", common::create_example_rdf_document())
+        .unwrap();
+    let rdf_node = workspace.get("rdf.xml").cloned().unwrap();
+    let description = as_document(&rdf_node)
+        .unwrap()
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+
+    let shallow = workspace.import_node(&description, MAIN, false).unwrap();
+    assert_eq!(shallow.owner_document(), Some(main_node.clone()));
+    assert!(!shallow.has_child_nodes());
+
+    let mut book = as_document(&main_node).unwrap().document_element().unwrap();
+    let imported = workspace
+        .append_imported(&mut book, &description, true)
+        .unwrap();
+    assert_eq!(imported.parent_node(), Some(book.clone()));
+    assert_eq!(imported.child_nodes().len(), 4);
+    let title = imported.child_nodes()[1].clone();
+    assert_eq!(title.owner_document(), Some(main_node.clone()));
+    assert_eq!(
+        title.get_attribute_ns(common::XML_NS_URI, "id"),
+        Some("title".to_string())
+    );
+    assert_eq!(
+        title.first_child().unwrap().node_value(),
+        Some("A Guide to Growing Roses".to_string())
+    );
+    // the source is unchanged.
+    assert_eq!(description.child_nodes().len(), 4);
+    assert_eq!(book.to_string(), format!("<book>{}</book>", description));
+
+    assert_eq!(
+        workspace.import_node(&rdf_node, MAIN, true).err(),
+        Some(Error::NotSupported)
+    );
+    assert_eq!(
+        workspace.import_node(&description, "other.xml", true).err(),
+        Some(Error::NotFound)
+    );
+}
+
+#[test]
+fn test_workspace_names() {
+    let mut workspace = Workspace::new();
+    let _safe_to_ignore = workspace.create_document(MAIN, None, Some("book")).unwrap();
+
+    let name = workspace.name(Some(common::DC_NS), "dc:title").unwrap();
+    assert_eq!(name.local_name(), "title");
+    assert_eq!(workspace.interned_names(), 1);
+    assert_eq!(
+        workspace.name(Some(common::DC_NS), "dc:title").unwrap(),
+        name
+    );
+    assert_eq!(workspace.interned_names(), 1);
+    assert_eq!(
+        workspace.name(None, "bad name").err(),
+        Some(Error::InvalidCharacter)
+    );
+    assert_eq!(workspace.interned_names(), 1);
+
+    let element = workspace
+        .create_element(MAIN, Some(common::DC_NS), "dc:title")
+        .unwrap();
+    assert_eq!(element.node_name(), name);
+    assert_eq!(workspace.interned_names(), 1);
+    assert_eq!(
+        workspace.create_element("other.xml", None, "title").err(),
+        Some(Error::NotFound)
+    );
+}
+
+#[test]
+fn test_workspace_resolve() {
+    let mut workspace = Workspace::new();
+    let _safe_to_ignore = workspace.create_document(MAIN, None, Some("book")).unwrap();
+    let schema_node = workspace
+        .create_document(SCHEMA, None, Some("schema"))
+        .unwrap();
+    let chapter_node = workspace
+        .create_document(
+            "http://example.com/docs/chapters/one.xml",
+            None,
+            Some("chapter"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        workspace.resolve(None, Some("chapters/one.xml"), Some(MAIN)),
+        Some(&chapter_node)
+    );
+    assert_eq!(
+        workspace.resolve(None, Some("./chapters/../chapters/one.xml"), Some(MAIN)),
+        Some(&chapter_node)
+    );
+    assert_eq!(
+        workspace.resolve(None, Some("../schemas/book.xsd"), Some(MAIN)),
+        Some(&schema_node)
+    );
+    assert_eq!(
+        workspace.resolve(None, Some("/schemas/book.xsd"), Some(MAIN)),
+        Some(&schema_node)
+    );
+    assert_eq!(
+        workspace.resolve(None, Some(SCHEMA), Some("other")),
+        Some(&schema_node)
+    );
+    assert!(workspace
+        .resolve(None, Some("book.xsd"), Some(MAIN))
+        .is_none());
+
+    assert_eq!(
+        workspace.resolve_uri("b/c.xml", Some("a/d.xml")),
+        "a/b/c.xml"
+    );
+    assert_eq!(workspace.resolve_uri("../c.xml", Some("d.xml")), "../c.xml");
+    assert_eq!(workspace.resolve_uri("urn:x:y", Some(MAIN)), "urn:x:y");
+
+    workspace.map_public_id("-//EXAMPLE//DTD Book//EN", SCHEMA);
+    assert_eq!(
+        workspace.resolve(Some("-//EXAMPLE//DTD Book//EN"), Some("missing.dtd"), None),
+        Some(&schema_node)
+    );
+
+    let implementation = get_implementation();
+    let doc_type = implementation
+        .create_document_type("book", None, Some("../schemas/book.xsd"))
+        .unwrap();
+    let document_node = implementation
+        .create_document(None, Some("book"), Some(doc_type))
+        .unwrap();
+    workspace
+        .insert("http://example.com/docs/other.xml", document_node.clone())
+        .unwrap();
+    let doc_type = as_document(&document_node).unwrap().doc_type().unwrap();
+    assert_eq!(workspace.resolve_external(&doc_type), Some(&schema_node));
+    assert!(workspace
+        .resolve_external(
+            &as_document(&document_node)
+                .unwrap()
+                .document_element()
+                .unwrap()
+        )
+        .is_none());
+}
+
+#[test]
+fn test_import_default_attributes() {
+    let memo_document = |internal_subset: &str| {
+        let mut builder = TreeBuilder::new();
+        builder
+            .document_type("memo", None, None, Some(internal_subset))
+            .unwrap();
+        builder.start_element("memo", &[("lang", "en")]).unwrap();
+        builder.end_element("memo").unwrap();
+        builder.finish().unwrap()
+    };
+    let source_node = memo_document(r#"<!ATTLIST memo status CDATA "draft" lang CDATA "fr">"#);
+    let source_element = as_document(&source_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(source_element.attributes().len(), 2);
+
+    let mut workspace = Workspace::new();
+    workspace
+        .insert(
+            MAIN,
+            memo_document(r#"<!ATTLIST memo version CDATA #FIXED "2">"#),
+        )
+        .unwrap();
+    let imported = workspace.import_node(&source_element, MAIN, false).unwrap();
+    assert_eq!(imported.get_attribute("status"), None);
+    assert_eq!(imported.get_attribute("lang").as_deref(), Some("en"));
+    let version_node = imported.get_attribute_node("version").unwrap();
+    assert!(!as_attribute(&version_node).unwrap().specified());
+
+    let created = workspace.create_element(MAIN, None, "memo").unwrap();
+    assert_eq!(created.get_attribute("version").as_deref(), Some("2"));
+}
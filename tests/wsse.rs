@@ -0,0 +1,73 @@
+#![cfg(feature = "soap")]
+
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::soap::{Envelope, SoapVersion};
+use xml_dom::level2::ext::wsse::{
+    insert_binary_security_token, insert_timestamp, insert_username_token, security_header, wsu_id,
+    UsernameToken, X509_V3_VALUE_TYPE,
+};
+use xml_dom::level2::{get_implementation, Node};
+
+#[test]
+fn test_security_header_structure() {
+    let ns = SoapVersion::V12.namespace_uri();
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(Some(ns), Some("env:Envelope"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut envelope_node = document.document_element().unwrap();
+    let body = document.create_element_ns(ns, "env:Body").unwrap();
+    let _safe_to_ignore = envelope_node.append_child(body).unwrap();
+
+    let envelope = Envelope::from_document(&document_node).unwrap();
+    let token = insert_username_token(
+        &envelope,
+        &UsernameToken::new("bob").with_password_digest(
+            "ZGlnZXN0",
+            "bm9uY2U=",
+            "2020-01-01T00:00:00Z",
+        ),
+    )
+    .unwrap();
+    let timestamp = insert_timestamp(&envelope, "2020-01-01T00:00:00Z", None).unwrap();
+    let binary =
+        insert_binary_security_token(&envelope, X509_V3_VALUE_TYPE, "TUlJQ2VqQ0NB").unwrap();
+    let second_token = insert_username_token(&envelope, &UsernameToken::new("carol")).unwrap();
+
+    // the header is created before the body, and only one security header exists.
+    let header = envelope.header().unwrap();
+    assert_eq!(envelope_node.first_child(), Some(header.clone()));
+    let security = security_header(&envelope).unwrap();
+    assert_eq!(header.child_nodes(), vec![security.clone()]);
+    assert_eq!(envelope.must_understand_headers().len(), 1);
+
+    // the timestamp is always first.
+    assert_eq!(
+        security.child_nodes(),
+        vec![
+            timestamp.clone(),
+            token.clone(),
+            binary.clone(),
+            second_token.clone()
+        ]
+    );
+    assert_eq!(wsu_id(&timestamp), Some("TS-1".to_string()));
+    assert_eq!(wsu_id(&token), Some("UsernameToken-1".to_string()));
+    assert_eq!(wsu_id(&binary), Some("X509-1".to_string()));
+    assert_eq!(wsu_id(&second_token), Some("UsernameToken-2".to_string()));
+
+    assert_eq!(
+        token
+            .child_nodes()
+            .iter()
+            .map(|child| child.node_name().to_string())
+            .collect::<Vec<String>>(),
+        vec![
+            "wsse:Username",
+            "wsse:Password",
+            "wsse:Nonce",
+            "wsu:Created"
+        ]
+    );
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "xml_rs")]
+
+use xml::reader::{EventReader, ParserConfig, XmlEvent};
+use xml::writer::EmitterConfig;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::xml_rs::{read_event_reader, write_event_writer, XmlRsBuilder};
+use xml_dom::level2::ext::DocumentDecl;
+use xml_dom::level2::*;
+
+pub mod common;
+
+const ORDER: &str = r#"<?xml version="1.0" encoding="UTF-8"?><!DOCTYPE order SYSTEM "order.dtd"><?audit checked?><o:order xmlns:o="urn:example:order" o:id="7"><item sku="a&amp;b">Pen &lt;blue&gt;</item><!--note--><note><![CDATA[x <y>]]></note><empty xmlns="urn:example:empty"/></o:order>"#;
+
+fn write_to_string(node: &RefNode) -> String {
+    let mut writer = EmitterConfig::new()
+        .write_document_declaration(false)
+        .autopad_comments(false)
+        .pad_self_closing(false)
+        .create_writer(Vec::new());
+    write_event_writer(node, &mut writer).unwrap();
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_xml_rs_round_trip() {
+    let mut reader = ParserConfig::new()
+        .ignore_comments(false)
+        .create_reader(ORDER.as_bytes());
+    let document_node = read_event_reader(&mut reader).unwrap();
+
+    let decl = document_node.xml_declaration().unwrap();
+    assert_eq!(decl.encoding().as_deref(), Some("UTF-8"));
+    let doc_type = document_node.doc_type().unwrap();
+    assert_eq!(doc_type.node_name().to_string(), "order");
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    assert_eq!(
+        root_node.get_attribute("xmlns:o").as_deref(),
+        Some("urn:example:order")
+    );
+    assert_eq!(root_node.get_attribute("o:id").as_deref(), Some("7"));
+    assert_eq!(
+        root_node.namespace_uri().as_deref(),
+        Some("urn:example:order")
+    );
+    assert_eq!(
+        root_node.last_child().unwrap().namespace_uri().as_deref(),
+        Some("urn:example:empty")
+    );
+    assert_eq!(
+        root_node
+            .first_child()
+            .unwrap()
+            .get_attribute("sku")
+            .as_deref(),
+        Some("a&#38;b")
+    );
+
+    assert_eq!(write_to_string(&document_node), ORDER);
+}
+
+#[test]
+fn test_xml_rs_write_escaping() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("list"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("quote", "\"").unwrap();
+    let text = document.create_text_node("1 < 2");
+    let _safe_to_ignore = root_node.append_child(text).unwrap();
+    let comment = document.create_comment("a--b-");
+    let _safe_to_ignore = root_node.append_child(comment).unwrap();
+    let cdata = document.create_cdata_section("]]>").unwrap();
+    let _safe_to_ignore = root_node.append_child(cdata).unwrap();
+
+    assert_eq!(
+        write_to_string(&root_node),
+        r#"<list quote="&quot;">1 &lt; 2<!--a- -b- --><![CDATA[]]]]><![CDATA[>]]></list>"#
+    );
+}
+
+#[test]
+fn test_xml_rs_builder() {
+    let mut reader = EventReader::from_str("<list><item>1</item><item>2</item></list>");
+    let mut builder = XmlRsBuilder::new();
+    let mut max_depth = 0;
+    loop {
+        match reader.next().unwrap() {
+            XmlEvent::EndDocument => break,
+            event => builder.handle_event(event).unwrap(),
+        }
+        max_depth = max_depth.max(builder.depth());
+    }
+    assert_eq!(max_depth, 2);
+    let document_node = builder.finish().unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert_eq!(document.document_element().unwrap().child_nodes().len(), 2);
+
+    let mut builder = XmlRsBuilder::new();
+    assert_eq!(
+        builder.document_type("<!ELEMENT list ANY>").err(),
+        Some(Error::Syntax)
+    );
+    builder
+        .document_type(r#"<!DOCTYPE list PUBLIC "-//Example//List" "list.dtd" [<!ENTITY a "b">]>"#)
+        .unwrap();
+    assert_eq!(builder.finish().err(), Some(Error::Syntax));
+}
+
+#[test]
+fn test_xml_rs_reader_error() {
+    let mut reader = EventReader::from_str("<list><item></list>");
+    assert_eq!(read_event_reader(&mut reader).err(), Some(Error::Syntax));
+}
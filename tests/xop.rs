@@ -0,0 +1,105 @@
+#![cfg(feature = "xop")]
+
+use std::collections::HashMap;
+use xml_dom::level2::convert::{as_document, as_element, as_element_mut};
+use xml_dom::level2::ext::xop::{
+    inline, optimize, Attachment, AttachmentRegistry, OptimizeOptions, XOP_NS,
+};
+use xml_dom::level2::{get_implementation, Error, Node, RefNode};
+
+fn make_document() -> (RefNode, RefNode) {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("message"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for (name, content) in &[
+        ("photo", "AAECAwQFBgcICQ=="),
+        ("title", "Not base64!"),
+        ("small", "AA=="),
+    ] {
+        let mut element = document.create_element(name).unwrap();
+        let _safe_to_ignore = element
+            .append_child(document.create_text_node(content))
+            .unwrap();
+        let _safe_to_ignore = root_node.append_child(element).unwrap();
+    }
+    (document_node, root_node)
+}
+
+#[test]
+fn test_optimize_and_inline() {
+    let (_document_node, root_node) = make_document();
+    let original = root_node.to_string();
+
+    let options = OptimizeOptions {
+        min_size: 4,
+        content_id_domain: "example.org".to_string(),
+    };
+    let attachments = optimize(&root_node, &options).unwrap();
+    assert_eq!(
+        attachments,
+        vec![Attachment {
+            content_id: "part.1@example.org".to_string(),
+            data: (0..10).collect(),
+        }]
+    );
+
+    let photo = root_node.first_child().unwrap();
+    let include = photo.first_child().unwrap();
+    assert_eq!(include.node_name().local_name(), "Include");
+    assert_eq!(
+        include.node_name().namespace_uri(),
+        &Some(XOP_NS.to_string())
+    );
+    assert_eq!(
+        as_element(&include).unwrap().get_attribute("href"),
+        Some("cid:part.1@example.org".to_string())
+    );
+    assert_eq!(
+        root_node.child_nodes()[1].to_string(),
+        "<title>Not base64!</title>"
+    );
+    assert_eq!(
+        root_node.child_nodes()[2].to_string(),
+        "<small>AA==</small>"
+    );
+
+    let mut registry = AttachmentRegistry::default();
+    registry.register(Box::new(attachments));
+    assert_eq!(inline(&root_node, &registry).unwrap(), 1);
+    assert_eq!(root_node.to_string(), original);
+}
+
+#[test]
+fn test_inline_providers() {
+    let (document_node, root_node) = make_document();
+    let document = as_document(&document_node).unwrap();
+    let mut include = document.create_element_ns(XOP_NS, "xop:Include").unwrap();
+    {
+        let element = as_element_mut(&mut include).unwrap();
+        element
+            .set_attribute_ns("http://www.w3.org/2000/xmlns/", "xmlns:xop", XOP_NS)
+            .unwrap();
+        element
+            .set_attribute("href", "cid:logo@example.org")
+            .unwrap();
+    }
+    let mut title = root_node.child_nodes()[1].clone();
+    let text = title.first_child().unwrap();
+    let _safe_to_ignore = title.replace_child(include, text).unwrap();
+
+    let mut registry = AttachmentRegistry::default();
+    assert!(registry.is_empty());
+    assert_eq!(inline(&root_node, &registry), Err(Error::NotFound));
+
+    registry.register(Box::new(Vec::<Attachment>::new()));
+    let mut parts = HashMap::new();
+    let _safe_to_ignore = parts.insert("logo@example.org".to_string(), b"logo".to_vec());
+    registry.register(Box::new(parts));
+    assert_eq!(registry.len(), 2);
+
+    assert_eq!(inline(&root_node, &registry).unwrap(), 1);
+    assert_eq!(title.to_string(), "<title>bG9nbw==</title>");
+}
@@ -0,0 +1,256 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::xpath::{evaluate, select, Value, XPath, XPathContext};
+use xml_dom::level2::{get_implementation, Element, Error, Node, RefNode};
+
+pub mod common;
+
+// <library>
+//   <book id="a" year="1815"><title xml:lang="en-GB">Emma</title><price>12.5</price></book>
+//   <!--out of print-->
+//   <book id="b" year="1817"><title>Persuasion</title><price>7.5</price></book>
+// </library>
+
+fn create_library_document() -> RefNode {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("library"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for (id, year, title, price) in &[
+        ("a", "1815", "Emma", "12.5"),
+        ("b", "1817", "Persuasion", "7.5"),
+    ] {
+        if *id == "b" {
+            let _safe_to_ignore = root_node
+                .append_child(document.create_comment("out of print"))
+                .unwrap();
+        }
+        let mut book_node = document.create_element("book").unwrap();
+        book_node.set_attribute("id", id).unwrap();
+        book_node.set_attribute("year", year).unwrap();
+        let mut title_node = document.create_element("title").unwrap();
+        if *id == "a" {
+            title_node.set_attribute("xml:lang", "en-GB").unwrap();
+        }
+        let _safe_to_ignore = title_node
+            .append_child(document.create_text_node(title))
+            .unwrap();
+        let _safe_to_ignore = book_node.append_child(title_node).unwrap();
+        let mut price_node = document.create_element("price").unwrap();
+        let _safe_to_ignore = price_node
+            .append_child(document.create_text_node(price))
+            .unwrap();
+        let _safe_to_ignore = book_node.append_child(price_node).unwrap();
+        let _safe_to_ignore = root_node.append_child(book_node).unwrap();
+    }
+    document_node
+}
+
+fn strings(document_node: &RefNode, expression: &str) -> Vec<String> {
+    select(document_node, expression)
+        .unwrap()
+        .iter()
+        .map(|node| Value::NodeSet(vec![node.clone()]).to_string())
+        .collect()
+}
+
+#[test]
+fn test_location_paths() {
+    let document_node = create_library_document();
+
+    assert_eq!(
+        strings(&document_node, "//book[@id='b']/title"),
+        vec!["Persuasion"]
+    );
+    assert_eq!(
+        strings(&document_node, "/library/book/title"),
+        vec!["Emma", "Persuasion"]
+    );
+    assert_eq!(strings(&document_node, "//book[2]/@id"), vec!["b"]);
+    assert_eq!(
+        strings(&document_node, "//book[last()]/@year"),
+        vec!["1817"]
+    );
+    assert_eq!(
+        strings(&document_node, "//book[price > 10]/title"),
+        vec!["Emma"]
+    );
+    assert_eq!(
+        strings(&document_node, "//book/@*"),
+        vec!["a", "1815", "b", "1817"]
+    );
+    assert_eq!(strings(&document_node, "//comment()"), vec!["out of print"]);
+    assert_eq!(
+        strings(&document_node, "//title/text()"),
+        vec!["Emma", "Persuasion"]
+    );
+    assert_eq!(
+        strings(&document_node, "//price/.. /title | //title[1]"),
+        vec!["Emma", "Persuasion"]
+    );
+    assert_eq!(
+        strings(&document_node, "//title[. = 'Persuasion']/ancestor::*/@id"),
+        vec!["b"]
+    );
+    assert_eq!(
+        strings(
+            &document_node,
+            "//book[1]/following-sibling::node()[1] | //book[2]/preceding::title"
+        ),
+        vec!["Emma", "out of print"]
+    );
+    assert_eq!(
+        strings(&document_node, "(//title)[last()]"),
+        vec!["Persuasion"]
+    );
+    assert!(select(&document_node, "/library/magazine")
+        .unwrap()
+        .is_empty());
+
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let books = select(&root_node, "book").unwrap();
+    assert_eq!(books.len(), 2);
+    assert_eq!(
+        evaluate(&books[1], "string(../book[1]/@id)").unwrap(),
+        Value::String("a".to_string())
+    );
+    assert_eq!(select(&books[0], "/").unwrap(), vec![document_node.clone()]);
+}
+
+#[test]
+fn test_functions() {
+    let document_node = create_library_document();
+    let cases: Vec<(&str, Value)> = vec![
+        ("count(//book)", Value::Number(2.0)),
+        ("sum(//price)", Value::Number(20.0)),
+        ("sum(//price) div count(//price)", Value::Number(10.0)),
+        ("7 mod 3 - -1", Value::Number(2.0)),
+        (
+            "round(2.5) + floor(-1.5) + ceiling(1.2)",
+            Value::Number(3.0),
+        ),
+        ("number('  42 ')", Value::Number(42.0)),
+        ("string(1 div 0)", Value::String("Infinity".to_string())),
+        ("string(0 div 0)", Value::String("NaN".to_string())),
+        ("string(2.0)", Value::String("2".to_string())),
+        (
+            "concat('a', 1, true())",
+            Value::String("a1true".to_string()),
+        ),
+        (
+            "substring('12345', 1.5, 2.6)",
+            Value::String("234".to_string()),
+        ),
+        ("substring('12345', 0, 3)", Value::String("12".to_string())),
+        (
+            "substring-before('1999/04/01', '/')",
+            Value::String("1999".to_string()),
+        ),
+        (
+            "substring-after('1999/04/01', '/')",
+            Value::String("04/01".to_string()),
+        ),
+        (
+            "normalize-space('  a \n b  ')",
+            Value::String("a b".to_string()),
+        ),
+        (
+            "translate('--aaa--', 'abc-', 'ABC')",
+            Value::String("AAA".to_string()),
+        ),
+        ("string-length(//book[1]/title)", Value::Number(4.0)),
+        ("starts-with(//book[2]/title, 'Pers')", Value::Boolean(true)),
+        ("contains(string(/), 'EmmaPer')", Value::Boolean(false)),
+        ("contains(string(/), 'Emma12.5')", Value::Boolean(true)),
+        ("name(//book[1]/*[2])", Value::String("price".to_string())),
+        ("local-name(//@xml:lang)", Value::String("lang".to_string())),
+        ("boolean(//magazine) or not(false())", Value::Boolean(true)),
+        ("//book/@year = 1817", Value::Boolean(true)),
+        ("//book/@year != 1817", Value::Boolean(true)),
+        ("//book/@year > 1817", Value::Boolean(false)),
+        ("//title = //book[2]/title", Value::Boolean(true)),
+        ("count(//title[lang('en')])", Value::Number(1.0)),
+        ("count(//title[lang('fr')])", Value::Number(0.0)),
+    ];
+    for (expression, expected) in cases {
+        assert_eq!(
+            evaluate(&document_node, expression).unwrap(),
+            expected,
+            "{}",
+            expression
+        );
+    }
+}
+
+#[test]
+fn test_namespaces_and_variables() {
+    let document_node = common::create_example_rdf_document();
+
+    assert_eq!(
+        strings(&document_node, "//rdf:Description/dc:title"),
+        vec!["A Guide to Growing Roses"]
+    );
+    assert_eq!(select(&document_node, "//dc:*").unwrap().len(), 4);
+    assert_eq!(
+        evaluate(&document_node, "namespace-uri(//dc:date)").unwrap(),
+        Value::String(common::DC_NS.to_string())
+    );
+    assert_eq!(
+        strings(&document_node, "id('main title')"),
+        vec!["A Guide to Growing Roses"]
+    );
+
+    let mut context = XPathContext::default();
+    let _safe_to_ignore = context
+        .namespaces
+        .insert("purl".to_string(), common::DC_NS.to_string());
+    let _safe_to_ignore = context
+        .variables
+        .insert("name".to_string(), Value::String("date".to_string()));
+    let xpath = XPath::compile("string(//purl:*[local-name() = $name])").unwrap();
+    assert_eq!(xpath.to_string(), "string(//purl:*[local-name() = $name])");
+    assert_eq!(
+        xpath.evaluate(&document_node, &context).unwrap(),
+        Value::String("2001-01-20".to_string())
+    );
+    assert_eq!(
+        xpath.evaluate(&document_node, &XPathContext::default()),
+        Ok(Value::String(String::new()))
+    );
+    assert_eq!(
+        XPath::compile("//dc:*[local-name() = $name]")
+            .unwrap()
+            .evaluate(&document_node, &XPathContext::default()),
+        Err(Error::Syntax)
+    );
+}
+
+#[test]
+fn test_errors() {
+    let document_node = create_library_document();
+    for expression in &[
+        "",
+        "//",
+        "book[",
+        "1 +",
+        "'unterminated",
+        "unknown(1)",
+        "count()",
+        "child::book::title",
+        "bogus::book",
+        "#",
+    ] {
+        assert_eq!(
+            XPath::compile(expression).err(),
+            Some(Error::Syntax),
+            "{}",
+            expression
+        );
+    }
+    assert_eq!(select(&document_node, "count(//book)"), Err(Error::Syntax));
+    assert_eq!(evaluate(&document_node, "'a'/book"), Err(Error::Syntax));
+}
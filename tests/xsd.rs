@@ -0,0 +1,132 @@
+use std::str::FromStr;
+use xml_dom::level2::convert::{as_document, as_element_mut};
+use xml_dom::level2::ext::xsd::{XsdDate, XsdDateTime, XsdDecimal, XsdDuration, XSI_NS};
+use xml_dom::level2::ext::XsdContent;
+use xml_dom::level2::{Element, Error, Node};
+
+pub mod common;
+
+#[test]
+fn test_attribute_values() {
+    let (_document_node, mut root_node) = common::make_element("order");
+    {
+        let root = as_element_mut(&mut root_node).unwrap();
+        root.set_attribute("placed", " 2002-10-10T17:00:00Z ")
+            .unwrap();
+        root.set_attribute("due", "2002-10-17").unwrap();
+        root.set_attribute("window", "P2DT12H").unwrap();
+        root.set_attribute("total", "0099.90").unwrap();
+        root.set_attribute("paid", "0").unwrap();
+    }
+
+    let placed = root_node
+        .get_attribute_xsd_datetime("placed")
+        .unwrap()
+        .unwrap();
+    assert_eq!(placed.timezone, Some(0));
+    assert_eq!(placed.date(), XsdDate::from_str("2002-10-10Z").unwrap());
+    assert_eq!(
+        root_node.get_attribute_xsd_date("due").unwrap(),
+        Some(XsdDate {
+            year: 2002,
+            month: 10,
+            day: 17,
+            timezone: None
+        })
+    );
+    assert_eq!(
+        root_node.get_attribute_xsd_duration("window").unwrap(),
+        Some(XsdDuration {
+            days: 2,
+            hours: 12,
+            ..Default::default()
+        })
+    );
+    let total = root_node
+        .get_attribute_xsd_decimal("total")
+        .unwrap()
+        .unwrap();
+    assert_eq!(total.to_string(), "99.9");
+    assert_eq!(total.to_f64(), 99.9);
+    assert_eq!(root_node.get_attribute_xsd_boolean("paid"), Ok(Some(false)));
+    assert_eq!(root_node.get_attribute_xsd_boolean("missing"), Ok(None));
+    assert_eq!(
+        root_node.get_attribute_xsd_datetime("due"),
+        Err(Error::Syntax)
+    );
+}
+
+#[test]
+fn test_set_values() {
+    let (document_node, mut root_node) = common::make_element("order");
+    let document = as_document(&document_node).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("replaced"))
+        .unwrap();
+
+    let placed = XsdDateTime::from_str("2002-10-10T12:00:00.000-05:00").unwrap();
+    root_node.set_attribute_xsd("placed", &placed).unwrap();
+    root_node.set_text_xsd(&XsdDecimal::from(-42)).unwrap();
+    assert_eq!(
+        root_node.to_string(),
+        r#"<order placed="2002-10-10T12:00:00-05:00">-42</order>"#
+    );
+    assert_eq!(
+        root_node.get_attribute("placed"),
+        Some("2002-10-10T12:00:00-05:00".to_string())
+    );
+    assert_eq!(
+        root_node.text_xsd::<XsdDecimal>().unwrap(),
+        Some(XsdDecimal::from(-42))
+    );
+    assert_eq!(root_node.text_xsd::<bool>(), Err(Error::Syntax));
+
+    root_node.set_text_xsd(&true).unwrap();
+    assert_eq!(root_node.text_xsd::<bool>(), Ok(Some(true)));
+}
+
+#[test]
+fn test_nil() {
+    let (_document_node, mut root_node) = common::make_element("order");
+    root_node.set_text_xsd(&XsdDecimal::from(1)).unwrap();
+    assert!(!root_node.is_nil());
+
+    root_node.set_nil(true).unwrap();
+    assert!(root_node.is_nil());
+    assert!(!root_node.has_child_nodes());
+    assert_eq!(
+        root_node.get_attribute_ns(XSI_NS, "nil"),
+        Some("true".to_string())
+    );
+    assert_eq!(
+        root_node.get_attribute("xmlns:xsi"),
+        Some(XSI_NS.to_string())
+    );
+    assert_eq!(root_node.text_xsd::<XsdDecimal>(), Ok(None));
+
+    root_node.set_text_xsd(&XsdDecimal::from(2)).unwrap();
+    assert!(!root_node.is_nil());
+    assert_eq!(root_node.get_attribute_ns(XSI_NS, "nil"), None);
+    assert_eq!(
+        root_node.text_xsd::<XsdDecimal>(),
+        Ok(Some(XsdDecimal::from(2)))
+    );
+}
+
+#[test]
+fn test_nil_declared_prefix() {
+    let (document_node, mut root_node) = common::make_element("order");
+    let document = as_document(&document_node).unwrap();
+    root_node.set_attribute("xmlns:s", XSI_NS).unwrap();
+    let mut child_node = document.create_element("item").unwrap();
+    child_node.set_attribute("s:nil", "1").unwrap();
+    let mut child_node = root_node.append_child(child_node).unwrap();
+    assert!(child_node.is_nil());
+
+    child_node.set_nil(false).unwrap();
+    assert!(!child_node.is_nil());
+    assert!(!child_node.has_attributes());
+
+    root_node.set_attribute("xmlns:xsi", "urn:other").unwrap();
+    assert_eq!(child_node.set_nil(true), Err(Error::Namespace));
+}